@@ -5,6 +5,7 @@ use crate::drivers::ble::mesh::config::configuration_manager::ConfigurationManag
 pub use crate::drivers::ble::mesh::driver::node::MeshNodeMessage;
 use crate::drivers::ble::mesh::driver::node::Node;
 use crate::drivers::ble::mesh::interface::NetworkInterfaces;
+use crate::drivers::ble::mesh::oob::OobHandler;
 use crate::drivers::ble::mesh::provisioning::Capabilities;
 use crate::drivers::ble::mesh::storage::Storage;
 use embassy::blocking_mutex::raw::ThreadModeRawMutex;
@@ -16,10 +17,11 @@ const PDU_SIZE: usize = 384;
 
 pub type NodeMutex = ThreadModeRawMutex;
 
-pub struct MeshNode<'a, E, N, S, R>
+pub struct MeshNode<'a, E, N, O, S, R>
 where
     E: ElementsHandler<'a> + 'a,
     N: NetworkInterfaces + 'a,
+    O: OobHandler + 'a,
     S: Storage + 'a,
     R: RngCore + CryptoRng + 'a,
 {
@@ -28,25 +30,35 @@ where
     force_reset: bool,
     capabilities: Option<Capabilities>,
     network: Option<N>,
+    oob: Option<O>,
     storage: Option<S>,
     rng: Option<R>,
-    node: Option<Node<'a, E, N, S, R>>,
+    node: Option<Node<'a, E, N, O, S, R>>,
 }
 
-impl<'a, E, N, S, R> MeshNode<'a, E, N, S, R>
+impl<'a, E, N, O, S, R> MeshNode<'a, E, N, O, S, R>
 where
     E: ElementsHandler<'a>,
     N: NetworkInterfaces,
+    O: OobHandler,
     S: Storage,
     R: RngCore + CryptoRng,
 {
-    pub fn new(elements: E, capabilities: Capabilities, network: N, storage: S, rng: R) -> Self {
+    pub fn new(
+        elements: E,
+        capabilities: Capabilities,
+        network: N,
+        oob: O,
+        storage: S,
+        rng: R,
+    ) -> Self {
         Self {
             channel: Channel::new(),
             elements: Some(elements),
             force_reset: false,
             capabilities: Some(capabilities),
             network: Some(network),
+            oob: Some(oob),
             storage: Some(storage),
             rng: Some(rng),
             node: None,
@@ -71,6 +83,7 @@ where
             self.elements.take().unwrap(),
             self.capabilities.take().unwrap(),
             self.network.take().unwrap(),
+            self.oob.take().unwrap(),
             configuration_manager,
             self.rng.take().unwrap(),
         ));
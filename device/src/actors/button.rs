@@ -1,14 +1,165 @@
 use crate::traits;
+use crate::traits::button::Event;
 use core::convert::TryFrom;
 use core::future::Future;
 use ector::{Actor, Address, Inbox};
+#[cfg(feature = "time")]
+use embassy::time::{Duration, Instant, Timer};
+#[cfg(feature = "time")]
+use embassy::util::{select, Either};
+use heapless::Vec;
 
-pub use crate::traits::button::Event as ButtonEvent;
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ButtonEvent {
+    Pressed,
+    Released,
+    /// Held down for the long press time.
+    LongPress,
+    /// Still held down after a long press, repeated every hold interval.
+    Hold,
+    /// Pressed again shortly after a short press.
+    DoubleClick,
+}
+
+impl From<Event> for ButtonEvent {
+    fn from(event: Event) -> Self {
+        match event {
+            Event::Pressed => ButtonEvent::Pressed,
+            Event::Released => ButtonEvent::Released,
+        }
+    }
+}
+
+/// Timings of the gestures detected on top of presses and releases.
+#[cfg(feature = "time")]
+#[derive(Debug, Copy, Clone)]
+pub struct Gestures {
+    pub long_press: Duration,
+    pub hold_interval: Duration,
+    /// Longest time between a release and the next press to make a double click.
+    pub double_click: Duration,
+}
+
+#[cfg(feature = "time")]
+impl Default for Gestures {
+    fn default() -> Self {
+        Self {
+            long_press: Duration::from_millis(1000),
+            hold_interval: Duration::from_millis(500),
+            double_click: Duration::from_millis(300),
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+#[derive(Debug, Copy, Clone)]
+enum GestureState {
+    Idle,
+    Pressed {
+        deadline: Instant,
+        long: bool,
+        double: bool,
+    },
+    Released {
+        deadline: Instant,
+    },
+}
+
+/// Turns presses and releases into gestures.
+///
+/// Long presses and double clicks exclude each other: releasing a long press
+/// or a double click does not start a new double click.
+#[cfg(feature = "time")]
+pub struct GestureDetector {
+    gestures: Gestures,
+    state: GestureState,
+}
+
+#[cfg(feature = "time")]
+impl GestureDetector {
+    pub fn new(gestures: Gestures) -> Self {
+        Self {
+            gestures,
+            state: GestureState::Idle,
+        }
+    }
+
+    /// When `on_timeout` must be called if no other event happens.
+    pub fn deadline(&self) -> Option<Instant> {
+        match self.state {
+            GestureState::Idle => None,
+            GestureState::Pressed { deadline, .. } => Some(deadline),
+            GestureState::Released { deadline } => Some(deadline),
+        }
+    }
+
+    pub fn on_event(&mut self, event: Event, now: Instant) -> Vec<ButtonEvent, 2> {
+        let mut events = Vec::new();
+        match event {
+            Event::Pressed => {
+                events.push(ButtonEvent::Pressed).ok();
+                let double =
+                    matches!(self.state, GestureState::Released { deadline } if now <= deadline);
+                if double {
+                    events.push(ButtonEvent::DoubleClick).ok();
+                }
+                self.state = GestureState::Pressed {
+                    deadline: now + self.gestures.long_press,
+                    long: false,
+                    double,
+                };
+            }
+            Event::Released => {
+                events.push(ButtonEvent::Released).ok();
+                self.state = match self.state {
+                    GestureState::Pressed {
+                        long: false,
+                        double: false,
+                        ..
+                    } => GestureState::Released {
+                        deadline: now + self.gestures.double_click,
+                    },
+                    _ => GestureState::Idle,
+                };
+            }
+        }
+        events
+    }
+
+    pub fn on_timeout(&mut self, now: Instant) -> Option<ButtonEvent> {
+        match self.state {
+            GestureState::Pressed {
+                deadline,
+                long,
+                double,
+            } if now >= deadline => {
+                self.state = GestureState::Pressed {
+                    deadline: deadline + self.gestures.hold_interval,
+                    long: true,
+                    double,
+                };
+                Some(if long {
+                    ButtonEvent::Hold
+                } else {
+                    ButtonEvent::LongPress
+                })
+            }
+            GestureState::Released { deadline } if now >= deadline => {
+                self.state = GestureState::Idle;
+                None
+            }
+            _ => None,
+        }
+    }
+}
 
 //pub struct Button<P: Wait + InputPin, H: ButtonEventHandler> {
 pub struct Button<P: traits::button::Button, H: 'static> {
     inner: P,
     handler: Address<H>,
+    #[cfg(feature = "time")]
+    gestures: Option<GestureDetector>,
 }
 
 //impl<P: Wait + InputPin, H: ButtonEventHandler> Button<P, H> {
@@ -17,7 +168,41 @@ where
     H: 'static,
 {
     pub fn new(inner: P, handler: Address<H>) -> Self {
-        Self { inner, handler }
+        Self {
+            inner,
+            handler,
+            #[cfg(feature = "time")]
+            gestures: None,
+        }
+    }
+
+    /// Also report long presses, holds and double clicks.
+    #[cfg(feature = "time")]
+    pub fn with_gestures(inner: P, handler: Address<H>, gestures: Gestures) -> Self {
+        Self {
+            inner,
+            handler,
+            gestures: Some(GestureDetector::new(gestures)),
+        }
+    }
+
+    async fn next_events(&mut self) -> Vec<ButtonEvent, 2> {
+        #[cfg(feature = "time")]
+        if let Some(detector) = &mut self.gestures {
+            if let Some(deadline) = detector.deadline() {
+                return match select(self.inner.wait_any(), Timer::at(deadline)).await {
+                    Either::First(event) => detector.on_event(event, Instant::now()),
+                    Either::Second(_) => detector.on_timeout(Instant::now()).into_iter().collect(),
+                };
+            } else {
+                let event = self.inner.wait_any().await;
+                return detector.on_event(event, Instant::now());
+            }
+        }
+
+        let mut events = Vec::new();
+        events.push(self.inner.wait_any().await.into()).ok();
+        events
     }
 }
 
@@ -33,9 +218,10 @@ where
     {
         async move {
             loop {
-                let event = self.inner.wait_any().await;
-                if let Ok(e) = H::try_from(event) {
-                    let _ = self.handler.try_notify(e);
+                for event in self.next_events().await {
+                    if let Ok(e) = H::try_from(event) {
+                        let _ = self.handler.try_notify(e);
+                    }
                 }
             }
         }
@@ -49,6 +235,82 @@ impl TryFrom<ButtonEvent> for ector::testutil::TestMessage {
         Ok(match event {
             ButtonEvent::Pressed => ector::testutil::TestMessage(0),
             ButtonEvent::Released => ector::testutil::TestMessage(1),
+            ButtonEvent::LongPress => ector::testutil::TestMessage(2),
+            ButtonEvent::Hold => ector::testutil::TestMessage(3),
+            ButtonEvent::DoubleClick => ector::testutil::TestMessage(4),
         })
     }
 }
+
+#[cfg(all(test, feature = "time"))]
+mod tests {
+    use super::*;
+
+    fn new_detector() -> GestureDetector {
+        GestureDetector::new(Gestures {
+            long_press: Duration::from_millis(1000),
+            hold_interval: Duration::from_millis(200),
+            double_click: Duration::from_millis(300),
+        })
+    }
+
+    fn at(millis: u64) -> Instant {
+        Instant::from_millis(millis)
+    }
+
+    #[test]
+    fn test_long_press() {
+        let mut detector = new_detector();
+        assert_eq!(None, detector.deadline());
+        assert_eq!(
+            [ButtonEvent::Pressed],
+            detector.on_event(Event::Pressed, at(0))[..]
+        );
+        assert_eq!(Some(at(1000)), detector.deadline());
+        assert_eq!(None, detector.on_timeout(at(999)));
+        assert_eq!(Some(ButtonEvent::LongPress), detector.on_timeout(at(1000)));
+        assert_eq!(Some(at(1200)), detector.deadline());
+        assert_eq!(Some(ButtonEvent::Hold), detector.on_timeout(at(1200)));
+        assert_eq!(Some(ButtonEvent::Hold), detector.on_timeout(at(1400)));
+
+        assert_eq!(
+            [ButtonEvent::Released],
+            detector.on_event(Event::Released, at(1500))[..]
+        );
+        assert_eq!(None, detector.deadline());
+        // a long press does not start a double click
+        assert_eq!(
+            [ButtonEvent::Pressed],
+            detector.on_event(Event::Pressed, at(1600))[..]
+        );
+    }
+
+    #[test]
+    fn test_double_click() {
+        let mut detector = new_detector();
+        detector.on_event(Event::Pressed, at(0));
+        detector.on_event(Event::Released, at(100));
+        assert_eq!(Some(at(400)), detector.deadline());
+        assert_eq!(
+            [ButtonEvent::Pressed, ButtonEvent::DoubleClick],
+            detector.on_event(Event::Pressed, at(350))[..]
+        );
+        detector.on_event(Event::Released, at(400));
+        assert_eq!(None, detector.deadline());
+        assert_eq!(
+            [ButtonEvent::Pressed],
+            detector.on_event(Event::Pressed, at(500))[..]
+        );
+
+        // too slow
+        let mut detector = new_detector();
+        detector.on_event(Event::Pressed, at(0));
+        detector.on_event(Event::Released, at(100));
+        assert_eq!(None, detector.on_timeout(at(400)));
+        assert_eq!(None, detector.deadline());
+        assert_eq!(
+            [ButtonEvent::Pressed],
+            detector.on_event(Event::Pressed, at(450))[..]
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use crate::drivers::led::neopixel::animation::{Animation, Effect, FromRgb};
+use crate::drivers::led::neopixel::filter::Filter;
+use crate::drivers::led::neopixel::{Pixel, SmartLedWriter};
+use core::future::Future;
+use ector::{Actor, Address, Inbox};
+use embassy::time::{Duration, Ticker};
+use embassy::util::{select, Either};
+use futures::StreamExt;
+
+#[derive(Copy, Clone)]
+pub enum AnimatorMessage<P: 'static, const N: usize> {
+    /// Switch to another effect, starting from its first step.
+    Effect(Effect<P, N>),
+    /// Render a frame, hence a step of the effect, every period.
+    FrameRate(Duration),
+    /// Keep showing the current frame.
+    Pause,
+    Resume,
+}
+
+/// Runs the effects of a whole strip of `N` smart LEDs.
+///
+/// Every frame goes through the filter before being written, so filters
+/// composed with `Filter::and` apply to any effect.
+pub struct Animator<W, F, P, const C: usize, const N: usize>
+where
+    W: SmartLedWriter<P, C>,
+    F: Filter<P, C>,
+    P: Pixel<C> + FromRgb + 'static,
+{
+    writer: W,
+    filter: F,
+    animation: Animation<P, C, N>,
+    period: Duration,
+    paused: bool,
+}
+
+impl<W, F, P, const C: usize, const N: usize> Animator<W, F, P, C, N>
+where
+    W: SmartLedWriter<P, C>,
+    F: Filter<P, C>,
+    P: Pixel<C> + FromRgb + 'static,
+{
+    pub fn new(writer: W, filter: F, effect: Effect<P, N>, period: Duration) -> Self {
+        Self {
+            writer,
+            filter,
+            animation: Animation::new(effect),
+            period,
+            paused: false,
+        }
+    }
+
+    async fn render(&mut self) {
+        let mut frame = *self.animation.next_frame();
+        for pixel in frame.iter_mut() {
+            if let Ok(filtered) = self.filter.apply(pixel) {
+                *pixel = filtered;
+            }
+        }
+        self.filter.complete();
+        if self.writer.write(&frame).await.is_err() {
+            warn!("error writing frame");
+        }
+    }
+}
+
+impl<W, F, P, const C: usize, const N: usize> Actor for Animator<W, F, P, C, N>
+where
+    W: SmartLedWriter<P, C>,
+    F: Filter<P, C>,
+    P: Pixel<C> + FromRgb + 'static,
+{
+    type Message<'m> = AnimatorMessage<P, N>;
+    type OnMountFuture<'m, M> = impl Future<Output = ()> + 'm where Self: 'm, M: Inbox<AnimatorMessage<P, N>> + 'm;
+    fn on_mount<'m, M>(
+        &'m mut self,
+        _: Address<AnimatorMessage<P, N>>,
+        mut inbox: M,
+    ) -> Self::OnMountFuture<'m, M>
+    where
+        M: Inbox<AnimatorMessage<P, N>> + 'm,
+    {
+        async move {
+            let mut ticker = Ticker::every(self.period);
+            loop {
+                match select(inbox.next(), ticker.next()).await {
+                    Either::First(AnimatorMessage::Effect(effect)) => {
+                        self.animation.set_effect(effect);
+                    }
+                    Either::First(AnimatorMessage::FrameRate(period)) => {
+                        self.period = period;
+                        ticker = Ticker::every(period);
+                    }
+                    Either::First(AnimatorMessage::Pause) => self.paused = true,
+                    Either::First(AnimatorMessage::Resume) => self.paused = false,
+                    Either::Second(_) => {
+                        if !self.paused {
+                            self.render().await;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -4,6 +4,9 @@ use core::convert::TryFrom;
 use core::future::Future;
 use ector::{Actor, Address, Inbox};
 
+#[cfg(all(feature = "neopixel", feature = "time"))]
+pub mod animator;
+
 #[derive(Clone, Copy)]
 pub enum LedMessage {
     On,
@@ -35,6 +38,7 @@ impl TryFrom<ButtonEvent> for LedMessage {
         match event {
             ButtonEvent::Pressed => Ok(LedMessage::On),
             ButtonEvent::Released => Ok(LedMessage::Off),
+            _ => Err(()),
         }
     }
 }
@@ -2,5 +2,7 @@
 pub mod ble;
 pub mod button;
 pub mod led;
+#[cfg(feature = "publisher")]
+pub mod publisher;
 pub mod sensors;
 pub mod transformer;
@@ -0,0 +1,213 @@
+use super::Backend;
+use core::future::Future;
+use embedded_io::asynch::{Read, Write};
+use embedded_nal_async::*;
+use reqwless::{client::*, request::*, response::Status};
+
+#[cfg(feature = "tls")]
+use embedded_tls::{Aes128GcmSha256, NoClock, TlsConfig, TlsConnection, TlsContext};
+#[cfg(feature = "tls")]
+use rand_core::{CryptoRng, RngCore};
+
+const RX_SIZE: usize = 512;
+#[cfg(feature = "tls")]
+const TLS_SIZE: usize = 16384;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HttpBackendError {
+    Dns,
+    Connect,
+    Tls,
+    Request,
+    /// The server answered with a status other than success.
+    Status,
+}
+
+/// Posts payloads to an HTTP endpoint, connecting for each of them.
+pub struct HttpBackend<N, D>
+where
+    N: TcpConnect,
+    D: Dns,
+{
+    network: N,
+    dns: D,
+    host: &'static str,
+    port: u16,
+    path: &'static str,
+    username: &'static str,
+    password: &'static str,
+    rx: [u8; RX_SIZE],
+}
+
+impl<N, D> HttpBackend<N, D>
+where
+    N: TcpConnect,
+    D: Dns,
+{
+    pub fn new(
+        network: N,
+        dns: D,
+        host: &'static str,
+        port: u16,
+        path: &'static str,
+        username: &'static str,
+        password: &'static str,
+    ) -> Self {
+        Self {
+            network,
+            dns,
+            host,
+            port,
+            path,
+            username,
+            password,
+            rx: [0; RX_SIZE],
+        }
+    }
+}
+
+impl<N, D> Backend for HttpBackend<N, D>
+where
+    N: TcpConnect,
+    D: Dns,
+{
+    type Error = HttpBackendError;
+
+    type SendFuture<'m> = impl Future<Output = Result<(), HttpBackendError>> + 'm where Self: 'm;
+    fn send<'m>(&'m mut self, payload: &'m [u8]) -> Self::SendFuture<'m> {
+        async move {
+            debug!("Resolving {}:{}", self.host, self.port);
+            let ip = self
+                .dns
+                .get_host_by_name(self.host, AddrType::IPv4)
+                .await
+                .map_err(|_| HttpBackendError::Dns)?;
+
+            let mut connection = self
+                .network
+                .connect(SocketAddr::new(ip, self.port))
+                .await
+                .map_err(|_| HttpBackendError::Connect)?;
+
+            post(
+                &mut connection,
+                self.host,
+                self.path,
+                (self.username, self.password),
+                payload,
+                &mut self.rx,
+            )
+            .await
+        }
+    }
+}
+
+/// Posts payloads to an HTTPS endpoint, connecting for each of them.
+#[cfg(feature = "tls")]
+pub struct HttpsBackend<N, D, R>
+where
+    N: TcpConnect,
+    D: Dns,
+    R: RngCore + CryptoRng,
+{
+    http: HttpBackend<N, D>,
+    rng: R,
+    tls: [u8; TLS_SIZE],
+}
+
+#[cfg(feature = "tls")]
+impl<N, D, R> HttpsBackend<N, D, R>
+where
+    N: TcpConnect,
+    D: Dns,
+    R: RngCore + CryptoRng,
+{
+    pub fn new(http: HttpBackend<N, D>, rng: R) -> Self {
+        Self {
+            http,
+            rng,
+            tls: [0; TLS_SIZE],
+        }
+    }
+}
+
+#[cfg(feature = "tls")]
+impl<N, D, R> Backend for HttpsBackend<N, D, R>
+where
+    N: TcpConnect,
+    D: Dns,
+    R: RngCore + CryptoRng,
+{
+    type Error = HttpBackendError;
+
+    type SendFuture<'m> = impl Future<Output = Result<(), HttpBackendError>> + 'm where Self: 'm;
+    fn send<'m>(&'m mut self, payload: &'m [u8]) -> Self::SendFuture<'m> {
+        async move {
+            let http = &mut self.http;
+            debug!("Resolving {}:{}", http.host, http.port);
+            let ip = http
+                .dns
+                .get_host_by_name(http.host, AddrType::IPv4)
+                .await
+                .map_err(|_| HttpBackendError::Dns)?;
+
+            let connection = http
+                .network
+                .connect(SocketAddr::new(ip, http.port))
+                .await
+                .map_err(|_| HttpBackendError::Connect)?;
+
+            let mut connection: TlsConnection<'_, _, Aes128GcmSha256> =
+                TlsConnection::new(connection, &mut self.tls);
+            connection
+                .open::<_, NoClock, 1>(TlsContext::new(
+                    &TlsConfig::new().with_server_name(http.host),
+                    &mut self.rng,
+                ))
+                .await
+                .map_err(|_| HttpBackendError::Tls)?;
+
+            post(
+                &mut connection,
+                http.host,
+                http.path,
+                (http.username, http.password),
+                payload,
+                &mut http.rx,
+            )
+            .await
+        }
+    }
+}
+
+async fn post<C>(
+    connection: &mut C,
+    host: &str,
+    path: &str,
+    (username, password): (&str, &str),
+    payload: &[u8],
+    rx: &mut [u8],
+) -> Result<(), HttpBackendError>
+where
+    C: Read + Write,
+{
+    let mut client = HttpClient::new(connection, host);
+    let response = client
+        .request(
+            Request::post()
+                .path(path)
+                .basic_auth(username, password)
+                .payload(payload)
+                .content_type(ContentType::ApplicationJson)
+                .build(),
+            rx,
+        )
+        .await
+        .map_err(|_| HttpBackendError::Request)?;
+    debug!("Response status: {:?}", response.status);
+    match response.status {
+        Status::Ok | Status::Created | Status::Accepted => Ok(()),
+        _ => Err(HttpBackendError::Status),
+    }
+}
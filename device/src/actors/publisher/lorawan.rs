@@ -0,0 +1,36 @@
+use super::Backend;
+use crate::traits::lora::{LoraDriver, LoraError, Port, QoS};
+use core::future::Future;
+
+/// Sends payloads as uplinks on a LoRaWAN port.
+///
+/// The driver must have joined the network already.
+pub struct LorawanBackend<D>
+where
+    D: LoraDriver,
+{
+    driver: D,
+    port: Port,
+    qos: QoS,
+}
+
+impl<D> LorawanBackend<D>
+where
+    D: LoraDriver,
+{
+    pub fn new(driver: D, port: Port, qos: QoS) -> Self {
+        Self { driver, port, qos }
+    }
+}
+
+impl<D> Backend for LorawanBackend<D>
+where
+    D: LoraDriver,
+{
+    type Error = LoraError;
+
+    type SendFuture<'m> = impl Future<Output = Result<(), LoraError>> + 'm where Self: 'm;
+    fn send<'m>(&'m mut self, payload: &'m [u8]) -> Self::SendFuture<'m> {
+        self.driver.send(self.qos, self.port, payload)
+    }
+}
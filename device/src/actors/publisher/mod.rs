@@ -0,0 +1,412 @@
+//! Publishing sensor readings to the cloud.
+//!
+//! The publisher buffers readings of any `Serialize` type, and sends them as
+//! JSON through a backend once a batch is full or its oldest reading waited
+//! long enough. Readings that could not be sent stay buffered until the next
+//! attempt, dropping the oldest ones when the buffer is full.
+
+pub mod http;
+#[cfg(feature = "lora")]
+pub mod lorawan;
+pub mod mqtt;
+
+use core::future::Future;
+use ector::{Actor, Address, Inbox};
+use embassy::time::{Duration, Instant, Timer};
+use embassy::util::{select, Either};
+use heapless::Deque;
+use serde::ser::{SerializeSeq, Serializer};
+use serde::Serialize;
+
+/// Where payloads are published.
+pub trait Backend {
+    type Error;
+
+    type SendFuture<'m>: Future<Output = Result<(), Self::Error>>
+    where
+        Self: 'm;
+    /// Send a JSON payload.
+    fn send<'m>(&'m mut self, payload: &'m [u8]) -> Self::SendFuture<'m>;
+}
+
+pub enum PublisherMessage<T> {
+    Reading(T),
+    /// Publish the buffered readings without waiting for the batch to fill.
+    Flush,
+}
+
+impl<T> From<T> for PublisherMessage<T> {
+    fn from(reading: T) -> Self {
+        PublisherMessage::Reading(reading)
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct PublisherConfig {
+    /// Readings sent in one payload. A batch of one reading is sent as the
+    /// reading itself, larger batches as an array.
+    pub batch_size: usize,
+    /// Shortest time between two publications.
+    pub min_interval: Duration,
+    /// Longest time a reading waits for its batch to fill.
+    pub max_delay: Duration,
+    /// Attempts after a failed send, before keeping the readings for later.
+    pub retries: u8,
+    pub retry_delay: Duration,
+}
+
+impl Default for PublisherConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 1,
+            min_interval: Duration::from_secs(0),
+            max_delay: Duration::from_secs(60),
+            retries: 2,
+            retry_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Publishes readings of type `T` through the backend `B`, buffering up to `N`
+/// readings and encoding payloads of up to `P` bytes.
+pub struct Publisher<B, T, const N: usize, const P: usize>
+where
+    B: Backend,
+    T: Serialize + Clone + 'static,
+{
+    backend: B,
+    config: PublisherConfig,
+    changed: fn(&T, &T) -> bool,
+    last: Option<T>,
+    buffer: Deque<T, N>,
+    oldest: Option<Instant>,
+    next_attempt: Option<Instant>,
+    flush: bool,
+    payload: [u8; P],
+}
+
+impl<B, T, const N: usize, const P: usize> Publisher<B, T, N, P>
+where
+    B: Backend,
+    T: Serialize + Clone + 'static,
+{
+    pub fn new(backend: B, config: PublisherConfig) -> Self {
+        Self::with_threshold(backend, config, |_, _| true)
+    }
+
+    /// Only keep readings for which `changed` tells they differ enough from
+    /// the last reading kept.
+    pub fn with_threshold(
+        backend: B,
+        config: PublisherConfig,
+        changed: fn(&T, &T) -> bool,
+    ) -> Self {
+        Self {
+            backend,
+            config,
+            changed,
+            last: None,
+            buffer: Deque::new(),
+            oldest: None,
+            next_attempt: None,
+            flush: false,
+            payload: [0; P],
+        }
+    }
+
+    /// Readings waiting to be published.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    fn batch_size(&self) -> usize {
+        self.config.batch_size.max(1).min(N)
+    }
+
+    fn add(&mut self, reading: T, now: Instant) {
+        if let Some(last) = &self.last {
+            if !(self.changed)(last, &reading) {
+                return;
+            }
+        }
+        self.last.replace(reading.clone());
+
+        if self.buffer.is_full() {
+            warn!("publisher buffer full, dropping the oldest reading");
+            self.buffer.pop_front();
+        }
+        if self.buffer.is_empty() {
+            self.oldest.replace(now);
+        }
+        self.buffer.push_back(reading).ok();
+    }
+
+    /// When the buffered readings are due, if any.
+    fn deadline(&self) -> Option<Instant> {
+        let oldest = self.oldest?;
+        let allowed = self.next_attempt.unwrap_or(oldest);
+        if self.flush || self.buffer.len() >= self.batch_size() {
+            Some(allowed)
+        } else {
+            Some(allowed.max(oldest + self.config.max_delay))
+        }
+    }
+
+    fn ready(&self, now: Instant) -> bool {
+        self.deadline()
+            .map(|deadline| now >= deadline)
+            .unwrap_or(false)
+    }
+
+    async fn publish(&mut self, now: Instant) -> bool {
+        let count = self.buffer.len().min(self.batch_size());
+        let encoded = match self.buffer.front() {
+            Some(reading) if count == 1 => serde_json_core::to_slice(reading, &mut self.payload),
+            _ => serde_json_core::to_slice(
+                &Batch {
+                    readings: &self.buffer,
+                    count,
+                },
+                &mut self.payload,
+            ),
+        };
+
+        let sent = match encoded {
+            Ok(len) => {
+                let mut sent = false;
+                for attempt in 0..=self.config.retries {
+                    if attempt > 0 {
+                        Timer::after(self.config.retry_delay).await;
+                    }
+                    if self.backend.send(&self.payload[..len]).await.is_ok() {
+                        sent = true;
+                        break;
+                    }
+                }
+                sent
+            }
+            Err(_) => {
+                warn!("payload buffer too small, dropping {} readings", count);
+                true
+            }
+        };
+
+        if sent {
+            for _ in 0..count {
+                self.buffer.pop_front();
+            }
+            self.next_attempt.replace(now + self.config.min_interval);
+        } else {
+            warn!("error publishing, keeping {} readings", self.buffer.len());
+            let delay = self.config.min_interval.max(self.config.retry_delay);
+            self.next_attempt.replace(now + delay);
+        }
+
+        if self.buffer.is_empty() {
+            self.oldest.take();
+            self.flush = false;
+        } else if sent {
+            self.oldest.replace(now);
+        }
+        sent
+    }
+}
+
+impl<B, T, const N: usize, const P: usize> Actor for Publisher<B, T, N, P>
+where
+    B: Backend,
+    T: Serialize + Clone + 'static,
+{
+    type Message<'m> = PublisherMessage<T>;
+    type OnMountFuture<'m, M> = impl Future<Output = ()> + 'm where Self: 'm, M: Inbox<PublisherMessage<T>> + 'm;
+    fn on_mount<'m, M>(
+        &'m mut self,
+        _: Address<PublisherMessage<T>>,
+        mut inbox: M,
+    ) -> Self::OnMountFuture<'m, M>
+    where
+        M: Inbox<PublisherMessage<T>> + 'm,
+    {
+        async move {
+            loop {
+                let message = match self.deadline() {
+                    Some(deadline) => match select(inbox.next(), Timer::at(deadline)).await {
+                        Either::First(message) => Some(message),
+                        Either::Second(_) => None,
+                    },
+                    None => Some(inbox.next().await),
+                };
+
+                let now = Instant::now();
+                match message {
+                    Some(PublisherMessage::Reading(reading)) => self.add(reading, now),
+                    Some(PublisherMessage::Flush) => self.flush = !self.buffer.is_empty(),
+                    None => {}
+                }
+
+                if self.ready(now) {
+                    self.publish(now).await;
+                }
+            }
+        }
+    }
+}
+
+/// The first `count` readings, serialized as an array.
+struct Batch<'a, T, const N: usize> {
+    readings: &'a Deque<T, N>,
+    count: usize,
+}
+
+impl<'a, T: Serialize, const N: usize> Serialize for Batch<'a, T, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.count))?;
+        for reading in self.readings.iter().take(self.count) {
+            seq.serialize_element(reading)?;
+        }
+        seq.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ector::testutil::TestContext;
+    use futures::executor::block_on;
+    use heapless::Vec;
+
+    struct MockBackend {
+        failures: usize,
+        sent: Vec<Vec<u8, 32>, 4>,
+    }
+
+    impl MockBackend {
+        fn new(failures: usize) -> Self {
+            Self {
+                failures,
+                sent: Vec::new(),
+            }
+        }
+    }
+
+    impl Backend for MockBackend {
+        type Error = ();
+
+        type SendFuture<'m> = impl Future<Output = Result<(), ()>> + 'm where Self: 'm;
+        fn send<'m>(&'m mut self, payload: &'m [u8]) -> Self::SendFuture<'m> {
+            async move {
+                if self.failures > 0 {
+                    self.failures -= 1;
+                    return Err(());
+                }
+                self.sent.push(Vec::from_slice(payload).unwrap()).unwrap();
+                Ok(())
+            }
+        }
+    }
+
+    fn at(millis: u64) -> Instant {
+        Instant::from_millis(millis)
+    }
+
+    fn config(batch_size: usize) -> PublisherConfig {
+        PublisherConfig {
+            batch_size,
+            min_interval: Duration::from_secs(0),
+            max_delay: Duration::from_secs(10),
+            retries: 0,
+            retry_delay: Duration::from_secs(0),
+        }
+    }
+
+    #[test]
+    fn test_batch() {
+        let mut publisher: Publisher<_, i32, 4, 32> =
+            Publisher::new(MockBackend::new(0), config(2));
+        assert_eq!(None, publisher.deadline());
+
+        publisher.add(1, at(0));
+        assert_eq!(Some(at(10_000)), publisher.deadline());
+        assert!(!publisher.ready(at(1)));
+
+        publisher.add(2, at(1));
+        assert!(publisher.ready(at(1)));
+        assert!(block_on(publisher.publish(at(1))));
+        assert_eq!(b"[1,2]", &publisher.backend.sent[0][..]);
+        assert_eq!(0, publisher.buffered());
+        assert_eq!(None, publisher.deadline());
+    }
+
+    #[test]
+    fn test_max_delay() {
+        let mut publisher: Publisher<_, i32, 4, 32> =
+            Publisher::new(MockBackend::new(0), config(3));
+        publisher.add(42, at(0));
+        assert!(!publisher.ready(at(9_999)));
+        assert!(publisher.ready(at(10_000)));
+        assert!(block_on(publisher.publish(at(10_000))));
+        assert_eq!(b"42", &publisher.backend.sent[0][..]);
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let mut publisher: Publisher<_, i32, 4, 32> = Publisher::new(
+            MockBackend::new(0),
+            PublisherConfig {
+                min_interval: Duration::from_secs(1),
+                ..config(1)
+            },
+        );
+        publisher.add(1, at(0));
+        assert!(block_on(publisher.publish(at(0))));
+
+        publisher.add(2, at(500));
+        assert!(!publisher.ready(at(500)));
+        assert!(publisher.ready(at(1_000)));
+    }
+
+    #[test]
+    fn test_threshold() {
+        let mut publisher: Publisher<_, i32, 4, 32> =
+            Publisher::with_threshold(MockBackend::new(0), config(4), |last, reading| {
+                (last - reading).abs() >= 5
+            });
+        publisher.add(10, at(0));
+        publisher.add(12, at(1));
+        publisher.add(16, at(2));
+        publisher.add(13, at(3));
+        assert_eq!(2, publisher.buffered());
+    }
+
+    #[test]
+    fn test_offline_buffer() {
+        run_task!(offline_buffer);
+    }
+
+    // retries wait on a timer, which needs the embassy executor.
+    #[embassy::task]
+    async fn offline_buffer(_context: TestContext<()>) {
+        let mut publisher: Publisher<_, i32, 2, 32> = Publisher::new(
+            MockBackend::new(2),
+            PublisherConfig {
+                min_interval: Duration::from_secs(5),
+                retries: 1,
+                ..config(2)
+            },
+        );
+        publisher.add(1, at(0));
+        publisher.add(2, at(1));
+        // both attempts fail, the readings are kept
+        assert!(!publisher.publish(at(1)).await);
+        assert_eq!(2, publisher.buffered());
+        assert_eq!(Some(at(5_001)), publisher.deadline());
+
+        // a full buffer drops the oldest reading
+        publisher.add(3, at(2));
+        assert_eq!(2, publisher.buffered());
+
+        assert!(publisher.publish(at(5_001)).await);
+        assert_eq!(b"[2,3]", &publisher.backend.sent[0][..]);
+        assert_eq!(0, publisher.buffered());
+    }
+}
@@ -0,0 +1,37 @@
+use super::Backend;
+use crate::drivers::mqtt::{MqttClient, MqttError};
+use core::future::Future;
+use embedded_nal_async::*;
+
+/// Publishes payloads on an MQTT topic.
+pub struct MqttBackend<N, D, const TX: usize>
+where
+    N: TcpConnect,
+    D: Dns,
+{
+    client: MqttClient<N, D, TX>,
+    topic: &'static str,
+}
+
+impl<N, D, const TX: usize> MqttBackend<N, D, TX>
+where
+    N: TcpConnect,
+    D: Dns,
+{
+    pub fn new(client: MqttClient<N, D, TX>, topic: &'static str) -> Self {
+        Self { client, topic }
+    }
+}
+
+impl<N, D, const TX: usize> Backend for MqttBackend<N, D, TX>
+where
+    N: TcpConnect,
+    D: Dns,
+{
+    type Error = MqttError;
+
+    type SendFuture<'m> = impl Future<Output = Result<(), MqttError>> + 'm where Self: 'm;
+    fn send<'m>(&'m mut self, payload: &'m [u8]) -> Self::SendFuture<'m> {
+        self.client.publish(self.topic, payload)
+    }
+}
@@ -1,9 +1,14 @@
 use crate::domain::{temperature::TemperatureScale, SensorAcquisition};
+use crate::traits;
 use crate::traits::sensors::temperature::TemperatureSensor;
 use core::future::Future;
 use ector::{Actor, Address, Inbox};
+#[cfg(feature = "time")]
+use embassy::time::{Duration, Ticker};
 use embedded_hal::digital::v2::InputPin;
 use embedded_hal_async::digital::Wait;
+#[cfg(feature = "time")]
+use futures::StreamExt;
 
 pub struct Temperature<P, T, C>
 where
@@ -66,3 +71,141 @@ where
         }
     }
 }
+
+/// What makes a `Sensor` actor take a reading.
+pub trait Trigger {
+    type WaitFuture<'m>: Future<Output = ()> + 'm
+    where
+        Self: 'm;
+    fn wait<'m>(&'m mut self) -> Self::WaitFuture<'m>;
+
+    /// Told when the reading taken after `wait` failed.
+    fn read_failed(&mut self) {}
+}
+
+/// Take a reading on a fixed schedule.
+#[cfg(feature = "time")]
+pub struct Every(Ticker);
+
+#[cfg(feature = "time")]
+impl Every {
+    pub fn new(period: Duration) -> Self {
+        Self(Ticker::every(period))
+    }
+}
+
+#[cfg(feature = "time")]
+impl Trigger for Every {
+    type WaitFuture<'m> = impl Future<Output = ()> + 'm where Self: 'm;
+    fn wait<'m>(&'m mut self) -> Self::WaitFuture<'m> {
+        async move {
+            self.0.next().await;
+        }
+    }
+}
+
+/// Take a reading whenever the data-ready pin of the sensor is high.
+///
+/// Once a reading fails, the pin may stay high, so the next one waits for
+/// the pin to change first.
+pub struct DataReady<P>
+where
+    P: Wait + InputPin,
+{
+    pin: P,
+    failed: bool,
+}
+
+impl<P> DataReady<P>
+where
+    P: Wait + InputPin,
+{
+    pub fn new(pin: P) -> Self {
+        Self { pin, failed: false }
+    }
+}
+
+impl<P> Trigger for DataReady<P>
+where
+    P: Wait + InputPin,
+{
+    type WaitFuture<'m> = impl Future<Output = ()> + 'm where Self: 'm;
+    fn wait<'m>(&'m mut self) -> Self::WaitFuture<'m> {
+        async move {
+            if self.failed {
+                self.failed = false;
+                if self.pin.wait_for_any_edge().await.is_err() {
+                    return;
+                }
+            }
+            while !self.pin.is_high().unwrap_or(false) {
+                if self.pin.wait_for_any_edge().await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    fn read_failed(&mut self) {
+        self.failed = true;
+    }
+}
+
+/// Sends the readings of any sensor, as triggered, to an address.
+pub struct Sensor<S, T>
+where
+    S: traits::sensors::Sensor + 'static,
+    T: Trigger + 'static,
+{
+    sensor: S,
+    trigger: T,
+    dest: Address<S::Reading>,
+}
+
+impl<S, T> Sensor<S, T>
+where
+    S: traits::sensors::Sensor + 'static,
+    T: Trigger + 'static,
+{
+    pub fn new(sensor: S, trigger: T, dest: Address<S::Reading>) -> Self {
+        Self {
+            sensor,
+            trigger,
+            dest,
+        }
+    }
+}
+
+impl<S, T> Actor for Sensor<S, T>
+where
+    S: traits::sensors::Sensor + 'static,
+    T: Trigger + 'static,
+{
+    type Message<'m> = ();
+    type OnMountFuture<'m, M> = impl Future<Output = ()> + 'm where Self: 'm, M: 'm + Inbox<Self::Message<'m>>;
+
+    fn on_mount<'m, M>(
+        &'m mut self,
+        _: Address<Self::Message<'m>>,
+        _: M,
+    ) -> Self::OnMountFuture<'m, M>
+    where
+        M: Inbox<Self::Message<'m>> + 'm,
+    {
+        async move {
+            if self.sensor.calibrate().await.is_err() {
+                warn!("error calibrating sensor");
+            }
+            loop {
+                self.trigger.wait().await;
+                match self.sensor.read().await {
+                    Ok(reading) => self.dest.notify(reading).await,
+                    Err(_) => {
+                        warn!("error reading sensor");
+                        self.trigger.read_failed();
+                    }
+                }
+            }
+        }
+    }
+}
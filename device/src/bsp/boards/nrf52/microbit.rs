@@ -1,6 +1,10 @@
 //use crate::actors::led::matrix::LedMatrixActor as MatrixActor;
 use crate::bsp::Board;
-use crate::drivers::{button::Button, led::matrix::LedMatrix as LedMatrixDriver, ActiveLow};
+use crate::drivers::{
+    button::Button,
+    led::matrix::{LedMatrix as LedMatrixDriver, OutputPins},
+    ActiveLow,
+};
 use crate::{
     domain::temperature::Celsius,
     domain::{temperature::Temperature, SensorAcquisition},
@@ -19,7 +23,7 @@ use embassy_nrf::{
     temp::Temp,
 };
 
-pub type LedMatrix = LedMatrixDriver<Output<'static, AnyPin>, 5, 5>;
+pub type LedMatrix = LedMatrixDriver<OutputPins<Output<'static, AnyPin>, 5, 5>, 5, 5>;
 //pub type LedMatrixActor = MatrixActor<Output<'static, AnyPin>, 5, 5>;
 
 pub type PinButtonA = Input<'static, P0_14>;
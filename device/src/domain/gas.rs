@@ -0,0 +1,19 @@
+//! Types related to gas concentrations.
+
+/// Concentration of carbon dioxide, in parts per million.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Co2(pub u16);
+
+/// Concentration of total volatile organic compounds, in parts per billion.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Tvoc(pub u16);
+
+/// A reading of a gas sensor, which may not measure every gas.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GasConcentration {
+    pub co2: Option<Co2>,
+    pub tvoc: Option<Tvoc>,
+}
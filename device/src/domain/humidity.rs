@@ -0,0 +1,53 @@
+//! Types related to humidity.
+
+use core::fmt::{Debug, Display, Formatter};
+
+/// Relative humidity, in percent.
+#[derive(Copy, Clone, PartialEq, PartialOrd)]
+pub struct RelativeHumidity(f32);
+
+impl RelativeHumidity {
+    pub fn new(percent: f32) -> Self {
+        Self(percent)
+    }
+
+    pub fn percent(&self) -> f32 {
+        self.0
+    }
+}
+
+impl Debug for RelativeHumidity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}%RH", &self.0)
+    }
+}
+
+impl Display for RelativeHumidity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.0, f)?;
+        f.write_str("%RH")
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for RelativeHumidity {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "{}%RH", &self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn test_format() {
+        let h = RelativeHumidity::new(42.5);
+        assert_eq!(42.5, h.percent());
+        assert_eq!("42.5%RH", format!("{}", h));
+        assert_eq!("42.5%RH", format!("{:?}", h));
+        assert_eq!("43%RH", format!("{:.0}", RelativeHumidity::new(42.6)));
+    }
+}
@@ -218,7 +218,163 @@ impl<const XSIZE: usize, const YSIZE: usize> Default for Frame<XSIZE, YSIZE> {
     }
 }
 
-#[derive(Clone, Copy)]
+#[cfg(feature = "embedded-graphics")]
+impl<const XSIZE: usize, const YSIZE: usize> embedded_graphics::geometry::OriginDimensions
+    for Frame<XSIZE, YSIZE>
+{
+    fn size(&self) -> embedded_graphics::geometry::Size {
+        embedded_graphics::geometry::Size::new(XSIZE as u32, YSIZE as u32)
+    }
+}
+
+/// Draw on a frame, where pixels outside of the frame are ignored.
+#[cfg(feature = "embedded-graphics")]
+impl<const XSIZE: usize, const YSIZE: usize> embedded_graphics::draw_target::DrawTarget
+    for Frame<XSIZE, YSIZE>
+{
+    type Color = embedded_graphics::pixelcolor::BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = embedded_graphics::Pixel<Self::Color>>,
+    {
+        for embedded_graphics::Pixel(point, color) in pixels {
+            if point.x >= 0
+                && point.y >= 0
+                && (point.x as usize) < XSIZE
+                && (point.y as usize) < YSIZE
+            {
+                if color.is_on() {
+                    self.set(point.x as usize, point.y as usize);
+                } else {
+                    self.unset(point.x as usize, point.y as usize);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/**
+ * A NxM frame where every pixel has its own brightness level, from 0 (off)
+ * up to `Brightness::MAX`.
+ */
+#[derive(Clone, Copy, PartialEq)]
+pub struct GrayFrame<const XSIZE: usize, const YSIZE: usize> {
+    levels: [[u8; XSIZE]; YSIZE],
+}
+
+impl<const XSIZE: usize, const YSIZE: usize> core::fmt::Debug for GrayFrame<XSIZE, YSIZE> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for row in self.levels.iter() {
+            for level in row.iter() {
+                write!(f, "{:X}", level)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<const XSIZE: usize, const YSIZE: usize> defmt::Format for GrayFrame<XSIZE, YSIZE> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        for row in self.levels.iter() {
+            defmt::write!(f, "{=[u8]}\n", &row[..]);
+        }
+    }
+}
+
+impl<const XSIZE: usize, const YSIZE: usize> GrayFrame<XSIZE, YSIZE> {
+    pub const fn empty() -> Self {
+        Self {
+            levels: [[0; XSIZE]; YSIZE],
+        }
+    }
+
+    /// Levels above `Brightness::MAX` are shown at the maximum.
+    pub const fn new(levels: [[u8; XSIZE]; YSIZE]) -> Self {
+        Self { levels }
+    }
+
+    /// Every pixel set in the frame at the given level.
+    pub fn from_frame(frame: &Frame<XSIZE, YSIZE>, level: Brightness) -> Self {
+        let mut gray = Self::empty();
+        for y in 0..YSIZE {
+            for x in 0..XSIZE {
+                if frame.is_set(x, y) {
+                    gray.levels[y][x] = level.level();
+                }
+            }
+        }
+        gray
+    }
+
+    pub fn clear(&mut self) {
+        self.levels = [[0; XSIZE]; YSIZE];
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, level: Brightness) {
+        self.levels[y][x] = core::cmp::min(level.level(), Brightness::MAX.level());
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Brightness {
+        Brightness(core::cmp::min(self.levels[y][x], Brightness::MAX.level()))
+    }
+
+    /// Keep the brightest level of each pixel.
+    pub fn or(&mut self, other: &GrayFrame<XSIZE, YSIZE>) {
+        for (row, other) in self.levels.iter_mut().zip(other.levels.iter()) {
+            for (level, other) in row.iter_mut().zip(other.iter()) {
+                *level = core::cmp::max(*level, *other);
+            }
+        }
+    }
+
+    pub fn shift_left(&mut self, nbits: usize) {
+        for row in self.levels.iter_mut() {
+            for x in 0..XSIZE {
+                row[x] = if x + nbits < XSIZE { row[x + nbits] } else { 0 };
+            }
+        }
+    }
+
+    pub fn shift_right(&mut self, nbits: usize) {
+        for row in self.levels.iter_mut() {
+            for x in (0..XSIZE).rev() {
+                row[x] = if x >= nbits { row[x - nbits] } else { 0 };
+            }
+        }
+    }
+
+    /// Blend towards another frame, `step` out of `steps` of the way.
+    pub fn blend(&self, other: &GrayFrame<XSIZE, YSIZE>, step: usize, steps: usize) -> Self {
+        let mut blended = Self::empty();
+        for y in 0..YSIZE {
+            for x in 0..XSIZE {
+                let from = self.get(x, y).level() as usize;
+                let to = other.get(x, y).level() as usize;
+                blended.levels[y][x] = ((from * (steps - step) + to * step) / steps) as u8;
+            }
+        }
+        blended
+    }
+}
+
+impl<const XSIZE: usize, const YSIZE: usize> Default for GrayFrame<XSIZE, YSIZE> {
+    fn default() -> Self {
+        GrayFrame::empty()
+    }
+}
+
+impl<const XSIZE: usize, const YSIZE: usize> From<Frame<XSIZE, YSIZE>> for GrayFrame<XSIZE, YSIZE> {
+    fn from(frame: Frame<XSIZE, YSIZE>) -> Self {
+        GrayFrame::from_frame(&frame, Brightness::MAX)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub struct Brightness(u8);
 
 impl Brightness {
@@ -229,7 +385,7 @@ impl Brightness {
         Self(level)
     }
 
-    pub fn level(&self) -> u8 {
+    pub const fn level(&self) -> u8 {
         self.0
     }
 }
@@ -314,4 +470,51 @@ mod tests {
         assert!(b.is_set(3));
         assert!(!b.is_set(4));
     }
+
+    #[test]
+    fn test_gray_frame() {
+        let mut frame: GrayFrame<3, 1> = GrayFrame::new([[0, 4, 10]]);
+        assert_eq!(Brightness::new(4), frame.get(1, 0));
+
+        let target = GrayFrame::new([[10, 0, 10]]);
+        assert_eq!(GrayFrame::new([[5, 2, 10]]), frame.blend(&target, 1, 2));
+        assert_eq!(target, frame.blend(&target, 2, 2));
+
+        frame.shift_right(1);
+        assert_eq!(GrayFrame::new([[0, 0, 4]]), frame);
+        frame.or(&GrayFrame::new([[1, 0, 2]]));
+        frame.shift_left(2);
+        assert_eq!(GrayFrame::new([[4, 0, 0]]), frame);
+
+        let mut bitmap: Frame<3, 1> = Frame::empty();
+        bitmap.set(1, 0);
+        assert_eq!(GrayFrame::new([[0, 10, 0]]), bitmap.into());
+    }
+
+    #[cfg(feature = "embedded-graphics")]
+    #[test]
+    fn test_draw_target() {
+        use embedded_graphics::{
+            pixelcolor::BinaryColor,
+            prelude::*,
+            primitives::{Line, PrimitiveStyle},
+        };
+
+        let mut frame: Frame<5, 5> = Frame::empty();
+        Line::new(Point::new(-1, -1), Point::new(5, 5))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(&mut frame)
+            .unwrap();
+
+        for x in 0..5 {
+            for y in 0..5 {
+                assert_eq!(x == y, frame.is_set(x, y));
+            }
+        }
+
+        Pixel(Point::new(2, 2), BinaryColor::Off)
+            .draw(&mut frame)
+            .unwrap();
+        assert!(!frame.is_set(2, 2));
+    }
 }
@@ -0,0 +1,53 @@
+//! Types related to light.
+
+use core::fmt::{Debug, Display, Formatter};
+
+/// Illuminance, in lux.
+#[derive(Copy, Clone, PartialEq, PartialOrd)]
+pub struct Illuminance(f32);
+
+impl Illuminance {
+    pub fn new(lux: f32) -> Self {
+        Self(lux)
+    }
+
+    pub fn lux(&self) -> f32 {
+        self.0
+    }
+}
+
+impl Debug for Illuminance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}lx", &self.0)
+    }
+}
+
+impl Display for Illuminance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.0, f)?;
+        f.write_str("lx")
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Illuminance {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "{}lx", &self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn test_format() {
+        let l = Illuminance::new(320.0);
+        assert_eq!(320.0, l.lux());
+        assert_eq!("320lx", format!("{}", l));
+        assert_eq!("320lx", format!("{:?}", l));
+        assert!(Illuminance::new(10.0) < l);
+    }
+}
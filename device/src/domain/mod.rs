@@ -1,4 +1,9 @@
+pub mod gas;
+pub mod humidity;
 pub mod led;
+pub mod light;
+pub mod motion;
+pub mod pressure;
 pub mod temperature;
 
 use core::fmt::{Debug, Formatter};
@@ -0,0 +1,31 @@
+//! Types related to motion and orientation.
+
+/// Acceleration along three axes, in milli-g.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Acceleration {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Acceleration {
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+}
+
+/// Magnetic field along three axes, in nanotesla.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MagneticField {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl MagneticField {
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+}
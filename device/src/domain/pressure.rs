@@ -0,0 +1,68 @@
+//! Types related to pressure.
+
+use core::fmt::{Debug, Display, Formatter};
+
+/// Pressure, in pascals.
+#[derive(Copy, Clone, PartialEq, PartialOrd)]
+pub struct Pressure(f32);
+
+impl Pressure {
+    pub fn new(pascals: f32) -> Self {
+        Self(pascals)
+    }
+
+    pub fn from_hectopascals(hectopascals: f32) -> Self {
+        Self(hectopascals * 100.0)
+    }
+
+    pub fn pascals(&self) -> f32 {
+        self.0
+    }
+
+    pub fn hectopascals(&self) -> f32 {
+        self.0 / 100.0
+    }
+}
+
+impl Debug for Pressure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}Pa", &self.0)
+    }
+}
+
+impl Display for Pressure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.0, f)?;
+        f.write_str("Pa")
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Pressure {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "{}Pa", &self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn test_hectopascals() {
+        let p = Pressure::from_hectopascals(1013.25);
+        assert_eq!(101325.0, p.pascals());
+        assert_eq!(1013.25, p.hectopascals());
+        assert_eq!(p, Pressure::new(101325.0));
+    }
+
+    #[test]
+    fn test_format() {
+        let p = Pressure::new(101325.5);
+        assert_eq!("101325.5Pa", format!("{}", p));
+        assert_eq!("101325.5Pa", format!("{:?}", p));
+        assert_eq!("101325.50Pa", format!("{:.2}", p));
+    }
+}
@@ -1,4 +1,9 @@
+use crate::drivers::ble::mesh::crypto;
 use crate::drivers::ble::mesh::device::Uuid;
+use crate::drivers::ble::mesh::pdu::ParseError;
+use crate::drivers::ble::mesh::InsufficientBuffer;
+use cmac::crypto_mac::InvalidKeyLength;
+use heapless::Vec;
 
 pub enum Beacon {
     Unprovisioned {
@@ -7,14 +12,122 @@ pub enum Beacon {
         uri_hash: Option<[u8; 4]>,
     },
 
-    SecureNetwork {
+    SecureNetwork(SecureNetworkBeacon),
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SecureNetworkBeacon {
+    pub(crate) flags: Flags,
+    pub(crate) network_id: [u8; 8],
+    pub(crate) iv_index: u32,
+    pub(crate) authentication_value: [u8; 8],
+}
+
+impl SecureNetworkBeacon {
+    const BEACON_TYPE: u8 = 0x01;
+
+    /// Build a beacon authenticated with the beacon key of a network.
+    pub fn new(
         flags: Flags,
         network_id: [u8; 8],
         iv_index: u32,
-        authentication_value: [u8; 8],
-    },
+        beacon_key: &[u8; 16],
+    ) -> Result<Self, InvalidKeyLength> {
+        let mut beacon = Self {
+            flags,
+            network_id,
+            iv_index,
+            authentication_value: [0; 8],
+        };
+        let cmac = crypto::aes_cmac(beacon_key, &beacon.authenticated_data())?.into_bytes();
+        beacon.authentication_value.copy_from_slice(&cmac[0..8]);
+        Ok(beacon)
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self, ParseError> {
+        if data.len() != 22 {
+            return Err(ParseError::InvalidLength);
+        }
+        if data[0] != Self::BEACON_TYPE {
+            return Err(ParseError::InvalidValue);
+        }
+        let flags = Flags::parse(data[1]);
+        let mut network_id = [0; 8];
+        network_id.copy_from_slice(&data[2..10]);
+        let iv_index = u32::from_be_bytes([data[10], data[11], data[12], data[13]]);
+        let mut authentication_value = [0; 8];
+        authentication_value.copy_from_slice(&data[14..22]);
+        Ok(Self {
+            flags,
+            network_id,
+            iv_index,
+            authentication_value,
+        })
+    }
+
+    pub fn emit<const N: usize>(&self, xmit: &mut Vec<u8, N>) -> Result<(), InsufficientBuffer> {
+        xmit.push(Self::BEACON_TYPE)
+            .map_err(|_| InsufficientBuffer)?;
+        xmit.extend_from_slice(&self.authenticated_data())
+            .map_err(|_| InsufficientBuffer)?;
+        xmit.extend_from_slice(&self.authentication_value)
+            .map_err(|_| InsufficientBuffer)?;
+        Ok(())
+    }
+
+    /// Verify the authentication value against the beacon key of a network.
+    pub fn authenticate(&self, beacon_key: &[u8; 16]) -> Result<bool, InvalidKeyLength> {
+        let cmac = crypto::aes_cmac(beacon_key, &self.authenticated_data())?.into_bytes();
+        Ok(cmac[0..8] == self.authentication_value)
+    }
+
+    fn authenticated_data(&self) -> [u8; 13] {
+        let mut data = [0; 13];
+        data[0] = self.flags.emit();
+        data[1..9].copy_from_slice(&self.network_id);
+        data[9..13].copy_from_slice(&self.iv_index.to_be_bytes());
+        data
+    }
+}
+
+/// Beacon advertised by a device waiting to be provisioned.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct UnprovisionedDeviceBeacon {
+    pub uuid: Uuid,
+    pub oob: OobInformation,
+    pub uri_hash: Option<[u8; 4]>,
+}
+
+impl UnprovisionedDeviceBeacon {
+    const BEACON_TYPE: u8 = 0x00;
+
+    pub fn parse(data: &[u8]) -> Result<Self, ParseError> {
+        if data.len() != 19 && data.len() != 23 {
+            return Err(ParseError::InvalidLength);
+        }
+        if data[0] != Self::BEACON_TYPE {
+            return Err(ParseError::InvalidValue);
+        }
+        let mut uuid = [0; 16];
+        uuid.copy_from_slice(&data[1..17]);
+        let oob = OobInformation::parse(u16::from_be_bytes([data[17], data[18]]));
+        let uri_hash = if data.len() == 23 {
+            let mut uri_hash = [0; 4];
+            uri_hash.copy_from_slice(&data[19..23]);
+            Some(uri_hash)
+        } else {
+            None
+        };
+        Ok(Self {
+            uuid: Uuid(uuid),
+            oob,
+            uri_hash,
+        })
+    }
 }
 
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct OobInformation {
     pub other: bool,
     pub electronic_url: bool,
@@ -30,7 +143,130 @@ pub struct OobInformation {
     pub on_device: bool,
 }
 
+impl OobInformation {
+    fn parse(bits: u16) -> Self {
+        Self {
+            other: bits & 0x0001 != 0,
+            electronic_url: bits & 0x0002 != 0,
+            two_dimensional_machine_readable_code: bits & 0x0004 != 0,
+            bar_code: bits & 0x0008 != 0,
+            nfc: bits & 0x0010 != 0,
+            number: bits & 0x0020 != 0,
+            string: bits & 0x0040 != 0,
+            // bits 7-10 are reserved
+            on_box: bits & 0x0800 != 0,
+            inside_box: bits & 0x1000 != 0,
+            on_piece_of_paper: bits & 0x2000 != 0,
+            inside_manual: bits & 0x4000 != 0,
+            on_device: bits & 0x8000 != 0,
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Flags {
-    key_refresh: bool,
-    iv_update: bool,
+    pub(crate) key_refresh: bool,
+    pub(crate) iv_update: bool,
+}
+
+impl Flags {
+    fn parse(val: u8) -> Self {
+        Self {
+            key_refresh: val & 0b01 != 0,
+            iv_update: val & 0b10 != 0,
+        }
+    }
+
+    fn emit(&self) -> u8 {
+        let mut val = 0;
+        if self.key_refresh {
+            val |= 0b01;
+        }
+        if self.iv_update {
+            val |= 0b10;
+        }
+        val
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drivers::ble::mesh::crypto::{beacon_key, k3};
+
+    fn net_key() -> [u8; 16] {
+        [
+            0x7d, 0xd7, 0x36, 0x4c, 0xd8, 0x42, 0xad, 0x18, 0xc1, 0x7c, 0x2b, 0x82, 0x0c, 0x84,
+            0xc3, 0xd6,
+        ]
+    }
+
+    #[test]
+    fn test_beacon_key() {
+        // Mesh Profile sample data 8.2.5
+        assert_eq!(
+            [
+                0x54, 0x23, 0xd9, 0x67, 0xda, 0x63, 0x9a, 0x99, 0xcb, 0x02, 0x23, 0x1a, 0x83, 0xf7,
+                0xd2, 0x54
+            ],
+            beacon_key(&net_key()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_secure_network_beacon() {
+        // Mesh Profile sample data 8.4.1
+        let data = [
+            0x01, 0x00, 0x3e, 0xca, 0xff, 0x67, 0x2f, 0x67, 0x33, 0x70, 0x12, 0x34, 0x56, 0x78,
+            0x8e, 0xa2, 0x61, 0x58, 0x2f, 0x36, 0x4f, 0x6f,
+        ];
+        let beacon = SecureNetworkBeacon::parse(&data).unwrap();
+        assert!(!beacon.flags.key_refresh);
+        assert!(!beacon.flags.iv_update);
+        assert_eq!(k3(&net_key()).unwrap(), beacon.network_id);
+        assert_eq!(0x12345678, beacon.iv_index);
+
+        let key = beacon_key(&net_key()).unwrap();
+        assert!(beacon.authenticate(&key).unwrap());
+        assert!(!beacon.authenticate(&[0; 16]).unwrap());
+
+        let mut xmit: Vec<u8, 32> = Vec::new();
+        beacon.emit(&mut xmit).unwrap();
+        assert_eq!(&data, &xmit[..]);
+
+        assert!(matches!(
+            SecureNetworkBeacon::parse(&data[..21]),
+            Err(ParseError::InvalidLength)
+        ));
+    }
+
+    #[test]
+    fn test_secure_network_beacon_flags() {
+        // Mesh Profile sample data 8.4.2, IV update in progress
+        let key = beacon_key(&net_key()).unwrap();
+        let flags = Flags {
+            key_refresh: false,
+            iv_update: true,
+        };
+        let beacon =
+            SecureNetworkBeacon::new(flags, k3(&net_key()).unwrap(), 0x12345679, &key).unwrap();
+        assert_eq!(
+            [0xc2, 0xaf, 0x80, 0xad, 0x07, 0x2a, 0x13, 0x5c],
+            beacon.authentication_value
+        );
+
+        let flags = Flags {
+            key_refresh: true,
+            iv_update: false,
+        };
+        let beacon =
+            SecureNetworkBeacon::new(flags, k3(&net_key()).unwrap(), 0x12345678, &key).unwrap();
+        let mut xmit: Vec<u8, 32> = Vec::new();
+        beacon.emit(&mut xmit).unwrap();
+        let parsed = SecureNetworkBeacon::parse(&xmit).unwrap();
+        assert!(parsed.flags.key_refresh);
+        assert!(!parsed.flags.iv_update);
+        assert!(parsed.authenticate(&key).unwrap());
+    }
 }
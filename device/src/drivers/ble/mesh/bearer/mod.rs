@@ -1,5 +1,7 @@
 #[cfg(feature = "ble+nrf-softdevice-s140")]
 pub mod nrf52;
+#[cfg(feature = "std")]
+pub mod simulated;
 
 /*
 use core::future::Future;
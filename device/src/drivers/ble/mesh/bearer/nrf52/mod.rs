@@ -137,51 +137,51 @@ impl SoftdeviceGattBearer {
         }
     }
 
+    /// Wait for a connection, or serve the current one until it closes.
     async fn run(&self) -> Result<(), BearerError> {
-        loop {
-            let connection = self.connection.wait().await;
-            self.current_connection.borrow_mut().replace(connection);
-            gatt_server::run(
-                self.current_connection.borrow().as_ref().unwrap(),
-                &self.server,
-                |e| match e {
-                    MeshGattServerEvent::Proxy(event) => match event {
-                        ProxyServiceEvent::DataInWrite(data) => {
-                            self.inbound.try_send(data).ok();
-                        }
-                        ProxyServiceEvent::DataOutCccdWrite { notifications } => {
-                            if notifications {
-                                self.connection_channel
-                                    .replace(Some(ConnectionChannel::Proxy));
-                            } else {
-                                self.connection_channel.take();
-                            }
-                        }
-                        _ => { /* ignorable */ }
-                    },
-                    MeshGattServerEvent::Provisioning(event) => match event {
-                        ProvisioningServiceEvent::DataInWrite(data) => {
-                            self.inbound.try_send(data).ok();
+        let current_connection = self.current_connection.borrow().clone();
+        if let Some(connection) = current_connection {
+            gatt_server::run(&connection, &self.server, |e| match e {
+                MeshGattServerEvent::Proxy(event) => match event {
+                    ProxyServiceEvent::DataInWrite(data) => {
+                        self.inbound.try_send(data).ok();
+                    }
+                    ProxyServiceEvent::DataOutCccdWrite { notifications } => {
+                        if notifications {
+                            self.connection_channel
+                                .replace(Some(ConnectionChannel::Proxy));
+                        } else {
+                            self.connection_channel.take();
                         }
-                        ProvisioningServiceEvent::DataOutCccdWrite { notifications } => {
-                            if notifications {
-                                self.connection_channel
-                                    .replace(Some(ConnectionChannel::Provisioning));
-                            } else {
-                                self.connection_channel.take();
-                            }
+                    }
+                    _ => { /* ignorable */ }
+                },
+                MeshGattServerEvent::Provisioning(event) => match event {
+                    ProvisioningServiceEvent::DataInWrite(data) => {
+                        self.inbound.try_send(data).ok();
+                    }
+                    ProvisioningServiceEvent::DataOutCccdWrite { notifications } => {
+                        if notifications {
+                            self.connection_channel
+                                .replace(Some(ConnectionChannel::Provisioning));
+                        } else {
+                            self.connection_channel.take();
                         }
-                        _ => { /* ignorable */ }
-                    },
+                    }
+                    _ => { /* ignorable */ }
                 },
-            )
+            })
             .await
             .ok();
 
             self.connection_channel.borrow_mut().take();
             self.current_connection.borrow_mut().take();
             self.connected.store(false, Ordering::Relaxed);
+        } else {
+            let connection = self.connection.wait().await;
+            self.current_connection.borrow_mut().replace(connection);
         }
+        Ok(())
     }
 }
 
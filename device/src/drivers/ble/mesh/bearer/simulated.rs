@@ -0,0 +1,659 @@
+//! Bearers simulated in memory, to run several mesh nodes in one std executor.
+//!
+//! Advertising bearers share a `SimulatedAir`, which delivers every advertisement
+//! to all the other bearers on the same air, after some latency and subject to
+//! some loss. GATT bearers are connected point to point, through a `SimulatedGattLink`
+//! whose other end is driven by the test as the client.
+
+use crate::drivers::ble::mesh::driver::node::{NetworkId, State};
+use crate::drivers::ble::mesh::interface::{AdvertisingBearer, BearerError};
+use crate::drivers::ble::mesh::interface::{GattBearer, PB_ADV_MTU};
+use core::cell::{Cell, RefCell};
+use core::future::Future;
+use embassy::blocking_mutex::raw::NoopRawMutex;
+use embassy::channel::mpmc::Channel;
+use embassy::channel::signal::Signal;
+use embassy::time::{Duration, Instant, Timer};
+use heapless::Vec;
+
+// Everything runs within one executor.
+type AirMutex = NoopRawMutex;
+
+// Advertisements a bearer may have in flight before further ones are lost.
+const QUEUE_SIZE: usize = 16;
+
+struct Frame<const N: usize> {
+    deliver_at: Instant,
+    data: Vec<u8, N>,
+}
+
+/// The medium shared by up to `N` simulated advertising bearers.
+pub struct SimulatedAir<const N: usize> {
+    radios: [Channel<AirMutex, Frame<PB_ADV_MTU>, QUEUE_SIZE>; N],
+    attached: Cell<usize>,
+    latency: Cell<Duration>,
+    loss: Cell<u8>,
+    seed: Cell<u32>,
+}
+
+impl<const N: usize> SimulatedAir<N> {
+    /// Air without latency nor loss.
+    pub fn new() -> Self {
+        const RADIO: Channel<AirMutex, Frame<PB_ADV_MTU>, QUEUE_SIZE> = Channel::new();
+        Self {
+            radios: [RADIO; N],
+            attached: Cell::new(0),
+            latency: Cell::new(Duration::from_ticks(0)),
+            loss: Cell::new(0),
+            seed: Cell::new(0x2545_F491),
+        }
+    }
+
+    /// Delay every advertisement by the given latency.
+    pub fn with_latency(self, latency: Duration) -> Self {
+        self.set_latency(latency);
+        self
+    }
+
+    /// Lose the given percentage of the advertisements, independently for each receiver.
+    pub fn with_loss(self, percent: u8) -> Self {
+        self.set_loss(percent);
+        self
+    }
+
+    /// Seed the choice of lost advertisements, to reproduce a run.
+    pub fn with_seed(self, seed: u32) -> Self {
+        self.seed.set(seed.max(1));
+        self
+    }
+
+    pub fn set_latency(&self, latency: Duration) {
+        self.latency.set(latency);
+    }
+
+    pub fn set_loss(&self, percent: u8) {
+        self.loss.set(percent.min(100));
+    }
+
+    /// Attach a new bearer to the air, or `None` once all `N` are attached.
+    pub fn bearer(&self) -> Option<SimulatedAdvertisingBearer<'_, N>> {
+        let index = self.attached.get();
+        if index < N {
+            self.attached.set(index + 1);
+            Some(SimulatedAdvertisingBearer { air: self, index })
+        } else {
+            None
+        }
+    }
+
+    fn lost(&self) -> bool {
+        // xorshift32
+        let mut x = self.seed.get();
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.seed.set(x);
+        (x % 100) < self.loss.get() as u32
+    }
+
+    fn transmit(&self, from: usize, data: &Vec<u8, PB_ADV_MTU>) {
+        let deliver_at = Instant::now() + self.latency.get();
+        for (index, radio) in self.radios[0..self.attached.get()].iter().enumerate() {
+            if index != from && !self.lost() {
+                // a full queue is a collision.
+                radio
+                    .try_send(Frame {
+                        deliver_at,
+                        data: data.clone(),
+                    })
+                    .ok();
+            }
+        }
+    }
+}
+
+impl<const N: usize> Default for SimulatedAir<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn deliver<const N: usize>(frame: Frame<N>) -> Vec<u8, N> {
+    if frame.deliver_at > Instant::now() {
+        Timer::at(frame.deliver_at).await;
+    }
+    frame.data
+}
+
+pub struct SimulatedAdvertisingBearer<'a, const N: usize> {
+    air: &'a SimulatedAir<N>,
+    index: usize,
+}
+
+impl<'a, const N: usize> AdvertisingBearer for SimulatedAdvertisingBearer<'a, N> {
+    fn set_state(&self, _state: State) {
+        // ignored.
+    }
+
+    fn set_network_id(&self, _network_id: NetworkId) {
+        // ignored
+    }
+
+    type ReceiveFuture<'m> = impl Future<Output=Result<Vec<u8, PB_ADV_MTU>, BearerError>> + 'm
+    where
+    Self: 'm;
+
+    fn receive<'m>(&'m self) -> Self::ReceiveFuture<'m> {
+        async move {
+            let frame = self.air.radios[self.index].recv().await;
+            Ok(deliver(frame).await)
+        }
+    }
+
+    type TransmitFuture<'m> = impl Future<Output = Result<(), BearerError>> + 'm
+    where
+    Self: 'm;
+
+    fn transmit<'m>(&'m self, pdu: &'m Vec<u8, PB_ADV_MTU>) -> Self::TransmitFuture<'m> {
+        async move {
+            self.air.transmit(self.index, pdu);
+            Ok(())
+        }
+    }
+}
+
+/// A GATT connection between a simulated bearer and a client driven by the test.
+pub struct SimulatedGattLink<const MTU: usize> {
+    to_bearer: Channel<AirMutex, Frame<MTU>, QUEUE_SIZE>,
+    to_client: Channel<AirMutex, Frame<MTU>, QUEUE_SIZE>,
+    advertisement: RefCell<Option<Vec<u8, 64>>>,
+    connected: Cell<bool>,
+    changed: Signal<()>,
+    latency: Cell<Duration>,
+}
+
+impl<const MTU: usize> SimulatedGattLink<MTU> {
+    pub fn new() -> Self {
+        Self {
+            to_bearer: Channel::new(),
+            to_client: Channel::new(),
+            advertisement: RefCell::new(None),
+            connected: Cell::new(false),
+            changed: Signal::new(),
+            latency: Cell::new(Duration::from_ticks(0)),
+        }
+    }
+
+    /// Delay every PDU by the given latency, in both directions.
+    pub fn with_latency(self, latency: Duration) -> Self {
+        self.latency.set(latency);
+        self
+    }
+
+    pub fn bearer(&self) -> SimulatedGattBearer<'_, MTU> {
+        SimulatedGattBearer { link: self }
+    }
+
+    /// The latest connectable advertisement of the bearer, if any.
+    pub fn advertisement(&self) -> Option<Vec<u8, 64>> {
+        self.advertisement.borrow().clone()
+    }
+
+    /// Connect the client, which stops the bearer from advertising.
+    pub fn connect(&self) {
+        self.connected.set(true);
+        self.changed.signal(());
+    }
+
+    /// Disconnect the client, dropping anything still in flight.
+    pub fn disconnect(&self) {
+        self.connected.set(false);
+        while self.to_bearer.try_recv().is_ok() {}
+        while self.to_client.try_recv().is_ok() {}
+        self.changed.signal(());
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected.get()
+    }
+
+    /// Write a PDU to the bearer, as the client.
+    pub fn send(&self, pdu: &Vec<u8, MTU>) -> Result<(), BearerError> {
+        self.queue(&self.to_bearer, pdu)
+    }
+
+    /// Receive a PDU notified by the bearer, as the client.
+    pub async fn receive(&self) -> Vec<u8, MTU> {
+        deliver(self.to_client.recv().await).await
+    }
+
+    fn queue(
+        &self,
+        channel: &Channel<AirMutex, Frame<MTU>, QUEUE_SIZE>,
+        pdu: &Vec<u8, MTU>,
+    ) -> Result<(), BearerError> {
+        if !self.connected.get() {
+            return Err(BearerError::InvalidLink);
+        }
+        channel
+            .try_send(Frame {
+                deliver_at: Instant::now() + self.latency.get(),
+                data: pdu.clone(),
+            })
+            .map_err(|_| BearerError::InsufficientResources)
+    }
+}
+
+impl<const MTU: usize> Default for SimulatedGattLink<MTU> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct SimulatedGattBearer<'a, const MTU: usize> {
+    link: &'a SimulatedGattLink<MTU>,
+}
+
+impl<'a, const MTU: usize> GattBearer<MTU> for SimulatedGattBearer<'a, MTU> {
+    fn set_state(&self, _state: State) {
+        // ignored.
+    }
+
+    fn set_network_id(&self, _network_id: NetworkId) {
+        // ignored
+    }
+
+    type RunFuture<'m> = impl Future<Output=Result<(), BearerError>> + 'm
+    where
+    Self: 'm;
+
+    fn run<'m>(&'m self) -> Self::RunFuture<'m> {
+        async move {
+            // connections are driven by the client.
+            self.link.changed.wait().await;
+            Ok(())
+        }
+    }
+
+    type ReceiveFuture<'m> = impl Future<Output=Result<Vec<u8, MTU>, BearerError>> + 'm
+    where
+    Self: 'm;
+
+    fn receive<'m>(&'m self) -> Self::ReceiveFuture<'m> {
+        async move { Ok(deliver(self.link.to_bearer.recv().await).await) }
+    }
+
+    type TransmitFuture<'m> = impl Future<Output = Result<(), BearerError>> + 'm
+    where
+    Self: 'm;
+
+    fn transmit<'m>(&'m self, pdu: &'m Vec<u8, MTU>) -> Self::TransmitFuture<'m> {
+        async move {
+            if self.link.is_connected() {
+                self.link.queue(&self.link.to_client, pdu)
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    type AdvertiseFuture<'m> = impl Future<Output = Result<(), BearerError>> + 'm
+    where
+    Self: 'm;
+
+    fn advertise<'m>(&'m self, adv_data: &'m Vec<u8, 64>) -> Self::AdvertiseFuture<'m> {
+        async move {
+            if !self.link.is_connected() {
+                self.link.advertisement.replace(Some(adv_data.clone()));
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actors::ble::mesh::{MeshNode, MeshNodeMessage, NodeMutex};
+    use crate::drivers::ble::mesh::address::UnicastAddress;
+    use crate::drivers::ble::mesh::composition::{
+        CompanyIdentifier, Composition, ElementsHandler, Features, ProductIdentifier,
+        VersionIdentifier,
+    };
+    use crate::drivers::ble::mesh::config::network::{Network, NetworkKey, NetworkKeyHandle};
+    use crate::drivers::ble::mesh::config::Configuration;
+    use crate::drivers::ble::mesh::driver::elements::AppElementsContext;
+    use crate::drivers::ble::mesh::driver::node::ProvisionedDevice;
+    use crate::drivers::ble::mesh::driver::DeviceError;
+    use crate::drivers::ble::mesh::interface::AdvertisingOnlyNetworkInterfaces;
+    use crate::drivers::ble::mesh::model::foundation::configuration::{AppKeyIndex, NetKeyIndex};
+    use crate::drivers::ble::mesh::model::ModelIdentifier;
+    use crate::drivers::ble::mesh::oob::NoOob;
+    use crate::drivers::ble::mesh::pdu::access::AccessMessage;
+    use crate::drivers::ble::mesh::provisioning::{
+        Algorithms, Capabilities, IVUpdateFlag, InputOOBActions, OOBSize, OutputOOBActions,
+        PublicKeyType, StaticOOBType,
+    };
+    use crate::drivers::ble::mesh::storage::{Payload, Storage};
+    use ector::testutil::TestContext;
+    use embassy::time::with_timeout;
+    use embassy::util::{select, Either};
+    use futures::executor::block_on;
+    use futures::future::join;
+    use rand_core::{CryptoRng, RngCore};
+
+    const NETWORK_KEY: [u8; 16] = [0x7d; 16];
+
+    fn pdu(data: &[u8]) -> Vec<u8, PB_ADV_MTU> {
+        Vec::from_slice(data).unwrap()
+    }
+
+    #[test]
+    fn test_broadcast_to_others() {
+        let air: SimulatedAir<3> = SimulatedAir::new();
+        let a = air.bearer().unwrap();
+        let b = air.bearer().unwrap();
+        let c = air.bearer().unwrap();
+        assert!(air.bearer().is_none());
+
+        block_on(a.transmit(&pdu(&[0x01]))).unwrap();
+        block_on(b.transmit(&pdu(&[0x02]))).unwrap();
+
+        assert_eq!(&[0x02], &block_on(a.receive()).unwrap()[..]);
+        assert_eq!(&[0x01], &block_on(b.receive()).unwrap()[..]);
+        assert_eq!(&[0x01], &block_on(c.receive()).unwrap()[..]);
+        assert_eq!(&[0x02], &block_on(c.receive()).unwrap()[..]);
+    }
+
+    #[test]
+    fn test_loss() {
+        let air: SimulatedAir<2> = SimulatedAir::new().with_loss(100);
+        let a = air.bearer().unwrap();
+        let b = air.bearer().unwrap();
+
+        block_on(a.transmit(&pdu(&[0x01]))).unwrap();
+        air.set_loss(0);
+        block_on(a.transmit(&pdu(&[0x02]))).unwrap();
+
+        assert_eq!(&[0x02], &block_on(b.receive()).unwrap()[..]);
+    }
+
+    #[test]
+    fn test_gatt_link() {
+        let link: SimulatedGattLink<66> = SimulatedGattLink::new();
+        let bearer = link.bearer();
+        let data: Vec<u8, 66> = Vec::from_slice(&[0x03, 0x00]).unwrap();
+
+        block_on(bearer.advertise(&Vec::from_slice(&[0x02, 0x01, 0x06]).unwrap())).unwrap();
+        assert!(link.advertisement().is_some());
+        assert!(link.send(&data).is_err());
+
+        link.connect();
+        block_on(bearer.run()).unwrap();
+        link.send(&data).unwrap();
+        assert_eq!(data, block_on(bearer.receive()).unwrap());
+
+        block_on(bearer.transmit(&data)).unwrap();
+        assert_eq!(data, block_on(link.receive()));
+
+        link.disconnect();
+        block_on(bearer.run()).unwrap();
+        assert!(link.send(&data).is_err());
+    }
+
+    #[test]
+    fn test_latency() {
+        run_task!(latency);
+    }
+
+    #[embassy::task]
+    async fn latency(_context: TestContext<()>) {
+        let air: SimulatedAir<2> = SimulatedAir::new().with_latency(Duration::from_millis(50));
+        let a = air.bearer().unwrap();
+        let b = air.bearer().unwrap();
+
+        let sent = Instant::now();
+        a.transmit(&pdu(&[0x01])).await.unwrap();
+        assert_eq!(&[0x01], &b.receive().await.unwrap()[..]);
+        assert!(Instant::now() >= sent + Duration::from_millis(50));
+
+        // delivered in order, each after its own latency.
+        air.set_latency(Duration::from_millis(0));
+        a.transmit(&pdu(&[0x02])).await.unwrap();
+        a.transmit(&pdu(&[0x03])).await.unwrap();
+        assert_eq!(&[0x02], &b.receive().await.unwrap()[..]);
+        assert_eq!(&[0x03], &b.receive().await.unwrap()[..]);
+    }
+
+    /// What a node hands its application, for the test to act upon.
+    #[derive(Default)]
+    struct Observer<'a> {
+        context: RefCell<Option<AppElementsContext<'a>>>,
+        provisioned: Cell<Option<ProvisionedDevice>>,
+    }
+
+    struct TestElements<'a> {
+        composition: Composition,
+        observer: &'a Observer<'a>,
+    }
+
+    impl<'a> TestElements<'a> {
+        fn new(observer: &'a Observer<'a>) -> Self {
+            Self {
+                composition: Composition::new(
+                    CompanyIdentifier(0x0003),
+                    ProductIdentifier(0x0001),
+                    VersionIdentifier(0x0001),
+                    Features {
+                        relay: false,
+                        proxy: false,
+                        friend: false,
+                        low_power: false,
+                    },
+                ),
+                observer,
+            }
+        }
+    }
+
+    impl<'a> ElementsHandler<'a> for TestElements<'a> {
+        fn composition(&self) -> &Composition {
+            &self.composition
+        }
+
+        fn connect(&mut self, ctx: AppElementsContext<'a>) {
+            self.observer.context.replace(Some(ctx));
+        }
+
+        fn provisioned(&mut self, device: &ProvisionedDevice) {
+            self.observer.provisioned.set(Some(*device));
+        }
+
+        type DispatchFuture<'m> = impl Future<Output = Result<(), DeviceError>> + 'm
+        where
+            Self: 'm;
+
+        fn dispatch<'m>(
+            &'m mut self,
+            _element: u8,
+            _model_identifier: &'m ModelIdentifier,
+            _message: &'m AccessMessage,
+        ) -> Self::DispatchFuture<'m> {
+            async move { Ok(()) }
+        }
+    }
+
+    #[derive(Default)]
+    struct MemoryStorage(Option<Payload>);
+
+    impl MemoryStorage {
+        /// Storage of a node already on the network, at address 0x0001.
+        fn provisioned() -> Self {
+            let network_key =
+                NetworkKeyHandle::new(NetworkKey::new(NETWORK_KEY), NetKeyIndex::new(0)).unwrap();
+            let mut config = Configuration::default();
+            config.network_mut().replace(Network::new(
+                network_key.into(),
+                IVUpdateFlag::NormalOperation,
+                0,
+                UnicastAddress(0x0001),
+            ));
+            Self(Some(config.to_payload().unwrap()))
+        }
+    }
+
+    impl Storage for MemoryStorage {
+        type StoreFuture<'m> = impl Future<Output = Result<(), ()>> + 'm
+        where
+            Self: 'm;
+
+        fn store<'m>(&'m mut self, payload: &'m Payload) -> Self::StoreFuture<'m> {
+            async move {
+                let payload =
+                    Payload::from_slice(payload.version(), payload.as_slice()).map_err(|_| ())?;
+                self.0.replace(payload);
+                Ok(())
+            }
+        }
+
+        type RetrieveFuture<'m> = impl Future<Output = Result<Option<Payload>, ()>> + 'm
+        where
+            Self: 'm;
+
+        fn retrieve<'m>(&'m mut self) -> Self::RetrieveFuture<'m> {
+            async move {
+                match &self.0 {
+                    Some(payload) => Payload::from_slice(payload.version(), payload.as_slice())
+                        .map(Some)
+                        .map_err(|_| ()),
+                    None => Ok(None),
+                }
+            }
+        }
+    }
+
+    /// Reproducible, and nothing like cryptographic.
+    struct TestRng(u32);
+
+    impl RngCore for TestRng {
+        fn next_u32(&mut self) -> u32 {
+            // xorshift32
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 17;
+            self.0 ^= self.0 << 5;
+            self.0
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            ((self.next_u32() as u64) << 32) | self.next_u32() as u64
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for byte in dest.iter_mut() {
+                *byte = self.next_u32() as u8;
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    impl CryptoRng for TestRng {}
+
+    fn capabilities() -> Capabilities {
+        Capabilities {
+            number_of_elements: 1,
+            algorithms: Algorithms::default(),
+            public_key_type: PublicKeyType::default(),
+            static_oob_type: StaticOOBType::default(),
+            output_oob_size: OOBSize::NotSupported,
+            output_oob_action: OutputOOBActions::default(),
+            input_oob_size: OOBSize::NotSupported,
+            input_oob_action: InputOOBActions::default(),
+        }
+    }
+
+    /// Poll until `f` finds what the nodes were expected to do.
+    async fn wait_for<T>(f: impl Fn() -> Option<T>) -> T {
+        loop {
+            if let Some(value) = f() {
+                return value;
+            }
+            Timer::after(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[test]
+    fn test_provision_and_configure() {
+        run_task!(provision_and_configure);
+    }
+
+    #[embassy::task]
+    async fn provision_and_configure(_context: TestContext<()>) {
+        let air: SimulatedAir<2> = SimulatedAir::new().with_latency(Duration::from_millis(5));
+        let provisioner_control: Channel<NodeMutex, MeshNodeMessage, 1> = Channel::new();
+        let device_control: Channel<NodeMutex, MeshNodeMessage, 1> = Channel::new();
+        let provisioner_observer = Observer::default();
+        let device_observer = Observer::default();
+
+        let mut provisioner = MeshNode::new(
+            TestElements::new(&provisioner_observer),
+            capabilities(),
+            AdvertisingOnlyNetworkInterfaces::new(air.bearer().unwrap()),
+            NoOob,
+            MemoryStorage::provisioned(),
+            TestRng(1),
+        );
+        let mut device = MeshNode::new(
+            TestElements::new(&device_observer),
+            capabilities(),
+            AdvertisingOnlyNetworkInterfaces::new(air.bearer().unwrap()),
+            NoOob,
+            MemoryStorage::default(),
+            TestRng(2),
+        );
+
+        let scenario = async {
+            provisioner_control
+                .send(MeshNodeMessage::ProvisionAnyDevice)
+                .await;
+
+            // over PB-ADV, after this node's own element.
+            let provisioned = wait_for(|| provisioner_observer.provisioned.get()).await;
+            assert_eq!(UnicastAddress(0x0002), provisioned.unicast_address);
+            let address = wait_for(|| {
+                device_observer
+                    .context
+                    .borrow()
+                    .as_ref()
+                    .map(|ctx| ctx.address())
+            })
+            .await;
+            assert_eq!(provisioned.unicast_address, address);
+
+            // App Key Add is segmented, and answered once reassembled.
+            let client = provisioner_observer
+                .context
+                .borrow()
+                .as_ref()
+                .unwrap()
+                .configuration_client(provisioned.unicast_address, provisioned.device_key);
+            client
+                .app_key_add(NetKeyIndex::new(0), AppKeyIndex::new(1), [0xAA; 16])
+                .await
+                .unwrap();
+        };
+
+        let nodes = join(
+            provisioner.run(provisioner_control.receiver().into()),
+            device.run(device_control.receiver().into()),
+        );
+        match select(nodes, with_timeout(Duration::from_secs(60), scenario)).await {
+            Either::First(_) => panic!("nodes stopped running"),
+            Either::Second(Err(_)) => panic!("timed out"),
+            Either::Second(Ok(())) => {}
+        }
+    }
+}
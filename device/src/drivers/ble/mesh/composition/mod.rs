@@ -1,5 +1,7 @@
 use crate::drivers::ble::mesh::config::foundation_models::ConfigurationModel;
+use crate::drivers::ble::mesh::config::replay_cache::MAX_REPLAY_ENTRIES;
 use crate::drivers::ble::mesh::driver::elements::AppElementsContext;
+use crate::drivers::ble::mesh::driver::node::ProvisionedDevice;
 use crate::drivers::ble::mesh::driver::DeviceError;
 use crate::drivers::ble::mesh::model::ModelIdentifier;
 use crate::drivers::ble::mesh::pdu::access::AccessMessage;
@@ -16,6 +18,15 @@ pub trait ElementsHandler<'a> {
 
     fn configure(&mut self, _: &ConfigurationModel) {}
 
+    /// Notified when this node, acting as provisioner, has provisioned another device.
+    fn provisioned(&mut self, _: &ProvisionedDevice) {}
+
+    /// Notified when the attention timer of the Health Server is set, or runs out.
+    ///
+    /// A non-zero number of seconds asks to draw attention to this node, such as
+    /// by blinking an LED, until notified again with zero.
+    fn attention(&mut self, _seconds: u8) {}
+
     type DispatchFuture<'m>: Future<Output = Result<(), DeviceError>> + 'm
     where
         Self: 'm;
@@ -28,7 +39,7 @@ pub trait ElementsHandler<'a> {
     ) -> Self::DispatchFuture<'m>;
 }
 
-#[derive(Serialize, Deserialize, Eq, PartialEq, Copy, Clone)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Copy, Clone, Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct CompanyIdentifier(pub u16);
 
@@ -60,6 +71,20 @@ pub struct Features {
 }
 
 impl Features {
+    pub(crate) fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() >= 2 {
+            let val = parameters[0];
+            Ok(Self {
+                relay: val & 0b0001 != 0,
+                proxy: val & 0b0010 != 0,
+                friend: val & 0b0100 != 0,
+                low_power: val & 0b1000 != 0,
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
     pub(crate) fn emit<const N: usize>(
         &self,
         xmit: &mut Vec<u8, N>,
@@ -106,7 +131,7 @@ impl Composition {
             cid,
             pid,
             vid,
-            crpl: 0,
+            crpl: MAX_REPLAY_ENTRIES as u16,
             features,
             elements: Default::default(),
         }
@@ -115,6 +140,37 @@ impl Composition {
     pub fn add_element(&mut self, element: ElementDescriptor) -> Result<(), ElementDescriptor> {
         self.elements.push(element)
     }
+
+    pub fn cid(&self) -> CompanyIdentifier {
+        self.cid
+    }
+
+    pub fn pid(&self) -> ProductIdentifier {
+        self.pid
+    }
+
+    pub fn vid(&self) -> VersionIdentifier {
+        self.vid
+    }
+
+    pub fn crpl(&self) -> u16 {
+        self.crpl
+    }
+
+    /// Set the number of sources kept in the replay protection list.
+    ///
+    /// Capped to the number of sources the node is able to store.
+    pub fn set_crpl(&mut self, crpl: u16) {
+        self.crpl = crpl.min(MAX_REPLAY_ENTRIES as u16);
+    }
+
+    pub fn features(&self) -> Features {
+        self.features
+    }
+
+    pub fn elements(&self) -> &[ElementDescriptor] {
+        &self.elements
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -125,7 +181,7 @@ pub struct Location(pub u16);
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ElementDescriptor {
     pub(crate) loc: Location,
-    pub(crate) models: Vec<ModelIdentifier, 4>,
+    pub(crate) models: Vec<ModelIdentifier, 8>,
 }
 
 impl ElementDescriptor {
@@ -140,4 +196,12 @@ impl ElementDescriptor {
         self.models.push(model).ok();
         self
     }
+
+    pub fn loc(&self) -> Location {
+        self.loc
+    }
+
+    pub fn models(&self) -> &[ModelIdentifier] {
+        &self.models
+    }
 }
@@ -1,4 +1,5 @@
 use crate::drivers::ble::mesh::app::ApplicationKeyIdentifier;
+use crate::drivers::ble::mesh::config::v0;
 use crate::drivers::ble::mesh::crypto;
 use crate::drivers::ble::mesh::model::foundation::configuration::AppKeyIndex;
 use crate::drivers::ble::mesh::model::Status;
@@ -12,7 +13,7 @@ pub struct AppKeys {
 
 impl AppKeys {
     fn find_by_aid(&self, aid: ApplicationKeyIdentifier) -> Option<&AppKeyDetails> {
-        self.keys.iter().find(|e| e.aid == aid)
+        self.keys.iter().find(|e| e.matches_aid(&aid))
     }
 
     fn find_by_index(&self, index: AppKeyIndex) -> Option<&AppKeyDetails> {
@@ -23,11 +24,8 @@ impl AppKeys {
         if let Some(_) = self.find_by_index(index) {
             Err(Status::KeyIndexAlreadyStored)
         } else {
-            let aid = crypto::k4(key.as_ref())
-                .map_err(|_| Status::UnspecifiedError)?
-                .into();
             self.keys
-                .push(AppKeyDetails { aid, key, index })
+                .push(AppKeyDetails::new(index, key)?)
                 .map_err(|_| Status::InsufficientResources)?;
             Ok(())
         }
@@ -59,16 +57,101 @@ pub struct AppKeyDetails {
     pub(crate) aid: ApplicationKeyIdentifier,
     pub(crate) key: AppKey,
     pub(crate) index: AppKeyIndex,
+    /// During a key refresh, the key not used for transmission
+    /// but still accepted on receive.
+    pub(crate) alternate: Option<AlternateAppKey>,
 }
 
 impl AppKeyDetails {
+    pub(crate) fn new(index: AppKeyIndex, key: AppKey) -> Result<Self, Status> {
+        let aid = crypto::k4(key.as_ref())
+            .map_err(|_| Status::UnspecifiedError)?
+            .into();
+        Ok(Self {
+            aid,
+            key,
+            index,
+            alternate: None,
+        })
+    }
+
+    pub(crate) fn matches_aid(&self, aid: &ApplicationKeyIdentifier) -> bool {
+        self.aid == *aid || matches!(&self.alternate, Some(alternate) if alternate.aid == *aid)
+    }
+
+    /// Every key, current or alternate, identified by the aid.
+    pub(crate) fn keys_by_aid<'m>(
+        &'m self,
+        aid: &'m ApplicationKeyIdentifier,
+    ) -> impl Iterator<Item = &'m AppKey> + 'm {
+        core::iter::once((&self.aid, &self.key))
+            .chain(self.alternate.iter().map(|e| (&e.aid, &e.key)))
+            .filter(move |(candidate, _)| *candidate == aid)
+            .map(|(_, key)| key)
+    }
+
+    /// Key Refresh phase 1: hold the new key alongside the current key.
+    pub(crate) fn update(&mut self, key: AppKey) -> Result<(), Status> {
+        match self.alternate {
+            Some(alternate) if alternate.key == key => Ok(()),
+            Some(_) => Err(Status::CannotUpdate),
+            None => {
+                let aid = crypto::k4(key.as_ref())
+                    .map_err(|_| Status::UnspecifiedError)?
+                    .into();
+                self.alternate.replace(AlternateAppKey { aid, key });
+                Ok(())
+            }
+        }
+    }
+
+    /// Key Refresh phase 2: transmit using the new key, keeping the old key as alternate.
+    pub(crate) fn use_new_key(&mut self) {
+        if let Some(alternate) = self.alternate.take() {
+            self.alternate.replace(AlternateAppKey {
+                aid: self.aid,
+                key: self.key,
+            });
+            self.aid = alternate.aid;
+            self.key = alternate.key;
+        }
+    }
+
+    pub(crate) fn revoke_old_key(&mut self) {
+        self.alternate.take();
+    }
+
     #[cfg(feature = "defmt")]
     pub(crate) fn display_configuration(&self) {
-        info!("  {}: {} [aid={}]", self.index, self.key, self.aid)
+        info!("  {}: {} [aid={}]", self.index, self.key, self.aid);
+        if let Some(alternate) = &self.alternate {
+            info!(
+                "     {} [aid={}] (key refresh)",
+                alternate.key, alternate.aid
+            );
+        }
+    }
+}
+
+impl From<v0::AppKeyDetails> for AppKeyDetails {
+    fn from(details: v0::AppKeyDetails) -> Self {
+        Self {
+            aid: details.aid,
+            key: details.key,
+            index: details.index,
+            alternate: None,
+        }
     }
 }
 
-#[derive(Serialize, Deserialize, Copy, Clone, Default)]
+#[derive(Serialize, Deserialize, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlternateAppKey {
+    pub(crate) aid: ApplicationKeyIdentifier,
+    pub(crate) key: AppKey,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Default, PartialEq)]
 pub struct AppKey([u8; 16]);
 
 impl AsRef<[u8; 16]> for AppKey {
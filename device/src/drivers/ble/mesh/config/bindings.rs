@@ -95,6 +95,10 @@ impl Bindings {
             Err(Status::InvalidBinding)
         }
     }
+
+    pub(crate) fn unbind_app_key(&mut self, app_key_index: &AppKeyIndex) {
+        self.bindings.retain(|e| e.app_key_index != *app_key_index);
+    }
 }
 
 #[derive(Serialize, Deserialize, Copy, Clone)]
@@ -1,14 +1,14 @@
-use crate::drivers::ble::mesh::address::Address;
+use crate::drivers::ble::mesh::address::{Address, UnicastAddress};
 use crate::drivers::ble::mesh::composition::{Composition, ElementDescriptor, Location};
 use crate::drivers::ble::mesh::config::Configuration;
 use crate::drivers::ble::mesh::driver::DeviceError;
 use crate::drivers::ble::mesh::model::foundation::configuration::CONFIGURATION_SERVER;
-use crate::drivers::ble::mesh::storage::{Payload, Storage};
+use crate::drivers::ble::mesh::model::foundation::health::HEALTH_SERVER;
+use crate::drivers::ble::mesh::storage::Storage;
 use atomic_polyfill::{AtomicBool, Ordering};
 use core::cell::Ref;
 use core::cell::RefCell;
 use heapless::Vec;
-use postcard::{from_bytes, to_slice};
 use rand_core::{CryptoRng, RngCore};
 
 pub(crate) const SEQUENCE_THRESHOLD: u32 = 100;
@@ -30,9 +30,12 @@ impl<S: Storage> ConfigurationManager<S> {
 
         let mut models = Vec::new();
         models.push(CONFIGURATION_SERVER).ok();
-        models
-            .extend_from_slice(&composition.elements[0].models)
-            .ok();
+        models.push(HEALTH_SERVER).ok();
+        for model in composition.elements[0].models.iter() {
+            if *model != HEALTH_SERVER {
+                models.push(*model).ok();
+            }
+        }
         composition.elements[0].models = models;
 
         let me = Self {
@@ -85,8 +88,7 @@ impl<S: Storage> ConfigurationManager<S> {
                     Err(DeviceError::StorageInitialization)
                 }
                 Some(payload) => {
-                    let mut config: Configuration =
-                        from_bytes(&payload.payload).map_err(|_| DeviceError::Serialization)?;
+                    let mut config = Configuration::from_payload(&payload)?;
                     if config.validate(rng) {
                         // we initialized some things that we should stuff away.
                         self.runtime_seq.replace(config.seq);
@@ -170,10 +172,7 @@ impl<S: Storage> ConfigurationManager<S> {
     }
 
     async fn store(&self) -> Result<(), DeviceError> {
-        let mut payload = [0; 512];
-        let config = self.config.borrow();
-        to_slice(&*config, &mut payload)?;
-        let payload = Payload { payload };
+        let payload = self.config.borrow().to_payload()?;
         self.storage
             .borrow_mut()
             .store(&payload)
@@ -196,6 +195,33 @@ impl<S: Storage> ConfigurationManager<S> {
         Ok(seq)
     }
 
+    /// Whether a message is a replay, according to the replay protection list.
+    pub(crate) fn is_replay(&self, iv_index: u32, seq: u32, src: UnicastAddress) -> bool {
+        self.configuration()
+            .replay_cache()
+            .is_replay(iv_index, seq, src)
+    }
+
+    /// Record a message accepted for this node in the replay protection list.
+    ///
+    /// The list is stored right away, so that the message cannot be replayed
+    /// once the node restarts.
+    pub(crate) async fn accepted(
+        &self,
+        iv_index: u32,
+        seq: u32,
+        src: UnicastAddress,
+    ) -> Result<(), DeviceError> {
+        let capacity = self.composition.crpl() as usize;
+        self.update_configuration(|config| {
+            config
+                .replay_cache_mut()
+                .accept(capacity, iv_index, seq, src);
+            Ok(())
+        })
+        .await
+    }
+
     pub(crate) fn reset(&self) {
         self.force_reset.store(true, Ordering::SeqCst);
     }
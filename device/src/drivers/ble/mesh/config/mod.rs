@@ -4,15 +4,23 @@ pub(crate) mod configuration_manager;
 pub(crate) mod device_keys;
 pub(crate) mod foundation_models;
 pub(crate) mod network;
+pub(crate) mod provisioned_devices;
 pub(crate) mod publications;
+pub(crate) mod replay_cache;
 pub(crate) mod subcriptions;
+mod v0;
 
 use crate::drivers::ble::mesh::config::configuration_manager::SEQUENCE_THRESHOLD;
 use crate::drivers::ble::mesh::config::device_keys::DeviceKeys;
 use crate::drivers::ble::mesh::config::foundation_models::FoundationModels;
 use crate::drivers::ble::mesh::config::network::Network;
+use crate::drivers::ble::mesh::config::provisioned_devices::ProvisionedDevices;
+use crate::drivers::ble::mesh::config::replay_cache::ReplayCache;
 use crate::drivers::ble::mesh::device::Uuid;
+use crate::drivers::ble::mesh::driver::DeviceError;
+use crate::drivers::ble::mesh::storage::Payload;
 use p256::SecretKey;
+use postcard::{from_bytes, to_slice};
 use rand_core::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
 
@@ -21,6 +29,19 @@ pub use crate::drivers::ble::mesh::config::foundation_models::ConfigurationModel
 #[cfg(feature = "defmt")]
 use crate::drivers::ble::mesh::composition::Composition;
 
+/// Version of the stored `Configuration`.
+///
+/// Fields appended to `Configuration` must default to their all-zero encoding,
+/// so that a configuration stored by an earlier version reads back with the new
+/// fields defaulted. Any other change to the layout, such as a field added to a
+/// nested struct, needs a frozen copy of the previous layout to convert from,
+/// as for version 0. Bump the version whenever the layout changes.
+///
+/// * 0: stored before it was versioned.
+/// * 1: key refresh state, and the replay protection list.
+/// * 2: the devices provisioned by this node.
+pub(crate) const CONFIGURATION_VERSION: u8 = 2;
+
 #[derive(Serialize, Deserialize, Clone, Default)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Configuration {
@@ -29,9 +50,46 @@ pub struct Configuration {
     device_keys: DeviceKeys,
     network: Option<Network>,
     foundation_models: FoundationModels,
+    replay_cache: ReplayCache,
+    provisioned_devices: ProvisionedDevices,
+}
+
+impl From<v0::Configuration> for Configuration {
+    fn from(config: v0::Configuration) -> Self {
+        Self {
+            seq: config.seq,
+            uuid: config.uuid,
+            device_keys: config.device_keys,
+            network: config.network.map(Into::into),
+            foundation_models: config.foundation_models,
+            replay_cache: Default::default(),
+            provisioned_devices: Default::default(),
+        }
+    }
 }
 
 impl Configuration {
+    pub(crate) fn from_payload(payload: &Payload) -> Result<Self, DeviceError> {
+        if payload.version() > CONFIGURATION_VERSION {
+            // stored by a newer firmware
+            return Err(DeviceError::Serialization);
+        }
+        if payload.version() == 0 {
+            let config: v0::Configuration =
+                from_bytes(payload.padded()).map_err(|_| DeviceError::Serialization)?;
+            return Ok(config.into());
+        }
+        // fields missing from older versions read from the zeroed padding.
+        from_bytes(payload.padded()).map_err(|_| DeviceError::Serialization)
+    }
+
+    pub(crate) fn to_payload(&self) -> Result<Payload, DeviceError> {
+        let mut payload = Payload::new(CONFIGURATION_VERSION);
+        let len = to_slice(self, payload.buffer_mut())?.len();
+        payload.set_len(len);
+        Ok(payload)
+    }
+
     fn validate<R: CryptoRng + RngCore>(&mut self, rng: &mut R) -> bool {
         let mut changed = false;
 
@@ -100,4 +158,164 @@ impl Configuration {
     pub fn foundation_models_mut(&mut self) -> &mut FoundationModels {
         &mut self.foundation_models
     }
+
+    pub(crate) fn replay_cache(&self) -> &ReplayCache {
+        &self.replay_cache
+    }
+
+    pub(crate) fn replay_cache_mut(&mut self) -> &mut ReplayCache {
+        &mut self.replay_cache
+    }
+
+    pub fn provisioned_devices(&self) -> &ProvisionedDevices {
+        &self.provisioned_devices
+    }
+
+    pub(crate) fn provisioned_devices_mut(&mut self) -> &mut ProvisionedDevices {
+        &mut self.provisioned_devices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drivers::ble::mesh::address::UnicastAddress;
+    use crate::drivers::ble::mesh::model::foundation::configuration::key_refresh_phase::KeyRefreshPhase;
+    use crate::drivers::ble::mesh::model::foundation::configuration::{AppKeyIndex, NetKeyIndex};
+    use heapless::Vec;
+
+    #[test]
+    fn test_payload_round_trip() {
+        let mut config = Configuration::default();
+        config.seq = 1234;
+        let payload = config.to_payload().unwrap();
+        assert_eq!(CONFIGURATION_VERSION, payload.version());
+
+        let restored = Configuration::from_payload(&payload).unwrap();
+        assert_eq!(1234, restored.seq);
+    }
+
+    // A provisioned node, with an application key bound to a model of its
+    // primary element, as stored before the configuration was versioned.
+    const BASELINE_HEAD: &[u8] = &[
+        // seq
+        0xC8, 0x01, //
+        // uuid
+        0x01, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11,
+        0x11, 0x11, //
+        // private key
+        0x01, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22,
+        0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22,
+        0x22, 0x22, 0x22, //
+        // shared secret
+        0x00, //
+        // device key
+        0x01, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33,
+        0x33, 0x33, //
+        // network, with a single network key
+        0x01, 0x01, //
+        0x44, 0x44, 0x44, 0x44, 0x44, 0x44, 0x44, 0x44, 0x44, 0x44, 0x44, 0x44, 0x44, 0x44, 0x44,
+        0x44, //
+        // key index, nid, encryption and privacy keys
+        0x00, 0x68, //
+        0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+        0x66, //
+        0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77, 0x77,
+        0x77, //
+        // a single app key: aid, key and index 1
+        0x01, 0x26, //
+        0x88, 0x88, 0x88, 0x88, 0x88, 0x88, 0x88, 0x88, 0x88, 0x88, 0x88, 0x88, 0x88, 0x88, 0x88,
+        0x88, //
+        0x01,
+    ];
+
+    const BASELINE_BINDINGS: &[u8] = &[
+        // generic onoff server of element 0x0002 bound to app key 1
+        0x01, 0x00, 0x80, 0x20, 0x02, 0x01, //
+        // publications
+        0x00,
+    ];
+
+    const BASELINE_TAIL: &[u8] = &[
+        // iv update flag, iv index, unicast address, subscriptions
+        0x00, 0x00, 0x02, 0x00, //
+        // configuration model
+        0x01, 0x7F, 0x00, 0x02, 0x01,
+    ];
+
+    fn extend_tail(data: &mut Vec<u8, 512>) {
+        data.extend_from_slice(BASELINE_TAIL).unwrap();
+        // relay, relay retransmit count and interval steps
+        #[cfg(feature = "ble-mesh-relay")]
+        data.extend_from_slice(&[0x01, 0x02, 0x03]).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_unversioned_payload() {
+        let mut data: Vec<u8, 512> = Vec::new();
+        data.extend_from_slice(BASELINE_HEAD).unwrap();
+        data.extend_from_slice(BASELINE_BINDINGS).unwrap();
+        extend_tail(&mut data);
+        let baseline = Payload::from_slice(0, &data).unwrap();
+
+        let restored = Configuration::from_payload(&baseline).unwrap();
+        assert_eq!(200, restored.seq);
+        assert!(restored.uuid == Some(Uuid([0x11; 16])));
+        assert_eq!(
+            [0x33; 16],
+            *restored.device_keys().device_key().unwrap().as_ref()
+        );
+        assert!(!restored
+            .replay_cache()
+            .is_replay(0, 0, UnicastAddress(0x0002)));
+        assert!(restored.provisioned_devices().next_address().is_none());
+
+        let network = restored.network().as_ref().unwrap();
+        assert_eq!(UnicastAddress(0x0002), *network.unicast_address());
+        let details = network.find_by_app_key_index(&AppKeyIndex::new(1)).unwrap();
+        assert_eq!(NetKeyIndex::new(0), details.key_index());
+        assert_eq!(KeyRefreshPhase::Normal, details.key_refresh_phase());
+        assert!(details.matches_nid(0x68));
+        let app_key = details.find_app_key_by_index(&AppKeyIndex::new(1)).unwrap();
+        assert_eq!([0x88; 16], *app_key.key.as_ref());
+        assert!(app_key.alternate.is_none());
+
+        // stored again in the current layout, with the new fields in place.
+        let mut expected: Vec<u8, 512> = Vec::new();
+        expected.extend_from_slice(BASELINE_HEAD).unwrap();
+        // no alternate app key
+        expected.push(0x00).unwrap();
+        expected.extend_from_slice(BASELINE_BINDINGS).unwrap();
+        // key refresh phase, no alternate network key
+        expected.extend_from_slice(&[0x00, 0x00]).unwrap();
+        extend_tail(&mut expected);
+        // replay protection list, provisioned devices
+        expected
+            .extend_from_slice(&[0x00, 0x00, 0x00, 0x00])
+            .unwrap();
+        let payload = restored.to_payload().unwrap();
+        assert_eq!(CONFIGURATION_VERSION, payload.version());
+        assert_eq!(&expected[..], payload.as_slice());
+    }
+
+    #[test]
+    fn test_migrate_appended_fields() {
+        let mut config = Configuration::default();
+        config.seq = 1234;
+        let payload = config.to_payload().unwrap();
+
+        // stored before the provisioned devices were appended.
+        let data = payload.as_slice();
+        let earlier = Payload::from_slice(1, &data[0..data.len() - 2]).unwrap();
+
+        let restored = Configuration::from_payload(&earlier).unwrap();
+        assert_eq!(1234, restored.seq);
+        assert!(restored.provisioned_devices().next_address().is_none());
+    }
+
+    #[test]
+    fn test_reject_newer_payload() {
+        let payload = Payload::from_slice(CONFIGURATION_VERSION + 1, &[]).unwrap();
+        assert!(Configuration::from_payload(&payload).is_err());
+    }
 }
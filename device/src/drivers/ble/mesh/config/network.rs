@@ -1,18 +1,24 @@
 use crate::drivers::ble::mesh::address::UnicastAddress;
 use crate::drivers::ble::mesh::app::ApplicationKeyIdentifier;
+use crate::drivers::ble::mesh::beacon::{Flags, SecureNetworkBeacon};
 #[cfg(feature = "defmt")]
 use crate::drivers::ble::mesh::composition::Composition;
-use crate::drivers::ble::mesh::config::app_keys::AppKeyDetails;
+use crate::drivers::ble::mesh::config::app_keys::{AppKey, AppKeyDetails};
 use crate::drivers::ble::mesh::config::bindings::Bindings;
 use crate::drivers::ble::mesh::config::publications::{Publication, Publications};
 use crate::drivers::ble::mesh::config::subcriptions::Subscriptions;
-use crate::drivers::ble::mesh::crypto::k3;
+use crate::drivers::ble::mesh::config::v0;
+use crate::drivers::ble::mesh::crypto::{beacon_key, identity_key, k2, k3};
 use crate::drivers::ble::mesh::driver::node::NetworkId;
 use crate::drivers::ble::mesh::driver::DeviceError;
+use crate::drivers::ble::mesh::model::foundation::configuration::key_refresh_phase::{
+    KeyRefreshPhase, KeyRefreshTransition,
+};
 use crate::drivers::ble::mesh::model::foundation::configuration::{AppKeyIndex, NetKeyIndex};
 use crate::drivers::ble::mesh::model::{ModelIdentifier, Status};
-use crate::drivers::ble::mesh::provisioning::IVUpdateFlag;
-use crate::drivers::ble::mesh::{crypto, InsufficientBuffer};
+use crate::drivers::ble::mesh::provisioning::{IVUpdateFlag, KeyRefreshFlag, ProvisioningData};
+use crate::drivers::ble::mesh::InsufficientBuffer;
+use cmac::crypto_mac::InvalidKeyLength;
 use core::slice::Iter;
 use heapless::Vec;
 use serde::{Deserialize, Serialize};
@@ -27,6 +33,18 @@ pub struct Network {
     subscriptions: Subscriptions,
 }
 
+impl From<v0::Network> for Network {
+    fn from(network: v0::Network) -> Self {
+        Self {
+            networks: network.networks.into(),
+            iv_update_flag: network.iv_update_flag,
+            iv_index: network.iv_index,
+            unicast_address: network.unicast_address,
+            subscriptions: network.subscriptions,
+        }
+    }
+}
+
 impl Network {
     pub(crate) fn new(
         primary_network_details: NetworkDetails,
@@ -103,7 +121,7 @@ impl Network {
     pub(crate) fn find_by_nid(
         &self,
         nid: u8,
-    ) -> Result<Vec<NetworkDetails, 10>, InsufficientBuffer> {
+    ) -> Result<Vec<NetworkKeyHandle, 10>, InsufficientBuffer> {
         self.networks.find_by_nid(nid)
     }
 
@@ -111,6 +129,51 @@ impl Network {
         self.networks.iter()
     }
 
+    pub(crate) fn net_key_indexes(&self) -> Vec<NetKeyIndex, 10> {
+        self.networks.iter().map(|e| e.key_index).collect()
+    }
+
+    pub(crate) fn add_network_key(
+        &mut self,
+        net_key_index: NetKeyIndex,
+        network_key: [u8; 16],
+    ) -> Result<(), Status> {
+        self.networks.add(net_key_index, network_key)
+    }
+
+    pub(crate) fn delete_network_key(
+        &mut self,
+        net_key_index: &NetKeyIndex,
+        received_on: &NetKeyIndex,
+    ) -> Result<(), Status> {
+        self.networks.delete(net_key_index, received_on)
+    }
+
+    /// Determine whether an authenticated secure network beacon
+    /// should advance the key refresh procedure of one of the networks.
+    pub(crate) fn key_refresh_transition(
+        &self,
+        beacon: &SecureNetworkBeacon,
+    ) -> Result<Option<(NetKeyIndex, KeyRefreshTransition)>, DeviceError> {
+        for network in self.networks.iter() {
+            if let Some(new_key) = network.new_key() {
+                if k3(&new_key.network_key.0)? != beacon.network_id {
+                    continue;
+                }
+                if !beacon.authenticate(&beacon_key(&new_key.network_key.0)?)? {
+                    continue;
+                }
+                let transition = match (network.key_refresh_phase, beacon.flags.key_refresh) {
+                    (KeyRefreshPhase::Phase1, true) => Some(KeyRefreshTransition::UseNewKeys),
+                    (_, false) => Some(KeyRefreshTransition::RevokeOldKeys),
+                    _ => None,
+                };
+                return Ok(transition.map(|transition| (network.key_index, transition)));
+            }
+        }
+        Ok(None)
+    }
+
     #[cfg(feature = "defmt")]
     pub(crate) fn display_configuration(&self, composition: &Composition) {
         info!("Primary unicast address: {}", self.unicast_address);
@@ -139,6 +202,35 @@ impl Network {
     pub fn unicast_address(&self) -> &UnicastAddress {
         &self.unicast_address
     }
+
+    /// Key refresh phase of the primary subnet.
+    pub(crate) fn key_refresh_phase(&self) -> KeyRefreshPhase {
+        self.networks.networks[0].key_refresh_phase
+    }
+
+    /// Secure network beacon of the primary subnet.
+    pub(crate) fn secure_network_beacon(&self) -> Result<SecureNetworkBeacon, DeviceError> {
+        let iv_update = matches!(self.iv_update_flag, IVUpdateFlag::UpdateActive);
+        self.networks.networks[0].secure_network_beacon(iv_update, self.iv_index)
+    }
+
+    /// Provisioning data admitting another device to the primary subnet.
+    pub(crate) fn provisioning_data(&self, unicast_address: UnicastAddress) -> ProvisioningData {
+        let primary = &self.networks.networks[0];
+        let key_refresh_flag = if primary.key_refresh_phase == KeyRefreshPhase::Phase2 {
+            KeyRefreshFlag::Phase2
+        } else {
+            KeyRefreshFlag::Phase0
+        };
+        ProvisioningData {
+            network_key: primary.network_key.0,
+            key_index: primary.key_index,
+            key_refresh_flag,
+            iv_update_flag: self.iv_update_flag,
+            iv_index: self.iv_index,
+            unicast_address,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -147,6 +239,14 @@ pub struct Networks {
     networks: Vec<NetworkDetails, 1>,
 }
 
+impl From<v0::Networks> for Networks {
+    fn from(networks: v0::Networks) -> Self {
+        Self {
+            networks: networks.networks.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
 impl Networks {
     fn new(primary_network_details: NetworkDetails) -> Self {
         let mut networks = Vec::new();
@@ -221,19 +321,48 @@ impl Networks {
     pub(crate) fn find_by_nid(
         &self,
         nid: u8,
-    ) -> Result<Vec<NetworkDetails, 10>, InsufficientBuffer> {
+    ) -> Result<Vec<NetworkKeyHandle, 10>, InsufficientBuffer> {
         let mut found = Vec::new();
         for network in &self.networks {
-            if network.nid == nid {
-                // todo: remove this clone
-                found
-                    .push(network.clone())
-                    .map_err(|_| InsufficientBuffer)?
+            for key in network.key_handles() {
+                if key.nid == nid {
+                    found.push(key).map_err(|_| InsufficientBuffer)?
+                }
             }
         }
         Ok(found)
     }
 
+    fn add(&mut self, net_key_index: NetKeyIndex, network_key: [u8; 16]) -> Result<(), Status> {
+        let network_key = NetworkKey::from(network_key);
+        if let Ok(network) = self.find_by_index(&net_key_index) {
+            if network.network_key == network_key {
+                Ok(())
+            } else {
+                Err(Status::KeyIndexAlreadyStored)
+            }
+        } else {
+            let key = NetworkKeyHandle::new(network_key, net_key_index)
+                .map_err(|_| Status::UnspecifiedError)?;
+            self.networks
+                .push(key.into())
+                .map_err(|_| Status::InsufficientResources)
+        }
+    }
+
+    fn delete(
+        &mut self,
+        net_key_index: &NetKeyIndex,
+        received_on: &NetKeyIndex,
+    ) -> Result<(), Status> {
+        if net_key_index == received_on {
+            Err(Status::CannotRemove)
+        } else {
+            self.networks.retain(|e| e.key_index != *net_key_index);
+            Ok(())
+        }
+    }
+
     pub(crate) fn find_by_index(
         &self,
         net_key_index: &NetKeyIndex,
@@ -285,7 +414,7 @@ impl Networks {
     }
 }
 
-#[derive(Serialize, Deserialize, Copy, Clone, Default, Debug)]
+#[derive(Serialize, Deserialize, Copy, Clone, Default, PartialEq, Debug)]
 pub struct NetworkKey([u8; 16]);
 
 impl NetworkKey {
@@ -322,6 +451,27 @@ pub struct NetworkDetails {
     app_keys: Vec<AppKeyDetails, 1>,
     bindings: Bindings,
     publications: Publications,
+    key_refresh_phase: KeyRefreshPhase,
+    /// During a key refresh, the key not used for transmission
+    /// but still accepted on receive.
+    alternate: Option<NetworkKeyHandle>,
+}
+
+impl From<v0::NetworkDetails> for NetworkDetails {
+    fn from(details: v0::NetworkDetails) -> Self {
+        Self {
+            network_key: details.network_key,
+            key_index: details.key_index,
+            nid: details.nid,
+            encryption_key: details.encryption_key,
+            privacy_key: details.privacy_key,
+            app_keys: details.app_keys.into_iter().map(Into::into).collect(),
+            bindings: details.bindings,
+            publications: details.publications,
+            key_refresh_phase: KeyRefreshPhase::Normal,
+            alternate: None,
+        }
+    }
 }
 
 impl NetworkDetails {
@@ -341,11 +491,151 @@ impl NetworkDetails {
             app_keys: Default::default(),
             bindings: Default::default(),
             publications: Default::default(),
+            key_refresh_phase: Default::default(),
+            alternate: None,
         }
     }
 
     pub fn matches_nid(&self, nid: u8) -> bool {
-        self.nid == nid
+        self.nid == nid || matches!(&self.alternate, Some(alternate) if alternate.nid == nid)
+    }
+
+    pub(crate) fn key_index(&self) -> NetKeyIndex {
+        self.key_index
+    }
+
+    pub(crate) fn key_refresh_phase(&self) -> KeyRefreshPhase {
+        self.key_refresh_phase
+    }
+
+    /// Identity key for Node Identity advertising, derived from the transmit key.
+    pub(crate) fn identity_key(&self) -> Result<[u8; 16], DeviceError> {
+        Ok(identity_key(&self.network_key.0)?)
+    }
+
+    /// Secure network beacon authenticated with the transmit key, flagging
+    /// the key refresh once the new keys are in use.
+    pub(crate) fn secure_network_beacon(
+        &self,
+        iv_update: bool,
+        iv_index: u32,
+    ) -> Result<SecureNetworkBeacon, DeviceError> {
+        let flags = Flags {
+            key_refresh: self.key_refresh_phase == KeyRefreshPhase::Phase2,
+            iv_update,
+        };
+        Ok(SecureNetworkBeacon::new(
+            flags,
+            k3(&self.network_key.0)?,
+            iv_index,
+            &beacon_key(&self.network_key.0)?,
+        )?)
+    }
+
+    /// Every key accepted on receive, starting with the key used for transmission.
+    pub(crate) fn key_handles(&self) -> Vec<NetworkKeyHandle, 2> {
+        let mut handles = Vec::new();
+        handles.push(NetworkKeyHandle::from(self)).ok();
+        if let Some(alternate) = self.alternate {
+            handles.push(alternate).ok();
+        }
+        handles
+    }
+
+    /// The key being distributed by an in-progress key refresh.
+    pub(crate) fn new_key(&self) -> Option<NetworkKeyHandle> {
+        match self.key_refresh_phase {
+            KeyRefreshPhase::Normal => None,
+            KeyRefreshPhase::Phase1 => self.alternate,
+            KeyRefreshPhase::Phase2 => Some(NetworkKeyHandle::from(self)),
+        }
+    }
+
+    /// Config NetKey Update, entering key refresh phase 1.
+    pub(crate) fn update_network_key(&mut self, network_key: [u8; 16]) -> Result<(), Status> {
+        let network_key = NetworkKey::from(network_key);
+        match self.key_refresh_phase {
+            KeyRefreshPhase::Normal => {
+                let alternate = NetworkKeyHandle::new(network_key, self.key_index)
+                    .map_err(|_| Status::UnspecifiedError)?;
+                self.alternate.replace(alternate);
+                self.key_refresh_phase = KeyRefreshPhase::Phase1;
+                Ok(())
+            }
+            KeyRefreshPhase::Phase1 if matches!(self.alternate, Some(alternate) if alternate.network_key == network_key) => {
+                Ok(())
+            }
+            _ => Err(Status::CannotUpdate),
+        }
+    }
+
+    /// Config AppKey Update, only allowed while the network is in key refresh phase 1.
+    pub(crate) fn update_app_key(
+        &mut self,
+        app_key_index: &AppKeyIndex,
+        app_key: [u8; 16],
+    ) -> Result<(), Status> {
+        let phase = self.key_refresh_phase;
+        let app_key_details = self
+            .app_keys
+            .iter_mut()
+            .find(|e| e.index == *app_key_index)
+            .ok_or(Status::InvalidAppKeyIndex)?;
+        if let KeyRefreshPhase::Phase1 = phase {
+            app_key_details.update(AppKey::from(app_key))
+        } else {
+            Err(Status::CannotUpdate)
+        }
+    }
+
+    pub(crate) fn set_key_refresh_phase(
+        &mut self,
+        transition: KeyRefreshTransition,
+    ) -> Result<(), Status> {
+        match (self.key_refresh_phase, transition) {
+            (KeyRefreshPhase::Normal, KeyRefreshTransition::UseNewKeys) => {
+                Err(Status::CannotUpdate)
+            }
+            (KeyRefreshPhase::Normal, KeyRefreshTransition::RevokeOldKeys) => Ok(()),
+            (KeyRefreshPhase::Phase1, KeyRefreshTransition::UseNewKeys) => {
+                self.use_new_keys();
+                self.key_refresh_phase = KeyRefreshPhase::Phase2;
+                Ok(())
+            }
+            (KeyRefreshPhase::Phase1, KeyRefreshTransition::RevokeOldKeys) => {
+                self.use_new_keys();
+                self.revoke_old_keys();
+                self.key_refresh_phase = KeyRefreshPhase::Normal;
+                Ok(())
+            }
+            (KeyRefreshPhase::Phase2, KeyRefreshTransition::UseNewKeys) => Ok(()),
+            (KeyRefreshPhase::Phase2, KeyRefreshTransition::RevokeOldKeys) => {
+                self.revoke_old_keys();
+                self.key_refresh_phase = KeyRefreshPhase::Normal;
+                Ok(())
+            }
+        }
+    }
+
+    fn use_new_keys(&mut self) {
+        if let Some(new_key) = self.alternate.take() {
+            let old_key = NetworkKeyHandle::from(&*self);
+            self.alternate.replace(old_key);
+            self.network_key = new_key.network_key;
+            self.nid = new_key.nid;
+            self.encryption_key = new_key.encryption_key;
+            self.privacy_key = new_key.privacy_key;
+        }
+        for app_key in self.app_keys.iter_mut() {
+            app_key.use_new_key();
+        }
+    }
+
+    fn revoke_old_keys(&mut self) {
+        self.alternate.take();
+        for app_key in self.app_keys.iter_mut() {
+            app_key.revoke_old_key();
+        }
     }
 
     pub(crate) fn find_publication(
@@ -368,7 +658,7 @@ impl NetworkDetails {
         &self,
         aid: &ApplicationKeyIdentifier,
     ) -> Option<&AppKeyDetails> {
-        self.app_keys.iter().find(|e| e.aid == *aid)
+        self.app_keys.iter().find(|e| e.matches_aid(aid))
     }
 
     pub(crate) fn find_app_key_by_index(
@@ -427,6 +717,12 @@ impl NetworkDetails {
             "  {}: {} [nid={}]",
             self.key_index, self.network_key, self.nid
         );
+        if let Some(alternate) = &self.alternate {
+            info!(
+                "     {} [nid={}] (key refresh {})",
+                alternate.network_key, alternate.nid, self.key_refresh_phase
+            );
+        }
         info!("Application Keys:");
         for app_key in &self.app_keys {
             app_key.display_configuration();
@@ -441,18 +737,21 @@ impl NetworkDetails {
         if let Some(_) = self.app_keys.iter().find(|e| e.index == app_key_index) {
             Err(Status::KeyIndexAlreadyStored)
         } else {
-            let aid = crypto::k4(&app_key).map_err(|_| Status::UnspecifiedError)?;
             self.app_keys
-                .push(AppKeyDetails {
-                    aid: aid.into(),
-                    key: app_key.into(),
-                    index: app_key_index,
-                })
+                .push(AppKeyDetails::new(app_key_index, app_key.into())?)
                 .map_err(|_| Status::InsufficientResources)?;
             Ok(())
         }
     }
 
+    /// Delete an application key, along with any bindings
+    /// and publications referencing it.
+    pub(crate) fn delete_app_key(&mut self, app_key_index: &AppKeyIndex) {
+        self.app_keys.retain(|e| e.index != *app_key_index);
+        self.bindings.unbind_app_key(app_key_index);
+        self.publications.remove_app_key(app_key_index);
+    }
+
     fn app_key_indexes(&self) -> Vec<AppKeyIndex, 10> {
         self.app_keys.iter().map(|e| e.index).collect()
     }
@@ -475,6 +774,34 @@ pub struct NetworkKeyHandle {
     pub(crate) privacy_key: [u8; 16],
 }
 
+impl NetworkKeyHandle {
+    pub(crate) fn new(
+        network_key: NetworkKey,
+        key_index: NetKeyIndex,
+    ) -> Result<Self, InvalidKeyLength> {
+        let (nid, encryption_key, privacy_key) = k2(&network_key.0, &[0x00])?;
+        Ok(Self {
+            network_key,
+            key_index,
+            nid,
+            encryption_key,
+            privacy_key,
+        })
+    }
+}
+
+impl From<NetworkKeyHandle> for NetworkDetails {
+    fn from(key: NetworkKeyHandle) -> Self {
+        NetworkDetails::new(
+            key.network_key,
+            key.key_index,
+            key.nid,
+            key.encryption_key,
+            key.privacy_key,
+        )
+    }
+}
+
 impl From<NetworkDetails> for NetworkKeyHandle {
     fn from(key: NetworkDetails) -> Self {
         Self {
@@ -498,3 +825,109 @@ impl From<&NetworkDetails> for NetworkKeyHandle {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(key: u8) -> NetworkDetails {
+        NetworkKeyHandle::new(NetworkKey::new([key; 16]), NetKeyIndex::new(0))
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn test_key_refresh_phases() {
+        let mut network = network(0x11);
+        let old_nid = network.nid;
+        let new_nid = self::network(0x22).nid;
+        assert_eq!(KeyRefreshPhase::Normal, network.key_refresh_phase());
+        assert_eq!(None, network.new_key().map(|key| key.nid));
+        assert_eq!(
+            Err(Status::CannotUpdate),
+            network.set_key_refresh_phase(KeyRefreshTransition::UseNewKeys)
+        );
+
+        // phase 1: new key distributed, old key still transmitting
+        network.update_network_key([0x22; 16]).unwrap();
+        assert_eq!(KeyRefreshPhase::Phase1, network.key_refresh_phase());
+        assert_eq!(old_nid, network.nid);
+        assert!(network.matches_nid(old_nid));
+        assert!(network.matches_nid(new_nid));
+        assert_eq!(Some(new_nid), network.new_key().map(|key| key.nid));
+        assert_eq!(Ok(()), network.update_network_key([0x22; 16]));
+        assert_eq!(
+            Err(Status::CannotUpdate),
+            network.update_network_key([0x33; 16])
+        );
+        let beacon = network.secure_network_beacon(false, 0).unwrap();
+        assert!(!beacon.flags.key_refresh);
+
+        // phase 2: new key transmitting, old key still accepted
+        network
+            .set_key_refresh_phase(KeyRefreshTransition::UseNewKeys)
+            .unwrap();
+        assert_eq!(KeyRefreshPhase::Phase2, network.key_refresh_phase());
+        assert_eq!(new_nid, network.nid);
+        assert!(network.matches_nid(old_nid));
+        assert_eq!(2, network.key_handles().len());
+        assert_eq!(
+            Err(Status::CannotUpdate),
+            network.update_network_key([0x22; 16])
+        );
+        let beacon = network.secure_network_beacon(false, 0).unwrap();
+        assert!(beacon.flags.key_refresh);
+        assert!(beacon
+            .authenticate(&beacon_key(&[0x22; 16]).unwrap())
+            .unwrap());
+
+        // phase 3: old key revoked, back to normal operation
+        network
+            .set_key_refresh_phase(KeyRefreshTransition::RevokeOldKeys)
+            .unwrap();
+        assert_eq!(KeyRefreshPhase::Normal, network.key_refresh_phase());
+        assert_eq!(new_nid, network.nid);
+        assert!(!network.matches_nid(old_nid));
+        assert_eq!(1, network.key_handles().len());
+        let beacon = network.secure_network_beacon(false, 0).unwrap();
+        assert!(!beacon.flags.key_refresh);
+    }
+
+    #[test]
+    fn test_key_refresh_beacon_transition() {
+        let mut details = network(0x11);
+        details.update_network_key([0x22; 16]).unwrap();
+        let network = Network::new(
+            details,
+            IVUpdateFlag::NormalOperation,
+            0,
+            UnicastAddress::parse([0x00, 0x01]).unwrap(),
+        );
+
+        let beacon = self::network(0x22).secure_network_beacon(false, 0).unwrap();
+        assert!(matches!(
+            network.key_refresh_transition(&beacon),
+            Ok(Some((_, KeyRefreshTransition::RevokeOldKeys)))
+        ));
+
+        // authenticated with the key being distributed
+        let beacon = SecureNetworkBeacon::new(
+            Flags {
+                key_refresh: true,
+                iv_update: false,
+            },
+            k3(&[0x22; 16]).unwrap(),
+            0,
+            &beacon_key(&[0x22; 16]).unwrap(),
+        )
+        .unwrap();
+        assert!(matches!(
+            network.key_refresh_transition(&beacon),
+            Ok(Some((_, KeyRefreshTransition::UseNewKeys)))
+        ));
+
+        // a beacon of the old key does not advance the procedure
+        let beacon = self::network(0x11).secure_network_beacon(true, 0).unwrap();
+        assert!(matches!(network.key_refresh_transition(&beacon), Ok(None)));
+    }
+}
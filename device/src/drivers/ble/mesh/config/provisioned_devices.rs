@@ -0,0 +1,110 @@
+use crate::drivers::ble::mesh::address::UnicastAddress;
+use crate::drivers::ble::mesh::driver::node::ProvisionedDevice;
+use crate::drivers::ble::mesh::driver::DeviceError;
+use heapless::Vec;
+use serde::{Deserialize, Serialize};
+
+/// Upper bound of the devices a node keeps track of, as a provisioner.
+pub const MAX_PROVISIONED_DEVICES: usize = 8;
+
+/// Devices provisioned by this node, along with their device keys.
+///
+/// Addresses are allocated sequentially, and never reused, even once a device
+/// is forgotten.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ProvisionedDevices {
+    next_address: Option<UnicastAddress>,
+    devices: Vec<ProvisionedDevice, MAX_PROVISIONED_DEVICES>,
+}
+
+impl ProvisionedDevices {
+    /// The first address past the last device provisioned, if any was.
+    pub fn next_address(&self) -> Option<UnicastAddress> {
+        self.next_address
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.devices.is_full()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ProvisionedDevice> {
+        self.devices.iter()
+    }
+
+    /// The device one of whose elements has the given address.
+    pub fn find(&self, addr: &UnicastAddress) -> Option<&ProvisionedDevice> {
+        let addr = u16::from(*addr);
+        self.devices.iter().find(|device| {
+            let first = u16::from(device.unicast_address);
+            addr >= first && addr < first + device.number_of_elements as u16
+        })
+    }
+
+    pub(crate) fn add(&mut self, device: ProvisionedDevice) -> Result<(), DeviceError> {
+        // provisioned again, such as after a reset.
+        self.devices.retain(|e| e.uuid != device.uuid);
+        self.devices
+            .push(device)
+            .map_err(|_| DeviceError::InsufficientBuffer)?;
+        self.next_address
+            .replace(device.unicast_address + device.number_of_elements);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drivers::ble::mesh::device::Uuid;
+
+    fn device(uuid: u8, address: u16, number_of_elements: u8) -> ProvisionedDevice {
+        ProvisionedDevice {
+            uuid: Uuid([uuid; 16]),
+            unicast_address: UnicastAddress(address),
+            number_of_elements,
+            device_key: [uuid; 16],
+        }
+    }
+
+    #[test]
+    fn test_add() {
+        let mut devices = ProvisionedDevices::default();
+        assert!(devices.next_address().is_none());
+
+        devices.add(device(1, 0x0002, 2)).unwrap();
+        devices.add(device(2, 0x0004, 1)).unwrap();
+        assert_eq!(Some(UnicastAddress(0x0005)), devices.next_address());
+        assert_eq!(2, devices.iter().count());
+
+        assert_eq!(
+            [1; 16],
+            devices.find(&UnicastAddress(0x0003)).unwrap().device_key
+        );
+        assert_eq!(
+            [2; 16],
+            devices.find(&UnicastAddress(0x0004)).unwrap().device_key
+        );
+        assert!(devices.find(&UnicastAddress(0x0005)).is_none());
+
+        // provisioned again, under a new address.
+        devices.add(device(1, 0x0005, 2)).unwrap();
+        assert_eq!(2, devices.iter().count());
+        assert!(devices.find(&UnicastAddress(0x0002)).is_none());
+        assert_eq!(Some(UnicastAddress(0x0007)), devices.next_address());
+    }
+
+    #[test]
+    fn test_full() {
+        let mut devices = ProvisionedDevices::default();
+        for i in 0..MAX_PROVISIONED_DEVICES {
+            devices.add(device(i as u8, 0x0002 + i as u16, 1)).unwrap();
+        }
+        assert!(devices.is_full());
+        assert!(devices.add(device(0xFF, 0x0100, 1)).is_err());
+        assert_eq!(
+            Some(UnicastAddress(0x0002 + MAX_PROVISIONED_DEVICES as u16)),
+            devices.next_address()
+        );
+    }
+}
@@ -57,10 +57,16 @@ impl Publications {
         publish_retransmit_interval_steps: u8,
         model_identifier: ModelIdentifier,
     ) -> Result<(), Status> {
-        if let Some(publication) = self.publications.iter_mut().find(|e| {
+        if publish_address == Address::Unassigned {
+            // an unassigned publish address disables publication
+            self.publications.retain(|e| {
+                e.element_address != element_address || e.model_identifier != model_identifier
+            });
+        } else if let Some(publication) = self.publications.iter_mut().find(|e| {
             e.element_address == element_address && e.model_identifier == model_identifier
         }) {
             publication.publish_address = publish_address;
+            publication.app_key_index = app_key_index;
             publication.credential_flag = credential_flag;
             publication.publish_ttl = publish_ttl;
             publication.publish_period = publish_period;
@@ -84,6 +90,11 @@ impl Publications {
         }
         Ok(())
     }
+
+    pub(crate) fn remove_app_key(&mut self, app_key_index: &AppKeyIndex) {
+        self.publications
+            .retain(|e| e.app_key_index != *app_key_index);
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -0,0 +1,124 @@
+use crate::drivers::ble::mesh::address::UnicastAddress;
+use heapless::Vec;
+use serde::{Deserialize, Serialize};
+
+/// Upper bound of the replay protection list, as it is stored along the configuration.
+pub const MAX_REPLAY_ENTRIES: usize = 20;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct CacheEntry {
+    seq: u32,
+    src: UnicastAddress,
+}
+
+/// Replay protection list, holding the last sequence number accepted from each source.
+///
+/// Only sources of messages accepted for this node are recorded. Once full,
+/// the least recently used source makes room for a new one.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ReplayCache {
+    iv_index: u32,
+    // least recently used first.
+    entries: Vec<CacheEntry, MAX_REPLAY_ENTRIES>,
+}
+
+impl ReplayCache {
+    /// Whether a message should be discarded as a replay.
+    ///
+    /// The list starts over when the IV index changes.
+    pub fn is_replay(&self, iv_index: u32, seq: u32, src: UnicastAddress) -> bool {
+        iv_index == self.iv_index
+            && self
+                .entries
+                .iter()
+                .any(|entry| entry.src == src && seq <= entry.seq)
+    }
+
+    /// Record a message accepted for this node, keeping up to `capacity` sources.
+    pub fn accept(&mut self, capacity: usize, iv_index: u32, seq: u32, src: UnicastAddress) {
+        if iv_index != self.iv_index {
+            self.iv_index = iv_index;
+            self.entries.clear();
+        }
+
+        if let Some(index) = self.entries.iter().position(|entry| entry.src == src) {
+            self.entries[index..].rotate_left(1);
+            self.entries.pop();
+        } else {
+            while !self.entries.is_empty() && self.entries.len() >= capacity {
+                self.entries.rotate_left(1);
+                self.entries.pop();
+            }
+        }
+        self.entries.push(CacheEntry { seq, src }).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use postcard::{from_bytes, to_slice};
+
+    fn reboot(cache: &ReplayCache) -> ReplayCache {
+        let mut payload = [0; 512];
+        to_slice(cache, &mut payload).unwrap();
+        from_bytes(&payload).unwrap()
+    }
+
+    #[test]
+    fn test_replays_rejected() {
+        let src = UnicastAddress(0x0002);
+        let mut cache = ReplayCache::default();
+        assert!(!cache.is_replay(0, 10, src));
+        cache.accept(4, 0, 10, src);
+        assert!(cache.is_replay(0, 10, src));
+        assert!(cache.is_replay(0, 9, src));
+        assert!(!cache.is_replay(0, 11, src));
+    }
+
+    #[test]
+    fn test_full_list_evicts_least_recently_used() {
+        let mut cache = ReplayCache::default();
+        cache.accept(2, 0, 1, UnicastAddress(0x0002));
+        cache.accept(2, 0, 1, UnicastAddress(0x0003));
+        cache.accept(2, 0, 2, UnicastAddress(0x0002));
+
+        // 0x0003 was used least recently.
+        cache.accept(2, 0, 1, UnicastAddress(0x0004));
+        assert!(cache.is_replay(0, 1, UnicastAddress(0x0004)));
+        assert!(cache.is_replay(0, 2, UnicastAddress(0x0002)));
+        assert!(!cache.is_replay(0, 1, UnicastAddress(0x0003)));
+    }
+
+    #[test]
+    fn test_iv_index_change_resets() {
+        let src = UnicastAddress(0x0002);
+        let mut cache = ReplayCache::default();
+        cache.accept(4, 0, 10, src);
+        assert!(!cache.is_replay(1, 1, src));
+        cache.accept(4, 1, 1, src);
+        assert!(cache.is_replay(1, 1, src));
+    }
+
+    #[test]
+    fn test_replays_rejected_across_reboot() {
+        let src = UnicastAddress(0x0002);
+        let mut cache = ReplayCache::default();
+        for seq in 10..20 {
+            assert!(!cache.is_replay(0, seq, src));
+            cache.accept(4, 0, seq, src);
+        }
+
+        // every accepted message is in the stored list.
+        let rebooted = reboot(&cache);
+        for seq in 0..20 {
+            assert!(rebooted.is_replay(0, seq, src));
+        }
+        assert!(!rebooted.is_replay(0, 20, src));
+
+        // a different IV index after reboot starts over.
+        assert!(!rebooted.is_replay(1, 10, src));
+    }
+}
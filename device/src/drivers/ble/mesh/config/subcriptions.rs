@@ -66,6 +66,42 @@ impl Subscriptions {
         }
     }
 
+    pub(crate) fn delete(
+        &mut self,
+        element_address: UnicastAddress,
+        subscription_address: SubscriptionAddress,
+        model_identifier: ModelIdentifier,
+    ) -> Result<(), Status> {
+        // deleting a subscription which does not exist is not an error
+        self.subscriptions.retain(|e| {
+            e.element_address != element_address
+                || e.subscription_address != subscription_address
+                || e.model_identifier != model_identifier
+        });
+        Ok(())
+    }
+
+    pub(crate) fn delete_all(
+        &mut self,
+        element_address: UnicastAddress,
+        model_identifier: ModelIdentifier,
+    ) -> Result<(), Status> {
+        self.subscriptions.retain(|e| {
+            e.element_address != element_address || e.model_identifier != model_identifier
+        });
+        Ok(())
+    }
+
+    pub(crate) fn overwrite(
+        &mut self,
+        element_address: UnicastAddress,
+        subscription_address: SubscriptionAddress,
+        model_identifier: ModelIdentifier,
+    ) -> Result<(), Status> {
+        self.delete_all(element_address, model_identifier)?;
+        self.add(element_address, subscription_address, model_identifier)
+    }
+
     pub(crate) fn has_subscription(
         &self,
         element_address: &UnicastAddress,
@@ -91,6 +127,13 @@ impl Subscriptions {
                     .find(|e| e.subscription_address == addr)
                     .is_some()
             }
+            Address::Group(addr) => {
+                let addr = SubscriptionAddress::Group(*addr);
+                self.subscriptions
+                    .iter()
+                    .find(|e| e.subscription_address == addr)
+                    .is_some()
+            }
             Address::LabelUuid(addr) => {
                 let addr = SubscriptionAddress::Virtual(*addr);
                 self.subscriptions
@@ -0,0 +1,64 @@
+//! Layout of the configuration stored before it was versioned.
+//!
+//! Only the structs whose layout changed since are frozen here; the types
+//! they hold are shared with the current layout, for as long as they stay
+//! unchanged.
+
+use crate::drivers::ble::mesh::address::UnicastAddress;
+use crate::drivers::ble::mesh::app::ApplicationKeyIdentifier;
+use crate::drivers::ble::mesh::config::app_keys::AppKey;
+use crate::drivers::ble::mesh::config::bindings::Bindings;
+use crate::drivers::ble::mesh::config::device_keys::DeviceKeys;
+use crate::drivers::ble::mesh::config::foundation_models::FoundationModels;
+use crate::drivers::ble::mesh::config::network::NetworkKey;
+use crate::drivers::ble::mesh::config::publications::Publications;
+use crate::drivers::ble::mesh::config::subcriptions::Subscriptions;
+use crate::drivers::ble::mesh::device::Uuid;
+use crate::drivers::ble::mesh::model::foundation::configuration::{AppKeyIndex, NetKeyIndex};
+use crate::drivers::ble::mesh::provisioning::IVUpdateFlag;
+use heapless::Vec;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub(super) struct Configuration {
+    pub(super) seq: u32,
+    pub(super) uuid: Option<Uuid>,
+    pub(super) device_keys: DeviceKeys,
+    pub(super) network: Option<Network>,
+    pub(super) foundation_models: FoundationModels,
+}
+
+#[derive(Deserialize)]
+pub(super) struct Network {
+    pub(super) networks: Networks,
+    pub(super) iv_update_flag: IVUpdateFlag,
+    pub(super) iv_index: u32,
+    pub(super) unicast_address: UnicastAddress,
+    pub(super) subscriptions: Subscriptions,
+}
+
+#[derive(Deserialize)]
+pub(super) struct Networks {
+    pub(super) networks: Vec<NetworkDetails, 1>,
+}
+
+/// Before key refresh.
+#[derive(Deserialize)]
+pub(super) struct NetworkDetails {
+    pub(super) network_key: NetworkKey,
+    pub(super) key_index: NetKeyIndex,
+    pub(super) nid: u8,
+    pub(super) encryption_key: [u8; 16],
+    pub(super) privacy_key: [u8; 16],
+    pub(super) app_keys: Vec<AppKeyDetails, 1>,
+    pub(super) bindings: Bindings,
+    pub(super) publications: Publications,
+}
+
+/// Before key refresh.
+#[derive(Deserialize)]
+pub(super) struct AppKeyDetails {
+    pub(super) aid: ApplicationKeyIdentifier,
+    pub(super) key: AppKey,
+    pub(super) index: AppKeyIndex,
+}
@@ -167,3 +167,62 @@ pub fn k4(n: &[u8]) -> Result<u8, InvalidKeyLength> {
         Err(InvalidKeyLength)
     }
 }
+
+const ID128: [u8; 6] = [b'i', b'd', b'1', b'2', b'8', 0x01];
+
+pub fn beacon_key(n: &[u8; 16]) -> Result<[u8; 16], InvalidKeyLength> {
+    let salt = s1(b"nkbk")?;
+    let result = k1(n, &salt.into_bytes(), &ID128)?.into_bytes();
+    Ok(result.try_into().map_err(|_| InvalidKeyLength)?)
+}
+
+pub fn identity_key(n: &[u8; 16]) -> Result<[u8; 16], InvalidKeyLength> {
+    let salt = s1(b"nkik")?;
+    let result = k1(n, &salt.into_bytes(), &ID128)?.into_bytes();
+    Ok(result.try_into().map_err(|_| InvalidKeyLength)?)
+}
+
+/// Node Identity hash, the last 8 octets of e(IdentityKey, Padding || Random || Address).
+pub fn node_identity_hash(
+    identity_key: &[u8; 16],
+    random: &[u8; 8],
+    address: [u8; 2],
+) -> Result<[u8; 8], InvalidKeyLength> {
+    let mut plaintext = [0; 16];
+    // 48 bits of padding
+    plaintext[6..14].copy_from_slice(random);
+    plaintext[14..16].copy_from_slice(&address);
+    let result = e(identity_key, plaintext)?;
+    Ok(result[8..].try_into().map_err(|_| InvalidKeyLength)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NET_KEY: [u8; 16] = [
+        0x7d, 0xd7, 0x36, 0x4c, 0xd8, 0x42, 0xad, 0x18, 0xc1, 0x7c, 0x2b, 0x82, 0x0c, 0x84, 0xc3,
+        0xd6,
+    ];
+
+    const IDENTITY_KEY: [u8; 16] = [
+        0x84, 0x39, 0x6c, 0x43, 0x5a, 0xc4, 0x85, 0x60, 0xb5, 0x96, 0x53, 0x85, 0x25, 0x3e, 0x21,
+        0x0c,
+    ];
+
+    #[test]
+    fn test_identity_key() {
+        // Mesh Profile sample data, IdentityKey
+        assert_eq!(IDENTITY_KEY, identity_key(&NET_KEY).unwrap());
+    }
+
+    #[test]
+    fn test_node_identity_hash() {
+        // Mesh Profile sample data, Mesh Proxy Service with Node Identity
+        let random = [0x34, 0xae, 0x60, 0x8f, 0xbb, 0xc1, 0xf2, 0xc6];
+        assert_eq!(
+            [0x00, 0x86, 0x17, 0x65, 0xae, 0xfc, 0xc5, 0x7b],
+            node_identity_hash(&IDENTITY_KEY, &random, [0x12, 0x01]).unwrap()
+        );
+    }
+}
@@ -132,3 +132,37 @@ impl Deref for DeviceNonce {
 }
 
 pub struct ProxyNonce([u8; 13]);
+
+impl ProxyNonce {
+    const NONCE_TYPE: u8 = 0x03;
+
+    pub fn new(seq: u32, src: [u8; 2], iv_index: u32) -> Self {
+        let mut nonce = [0; 13];
+        nonce[0] = Self::NONCE_TYPE;
+        // pad
+        nonce[1] = 0x00;
+
+        let seq = seq.to_be_bytes();
+        nonce[2] = seq[1];
+        nonce[3] = seq[2];
+        nonce[4] = seq[3];
+
+        nonce[5] = src[0];
+        nonce[6] = src[1];
+
+        nonce[7] = 0x00;
+        nonce[8] = 0x00;
+
+        let iv_index = iv_index.to_be_bytes();
+        nonce[9] = iv_index[0];
+        nonce[10] = iv_index[1];
+        nonce[11] = iv_index[2];
+        nonce[12] = iv_index[3];
+
+        Self(nonce)
+    }
+
+    pub fn into_bytes(self) -> [u8; 13] {
+        self.0
+    }
+}
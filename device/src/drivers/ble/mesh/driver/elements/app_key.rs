@@ -47,6 +47,44 @@ pub(crate) async fn dispatch<C: PrimaryElementContext>(
             )?)
             .await?;
         }
+        AppKeyMessage::Delete(delete) => {
+            let result = ctx
+                .update_configuration(|config| {
+                    if let Some(network) = config.network_mut() {
+                        if let Ok(bound) = network.find_by_app_key_index(&delete.indexes.app_key())
+                        {
+                            if bound.key_index() != delete.indexes.net_key() {
+                                Err(Status::InvalidBinding)?
+                            }
+                        }
+                        // deleting a key which does not exist is not an error
+                        network
+                            .find_by_net_key_index_mut(&delete.indexes.net_key())?
+                            .delete_app_key(&delete.indexes.app_key());
+                        Ok(())
+                    } else {
+                        Err(DeviceError::NotProvisioned)
+                    }
+                })
+                .await;
+
+            let status = match result {
+                Ok(_) => Status::Success,
+                Err(DeviceError::Status(status)) => status,
+                Err(all_others) => Err(all_others)?,
+            };
+
+            let response = AppKeyStatusMessage {
+                status,
+                indexes: delete.indexes,
+            };
+
+            ctx.transmit(access.create_response(
+                ctx.address().ok_or(DeviceError::NotProvisioned)?,
+                AppKeyMessage::Status(response),
+            )?)
+            .await?;
+        }
         AppKeyMessage::Get(get) => {
             let result = if let Some(networks) = ctx.configuration().network() {
                 if let Ok(network) = networks.find_by_net_key_index(&get.net_key_index) {
@@ -77,25 +115,40 @@ pub(crate) async fn dispatch<C: PrimaryElementContext>(
             )?)
             .await?;
         }
-        /*
-        AppKeyMessage::Delete(delete) => {
-            ctx.network_details(delete.net_key_index)
-                .delete_app_key(delete.app_key_index).await;
-        }
-        AppKeyMessage::Get(get) => {
-            ctx.network_details(get.net_key_index)
-                .get_app_key(get.app_key_index);
-        }
-        AppKeyMessage::List(list) => {
-            let keys = ctx.network_details(delete.net_key_index)
-                .app_keys(delete.app_key_index);
-
-        }
         AppKeyMessage::Update(update) => {
-            let result = ctx.network_details(delete.net_key_index)
-                .update_app_key(update.app_key_index, update.app_key).await;
+            let result = ctx
+                .update_configuration(|config| {
+                    if let Some(network) = config.network_mut() {
+                        let network =
+                            network.find_by_app_key_index_mut(&update.indexes.app_key())?;
+                        if network.key_index() != update.indexes.net_key() {
+                            Err(Status::InvalidBinding)?
+                        }
+                        network.update_app_key(&update.indexes.app_key(), update.app_key)?;
+                        Ok(())
+                    } else {
+                        Err(DeviceError::NotProvisioned)
+                    }
+                })
+                .await;
+
+            let status = match result {
+                Ok(_) => Status::Success,
+                Err(DeviceError::Status(status)) => status,
+                Err(all_others) => Err(all_others)?,
+            };
+
+            let response = AppKeyStatusMessage {
+                status,
+                indexes: update.indexes,
+            };
+
+            ctx.transmit(access.create_response(
+                ctx.address().ok_or(DeviceError::NotProvisioned)?,
+                AppKeyMessage::Status(response),
+            )?)
+            .await?;
         }
-         */
         _ => {
             // not applicable to server role
         }
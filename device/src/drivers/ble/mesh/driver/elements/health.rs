@@ -0,0 +1,83 @@
+use crate::drivers::ble::mesh::driver::elements::PrimaryElementContext;
+use crate::drivers::ble::mesh::driver::DeviceError;
+use crate::drivers::ble::mesh::model::foundation::health::HealthMessage;
+use crate::drivers::ble::mesh::pdu::access::AccessMessage;
+
+/// Returns the new attention timer, in seconds, when it was set.
+pub(crate) async fn dispatch<C: PrimaryElementContext>(
+    ctx: &C,
+    access: &AccessMessage,
+    message: &HealthMessage,
+) -> Result<Option<u8>, DeviceError> {
+    let health = ctx.health();
+    let address = ctx.address().ok_or(DeviceError::NotProvisioned)?;
+    match message {
+        HealthMessage::FaultGet(company_id) if health.handles(*company_id) => {
+            ctx.transmit(
+                access
+                    .create_response(address, HealthMessage::FaultStatus(health.fault_status()))?,
+            )
+            .await?;
+        }
+        HealthMessage::FaultClear(company_id) if health.handles(*company_id) => {
+            health.clear_registered_faults();
+            ctx.transmit(
+                access
+                    .create_response(address, HealthMessage::FaultStatus(health.fault_status()))?,
+            )
+            .await?;
+        }
+        HealthMessage::FaultClearUnacknowledged(company_id) if health.handles(*company_id) => {
+            health.clear_registered_faults();
+        }
+        HealthMessage::FaultTest(test) if health.handles(test.company_id) => {
+            health.test(test.test_id);
+            ctx.transmit(
+                access
+                    .create_response(address, HealthMessage::FaultStatus(health.fault_status()))?,
+            )
+            .await?;
+        }
+        HealthMessage::FaultTestUnacknowledged(test) if health.handles(test.company_id) => {
+            health.test(test.test_id);
+        }
+        HealthMessage::PeriodGet => {
+            ctx.transmit(access.create_response(
+                address,
+                HealthMessage::PeriodStatus(health.fast_period_divisor()),
+            )?)
+            .await?;
+        }
+        HealthMessage::PeriodSet(divisor) => {
+            health.set_fast_period_divisor(*divisor);
+            ctx.transmit(access.create_response(address, HealthMessage::PeriodStatus(*divisor))?)
+                .await?;
+        }
+        HealthMessage::PeriodSetUnacknowledged(divisor) => {
+            health.set_fast_period_divisor(*divisor);
+        }
+        HealthMessage::AttentionGet => {
+            ctx.transmit(
+                access
+                    .create_response(address, HealthMessage::AttentionStatus(health.attention()))?,
+            )
+            .await?;
+        }
+        HealthMessage::AttentionSet(attention) => {
+            health.set_attention(*attention);
+            ctx.transmit(
+                access.create_response(address, HealthMessage::AttentionStatus(*attention))?,
+            )
+            .await?;
+            return Ok(Some(*attention));
+        }
+        HealthMessage::AttentionSetUnacknowledged(attention) => {
+            health.set_attention(*attention);
+            return Ok(Some(*attention));
+        }
+        _ => {
+            // not applicable to server role, or another company's faults
+        }
+    }
+    Ok(None)
+}
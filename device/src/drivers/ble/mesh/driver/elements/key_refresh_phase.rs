@@ -0,0 +1,81 @@
+use crate::drivers::ble::mesh::driver::elements::PrimaryElementContext;
+use crate::drivers::ble::mesh::driver::DeviceError;
+use crate::drivers::ble::mesh::model::foundation::configuration::key_refresh_phase::{
+    KeyRefreshPhase, KeyRefreshPhaseMessage, KeyRefreshPhaseStatusMessage,
+};
+use crate::drivers::ble::mesh::model::foundation::configuration::NetKeyIndex;
+use crate::drivers::ble::mesh::model::Status;
+use crate::drivers::ble::mesh::pdu::access::AccessMessage;
+
+pub(crate) async fn dispatch<C: PrimaryElementContext>(
+    ctx: &C,
+    access: &AccessMessage,
+    message: &KeyRefreshPhaseMessage,
+) -> Result<(), DeviceError> {
+    match message {
+        KeyRefreshPhaseMessage::Get(net_key_index) => {
+            respond(ctx, access, Status::Success, *net_key_index).await?;
+        }
+        KeyRefreshPhaseMessage::Set(set) => {
+            let result = ctx
+                .update_configuration(|config| {
+                    if let Some(network) = config.network_mut() {
+                        network
+                            .find_by_net_key_index_mut(&set.net_key_index)?
+                            .set_key_refresh_phase(set.transition)?;
+                        Ok(())
+                    } else {
+                        Err(DeviceError::NotProvisioned)
+                    }
+                })
+                .await;
+
+            let status = match result {
+                Ok(_) => Status::Success,
+                Err(DeviceError::Status(status)) => status,
+                Err(all_others) => Err(all_others)?,
+            };
+
+            respond(ctx, access, status, set.net_key_index).await?;
+        }
+        _ => {
+            // not applicable to server role
+        }
+    }
+    Ok(())
+}
+
+async fn respond<C: PrimaryElementContext>(
+    ctx: &C,
+    access: &AccessMessage,
+    status: Status,
+    net_key_index: NetKeyIndex,
+) -> Result<(), DeviceError> {
+    let current = ctx
+        .configuration()
+        .network()
+        .ok_or(DeviceError::NotProvisioned)
+        .map(|network| {
+            network
+                .find_by_net_key_index(&net_key_index)
+                .map(|network| network.key_refresh_phase())
+        })?;
+
+    let (status, phase) = match current {
+        Ok(phase) => (status, phase),
+        Err(_) => (Status::InvalidNetKeyIndex, KeyRefreshPhase::Normal),
+    };
+
+    let response = KeyRefreshPhaseStatusMessage {
+        status,
+        net_key_index,
+        phase,
+    };
+
+    ctx.transmit(access.create_response(
+        ctx.address().ok_or(DeviceError::NotProvisioned)?,
+        KeyRefreshPhaseMessage::Status(response),
+    )?)
+    .await?;
+    Ok(())
+}
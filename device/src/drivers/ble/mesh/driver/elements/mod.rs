@@ -2,9 +2,13 @@ mod app_key;
 mod beacon;
 mod composition_data;
 mod default_ttl;
+mod health;
+mod key_refresh_phase;
 mod model_app;
 mod model_publication;
 mod model_subscription;
+mod net_key;
+mod node_identity;
 mod node_reset;
 #[cfg(feature = "ble-mesh-relay")]
 mod relay;
@@ -12,11 +16,18 @@ mod relay;
 use crate::drivers::ble::mesh::address::{Address, UnicastAddress};
 use crate::drivers::ble::mesh::composition::{Composition, ElementsHandler};
 use crate::drivers::ble::mesh::config::Configuration;
+use crate::drivers::ble::mesh::driver::node::configuration_client::{
+    ConfigurationClientChannel, ConfigurationClientContext,
+};
+use crate::drivers::ble::mesh::driver::node::health::{HealthContext, HealthServerState};
 use crate::drivers::ble::mesh::driver::node::outbound::OutboundPublishMessage;
+use crate::drivers::ble::mesh::driver::node::ProvisionedDevice;
 use crate::drivers::ble::mesh::driver::DeviceError;
+use crate::drivers::ble::mesh::model::foundation::configuration::node_identity::NodeIdentityState;
 use crate::drivers::ble::mesh::model::foundation::configuration::{
-    ConfigurationMessage, ConfigurationServer,
+    ConfigurationMessage, ConfigurationServer, NetKeyIndex,
 };
+use crate::drivers::ble::mesh::model::foundation::health::HealthServer;
 use crate::drivers::ble::mesh::model::Message;
 use crate::drivers::ble::mesh::model::Model;
 use crate::drivers::ble::mesh::pdu::access::{AccessMessage, AccessPayload};
@@ -32,6 +43,8 @@ use heapless::Vec;
 pub struct AppElementsContext<'a> {
     pub(crate) sender: ChannelSender<'a, ThreadModeRawMutex, OutboundPublishMessage, 1>,
     pub(crate) access_sender: ChannelSender<'a, ThreadModeRawMutex, AccessMessage, 1>,
+    pub(crate) configuration_client: &'a ConfigurationClientChannel,
+    pub(crate) health: &'a HealthServerState,
     pub(crate) address: UnicastAddress,
 }
 
@@ -48,6 +61,20 @@ impl<'a> AppElementsContext<'a> {
         self.address
     }
 
+    /// Configuration Client for the remote node at `address`, holding `device_key`.
+    pub fn configuration_client(
+        &self,
+        address: UnicastAddress,
+        device_key: [u8; 16],
+    ) -> ConfigurationClientContext<'a> {
+        ConfigurationClientContext::new(self.configuration_client, address, device_key)
+    }
+
+    /// Fault reporting of the Health Server on the primary element.
+    pub fn health(&self) -> HealthContext<'a> {
+        HealthContext::new(self.health, self.sender.clone(), self.address)
+    }
+
     pub async fn respond<M: Message>(
         &self,
         access: &AccessMessage,
@@ -123,6 +150,16 @@ pub trait PrimaryElementContext: ElementContext {
     ) -> Self::UpdateConfigurationFuture<'_, F>;
 
     fn is_local(&self, addr: &UnicastAddress) -> bool;
+
+    fn node_identity(&self, net_key_index: &NetKeyIndex) -> NodeIdentityState;
+
+    fn set_node_identity(
+        &self,
+        net_key_index: &NetKeyIndex,
+        identity: NodeIdentityState,
+    ) -> Result<(), DeviceError>;
+
+    fn health(&self) -> &HealthServerState;
 }
 
 pub struct Elements<'a, E: ElementsHandler<'a>> {
@@ -175,6 +212,26 @@ impl<'a, E: ElementsHandler<'a>> Elements<'a, E> {
                     );
                     return Ok(());
                 }
+                if let Ok(Some(health)) =
+                    HealthServer::parse(message.payload.opcode, &message.payload.parameters)
+                {
+                    if let Some(attention) = self::health::dispatch(ctx, message, &health).await? {
+                        if attention > 0 {
+                            // also let provisioners find the node drawing attention.
+                            let net_key_index = ctx
+                                .configuration()
+                                .network()
+                                .as_ref()
+                                .and_then(|network| network.net_key_indexes().first().copied());
+                            if let Some(net_key_index) = net_key_index {
+                                ctx.set_node_identity(&net_key_index, NodeIdentityState::Running)?;
+                            }
+                        }
+                        self.elements.attention(attention);
+                    }
+                    info!("d<");
+                    return Ok(());
+                }
             }
             let element = &composition.elements[element_index as usize];
             for model in &element.models {
@@ -216,6 +273,14 @@ impl<'a, E: ElementsHandler<'a>> Elements<'a, E> {
     pub(crate) fn connect(&mut self, ctx: AppElementsContext<'a>) {
         self.elements.connect(ctx);
     }
+
+    pub(crate) fn provisioned(&mut self, device: &ProvisionedDevice) {
+        self.elements.provisioned(device);
+    }
+
+    pub(crate) fn attention(&mut self, seconds: u8) {
+        self.elements.attention(seconds);
+    }
 }
 
 pub struct ElementZero {}
@@ -251,6 +316,15 @@ impl ElementZero {
                 ConfigurationMessage::AppKey(message) => {
                     self::app_key::dispatch(ctx, access, message).await?;
                 }
+                ConfigurationMessage::NetKey(message) => {
+                    self::net_key::dispatch(ctx, access, message).await?;
+                }
+                ConfigurationMessage::KeyRefreshPhase(message) => {
+                    self::key_refresh_phase::dispatch(ctx, access, message).await?;
+                }
+                ConfigurationMessage::NodeIdentity(message) => {
+                    self::node_identity::dispatch(ctx, access, message).await?;
+                }
                 ConfigurationMessage::ModelApp(message) => {
                     self::model_app::dispatch(ctx, access, message).await?;
                 }
@@ -1,6 +1,12 @@
+use crate::drivers::ble::mesh::address::Address;
+use crate::drivers::ble::mesh::config::publications::Publications;
 use crate::drivers::ble::mesh::driver::elements::PrimaryElementContext;
 use crate::drivers::ble::mesh::driver::DeviceError;
-use crate::drivers::ble::mesh::model::foundation::configuration::model_publication::ModelPublicationMessage;
+use crate::drivers::ble::mesh::model::foundation::configuration::model_publication::{
+    ModelPublicationMessage, ModelPublicationSetMessage, ModelPublicationStatusMessage,
+    PublishAddress,
+};
+use crate::drivers::ble::mesh::model::foundation::configuration::AppKeyIndex;
 use crate::drivers::ble::mesh::model::Status;
 use crate::drivers::ble::mesh::pdu::access::AccessMessage;
 
@@ -10,7 +16,7 @@ pub(crate) async fn dispatch<C: PrimaryElementContext>(
     message: &ModelPublicationMessage,
 ) -> Result<(), DeviceError> {
     match message {
-        ModelPublicationMessage::Set(set) => {
+        ModelPublicationMessage::Set(set) | ModelPublicationMessage::VirtualAddressSet(set) => {
             let status = if ctx.is_local(&set.element_address) {
                 let result = ctx
                     .update_configuration(|config| {
@@ -19,24 +25,29 @@ pub(crate) async fn dispatch<C: PrimaryElementContext>(
                             .configuration_model_mut()
                             .publish_period_mut() = set.publish_period;
                         if let Some(network) = config.network_mut() {
-                            if let Ok(network) =
-                                network.find_by_app_key_index_mut(&set.app_key_index)
+                            let net_key_index = if set.publish_address == PublishAddress::Unassigned
                             {
-                                network.publications_mut().set(
-                                    set.element_address,
-                                    set.publish_address.into(),
-                                    set.app_key_index,
-                                    set.credential_flag,
-                                    set.publish_ttl,
-                                    set.publish_period,
-                                    set.publish_retransmit_count,
-                                    set.publish_retransmit_interval_steps,
-                                    set.model_identifier,
-                                )?;
-                                Ok(())
+                                // disabling publication, so look up wherever it currently lives
+                                match network
+                                    .find_publication(&set.element_address, &set.model_identifier)
+                                {
+                                    Some((network, _)) => network.key_index(),
+                                    None => return Ok(()),
+                                }
+                            } else if let Ok(network) =
+                                network.find_by_app_key_index(&set.app_key_index)
+                            {
+                                network.key_index()
                             } else {
                                 Err(Status::InvalidAppKeyIndex)?
-                            }
+                            };
+                            set_publication(
+                                network
+                                    .find_by_net_key_index_mut(&net_key_index)?
+                                    .publications_mut(),
+                                set,
+                            )?;
+                            Ok(())
                         } else {
                             Err(DeviceError::NotProvisioned)?
                         }
@@ -59,11 +70,68 @@ pub(crate) async fn dispatch<C: PrimaryElementContext>(
             )?)
             .await?;
         }
-        //ModelPublicationMessage::Get(_) => {}
-        //ModelPublicationMessage::VirtualAddressSet(_) => {}
+        ModelPublicationMessage::Get(get) => {
+            let mut response = ModelPublicationStatusMessage {
+                status: Status::Success,
+                element_address: get.element_address,
+                publish_address: Address::Unassigned,
+                app_key_index: AppKeyIndex::new(0),
+                credential_flag: false,
+                publish_ttl: Some(0),
+                publish_period: 0,
+                publish_retransmit_count: 0,
+                publish_retransmit_interval_steps: 0,
+                model_identifier: get.model_identifier,
+            };
+
+            if !ctx.is_local(&get.element_address) {
+                response.status = Status::InvalidAddress;
+            } else if let Some(network) = ctx.configuration().network() {
+                if let Some((_, publication)) =
+                    network.find_publication(&get.element_address, &get.model_identifier)
+                {
+                    response.publish_address = match publication.publish_address {
+                        Address::LabelUuid(label_uuid) => {
+                            Address::Virtual(label_uuid.virtual_address())
+                        }
+                        publish_address => publish_address,
+                    };
+                    response.app_key_index = publication.app_key_index;
+                    response.credential_flag = publication.credential_flag;
+                    response.publish_ttl = publication.publish_ttl;
+                    response.publish_period = publication.publish_period;
+                    response.publish_retransmit_count = publication.publish_retransmit_count;
+                    response.publish_retransmit_interval_steps =
+                        publication.publish_retransmit_interval_steps;
+                }
+            }
+
+            ctx.transmit(access.create_response(
+                ctx.address().ok_or(DeviceError::NotProvisioned)?,
+                ModelPublicationMessage::Status(response),
+            )?)
+            .await?;
+        }
         _ => {
             // not applicable to server role
         }
     }
     Ok(())
 }
+
+fn set_publication(
+    publications: &mut Publications,
+    set: &ModelPublicationSetMessage,
+) -> Result<(), Status> {
+    publications.set(
+        set.element_address,
+        set.publish_address.into(),
+        set.app_key_index,
+        set.credential_flag,
+        set.publish_ttl,
+        set.publish_period,
+        set.publish_retransmit_count,
+        set.publish_retransmit_interval_steps,
+        set.model_identifier,
+    )
+}
@@ -1,6 +1,10 @@
+use crate::drivers::ble::mesh::address::UnicastAddress;
+use crate::drivers::ble::mesh::config::subcriptions::Subscriptions;
 use crate::drivers::ble::mesh::driver::elements::PrimaryElementContext;
 use crate::drivers::ble::mesh::driver::DeviceError;
-use crate::drivers::ble::mesh::model::foundation::configuration::model_subscription::ModelSubscriptionMessage;
+use crate::drivers::ble::mesh::model::foundation::configuration::model_subscription::{
+    ModelSubscriptionMessage, ModelSubscriptionStatusMessage,
+};
 use crate::drivers::ble::mesh::model::Status;
 use crate::drivers::ble::mesh::pdu::access::AccessMessage;
 
@@ -9,50 +13,94 @@ pub(crate) async fn dispatch<C: PrimaryElementContext>(
     access: &AccessMessage,
     message: &ModelSubscriptionMessage,
 ) -> Result<(), DeviceError> {
-    match message {
-        ModelSubscriptionMessage::Add(add) => {
-            let status = if ctx.is_local(&add.element_address) {
-                let result = ctx
-                    .update_configuration(|config| {
-                        if let Some(network) = config.network_mut() {
-                            network.subscriptions_mut().add(
-                                add.element_address,
-                                add.subscription_address,
-                                add.model_identifier,
-                            )?;
-                            Ok(())
-                        } else {
-                            Err(DeviceError::NotProvisioned)
-                        }
-                    })
-                    .await;
-
-                match result {
-                    Ok(_) => Status::Success,
-                    Err(DeviceError::Status(status)) => status,
-                    Err(all_others) => Err(all_others)?,
-                }
-            } else {
-                Status::InvalidAddress
-            };
-
-            let response = add.create_status_response(status);
-            ctx.transmit(access.create_response(
-                ctx.address().ok_or(DeviceError::NotProvisioned)?,
-                ModelSubscriptionMessage::Status(response),
-            )?)
+    let response = match message {
+        ModelSubscriptionMessage::Add(add) | ModelSubscriptionMessage::VirtualAddressAdd(add) => {
+            let status = update(ctx, &add.element_address, |subscriptions| {
+                subscriptions.add(
+                    add.element_address,
+                    add.subscription_address,
+                    add.model_identifier,
+                )
+            })
             .await?;
-            Ok(())
+            add.create_status_response(status)
+        }
+        ModelSubscriptionMessage::Delete(delete)
+        | ModelSubscriptionMessage::VirtualAddressDelete(delete) => {
+            let status = update(ctx, &delete.element_address, |subscriptions| {
+                subscriptions.delete(
+                    delete.element_address,
+                    delete.subscription_address,
+                    delete.model_identifier,
+                )
+            })
+            .await?;
+            delete.create_status_response(status)
+        }
+        ModelSubscriptionMessage::Overwrite(overwrite)
+        | ModelSubscriptionMessage::VirtualAddressOverwrite(overwrite) => {
+            let status = update(ctx, &overwrite.element_address, |subscriptions| {
+                subscriptions.overwrite(
+                    overwrite.element_address,
+                    overwrite.subscription_address,
+                    overwrite.model_identifier,
+                )
+            })
+            .await?;
+            overwrite.create_status_response(status)
+        }
+        ModelSubscriptionMessage::DeleteAll(delete_all) => {
+            let status = update(ctx, &delete_all.element_address, |subscriptions| {
+                subscriptions.delete_all(delete_all.element_address, delete_all.model_identifier)
+            })
+            .await?;
+            delete_all.create_status_response(status)
         }
-        //ModelSubscriptionMessage::VirtualAddressAdd(add) => {}
-        //ModelSubscriptionMessage::Delete(_) => {}
-        //ModelSubscriptionMessage::DeleteAll(_) => {}
-        //ModelSubscriptionMessage::Overwrite(_) => {}
-        //ModelSubscriptionMessage::VirtualAddressDelete(_) => {}
-        //ModelSubscriptionMessage::VirtualAddressOverwrite(_) => {}
         _ => {
             // not applicable to server role
-            Ok(())
+            return Ok(());
         }
+    };
+
+    respond(ctx, access, response).await
+}
+
+async fn update<C: PrimaryElementContext, F: FnOnce(&mut Subscriptions) -> Result<(), Status>>(
+    ctx: &C,
+    element_address: &UnicastAddress,
+    update: F,
+) -> Result<Status, DeviceError> {
+    if ctx.is_local(element_address) {
+        let result = ctx
+            .update_configuration(|config| {
+                if let Some(network) = config.network_mut() {
+                    update(network.subscriptions_mut())?;
+                    Ok(())
+                } else {
+                    Err(DeviceError::NotProvisioned)
+                }
+            })
+            .await;
+
+        match result {
+            Ok(_) => Ok(Status::Success),
+            Err(DeviceError::Status(status)) => Ok(status),
+            Err(all_others) => Err(all_others),
+        }
+    } else {
+        Ok(Status::InvalidAddress)
     }
 }
+
+async fn respond<C: PrimaryElementContext>(
+    ctx: &C,
+    access: &AccessMessage,
+    response: ModelSubscriptionStatusMessage,
+) -> Result<(), DeviceError> {
+    ctx.transmit(access.create_response(
+        ctx.address().ok_or(DeviceError::NotProvisioned)?,
+        ModelSubscriptionMessage::Status(response),
+    )?)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use crate::drivers::ble::mesh::driver::elements::PrimaryElementContext;
+use crate::drivers::ble::mesh::driver::DeviceError;
+use crate::drivers::ble::mesh::model::foundation::configuration::net_key::{
+    NetKeyListMessage, NetKeyMessage, NetKeyStatusMessage,
+};
+use crate::drivers::ble::mesh::model::foundation::configuration::NetKeyIndex;
+use crate::drivers::ble::mesh::model::Status;
+use crate::drivers::ble::mesh::pdu::access::AccessMessage;
+
+pub(crate) async fn dispatch<C: PrimaryElementContext>(
+    ctx: &C,
+    access: &AccessMessage,
+    message: &NetKeyMessage,
+) -> Result<(), DeviceError> {
+    match message {
+        NetKeyMessage::Add(add) => {
+            let result = ctx
+                .update_configuration(|config| {
+                    if let Some(network) = config.network_mut() {
+                        network.add_network_key(add.net_key_index, add.net_key)?;
+                        Ok(())
+                    } else {
+                        Err(DeviceError::NotProvisioned)
+                    }
+                })
+                .await;
+
+            respond(ctx, access, result, add.net_key_index).await?;
+        }
+        NetKeyMessage::Update(update) => {
+            let result = ctx
+                .update_configuration(|config| {
+                    if let Some(network) = config.network_mut() {
+                        network
+                            .find_by_net_key_index_mut(&update.net_key_index)?
+                            .update_network_key(update.net_key)?;
+                        Ok(())
+                    } else {
+                        Err(DeviceError::NotProvisioned)
+                    }
+                })
+                .await;
+
+            respond(ctx, access, result, update.net_key_index).await?;
+        }
+        NetKeyMessage::Delete(net_key_index) => {
+            let result = ctx
+                .update_configuration(|config| {
+                    if let Some(network) = config.network_mut() {
+                        network.delete_network_key(net_key_index, &access.network_key.key_index)?;
+                        Ok(())
+                    } else {
+                        Err(DeviceError::NotProvisioned)
+                    }
+                })
+                .await;
+
+            respond(ctx, access, result, *net_key_index).await?;
+        }
+        NetKeyMessage::Get => {
+            let net_key_indexes = if let Some(network) = ctx.configuration().network() {
+                network.net_key_indexes()
+            } else {
+                Default::default()
+            };
+
+            ctx.transmit(access.create_response(
+                ctx.address().ok_or(DeviceError::NotProvisioned)?,
+                NetKeyMessage::List(NetKeyListMessage { net_key_indexes }),
+            )?)
+            .await?;
+        }
+        _ => {
+            // not applicable to server role
+        }
+    }
+    Ok(())
+}
+
+async fn respond<C: PrimaryElementContext>(
+    ctx: &C,
+    access: &AccessMessage,
+    result: Result<(), DeviceError>,
+    net_key_index: NetKeyIndex,
+) -> Result<(), DeviceError> {
+    let status = match result {
+        Ok(_) => Status::Success,
+        Err(DeviceError::Status(status)) => status,
+        Err(all_others) => Err(all_others)?,
+    };
+
+    let response = NetKeyStatusMessage {
+        status,
+        net_key_index,
+    };
+
+    ctx.transmit(access.create_response(
+        ctx.address().ok_or(DeviceError::NotProvisioned)?,
+        NetKeyMessage::Status(response),
+    )?)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use crate::drivers::ble::mesh::driver::elements::PrimaryElementContext;
+use crate::drivers::ble::mesh::driver::DeviceError;
+use crate::drivers::ble::mesh::model::foundation::configuration::node_identity::{
+    NodeIdentityMessage, NodeIdentityState, NodeIdentityStatusMessage,
+};
+use crate::drivers::ble::mesh::model::foundation::configuration::NetKeyIndex;
+use crate::drivers::ble::mesh::model::Status;
+use crate::drivers::ble::mesh::pdu::access::AccessMessage;
+
+pub(crate) async fn dispatch<C: PrimaryElementContext>(
+    ctx: &C,
+    access: &AccessMessage,
+    message: &NodeIdentityMessage,
+) -> Result<(), DeviceError> {
+    match message {
+        NodeIdentityMessage::Get(net_key_index) => {
+            respond(ctx, access, *net_key_index).await?;
+        }
+        NodeIdentityMessage::Set(set) => {
+            if is_known(ctx, &set.net_key_index)? {
+                ctx.set_node_identity(&set.net_key_index, set.identity)?;
+            }
+            respond(ctx, access, set.net_key_index).await?;
+        }
+        _ => {
+            // not applicable to server role
+        }
+    }
+    Ok(())
+}
+
+fn is_known<C: PrimaryElementContext>(
+    ctx: &C,
+    net_key_index: &NetKeyIndex,
+) -> Result<bool, DeviceError> {
+    Ok(ctx
+        .configuration()
+        .network()
+        .ok_or(DeviceError::NotProvisioned)?
+        .find_by_net_key_index(net_key_index)
+        .is_ok())
+}
+
+async fn respond<C: PrimaryElementContext>(
+    ctx: &C,
+    access: &AccessMessage,
+    net_key_index: NetKeyIndex,
+) -> Result<(), DeviceError> {
+    let (status, identity) = if is_known(ctx, &net_key_index)? {
+        (Status::Success, ctx.node_identity(&net_key_index))
+    } else {
+        (Status::InvalidNetKeyIndex, NodeIdentityState::Stopped)
+    };
+
+    let response = NodeIdentityStatusMessage {
+        status,
+        net_key_index,
+        identity,
+    };
+
+    ctx.transmit(access.create_response(
+        ctx.address().ok_or(DeviceError::NotProvisioned)?,
+        NodeIdentityMessage::Status(response),
+    )?)
+    .await?;
+    Ok(())
+}
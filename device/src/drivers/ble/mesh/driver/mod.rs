@@ -35,6 +35,7 @@ pub enum DeviceError {
     NotProvisioned,
     Status(Status),
     Network(NetworkError),
+    Timeout,
 }
 
 impl From<NetworkError> for DeviceError {
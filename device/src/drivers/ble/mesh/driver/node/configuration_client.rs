@@ -0,0 +1,400 @@
+use crate::drivers::ble::mesh::address::UnicastAddress;
+use crate::drivers::ble::mesh::composition::Composition;
+use crate::drivers::ble::mesh::driver::node::NodeMutex;
+use crate::drivers::ble::mesh::driver::DeviceError;
+use crate::drivers::ble::mesh::model::foundation::configuration::app_key::{
+    AppKeyAddMessage, AppKeyMessage, CONFIG_APPKEY_STATUS,
+};
+use crate::drivers::ble::mesh::model::foundation::configuration::composition_data::{
+    CompositionDataMessage, CONFIG_COMPOSITION_DATA_STATUS,
+};
+use crate::drivers::ble::mesh::model::foundation::configuration::model_app::{
+    ModelAppMessage, ModelAppPayload, CONFIG_MODEL_APP_STATUS,
+};
+use crate::drivers::ble::mesh::model::foundation::configuration::model_publication::{
+    ModelPublicationMessage, ModelPublicationSetMessage, PublishAddress,
+    CONFIG_MODEL_PUBLICATION_STATUS,
+};
+use crate::drivers::ble::mesh::model::foundation::configuration::model_subscription::{
+    ModelSubscriptionAddMessage, ModelSubscriptionMessage, SubscriptionAddress,
+    CONFIG_MODEL_SUBSCRIPTION_STATUS,
+};
+use crate::drivers::ble::mesh::model::foundation::configuration::{
+    AppKeyIndex, ConfigurationClient, ConfigurationMessage, NetKeyAppKeyIndexesPair, NetKeyIndex,
+};
+use crate::drivers::ble::mesh::model::{Model, ModelIdentifier, Status};
+use crate::drivers::ble::mesh::pdu::access::{AccessMessage, Opcode};
+use core::cell::Cell;
+use embassy::blocking_mutex::Mutex;
+use embassy::channel::mpmc::Channel;
+use embassy::channel::signal::Signal;
+use embassy::time::{with_timeout, Duration};
+
+/// How long to wait for the status answering a configuration request.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A request for the Configuration Server of a remote node.
+pub struct ConfigurationRequest {
+    pub(crate) dst: UnicastAddress,
+    pub(crate) device_key: [u8; 16],
+    pub(crate) message: ConfigurationMessage,
+}
+
+#[derive(Copy, Clone)]
+struct PendingRequest {
+    dst: UnicastAddress,
+    device_key: [u8; 16],
+    status: Opcode,
+}
+
+/// Carries Configuration Client requests to the node, and the status
+/// answering each request back to the application.
+///
+/// A single request is outstanding at a time, concurrent requests wait their
+/// turn; its status is only accepted from the addressed node, encrypted with
+/// that node's device key.
+pub(crate) struct ConfigurationClientChannel {
+    turn: Channel<NodeMutex, (), 1>,
+    requests: Channel<NodeMutex, ConfigurationRequest, 1>,
+    pending: Mutex<NodeMutex, Cell<Option<PendingRequest>>>,
+    response: Signal<ConfigurationMessage>,
+}
+
+impl ConfigurationClientChannel {
+    pub(crate) fn new() -> Self {
+        let channel = Self {
+            turn: Channel::new(),
+            requests: Channel::new(),
+            pending: Mutex::new(Cell::new(None)),
+            response: Signal::new(),
+        };
+        channel.turn.try_send(()).ok();
+        channel
+    }
+
+    async fn request(
+        &self,
+        request: ConfigurationRequest,
+        status: Opcode,
+    ) -> Result<ConfigurationMessage, DeviceError> {
+        // one request at a time, so each status finds its request.
+        let _turn = Turn::take(&self.turn).await;
+        self.pending.lock(|pending| {
+            pending.set(Some(PendingRequest {
+                dst: request.dst,
+                device_key: request.device_key,
+                status,
+            }))
+        });
+        self.response.reset();
+        self.requests.send(request).await;
+        let result = with_timeout(RESPONSE_TIMEOUT, self.response.wait()).await;
+        self.pending.lock(|pending| pending.set(None));
+        result.map_err(|_| DeviceError::Timeout)
+    }
+
+    pub(crate) async fn next(&self) -> ConfigurationRequest {
+        self.requests.recv().await
+    }
+
+    pub(crate) fn remote_device_key(&self, addr: &UnicastAddress) -> Option<[u8; 16]> {
+        self.pending.lock(|pending| match pending.get() {
+            Some(pending) if pending.dst == *addr => Some(pending.device_key),
+            _ => None,
+        })
+    }
+
+    /// Complete the outstanding request if this message is its status.
+    pub(crate) fn process_status(&self, message: &AccessMessage) -> Result<bool, DeviceError> {
+        let pending = match self.pending.lock(|pending| pending.get()) {
+            Some(pending) => pending,
+            None => return Ok(false),
+        };
+        if message.src != pending.dst
+            || message.remote_device_key != Some(pending.device_key)
+            || message.opcode() != pending.status
+        {
+            return Ok(false);
+        }
+        if let Some(status) = ConfigurationClient::parse(message.opcode(), message.parameters())? {
+            self.pending.lock(|pending| pending.set(None));
+            self.response.signal(status);
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+/// Held by the request in flight, handing the turn over once dropped.
+struct Turn<'a> {
+    turn: &'a Channel<NodeMutex, (), 1>,
+}
+
+impl<'a> Turn<'a> {
+    async fn take(turn: &'a Channel<NodeMutex, (), 1>) -> Turn<'a> {
+        turn.recv().await;
+        Self { turn }
+    }
+}
+
+impl<'a> Drop for Turn<'a> {
+    fn drop(&mut self) {
+        self.turn.try_send(()).ok();
+    }
+}
+
+/// Configures a remote node through its Configuration Server.
+///
+/// Requests are encrypted with the device key of the remote node, as handed
+/// out when it was provisioned, by this node or by another provisioner.
+#[derive(Clone)]
+pub struct ConfigurationClientContext<'a> {
+    channel: &'a ConfigurationClientChannel,
+    address: UnicastAddress,
+    device_key: [u8; 16],
+}
+
+impl<'a> ConfigurationClientContext<'a> {
+    pub(crate) fn new(
+        channel: &'a ConfigurationClientChannel,
+        address: UnicastAddress,
+        device_key: [u8; 16],
+    ) -> Self {
+        Self {
+            channel,
+            address,
+            device_key,
+        }
+    }
+
+    /// Primary element address of the remote node.
+    pub fn address(&self) -> UnicastAddress {
+        self.address
+    }
+
+    async fn request(
+        &self,
+        message: ConfigurationMessage,
+        status: Opcode,
+    ) -> Result<ConfigurationMessage, DeviceError> {
+        self.channel
+            .request(
+                ConfigurationRequest {
+                    dst: self.address,
+                    device_key: self.device_key,
+                    message,
+                },
+                status,
+            )
+            .await
+    }
+
+    fn check(status: Status) -> Result<(), DeviceError> {
+        match status {
+            Status::Success => Ok(()),
+            status => Err(DeviceError::Status(status)),
+        }
+    }
+
+    /// Composition Data page 0 of the remote node.
+    pub async fn composition_data_get(&self) -> Result<Composition, DeviceError> {
+        match self
+            .request(
+                ConfigurationMessage::CompositionData(CompositionDataMessage::Get(0)),
+                CONFIG_COMPOSITION_DATA_STATUS,
+            )
+            .await?
+        {
+            ConfigurationMessage::CompositionData(CompositionDataMessage::Status(status)) => {
+                Ok(status.data)
+            }
+            _ => Err(DeviceError::InvalidPacket),
+        }
+    }
+
+    pub async fn app_key_add(
+        &self,
+        net_key_index: NetKeyIndex,
+        app_key_index: AppKeyIndex,
+        app_key: [u8; 16],
+    ) -> Result<(), DeviceError> {
+        let message = AppKeyMessage::Add(AppKeyAddMessage {
+            indexes: NetKeyAppKeyIndexesPair::new(net_key_index, app_key_index),
+            app_key,
+        });
+        match self
+            .request(ConfigurationMessage::AppKey(message), CONFIG_APPKEY_STATUS)
+            .await?
+        {
+            ConfigurationMessage::AppKey(AppKeyMessage::Status(status)) => {
+                Self::check(status.status)
+            }
+            _ => Err(DeviceError::InvalidPacket),
+        }
+    }
+
+    pub async fn model_app_bind(
+        &self,
+        element_address: UnicastAddress,
+        app_key_index: AppKeyIndex,
+        model_identifier: ModelIdentifier,
+    ) -> Result<(), DeviceError> {
+        let message = ModelAppMessage::Bind(ModelAppPayload {
+            element_address,
+            app_key_index,
+            model_identifier,
+        });
+        match self
+            .request(
+                ConfigurationMessage::ModelApp(message),
+                CONFIG_MODEL_APP_STATUS,
+            )
+            .await?
+        {
+            ConfigurationMessage::ModelApp(ModelAppMessage::Status(status)) => {
+                Self::check(status.status)
+            }
+            _ => Err(DeviceError::InvalidPacket),
+        }
+    }
+
+    /// Set the publication of a model, using the virtual address form when required.
+    pub async fn model_publication_set(
+        &self,
+        publication: ModelPublicationSetMessage,
+    ) -> Result<(), DeviceError> {
+        let message = if let PublishAddress::Virtual(_) = publication.publish_address {
+            ModelPublicationMessage::VirtualAddressSet(publication)
+        } else {
+            ModelPublicationMessage::Set(publication)
+        };
+        match self
+            .request(
+                ConfigurationMessage::ModelPublication(message),
+                CONFIG_MODEL_PUBLICATION_STATUS,
+            )
+            .await?
+        {
+            ConfigurationMessage::ModelPublication(ModelPublicationMessage::Status(status)) => {
+                Self::check(status.status)
+            }
+            _ => Err(DeviceError::InvalidPacket),
+        }
+    }
+
+    /// Add a subscription to a model, using the virtual address form when required.
+    pub async fn model_subscription_add(
+        &self,
+        element_address: UnicastAddress,
+        subscription_address: SubscriptionAddress,
+        model_identifier: ModelIdentifier,
+    ) -> Result<(), DeviceError> {
+        let add = ModelSubscriptionAddMessage {
+            element_address,
+            subscription_address,
+            model_identifier,
+        };
+        let message = if let SubscriptionAddress::Virtual(_) = subscription_address {
+            ModelSubscriptionMessage::VirtualAddressAdd(add)
+        } else {
+            ModelSubscriptionMessage::Add(add)
+        };
+        match self
+            .request(
+                ConfigurationMessage::ModelSubscription(message),
+                CONFIG_MODEL_SUBSCRIPTION_STATUS,
+            )
+            .await?
+        {
+            ConfigurationMessage::ModelSubscription(ModelSubscriptionMessage::Status(status)) => {
+                Self::check(status.status)
+            }
+            _ => Err(DeviceError::InvalidPacket),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drivers::ble::mesh::address::Address;
+    use crate::drivers::ble::mesh::config::network::{NetworkKey, NetworkKeyHandle};
+    use crate::drivers::ble::mesh::pdu::access::AccessPayload;
+    use ector::testutil::TestContext;
+    use futures::future::join3;
+    use heapless::Vec;
+
+    fn status(src: UnicastAddress, device_key: [u8; 16]) -> AccessMessage {
+        let mut parameters = Vec::new();
+        // success, for net key 0 and app key 1.
+        parameters
+            .extend_from_slice(&[0x00, 0x00, 0x10, 0x00])
+            .unwrap();
+        AccessMessage {
+            ttl: None,
+            network_key: NetworkKeyHandle::new(NetworkKey::new([0; 16]), NetKeyIndex::new(0))
+                .unwrap(),
+            ivi: 0,
+            nid: 0,
+            akf: false,
+            aid: 0.into(),
+            remote_device_key: Some(device_key),
+            src,
+            dst: Address::Unicast(UnicastAddress(0x0001)),
+            payload: AccessPayload {
+                opcode: CONFIG_APPKEY_STATUS,
+                parameters,
+            },
+        }
+    }
+
+    #[test]
+    fn test_requests_take_turns() {
+        run_task!(requests_take_turns);
+    }
+
+    #[embassy::task]
+    async fn requests_take_turns(_context: TestContext<()>) {
+        let channel = ConfigurationClientChannel::new();
+        let first = ConfigurationClientContext::new(&channel, UnicastAddress(0x0002), [2; 16]);
+        let second = ConfigurationClientContext::new(&channel, UnicastAddress(0x0003), [3; 16]);
+
+        let node = async {
+            let request = channel.next().await;
+            assert_eq!(UnicastAddress(0x0002), request.dst);
+            // the second request waits for the first to be answered.
+            assert!(channel.requests.try_recv().is_err());
+            assert_eq!(
+                Some([2; 16]),
+                channel.remote_device_key(&UnicastAddress(0x0002))
+            );
+            assert!(channel.remote_device_key(&UnicastAddress(0x0003)).is_none());
+
+            assert!(!channel
+                .process_status(&status(UnicastAddress(0x0003), [2; 16]))
+                .unwrap());
+            assert!(!channel
+                .process_status(&status(UnicastAddress(0x0002), [3; 16]))
+                .unwrap());
+            assert!(channel
+                .process_status(&status(UnicastAddress(0x0002), [2; 16]))
+                .unwrap());
+
+            let request = channel.next().await;
+            assert_eq!(UnicastAddress(0x0003), request.dst);
+            assert!(channel
+                .process_status(&status(UnicastAddress(0x0003), [3; 16]))
+                .unwrap());
+        };
+
+        let (first, second, _) = join3(
+            first.app_key_add(NetKeyIndex::new(0), AppKeyIndex::new(1), [0xAA; 16]),
+            second.app_key_add(NetKeyIndex::new(0), AppKeyIndex::new(1), [0xBB; 16]),
+            node,
+        )
+        .await;
+        assert!(first.is_ok());
+        assert!(second.is_ok());
+    }
+}
@@ -1,14 +1,16 @@
 use crate::drivers::ble::mesh::address::{Address, LabelUuid, UnicastAddress};
 use crate::drivers::ble::mesh::app::ApplicationKeyIdentifier;
+use crate::drivers::ble::mesh::beacon::SecureNetworkBeacon;
 use crate::drivers::ble::mesh::composition::{Composition, ElementsHandler};
-use crate::drivers::ble::mesh::config::network::NetworkDetails;
+use crate::drivers::ble::mesh::config::network::NetworkKeyHandle;
 use crate::drivers::ble::mesh::config::Configuration;
 use crate::drivers::ble::mesh::crypto;
 use crate::drivers::ble::mesh::crypto::nonce::{ApplicationNonce, DeviceNonce};
 use crate::drivers::ble::mesh::device::Uuid;
 use crate::drivers::ble::mesh::driver::elements::{ElementContext, PrimaryElementContext};
+use crate::drivers::ble::mesh::driver::node::health::HealthServerState;
 use crate::drivers::ble::mesh::driver::node::outbound::OutboundPublishMessage;
-use crate::drivers::ble::mesh::driver::node::Node;
+use crate::drivers::ble::mesh::driver::node::{Node, ProvisionedDevice};
 use crate::drivers::ble::mesh::driver::pipeline::mesh::{MeshContext, NetworkRetransmitDetails};
 use crate::drivers::ble::mesh::driver::pipeline::provisioned::access::AccessContext;
 use crate::drivers::ble::mesh::driver::pipeline::provisioned::lower::LowerContext;
@@ -18,13 +20,19 @@ use crate::drivers::ble::mesh::driver::pipeline::provisioned::network::relay::Re
 use crate::drivers::ble::mesh::driver::pipeline::provisioned::network::NetworkContext;
 use crate::drivers::ble::mesh::driver::pipeline::provisioned::upper::UpperContext;
 use crate::drivers::ble::mesh::driver::pipeline::provisioned::ProvisionedContext;
+use crate::drivers::ble::mesh::driver::pipeline::provisioner::ProvisionerContext;
 use crate::drivers::ble::mesh::driver::pipeline::unprovisioned::provisionable::UnprovisionedContext;
 use crate::drivers::ble::mesh::driver::pipeline::PipelineContext;
 use crate::drivers::ble::mesh::driver::DeviceError;
+use crate::drivers::ble::mesh::generic_provisioning::Reason;
 use crate::drivers::ble::mesh::interface::{NetworkInterfaces, PDU};
+use crate::drivers::ble::mesh::model::foundation::configuration::node_identity::NodeIdentityState;
 #[cfg(feature = "ble-mesh-relay")]
 use crate::drivers::ble::mesh::model::foundation::configuration::relay::Relay;
+use crate::drivers::ble::mesh::model::foundation::configuration::NetKeyIndex;
+use crate::drivers::ble::mesh::oob::OobHandler;
 use crate::drivers::ble::mesh::pdu::access::AccessMessage;
+use crate::drivers::ble::mesh::pdu::proxy::ProxyConfigurationMessage;
 use crate::drivers::ble::mesh::provisioning::ProvisioningData;
 use crate::drivers::ble::mesh::storage::Storage;
 use crate::drivers::ble::mesh::vault::Vault;
@@ -42,17 +50,14 @@ use rand_core::{CryptoRng, RngCore};
 // Unprovisioned pipeline context
 // ------------------------------------------------------------------------
 
-impl<'a, E, N, S, R> UnprovisionedContext for Node<'a, E, N, S, R>
+impl<'a, E, N, O, S, R> UnprovisionedContext for Node<'a, E, N, O, S, R>
 where
     E: ElementsHandler<'a> + 'a,
     N: NetworkInterfaces + 'a,
+    O: OobHandler + 'a,
     S: Storage + 'a,
     R: RngCore + CryptoRng + 'a,
 {
-    fn rng_fill(&self, dest: &mut [u8]) {
-        self.rng.borrow_mut().fill_bytes(dest);
-    }
-
     type SetPeerPublicKeyFuture<'m> = impl Future<Output = Result<(), DeviceError>>
     where
         Self: 'm;
@@ -121,12 +126,17 @@ where
     fn rng_u32(&self) -> u32 {
         self.rng.borrow_mut().next_u32()
     }
+
+    fn static_oob(&self) -> Option<[u8; 16]> {
+        self.oob.static_oob()
+    }
 }
 
-impl<'a, E, N, S, R> MeshContext for Node<'a, E, N, S, R>
+impl<'a, E, N, O, S, R> MeshContext for Node<'a, E, N, O, S, R>
 where
     E: ElementsHandler<'a> + 'a,
     N: NetworkInterfaces + 'a,
+    O: OobHandler + 'a,
     S: Storage + 'a,
     R: RngCore + CryptoRng + 'a,
 {
@@ -134,6 +144,10 @@ where
         self.vault().uuid()
     }
 
+    fn rng_fill(&self, dest: &mut [u8]) {
+        self.rng.borrow_mut().fill_bytes(dest);
+    }
+
     fn network_retransmit(&self) -> NetworkRetransmitDetails {
         self.configuration_manager
             .configuration()
@@ -192,36 +206,161 @@ where
     }
 }
 
+// ------------------------------------------------------------------------
+// Provisioner pipeline context
+// ------------------------------------------------------------------------
+
+impl<'a, E, N, O, S, R> ProvisionerContext for Node<'a, E, N, O, S, R>
+where
+    E: ElementsHandler<'a> + 'a,
+    N: NetworkInterfaces + 'a,
+    O: OobHandler + 'a,
+    S: Storage + 'a,
+    R: RngCore + CryptoRng + 'a,
+{
+    fn next_address(&self) -> Result<UnicastAddress, DeviceError> {
+        let configuration = self.configuration_manager.configuration();
+        let devices = configuration.provisioned_devices();
+        if devices.is_full() {
+            return Err(DeviceError::InsufficientBuffer);
+        }
+        match devices.next_address() {
+            Some(address) => Ok(address),
+            None => Ok(self.primary_unicast_address()?
+                + self.configuration_manager.composition().elements.len() as u8),
+        }
+    }
+
+    fn provisioning_data(
+        &self,
+        unicast_address: UnicastAddress,
+    ) -> Result<ProvisioningData, DeviceError> {
+        if let Some(network) = self.configuration_manager.configuration().network() {
+            Ok(network.provisioning_data(unicast_address))
+        } else {
+            Err(DeviceError::NotProvisioned)
+        }
+    }
+
+    type OpenLinkFuture<'m> = impl Future<Output = Result<(), DeviceError>> + 'm
+    where
+        Self: 'm;
+
+    fn open_link<'m>(&'m self, uuid: Uuid, link_id: u32) -> Self::OpenLinkFuture<'m> {
+        async move { Ok(self.network.open_link(uuid, link_id).await?) }
+    }
+
+    type CloseLinkFuture<'m> = impl Future<Output = Result<(), DeviceError>> + 'm
+    where
+        Self: 'm;
+
+    fn close_link<'m>(&'m self, reason: Reason) -> Self::CloseLinkFuture<'m> {
+        async move { Ok(self.network.close_link(reason).await?) }
+    }
+
+    type RetransmitLinkFuture<'m> = impl Future<Output = Result<(), DeviceError>> + 'm
+    where
+        Self: 'm;
+
+    fn retransmit_link<'m>(&'m self) -> Self::RetransmitLinkFuture<'m> {
+        async move { Ok(self.network.retransmit().await?) }
+    }
+
+    type ProvisionedFuture<'m> = impl Future<Output = Result<(), DeviceError>> + 'm
+    where
+        Self: 'm;
+
+    fn provisioned<'m>(&'m self, device: ProvisionedDevice) -> Self::ProvisionedFuture<'m> {
+        async move {
+            self.configuration_manager
+                .update_configuration(|config| config.provisioned_devices_mut().add(device))
+                .await?;
+            self.elements.borrow_mut().provisioned(&device);
+            Ok(())
+        }
+    }
+}
+
 // ------------------------------------------------------------------------
 // Provisioned pipeline context
 // ------------------------------------------------------------------------
 
-impl<'a, E, N, S, R> ProvisionedContext for Node<'a, E, N, S, R>
+impl<'a, E, N, O, S, R> ProvisionedContext for Node<'a, E, N, O, S, R>
 where
     E: ElementsHandler<'a> + 'a,
     N: NetworkInterfaces + 'a,
+    O: OobHandler + 'a,
     R: CryptoRng + RngCore + 'a,
     S: Storage + 'a,
 {
 }
 
-impl<'a, E, N, S, R> NetworkContext for Node<'a, E, N, S, R>
+impl<'a, E, N, O, S, R> NetworkContext for Node<'a, E, N, O, S, R>
 where
     E: ElementsHandler<'a> + 'a,
     N: NetworkInterfaces + 'a,
+    O: OobHandler + 'a,
     R: CryptoRng + RngCore + 'a,
     S: Storage + 'a,
 {
     fn network_deadline(&self, deadline: Option<Instant>) {
         self.deadline.borrow_mut().network(deadline)
     }
+
+    type SecureNetworkBeaconFuture<'m> = impl Future<Output = Result<(), DeviceError>> + 'm
+    where
+        Self: 'm;
+
+    fn secure_network_beacon<'m>(
+        &'m self,
+        beacon: &'m SecureNetworkBeacon,
+    ) -> Self::SecureNetworkBeaconFuture<'m> {
+        async move {
+            let transition =
+                if let Some(network) = self.configuration_manager.configuration().network() {
+                    network.key_refresh_transition(beacon)?
+                } else {
+                    None
+                };
+
+            if let Some((net_key_index, transition)) = transition {
+                info!(
+                    "Key refresh {:?} on net key {:?}",
+                    transition, net_key_index
+                );
+                self.configuration_manager
+                    .update_configuration(|config| {
+                        if let Some(network) = config.network_mut() {
+                            network
+                                .find_by_net_key_index_mut(&net_key_index)?
+                                .set_key_refresh_phase(transition)?;
+                        }
+                        Ok(())
+                    })
+                    .await?;
+            }
+            Ok(())
+        }
+    }
+
+    fn configure_proxy_filter(
+        &self,
+        message: &ProxyConfigurationMessage,
+    ) -> Option<ProxyConfigurationMessage> {
+        self.network.configure_proxy_filter(message)
+    }
+
+    fn proxy_client_source(&self, src: &Address) {
+        self.network.proxy_client_source(src)
+    }
 }
 
 #[cfg(feature = "ble-mesh-relay")]
-impl<'a, E, N, S, R> RelayContext for Node<'a, E, N, S, R>
+impl<'a, E, N, O, S, R> RelayContext for Node<'a, E, N, O, S, R>
 where
     E: ElementsHandler<'a> + 'a,
     N: NetworkInterfaces + 'a,
+    O: OobHandler + 'a,
     R: CryptoRng + RngCore + 'a,
     S: Storage + 'a,
 {
@@ -254,10 +393,11 @@ where
     }
 }
 
-impl<'a, E, N, S, R> AuthenticationContext for Node<'a, E, N, S, R>
+impl<'a, E, N, O, S, R> AuthenticationContext for Node<'a, E, N, O, S, R>
 where
     E: ElementsHandler<'a> + 'a,
     N: NetworkInterfaces + 'a,
+    O: OobHandler + 'a,
     R: CryptoRng + RngCore + 'a,
     S: Storage + 'a,
 {
@@ -265,7 +405,7 @@ where
         self.vault().iv_index()
     }
 
-    fn find_network_keys_by_nid(&self, nid: u8) -> Result<Vec<NetworkDetails, 10>, DeviceError> {
+    fn find_network_keys_by_nid(&self, nid: u8) -> Result<Vec<NetworkKeyHandle, 10>, DeviceError> {
         if let Some(networks) = self.configuration_manager.configuration().network() {
             Ok(networks.find_by_nid(nid)?)
         } else {
@@ -274,10 +414,11 @@ where
     }
 }
 
-impl<'a, E, N, S, R> LowerContext for Node<'a, E, N, S, R>
+impl<'a, E, N, O, S, R> LowerContext for Node<'a, E, N, O, S, R>
 where
     E: ElementsHandler<'a> + 'a,
     N: NetworkInterfaces + 'a,
+    O: OobHandler + 'a,
     R: CryptoRng + RngCore + 'a,
     S: Storage + 'a,
 {
@@ -318,6 +459,10 @@ where
         self.vault().encrypt_device_key(nonce, bytes, mic)
     }
 
+    fn remote_device_key(&self, addr: &UnicastAddress) -> Option<[u8; 16]> {
+        self.outbound.configuration.remote_device_key(addr)
+    }
+
     fn encrypt_application_key(
         &self,
         aid: ApplicationKeyIdentifier,
@@ -350,6 +495,23 @@ where
         async move { self.configuration_manager.next_sequence().await }
     }
 
+    fn is_replay(&self, seq: u32, src: UnicastAddress) -> bool {
+        self.configuration_manager
+            .is_replay(self.iv_index().unwrap_or(0), seq, src)
+    }
+
+    type AcceptedFuture<'m> = impl Future<Output = Result<(), DeviceError>> + 'm
+    where
+        Self: 'm;
+
+    fn accepted<'m>(&'m self, seq: u32, src: UnicastAddress) -> Self::AcceptedFuture<'m> {
+        async move {
+            self.configuration_manager
+                .accepted(self.iv_index().unwrap_or(0), seq, src)
+                .await
+        }
+    }
+
     fn default_ttl(&self) -> u8 {
         self.configuration_manager
             .configuration()
@@ -378,10 +540,11 @@ where
     }
 }
 
-impl<'a, E, N, S, R> UpperContext for Node<'a, E, N, S, R>
+impl<'a, E, N, O, S, R> UpperContext for Node<'a, E, N, O, S, R>
 where
     E: ElementsHandler<'a> + 'a,
     N: NetworkInterfaces + 'a,
+    O: OobHandler + 'a,
     R: CryptoRng + RngCore + 'a,
     S: Storage + 'a,
 {
@@ -396,10 +559,11 @@ where
     }
 }
 
-impl<'a, E, N, S, R> AccessContext for Node<'a, E, N, S, R>
+impl<'a, E, N, O, S, R> AccessContext for Node<'a, E, N, O, S, R>
 where
     E: ElementsHandler<'a> + 'a,
     N: NetworkInterfaces + 'a,
+    O: OobHandler + 'a,
     R: CryptoRng + RngCore + 'a,
     S: Storage + 'a,
 {
@@ -408,23 +572,30 @@ where
         Self: 'm;
 
     fn dispatch_access<'m>(&'m self, message: &'m AccessMessage) -> Self::DispatchFuture<'m> {
-        async move { self.elements.borrow_mut().dispatch(self, message).await }
+        async move {
+            if self.outbound.configuration.process_status(message)? {
+                return Ok(());
+            }
+            self.elements.borrow_mut().dispatch(self, message).await
+        }
     }
 }
 
-impl<'a, E, N, S, R> PipelineContext for Node<'a, E, N, S, R>
+impl<'a, E, N, O, S, R> PipelineContext for Node<'a, E, N, O, S, R>
 where
     E: ElementsHandler<'a> + 'a,
     N: NetworkInterfaces + 'a,
+    O: OobHandler + 'a,
     S: Storage + 'a,
     R: RngCore + CryptoRng + 'a,
 {
 }
 
-impl<'a, E, N, S, R> ElementContext for Node<'a, E, N, S, R>
+impl<'a, E, N, O, S, R> ElementContext for Node<'a, E, N, O, S, R>
 where
     E: ElementsHandler<'a> + 'a,
     N: NetworkInterfaces + 'a,
+    O: OobHandler + 'a,
     R: CryptoRng + RngCore + 'a,
     S: Storage + 'a,
 {
@@ -448,10 +619,11 @@ where
     }
 }
 
-impl<'a, E, N, S, R> PrimaryElementContext for Node<'a, E, N, S, R>
+impl<'a, E, N, O, S, R> PrimaryElementContext for Node<'a, E, N, O, S, R>
 where
     E: ElementsHandler<'a> + 'a,
     N: NetworkInterfaces + 'a,
+    O: OobHandler + 'a,
     S: Storage + 'a,
     R: RngCore + CryptoRng + 'a,
 {
@@ -486,4 +658,32 @@ where
     fn is_local(&self, addr: &UnicastAddress) -> bool {
         self.is_local_unicast(&Address::Unicast(*addr))
     }
+
+    fn node_identity(&self, net_key_index: &NetKeyIndex) -> NodeIdentityState {
+        if self.node_identity_net_key_index() == Some(*net_key_index) {
+            NodeIdentityState::Running
+        } else {
+            NodeIdentityState::Stopped
+        }
+    }
+
+    fn set_node_identity(
+        &self,
+        net_key_index: &NetKeyIndex,
+        identity: NodeIdentityState,
+    ) -> Result<(), DeviceError> {
+        match identity {
+            NodeIdentityState::Running => self.start_node_identity(net_key_index),
+            _ => {
+                if self.node_identity_net_key_index() == Some(*net_key_index) {
+                    self.stop_node_identity();
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn health(&self) -> &HealthServerState {
+        &self.health
+    }
 }
@@ -0,0 +1,289 @@
+use crate::drivers::ble::mesh::address::UnicastAddress;
+use crate::drivers::ble::mesh::composition::CompanyIdentifier;
+use crate::drivers::ble::mesh::driver::node::outbound::OutboundPublishMessage;
+use crate::drivers::ble::mesh::driver::node::NodeMutex;
+use crate::drivers::ble::mesh::driver::DeviceError;
+use crate::drivers::ble::mesh::model::foundation::health::{
+    FaultStatus, HealthMessage, HEALTH_SERVER, MAX_FAULTS,
+};
+use crate::drivers::ble::mesh::model::Message;
+use crate::drivers::ble::mesh::pdu::access::AccessPayload;
+use core::cell::RefCell;
+use embassy::blocking_mutex::Mutex;
+use embassy::channel::mpmc::Sender as ChannelSender;
+use embassy::time::{Duration, Instant};
+use heapless::Vec;
+
+struct Health {
+    test_id: u8,
+    registered_faults: Vec<u8, MAX_FAULTS>,
+    current_faults: Vec<u8, MAX_FAULTS>,
+    fast_period_divisor: u8,
+    attention_until: Option<Instant>,
+}
+
+/// State of the Health Server on the primary element.
+///
+/// Faults are raised by the application, through a `HealthContext`, and
+/// read or cleared by Health Clients.
+pub struct HealthServerState {
+    company_id: CompanyIdentifier,
+    health: Mutex<NodeMutex, RefCell<Health>>,
+}
+
+impl HealthServerState {
+    pub(crate) fn new(company_id: CompanyIdentifier) -> Self {
+        Self {
+            company_id,
+            health: Mutex::new(RefCell::new(Health {
+                test_id: 0,
+                registered_faults: Vec::new(),
+                current_faults: Vec::new(),
+                fast_period_divisor: 0,
+                attention_until: None,
+            })),
+        }
+    }
+
+    /// Whether fault requests for `company_id` are ours to answer.
+    pub(crate) fn handles(&self, company_id: CompanyIdentifier) -> bool {
+        company_id == self.company_id
+    }
+
+    fn register_fault(&self, fault: u8) {
+        self.health.lock(|health| {
+            let mut health = health.borrow_mut();
+            if !health.current_faults.contains(&fault) {
+                health.current_faults.push(fault).ok();
+            }
+            if !health.registered_faults.contains(&fault) {
+                health.registered_faults.push(fault).ok();
+            }
+        })
+    }
+
+    fn clear_fault(&self, fault: u8) {
+        self.health.lock(|health| {
+            health
+                .borrow_mut()
+                .current_faults
+                .retain(|current| *current != fault)
+        })
+    }
+
+    pub(crate) fn current_status(&self) -> FaultStatus {
+        self.health.lock(|health| {
+            let health = health.borrow();
+            FaultStatus {
+                test_id: health.test_id,
+                company_id: self.company_id,
+                faults: health.current_faults.clone(),
+            }
+        })
+    }
+
+    pub(crate) fn fault_status(&self) -> FaultStatus {
+        self.health.lock(|health| {
+            let health = health.borrow();
+            FaultStatus {
+                test_id: health.test_id,
+                company_id: self.company_id,
+                faults: health.registered_faults.clone(),
+            }
+        })
+    }
+
+    pub(crate) fn clear_registered_faults(&self) {
+        self.health
+            .lock(|health| health.borrow_mut().registered_faults.clear())
+    }
+
+    pub(crate) fn test(&self, test_id: u8) {
+        self.health
+            .lock(|health| health.borrow_mut().test_id = test_id)
+    }
+
+    pub(crate) fn fast_period_divisor(&self) -> u8 {
+        self.health
+            .lock(|health| health.borrow().fast_period_divisor)
+    }
+
+    pub(crate) fn set_fast_period_divisor(&self, divisor: u8) {
+        self.health
+            .lock(|health| health.borrow_mut().fast_period_divisor = divisor)
+    }
+
+    /// Remaining seconds of the attention timer, rounded up.
+    pub(crate) fn attention(&self) -> u8 {
+        self.health
+            .lock(|health| match health.borrow().attention_until {
+                Some(until) => {
+                    let now = Instant::now();
+                    if until > now {
+                        ((until - now).as_millis() + 999) / 1000
+                    } else {
+                        0
+                    }
+                }
+                None => 0,
+            }) as u8
+    }
+
+    pub(crate) fn set_attention(&self, seconds: u8) {
+        self.health.lock(|health| {
+            health.borrow_mut().attention_until = if seconds == 0 {
+                None
+            } else {
+                Some(Instant::now() + Duration::from_secs(seconds as u64))
+            }
+        })
+    }
+
+    /// Whether the attention timer just ran out, in which case it is stopped.
+    pub(crate) fn attention_expired(&self) -> bool {
+        self.health.lock(|health| {
+            let mut health = health.borrow_mut();
+            match health.attention_until {
+                Some(until) if until <= Instant::now() => {
+                    health.attention_until = None;
+                    true
+                }
+                _ => false,
+            }
+        })
+    }
+}
+
+/// Raises and clears faults reported by the Health Server of this node.
+#[derive(Clone)]
+pub struct HealthContext<'a> {
+    state: &'a HealthServerState,
+    sender: ChannelSender<'a, NodeMutex, OutboundPublishMessage, 1>,
+    address: UnicastAddress,
+}
+
+impl<'a> HealthContext<'a> {
+    pub(crate) fn new(
+        state: &'a HealthServerState,
+        sender: ChannelSender<'a, NodeMutex, OutboundPublishMessage, 1>,
+        address: UnicastAddress,
+    ) -> Self {
+        Self {
+            state,
+            sender,
+            address,
+        }
+    }
+
+    /// Raise a fault, and publish the current faults.
+    ///
+    /// The fault stays registered until cleared by a Health Client.
+    pub async fn register_fault(&self, fault: u8) -> Result<(), DeviceError> {
+        self.state.register_fault(fault);
+        self.publish_current_status().await
+    }
+
+    /// Clear a fault that is no longer present, and publish the current faults.
+    pub async fn clear_fault(&self, fault: u8) -> Result<(), DeviceError> {
+        self.state.clear_fault(fault);
+        self.publish_current_status().await
+    }
+
+    async fn publish_current_status(&self) -> Result<(), DeviceError> {
+        let message = HealthMessage::CurrentStatus(self.state.current_status());
+        let mut parameters = Vec::new();
+        message.emit_parameters(&mut parameters)?;
+        self.sender
+            .send(OutboundPublishMessage {
+                element_address: self.address,
+                model_identifier: HEALTH_SERVER,
+                payload: AccessPayload {
+                    opcode: message.opcode(),
+                    parameters,
+                },
+            })
+            .await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ector::testutil::TestContext;
+    use embassy::time::Timer;
+
+    const COMPANY_ID: CompanyIdentifier = CompanyIdentifier(0x0059);
+
+    #[test]
+    fn test_faults() {
+        run_task!(faults);
+    }
+
+    #[embassy::task]
+    async fn faults(_context: TestContext<()>) {
+        let state = HealthServerState::new(COMPANY_ID);
+        state.register_fault(0x10);
+        state.register_fault(0x20);
+        state.register_fault(0x10);
+        assert_eq!([0x10, 0x20], state.current_status().faults[..]);
+        assert_eq!([0x10, 0x20], state.fault_status().faults[..]);
+
+        // a cleared fault stays registered
+        state.clear_fault(0x10);
+        assert_eq!([0x20], state.current_status().faults[..]);
+        assert_eq!([0x10, 0x20], state.fault_status().faults[..]);
+
+        // Fault Clear only clears the registered faults
+        state.clear_registered_faults();
+        assert!(state.fault_status().faults.is_empty());
+        assert_eq!([0x20], state.current_status().faults[..]);
+
+        state.test(3);
+        assert_eq!(3, state.fault_status().test_id);
+        assert_eq!(COMPANY_ID, state.fault_status().company_id);
+    }
+
+    #[test]
+    fn test_company_id() {
+        run_task!(company_id);
+    }
+
+    #[embassy::task]
+    async fn company_id(_context: TestContext<()>) {
+        let state = HealthServerState::new(COMPANY_ID);
+        assert!(state.handles(COMPANY_ID));
+        assert!(!state.handles(CompanyIdentifier(0x0001)));
+    }
+
+    #[test]
+    fn test_attention() {
+        run_task!(attention);
+    }
+
+    #[embassy::task]
+    async fn attention(_context: TestContext<()>) {
+        let state = HealthServerState::new(COMPANY_ID);
+        assert_eq!(0, state.attention());
+        assert!(!state.attention_expired());
+
+        state.set_attention(2);
+        assert_eq!(2, state.attention());
+        Timer::after(Duration::from_millis(100)).await;
+        // 1.9 seconds left
+        assert_eq!(2, state.attention());
+        Timer::after(Duration::from_millis(1000)).await;
+        assert_eq!(1, state.attention());
+        assert!(!state.attention_expired());
+
+        Timer::after(Duration::from_millis(1000)).await;
+        assert_eq!(0, state.attention());
+        assert!(state.attention_expired());
+        assert!(!state.attention_expired());
+
+        state.set_attention(5);
+        state.set_attention(0);
+        assert_eq!(0, state.attention());
+        assert!(!state.attention_expired());
+    }
+}
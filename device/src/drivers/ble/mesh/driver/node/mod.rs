@@ -1,40 +1,92 @@
+use crate::drivers::ble::mesh::address::UnicastAddress;
 use crate::drivers::ble::mesh::composition::ElementsHandler;
 use crate::drivers::ble::mesh::config::configuration_manager::ConfigurationManager;
 use crate::drivers::ble::mesh::config::network::NetworkKeyHandle;
+use crate::drivers::ble::mesh::device::Uuid;
 use crate::drivers::ble::mesh::driver::elements::{
     AppElementsContext, ElementContext, Elements, PrimaryElementContext,
 };
+use crate::drivers::ble::mesh::driver::node::configuration_client::ConfigurationRequest;
 use crate::drivers::ble::mesh::driver::node::deadline::Deadline;
+use crate::drivers::ble::mesh::driver::node::health::HealthServerState;
 use crate::drivers::ble::mesh::driver::node::outbound::{
     Outbound, OutboundEvent, OutboundPublishMessage,
 };
 use crate::drivers::ble::mesh::driver::pipeline::mesh::MeshContext;
+use crate::drivers::ble::mesh::driver::pipeline::provisioner::ProvisionTarget;
+use crate::drivers::ble::mesh::driver::pipeline::unprovisioned::provisionable::OobRequest;
 use crate::drivers::ble::mesh::driver::pipeline::Pipeline;
 use crate::drivers::ble::mesh::driver::DeviceError;
-use crate::drivers::ble::mesh::interface::{Beacon, NetworkInterfaces};
-use crate::drivers::ble::mesh::pdu::access::AccessMessage;
+use crate::drivers::ble::mesh::interface::{Beacon, NetworkInterfaces, PDU};
+use crate::drivers::ble::mesh::model::foundation::configuration::key_refresh_phase::KeyRefreshPhase;
+use crate::drivers::ble::mesh::model::foundation::configuration::NetKeyIndex;
+use crate::drivers::ble::mesh::model::Message;
+use crate::drivers::ble::mesh::oob::OobHandler;
+use crate::drivers::ble::mesh::pdu::access::{AccessMessage, AccessPayload};
 use crate::drivers::ble::mesh::provisioning::Capabilities;
 use crate::drivers::ble::mesh::storage::Storage;
-use crate::drivers::ble::mesh::vault::StorageVault;
+use crate::drivers::ble::mesh::vault::{StorageVault, Vault};
 use core::cell::{Cell, RefCell};
 use embassy::blocking_mutex::raw::ThreadModeRawMutex;
 use embassy::channel::mpmc::DynamicReceiver as ChannelReceiver;
-use embassy::time::{Duration, Ticker};
+use embassy::time::{Duration, Instant, Ticker, Timer};
 use embassy::util::{select, select4, Either, Either4};
 use futures::future::join;
-use futures::StreamExt;
+use futures::{pin_mut, StreamExt};
+use heapless::Vec;
 use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
 //use crate::drivers::ble::mesh::model::foundation::configuration::ConfigurationMessage::Beacon;
 
+pub(crate) mod configuration_client;
 pub(crate) mod context;
 pub(crate) mod deadline;
+pub(crate) mod health;
 pub(crate) mod outbound;
 
+pub use configuration_client::ConfigurationClientContext;
+pub use health::HealthContext;
+
 type NodeMutex = ThreadModeRawMutex;
 
 #[derive(Copy, Clone)]
 pub struct NetworkId(pub [u8; 8]);
 
+#[derive(Copy, Clone)]
+pub struct NodeIdentity {
+    pub hash: [u8; 8],
+    pub random: [u8; 8],
+}
+
+/// A device this node provisioned onto its network.
+#[derive(Serialize, Deserialize, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ProvisionedDevice {
+    pub uuid: Uuid,
+    pub unicast_address: UnicastAddress,
+    pub number_of_elements: u8,
+    pub device_key: [u8; 16],
+}
+
+/// How long Node Identity is advertised once started.
+const NODE_IDENTITY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How often the random value of the Node Identity hash changes,
+/// so the advertisements can't be linked to each other for long.
+const NODE_IDENTITY_ROTATION: Duration = Duration::from_secs(10);
+
+/// How often the secure network beacon is transmitted, unless the key
+/// refresh phase changes in between.
+const SECURE_BEACON_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Copy, Clone)]
+struct NodeIdentityAdvertising {
+    net_key_index: NetKeyIndex,
+    identity: NodeIdentity,
+    rotate_at: Instant,
+    until: Instant,
+}
+
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[derive(Copy, Clone, PartialEq)]
 pub enum State {
@@ -46,12 +98,20 @@ pub enum State {
 pub enum MeshNodeMessage {
     ForceReset,
     Shutdown,
+    /// Advertise Node Identity on the primary subnet for a limited time,
+    /// such as after a button press, so a provisioner can find this node.
+    StartNodeIdentity,
+    /// Provision the unprovisioned device with this UUID once its beacon is heard.
+    ProvisionDevice(Uuid),
+    /// Provision the next unprovisioned device heard beaconing.
+    ProvisionAnyDevice,
 }
 
-pub struct Node<'a, E, N, S, R>
+pub struct Node<'a, E, N, O, S, R>
 where
     E: ElementsHandler<'a>,
     N: NetworkInterfaces + 'a,
+    O: OobHandler + 'a,
     S: Storage + 'a,
     R: RngCore + CryptoRng + 'a,
 {
@@ -59,19 +119,26 @@ where
     state: Cell<State>,
     //
     network: N,
+    pub(crate) oob: O,
     configuration_manager: ConfigurationManager<S>,
     rng: RefCell<R>,
     pipeline: RefCell<Pipeline>,
     pub(crate) deadline: RefCell<Deadline>,
+    node_identity: Cell<Option<NodeIdentityAdvertising>>,
+    node_identity_turn: Cell<bool>,
+    /// Key refresh phase and time of the last secure network beacon.
+    secure_beacon: Cell<Option<(KeyRefreshPhase, Instant)>>,
+    pub(crate) health: HealthServerState,
     //
     pub(crate) elements: RefCell<Elements<'a, E>>,
     pub(crate) outbound: Outbound<'a>,
 }
 
-impl<'a, E, N, S, R> Node<'a, E, N, S, R>
+impl<'a, E, N, O, S, R> Node<'a, E, N, O, S, R>
 where
     E: ElementsHandler<'a>,
     N: NetworkInterfaces,
+    O: OobHandler,
     S: Storage,
     R: RngCore + CryptoRng,
 {
@@ -79,16 +146,23 @@ where
         app_elements: E,
         capabilities: Capabilities,
         network: N,
+        oob: O,
         configuration_manager: ConfigurationManager<S>,
         rng: R,
     ) -> Self {
+        let health = HealthServerState::new(configuration_manager.composition().cid());
         let me = Self {
             state: Cell::new(State::Unprovisioned),
             network,
+            oob,
             configuration_manager,
             rng: RefCell::new(rng),
             pipeline: RefCell::new(Pipeline::new(capabilities)),
             deadline: RefCell::new(Default::default()),
+            node_identity: Cell::new(None),
+            node_identity_turn: Cell::new(false),
+            secure_beacon: Cell::new(None),
+            health,
             //
             elements: RefCell::new(Elements::new(app_elements)),
             outbound: Default::default(),
@@ -114,6 +188,7 @@ where
     async fn publish(&self, publish: OutboundPublishMessage) -> Result<(), DeviceError> {
         let network = self.configuration_manager.configuration().network().clone();
         if let Some(network) = network {
+            let ivi = (network.iv_index() & 1) as u8;
             if let Some((network, publication)) =
                 network.find_publication(&publish.element_address, &publish.model_identifier)
             {
@@ -124,11 +199,11 @@ where
                     let message = AccessMessage {
                         ttl: publication.publish_ttl,
                         network_key: NetworkKeyHandle::from(network),
-                        // todo: ivi isn't always zero.
-                        ivi: 0,
+                        ivi,
                         nid: network.nid,
                         akf: true,
                         aid: app_key_details.aid,
+                        remote_device_key: None,
                         src: publish.element_address,
                         dst: publication.publish_address,
                         payload: publish.payload,
@@ -149,6 +224,37 @@ where
         Ok(())
     }
 
+    async fn transmit_configuration_request(
+        &self,
+        request: ConfigurationRequest,
+    ) -> Result<(), DeviceError> {
+        let network = self.configuration_manager.configuration().network().clone();
+        let network = network.ok_or(DeviceError::NotProvisioned)?;
+        let primary = network.iter().next().ok_or(DeviceError::NotProvisioned)?;
+
+        let mut parameters = Vec::new();
+        request.message.emit_parameters(&mut parameters)?;
+        let message = AccessMessage {
+            ttl: None,
+            network_key: NetworkKeyHandle::from(primary),
+            ivi: (network.iv_index() & 1) as u8,
+            nid: primary.nid,
+            akf: false,
+            aid: 0.into(),
+            remote_device_key: Some(request.device_key),
+            src: *network.unicast_address(),
+            dst: request.dst.into(),
+            payload: AccessPayload {
+                opcode: request.message.opcode(),
+                parameters,
+            },
+        };
+        self.pipeline
+            .borrow_mut()
+            .process_outbound(self, &message, None, self.network_retransmit())
+            .await
+    }
+
     async fn loop_unprovisioned(&self) -> Result<Option<State>, DeviceError> {
         debug!("State: unprovisioned");
 
@@ -197,11 +303,14 @@ where
                     .process_inbound(self, inbound)
                     .await;
                 self.network.retransmit().await?;
+                if let Some(abandoned) = self.process_oob_request().await? {
+                    return Ok(Some(abandoned));
+                }
                 next_state
             }
             Either::Second(_) => {
                 self.network.retransmit().await.ok();
-                Ok(None)
+                self.pipeline.borrow_mut().expire_provisioning(self).await
             }
             _ => {
                 // TODO handle this
@@ -218,7 +327,146 @@ where
         next_state
     }
 
+    /// Perform any OOB interaction requested by the provisioning session, returning
+    /// the next state if the session gets abandoned in the meantime.
+    async fn process_oob_request(&self) -> Result<Option<State>, DeviceError> {
+        let request = self.pipeline.borrow_mut().take_oob_request();
+        match request {
+            Some(OobRequest::Display(action, value)) => {
+                self.oob.display(action, &value).await;
+            }
+            Some(OobRequest::Input(action, size)) => {
+                let input_fut = self.oob.input(action, size);
+                pin_mut!(input_fut);
+                // keep the session going while waiting on the user, until
+                // the provisioner gives up or it times out. A forced reset
+                // drops the wait along with the rest of the loop.
+                let mut ticker = Ticker::every(Duration::from_secs(1));
+                let value = loop {
+                    let timeout = self
+                        .pipeline
+                        .borrow()
+                        .provisioning_timeout()
+                        .ok_or(DeviceError::InvalidState)?;
+                    match select4(
+                        &mut input_fut,
+                        self.network.receive(),
+                        ticker.next(),
+                        Timer::at(timeout),
+                    )
+                    .await
+                    {
+                        Either4::First(value) => break value,
+                        Either4::Second(Ok(inbound)) => {
+                            let next_state = self
+                                .pipeline
+                                .borrow_mut()
+                                .process_inbound(self, inbound)
+                                .await?;
+                            if let Some(State::Unprovisioned) = next_state {
+                                return Ok(next_state);
+                            }
+                        }
+                        Either4::Second(Err(_)) => {}
+                        Either4::Third(_) => {
+                            self.network.retransmit().await.ok();
+                        }
+                        Either4::Fourth(_) => {
+                            let next_state =
+                                self.pipeline.borrow_mut().expire_provisioning(self).await?;
+                            if next_state.is_some() {
+                                return Ok(next_state);
+                            }
+                        }
+                    }
+                };
+                self.pipeline.borrow_mut().oob_input(self, value).await?;
+            }
+            None => {}
+        }
+        Ok(None)
+    }
+
+    pub(crate) fn start_node_identity(
+        &self,
+        net_key_index: &NetKeyIndex,
+    ) -> Result<(), DeviceError> {
+        let now = Instant::now();
+        self.node_identity.set(Some(NodeIdentityAdvertising {
+            net_key_index: *net_key_index,
+            identity: self.new_node_identity(net_key_index)?,
+            rotate_at: now + NODE_IDENTITY_ROTATION,
+            until: now + NODE_IDENTITY_TIMEOUT,
+        }));
+        Ok(())
+    }
+
+    fn new_node_identity(&self, net_key_index: &NetKeyIndex) -> Result<NodeIdentity, DeviceError> {
+        let mut random = [0; 8];
+        self.rng.borrow_mut().fill_bytes(&mut random);
+        self.vault().node_identity(net_key_index, random)
+    }
+
+    pub(crate) fn stop_node_identity(&self) {
+        self.node_identity.set(None);
+    }
+
+    /// The subnet Node Identity is currently advertised for, if any.
+    pub(crate) fn node_identity_net_key_index(&self) -> Option<NetKeyIndex> {
+        self.active_node_identity()
+            .map(|advertising| advertising.net_key_index)
+    }
+
+    fn active_node_identity(&self) -> Option<NodeIdentityAdvertising> {
+        match self.node_identity.get() {
+            Some(advertising) if Instant::now() < advertising.until => Some(advertising),
+            _ => {
+                self.node_identity.set(None);
+                None
+            }
+        }
+    }
+
+    /// Change the random value of the active Node Identity once it is due.
+    fn rotate_node_identity(&self) -> Result<(), DeviceError> {
+        if let Some(mut advertising) = self.active_node_identity() {
+            let now = Instant::now();
+            if now >= advertising.rotate_at {
+                advertising.identity = self.new_node_identity(&advertising.net_key_index)?;
+                advertising.rotate_at = now + NODE_IDENTITY_ROTATION;
+                self.node_identity.set(Some(advertising));
+            }
+        }
+        Ok(())
+    }
+
+    fn start_primary_node_identity(&self) -> Result<(), DeviceError> {
+        let net_key_index = self
+            .configuration_manager
+            .configuration()
+            .network()
+            .as_ref()
+            .ok_or(DeviceError::NotProvisioned)?
+            .net_key_indexes()
+            .first()
+            .copied()
+            .ok_or(DeviceError::NotProvisioned)?;
+        self.start_node_identity(&net_key_index)
+    }
+
     async fn transmit_provisioned_beacon(&self) -> Result<(), DeviceError> {
+        self.rotate_node_identity()?;
+        if let Some(advertising) = self.active_node_identity() {
+            // alternate with the network id while node identity is running.
+            let turn = !self.node_identity_turn.get();
+            self.node_identity_turn.set(turn);
+            if turn {
+                self.network
+                    .beacon(Beacon::NodeIdentity(advertising.identity))
+                    .await?;
+                return Ok(());
+            }
+        }
         if let Some(network) = self.configuration_manager.configuration().network() {
             if let Ok(network_id) = network.network_id() {
                 debug!("pre-beacon");
@@ -229,9 +477,53 @@ where
         Ok(())
     }
 
+    /// Transmit the secure network beacon of the primary subnet when its key
+    /// refresh phase changed, or periodically otherwise.
+    async fn transmit_secure_beacon(&self) -> Result<(), DeviceError> {
+        let beacon = {
+            let configuration = self.configuration_manager.configuration();
+            if !configuration.foundation_models().secure_beacon() {
+                return Ok(());
+            }
+            let network = configuration
+                .network()
+                .as_ref()
+                .ok_or(DeviceError::NotProvisioned)?;
+            let phase = network.key_refresh_phase();
+            let now = Instant::now();
+            match self.secure_beacon.get() {
+                Some((last_phase, sent_at))
+                    if last_phase == phase && now < sent_at + SECURE_BEACON_INTERVAL =>
+                {
+                    return Ok(());
+                }
+                _ => {}
+            }
+            self.secure_beacon.set(Some((phase, now)));
+            network.secure_network_beacon()?
+        };
+        self.network.transmit(&PDU::Beacon(beacon)).await?;
+        Ok(())
+    }
+
+    fn provision(&self, target: ProvisionTarget) {
+        if let Err(e) = self.pipeline.borrow_mut().provision(target) {
+            warn!("Unable to provision: {:?}", e);
+        }
+    }
+
     async fn loop_provisioned(&self) -> Result<Option<State>, DeviceError> {
         debug!("State: provisioned");
         self.transmit_provisioned_beacon().await.ok();
+        self.transmit_secure_beacon().await.ok();
+        let provisioner_deadline = self.pipeline.borrow().provisioner_deadline();
+        if matches!(provisioner_deadline, Some(deadline) if Instant::now() >= deadline) {
+            self.pipeline
+                .borrow_mut()
+                .retransmit_provisioning(self)
+                .await
+                .ok();
+        }
 
         let mut deadline = self.deadline.borrow_mut();
         let deadline_fut = deadline.next();
@@ -264,6 +556,10 @@ where
                     self.publish(publish).await?;
                     Ok(None)
                 }
+                OutboundEvent::Configuration(request) => {
+                    self.transmit_configuration_request(request).await?;
+                    Ok(None)
+                }
             },
             Either4::Third(expiration) => {
                 self.pipeline
@@ -272,6 +568,12 @@ where
                     .await?;
                 Ok(None)
             }
+            Either4::Fourth(_) => {
+                if self.health.attention_expired() {
+                    self.elements.borrow_mut().attention(0);
+                }
+                Ok(None)
+            }
             _ => Ok(None),
         }
     }
@@ -304,6 +606,8 @@ where
         let ctx: AppElementsContext<'a> = AppElementsContext {
             access_sender: self.outbound.access.sender(),
             sender: self.outbound.publish.sender(),
+            configuration_client: &self.outbound.configuration,
+            health: &self.health,
             address: self.address().unwrap(),
         };
         self.elements.borrow_mut().connect(ctx);
@@ -369,6 +673,17 @@ where
                         self.configuration_manager.node_reset().await;
                     }
                     MeshNodeMessage::Shutdown => {}
+                    MeshNodeMessage::StartNodeIdentity => {
+                        if let Err(e) = self.start_primary_node_identity() {
+                            warn!("Unable to start node identity: {:?}", e);
+                        }
+                    }
+                    MeshNodeMessage::ProvisionDevice(uuid) => {
+                        self.provision(ProvisionTarget::Device(uuid));
+                    }
+                    MeshNodeMessage::ProvisionAnyDevice => {
+                        self.provision(ProvisionTarget::Any);
+                    }
                 },
             }
         }
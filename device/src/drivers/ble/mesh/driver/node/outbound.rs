@@ -1,4 +1,7 @@
 use crate::drivers::ble::mesh::address::UnicastAddress;
+use crate::drivers::ble::mesh::driver::node::configuration_client::{
+    ConfigurationClientChannel, ConfigurationRequest,
+};
 use crate::drivers::ble::mesh::driver::node::NodeMutex;
 use crate::drivers::ble::mesh::driver::pipeline::provisioned::network::transmit::ModelKey;
 use crate::drivers::ble::mesh::model::ModelIdentifier;
@@ -81,6 +84,7 @@ impl<'a> OutboundPublishChannel<'a> {
 pub struct Outbound<'a> {
     pub(crate) access: OutboundAccessChannel<'a>,
     pub(crate) publish: OutboundPublishChannel<'a>,
+    pub(crate) configuration: ConfigurationClientChannel,
 }
 
 impl<'a> Default for Outbound<'a> {
@@ -88,6 +92,7 @@ impl<'a> Default for Outbound<'a> {
         Self {
             access: OutboundAccessChannel::new(),
             publish: OutboundPublishChannel::new(),
+            configuration: ConfigurationClientChannel::new(),
         }
     }
 }
@@ -95,16 +100,19 @@ impl<'a> Default for Outbound<'a> {
 pub enum OutboundEvent {
     Access(AccessMessage),
     Publish(OutboundPublishMessage),
+    Configuration(ConfigurationRequest),
 }
 
 impl<'a> Outbound<'a> {
     pub async fn next(&self) -> OutboundEvent {
         let access_fut = self.access.next();
         let publish_fut = self.publish.next();
+        let configuration_fut = self.configuration.next();
 
-        match select(access_fut, publish_fut).await {
-            Either::First(access) => OutboundEvent::Access(access),
-            Either::Second(publish) => OutboundEvent::Publish(publish),
+        match select(select(access_fut, publish_fut), configuration_fut).await {
+            Either::First(Either::First(access)) => OutboundEvent::Access(access),
+            Either::First(Either::Second(publish)) => OutboundEvent::Publish(publish),
+            Either::Second(request) => OutboundEvent::Configuration(request),
         }
     }
 }
@@ -58,6 +58,8 @@ impl From<&Publication> for PublishRetransmitDetails {
 pub trait MeshContext {
     fn uuid(&self) -> Uuid;
 
+    fn rng_fill(&self, dest: &mut [u8]);
+
     fn network_retransmit(&self) -> NetworkRetransmitDetails;
 
     type TransmitFuture<'m>: Future<Output = Result<(), DeviceError>>
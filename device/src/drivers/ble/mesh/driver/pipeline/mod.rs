@@ -7,18 +7,26 @@ use crate::drivers::ble::mesh::driver::pipeline::provisioned::network::transmit:
 use crate::drivers::ble::mesh::driver::pipeline::provisioned::{
     ProvisionedContext, ProvisionedPipeline,
 };
-use crate::drivers::ble::mesh::driver::pipeline::unprovisioned::provisionable::UnprovisionedContext;
+use crate::drivers::ble::mesh::driver::pipeline::provisioner::{
+    ProvisionTarget, ProvisionerContext,
+};
+use crate::drivers::ble::mesh::driver::pipeline::unprovisioned::provisionable::{
+    OobRequest, UnprovisionedContext,
+};
 use crate::drivers::ble::mesh::driver::pipeline::unprovisioned::UnprovisionedPipeline;
 use crate::drivers::ble::mesh::driver::DeviceError;
 use crate::drivers::ble::mesh::interface::PDU;
+use crate::drivers::ble::mesh::oob::OobValue;
 use crate::drivers::ble::mesh::pdu::access::AccessMessage;
 use crate::drivers::ble::mesh::provisioning::Capabilities;
+use embassy::time::Instant;
 
 pub mod mesh;
 pub mod provisioned;
+pub mod provisioner;
 pub mod unprovisioned;
 
-pub trait PipelineContext: UnprovisionedContext + ProvisionedContext {}
+pub trait PipelineContext: UnprovisionedContext + ProvisionedContext + ProvisionerContext {}
 
 pub struct Pipeline {
     capabilities: Capabilities,
@@ -39,20 +47,26 @@ impl PipelineInner {
     ) -> Result<Option<State>, DeviceError> {
         match self {
             PipelineInner::Unconfigured => Ok(None),
-            PipelineInner::Unprovisioned(inner) => {
-                if let PDU::Provisioning(pdu) = message {
-                    inner.process_inbound(ctx, pdu).await
-                } else {
-                    Ok(None)
+            PipelineInner::Unprovisioned(inner) => match message {
+                PDU::Provisioning(pdu) => inner.process_inbound(ctx, pdu).await,
+                PDU::LinkClose(_) => Ok(inner.link_closed()),
+                _ => Ok(None),
+            },
+            PipelineInner::Provisioned(inner) => match message {
+                PDU::Network(ref mut pdu) => inner.process_inbound(ctx, pdu).await,
+                PDU::Beacon(ref beacon) => inner.process_beacon(ctx, beacon).await,
+                PDU::ProxyConfiguration(ref pdu) => {
+                    inner.process_proxy_configuration(ctx, pdu).await
                 }
-            }
-            PipelineInner::Provisioned(inner) => {
-                if let PDU::Network(ref mut pdu) = message {
-                    inner.process_inbound(ctx, pdu).await
-                } else {
+                PDU::UnprovisionedBeacon(ref beacon) => {
+                    inner.process_unprovisioned_beacon(ctx, beacon).await
+                }
+                PDU::Provisioning(pdu) => inner.process_provisioning(ctx, pdu).await,
+                PDU::LinkClose(_) => {
+                    // only devices being provisioned are sent a link close.
                     Ok(None)
                 }
-            }
+            },
         }
     }
 
@@ -74,6 +88,68 @@ impl PipelineInner {
         }
     }
 
+    fn take_oob_request(&mut self) -> Option<OobRequest> {
+        match self {
+            PipelineInner::Unprovisioned(inner) => inner.take_oob_request(),
+            _ => None,
+        }
+    }
+
+    async fn oob_input<C: PipelineContext>(
+        &mut self,
+        ctx: &C,
+        value: OobValue,
+    ) -> Result<(), DeviceError> {
+        match self {
+            PipelineInner::Unprovisioned(inner) => inner.oob_input(ctx, value).await,
+            _ => Err(DeviceError::InvalidState),
+        }
+    }
+
+    fn provisioning_timeout(&self) -> Option<Instant> {
+        match self {
+            PipelineInner::Unprovisioned(inner) => inner.timeout(),
+            _ => None,
+        }
+    }
+
+    async fn expire_provisioning<C: PipelineContext>(
+        &mut self,
+        ctx: &C,
+    ) -> Result<Option<State>, DeviceError> {
+        match self {
+            PipelineInner::Unprovisioned(inner) => inner.expire(ctx).await,
+            _ => Ok(None),
+        }
+    }
+
+    fn provision(&mut self, target: ProvisionTarget) -> Result<(), DeviceError> {
+        match self {
+            PipelineInner::Provisioned(inner) => {
+                inner.provision(target);
+                Ok(())
+            }
+            _ => Err(DeviceError::NotProvisioned),
+        }
+    }
+
+    fn provisioner_deadline(&self) -> Option<Instant> {
+        match self {
+            PipelineInner::Provisioned(inner) => inner.provisioning_deadline(),
+            _ => None,
+        }
+    }
+
+    async fn retransmit_provisioning<C: PipelineContext>(
+        &mut self,
+        ctx: &C,
+    ) -> Result<(), DeviceError> {
+        match self {
+            PipelineInner::Provisioned(inner) => inner.retransmit_provisioning(ctx).await,
+            _ => Ok(()),
+        }
+    }
+
     pub async fn retransmit<C: PipelineContext>(
         &mut self,
         ctx: &C,
@@ -147,6 +223,48 @@ impl Pipeline {
             .await
     }
 
+    pub(crate) fn take_oob_request(&mut self) -> Option<OobRequest> {
+        self.inner.take_oob_request()
+    }
+
+    pub(crate) async fn oob_input<C: PipelineContext>(
+        &mut self,
+        ctx: &C,
+        value: OobValue,
+    ) -> Result<(), DeviceError> {
+        self.inner.oob_input(ctx, value).await
+    }
+
+    /// When the provisioning session of this device times out, if one is in progress.
+    pub(crate) fn provisioning_timeout(&self) -> Option<Instant> {
+        self.inner.provisioning_timeout()
+    }
+
+    /// Abandon the provisioning session of this device once it timed out.
+    pub(crate) async fn expire_provisioning<C: PipelineContext>(
+        &mut self,
+        ctx: &C,
+    ) -> Result<Option<State>, DeviceError> {
+        self.inner.expire_provisioning(ctx).await
+    }
+
+    /// Provision other devices onto this node's network.
+    pub(crate) fn provision(&mut self, target: ProvisionTarget) -> Result<(), DeviceError> {
+        self.inner.provision(target)
+    }
+
+    /// When provisioning other devices next needs retransmitting or timing out.
+    pub(crate) fn provisioner_deadline(&self) -> Option<Instant> {
+        self.inner.provisioner_deadline()
+    }
+
+    pub(crate) async fn retransmit_provisioning<C: PipelineContext>(
+        &mut self,
+        ctx: &C,
+    ) -> Result<(), DeviceError> {
+        self.inner.retransmit_provisioning(ctx).await
+    }
+
     pub async fn retransmit<C: PipelineContext>(
         &mut self,
         ctx: &C,
@@ -9,12 +9,12 @@ use crate::drivers::ble::mesh::pdu::network::CleartextNetworkPDU;
 use ccm::aead::Buffer;
 
 use self::inbound_segmentation::InboundSegmentation;
-use crate::drivers::ble::mesh::address::{Address, LabelUuid};
+use crate::drivers::ble::mesh::address::{Address, LabelUuid, UnicastAddress};
 use crate::drivers::ble::mesh::app::ApplicationKeyIdentifier;
+use crate::drivers::ble::mesh::crypto;
 use crate::drivers::ble::mesh::crypto::nonce::{ApplicationNonce, DeviceNonce};
 use crate::drivers::ble::mesh::driver::pipeline::provisioned::lower::outbound_segmentation::OutboundSegmentation;
 use crate::drivers::ble::mesh::driver::pipeline::provisioned::network::authentication::AuthenticationContext;
-use crate::drivers::ble::mesh::driver::pipeline::provisioned::network::replay_cache::ReplayCache;
 use crate::drivers::ble::mesh::pdu::upper::{UpperAccess, UpperPDU};
 use core::future::Future;
 use embassy::time::Instant;
@@ -40,6 +40,9 @@ pub trait LowerContext: AuthenticationContext {
         mic: &mut [u8],
     ) -> Result<(), DeviceError>;
 
+    /// Device key of a remote node currently being configured by this node.
+    fn remote_device_key(&self, addr: &UnicastAddress) -> Option<[u8; 16]>;
+
     fn encrypt_application_key(
         &self,
         aid: ApplicationKeyIdentifier,
@@ -64,6 +67,17 @@ pub trait LowerContext: AuthenticationContext {
 
     fn next_sequence<'m>(&'m self) -> Self::NextSequenceFuture<'m>;
 
+    /// Whether a message was already seen, according to the replay protection list.
+    fn is_replay(&self, seq: u32, src: UnicastAddress) -> bool;
+
+    type AcceptedFuture<'m>: Future<Output = Result<(), DeviceError>> + 'm
+    where
+        Self: 'm;
+
+    /// Record a message accepted for this node in the replay protection list,
+    /// before it is processed.
+    fn accepted<'m>(&'m self, seq: u32, src: UnicastAddress) -> Self::AcceptedFuture<'m>;
+
     fn default_ttl(&self) -> u8;
 
     fn has_any_subscription(&self, dst: &Address) -> bool;
@@ -73,7 +87,6 @@ pub trait LowerContext: AuthenticationContext {
 }
 
 pub struct Lower {
-    replay_cache: ReplayCache,
     inbound_segmentation: InboundSegmentation,
     outbound_segmentation: OutboundSegmentation,
 }
@@ -81,7 +94,6 @@ pub struct Lower {
 impl Default for Lower {
     fn default() -> Self {
         Self {
-            replay_cache: Default::default(),
             inbound_segmentation: Default::default(),
             outbound_segmentation: Default::default(),
         }
@@ -102,6 +114,7 @@ impl Lower {
         trans_mic: &[u8],
         mut payload: Vec<u8, 380>,
     ) -> Result<Option<UpperPDU>, DeviceError> {
+        let mut remote_device_key = None;
         let dst = if access.akf {
             // decrypt with aid key
             let nonce = ApplicationNonce::new(
@@ -162,7 +175,25 @@ impl Lower {
                 ctx.iv_index()
                     .ok_or(DeviceError::CryptoError("inbound device access pdu"))?,
             );
-            ctx.decrypt_device_key(nonce, &mut payload, &trans_mic)?;
+            // responses to our own configuration requests use the remote node's key.
+            if let Some(device_key) = ctx.remote_device_key(&pdu.src) {
+                let mut temp_payload = payload.clone();
+                if crypto::aes_ccm_decrypt_detached(
+                    &device_key,
+                    &*nonce,
+                    &mut temp_payload,
+                    &trans_mic,
+                    None,
+                )
+                .is_ok()
+                {
+                    payload = temp_payload;
+                    remote_device_key.replace(device_key);
+                }
+            }
+            if remote_device_key.is_none() {
+                ctx.decrypt_device_key(nonce, &mut payload, &trans_mic)?;
+            }
             pdu.dst
         };
         Ok(Some(UpperPDU::Access(UpperAccess {
@@ -172,12 +203,26 @@ impl Lower {
             nid: pdu.nid,
             akf: access.akf,
             aid: access.aid,
+            remote_device_key,
             src: pdu.src,
             dst,
             payload,
         })))
     }
 
+    // Only sources of messages meant for this node take a place in the replay
+    // protection list, which is stored before the message is processed.
+    async fn accepted<C: LowerContext>(
+        ctx: &C,
+        pdu: &CleartextNetworkPDU,
+        upper: &Option<UpperPDU>,
+    ) -> Result<(), DeviceError> {
+        if upper.is_some() && ctx.is_locally_relevant(&pdu.dst) {
+            ctx.accepted(pdu.seq, pdu.src).await?;
+        }
+        Ok(())
+    }
+
     pub async fn process_inbound<C: LowerContext>(
         &mut self,
         ctx: &C,
@@ -192,10 +237,7 @@ impl Lower {
                         let payload = Vec::from_slice(payload)
                             .map_err(|_| DeviceError::InsufficientBuffer)?;
 
-                        if self
-                            .replay_cache
-                            .has_seen(ctx.iv_index().unwrap_or(0), pdu.seq, pdu.src)
-                        {
+                        if ctx.is_replay(pdu.seq, pdu.src) {
                             return Ok((None, None));
                         }
 
@@ -208,6 +250,7 @@ impl Lower {
                             trans_mic,
                             payload,
                         )?;
+                        Self::accepted(ctx, pdu, &upper).await?;
                         Ok((None, upper))
                     }
                     LowerAccessMessage::Segmented {
@@ -275,17 +318,14 @@ impl Lower {
                                 *seq_zero,
                             );
 
-                            if self.replay_cache.has_seen(
-                                ctx.iv_index().unwrap_or(0),
-                                pdu.seq,
-                                pdu.src,
-                            ) {
+                            if ctx.is_replay(pdu.seq, pdu.src) {
                                 return Ok((None, None));
                             }
 
                             let upper = self.decrypt_payload(
                                 ctx, pdu, access, *szmic, seq_auth, trans_mic, payload,
                             )?;
+                            Self::accepted(ctx, pdu, &upper).await?;
                             Ok((Some(ack), upper))
                         } else {
                             Ok((Some(ack), None))
@@ -376,7 +416,18 @@ impl Lower {
                             .ok_or(DeviceError::CryptoError("device nonce"))?,
                     );
                     let mut trans_mic = [0; 4];
-                    ctx.encrypt_device_key(nonce, &mut payload, &mut trans_mic)?;
+                    if let Some(device_key) = access.remote_device_key {
+                        crypto::aes_ccm_encrypt_detached(
+                            &device_key,
+                            &*nonce,
+                            &mut payload,
+                            &mut trans_mic,
+                            None,
+                        )
+                        .map_err(|_| DeviceError::CryptoError("encrypt remote device key"))?;
+                    } else {
+                        ctx.encrypt_device_key(nonce, &mut payload, &mut trans_mic)?;
+                    }
                     payload
                         .extend_from_slice(&trans_mic)
                         .map_err(|_| DeviceError::InsufficientBuffer)?;
@@ -1,3 +1,4 @@
+use crate::drivers::ble::mesh::beacon::{SecureNetworkBeacon, UnprovisionedDeviceBeacon};
 use crate::drivers::ble::mesh::driver::node::deadline::Expiration;
 use crate::drivers::ble::mesh::driver::node::State;
 use crate::drivers::ble::mesh::driver::pipeline::mesh::{
@@ -17,11 +18,17 @@ use crate::drivers::ble::mesh::driver::pipeline::provisioned::network::transmit:
 };
 use crate::drivers::ble::mesh::driver::pipeline::provisioned::network::NetworkContext;
 use crate::drivers::ble::mesh::driver::pipeline::provisioned::upper::{Upper, UpperContext};
+use crate::drivers::ble::mesh::driver::pipeline::provisioner::{
+    ProvisionTarget, ProvisionerPipeline,
+};
 use crate::drivers::ble::mesh::driver::pipeline::PipelineContext;
 use crate::drivers::ble::mesh::driver::DeviceError;
 use crate::drivers::ble::mesh::interface::PDU;
 use crate::drivers::ble::mesh::pdu::access::AccessMessage;
 use crate::drivers::ble::mesh::pdu::network::ObfuscatedAndEncryptedNetworkPDU;
+use crate::drivers::ble::mesh::pdu::proxy::CleartextProxyConfigurationPDU;
+use crate::drivers::ble::mesh::provisioning::ProvisioningPDU;
+use embassy::time::Instant;
 use futures::{join, pin_mut};
 
 pub mod access;
@@ -48,6 +55,7 @@ pub(crate) struct ProvisionedPipeline {
     relay: Relay,
     lower: Lower,
     upper: Upper,
+    provisioner: ProvisionerPipeline,
 }
 
 impl ProvisionedPipeline {
@@ -59,6 +67,7 @@ impl ProvisionedPipeline {
             relay: Default::default(),
             lower: Default::default(),
             upper: Default::default(),
+            provisioner: Default::default(),
         }
     }
 
@@ -68,6 +77,9 @@ impl ProvisionedPipeline {
         pdu: &mut ObfuscatedAndEncryptedNetworkPDU,
     ) -> Result<Option<State>, DeviceError> {
         if let Some(inboud_pdu) = self.authentication.process_inbound(ctx, pdu)? {
+            if pdu.from_proxy_client {
+                ctx.proxy_client_source(&inboud_pdu.src.into());
+            }
             let result = self.lower.process_inbound(ctx, &inboud_pdu).await;
             let mut error = None;
             match result {
@@ -112,6 +124,77 @@ impl ProvisionedPipeline {
         Ok(None)
     }
 
+    pub(crate) async fn process_beacon<C: PipelineContext>(
+        &mut self,
+        ctx: &C,
+        beacon: &SecureNetworkBeacon,
+    ) -> Result<Option<State>, DeviceError> {
+        ctx.secure_network_beacon(beacon).await?;
+        Ok(None)
+    }
+
+    pub(crate) async fn process_unprovisioned_beacon<C: PipelineContext>(
+        &mut self,
+        ctx: &C,
+        beacon: &UnprovisionedDeviceBeacon,
+    ) -> Result<Option<State>, DeviceError> {
+        self.provisioner.process_beacon(ctx, beacon).await?;
+        Ok(None)
+    }
+
+    pub(crate) async fn process_provisioning<C: PipelineContext>(
+        &mut self,
+        ctx: &C,
+        pdu: ProvisioningPDU,
+    ) -> Result<Option<State>, DeviceError> {
+        self.provisioner.process_inbound(ctx, pdu).await?;
+        Ok(None)
+    }
+
+    pub(crate) fn provision(&mut self, target: ProvisionTarget) {
+        self.provisioner.provision(target);
+    }
+
+    pub(crate) fn provisioning_deadline(&self) -> Option<Instant> {
+        self.provisioner.deadline()
+    }
+
+    pub(crate) async fn retransmit_provisioning<C: PipelineContext>(
+        &mut self,
+        ctx: &C,
+    ) -> Result<(), DeviceError> {
+        self.provisioner.retransmit(ctx).await
+    }
+
+    pub(crate) async fn process_proxy_configuration<C: PipelineContext>(
+        &mut self,
+        ctx: &C,
+        pdu: &ObfuscatedAndEncryptedNetworkPDU,
+    ) -> Result<Option<State>, DeviceError> {
+        if let Some(inbound) = self
+            .authentication
+            .process_inbound_proxy_configuration(ctx, pdu)?
+        {
+            if let Some(status) = ctx.configure_proxy_filter(&inbound.message) {
+                let reply = CleartextProxyConfigurationPDU {
+                    network_key: inbound.network_key,
+                    ivi: inbound.ivi,
+                    nid: inbound.nid,
+                    seq: ctx.next_sequence().await?,
+                    src: ctx.primary_unicast_address()?,
+                    message: status,
+                };
+                if let Some(reply) = self
+                    .authentication
+                    .process_outbound_proxy_configuration(ctx, &reply)?
+                {
+                    ctx.transmit(&PDU::ProxyConfiguration(reply)).await?;
+                }
+            }
+        }
+        Ok(None)
+    }
+
     pub(crate) async fn process_outbound<C: PipelineContext>(
         &mut self,
         ctx: &C,
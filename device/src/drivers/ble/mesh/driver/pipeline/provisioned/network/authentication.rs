@@ -1,6 +1,6 @@
 use crate::drivers::ble::mesh::address::{Address, UnicastAddress};
-use crate::drivers::ble::mesh::config::network::NetworkDetails;
-use crate::drivers::ble::mesh::crypto::nonce::NetworkNonce;
+use crate::drivers::ble::mesh::config::network::NetworkKeyHandle;
+use crate::drivers::ble::mesh::crypto::nonce::{NetworkNonce, ProxyNonce};
 use crate::drivers::ble::mesh::crypto::{aes_ccm_decrypt_detached, aes_ccm_encrypt_detached, e};
 use crate::drivers::ble::mesh::driver::pipeline::mesh::MeshContext;
 use crate::drivers::ble::mesh::driver::DeviceError;
@@ -9,12 +9,18 @@ use crate::drivers::ble::mesh::pdu::lower::LowerPDU;
 use crate::drivers::ble::mesh::pdu::network::{
     CleartextNetworkPDU, ObfuscatedAndEncryptedNetworkPDU,
 };
+use crate::drivers::ble::mesh::pdu::proxy::{
+    CleartextProxyConfigurationPDU, ProxyConfigurationMessage,
+};
 use heapless::Vec;
 
+/// Proxy configuration PDUs are always control PDUs with a TTL of zero.
+const PROXY_CONFIGURATION_CTL_TTL: u8 = 0b10000000;
+
 pub trait AuthenticationContext: MeshContext {
     fn iv_index(&self) -> Option<u32>;
 
-    fn find_network_keys_by_nid(&self, nid: u8) -> Result<Vec<NetworkDetails, 10>, DeviceError>;
+    fn find_network_keys_by_nid(&self, nid: u8) -> Result<Vec<NetworkKeyHandle, 10>, DeviceError>;
 }
 
 pub struct Authentication {}
@@ -32,42 +38,13 @@ impl Authentication {
         pdu: &mut ObfuscatedAndEncryptedNetworkPDU,
     ) -> Result<Option<CleartextNetworkPDU>, DeviceError> {
         if let Some(iv_index) = ctx.iv_index() {
-            let privacy_plaintext = Self::privacy_plaintext(iv_index, &pdu.encrypted_and_mic);
             let networks = ctx.find_network_keys_by_nid(pdu.nid)?;
+            let attempted = !networks.is_empty();
             for network_key in networks {
-                let pecb = e(&network_key.privacy_key, privacy_plaintext)
-                    .map_err(|_| DeviceError::InvalidKeyLength)?;
-
-                let unobfuscated = Self::xor(pecb, pdu.obfuscated);
-                let ctl = (unobfuscated[0] & 0b10000000) != 0;
-
-                let seq =
-                    u32::from_be_bytes([0, unobfuscated[1], unobfuscated[2], unobfuscated[3]]);
-
-                let nonce = NetworkNonce::new(
-                    unobfuscated[0],
-                    seq,
-                    [unobfuscated[4], unobfuscated[5]],
-                    iv_index,
-                );
-
-                let encrypted_len = pdu.encrypted_and_mic.len();
-
-                let (payload, mic) = if !ctl {
-                    // 32 bit mic
-                    pdu.encrypted_and_mic.split_at_mut(encrypted_len - 4)
-                } else {
-                    // 64 bit mic
-                    pdu.encrypted_and_mic.split_at_mut(encrypted_len - 8)
-                };
-
-                if let Ok(_) = aes_ccm_decrypt_detached(
-                    &network_key.encryption_key,
-                    &nonce.into_bytes(),
-                    payload,
-                    mic,
-                    None,
-                ) {
+                if let Some((unobfuscated, payload)) =
+                    Self::decrypt(iv_index, &network_key, pdu, false)?
+                {
+                    let ctl = (unobfuscated[0] & 0b10000000) != 0;
                     let ttl = unobfuscated[0] & 0b01111111;
                     let seq =
                         u32::from_be_bytes([0, unobfuscated[1], unobfuscated[2], unobfuscated[3]]);
@@ -80,7 +57,7 @@ impl Authentication {
                     let transport_pdu = lower::LowerPDU::parse(ctl, &payload[2..])?;
 
                     return Ok(Some(CleartextNetworkPDU {
-                        network_key: network_key.into(),
+                        network_key,
                         ivi: pdu.ivi,
                         nid: pdu.nid,
                         ttl,
@@ -89,10 +66,52 @@ impl Authentication {
                         dst,
                         transport_pdu,
                     }));
-                } else {
-                    return Err(DeviceError::CryptoError("inbound network pdu"));
                 }
             }
+            if attempted {
+                return Err(DeviceError::CryptoError("inbound network pdu"));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn process_inbound_proxy_configuration<C: AuthenticationContext>(
+        &mut self,
+        ctx: &C,
+        pdu: &ObfuscatedAndEncryptedNetworkPDU,
+    ) -> Result<Option<CleartextProxyConfigurationPDU>, DeviceError> {
+        if let Some(iv_index) = ctx.iv_index() {
+            let networks = ctx.find_network_keys_by_nid(pdu.nid)?;
+            let attempted = !networks.is_empty();
+            for network_key in networks {
+                if let Some((unobfuscated, payload)) =
+                    Self::decrypt(iv_index, &network_key, pdu, true)?
+                {
+                    let seq =
+                        u32::from_be_bytes([0, unobfuscated[1], unobfuscated[2], unobfuscated[3]]);
+
+                    let src = UnicastAddress::parse([unobfuscated[4], unobfuscated[5]])
+                        .map_err(|_| DeviceError::InvalidSrcAddress)?;
+
+                    if Address::parse([payload[0], payload[1]]) != Address::Unassigned {
+                        return Err(DeviceError::InvalidDstAddress);
+                    }
+
+                    let message = ProxyConfigurationMessage::parse(&payload[2..])?;
+
+                    return Ok(Some(CleartextProxyConfigurationPDU {
+                        network_key,
+                        ivi: pdu.ivi,
+                        nid: pdu.nid,
+                        seq,
+                        src,
+                        message,
+                    }));
+                }
+            }
+            if attempted {
+                return Err(DeviceError::CryptoError("inbound proxy configuration pdu"));
+            }
         }
         Ok(None)
     }
@@ -112,72 +131,188 @@ impl Authentication {
 
             let nonce = NetworkNonce::new(ctl_ttl, pdu.seq, pdu.src.as_bytes(), iv_index);
 
-            let mut encrypted_and_mic = Vec::new();
-            encrypted_and_mic
+            let mut payload = Vec::new();
+            payload
                 .extend_from_slice(&pdu.dst.as_bytes())
                 .map_err(|_| DeviceError::InsufficientBuffer)?;
 
-            pdu.transport_pdu.emit(&mut encrypted_and_mic)?;
-
-            if ctl {
-                let mut mic = [0; 8];
-
-                aes_ccm_encrypt_detached(
-                    &pdu.network_key.encryption_key,
-                    &nonce.into_bytes(),
-                    &mut encrypted_and_mic,
-                    &mut mic,
-                    None,
-                )
-                .map_err(|_| DeviceError::CryptoError("outbound network ctl pdu"))?;
-                encrypted_and_mic
-                    .extend_from_slice(&mic)
-                    .map_err(|_| DeviceError::InsufficientBuffer)?;
-            } else {
-                let mut mic = [0; 4];
-
-                aes_ccm_encrypt_detached(
-                    &pdu.network_key.encryption_key,
-                    &nonce.into_bytes(),
-                    &mut encrypted_and_mic,
-                    &mut mic,
-                    None,
-                )
-                .map_err(|_| DeviceError::CryptoError("outbound network access pdu"))?;
-                encrypted_and_mic
-                    .extend_from_slice(&mic)
-                    .map_err(|_| DeviceError::InsufficientBuffer)?;
-            }
-
-            let privacy_plaintext = Self::privacy_plaintext(iv_index, &encrypted_and_mic);
+            pdu.transport_pdu.emit(&mut payload)?;
 
-            let pecb = e(&pdu.network_key.privacy_key, privacy_plaintext)
-                .map_err(|_| DeviceError::InvalidKeyLength)?;
+            let (obfuscated, encrypted_and_mic) = Self::encrypt(
+                iv_index,
+                &pdu.network_key,
+                ctl_ttl,
+                pdu.seq,
+                pdu.src,
+                nonce.into_bytes(),
+                payload,
+            )?;
 
-            let mut unobfuscated = [0; 6];
-            unobfuscated[0] = ctl_ttl;
+            Ok(Some(ObfuscatedAndEncryptedNetworkPDU {
+                ivi: pdu.ivi,
+                nid: pdu.nid,
+                obfuscated,
+                encrypted_and_mic,
+                dst: Some(pdu.dst),
+                from_proxy_client: false,
+            }))
+        } else {
+            Err(DeviceError::NotProvisioned)
+        }
+    }
 
-            let seq_bytes = pdu.seq.to_be_bytes();
-            unobfuscated[1] = seq_bytes[1];
-            unobfuscated[2] = seq_bytes[2];
-            unobfuscated[3] = seq_bytes[3];
+    pub fn process_outbound_proxy_configuration<C: AuthenticationContext>(
+        &mut self,
+        ctx: &C,
+        pdu: &CleartextProxyConfigurationPDU,
+    ) -> Result<Option<ObfuscatedAndEncryptedNetworkPDU>, DeviceError> {
+        if let Some(iv_index) = ctx.iv_index() {
+            let nonce = ProxyNonce::new(pdu.seq, pdu.src.as_bytes(), iv_index);
 
-            let src_bytes = pdu.src.as_bytes();
-            unobfuscated[4] = src_bytes[0];
-            unobfuscated[5] = src_bytes[1];
-            let obfuscated = Self::xor(pecb, unobfuscated);
+            let mut payload = Vec::new();
+            payload
+                .extend_from_slice(&Address::Unassigned.as_bytes())
+                .map_err(|_| DeviceError::InsufficientBuffer)?;
+            pdu.message.emit(&mut payload)?;
+
+            let (obfuscated, encrypted_and_mic) = Self::encrypt(
+                iv_index,
+                &pdu.network_key,
+                PROXY_CONFIGURATION_CTL_TTL,
+                pdu.seq,
+                pdu.src,
+                nonce.into_bytes(),
+                payload,
+            )?;
 
             Ok(Some(ObfuscatedAndEncryptedNetworkPDU {
                 ivi: pdu.ivi,
                 nid: pdu.nid,
                 obfuscated,
                 encrypted_and_mic,
+                dst: None,
+                from_proxy_client: false,
             }))
         } else {
             Err(DeviceError::NotProvisioned)
         }
     }
 
+    /// Deobfuscate and decrypt a PDU with a single candidate key, returning the
+    /// deobfuscated header and the decrypted payload, or `None` if the key does not match.
+    fn decrypt(
+        iv_index: u32,
+        network_key: &NetworkKeyHandle,
+        pdu: &ObfuscatedAndEncryptedNetworkPDU,
+        proxy_configuration: bool,
+    ) -> Result<Option<([u8; 6], Vec<u8, 28>)>, DeviceError> {
+        let privacy_plaintext = Self::privacy_plaintext(iv_index, &pdu.encrypted_and_mic);
+        let pecb = e(&network_key.privacy_key, privacy_plaintext)
+            .map_err(|_| DeviceError::InvalidKeyLength)?;
+
+        let unobfuscated = Self::xor(pecb, pdu.obfuscated);
+        let ctl = (unobfuscated[0] & 0b10000000) != 0;
+
+        let seq = u32::from_be_bytes([0, unobfuscated[1], unobfuscated[2], unobfuscated[3]]);
+        let src = [unobfuscated[4], unobfuscated[5]];
+
+        let nonce = if proxy_configuration {
+            if unobfuscated[0] != PROXY_CONFIGURATION_CTL_TTL {
+                return Ok(None);
+            }
+            ProxyNonce::new(seq, src, iv_index).into_bytes()
+        } else {
+            NetworkNonce::new(unobfuscated[0], seq, src, iv_index).into_bytes()
+        };
+
+        // during key refresh several keys may share a nid,
+        // so decrypt a copy in case this is the wrong key.
+        let mut payload = pdu.encrypted_and_mic.clone();
+        let mic_len = if ctl {
+            // 64 bit mic
+            8
+        } else {
+            // 32 bit mic
+            4
+        };
+        if payload.len() < mic_len + 2 {
+            return Ok(None);
+        }
+        let encrypted_len = payload.len() - mic_len;
+
+        let (encrypted, mic) = payload.split_at_mut(encrypted_len);
+
+        if let Ok(_) =
+            aes_ccm_decrypt_detached(&network_key.encryption_key, &nonce, encrypted, mic, None)
+        {
+            payload.truncate(encrypted_len);
+            Ok(Some((unobfuscated, payload)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Encrypt a payload (destination address onwards) and obfuscate the header,
+    /// returning the obfuscated header and the encrypted payload with its mic.
+    fn encrypt(
+        iv_index: u32,
+        network_key: &NetworkKeyHandle,
+        ctl_ttl: u8,
+        seq: u32,
+        src: UnicastAddress,
+        nonce: [u8; 13],
+        mut encrypted_and_mic: Vec<u8, 28>,
+    ) -> Result<([u8; 6], Vec<u8, 28>), DeviceError> {
+        if (ctl_ttl & 0b10000000) != 0 {
+            let mut mic = [0; 8];
+
+            aes_ccm_encrypt_detached(
+                &network_key.encryption_key,
+                &nonce,
+                &mut encrypted_and_mic,
+                &mut mic,
+                None,
+            )
+            .map_err(|_| DeviceError::CryptoError("outbound network ctl pdu"))?;
+            encrypted_and_mic
+                .extend_from_slice(&mic)
+                .map_err(|_| DeviceError::InsufficientBuffer)?;
+        } else {
+            let mut mic = [0; 4];
+
+            aes_ccm_encrypt_detached(
+                &network_key.encryption_key,
+                &nonce,
+                &mut encrypted_and_mic,
+                &mut mic,
+                None,
+            )
+            .map_err(|_| DeviceError::CryptoError("outbound network access pdu"))?;
+            encrypted_and_mic
+                .extend_from_slice(&mic)
+                .map_err(|_| DeviceError::InsufficientBuffer)?;
+        }
+
+        let privacy_plaintext = Self::privacy_plaintext(iv_index, &encrypted_and_mic);
+
+        let pecb = e(&network_key.privacy_key, privacy_plaintext)
+            .map_err(|_| DeviceError::InvalidKeyLength)?;
+
+        let mut unobfuscated = [0; 6];
+        unobfuscated[0] = ctl_ttl;
+
+        let seq_bytes = seq.to_be_bytes();
+        unobfuscated[1] = seq_bytes[1];
+        unobfuscated[2] = seq_bytes[2];
+        unobfuscated[3] = seq_bytes[3];
+
+        let src_bytes = src.as_bytes();
+        unobfuscated[4] = src_bytes[0];
+        unobfuscated[5] = src_bytes[1];
+
+        Ok((Self::xor(pecb, unobfuscated), encrypted_and_mic))
+    }
+
     fn privacy_plaintext(iv_index: u32, encrypted_and_mic: &[u8]) -> [u8; 16] {
         let mut privacy_plaintext = [0; 16];
 
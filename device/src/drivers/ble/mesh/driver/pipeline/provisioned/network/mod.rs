@@ -1,13 +1,37 @@
+use crate::drivers::ble::mesh::address::Address;
+use crate::drivers::ble::mesh::beacon::SecureNetworkBeacon;
 use crate::drivers::ble::mesh::driver::pipeline::mesh::MeshContext;
+use crate::drivers::ble::mesh::driver::DeviceError;
+use crate::drivers::ble::mesh::pdu::proxy::ProxyConfigurationMessage;
+use core::future::Future;
 use embassy::time::Instant;
 
 pub mod authentication;
 pub mod network_message_cache;
 #[cfg(feature = "ble-mesh-relay")]
 pub mod relay;
-pub mod replay_cache;
 pub mod transmit;
 
 pub trait NetworkContext: MeshContext {
     fn network_deadline(&self, deadline: Option<Instant>);
+
+    type SecureNetworkBeaconFuture<'m>: Future<Output = Result<(), DeviceError>> + 'm
+    where
+        Self: 'm;
+
+    /// Apply any key refresh phase change signalled by a secure network beacon.
+    fn secure_network_beacon<'m>(
+        &'m self,
+        beacon: &'m SecureNetworkBeacon,
+    ) -> Self::SecureNetworkBeaconFuture<'m>;
+
+    /// Apply a proxy configuration message received from a proxy client,
+    /// returning the filter status to reply with, if any.
+    fn configure_proxy_filter(
+        &self,
+        message: &ProxyConfigurationMessage,
+    ) -> Option<ProxyConfigurationMessage>;
+
+    /// Note the source address of a network PDU received from a proxy client.
+    fn proxy_client_source(&self, src: &Address);
 }
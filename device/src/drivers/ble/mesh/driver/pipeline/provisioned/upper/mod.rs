@@ -70,6 +70,7 @@ impl Upper {
             nid: message.nid,
             akf: message.akf,
             aid: message.aid,
+            remote_device_key: message.remote_device_key,
             src: message.src,
             dst: message.dst,
             payload,
@@ -0,0 +1,453 @@
+use crate::drivers::ble::mesh::address::UnicastAddress;
+use crate::drivers::ble::mesh::beacon::UnprovisionedDeviceBeacon;
+use crate::drivers::ble::mesh::device::Uuid;
+use crate::drivers::ble::mesh::driver::node::ProvisionedDevice;
+use crate::drivers::ble::mesh::driver::pipeline::mesh::MeshContext;
+use crate::drivers::ble::mesh::driver::pipeline::provisioner::session::{Session, Step};
+use crate::drivers::ble::mesh::driver::DeviceError;
+use crate::drivers::ble::mesh::generic_provisioning::Reason;
+use crate::drivers::ble::mesh::interface::PDU;
+use crate::drivers::ble::mesh::provisioning::{ProvisioningData, ProvisioningPDU};
+use core::future::Future;
+use embassy::time::{Duration, Instant};
+
+mod session;
+
+/// Give up on a device that stops responding, as per the provisioning protocol timeout.
+const PROVISIONING_TIMEOUT: Duration = Duration::from_secs(60);
+const RETRANSMIT_INTERVAL: Duration = Duration::from_secs(1);
+
+pub trait ProvisionerContext: MeshContext {
+    /// The address to assign to the next device, past this node's own elements
+    /// and any device provisioned before.
+    fn next_address(&self) -> Result<UnicastAddress, DeviceError>;
+
+    /// Provisioning data for the primary subnet, assigning the given address.
+    fn provisioning_data(
+        &self,
+        unicast_address: UnicastAddress,
+    ) -> Result<ProvisioningData, DeviceError>;
+
+    type OpenLinkFuture<'m>: Future<Output = Result<(), DeviceError>>
+    where
+        Self: 'm;
+
+    fn open_link<'m>(&'m self, uuid: Uuid, link_id: u32) -> Self::OpenLinkFuture<'m>;
+
+    type CloseLinkFuture<'m>: Future<Output = Result<(), DeviceError>>
+    where
+        Self: 'm;
+
+    fn close_link<'m>(&'m self, reason: Reason) -> Self::CloseLinkFuture<'m>;
+
+    type RetransmitLinkFuture<'m>: Future<Output = Result<(), DeviceError>>
+    where
+        Self: 'm;
+
+    fn retransmit_link<'m>(&'m self) -> Self::RetransmitLinkFuture<'m>;
+
+    type ProvisionedFuture<'m>: Future<Output = Result<(), DeviceError>>
+    where
+        Self: 'm;
+
+    /// Keep the device and its device key, and notify the application.
+    fn provisioned<'m>(&'m self, device: ProvisionedDevice) -> Self::ProvisionedFuture<'m>;
+}
+
+/// Which unprovisioned devices to provision.
+#[derive(Copy, Clone)]
+pub(crate) enum ProvisionTarget {
+    /// The next device heard beaconing.
+    Any,
+    /// Only the device with this UUID.
+    Device(Uuid),
+}
+
+struct ActiveSession {
+    session: Session,
+    timeout: Instant,
+    retransmit: Instant,
+}
+
+/// Provisions other devices onto the network of this node, over PB-ADV.
+///
+/// Addresses are allocated sequentially after this node's own elements,
+/// and kept along with the device keys in the configuration.
+pub(crate) struct ProvisionerPipeline {
+    target: Option<ProvisionTarget>,
+    active: Option<ActiveSession>,
+}
+
+impl Default for ProvisionerPipeline {
+    fn default() -> Self {
+        Self {
+            target: None,
+            active: None,
+        }
+    }
+}
+
+impl ProvisionerPipeline {
+    pub(crate) fn provision(&mut self, target: ProvisionTarget) {
+        self.target.replace(target);
+    }
+
+    pub(crate) async fn process_beacon<C: ProvisionerContext>(
+        &mut self,
+        ctx: &C,
+        beacon: &UnprovisionedDeviceBeacon,
+    ) -> Result<(), DeviceError> {
+        if self.active.is_some() {
+            return Ok(());
+        }
+        match self.target {
+            Some(ProvisionTarget::Any) => {}
+            Some(ProvisionTarget::Device(uuid)) if uuid == beacon.uuid => {}
+            _ => return Ok(()),
+        }
+
+        let unicast_address = match ctx.next_address() {
+            Ok(address) => address,
+            Err(err) => {
+                warn!("unable to provision more devices");
+                self.target.take();
+                return Err(err);
+            }
+        };
+
+        let mut link_id = [0; 4];
+        ctx.rng_fill(&mut link_id);
+        info!("provisioning device as {:04x}", unicast_address);
+        ctx.open_link(beacon.uuid, u32::from_be_bytes(link_id))
+            .await?;
+
+        let mut session = Session::new(beacon.uuid, unicast_address);
+        let invite = session.invite()?;
+        let now = Instant::now();
+        self.active.replace(ActiveSession {
+            session,
+            timeout: now + PROVISIONING_TIMEOUT,
+            retransmit: now + RETRANSMIT_INTERVAL,
+        });
+        // held by the bearer until the link is acknowledged.
+        ctx.transmit(&PDU::Provisioning(invite)).await
+    }
+
+    pub(crate) async fn process_inbound<C: ProvisionerContext>(
+        &mut self,
+        ctx: &C,
+        pdu: ProvisioningPDU,
+    ) -> Result<(), DeviceError> {
+        let step = match &mut self.active {
+            Some(active) => active.session.process_inbound(ctx, pdu),
+            None => return Ok(()),
+        };
+
+        match step {
+            Ok(Some(Step::Transmit(pdus))) => {
+                for pdu in pdus {
+                    ctx.transmit(&PDU::Provisioning(pdu)).await?;
+                }
+                Ok(())
+            }
+            Ok(Some(Step::Complete(device))) => {
+                info!("provisioned device as {:04x}", device.unicast_address);
+                self.active.take();
+                self.target.take();
+                ctx.close_link(Reason::Success).await?;
+                ctx.provisioned(device).await
+            }
+            Ok(Some(Step::Failed(error_code))) => {
+                warn!("provisioning failed: {}", error_code as u8);
+                // don't keep trying a device that fails.
+                self.active.take();
+                self.target.take();
+                ctx.close_link(Reason::Fail).await
+            }
+            Ok(None) => Ok(()),
+            Err(err) => {
+                self.active.take();
+                self.target.take();
+                ctx.close_link(Reason::Fail).await?;
+                Err(err)
+            }
+        }
+    }
+
+    /// When `retransmit` next has something to do, if a session is active.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.active
+            .as_ref()
+            .map(|active| active.retransmit.min(active.timeout))
+    }
+
+    /// Retransmit unacknowledged provisioning PDUs, and time out unresponsive devices.
+    pub(crate) async fn retransmit<C: ProvisionerContext>(
+        &mut self,
+        ctx: &C,
+    ) -> Result<(), DeviceError> {
+        let now = Instant::now();
+        if matches!(&self.active, Some(active) if now >= active.timeout) {
+            warn!("provisioning timed out");
+            self.active.take();
+            return ctx.close_link(Reason::Timeout).await;
+        }
+        if let Some(active) = &mut self.active {
+            if now >= active.retransmit {
+                active.retransmit = now + RETRANSMIT_INTERVAL;
+                ctx.retransmit_link().await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drivers::ble::mesh::address::Address;
+    use crate::drivers::ble::mesh::driver::pipeline::mesh::NetworkRetransmitDetails;
+    use crate::drivers::ble::mesh::model::foundation::configuration::NetKeyIndex;
+    use crate::drivers::ble::mesh::provisioning::{
+        ErrorCode, Failed, IVUpdateFlag, KeyRefreshFlag,
+    };
+    use core::cell::{Cell, RefCell};
+    use futures::executor::block_on;
+    use heapless::Vec;
+
+    pub(super) const UUID: Uuid = Uuid([0x42; 16]);
+    pub(super) const NETWORK_KEY: [u8; 16] = [0x7d; 16];
+
+    /// A provisioner with a single element, at address 0x0001.
+    pub(super) struct TestContext {
+        seed: Cell<u8>,
+        full: Cell<bool>,
+        link: Cell<Option<Uuid>>,
+        closed: Cell<Option<Reason>>,
+        retransmits: Cell<u8>,
+        transmitted: RefCell<Vec<ProvisioningPDU, 8>>,
+        devices: RefCell<Vec<ProvisionedDevice, 4>>,
+    }
+
+    impl TestContext {
+        pub(super) fn new() -> Self {
+            Self {
+                seed: Cell::new(0),
+                full: Cell::new(false),
+                link: Cell::new(None),
+                closed: Cell::new(None),
+                retransmits: Cell::new(0),
+                transmitted: RefCell::new(Vec::new()),
+                devices: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl MeshContext for TestContext {
+        fn uuid(&self) -> Uuid {
+            Uuid([0x01; 16])
+        }
+
+        fn rng_fill(&self, dest: &mut [u8]) {
+            for byte in dest.iter_mut() {
+                self.seed.set(self.seed.get().wrapping_add(1));
+                *byte = self.seed.get();
+            }
+        }
+
+        fn network_retransmit(&self) -> NetworkRetransmitDetails {
+            NetworkRetransmitDetails {
+                count: 0,
+                interval: Duration::from_millis(10),
+            }
+        }
+
+        type TransmitFuture<'m> = impl Future<Output = Result<(), DeviceError>> + 'm
+        where
+            Self: 'm;
+
+        fn transmit<'m>(&'m self, pdu: &'m PDU) -> Self::TransmitFuture<'m> {
+            async move {
+                if let PDU::Provisioning(pdu) = pdu {
+                    self.transmitted
+                        .borrow_mut()
+                        .push(pdu.clone())
+                        .map_err(|_| DeviceError::InsufficientBuffer)?;
+                }
+                Ok(())
+            }
+        }
+
+        fn primary_unicast_address(&self) -> Result<UnicastAddress, DeviceError> {
+            Ok(UnicastAddress(0x0001))
+        }
+
+        fn is_local_unicast(&self, _addr: &Address) -> bool {
+            false
+        }
+    }
+
+    impl ProvisionerContext for TestContext {
+        fn next_address(&self) -> Result<UnicastAddress, DeviceError> {
+            if self.full.get() {
+                return Err(DeviceError::InsufficientBuffer);
+            }
+            Ok(match self.devices.borrow().last() {
+                Some(device) => device.unicast_address + device.number_of_elements,
+                None => UnicastAddress(0x0002),
+            })
+        }
+
+        fn provisioning_data(
+            &self,
+            unicast_address: UnicastAddress,
+        ) -> Result<ProvisioningData, DeviceError> {
+            Ok(ProvisioningData {
+                network_key: NETWORK_KEY,
+                key_index: NetKeyIndex::new(0),
+                key_refresh_flag: KeyRefreshFlag::Phase0,
+                iv_update_flag: IVUpdateFlag::NormalOperation,
+                iv_index: 0x12345678,
+                unicast_address,
+            })
+        }
+
+        type OpenLinkFuture<'m> = impl Future<Output = Result<(), DeviceError>> + 'm
+        where
+            Self: 'm;
+
+        fn open_link<'m>(&'m self, uuid: Uuid, _link_id: u32) -> Self::OpenLinkFuture<'m> {
+            async move {
+                self.link.set(Some(uuid));
+                Ok(())
+            }
+        }
+
+        type CloseLinkFuture<'m> = impl Future<Output = Result<(), DeviceError>> + 'm
+        where
+            Self: 'm;
+
+        fn close_link<'m>(&'m self, reason: Reason) -> Self::CloseLinkFuture<'m> {
+            async move {
+                self.link.set(None);
+                self.closed.set(Some(reason));
+                Ok(())
+            }
+        }
+
+        type RetransmitLinkFuture<'m> = impl Future<Output = Result<(), DeviceError>> + 'm
+        where
+            Self: 'm;
+
+        fn retransmit_link<'m>(&'m self) -> Self::RetransmitLinkFuture<'m> {
+            async move {
+                self.retransmits.set(self.retransmits.get() + 1);
+                Ok(())
+            }
+        }
+
+        type ProvisionedFuture<'m> = impl Future<Output = Result<(), DeviceError>> + 'm
+        where
+            Self: 'm;
+
+        fn provisioned<'m>(&'m self, device: ProvisionedDevice) -> Self::ProvisionedFuture<'m> {
+            async move {
+                self.devices
+                    .borrow_mut()
+                    .push(device)
+                    .map_err(|_| DeviceError::InsufficientBuffer)
+            }
+        }
+    }
+
+    fn beacon(uuid: Uuid) -> UnprovisionedDeviceBeacon {
+        let mut data = [0; 19];
+        data[1..17].copy_from_slice(&uuid.0);
+        UnprovisionedDeviceBeacon::parse(&data).unwrap()
+    }
+
+    #[test]
+    fn test_provision_target() {
+        let ctx = TestContext::new();
+        let mut provisioner = ProvisionerPipeline::default();
+
+        block_on(provisioner.process_beacon(&ctx, &beacon(UUID))).unwrap();
+        assert!(ctx.link.get().is_none());
+
+        provisioner.provision(ProvisionTarget::Device(Uuid([0x43; 16])));
+        block_on(provisioner.process_beacon(&ctx, &beacon(UUID))).unwrap();
+        assert!(ctx.link.get().is_none());
+        assert!(provisioner.deadline().is_none());
+
+        provisioner.provision(ProvisionTarget::Device(UUID));
+        block_on(provisioner.process_beacon(&ctx, &beacon(UUID))).unwrap();
+        assert!(ctx.link.get() == Some(UUID));
+        assert!(matches!(
+            &ctx.transmitted.borrow()[..],
+            [ProvisioningPDU::Invite(_)]
+        ));
+        assert!(provisioner.deadline().is_some());
+
+        // one device at a time.
+        block_on(provisioner.process_beacon(&ctx, &beacon(UUID))).unwrap();
+        assert_eq!(1, ctx.transmitted.borrow().len());
+    }
+
+    #[test]
+    fn test_failed_clears_target() {
+        let ctx = TestContext::new();
+        let mut provisioner = ProvisionerPipeline::default();
+        provisioner.provision(ProvisionTarget::Any);
+        block_on(provisioner.process_beacon(&ctx, &beacon(UUID))).unwrap();
+
+        block_on(provisioner.process_inbound(
+            &ctx,
+            ProvisioningPDU::Failed(Failed {
+                error_code: ErrorCode::UnexpectedPDU,
+            }),
+        ))
+        .unwrap();
+        assert!(matches!(ctx.closed.get(), Some(Reason::Fail)));
+        assert!(provisioner.deadline().is_none());
+
+        // not retried until asked again.
+        block_on(provisioner.process_beacon(&ctx, &beacon(UUID))).unwrap();
+        assert!(ctx.link.get().is_none());
+    }
+
+    #[test]
+    fn test_no_address_left() {
+        let ctx = TestContext::new();
+        ctx.full.set(true);
+        let mut provisioner = ProvisionerPipeline::default();
+        provisioner.provision(ProvisionTarget::Any);
+
+        assert!(block_on(provisioner.process_beacon(&ctx, &beacon(UUID))).is_err());
+        assert!(ctx.link.get().is_none());
+        assert!(provisioner.target.is_none());
+    }
+
+    #[test]
+    fn test_retransmit_and_timeout() {
+        let ctx = TestContext::new();
+        let mut provisioner = ProvisionerPipeline::default();
+        provisioner.provision(ProvisionTarget::Any);
+        block_on(provisioner.process_beacon(&ctx, &beacon(UUID))).unwrap();
+
+        // nothing is due yet.
+        block_on(provisioner.retransmit(&ctx)).unwrap();
+        assert_eq!(0, ctx.retransmits.get());
+
+        let now = Instant::now();
+        provisioner.active.as_mut().unwrap().retransmit = now;
+        assert_eq!(Some(now), provisioner.deadline());
+        block_on(provisioner.retransmit(&ctx)).unwrap();
+        assert_eq!(1, ctx.retransmits.get());
+        assert!(provisioner.deadline().unwrap() > now);
+
+        provisioner.active.as_mut().unwrap().timeout = Instant::now();
+        block_on(provisioner.retransmit(&ctx)).unwrap();
+        assert!(matches!(ctx.closed.get(), Some(Reason::Timeout)));
+        assert!(provisioner.deadline().is_none());
+    }
+}
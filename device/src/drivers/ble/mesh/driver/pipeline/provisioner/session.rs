@@ -0,0 +1,472 @@
+use crate::drivers::ble::mesh::address::UnicastAddress;
+use crate::drivers::ble::mesh::crypto;
+use crate::drivers::ble::mesh::device::Uuid;
+use crate::drivers::ble::mesh::driver::node::ProvisionedDevice;
+use crate::drivers::ble::mesh::driver::pipeline::provisioner::ProvisionerContext;
+use crate::drivers::ble::mesh::driver::pipeline::unprovisioned::provisionable::transcript::Transcript;
+use crate::drivers::ble::mesh::driver::DeviceError;
+use crate::drivers::ble::mesh::provisioning::{
+    Algorithm, AuthenticationMethod, Confirmation, Data, ErrorCode, Invite, OOBAction, OOBSize,
+    ProvisioningPDU, PublicKey, PublicKeySelected, Random, Start,
+};
+use core::convert::TryFrom;
+use heapless::Vec;
+use p256::elliptic_curve::ecdh::diffie_hellman;
+use p256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+use p256::{EncodedPoint, SecretKey};
+
+const LAST_UNICAST_ADDRESS: u16 = 0x7FFF;
+
+pub(crate) enum Step {
+    Transmit(Vec<ProvisioningPDU, 2>),
+    Complete(ProvisionedDevice),
+    Failed(ErrorCode),
+}
+
+/// The provisioner's side of provisioning a single device.
+///
+/// Only No OOB authentication is offered, which every device supports.
+pub(crate) struct Session {
+    uuid: Uuid,
+    unicast_address: UnicastAddress,
+    number_of_elements: u8,
+    transcript: Transcript,
+    private_key: Option<SecretKey>,
+    ecdh_secret: Option<[u8; 32]>,
+    random_provisioner: [u8; 16],
+    confirmation_device: Option<[u8; 16]>,
+    device_key: Option<[u8; 16]>,
+}
+
+impl Session {
+    pub(crate) fn new(uuid: Uuid, unicast_address: UnicastAddress) -> Self {
+        Self {
+            uuid,
+            unicast_address,
+            number_of_elements: 0,
+            transcript: Transcript::default(),
+            private_key: None,
+            ecdh_secret: None,
+            random_provisioner: [0; 16],
+            confirmation_device: None,
+            device_key: None,
+        }
+    }
+
+    pub(crate) fn invite(&mut self) -> Result<ProvisioningPDU, DeviceError> {
+        let invite = Invite {
+            attention_duration: 0,
+        };
+        self.transcript.add_invite(&invite)?;
+        Ok(ProvisioningPDU::Invite(invite))
+    }
+
+    pub(crate) fn process_inbound<C: ProvisionerContext>(
+        &mut self,
+        ctx: &C,
+        pdu: ProvisioningPDU,
+    ) -> Result<Option<Step>, DeviceError> {
+        match pdu {
+            ProvisioningPDU::Capabilities(capabilities) => {
+                info!("capabilities");
+                let last_address =
+                    u16::from(self.unicast_address) as u32 + capabilities.number_of_elements as u32;
+                if capabilities.number_of_elements == 0
+                    || last_address > LAST_UNICAST_ADDRESS as u32 + 1
+                {
+                    return Ok(Some(Step::Failed(ErrorCode::CannotAssignAddresses)));
+                }
+                self.number_of_elements = capabilities.number_of_elements;
+                self.transcript.add_capabilities(&capabilities)?;
+
+                let start = Start {
+                    algorithm: Algorithm::P256,
+                    public_key: PublicKeySelected::NoPublicKey,
+                    authentication_method: AuthenticationMethod::NoOOBAuthentication,
+                    authentication_action: OOBAction::None,
+                    authentication_size: OOBSize::NotSupported,
+                };
+                self.transcript.add_start(&start)?;
+
+                let private_key = Self::generate_private_key(ctx);
+                let public_key = Self::public_key(&private_key)?;
+                self.transcript.add_pubkey_provisioner(&public_key)?;
+                self.private_key.replace(private_key);
+
+                let mut pdus = Vec::new();
+                pdus.push(ProvisioningPDU::Start(start))
+                    .map_err(|_| DeviceError::InsufficientBuffer)?;
+                pdus.push(ProvisioningPDU::PublicKey(public_key))
+                    .map_err(|_| DeviceError::InsufficientBuffer)?;
+                Ok(Some(Step::Transmit(pdus)))
+            }
+            ProvisioningPDU::PublicKey(public_key) => {
+                info!("public_key");
+                self.transcript.add_pubkey_device(&public_key)?;
+                let peer_pk: Option<p256::PublicKey> =
+                    p256::PublicKey::from_encoded_point(&EncodedPoint::from_affine_coordinates(
+                        &public_key.x.into(),
+                        &public_key.y.into(),
+                        false,
+                    ))
+                    .into();
+                let peer_pk = peer_pk.ok_or(DeviceError::CryptoError("device public key"))?;
+
+                let private_key = self.private_key.as_ref().ok_or(DeviceError::InvalidState)?;
+                let shared_secret =
+                    diffie_hellman(private_key.to_nonzero_scalar(), peer_pk.as_affine());
+                let mut ecdh_secret = [0; 32];
+                ecdh_secret.copy_from_slice(shared_secret.as_bytes());
+                self.ecdh_secret.replace(ecdh_secret);
+
+                ctx.rng_fill(&mut self.random_provisioner);
+                let confirmation = self.confirmation(&self.random_provisioner)?;
+                Ok(Some(Self::transmit(ProvisioningPDU::Confirmation(
+                    Confirmation { confirmation },
+                ))?))
+            }
+            ProvisioningPDU::Confirmation(confirmation) => {
+                info!("confirmation");
+                self.confirmation_device.replace(confirmation.confirmation);
+                Ok(Some(Self::transmit(ProvisioningPDU::Random(Random {
+                    random: self.random_provisioner,
+                }))?))
+            }
+            ProvisioningPDU::Random(random) => {
+                info!("random");
+                if self.confirmation_device != Some(self.confirmation(&random.random)?) {
+                    return Ok(Some(Step::Failed(ErrorCode::ConfirmationFailed)));
+                }
+                let data = self.data(ctx, &random.random)?;
+                Ok(Some(Self::transmit(ProvisioningPDU::Data(data))?))
+            }
+            ProvisioningPDU::Complete => {
+                info!("complete");
+                Ok(Some(Step::Complete(ProvisionedDevice {
+                    uuid: self.uuid,
+                    unicast_address: self.unicast_address,
+                    number_of_elements: self.number_of_elements,
+                    device_key: self.device_key.ok_or(DeviceError::InvalidState)?,
+                })))
+            }
+            ProvisioningPDU::Failed(failed) => Ok(Some(Step::Failed(failed.error_code))),
+            // sent by the provisioner, or not applicable without OOB authentication.
+            ProvisioningPDU::Invite(_)
+            | ProvisioningPDU::Start(_)
+            | ProvisioningPDU::InputComplete
+            | ProvisioningPDU::Data(_) => Ok(None),
+        }
+    }
+
+    fn transmit(pdu: ProvisioningPDU) -> Result<Step, DeviceError> {
+        let mut pdus = Vec::new();
+        pdus.push(pdu)
+            .map_err(|_| DeviceError::InsufficientBuffer)?;
+        Ok(Step::Transmit(pdus))
+    }
+
+    fn generate_private_key<C: ProvisionerContext>(ctx: &C) -> SecretKey {
+        loop {
+            let mut bytes = [0; 32];
+            ctx.rng_fill(&mut bytes);
+            // retry the rare value outside of the curve's scalar range.
+            if let Ok(private_key) = SecretKey::from_be_bytes(&bytes) {
+                return private_key;
+            }
+        }
+    }
+
+    fn public_key(private_key: &SecretKey) -> Result<PublicKey, DeviceError> {
+        let xy = private_key.public_key().to_encoded_point(false);
+        let x = xy.x().ok_or(DeviceError::KeyInitialization)?;
+        let y = xy.y().ok_or(DeviceError::KeyInitialization)?;
+        Ok(PublicKey {
+            x: <[u8; 32]>::try_from(x.as_slice()).map_err(|_| DeviceError::InsufficientBuffer)?,
+            y: <[u8; 32]>::try_from(y.as_slice()).map_err(|_| DeviceError::InsufficientBuffer)?,
+        })
+    }
+
+    fn ecdh_secret(&self) -> Result<&[u8; 32], DeviceError> {
+        self.ecdh_secret.as_ref().ok_or(DeviceError::NoSharedSecret)
+    }
+
+    fn confirmation(&self, random: &[u8; 16]) -> Result<[u8; 16], DeviceError> {
+        let salt = self.transcript.confirmation_salt()?;
+        let confirmation_key = crypto::k1(self.ecdh_secret()?, &*salt.into_bytes(), b"prck")?;
+        // without OOB authentication the auth value is all zeros.
+        let mut input = [0; 32];
+        input[0..16].copy_from_slice(random);
+        let confirmation = crypto::aes_cmac(&confirmation_key.into_bytes(), &input)?;
+
+        let mut bytes = [0; 16];
+        bytes.copy_from_slice(&confirmation.into_bytes());
+        Ok(bytes)
+    }
+
+    fn data<C: ProvisionerContext>(
+        &mut self,
+        ctx: &C,
+        random_device: &[u8; 16],
+    ) -> Result<Data, DeviceError> {
+        let mut provisioning_salt = [0; 48];
+        provisioning_salt[0..16]
+            .copy_from_slice(&self.transcript.confirmation_salt()?.into_bytes());
+        provisioning_salt[16..32].copy_from_slice(&self.random_provisioner);
+        provisioning_salt[32..48].copy_from_slice(random_device);
+        let provisioning_salt = crypto::s1(&provisioning_salt)?.into_bytes();
+
+        let ecdh_secret = self.ecdh_secret()?;
+        let session_key = crypto::k1(ecdh_secret, &provisioning_salt, b"prsk")?.into_bytes();
+        let session_nonce = crypto::k1(ecdh_secret, &provisioning_salt, b"prsn")?.into_bytes();
+        let device_key = crypto::k1(ecdh_secret, &provisioning_salt, b"prdk")?.into_bytes();
+
+        let mut cleartext = Vec::<u8, 25>::new();
+        ctx.provisioning_data(self.unicast_address)?
+            .emit(&mut cleartext)?;
+
+        let mut encrypted = [0; 25];
+        encrypted.copy_from_slice(&cleartext);
+        let mut mic = [0; 8];
+        crypto::aes_ccm_encrypt_detached(
+            &session_key,
+            &session_nonce[3..],
+            &mut encrypted,
+            &mut mic,
+            None,
+        )
+        .map_err(|_| DeviceError::CryptoError("provisioning data"))?;
+
+        let mut key = [0; 16];
+        key.copy_from_slice(&device_key);
+        self.device_key.replace(key);
+
+        Ok(Data { encrypted, mic })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drivers::ble::mesh::driver::pipeline::provisioner::tests::{
+        TestContext, NETWORK_KEY, UUID,
+    };
+    use crate::drivers::ble::mesh::provisioning::{Capabilities, ProvisioningData};
+
+    fn capabilities(number_of_elements: u8) -> Capabilities {
+        let mut data = [0; 12];
+        data[0] = 0x01;
+        data[1] = number_of_elements;
+        data[3] = 0x01;
+        match ProvisioningPDU::parse(&data) {
+            Ok(ProvisioningPDU::Capabilities(capabilities)) => capabilities,
+            _ => panic!("expected capabilities"),
+        }
+    }
+
+    fn transmitted(step: Option<Step>) -> Vec<ProvisioningPDU, 2> {
+        match step {
+            Some(Step::Transmit(pdus)) => pdus,
+            _ => panic!("expected transmit"),
+        }
+    }
+
+    fn device_public_key(private_key: &SecretKey) -> PublicKey {
+        Session::public_key(private_key).unwrap()
+    }
+
+    #[test]
+    fn test_provision_device() {
+        let ctx = TestContext::new();
+        let mut session = Session::new(UUID, UnicastAddress(0x0002));
+        assert!(matches!(session.invite(), Ok(ProvisioningPDU::Invite(_))));
+
+        // the device's side of the transcript.
+        let mut transcript = Transcript::default();
+        transcript
+            .add_invite(&Invite {
+                attention_duration: 0,
+            })
+            .unwrap();
+        transcript.add_capabilities(&capabilities(2)).unwrap();
+
+        let pdus = transmitted(
+            session
+                .process_inbound(&ctx, ProvisioningPDU::Capabilities(capabilities(2)))
+                .unwrap(),
+        );
+        let (start, provisioner_public_key) = match &pdus[..] {
+            [ProvisioningPDU::Start(start), ProvisioningPDU::PublicKey(public_key)] => {
+                (start.clone(), public_key.clone())
+            }
+            _ => panic!("expected start and public key"),
+        };
+        assert!(matches!(
+            start.authentication_method,
+            AuthenticationMethod::NoOOBAuthentication
+        ));
+        transcript.add_start(&start).unwrap();
+        transcript
+            .add_pubkey_provisioner(&provisioner_public_key)
+            .unwrap();
+
+        let private_key = SecretKey::from_be_bytes(&[0x11; 32]).unwrap();
+        let public_key = device_public_key(&private_key);
+        transcript.add_pubkey_device(&public_key).unwrap();
+        let peer_pk: Option<p256::PublicKey> =
+            p256::PublicKey::from_encoded_point(&EncodedPoint::from_affine_coordinates(
+                &provisioner_public_key.x.into(),
+                &provisioner_public_key.y.into(),
+                false,
+            ))
+            .into();
+        let peer_pk = peer_pk.unwrap();
+        let shared_secret = diffie_hellman(private_key.to_nonzero_scalar(), peer_pk.as_affine());
+        let ecdh_secret = shared_secret.as_bytes();
+
+        let confirmation_provisioner = match transmitted(
+            session
+                .process_inbound(&ctx, ProvisioningPDU::PublicKey(public_key))
+                .unwrap(),
+        )
+        .pop()
+        {
+            Some(ProvisioningPDU::Confirmation(confirmation)) => confirmation.confirmation,
+            _ => panic!("expected confirmation"),
+        };
+
+        // without OOB authentication the auth value is all zeros.
+        let confirmation_salt = transcript.confirmation_salt().unwrap().into_bytes();
+        let confirmation_key = crypto::k1(ecdh_secret, &confirmation_salt, b"prck")
+            .unwrap()
+            .into_bytes();
+        let confirmation = |random: &[u8; 16]| {
+            let mut input = [0; 32];
+            input[0..16].copy_from_slice(random);
+            let mut confirmation = [0; 16];
+            confirmation.copy_from_slice(
+                &crypto::aes_cmac(&confirmation_key, &input)
+                    .unwrap()
+                    .into_bytes(),
+            );
+            confirmation
+        };
+
+        let random_device = [0x22; 16];
+        let random_provisioner = match transmitted(
+            session
+                .process_inbound(
+                    &ctx,
+                    ProvisioningPDU::Confirmation(Confirmation {
+                        confirmation: confirmation(&random_device),
+                    }),
+                )
+                .unwrap(),
+        )
+        .pop()
+        {
+            Some(ProvisioningPDU::Random(random)) => random.random,
+            _ => panic!("expected random"),
+        };
+        assert_eq!(confirmation_provisioner, confirmation(&random_provisioner));
+
+        let mut data = match transmitted(
+            session
+                .process_inbound(
+                    &ctx,
+                    ProvisioningPDU::Random(Random {
+                        random: random_device,
+                    }),
+                )
+                .unwrap(),
+        )
+        .pop()
+        {
+            Some(ProvisioningPDU::Data(data)) => data,
+            _ => panic!("expected data"),
+        };
+
+        let mut provisioning_salt = [0; 48];
+        provisioning_salt[0..16].copy_from_slice(&confirmation_salt);
+        provisioning_salt[16..32].copy_from_slice(&random_provisioner);
+        provisioning_salt[32..48].copy_from_slice(&random_device);
+        let provisioning_salt = crypto::s1(&provisioning_salt).unwrap().into_bytes();
+        let session_key = crypto::k1(ecdh_secret, &provisioning_salt, b"prsk")
+            .unwrap()
+            .into_bytes();
+        let session_nonce = crypto::k1(ecdh_secret, &provisioning_salt, b"prsn")
+            .unwrap()
+            .into_bytes();
+        let device_key = crypto::k1(ecdh_secret, &provisioning_salt, b"prdk")
+            .unwrap()
+            .into_bytes();
+        crypto::aes_ccm_decrypt_detached(
+            &session_key,
+            &session_nonce[3..],
+            &mut data.encrypted,
+            &data.mic,
+            None,
+        )
+        .unwrap();
+        let provisioning_data = ProvisioningData::parse(&data.encrypted).unwrap();
+        assert_eq!(NETWORK_KEY, provisioning_data.network_key);
+        assert_eq!(UnicastAddress(0x0002), provisioning_data.unicast_address);
+
+        match session
+            .process_inbound(&ctx, ProvisioningPDU::Complete)
+            .unwrap()
+        {
+            Some(Step::Complete(device)) => {
+                assert!(device.uuid == UUID);
+                assert_eq!(UnicastAddress(0x0002), device.unicast_address);
+                assert_eq!(2, device.number_of_elements);
+                assert_eq!(&device_key[..], &device.device_key[..]);
+            }
+            _ => panic!("expected complete"),
+        }
+    }
+
+    #[test]
+    fn test_cannot_assign_addresses() {
+        let ctx = TestContext::new();
+        let mut session = Session::new(UUID, UnicastAddress(0x0002));
+        assert!(matches!(
+            session.process_inbound(&ctx, ProvisioningPDU::Capabilities(capabilities(0))),
+            Ok(Some(Step::Failed(ErrorCode::CannotAssignAddresses)))
+        ));
+
+        let mut session = Session::new(UUID, UnicastAddress(0x7FFF));
+        assert!(matches!(
+            session.process_inbound(&ctx, ProvisioningPDU::Capabilities(capabilities(2))),
+            Ok(Some(Step::Failed(ErrorCode::CannotAssignAddresses)))
+        ));
+    }
+
+    #[test]
+    fn test_confirmation_failed() {
+        let ctx = TestContext::new();
+        let mut session = Session::new(UUID, UnicastAddress(0x0002));
+        session.invite().unwrap();
+        session
+            .process_inbound(&ctx, ProvisioningPDU::Capabilities(capabilities(1)))
+            .unwrap();
+        let private_key = SecretKey::from_be_bytes(&[0x11; 32]).unwrap();
+        session
+            .process_inbound(
+                &ctx,
+                ProvisioningPDU::PublicKey(device_public_key(&private_key)),
+            )
+            .unwrap();
+        session
+            .process_inbound(
+                &ctx,
+                ProvisioningPDU::Confirmation(Confirmation {
+                    confirmation: [0; 16],
+                }),
+            )
+            .unwrap();
+        assert!(matches!(
+            session.process_inbound(&ctx, ProvisioningPDU::Random(Random { random: [0x22; 16] })),
+            Ok(Some(Step::Failed(ErrorCode::ConfirmationFailed)))
+        ));
+    }
+}
@@ -1,20 +1,30 @@
 use crate::drivers::ble::mesh::driver::node::State;
-use crate::drivers::ble::mesh::driver::pipeline::unprovisioned::provisionable::Provisionable;
+use crate::drivers::ble::mesh::driver::pipeline::unprovisioned::provisionable::{
+    OobRequest, Provisionable,
+};
 use crate::drivers::ble::mesh::driver::pipeline::PipelineContext;
 use crate::drivers::ble::mesh::driver::DeviceError;
+use crate::drivers::ble::mesh::generic_provisioning::Reason;
 use crate::drivers::ble::mesh::interface::PDU;
+use crate::drivers::ble::mesh::oob::OobValue;
 use crate::drivers::ble::mesh::provisioning::{Capabilities, ProvisioningPDU};
+use embassy::time::{Duration, Instant};
 
 pub mod provisionable;
 
+/// Give up on a provisioner that stops responding, as per the provisioning protocol timeout.
+const PROVISIONING_TIMEOUT: Duration = Duration::from_secs(60);
+
 pub(crate) struct UnprovisionedPipeline {
     provisionable: Provisionable,
+    timeout: Option<Instant>,
 }
 
 impl UnprovisionedPipeline {
     pub(crate) fn new(capabilities: Capabilities) -> Self {
         Self {
             provisionable: Provisionable::new(capabilities),
+            timeout: None,
         }
     }
 
@@ -23,6 +33,7 @@ impl UnprovisionedPipeline {
         ctx: &C,
         pdu: ProvisioningPDU,
     ) -> Result<Option<State>, DeviceError> {
+        self.timeout.replace(Instant::now() + PROVISIONING_TIMEOUT);
         if let Some(response) = self.provisionable.process_inbound(ctx, pdu).await? {
             ctx.transmit(&PDU::Provisioning(response)).await?;
             Ok(Some(State::Provisioning))
@@ -30,4 +41,74 @@ impl UnprovisionedPipeline {
             Ok(None)
         }
     }
+
+    pub(crate) fn take_oob_request(&mut self) -> Option<OobRequest> {
+        self.provisionable.take_oob_request()
+    }
+
+    pub(crate) async fn oob_input<C: PipelineContext>(
+        &mut self,
+        ctx: &C,
+        value: OobValue,
+    ) -> Result<(), DeviceError> {
+        let input_complete = self.provisionable.oob_input(value);
+        ctx.transmit(&PDU::Provisioning(input_complete)).await
+    }
+
+    /// When the current provisioning session times out, if one is in progress.
+    pub(crate) fn timeout(&self) -> Option<Instant> {
+        self.timeout
+    }
+
+    /// Abandon the current session, once the provisioner closed the link.
+    pub(crate) fn link_closed(&mut self) -> Option<State> {
+        self.timeout.take();
+        self.provisionable.reset();
+        Some(State::Unprovisioned)
+    }
+
+    /// Abandon the current session if the provisioner stopped responding,
+    /// closing the link.
+    pub(crate) async fn expire<C: PipelineContext>(
+        &mut self,
+        ctx: &C,
+    ) -> Result<Option<State>, DeviceError> {
+        if matches!(self.timeout, Some(timeout) if Instant::now() >= timeout) {
+            warn!("provisioning timed out");
+            ctx.close_link(Reason::Timeout).await?;
+            Ok(self.link_closed())
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pipeline() -> UnprovisionedPipeline {
+        // one element, input OOB by pushing a button up to 4 times.
+        match ProvisioningPDU::parse(&[
+            0x01, 0x01, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x01,
+        ]) {
+            Ok(ProvisioningPDU::Capabilities(capabilities)) => {
+                UnprovisionedPipeline::new(capabilities)
+            }
+            _ => panic!("expected capabilities"),
+        }
+    }
+
+    #[test]
+    fn test_link_closed() {
+        let mut pipeline = pipeline();
+        assert!(pipeline.timeout().is_none());
+
+        pipeline
+            .timeout
+            .replace(Instant::now() + PROVISIONING_TIMEOUT);
+        assert!(matches!(pipeline.link_closed(), Some(State::Unprovisioned)));
+        assert!(pipeline.timeout().is_none());
+        assert!(pipeline.take_oob_request().is_none());
+    }
 }
@@ -1,7 +1,8 @@
 use crate::drivers::ble::mesh::driver::pipeline::unprovisioned::provisionable::UnprovisionedContext;
 use crate::drivers::ble::mesh::driver::DeviceError;
+use crate::drivers::ble::mesh::oob::OobValue;
 use crate::drivers::ble::mesh::provisioning::{
-    InputOOBAction, OOBAction, OOBSize, OutputOOBAction, Start,
+    AuthenticationMethod, InputOOBAction, OOBAction, OOBSize, OutputOOBAction, Start,
 };
 use heapless::Vec;
 
@@ -13,6 +14,7 @@ pub enum AuthValue {
     OutputNumeric(u32),
     InputAlphanumeric(Vec<u8, 8>),
     OutputAlphanumeric(Vec<u8, 8>),
+    Static([u8; 16]),
 }
 
 impl AuthValue {
@@ -37,58 +39,93 @@ impl AuthValue {
                     bytes[i] = *byte
                 }
             }
+            AuthValue::Static(value) => {
+                bytes = *value;
+            }
         }
 
         bytes
     }
 }
 
+impl From<OobValue> for AuthValue {
+    fn from(value: OobValue) -> Self {
+        match value {
+            OobValue::Count(count) => AuthValue::InputEvents(count),
+            OobValue::Numeric(num) => AuthValue::InputNumeric(num),
+            OobValue::Alphanumeric(chars) => AuthValue::InputAlphanumeric(chars),
+        }
+    }
+}
+
+/// Interaction with the application required to authenticate provisioning.
+pub enum OobRequest {
+    Display(OutputOOBAction, OobValue),
+    Input(InputOOBAction, u8),
+}
+
+/// Determine the auth value selected by the provisioner's start PDU.
+///
+/// Output values are chosen at random, and are returned alongside the request to
+/// display them. Input values are unknown until the application supplies them.
 pub fn determine_auth_value<C: UnprovisionedContext>(
     ctx: &C,
     start: &Start,
-) -> Result<AuthValue, DeviceError> {
+) -> Result<(Option<AuthValue>, Option<OobRequest>), DeviceError> {
     Ok(
         match (&start.authentication_action, &start.authentication_size) {
             (
-                OOBAction::Output(OutputOOBAction::Blink)
-                | OOBAction::Output(OutputOOBAction::Beep)
-                | OOBAction::Output(OutputOOBAction::Vibrate),
-                OOBSize::MaximumSize(size),
-            ) => {
-                let auth_raw = random_physical_oob(ctx, *size);
-                AuthValue::OutputEvents(auth_raw)
-            }
-            (
-                OOBAction::Input(InputOOBAction::Push) | OOBAction::Input(InputOOBAction::Twist),
+                OOBAction::Output(
+                    action @ (OutputOOBAction::Blink
+                    | OutputOOBAction::Beep
+                    | OutputOOBAction::Vibrate),
+                ),
                 OOBSize::MaximumSize(size),
             ) => {
                 let auth_raw = random_physical_oob(ctx, *size);
-                AuthValue::InputEvents(auth_raw)
+                (
+                    Some(AuthValue::OutputEvents(auth_raw)),
+                    Some(OobRequest::Display(*action, OobValue::Count(auth_raw))),
+                )
             }
             (OOBAction::Output(OutputOOBAction::OutputNumeric), OOBSize::MaximumSize(size)) => {
                 let auth_raw = random_numeric(ctx, *size);
-                AuthValue::OutputNumeric(auth_raw)
-            }
-            // TODO actually dispatch to device/app/thing's UI for inputs instead of just making up shit.
-            (OOBAction::Input(InputOOBAction::InputNumeric), OOBSize::MaximumSize(size)) => {
-                let auth_raw = random_numeric(ctx, *size);
-                AuthValue::InputNumeric(auth_raw)
+                (
+                    Some(AuthValue::OutputNumeric(auth_raw)),
+                    Some(OobRequest::Display(
+                        OutputOOBAction::OutputNumeric,
+                        OobValue::Numeric(auth_raw),
+                    )),
+                )
             }
             (
                 OOBAction::Output(OutputOOBAction::OutputAlphanumeric),
                 OOBSize::MaximumSize(size),
             ) => {
                 let auth_raw = random_alphanumeric(ctx, *size)?;
-                AuthValue::OutputAlphanumeric(auth_raw)
-            }
-            (OOBAction::Input(InputOOBAction::InputAlphanumeric), OOBSize::MaximumSize(size)) => {
-                let auth_raw = random_alphanumeric(ctx, *size)?;
-                AuthValue::InputAlphanumeric(auth_raw)
-            }
-            _ => {
-                // zeros!
-                AuthValue::None
-            }
+                (
+                    Some(AuthValue::OutputAlphanumeric(auth_raw.clone())),
+                    Some(OobRequest::Display(
+                        OutputOOBAction::OutputAlphanumeric,
+                        OobValue::Alphanumeric(auth_raw),
+                    )),
+                )
+            }
+            (OOBAction::Input(action), OOBSize::MaximumSize(size)) => {
+                (None, Some(OobRequest::Input(*action, *size)))
+            }
+            _ => match start.authentication_method {
+                AuthenticationMethod::StaticOOBAuthentication => (
+                    Some(AuthValue::Static(
+                        ctx.static_oob().ok_or(DeviceError::InvalidState)?,
+                    )),
+                    None,
+                ),
+                _ => {
+                    // zeros!
+                    (Some(AuthValue::None), None)
+                }
+            },
         },
     )
 }
@@ -171,16 +208,18 @@ fn random_alphanumeric<C: UnprovisionedContext>(
     for _ in 0..size {
         loop {
             let candidate = ctx.rng_u8();
-            if candidate >= 64 && candidate <= 90 {
+            if candidate >= 65 && candidate <= 90 {
                 // Capital ASCII letters A-Z
                 random
                     .push(candidate)
                     .map_err(|_| DeviceError::InsufficientBuffer)?;
+                break;
             } else if candidate >= 48 && candidate <= 57 {
                 // ASCII numbers 0-9
                 random
                     .push(candidate)
                     .map_err(|_| DeviceError::InsufficientBuffer)?;
+                break;
             }
         }
     }
@@ -1,12 +1,14 @@
 mod auth_value;
-mod transcript;
+pub(crate) mod transcript;
 
 use crate::drivers::ble::mesh::driver::pipeline::mesh::MeshContext;
+pub(crate) use crate::drivers::ble::mesh::driver::pipeline::unprovisioned::provisionable::auth_value::OobRequest;
 use crate::drivers::ble::mesh::driver::pipeline::unprovisioned::provisionable::auth_value::{
     determine_auth_value, AuthValue,
 };
 use crate::drivers::ble::mesh::driver::pipeline::unprovisioned::provisionable::transcript::Transcript;
 use crate::drivers::ble::mesh::driver::DeviceError;
+use crate::drivers::ble::mesh::oob::OobValue;
 use crate::drivers::ble::mesh::provisioning::{
     Capabilities, Confirmation, ProvisioningData, ProvisioningPDU, PublicKey, Random,
 };
@@ -20,8 +22,6 @@ use p256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
 use p256::EncodedPoint;
 
 pub trait UnprovisionedContext: MeshContext {
-    fn rng_fill(&self, dest: &mut [u8]);
-
     type SetPeerPublicKeyFuture<'m>: Future<Output = Result<(), DeviceError>>
     where
         Self: 'm;
@@ -59,12 +59,16 @@ pub trait UnprovisionedContext: MeshContext {
 
     fn rng_u8(&self) -> u8;
     fn rng_u32(&self) -> u32;
+
+    fn static_oob(&self) -> Option<[u8; 16]>;
 }
 
 pub struct Provisionable {
     capabilities: Capabilities,
     transcript: Transcript,
     auth_value: Option<AuthValue>,
+    oob_request: Option<OobRequest>,
+    public_key_exchanged: bool,
     random_device: Option<[u8; 16]>,
     random_provisioner: Option<[u8; 16]>,
 }
@@ -75,6 +79,8 @@ impl Provisionable {
             capabilities,
             transcript: Transcript::default(),
             auth_value: None,
+            oob_request: None,
+            public_key_exchanged: false,
             random_device: None,
             random_provisioner: None,
         }
@@ -83,6 +89,8 @@ impl Provisionable {
     pub fn reset(&mut self) {
         self.transcript.reset();
         self.auth_value.take();
+        self.oob_request.take();
+        self.public_key_exchanged = false;
         self.random_device.take();
         self.random_provisioner.take();
     }
@@ -105,9 +113,9 @@ impl Provisionable {
             ProvisioningPDU::Start(start) => {
                 info!("start");
                 self.transcript.add_start(&start)?;
-                let auth_value = determine_auth_value(ctx, &start)?;
-                // TODO actually let the device/app/thingy know what it is so that it can blink/flash/accept input
-                self.auth_value.replace(auth_value);
+                let (auth_value, oob_request) = determine_auth_value(ctx, &start)?;
+                self.auth_value = auth_value;
+                self.oob_request = oob_request;
                 Ok(None)
             }
             ProvisioningPDU::PublicKey(public_key) => {
@@ -137,6 +145,7 @@ impl Provisionable {
                         .map_err(|_| DeviceError::InsufficientBuffer)?,
                 };
                 self.transcript.add_pubkey_device(&pk)?;
+                self.public_key_exchanged = true;
                 Ok(Some(ProvisioningPDU::PublicKey(pk)))
             }
             ProvisioningPDU::InputComplete => Ok(None),
@@ -195,6 +204,21 @@ impl Provisionable {
         }
     }
 
+    /// Any OOB interaction with the application, once public keys have been exchanged.
+    pub fn take_oob_request(&mut self) -> Option<OobRequest> {
+        if self.public_key_exchanged {
+            self.oob_request.take()
+        } else {
+            None
+        }
+    }
+
+    /// Accept the value input by the application, completing input OOB.
+    pub fn oob_input(&mut self, value: OobValue) -> ProvisioningPDU {
+        self.auth_value.replace(value.into());
+        ProvisioningPDU::InputComplete
+    }
+
     fn confirmation_device<C: UnprovisionedContext>(
         &self,
         ctx: &C,
@@ -1,7 +1,8 @@
+use crate::drivers::ble::mesh::beacon::{SecureNetworkBeacon, UnprovisionedDeviceBeacon};
 use crate::drivers::ble::mesh::device::Uuid;
 use crate::drivers::ble::mesh::driver::node::{NetworkId, State};
 use crate::drivers::ble::mesh::generic_provisioning::{
-    GenericProvisioningPDU, ProvisioningBearerControl,
+    GenericProvisioningPDU, ProvisioningBearerControl, Reason,
 };
 use crate::drivers::ble::mesh::interface::advertising::segmentation::outbound::{
     OutboundSegments, OutboundSegmentsIter,
@@ -25,9 +26,12 @@ pub struct AdvertisingBearerNetworkInterface<B: AdvertisingBearer> {
     bearer: B,
     segmentation: Segmentation,
     link_id: Cell<Option<u32>>,
+    link_initiator: Cell<bool>,
+    link_opening: Cell<Option<Uuid>>,
     inbound_transaction_number: Cell<Option<u8>>,
     acked_inbound_transaction_number: Cell<Option<u8>>,
     outbound_pdu: RefCell<Option<OutboundPDU>>,
+    queued_pdu: RefCell<Option<OutboundPDU>>,
     outbound_transaction_number: Cell<u8>,
 }
 
@@ -38,9 +42,12 @@ impl<B: AdvertisingBearer> AdvertisingBearerNetworkInterface<B> {
             bearer,
             segmentation: Default::default(),
             link_id: Cell::new(None),
+            link_initiator: Cell::new(false),
+            link_opening: Cell::new(None),
             inbound_transaction_number: Cell::new(None),
             acked_inbound_transaction_number: Cell::new(None),
             outbound_pdu: RefCell::new(None),
+            queued_pdu: RefCell::new(None),
             outbound_transaction_number: Cell::new(0x80),
         }
     }
@@ -68,7 +75,7 @@ impl<B: AdvertisingBearer> AdvertisingBearerNetworkInterface<B> {
                     self.bearer.transmit(&adv_data).await?;
                 }
             }
-            Beacon::Provisioned(_) => {
+            Beacon::Provisioned(_) | Beacon::NodeIdentity(_) => {
                 // not applicable to this role
             }
             Beacon::Secure => {
@@ -82,9 +89,66 @@ impl<B: AdvertisingBearer> AdvertisingBearerNetworkInterface<B> {
         match pdu {
             PDU::Provisioning(pdu) => self.transmit_provisioning_pdu(&pdu).await,
             PDU::Network(pdu) => self.transmit_network_pdu(&pdu).await,
+            PDU::Beacon(beacon) => self.transmit_secure_network_beacon(&beacon).await,
+            PDU::ProxyConfiguration(_) => {
+                // not applicable to this bearer
+                Ok(())
+            }
+            PDU::UnprovisionedBeacon(_) | PDU::LinkClose(_) => {
+                // only ever received
+                Ok(())
+            }
         }
     }
 
+    /// Open a link to an unprovisioned device, as a provisioner.
+    ///
+    /// Provisioning PDUs are held back until the device acknowledges the link.
+    pub async fn open_link(&self, uuid: Uuid, link_id: u32) -> Result<(), BearerError> {
+        if self.link_id.get().is_some() {
+            return Err(BearerError::InvalidLink);
+        }
+        self.link_id.replace(Some(link_id));
+        self.link_initiator.replace(true);
+        self.link_opening.replace(Some(uuid));
+        // the device numbers its transactions from 0x80, and we from 0x00.
+        self.inbound_transaction_number.replace(Some(0x80));
+        self.acked_inbound_transaction_number.take();
+        self.outbound_transaction_number.replace(0x00);
+        self.outbound_pdu.take();
+        self.queued_pdu.take();
+        self.retransmit().await
+    }
+
+    /// Close the current link, whether opened as a provisioner or by one.
+    pub async fn close_link(&self, reason: Reason) -> Result<(), BearerError> {
+        if self.link_id.get().is_some() {
+            self.transmit_link_control(ProvisioningBearerControl::LinkClose(reason))
+                .await?;
+        }
+        self.link_id.take();
+        self.link_initiator.replace(false);
+        self.link_opening.take();
+        self.inbound_transaction_number.take();
+        self.acked_inbound_transaction_number.take();
+        self.outbound_transaction_number.replace(0x80);
+        self.outbound_pdu.take();
+        self.queued_pdu.take();
+        Ok(())
+    }
+
+    async fn transmit_link_control(
+        &self,
+        control: ProvisioningBearerControl,
+    ) -> Result<(), BearerError> {
+        self.transmit_advertising_pdu(&AdvertisingPDU {
+            link_id: self.link_id.get().ok_or(BearerError::InvalidLink)?,
+            transaction_number: 0,
+            pdu: GenericProvisioningPDU::ProvisioningBearerControl(control),
+        })
+        .await
+    }
+
     async fn transmit_provisioning_pdu(&self, pdu: &ProvisioningPDU) -> Result<(), BearerError> {
         let segments = self.segmentation.process_outbound(&pdu)?;
 
@@ -92,18 +156,23 @@ impl<B: AdvertisingBearer> AdvertisingBearerNetworkInterface<B> {
         self.outbound_transaction_number
             .replace(transaction_number + 1);
 
-        self.outbound_pdu.replace(Some(OutboundPDU {
+        let outbound = OutboundPDU {
             link_id: self.link_id.get().ok_or(BearerError::InvalidLink)?,
             transaction_number,
             segments: segments,
-        }));
+        };
 
-        if let Some(pdu) = &*self.outbound_pdu.borrow() {
-            for pdu in pdu.iter() {
-                self.transmit_advertising_pdu(&pdu).await?;
+        if self.link_initiator.get() && self.outbound_pdu.borrow().is_some() {
+            // hold it until the device acknowledges the current transaction.
+            if self.queued_pdu.borrow().is_some() {
+                return Err(BearerError::InsufficientResources);
             }
+            self.queued_pdu.replace(Some(outbound));
+            return Ok(());
         }
-        Ok(())
+
+        self.outbound_pdu.replace(Some(outbound));
+        self.retransmit().await
     }
 
     async fn transmit_network_pdu(
@@ -119,6 +188,19 @@ impl<B: AdvertisingBearer> AdvertisingBearerNetworkInterface<B> {
         Ok(())
     }
 
+    async fn transmit_secure_network_beacon(
+        &self,
+        beacon: &SecureNetworkBeacon,
+    ) -> Result<(), BearerError> {
+        let mut bytes = Vec::<u8, 64>::new();
+        bytes.push(0x00)?;
+        bytes.push(MESH_BEACON)?;
+        beacon.emit(&mut bytes)?;
+        bytes[0] = bytes.len() as u8 - 1;
+        self.bearer.transmit(&bytes).await?;
+        Ok(())
+    }
+
     pub async fn receive(&self) -> Result<PDU, BearerError> {
         loop {
             let data = self.bearer.receive().await?;
@@ -126,7 +208,7 @@ impl<B: AdvertisingBearer> AdvertisingBearerNetworkInterface<B> {
                 match data[1] {
                     PB_ADV => {
                         if let Some(pdu) = self.receive_pb_adv(&data).await? {
-                            return Ok(PDU::Provisioning(pdu));
+                            return Ok(pdu);
                         }
                     }
                     MESH_MESSAGE => {
@@ -134,17 +216,26 @@ impl<B: AdvertisingBearer> AdvertisingBearerNetworkInterface<B> {
                             return Ok(PDU::Network(pdu));
                         }
                     }
+                    MESH_BEACON => {
+                        if let Ok(beacon) = SecureNetworkBeacon::parse(&data[2..]) {
+                            return Ok(PDU::Beacon(beacon));
+                        }
+                        if let Ok(beacon) = UnprovisionedDeviceBeacon::parse(&data[2..]) {
+                            return Ok(PDU::UnprovisionedBeacon(beacon));
+                        }
+                    }
                     _ => {}
                 }
             }
         }
     }
 
-    async fn receive_pb_adv(
-        &self,
-        data: &Vec<u8, PB_ADV_MTU>,
-    ) -> Result<Option<ProvisioningPDU>, BearerError> {
+    async fn receive_pb_adv(&self, data: &Vec<u8, PB_ADV_MTU>) -> Result<Option<PDU>, BearerError> {
         if let Ok(pdu) = AdvertisingPDU::parse(data) {
+            if self.link_initiator.get() && self.link_id.get() != Some(pdu.link_id) {
+                // another provisioning session nearby.
+                return Ok(None);
+            }
             match &pdu.pdu {
                 GenericProvisioningPDU::ProvisioningBearerControl(pbc) => {
                     match pbc {
@@ -191,14 +282,29 @@ impl<B: AdvertisingBearer> AdvertisingBearerNetworkInterface<B> {
                             }
                         }
                         ProvisioningBearerControl::LinkAck => {
-                            /* not applicable for this role */
+                            if self.link_opening.get().is_some()
+                                && self.link_id.get() == Some(pdu.link_id)
+                            {
+                                // link established, send anything held back.
+                                self.link_opening.take();
+                                self.retransmit().await?;
+                            }
                             Ok(None)
                         }
-                        ProvisioningBearerControl::LinkClose(_reason) => {
+                        ProvisioningBearerControl::LinkClose(reason) => {
+                            if self.link_id.get() != Some(pdu.link_id) {
+                                // not our link.
+                                return Ok(None);
+                            }
                             self.link_id.take();
+                            self.link_initiator.replace(false);
+                            self.link_opening.take();
+                            self.outbound_pdu.take();
+                            self.queued_pdu.take();
                             self.inbound_transaction_number.take();
-                            //Ok(Some(BearerMessage::Close(*reason)))
-                            Ok(None)
+                            self.acked_inbound_transaction_number.take();
+                            self.outbound_transaction_number.replace(0x80);
+                            Ok(Some(PDU::LinkClose(*reason)))
                         }
                     }
                 }
@@ -208,7 +314,7 @@ impl<B: AdvertisingBearer> AdvertisingBearerNetworkInterface<B> {
                         let result = self.segmentation.process_inbound(&pdu.pdu);
                         if let Ok(Some(result)) = result {
                             self.ack_transaction().await?;
-                            Ok(Some(result))
+                            Ok(Some(PDU::Provisioning(result)))
                         } else {
                             Ok(None)
                         }
@@ -220,11 +326,18 @@ impl<B: AdvertisingBearer> AdvertisingBearerNetworkInterface<B> {
                     }
                 }
                 GenericProvisioningPDU::TransactionAck => {
-                    let mut borrowed_pdu = self.outbound_pdu.borrow_mut();
-                    if let Some(outbound) = &*borrowed_pdu {
-                        if outbound.transaction_number == pdu.transaction_number {
-                            // They heard us, we can stop retransmitting.
-                            borrowed_pdu.take();
+                    let acked = matches!(
+                        &*self.outbound_pdu.borrow(),
+                        Some(outbound) if outbound.transaction_number == pdu.transaction_number
+                    );
+                    if acked {
+                        // They heard us, we can stop retransmitting,
+                        // and move on to anything held back.
+                        let queued = self.queued_pdu.borrow_mut().take();
+                        let more = queued.is_some();
+                        self.outbound_pdu.replace(queued);
+                        if more {
+                            self.retransmit().await?;
                         }
                     }
                     Ok(None)
@@ -305,6 +418,12 @@ impl<B: AdvertisingBearer> AdvertisingBearerNetworkInterface<B> {
     }
 
     pub async fn retransmit(&self) -> Result<(), BearerError> {
+        if let Some(uuid) = self.link_opening.get() {
+            // keep opening the link until the device acknowledges it.
+            return self
+                .transmit_link_control(ProvisioningBearerControl::LinkOpen(uuid))
+                .await;
+        }
         if let Some(outbound) = &*self.outbound_pdu.borrow() {
             for pdu in outbound.iter() {
                 self.transmit_advertising_pdu(&pdu).await?
@@ -368,3 +487,92 @@ impl<'i> Iterator for OutboundPDUIter<'i> {
         }
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::drivers::ble::mesh::bearer::simulated::{SimulatedAdvertisingBearer, SimulatedAir};
+    use futures::executor::block_on;
+    use futures::FutureExt;
+
+    const UUID: Uuid = Uuid([0x42; 16]);
+
+    fn transmit_control<const N: usize>(
+        provisioner: &SimulatedAdvertisingBearer<'_, N>,
+        link_id: u32,
+        control: ProvisioningBearerControl,
+    ) {
+        let mut data = Vec::new();
+        AdvertisingPDU {
+            link_id,
+            transaction_number: 0,
+            pdu: GenericProvisioningPDU::ProvisioningBearerControl(control),
+        }
+        .emit(&mut data)
+        .unwrap();
+        block_on(provisioner.transmit(&data)).unwrap();
+    }
+
+    fn receive_control<const N: usize>(
+        provisioner: &SimulatedAdvertisingBearer<'_, N>,
+    ) -> (u32, ProvisioningBearerControl) {
+        let data = block_on(provisioner.receive()).unwrap();
+        match AdvertisingPDU::parse(&data) {
+            Ok(AdvertisingPDU {
+                link_id,
+                pdu: GenericProvisioningPDU::ProvisioningBearerControl(control),
+                ..
+            }) => (link_id, control),
+            _ => panic!("expected link control"),
+        }
+    }
+
+    #[test]
+    fn test_link_close() {
+        let air: SimulatedAir<2> = SimulatedAir::new();
+        let interface = AdvertisingBearerNetworkInterface::new(air.bearer().unwrap());
+        let provisioner = air.bearer().unwrap();
+        interface.set_uuid(UUID);
+
+        // the provisioner gives up, such as while waiting on OOB input.
+        transmit_control(&provisioner, 1, ProvisioningBearerControl::LinkOpen(UUID));
+        transmit_control(
+            &provisioner,
+            2,
+            ProvisioningBearerControl::LinkClose(Reason::Fail),
+        );
+        transmit_control(
+            &provisioner,
+            1,
+            ProvisioningBearerControl::LinkClose(Reason::Timeout),
+        );
+        assert!(matches!(
+            block_on(interface.receive()),
+            Ok(PDU::LinkClose(Reason::Timeout))
+        ));
+        assert!(matches!(
+            receive_control(&provisioner),
+            (1, ProvisioningBearerControl::LinkAck)
+        ));
+
+        // a new session can start, and gets abandoned by the device.
+        transmit_control(&provisioner, 3, ProvisioningBearerControl::LinkOpen(UUID));
+        assert!(interface.receive().now_or_never().is_none());
+        assert!(matches!(
+            receive_control(&provisioner),
+            (3, ProvisioningBearerControl::LinkAck)
+        ));
+        block_on(interface.close_link(Reason::Timeout)).unwrap();
+        assert!(matches!(
+            receive_control(&provisioner),
+            (3, ProvisioningBearerControl::LinkClose(Reason::Timeout))
+        ));
+
+        transmit_control(&provisioner, 4, ProvisioningBearerControl::LinkOpen(UUID));
+        assert!(interface.receive().now_or_never().is_none());
+        assert!(matches!(
+            receive_control(&provisioner),
+            (4, ProvisioningBearerControl::LinkAck)
+        ));
+    }
+}
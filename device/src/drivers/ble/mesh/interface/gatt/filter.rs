@@ -0,0 +1,193 @@
+use crate::drivers::ble::mesh::address::Address;
+use crate::drivers::ble::mesh::pdu::proxy::{FilterType, ProxyConfigurationMessage};
+use heapless::Vec;
+
+const MAX_FILTER_SIZE: usize = 16;
+
+/// Proxy filter maintained on behalf of a connected proxy client.
+pub struct ProxyFilter {
+    filter_type: FilterType,
+    addresses: Vec<Address, MAX_FILTER_SIZE>,
+}
+
+impl Default for ProxyFilter {
+    // Each connection starts with an empty whitelist, forwarding
+    // nothing until the proxy client asks for addresses.
+    fn default() -> Self {
+        Self {
+            filter_type: FilterType::Whitelist,
+            addresses: Vec::new(),
+        }
+    }
+}
+
+impl ProxyFilter {
+    /// Start over with an empty whitelist, when a connection opens or closes.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Apply a proxy configuration message, returning the resulting filter status.
+    pub fn configure(
+        &mut self,
+        message: &ProxyConfigurationMessage,
+    ) -> Option<ProxyConfigurationMessage> {
+        match message {
+            ProxyConfigurationMessage::SetFilterType(filter_type) => {
+                self.filter_type = *filter_type;
+                self.addresses.clear();
+            }
+            ProxyConfigurationMessage::AddAddresses(addresses) => {
+                for address in addresses {
+                    self.add(address);
+                }
+            }
+            ProxyConfigurationMessage::RemoveAddresses(addresses) => {
+                for address in addresses {
+                    self.remove(address);
+                }
+            }
+            ProxyConfigurationMessage::FilterStatus { .. } => {
+                // not applicable to the proxy server
+                return None;
+            }
+        }
+
+        Some(ProxyConfigurationMessage::FilterStatus {
+            filter_type: self.filter_type,
+            list_size: self.addresses.len() as u16,
+        })
+    }
+
+    /// Note the source address of a message sent by the proxy client,
+    /// so that replies to it get forwarded.
+    pub fn client_source(&mut self, src: &Address) {
+        match self.filter_type {
+            FilterType::Whitelist => self.add(src),
+            FilterType::Blacklist => self.remove(src),
+        }
+    }
+
+    /// Determine if a message to `dst` should be forwarded to the proxy client.
+    pub fn accepts(&self, dst: &Address) -> bool {
+        match self.filter_type {
+            FilterType::Whitelist => self.contains(dst),
+            FilterType::Blacklist => !self.contains(dst),
+        }
+    }
+
+    fn add(&mut self, address: &Address) {
+        if !self.contains(address) {
+            // silently ignore addresses beyond capacity
+            self.addresses.push(*address).ok();
+        }
+    }
+
+    fn remove(&mut self, address: &Address) {
+        self.addresses
+            .retain(|e| e.as_bytes() != address.as_bytes());
+    }
+
+    fn contains(&self, address: &Address) -> bool {
+        self.addresses
+            .iter()
+            .any(|e| e.as_bytes() == address.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(value: u16) -> Address {
+        Address::parse(value.to_be_bytes())
+    }
+
+    fn addresses(values: &[u16]) -> ProxyConfigurationMessage {
+        let mut addresses = Vec::new();
+        for value in values {
+            addresses.push(address(*value)).unwrap();
+        }
+        ProxyConfigurationMessage::AddAddresses(addresses)
+    }
+
+    #[test]
+    fn test_default_whitelist() {
+        let mut filter = ProxyFilter::default();
+        assert!(!filter.accepts(&address(0x0001)));
+        assert!(!filter.accepts(&address(0xC000)));
+
+        let status = filter.configure(&addresses(&[0x0001, 0xC000, 0x0001]));
+        assert!(matches!(
+            status,
+            Some(ProxyConfigurationMessage::FilterStatus {
+                filter_type: FilterType::Whitelist,
+                list_size: 2,
+            })
+        ));
+        assert!(filter.accepts(&address(0x0001)));
+        assert!(filter.accepts(&address(0xC000)));
+        assert!(!filter.accepts(&address(0x0002)));
+
+        let mut remove = Vec::new();
+        remove.push(address(0x0001)).unwrap();
+        filter.configure(&ProxyConfigurationMessage::RemoveAddresses(remove));
+        assert!(!filter.accepts(&address(0x0001)));
+        assert!(filter.accepts(&address(0xC000)));
+    }
+
+    #[test]
+    fn test_blacklist() {
+        let mut filter = ProxyFilter::default();
+        filter.configure(&addresses(&[0x0001]));
+        let status = filter.configure(&ProxyConfigurationMessage::SetFilterType(
+            FilterType::Blacklist,
+        ));
+        // changing the type clears the list
+        assert!(matches!(
+            status,
+            Some(ProxyConfigurationMessage::FilterStatus {
+                filter_type: FilterType::Blacklist,
+                list_size: 0,
+            })
+        ));
+        assert!(filter.accepts(&address(0x0001)));
+
+        filter.configure(&addresses(&[0x0002]));
+        assert!(filter.accepts(&address(0x0001)));
+        assert!(!filter.accepts(&address(0x0002)));
+    }
+
+    #[test]
+    fn test_client_source() {
+        let mut filter = ProxyFilter::default();
+        filter.client_source(&address(0x0042));
+        assert!(filter.accepts(&address(0x0042)));
+
+        filter.configure(&ProxyConfigurationMessage::SetFilterType(
+            FilterType::Blacklist,
+        ));
+        filter.configure(&addresses(&[0x0042]));
+        assert!(!filter.accepts(&address(0x0042)));
+        filter.client_source(&address(0x0042));
+        assert!(filter.accepts(&address(0x0042)));
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut filter = ProxyFilter::default();
+        filter.configure(&ProxyConfigurationMessage::SetFilterType(
+            FilterType::Blacklist,
+        ));
+        assert!(filter.accepts(&address(0x0001)));
+        filter.reset();
+        assert!(!filter.accepts(&address(0x0001)));
+
+        assert!(filter
+            .configure(&ProxyConfigurationMessage::FilterStatus {
+                filter_type: FilterType::Whitelist,
+                list_size: 0,
+            })
+            .is_none());
+    }
+}
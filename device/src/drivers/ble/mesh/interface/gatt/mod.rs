@@ -1,15 +1,24 @@
+use crate::drivers::ble::mesh::address::Address;
+use crate::drivers::ble::mesh::beacon::SecureNetworkBeacon;
 use crate::drivers::ble::mesh::device::Uuid;
 use crate::drivers::ble::mesh::driver::node::{NetworkId, State};
+use crate::drivers::ble::mesh::interface::gatt::filter::ProxyFilter;
 use crate::drivers::ble::mesh::interface::{Beacon, BearerError, GattBearer, NetworkError, PDU};
 use crate::drivers::ble::mesh::pdu::network::ObfuscatedAndEncryptedNetworkPDU;
-use crate::drivers::ble::mesh::pdu::proxy::{MessageType, ProxyPDU, SAR};
+use crate::drivers::ble::mesh::pdu::proxy::{
+    MessageType, ProxyConfigurationMessage, ProxyPDU, SAR,
+};
 use crate::drivers::ble::mesh::provisioning::ProvisioningPDU;
-use core::cell::Cell;
+use core::cell::{Cell, RefCell};
 use heapless::Vec;
 
+mod filter;
+
 pub struct GattBearerNetworkInterface<B: GattBearer<MTU>, const MTU: usize> {
     uuid: Cell<Option<Uuid>>,
     bearer: B,
+    state: Cell<State>,
+    filter: RefCell<ProxyFilter>,
 }
 
 impl<B: GattBearer<MTU>, const MTU: usize> GattBearerNetworkInterface<B, MTU> {
@@ -17,6 +26,8 @@ impl<B: GattBearer<MTU>, const MTU: usize> GattBearerNetworkInterface<B, MTU> {
         Self {
             uuid: Cell::new(None),
             bearer,
+            state: Cell::new(State::Unprovisioned),
+            filter: RefCell::new(ProxyFilter::default()),
         }
     }
 
@@ -29,12 +40,41 @@ impl<B: GattBearer<MTU>, const MTU: usize> GattBearerNetworkInterface<B, MTU> {
     }
 
     pub(super) fn set_state(&self, state: State) {
+        self.state.set(state);
         self.bearer.set_state(state);
     }
 
+    pub(super) fn configure_filter(
+        &self,
+        message: &ProxyConfigurationMessage,
+    ) -> Option<ProxyConfigurationMessage> {
+        self.filter.borrow_mut().configure(message)
+    }
+
+    /// Let replies to a source address of the proxy client through the filter.
+    pub(super) fn proxy_client_source(&self, src: &Address) {
+        self.filter.borrow_mut().client_source(src);
+    }
+
+    /// Determine if a PDU should be forwarded to the proxy client.
+    pub(super) fn accepts(&self, pdu: &PDU) -> bool {
+        match pdu {
+            PDU::Network(pdu) => match &pdu.dst {
+                Some(dst) => self.filter.borrow().accepts(dst),
+                None => true,
+            },
+            // once provisioned, other devices are only provisioned over PB-ADV.
+            PDU::Provisioning(_) => !matches!(self.state.get(), State::Provisioned),
+            _ => true,
+        }
+    }
+
     pub async fn run(&self) -> Result<(), NetworkError> {
-        self.bearer.run().await?;
-        Ok(())
+        loop {
+            self.bearer.run().await?;
+            // the connection opened or closed.
+            self.filter.borrow_mut().reset();
+        }
     }
 
     pub async fn receive(&self) -> Result<PDU, BearerError> {
@@ -44,11 +84,19 @@ impl<B: GattBearer<MTU>, const MTU: usize> GattBearerNetworkInterface<B, MTU> {
             if let SAR::Complete = proxy_pdu.sar {
                 match proxy_pdu.message_type {
                     MessageType::NetworkPDU => {
-                        let pdu = ObfuscatedAndEncryptedNetworkPDU::parse(&proxy_pdu.data)?;
+                        let mut pdu = ObfuscatedAndEncryptedNetworkPDU::parse(&proxy_pdu.data)?;
+                        pdu.from_proxy_client = true;
                         return Ok(PDU::Network(pdu));
                     }
-                    MessageType::MeshBeacon => {}
-                    MessageType::ProxyConfiguration => {}
+                    MessageType::MeshBeacon => {
+                        if let Ok(beacon) = SecureNetworkBeacon::parse(&proxy_pdu.data) {
+                            return Ok(PDU::Beacon(beacon));
+                        }
+                    }
+                    MessageType::ProxyConfiguration => {
+                        let pdu = ObfuscatedAndEncryptedNetworkPDU::parse(&proxy_pdu.data)?;
+                        return Ok(PDU::ProxyConfiguration(pdu));
+                    }
                     MessageType::ProvisioningPDU => {
                         let pdu = ProvisioningPDU::parse(&proxy_pdu.data)?;
                         return Ok(PDU::Provisioning(pdu));
@@ -86,6 +134,32 @@ impl<B: GattBearer<MTU>, const MTU: usize> GattBearerNetworkInterface<B, MTU> {
 
                 self.transmit_proxy_pdu(&proxy_pdu).await
             }
+            PDU::ProxyConfiguration(pdu) => {
+                let mut data = Vec::new();
+                pdu.emit(&mut data)?;
+                let proxy_pdu = ProxyPDU {
+                    sar: SAR::Complete,
+                    message_type: MessageType::ProxyConfiguration,
+                    data,
+                };
+
+                self.transmit_proxy_pdu(&proxy_pdu).await
+            }
+            PDU::Beacon(beacon) => {
+                let mut data = Vec::new();
+                beacon.emit(&mut data)?;
+                let proxy_pdu = ProxyPDU {
+                    sar: SAR::Complete,
+                    message_type: MessageType::MeshBeacon,
+                    data,
+                };
+
+                self.transmit_proxy_pdu(&proxy_pdu).await
+            }
+            PDU::UnprovisionedBeacon(_) | PDU::LinkClose(_) => {
+                // only ever received
+                Ok(())
+            }
         }
     }
 
@@ -131,6 +205,21 @@ impl<B: GattBearer<MTU>, const MTU: usize> GattBearerNetworkInterface<B, MTU> {
                 adv_data.extend_from_slice(&network_id.0)?;
                 self.bearer.advertise(&adv_data).await?;
             }
+            Beacon::NodeIdentity(node_identity) => {
+                let mut adv_data = Vec::new();
+
+                #[rustfmt::skip]
+                adv_data.extend_from_slice(&[
+                    0x02, 0x01, 0x06,
+                    0x03, 0x03, 0x28, 0x18,
+                    0x14, 0x16, 0x28, 0x18
+                ]).unwrap();
+
+                adv_data.push(0x01)?; // node identity
+                adv_data.extend_from_slice(&node_identity.hash)?;
+                adv_data.extend_from_slice(&node_identity.random)?;
+                self.bearer.advertise(&adv_data).await?;
+            }
             Beacon::Secure => {
                 // nothing yet
             }
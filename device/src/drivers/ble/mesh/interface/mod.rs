@@ -1,15 +1,19 @@
 pub mod advertising;
 pub mod gatt;
 
+use crate::drivers::ble::mesh::address::Address;
+use crate::drivers::ble::mesh::beacon::{SecureNetworkBeacon, UnprovisionedDeviceBeacon};
 use crate::drivers::ble::mesh::pdu::network::ObfuscatedAndEncryptedNetworkPDU;
 use core::future::Future;
 use embassy::util::{select, Either};
 use futures::future::join;
 
 use crate::drivers::ble::mesh::device::Uuid;
-use crate::drivers::ble::mesh::driver::node::{NetworkId, State};
+use crate::drivers::ble::mesh::driver::node::{NetworkId, NodeIdentity, State};
+use crate::drivers::ble::mesh::generic_provisioning::Reason;
 use crate::drivers::ble::mesh::interface::advertising::AdvertisingBearerNetworkInterface;
 use crate::drivers::ble::mesh::interface::gatt::GattBearerNetworkInterface;
+use crate::drivers::ble::mesh::pdu::proxy::ProxyConfigurationMessage;
 use crate::drivers::ble::mesh::pdu::ParseError;
 use crate::drivers::ble::mesh::provisioning::ProvisioningPDU;
 use crate::drivers::ble::mesh::InsufficientBuffer;
@@ -34,6 +38,7 @@ impl From<BearerError> for NetworkError {
 pub enum Beacon {
     Unprovisioned,
     Provisioned(NetworkId),
+    NodeIdentity(NodeIdentity),
     Secure,
 }
 
@@ -41,6 +46,11 @@ pub enum Beacon {
 pub enum PDU {
     Provisioning(ProvisioningPDU),
     Network(ObfuscatedAndEncryptedNetworkPDU),
+    Beacon(SecureNetworkBeacon),
+    ProxyConfiguration(ObfuscatedAndEncryptedNetworkPDU),
+    UnprovisionedBeacon(UnprovisionedDeviceBeacon),
+    /// The provisioner closed the provisioning link to this device.
+    LinkClose(Reason),
 }
 
 /// A possibly plurality of network interfaces covering one or more bearers.
@@ -52,6 +62,17 @@ pub trait NetworkInterfaces {
 
     fn set_uuid(&self, uuid: Uuid);
 
+    /// Apply a proxy configuration message to the proxy filter of any proxy-capable
+    /// interface, returning the filter status to report back to the proxy client.
+    fn configure_proxy_filter(
+        &self,
+        message: &ProxyConfigurationMessage,
+    ) -> Option<ProxyConfigurationMessage>;
+
+    /// Note the source address of a network PDU received from a proxy client,
+    /// so the proxy filter forwards replies to it.
+    fn proxy_client_source(&self, src: &Address);
+
     type RunFuture<'m>: Future<Output = Result<(), NetworkError>> + 'm
     where
         Self: 'm;
@@ -86,6 +107,20 @@ pub trait NetworkInterfaces {
 
     /// Perform beaconing on all of the network interfaces.
     fn beacon<'m>(&'m self, beacon: Beacon) -> Self::BeaconFuture<'m>;
+
+    type OpenLinkFuture<'m>: Future<Output = Result<(), NetworkError>> + 'm
+    where
+        Self: 'm;
+
+    /// Open a provisioning link to an unprovisioned device, as a provisioner.
+    fn open_link<'m>(&'m self, uuid: Uuid, link_id: u32) -> Self::OpenLinkFuture<'m>;
+
+    type CloseLinkFuture<'m>: Future<Output = Result<(), NetworkError>> + 'm
+    where
+        Self: 'm;
+
+    /// Close the current provisioning link, whichever end opened it.
+    fn close_link<'m>(&'m self, reason: Reason) -> Self::CloseLinkFuture<'m>;
 }
 
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -153,6 +188,7 @@ pub trait GattBearer<const MTU: usize> {
     where
         Self: 'm;
 
+    /// Run the bearer until a client connects or disconnects.
     fn run<'m>(&'m self) -> Self::RunFuture<'m>;
 
     type ReceiveFuture<'m>: Future<Output = Result<Vec<u8, MTU>, BearerError>> + 'm
@@ -211,6 +247,17 @@ impl<AB: AdvertisingBearer, GB: GattBearer<MTU>, const MTU: usize> NetworkInterf
         self.gatt_interface.set_uuid(uuid);
     }
 
+    fn configure_proxy_filter(
+        &self,
+        message: &ProxyConfigurationMessage,
+    ) -> Option<ProxyConfigurationMessage> {
+        self.gatt_interface.configure_filter(message)
+    }
+
+    fn proxy_client_source(&self, src: &Address) {
+        self.gatt_interface.proxy_client_source(src);
+    }
+
     type RunFuture<'m> = impl Future<Output=Result<(), NetworkError>> + 'm
     where
     Self: 'm;
@@ -243,10 +290,15 @@ impl<AB: AdvertisingBearer, GB: GattBearer<MTU>, const MTU: usize> NetworkInterf
     fn transmit<'m>(&'m self, pdu: &'m PDU) -> Self::TransmitFuture<'m> {
         //async move { Ok(self.advertising_interface.transmit(pdu).await?) }
         async move {
-            let gatt_fut = self.gatt_interface.transmit(pdu);
-            let adv_fut = self.advertising_interface.transmit(pdu);
-
-            let _result = join(gatt_fut, adv_fut).await;
+            if self.gatt_interface.accepts(pdu) {
+                let gatt_fut = self.gatt_interface.transmit(pdu);
+                let adv_fut = self.advertising_interface.transmit(pdu);
+
+                let _result = join(gatt_fut, adv_fut).await;
+            } else {
+                // filtered out by the proxy client.
+                let _result = self.advertising_interface.transmit(pdu).await;
+            }
             Ok(())
         }
     }
@@ -270,6 +322,21 @@ impl<AB: AdvertisingBearer, GB: GattBearer<MTU>, const MTU: usize> NetworkInterf
             Ok(())
         }
     }
+    type OpenLinkFuture<'m> = impl Future<Output=Result<(), NetworkError>> + 'm
+    where
+    Self: 'm;
+
+    fn open_link<'m>(&'m self, uuid: Uuid, link_id: u32) -> Self::OpenLinkFuture<'m> {
+        async move { Ok(self.advertising_interface.open_link(uuid, link_id).await?) }
+    }
+
+    type CloseLinkFuture<'m> = impl Future<Output=Result<(), NetworkError>> + 'm
+    where
+    Self: 'm;
+
+    fn close_link<'m>(&'m self, reason: Reason) -> Self::CloseLinkFuture<'m> {
+        async move { Ok(self.advertising_interface.close_link(reason).await?) }
+    }
 }
 
 pub struct AdvertisingOnlyNetworkInterfaces<B: AdvertisingBearer> {
@@ -293,6 +360,18 @@ impl<B: AdvertisingBearer> NetworkInterfaces for AdvertisingOnlyNetworkInterface
         self.interface.set_uuid(uuid);
     }
 
+    fn configure_proxy_filter(
+        &self,
+        _message: &ProxyConfigurationMessage,
+    ) -> Option<ProxyConfigurationMessage> {
+        // no proxy-capable bearers
+        None
+    }
+
+    fn proxy_client_source(&self, _src: &Address) {
+        // no proxy-capable bearers
+    }
+
     type RunFuture<'m> = impl Future<Output=Result<(), NetworkError>> + 'm
     where
     Self: 'm;
@@ -335,4 +414,19 @@ impl<B: AdvertisingBearer> NetworkInterfaces for AdvertisingOnlyNetworkInterface
     fn beacon<'m>(&'m self, beacon: Beacon) -> Self::BeaconFuture<'m> {
         async move { Ok(self.interface.beacon(beacon).await?) }
     }
+    type OpenLinkFuture<'m> = impl Future<Output=Result<(), NetworkError>> + 'm
+    where
+    Self: 'm;
+
+    fn open_link<'m>(&'m self, uuid: Uuid, link_id: u32) -> Self::OpenLinkFuture<'m> {
+        async move { Ok(self.interface.open_link(uuid, link_id).await?) }
+    }
+
+    type CloseLinkFuture<'m> = impl Future<Output=Result<(), NetworkError>> + 'm
+    where
+    Self: 'm;
+
+    fn close_link<'m>(&'m self, reason: Reason) -> Self::CloseLinkFuture<'m> {
+        async move { Ok(self.interface.close_link(reason).await?) }
+    }
 }
@@ -14,6 +14,7 @@ pub mod driver;
 pub mod generic_provisioning;
 pub mod interface;
 pub mod model;
+pub mod oob;
 pub mod pdu;
 pub mod provisioning;
 pub mod status;
@@ -39,6 +39,27 @@ impl AppKeyMessage {
         }
     }
 
+    pub fn parse_update(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 19 {
+            let indexes = NetKeyAppKeyIndexesPair::parse(&parameters[0..=2])?;
+            let app_key = parameters[3..]
+                .try_into()
+                .map_err(|_| ParseError::InvalidLength)?;
+            Ok(Self::Update(AppKeyUpdateMessage { indexes, app_key }))
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    pub fn parse_delete(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 3 {
+            let indexes = NetKeyAppKeyIndexesPair::parse(parameters)?;
+            Ok(Self::Delete(AppKeyDeleteMessage { indexes }))
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
     pub fn parse_get(parameters: &[u8]) -> Result<Self, ParseError> {
         if parameters.len() == 2 {
             let net_key_index = NetKeyIndex(KeyIndex::parse_one(parameters)?);
@@ -47,6 +68,34 @@ impl AppKeyMessage {
             Err(ParseError::InvalidLength)
         }
     }
+
+    pub fn parse_list(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() >= 3 {
+            let status = Status::parse(parameters[0])?;
+            let net_key_index = NetKeyIndex(KeyIndex::parse_one(&parameters[1..=2])?);
+            let app_key_indexes = KeyIndex::parse_many::<10>(&parameters[3..])?
+                .iter()
+                .map(|e| AppKeyIndex(*e))
+                .collect();
+            Ok(Self::List(AppKeyListMessage {
+                status,
+                net_key_index,
+                app_key_indexes,
+            }))
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    pub fn parse_status(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 4 {
+            let status = Status::parse(parameters[0])?;
+            let indexes = NetKeyAppKeyIndexesPair::parse(&parameters[1..])?;
+            Ok(Self::Status(AppKeyStatusMessage { status, indexes }))
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
 }
 
 impl Message for AppKeyMessage {
@@ -57,7 +106,7 @@ impl Message for AppKeyMessage {
             Self::Get(_) => CONFIG_APPKEY_GET,
             Self::List(_) => CONFIG_APPKEY_LIST,
             Self::Status(_) => CONFIG_APPKEY_STATUS,
-            Self::Update(_) => CONFIG_APPKEY_STATUS,
+            Self::Update(_) => CONFIG_APPKEY_UPDATE,
         }
     }
 
@@ -85,9 +134,12 @@ pub struct AppKeyAddMessage {
 impl AppKeyAddMessage {
     fn emit_parameters<const N: usize>(
         &self,
-        _xmit: &mut Vec<u8, N>,
+        xmit: &mut Vec<u8, N>,
     ) -> Result<(), InsufficientBuffer> {
-        todo!();
+        self.indexes.emit(xmit)?;
+        xmit.extend_from_slice(&self.app_key)
+            .map_err(|_| InsufficientBuffer)?;
+        Ok(())
     }
 }
 
@@ -99,9 +151,9 @@ pub struct AppKeyDeleteMessage {
 impl AppKeyDeleteMessage {
     fn emit_parameters<const N: usize>(
         &self,
-        _xmit: &mut Vec<u8, N>,
+        xmit: &mut Vec<u8, N>,
     ) -> Result<(), InsufficientBuffer> {
-        todo!();
+        self.indexes.emit(xmit)
     }
 }
 
@@ -113,9 +165,9 @@ pub struct AppKeyGetMessage {
 impl AppKeyGetMessage {
     fn emit_parameters<const N: usize>(
         &self,
-        _xmit: &mut Vec<u8, N>,
+        xmit: &mut Vec<u8, N>,
     ) -> Result<(), InsufficientBuffer> {
-        todo!()
+        self.net_key_index.emit(xmit)
     }
 }
 
@@ -134,24 +186,8 @@ impl AppKeyListMessage {
         xmit.push(self.status as u8)
             .map_err(|_| InsufficientBuffer)?;
         self.net_key_index.emit(xmit)?;
-
-        /*
-        for (i, app_key_index) in self.app_key_indexes.iter().enumerate() {
-            if (i + 1) % 2 == 0 {
-                app_key_index.0.emit_second(xmit)?;
-            } else {
-                app_key_index.0.emit_first(xmit)?;
-            }
-        }
-         */
-        for chunk in self.app_key_indexes.chunks(2) {
-            if chunk.len() == 2 {
-                KeyIndex::emit_two((&chunk[0].0, &chunk[1].0), xmit)?;
-            } else {
-                KeyIndex::emit_one(&chunk[0].0, xmit)?;
-            }
-        }
-
+        let indexes: Vec<KeyIndex, 10> = self.app_key_indexes.iter().map(|e| e.0).collect();
+        KeyIndex::emit_many(&indexes, xmit)?;
         Ok(())
     }
 }
@@ -176,16 +212,141 @@ impl AppKeyStatusMessage {
 
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct AppKeyUpdateMessage {
-    pub(crate) net_key_index: NetKeyIndex,
-    pub(crate) app_key_index: AppKeyIndex,
+    pub(crate) indexes: NetKeyAppKeyIndexesPair,
     pub(crate) app_key: [u8; 16],
 }
 
 impl AppKeyUpdateMessage {
     fn emit_parameters<const N: usize>(
         &self,
-        _xmit: &mut Vec<u8, N>,
+        xmit: &mut Vec<u8, N>,
     ) -> Result<(), InsufficientBuffer> {
-        todo!()
+        self.indexes.emit(xmit)?;
+        xmit.extend_from_slice(&self.app_key)
+            .map_err(|_| InsufficientBuffer)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn indexes() -> NetKeyAppKeyIndexesPair {
+        NetKeyAppKeyIndexesPair(NetKeyIndex::new(0x123), AppKeyIndex(KeyIndex(0x456)))
+    }
+
+    fn emit(message: AppKeyMessage) -> Vec<u8, 64> {
+        let mut xmit = Vec::new();
+        message.emit_parameters(&mut xmit).unwrap();
+        xmit
+    }
+
+    #[test]
+    fn test_indexes_packing() {
+        let xmit = emit(AppKeyMessage::Delete(AppKeyDeleteMessage {
+            indexes: indexes(),
+        }));
+        assert_eq!(&[0x23, 0x61, 0x45], &xmit[..]);
+    }
+
+    #[test]
+    fn test_add_round_trip() {
+        let app_key = [0xAA; 16];
+        let xmit = emit(AppKeyMessage::Add(AppKeyAddMessage {
+            indexes: indexes(),
+            app_key,
+        }));
+        match AppKeyMessage::parse_add(&xmit).unwrap() {
+            AppKeyMessage::Add(add) => {
+                assert_eq!(NetKeyIndex::new(0x123), add.indexes.net_key());
+                assert_eq!(AppKeyIndex(KeyIndex(0x456)), add.indexes.app_key());
+                assert_eq!(app_key, add.app_key);
+            }
+            _ => panic!("expected add"),
+        }
+    }
+
+    #[test]
+    fn test_update_round_trip() {
+        let app_key = [0x55; 16];
+        let xmit = emit(AppKeyMessage::Update(AppKeyUpdateMessage {
+            indexes: indexes(),
+            app_key,
+        }));
+        match AppKeyMessage::parse_update(&xmit).unwrap() {
+            AppKeyMessage::Update(update) => {
+                assert_eq!(NetKeyIndex::new(0x123), update.indexes.net_key());
+                assert_eq!(AppKeyIndex(KeyIndex(0x456)), update.indexes.app_key());
+                assert_eq!(app_key, update.app_key);
+            }
+            _ => panic!("expected update"),
+        }
+    }
+
+    #[test]
+    fn test_delete_round_trip() {
+        let xmit = emit(AppKeyMessage::Delete(AppKeyDeleteMessage {
+            indexes: indexes(),
+        }));
+        match AppKeyMessage::parse_delete(&xmit).unwrap() {
+            AppKeyMessage::Delete(delete) => {
+                assert_eq!(NetKeyIndex::new(0x123), delete.indexes.net_key());
+                assert_eq!(AppKeyIndex(KeyIndex(0x456)), delete.indexes.app_key());
+            }
+            _ => panic!("expected delete"),
+        }
+    }
+
+    #[test]
+    fn test_get_round_trip() {
+        let xmit = emit(AppKeyMessage::Get(AppKeyGetMessage {
+            net_key_index: NetKeyIndex::new(0xABC),
+        }));
+        assert_eq!(&[0xBC, 0x0A], &xmit[..]);
+        match AppKeyMessage::parse_get(&xmit).unwrap() {
+            AppKeyMessage::Get(get) => {
+                assert_eq!(NetKeyIndex::new(0xABC), get.net_key_index);
+            }
+            _ => panic!("expected get"),
+        }
+    }
+
+    #[test]
+    fn test_list_round_trip() {
+        for count in 0..=5 {
+            let app_key_indexes: Vec<AppKeyIndex, 10> = (0..count)
+                .map(|i| AppKeyIndex(KeyIndex(0x100 + i)))
+                .collect();
+            let xmit = emit(AppKeyMessage::List(AppKeyListMessage {
+                status: Status::Success,
+                net_key_index: NetKeyIndex::new(0x001),
+                app_key_indexes: app_key_indexes.clone(),
+            }));
+            match AppKeyMessage::parse_list(&xmit).unwrap() {
+                AppKeyMessage::List(list) => {
+                    assert_eq!(Status::Success, list.status);
+                    assert_eq!(NetKeyIndex::new(0x001), list.net_key_index);
+                    assert_eq!(app_key_indexes, list.app_key_indexes);
+                }
+                _ => panic!("expected list"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_status_round_trip() {
+        let xmit = emit(AppKeyMessage::Status(AppKeyStatusMessage {
+            status: Status::InvalidBinding,
+            indexes: indexes(),
+        }));
+        match AppKeyMessage::parse_status(&xmit).unwrap() {
+            AppKeyMessage::Status(status) => {
+                assert_eq!(Status::InvalidBinding, status.status);
+                assert_eq!(NetKeyIndex::new(0x123), status.indexes.net_key());
+                assert_eq!(AppKeyIndex(KeyIndex(0x456)), status.indexes.app_key());
+            }
+            _ => panic!("expected status"),
+        }
     }
 }
@@ -1,4 +1,7 @@
-use crate::drivers::ble::mesh::composition::Composition;
+use crate::drivers::ble::mesh::composition::{
+    CompanyIdentifier, Composition, ElementDescriptor, Features, Location, ProductIdentifier,
+    VersionIdentifier,
+};
 use crate::drivers::ble::mesh::model::{Message, ModelIdentifier};
 use crate::drivers::ble::mesh::pdu::access::Opcode;
 use crate::drivers::ble::mesh::pdu::ParseError;
@@ -24,6 +27,10 @@ impl CompositionDataMessage {
             Err(ParseError::InvalidLength)
         }
     }
+
+    pub fn parse_status(parameters: &[u8]) -> Result<Self, ParseError> {
+        Ok(Self::Status(CompositionStatus::parse(parameters)?))
+    }
 }
 
 impl Message for CompositionDataMessage {
@@ -55,18 +62,71 @@ pub struct CompositionStatus {
 }
 
 impl CompositionStatus {
+    pub fn page(&self) -> u8 {
+        self.page
+    }
+
+    pub fn data(&self) -> &Composition {
+        &self.data
+    }
+
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() < 11 {
+            return Err(ParseError::InvalidLength);
+        }
+        let page = parameters[0];
+        let cid = CompanyIdentifier::parse(&parameters[1..=2])?;
+        let pid = ProductIdentifier(u16::from_le_bytes([parameters[3], parameters[4]]));
+        let vid = VersionIdentifier(u16::from_le_bytes([parameters[5], parameters[6]]));
+        let crpl = u16::from_le_bytes([parameters[7], parameters[8]]);
+        let features = Features::parse(&parameters[9..=10])?;
+
+        let mut data = Composition::new(cid, pid, vid, features);
+        data.crpl = crpl;
+
+        let mut remaining = &parameters[11..];
+        while !remaining.is_empty() {
+            if remaining.len() < 4 {
+                return Err(ParseError::InvalidLength);
+            }
+            let loc = Location(u16::from_le_bytes([remaining[0], remaining[1]]));
+            let num_s = remaining[2] as usize;
+            let num_v = remaining[3] as usize;
+            remaining = &remaining[4..];
+            if remaining.len() < num_s * 2 + num_v * 4 {
+                return Err(ParseError::InvalidLength);
+            }
+
+            let mut element = ElementDescriptor::new(loc);
+            let (sig_models, rest) = remaining.split_at(num_s * 2);
+            let (vendor_models, rest) = rest.split_at(num_v * 4);
+            for model in sig_models.chunks(2).chain(vendor_models.chunks(4)) {
+                element
+                    .models
+                    .push(ModelIdentifier::parse(model)?)
+                    .map_err(|_| ParseError::InsufficientBuffer)?;
+            }
+            data.elements
+                .push(element)
+                .map_err(|_| ParseError::InsufficientBuffer)?;
+            remaining = rest;
+        }
+
+        Ok(Self { page, data })
+    }
+
     fn emit_parameters<const N: usize>(
         &self,
         xmit: &mut Vec<u8, N>,
     ) -> Result<(), InsufficientBuffer> {
         xmit.push(self.page).map_err(|_| InsufficientBuffer)?;
-        xmit.extend_from_slice(&self.data.cid.0.to_be_bytes())
+        xmit.extend_from_slice(&self.data.cid.0.to_le_bytes())
             .map_err(|_| InsufficientBuffer)?;
-        xmit.extend_from_slice(&self.data.pid.0.to_be_bytes())
+        xmit.extend_from_slice(&self.data.pid.0.to_le_bytes())
             .map_err(|_| InsufficientBuffer)?;
-        xmit.extend_from_slice(&self.data.vid.0.to_be_bytes())
+        xmit.extend_from_slice(&self.data.vid.0.to_le_bytes())
             .map_err(|_| InsufficientBuffer)?;
-        xmit.extend_from_slice(&self.data.crpl.to_be_bytes())
+        xmit.extend_from_slice(&self.data.crpl.to_le_bytes())
             .map_err(|_| InsufficientBuffer)?;
         self.data.features.emit(xmit)?;
         for element in self.data.elements.iter() {
@@ -99,3 +159,62 @@ impl CompositionStatus {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_round_trip() {
+        let mut data = Composition::new(
+            CompanyIdentifier(0x0003),
+            ProductIdentifier(0x0102),
+            VersionIdentifier(0x0304),
+            Features {
+                relay: true,
+                proxy: false,
+                friend: false,
+                low_power: true,
+            },
+        );
+        data.add_element(
+            ElementDescriptor::new(Location(0x0100))
+                .add_model(ModelIdentifier::SIG(0x0000))
+                .add_model(ModelIdentifier::Vendor(CompanyIdentifier(0x0059), 0x0001))
+                .add_model(ModelIdentifier::SIG(0x1000)),
+        )
+        .ok();
+        data.add_element(ElementDescriptor::new(Location(0x0000)))
+            .ok();
+
+        let mut xmit: Vec<u8, 64> = Vec::new();
+        CompositionDataMessage::Status(CompositionStatus { page: 0, data })
+            .emit_parameters(&mut xmit)
+            .unwrap();
+        assert_eq!(&[0x00, 0x03, 0x00, 0x02, 0x01], &xmit[0..5]);
+
+        match CompositionDataMessage::parse_status(&xmit).unwrap() {
+            CompositionDataMessage::Status(status) => {
+                assert_eq!(0, status.page());
+                let data = status.data();
+                assert_eq!(CompanyIdentifier(0x0003), data.cid());
+                assert_eq!(0x0102, data.pid().0);
+                assert_eq!(0x0304, data.vid().0);
+                assert!(data.features().relay);
+                assert!(data.features().low_power);
+                assert_eq!(2, data.elements().len());
+                assert_eq!(0x0100, data.elements()[0].loc().0);
+                assert_eq!(
+                    &[
+                        ModelIdentifier::SIG(0x0000),
+                        ModelIdentifier::SIG(0x1000),
+                        ModelIdentifier::Vendor(CompanyIdentifier(0x0059), 0x0001),
+                    ],
+                    data.elements()[0].models()
+                );
+                assert!(data.elements()[1].models().is_empty());
+            }
+            _ => panic!("expected status"),
+        }
+    }
+}
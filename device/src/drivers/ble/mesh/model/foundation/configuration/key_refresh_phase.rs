@@ -0,0 +1,213 @@
+use crate::drivers::ble::mesh::model::foundation::configuration::{KeyIndex, NetKeyIndex};
+use crate::drivers::ble::mesh::model::{Message, Status};
+use crate::drivers::ble::mesh::pdu::access::Opcode;
+use crate::drivers::ble::mesh::pdu::ParseError;
+use crate::drivers::ble::mesh::InsufficientBuffer;
+use crate::opcode;
+use heapless::Vec;
+use serde::{Deserialize, Serialize};
+
+opcode!( CONFIG_KEY_REFRESH_PHASE_GET 0x80, 0x15 );
+opcode!( CONFIG_KEY_REFRESH_PHASE_SET 0x80, 0x16 );
+opcode!( CONFIG_KEY_REFRESH_PHASE_STATUS 0x80, 0x17 );
+
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum KeyRefreshPhase {
+    /// Normal operation, only the current keys are in use.
+    Normal,
+    /// New keys distributed, old keys still used for transmission.
+    Phase1,
+    /// New keys used for transmission, old keys still accepted.
+    Phase2,
+}
+
+impl Default for KeyRefreshPhase {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+impl KeyRefreshPhase {
+    pub fn parse(val: u8) -> Result<Self, ParseError> {
+        match val {
+            0x00 => Ok(Self::Normal),
+            0x01 => Ok(Self::Phase1),
+            0x02 => Ok(Self::Phase2),
+            _ => Err(ParseError::InvalidValue),
+        }
+    }
+
+    pub fn emit<const N: usize>(&self, xmit: &mut Vec<u8, N>) -> Result<(), InsufficientBuffer> {
+        let val = match self {
+            Self::Normal => 0x00,
+            Self::Phase1 => 0x01,
+            Self::Phase2 => 0x02,
+        };
+        xmit.push(val).map_err(|_| InsufficientBuffer)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum KeyRefreshTransition {
+    /// Transition 2: start transmitting using the new keys.
+    UseNewKeys,
+    /// Transition 3: revoke the old keys.
+    RevokeOldKeys,
+}
+
+impl KeyRefreshTransition {
+    pub fn parse(val: u8) -> Result<Self, ParseError> {
+        match val {
+            0x02 => Ok(Self::UseNewKeys),
+            0x03 => Ok(Self::RevokeOldKeys),
+            _ => Err(ParseError::InvalidValue),
+        }
+    }
+
+    pub fn emit<const N: usize>(&self, xmit: &mut Vec<u8, N>) -> Result<(), InsufficientBuffer> {
+        let val = match self {
+            Self::UseNewKeys => 0x02,
+            Self::RevokeOldKeys => 0x03,
+        };
+        xmit.push(val).map_err(|_| InsufficientBuffer)
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum KeyRefreshPhaseMessage {
+    Get(NetKeyIndex),
+    Set(KeyRefreshPhaseSetMessage),
+    Status(KeyRefreshPhaseStatusMessage),
+}
+
+#[allow(unused)]
+impl KeyRefreshPhaseMessage {
+    pub fn parse_get(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 2 {
+            Ok(Self::Get(NetKeyIndex(KeyIndex::parse_one(parameters)?)))
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    pub fn parse_set(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 3 {
+            let net_key_index = NetKeyIndex(KeyIndex::parse_one(&parameters[0..=1])?);
+            let transition = KeyRefreshTransition::parse(parameters[2])?;
+            Ok(Self::Set(KeyRefreshPhaseSetMessage {
+                net_key_index,
+                transition,
+            }))
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+}
+
+impl Message for KeyRefreshPhaseMessage {
+    fn opcode(&self) -> Opcode {
+        match self {
+            Self::Get(_) => CONFIG_KEY_REFRESH_PHASE_GET,
+            Self::Set(_) => CONFIG_KEY_REFRESH_PHASE_SET,
+            Self::Status(_) => CONFIG_KEY_REFRESH_PHASE_STATUS,
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        match self {
+            Self::Get(net_key_index) => net_key_index.emit(xmit),
+            Self::Set(inner) => inner.emit_parameters(xmit),
+            Self::Status(inner) => inner.emit_parameters(xmit),
+        }
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct KeyRefreshPhaseSetMessage {
+    pub(crate) net_key_index: NetKeyIndex,
+    pub(crate) transition: KeyRefreshTransition,
+}
+
+impl KeyRefreshPhaseSetMessage {
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        self.net_key_index.emit(xmit)?;
+        self.transition.emit(xmit)?;
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct KeyRefreshPhaseStatusMessage {
+    pub(crate) status: Status,
+    pub(crate) net_key_index: NetKeyIndex,
+    pub(crate) phase: KeyRefreshPhase,
+}
+
+impl KeyRefreshPhaseStatusMessage {
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        xmit.push(self.status as u8)
+            .map_err(|_| InsufficientBuffer)?;
+        self.net_key_index.emit(xmit)?;
+        self.phase.emit(xmit)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_set() {
+        match KeyRefreshPhaseMessage::parse_set(&[0x23, 0x01, 0x02]).unwrap() {
+            KeyRefreshPhaseMessage::Set(set) => {
+                assert_eq!(NetKeyIndex::new(0x123), set.net_key_index);
+                assert_eq!(KeyRefreshTransition::UseNewKeys, set.transition);
+            }
+            _ => panic!("expected set"),
+        }
+        // only transitions 2 and 3 can be set
+        assert!(matches!(
+            KeyRefreshPhaseMessage::parse_set(&[0x23, 0x01, 0x01]),
+            Err(ParseError::InvalidValue)
+        ));
+        assert!(matches!(
+            KeyRefreshPhaseMessage::parse_get(&[0x23]),
+            Err(ParseError::InvalidLength)
+        ));
+    }
+
+    #[test]
+    fn test_emit_status() {
+        let mut xmit: Vec<u8, 8> = Vec::new();
+        KeyRefreshPhaseMessage::Status(KeyRefreshPhaseStatusMessage {
+            status: Status::Success,
+            net_key_index: NetKeyIndex::new(0x123),
+            phase: KeyRefreshPhase::Phase2,
+        })
+        .emit_parameters(&mut xmit)
+        .unwrap();
+        assert_eq!(&[0x00, 0x23, 0x01, 0x02], &xmit[..]);
+
+        for phase in [
+            KeyRefreshPhase::Normal,
+            KeyRefreshPhase::Phase1,
+            KeyRefreshPhase::Phase2,
+        ] {
+            let mut xmit: Vec<u8, 1> = Vec::new();
+            phase.emit(&mut xmit).unwrap();
+            assert_eq!(phase, KeyRefreshPhase::parse(xmit[0]).unwrap());
+        }
+    }
+}
@@ -1,34 +1,48 @@
 use crate::drivers::ble::mesh::model::foundation::configuration::app_key::{
-    AppKeyMessage, CONFIG_APPKEY_ADD, CONFIG_APPKEY_GET,
+    AppKeyMessage, CONFIG_APPKEY_ADD, CONFIG_APPKEY_DELETE, CONFIG_APPKEY_GET, CONFIG_APPKEY_LIST,
+    CONFIG_APPKEY_STATUS, CONFIG_APPKEY_UPDATE,
 };
 use crate::drivers::ble::mesh::model::foundation::configuration::beacon::{
     BeaconMessage, CONFIG_BEACON_GET, CONFIG_BEACON_SET,
 };
 use crate::drivers::ble::mesh::model::foundation::configuration::composition_data::{
-    CompositionDataMessage, CONFIG_COMPOSITION_DATA_GET,
+    CompositionDataMessage, CONFIG_COMPOSITION_DATA_GET, CONFIG_COMPOSITION_DATA_STATUS,
 };
 use crate::drivers::ble::mesh::model::foundation::configuration::default_ttl::{
     DefaultTTLMessage, CONFIG_DEFAULT_TTL_GET, CONFIG_DEFAULT_TTL_SET,
 };
+use crate::drivers::ble::mesh::model::foundation::configuration::key_refresh_phase::{
+    KeyRefreshPhaseMessage, CONFIG_KEY_REFRESH_PHASE_GET, CONFIG_KEY_REFRESH_PHASE_SET,
+};
 use crate::drivers::ble::mesh::model::foundation::configuration::model_app::{
-    ModelAppMessage, CONFIG_MODEL_APP_BIND, CONFIG_MODEL_APP_UNBIND,
+    ModelAppMessage, CONFIG_MODEL_APP_BIND, CONFIG_MODEL_APP_STATUS, CONFIG_MODEL_APP_UNBIND,
 };
 use crate::drivers::ble::mesh::model::foundation::configuration::model_publication::{
-    ModelPublicationMessage, CONFIG_MODEL_PUBLICATION_SET,
-    CONFIG_MODEL_PUBLICATION_VIRTUAL_ADDRESS_SET,
+    ModelPublicationMessage, CONFIG_MODEL_PUBLICATION_GET, CONFIG_MODEL_PUBLICATION_SET,
+    CONFIG_MODEL_PUBLICATION_STATUS, CONFIG_MODEL_PUBLICATION_VIRTUAL_ADDRESS_SET,
 };
 
 use crate::drivers::ble::mesh::model::foundation::configuration::model_subscription::{
-    ModelSubscriptionMessage, CONFIG_MODEL_SUBSCRIPTION_ADD,
-    CONFIG_MODEL_SUBSCRIPTION_VIRTUAL_ADDRESS_ADD,
+    ModelSubscriptionMessage, CONFIG_MODEL_SUBSCRIPTION_ADD, CONFIG_MODEL_SUBSCRIPTION_DELETE,
+    CONFIG_MODEL_SUBSCRIPTION_DELETE_ALL, CONFIG_MODEL_SUBSCRIPTION_OVERWRITE,
+    CONFIG_MODEL_SUBSCRIPTION_STATUS, CONFIG_MODEL_SUBSCRIPTION_VIRTUAL_ADDRESS_ADD,
+    CONFIG_MODEL_SUBSCRIPTION_VIRTUAL_ADDRESS_DELETE,
+    CONFIG_MODEL_SUBSCRIPTION_VIRTUAL_ADDRESS_OVERWRITE,
+};
+use crate::drivers::ble::mesh::model::foundation::configuration::net_key::{
+    NetKeyMessage, CONFIG_NETKEY_ADD, CONFIG_NETKEY_DELETE, CONFIG_NETKEY_GET, CONFIG_NETKEY_UPDATE,
+};
+use crate::drivers::ble::mesh::model::foundation::configuration::node_identity::{
+    NodeIdentityMessage, CONFIG_NODE_IDENTITY_GET, CONFIG_NODE_IDENTITY_SET,
+    CONFIG_NODE_IDENTITY_STATUS,
 };
 use crate::drivers::ble::mesh::model::foundation::configuration::node_reset::{
-    NodeResetMessage, CONFIG_NODE_RESET,
+    NodeResetMessage, CONFIG_NODE_RESET, CONFIG_NODE_RESET_STATUS,
 };
 
 #[cfg(feature = "ble-mesh-relay")]
 use crate::drivers::ble::mesh::model::foundation::configuration::relay::{
-    RelayMessage, CONFIG_RELAY_GET, CONFIG_RELAY_SET,
+    RelayMessage, CONFIG_RELAY_GET, CONFIG_RELAY_SET, CONFIG_RELAY_STATUS,
 };
 use crate::drivers::ble::mesh::model::{Message, Model, ModelIdentifier};
 use crate::drivers::ble::mesh::pdu::access::Opcode;
@@ -41,10 +55,13 @@ pub mod app_key;
 pub mod beacon;
 pub mod composition_data;
 pub mod default_ttl;
+pub mod key_refresh_phase;
 pub mod model_app;
 pub mod model_publication;
 pub mod model_subscription;
+pub mod net_key;
 pub mod network_transmit;
+pub mod node_identity;
 pub mod node_reset;
 
 #[cfg(feature = "ble-mesh-relay")]
@@ -60,6 +77,9 @@ pub enum ConfigurationMessage {
     NodeReset(NodeResetMessage),
     CompositionData(CompositionDataMessage),
     AppKey(AppKeyMessage),
+    NetKey(NetKeyMessage),
+    KeyRefreshPhase(KeyRefreshPhaseMessage),
+    NodeIdentity(NodeIdentityMessage),
     ModelApp(ModelAppMessage),
     ModelPublication(ModelPublicationMessage),
     ModelSubscription(ModelSubscriptionMessage),
@@ -75,6 +95,9 @@ impl Message for ConfigurationMessage {
             ConfigurationMessage::NodeReset(inner) => inner.opcode(),
             ConfigurationMessage::CompositionData(inner) => inner.opcode(),
             ConfigurationMessage::AppKey(inner) => inner.opcode(),
+            ConfigurationMessage::NetKey(inner) => inner.opcode(),
+            ConfigurationMessage::KeyRefreshPhase(inner) => inner.opcode(),
+            ConfigurationMessage::NodeIdentity(inner) => inner.opcode(),
             ConfigurationMessage::ModelApp(inner) => inner.opcode(),
             ConfigurationMessage::ModelPublication(inner) => inner.opcode(),
             ConfigurationMessage::ModelSubscription(inner) => inner.opcode(),
@@ -93,6 +116,9 @@ impl Message for ConfigurationMessage {
             ConfigurationMessage::NodeReset(inner) => inner.emit_parameters(xmit),
             ConfigurationMessage::CompositionData(inner) => inner.emit_parameters(xmit),
             ConfigurationMessage::AppKey(inner) => inner.emit_parameters(xmit),
+            ConfigurationMessage::NetKey(inner) => inner.emit_parameters(xmit),
+            ConfigurationMessage::KeyRefreshPhase(inner) => inner.emit_parameters(xmit),
+            ConfigurationMessage::NodeIdentity(inner) => inner.emit_parameters(xmit),
             ConfigurationMessage::ModelApp(inner) => inner.emit_parameters(xmit),
             ConfigurationMessage::ModelPublication(inner) => inner.emit_parameters(xmit),
             ConfigurationMessage::ModelSubscription(inner) => inner.emit_parameters(xmit),
@@ -138,9 +164,42 @@ impl Model for ConfigurationServer {
             CONFIG_APPKEY_ADD => Ok(Some(ConfigurationMessage::AppKey(
                 AppKeyMessage::parse_add(parameters)?,
             ))),
+            CONFIG_APPKEY_DELETE => Ok(Some(ConfigurationMessage::AppKey(
+                AppKeyMessage::parse_delete(parameters)?,
+            ))),
             CONFIG_APPKEY_GET => Ok(Some(ConfigurationMessage::AppKey(
                 AppKeyMessage::parse_get(parameters)?,
             ))),
+            CONFIG_APPKEY_UPDATE => Ok(Some(ConfigurationMessage::AppKey(
+                AppKeyMessage::parse_update(parameters)?,
+            ))),
+            // Net Key
+            CONFIG_NETKEY_ADD => Ok(Some(ConfigurationMessage::NetKey(
+                NetKeyMessage::parse_add(parameters)?,
+            ))),
+            CONFIG_NETKEY_DELETE => Ok(Some(ConfigurationMessage::NetKey(
+                NetKeyMessage::parse_delete(parameters)?,
+            ))),
+            CONFIG_NETKEY_GET => Ok(Some(ConfigurationMessage::NetKey(
+                NetKeyMessage::parse_get(parameters)?,
+            ))),
+            CONFIG_NETKEY_UPDATE => Ok(Some(ConfigurationMessage::NetKey(
+                NetKeyMessage::parse_update(parameters)?,
+            ))),
+            // Key Refresh Phase
+            CONFIG_KEY_REFRESH_PHASE_GET => Ok(Some(ConfigurationMessage::KeyRefreshPhase(
+                KeyRefreshPhaseMessage::parse_get(parameters)?,
+            ))),
+            CONFIG_KEY_REFRESH_PHASE_SET => Ok(Some(ConfigurationMessage::KeyRefreshPhase(
+                KeyRefreshPhaseMessage::parse_set(parameters)?,
+            ))),
+            // Node Identity
+            CONFIG_NODE_IDENTITY_GET => Ok(Some(ConfigurationMessage::NodeIdentity(
+                NodeIdentityMessage::parse_get(parameters)?,
+            ))),
+            CONFIG_NODE_IDENTITY_SET => Ok(Some(ConfigurationMessage::NodeIdentity(
+                NodeIdentityMessage::parse_set(parameters)?,
+            ))),
             // Model App
             CONFIG_MODEL_APP_BIND => Ok(Some(ConfigurationMessage::ModelApp(
                 ModelAppMessage::parse_bind(parameters)?,
@@ -149,6 +208,9 @@ impl Model for ConfigurationServer {
                 ModelAppMessage::parse_unbind(parameters)?,
             ))),
             // Model Publication
+            CONFIG_MODEL_PUBLICATION_GET => Ok(Some(ConfigurationMessage::ModelPublication(
+                ModelPublicationMessage::parse_get(parameters)?,
+            ))),
             CONFIG_MODEL_PUBLICATION_SET => Ok(Some(ConfigurationMessage::ModelPublication(
                 ModelPublicationMessage::parse_set(parameters)?,
             ))),
@@ -166,6 +228,29 @@ impl Model for ConfigurationServer {
                     ModelSubscriptionMessage::parse_virtual_address_add(parameters)?,
                 )))
             }
+            CONFIG_MODEL_SUBSCRIPTION_DELETE => Ok(Some(ConfigurationMessage::ModelSubscription(
+                ModelSubscriptionMessage::parse_delete(parameters)?,
+            ))),
+            CONFIG_MODEL_SUBSCRIPTION_VIRTUAL_ADDRESS_DELETE => {
+                Ok(Some(ConfigurationMessage::ModelSubscription(
+                    ModelSubscriptionMessage::parse_virtual_address_delete(parameters)?,
+                )))
+            }
+            CONFIG_MODEL_SUBSCRIPTION_OVERWRITE => {
+                Ok(Some(ConfigurationMessage::ModelSubscription(
+                    ModelSubscriptionMessage::parse_overwrite(parameters)?,
+                )))
+            }
+            CONFIG_MODEL_SUBSCRIPTION_VIRTUAL_ADDRESS_OVERWRITE => {
+                Ok(Some(ConfigurationMessage::ModelSubscription(
+                    ModelSubscriptionMessage::parse_virtual_address_overwrite(parameters)?,
+                )))
+            }
+            CONFIG_MODEL_SUBSCRIPTION_DELETE_ALL => {
+                Ok(Some(ConfigurationMessage::ModelSubscription(
+                    ModelSubscriptionMessage::parse_delete_all(parameters)?,
+                )))
+            }
             // Relay
             #[cfg(feature = "ble-mesh-relay")]
             CONFIG_RELAY_GET => Ok(Some(ConfigurationMessage::Relay(RelayMessage::parse_get(
@@ -190,10 +275,46 @@ impl Model for ConfigurationClient {
     type Message<'m> = ConfigurationMessage;
 
     fn parse<'m>(
-        _opcode: Opcode,
-        _parameters: &'m [u8],
+        opcode: Opcode,
+        parameters: &'m [u8],
     ) -> Result<Option<Self::Message<'m>>, ParseError> {
-        todo!();
+        match opcode {
+            CONFIG_COMPOSITION_DATA_STATUS => Ok(Some(ConfigurationMessage::CompositionData(
+                CompositionDataMessage::parse_status(parameters)?,
+            ))),
+            CONFIG_NODE_RESET_STATUS => Ok(Some(ConfigurationMessage::NodeReset(
+                NodeResetMessage::parse_status(parameters)?,
+            ))),
+            // App Key
+            CONFIG_APPKEY_STATUS => Ok(Some(ConfigurationMessage::AppKey(
+                AppKeyMessage::parse_status(parameters)?,
+            ))),
+            CONFIG_APPKEY_LIST => Ok(Some(ConfigurationMessage::AppKey(
+                AppKeyMessage::parse_list(parameters)?,
+            ))),
+            // Node Identity
+            CONFIG_NODE_IDENTITY_STATUS => Ok(Some(ConfigurationMessage::NodeIdentity(
+                NodeIdentityMessage::parse_status(parameters)?,
+            ))),
+            // Model App
+            CONFIG_MODEL_APP_STATUS => Ok(Some(ConfigurationMessage::ModelApp(
+                ModelAppMessage::parse_status(parameters)?,
+            ))),
+            // Model Publication
+            CONFIG_MODEL_PUBLICATION_STATUS => Ok(Some(ConfigurationMessage::ModelPublication(
+                ModelPublicationMessage::parse_status(parameters)?,
+            ))),
+            // Model Subscription
+            CONFIG_MODEL_SUBSCRIPTION_STATUS => Ok(Some(ConfigurationMessage::ModelSubscription(
+                ModelSubscriptionMessage::parse_status(parameters)?,
+            ))),
+            // Relay
+            #[cfg(feature = "ble-mesh-relay")]
+            CONFIG_RELAY_STATUS => Ok(Some(ConfigurationMessage::Relay(
+                RelayMessage::parse_status(parameters)?,
+            ))),
+            _ => Ok(None),
+        }
     }
 }
 
@@ -214,7 +335,7 @@ impl KeyIndex {
     fn parse_one(parameters: &[u8]) -> Result<Self, ParseError> {
         if parameters.len() >= 2 {
             let byte1 = parameters[0];
-            let byte2 = parameters[1] & 0b11110000 >> 4;
+            let byte2 = parameters[1] & 0b00001111;
             let val = u16::from_be_bytes([byte2, byte1]);
             Ok(Self(val))
         } else {
@@ -228,24 +349,20 @@ impl KeyIndex {
     ) -> Result<(), InsufficientBuffer> {
         let bytes = index.0.to_be_bytes();
         let byte1 = bytes[1];
-        let byte2 = bytes[0] << 4;
+        let byte2 = bytes[0] & 0b00001111;
         xmit.push(byte1).map_err(|_| InsufficientBuffer)?;
         xmit.push(byte2).map_err(|_| InsufficientBuffer)?;
         Ok(())
     }
 
+    // Two indexes are packed little-endian into 3 octets,
+    // with the first index occupying the low 12 bits.
     fn parse_two(parameters: &[u8]) -> Result<(Self, Self), ParseError> {
         if parameters.len() >= 3 {
-            let byte1 = parameters[0];
-            let byte2 = (parameters[1] & 0b11110000) >> 4;
-
-            let index1 = u16::from_be_bytes([byte1, byte2]);
-
-            let byte1 = parameters[1] & 0b00001111;
-            let byte2 = parameters[2];
-
-            let index2 = u16::from_be_bytes([byte1, byte2]);
-            Ok((Self(index2), Self(index1)))
+            let val = u32::from_le_bytes([parameters[0], parameters[1], parameters[2], 0]);
+            let index1 = (val & 0x0FFF) as u16;
+            let index2 = ((val >> 12) & 0x0FFF) as u16;
+            Ok((Self(index1), Self(index2)))
         } else {
             Err(ParseError::InvalidLength)
         }
@@ -255,19 +372,44 @@ impl KeyIndex {
         indexes: (&KeyIndex, &KeyIndex),
         xmit: &mut Vec<u8, N>,
     ) -> Result<(), InsufficientBuffer> {
-        let bytes = indexes.1 .0.to_be_bytes();
-        let byte1 = bytes[0];
-        xmit.push(byte1).map_err(|_| InsufficientBuffer)?;
-
-        let byte2 = bytes[1] << 4;
-        let bytes = indexes.0 .0.to_be_bytes();
-        let byte1 = byte2 | bytes[0];
-
-        xmit.push(byte1).map_err(|_| InsufficientBuffer)?;
+        let val = (indexes.0 .0 as u32 & 0x0FFF) | ((indexes.1 .0 as u32 & 0x0FFF) << 12);
+        let bytes = val.to_le_bytes();
+        xmit.extend_from_slice(&bytes[0..3])
+            .map_err(|_| InsufficientBuffer)?;
+        Ok(())
+    }
 
-        let byte2 = bytes[1];
-        xmit.push(byte2).map_err(|_| InsufficientBuffer)?;
+    fn parse_many<const N: usize>(parameters: &[u8]) -> Result<Vec<Self, N>, ParseError> {
+        let mut indexes = Vec::new();
+        for chunk in parameters.chunks(3) {
+            if chunk.len() == 3 {
+                let (index1, index2) = Self::parse_two(chunk)?;
+                indexes
+                    .push(index1)
+                    .map_err(|_| ParseError::InsufficientBuffer)?;
+                indexes
+                    .push(index2)
+                    .map_err(|_| ParseError::InsufficientBuffer)?;
+            } else {
+                indexes
+                    .push(Self::parse_one(chunk)?)
+                    .map_err(|_| ParseError::InsufficientBuffer)?;
+            }
+        }
+        Ok(indexes)
+    }
 
+    fn emit_many<const N: usize>(
+        indexes: &[KeyIndex],
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        for chunk in indexes.chunks(2) {
+            if chunk.len() == 2 {
+                Self::emit_two((&chunk[0], &chunk[1]), xmit)?;
+            } else {
+                Self::emit_one(&chunk[0], xmit)?;
+            }
+        }
         Ok(())
     }
 }
@@ -285,6 +427,12 @@ impl NetKeyIndex {
     }
 }
 
+impl From<NetKeyIndex> for u16 {
+    fn from(index: NetKeyIndex) -> Self {
+        (index.0).0
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for NetKeyIndex {
     fn format(&self, fmt: defmt::Formatter) {
@@ -296,6 +444,10 @@ impl defmt::Format for NetKeyIndex {
 pub struct AppKeyIndex(KeyIndex);
 
 impl AppKeyIndex {
+    pub fn new(index: u16) -> Self {
+        Self(KeyIndex(index))
+    }
+
     fn emit<const N: usize>(&self, xmit: &mut Vec<u8, N>) -> Result<(), InsufficientBuffer> {
         KeyIndex::emit_one(&self.0, xmit)
     }
@@ -313,6 +465,10 @@ impl defmt::Format for AppKeyIndex {
 pub struct NetKeyAppKeyIndexesPair(NetKeyIndex, AppKeyIndex);
 
 impl NetKeyAppKeyIndexesPair {
+    pub fn new(net_key: NetKeyIndex, app_key: AppKeyIndex) -> Self {
+        Self(net_key, app_key)
+    }
+
     fn emit<const N: usize>(&self, xmit: &mut Vec<u8, N>) -> Result<(), InsufficientBuffer> {
         KeyIndex::emit_two((&self.0 .0, &self.1 .0), xmit).map_err(|_| InsufficientBuffer)?;
         Ok(())
@@ -26,6 +26,17 @@ impl ModelAppMessage {
     pub fn parse_unbind(parameters: &[u8]) -> Result<Self, ParseError> {
         Ok(Self::Unbind(ModelAppPayload::parse(parameters)?))
     }
+
+    pub fn parse_status(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() >= 7 {
+            Ok(Self::Status(ModelAppStatusMessage {
+                status: Status::parse(parameters[0])?,
+                payload: ModelAppPayload::parse(&parameters[1..])?,
+            }))
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
 }
 
 impl Message for ModelAppMessage {
@@ -104,3 +115,36 @@ impl ModelAppStatusMessage {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_round_trip() {
+        let mut xmit: Vec<u8, 16> = Vec::new();
+        ModelAppMessage::Status(ModelAppStatusMessage {
+            status: Status::InvalidAppKeyIndex,
+            payload: ModelAppPayload {
+                element_address: UnicastAddress(0x0102),
+                app_key_index: AppKeyIndex::new(0x123),
+                model_identifier: ModelIdentifier::SIG(0x1000),
+            },
+        })
+        .emit_parameters(&mut xmit)
+        .unwrap();
+
+        match ModelAppMessage::parse_status(&xmit).unwrap() {
+            ModelAppMessage::Status(status) => {
+                assert_eq!(Status::InvalidAppKeyIndex, status.status);
+                assert_eq!(UnicastAddress(0x0102), status.payload.element_address);
+                assert_eq!(AppKeyIndex::new(0x123), status.payload.app_key_index);
+                assert_eq!(
+                    ModelIdentifier::SIG(0x1000),
+                    status.payload.model_identifier
+                );
+            }
+            _ => panic!("expected status"),
+        }
+    }
+}
@@ -1,19 +1,20 @@
-use crate::opcode;
-use heapless::Vec;
-use crate::drivers::ble::mesh::address::{Address, GroupAddress, UnicastAddress};
 use crate::drivers::ble::mesh::address::virtual_address::LabelUuid;
-use crate::drivers::ble::mesh::InsufficientBuffer;
+use crate::drivers::ble::mesh::address::{Address, GroupAddress, UnicastAddress};
 use crate::drivers::ble::mesh::model::foundation::configuration::{AppKeyIndex, KeyIndex};
 use crate::drivers::ble::mesh::model::{Message, ModelIdentifier, Status};
-use crate::drivers::ble::mesh::model::foundation::configuration::model_publication::PublishAddress::Unicast;
 use crate::drivers::ble::mesh::pdu::access::Opcode;
 use crate::drivers::ble::mesh::pdu::ParseError;
+use crate::drivers::ble::mesh::InsufficientBuffer;
+use crate::opcode;
+use heapless::Vec;
 
 opcode!( CONFIG_MODEL_PUBLICATION_SET 0x03 );
 opcode!( CONFIG_MODEL_PUBLICATION_GET 0x80, 0x18);
 opcode!( CONFIG_MODEL_PUBLICATION_STATUS 0x80, 0x19);
 opcode!( CONFIG_MODEL_PUBLICATION_VIRTUAL_ADDRESS_SET 0x80, 0x1A);
 
+const CREDENTIAL_FLAG: u8 = 0b00010000;
+
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ModelPublicationMessage {
     Get(ModelPublicationGetMessage),
@@ -48,44 +49,94 @@ impl Message for ModelPublicationMessage {
 }
 
 impl ModelPublicationMessage {
+    pub fn parse_get(parameters: &[u8]) -> Result<Self, ParseError> {
+        Ok(Self::Get(ModelPublicationGetMessage::parse(parameters)?))
+    }
+
     pub fn parse_set(parameters: &[u8]) -> Result<Self, ParseError> {
         Ok(Self::Set(ModelPublicationSetMessage::parse(parameters)?))
     }
 
     pub fn parse_virtual_address_set(parameters: &[u8]) -> Result<Self, ParseError> {
-        Ok(Self::Set(
+        Ok(Self::VirtualAddressSet(
             ModelPublicationSetMessage::parse_virtual_address(parameters)?,
         ))
     }
+
+    pub fn parse_status(parameters: &[u8]) -> Result<Self, ParseError> {
+        Ok(Self::Status(ModelPublicationStatusMessage::parse(
+            parameters,
+        )?))
+    }
 }
 
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ModelPublicationGetMessage {
-    element_address: UnicastAddress,
-    model_identifier: ModelIdentifier,
+    pub element_address: UnicastAddress,
+    pub model_identifier: ModelIdentifier,
 }
 
 impl ModelPublicationGetMessage {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() >= 4 {
+            let element_address = UnicastAddress::parse([parameters[1], parameters[0]])?;
+            let model_identifier = ModelIdentifier::parse(&parameters[2..])?;
+            Ok(Self {
+                element_address,
+                model_identifier,
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
     fn emit_parameters<const N: usize>(
         &self,
-        _xmit: &mut Vec<u8, N>,
+        xmit: &mut Vec<u8, N>,
     ) -> Result<(), InsufficientBuffer> {
-        todo!()
+        let addr_bytes = self.element_address.as_bytes();
+        xmit.push(addr_bytes[1]).map_err(|_| InsufficientBuffer)?;
+        xmit.push(addr_bytes[0]).map_err(|_| InsufficientBuffer)?;
+        self.model_identifier.emit(xmit)?;
+        Ok(())
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum PublishAddress {
+    Unassigned,
     Unicast(UnicastAddress),
     Group(GroupAddress),
     Virtual(LabelUuid),
 }
 
+impl PublishAddress {
+    fn parse(data: [u8; 2]) -> Result<Self, ParseError> {
+        match Address::parse(data) {
+            Address::Unassigned => Ok(Self::Unassigned),
+            Address::Unicast(inner) => Ok(Self::Unicast(inner)),
+            Address::Group(inner) => Ok(Self::Group(inner)),
+            _ => Err(ParseError::InvalidValue),
+        }
+    }
+
+    /// The 16-bit form of the address, as reported in a status message.
+    pub fn address(&self) -> Address {
+        match self {
+            PublishAddress::Unassigned => Address::Unassigned,
+            PublishAddress::Unicast(inner) => Address::Unicast(*inner),
+            PublishAddress::Group(inner) => Address::Group(*inner),
+            PublishAddress::Virtual(inner) => Address::Virtual(inner.virtual_address()),
+        }
+    }
+}
+
 impl Into<Address> for PublishAddress {
     fn into(self) -> Address {
         match self {
-            Unicast(inner) => Address::Unicast(inner),
+            PublishAddress::Unassigned => Address::Unassigned,
+            PublishAddress::Unicast(inner) => Address::Unicast(inner),
             PublishAddress::Group(inner) => Address::Group(inner),
             PublishAddress::Virtual(inner) => Address::LabelUuid(inner),
         }
@@ -108,39 +159,40 @@ pub struct ModelPublicationSetMessage {
 impl ModelPublicationSetMessage {
     fn emit_parameters<const N: usize>(
         &self,
-        _xmit: &mut Vec<u8, N>,
+        xmit: &mut Vec<u8, N>,
     ) -> Result<(), InsufficientBuffer> {
-        todo!()
+        let addr_bytes = self.element_address.as_bytes();
+        xmit.push(addr_bytes[1]).map_err(|_| InsufficientBuffer)?;
+        xmit.push(addr_bytes[0]).map_err(|_| InsufficientBuffer)?;
+        match self.publish_address {
+            PublishAddress::Virtual(label_uuid) => {
+                xmit.extend_from_slice(label_uuid.label_uuid())
+                    .map_err(|_| InsufficientBuffer)?;
+            }
+            _ => {
+                let addr_bytes = self.publish_address.address().as_bytes();
+                xmit.push(addr_bytes[1]).map_err(|_| InsufficientBuffer)?;
+                xmit.push(addr_bytes[0]).map_err(|_| InsufficientBuffer)?;
+            }
+        }
+        emit_publish_parameters(
+            &self.app_key_index,
+            self.credential_flag,
+            self.publish_ttl,
+            self.publish_period,
+            self.publish_retransmit_count,
+            self.publish_retransmit_interval_steps,
+            xmit,
+        )?;
+        self.model_identifier.emit(xmit)?;
+        Ok(())
     }
 
     fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
         if parameters.len() >= 11 {
             let element_address = UnicastAddress::parse([parameters[1], parameters[0]])?;
-            let publish_address =
-                PublishAddress::Unicast(UnicastAddress::parse([parameters[3], parameters[2]])?);
-            let app_key_index = AppKeyIndex(KeyIndex::parse_one(&parameters[4..=5])?);
-            let credential_flag = (parameters[5] & 0b0001000) != 0;
-            let publish_ttl = parameters[6];
-            let publish_ttl = if publish_ttl == 0xFF {
-                None
-            } else {
-                Some(publish_ttl)
-            };
-            let publish_period = parameters[7];
-            let publish_retransmit_count = (parameters[8] & 0b11100000) >> 5;
-            let publish_retransmit_interval_steps = parameters[8] & 0b00011111;
-            let model_identifier = ModelIdentifier::parse(&parameters[9..])?;
-            Ok(Self {
-                element_address,
-                publish_address,
-                app_key_index,
-                credential_flag,
-                publish_ttl,
-                publish_period,
-                publish_retransmit_count,
-                publish_retransmit_interval_steps,
-                model_identifier,
-            })
+            let publish_address = PublishAddress::parse([parameters[3], parameters[2]])?;
+            Self::parse_publish_parameters(element_address, publish_address, &parameters[4..])
         } else {
             Err(ParseError::InvalidLength)
         }
@@ -150,40 +202,47 @@ impl ModelPublicationSetMessage {
         if parameters.len() >= 25 {
             let element_address = UnicastAddress::parse([parameters[1], parameters[0]])?;
             let publish_address = PublishAddress::Virtual(LabelUuid::parse(&parameters[2..=17])?);
-
-            let app_key_index = AppKeyIndex(KeyIndex::parse_one(&parameters[18..=19])?);
-            let credential_flag = (parameters[19] & 0b0001000) != 0;
-            let publish_ttl = parameters[20];
-            let publish_ttl = if publish_ttl == 0xFF {
-                None
-            } else {
-                Some(publish_ttl)
-            };
-            let publish_period = parameters[21];
-            let publish_retransmit_count = (parameters[22] & 0b11100000) >> 5;
-            let publish_retransmit_interval_steps = parameters[22] & 0b00011111;
-            let model_identifier = ModelIdentifier::parse(&parameters[23..])?;
-            Ok(Self {
-                element_address,
-                publish_address,
-                app_key_index,
-                credential_flag,
-                publish_ttl,
-                publish_period,
-                publish_retransmit_count,
-                publish_retransmit_interval_steps,
-                model_identifier,
-            })
+            Self::parse_publish_parameters(element_address, publish_address, &parameters[18..])
         } else {
             Err(ParseError::InvalidLength)
         }
     }
 
+    fn parse_publish_parameters(
+        element_address: UnicastAddress,
+        publish_address: PublishAddress,
+        parameters: &[u8],
+    ) -> Result<Self, ParseError> {
+        let app_key_index = AppKeyIndex(KeyIndex::parse_one(&parameters[0..=1])?);
+        let credential_flag = (parameters[1] & CREDENTIAL_FLAG) != 0;
+        let publish_ttl = parameters[2];
+        let publish_ttl = if publish_ttl == 0xFF {
+            None
+        } else {
+            Some(publish_ttl)
+        };
+        let publish_period = parameters[3];
+        let publish_retransmit_count = (parameters[4] & 0b11100000) >> 5;
+        let publish_retransmit_interval_steps = parameters[4] & 0b00011111;
+        let model_identifier = ModelIdentifier::parse(&parameters[5..])?;
+        Ok(Self {
+            element_address,
+            publish_address,
+            app_key_index,
+            credential_flag,
+            publish_ttl,
+            publish_period,
+            publish_retransmit_count,
+            publish_retransmit_interval_steps,
+            model_identifier,
+        })
+    }
+
     pub fn create_status_response(&self, status: Status) -> ModelPublicationStatusMessage {
         ModelPublicationStatusMessage {
             status,
             element_address: self.element_address,
-            publish_address: self.publish_address,
+            publish_address: self.publish_address.address(),
             app_key_index: self.app_key_index,
             credential_flag: self.credential_flag,
             publish_ttl: self.publish_ttl,
@@ -197,19 +256,53 @@ impl ModelPublicationSetMessage {
 
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ModelPublicationStatusMessage {
-    status: Status,
-    element_address: UnicastAddress,
-    publish_address: PublishAddress,
-    app_key_index: AppKeyIndex,
-    credential_flag: bool,
-    publish_ttl: Option<u8>,
-    publish_period: u8,
-    publish_retransmit_count: u8,
-    publish_retransmit_interval_steps: u8,
-    model_identifier: ModelIdentifier,
+    pub status: Status,
+    pub element_address: UnicastAddress,
+    pub publish_address: Address,
+    pub app_key_index: AppKeyIndex,
+    pub credential_flag: bool,
+    pub publish_ttl: Option<u8>,
+    pub publish_period: u8,
+    pub publish_retransmit_count: u8,
+    pub publish_retransmit_interval_steps: u8,
+    pub model_identifier: ModelIdentifier,
 }
 
 impl ModelPublicationStatusMessage {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() >= 12 {
+            let status = Status::parse(parameters[0])?;
+            let element_address = UnicastAddress::parse([parameters[2], parameters[1]])?;
+            let publish_address = Address::parse([parameters[4], parameters[3]]);
+            let app_key_index = AppKeyIndex(KeyIndex::parse_one(&parameters[5..=6])?);
+            let credential_flag = (parameters[6] & CREDENTIAL_FLAG) != 0;
+            let publish_ttl = parameters[7];
+            let publish_ttl = if publish_ttl == 0xFF {
+                None
+            } else {
+                Some(publish_ttl)
+            };
+            let publish_period = parameters[8];
+            let publish_retransmit_count = (parameters[9] & 0b11100000) >> 5;
+            let publish_retransmit_interval_steps = parameters[9] & 0b00011111;
+            let model_identifier = ModelIdentifier::parse(&parameters[10..])?;
+            Ok(Self {
+                status,
+                element_address,
+                publish_address,
+                app_key_index,
+                credential_flag,
+                publish_ttl,
+                publish_period,
+                publish_retransmit_count,
+                publish_retransmit_interval_steps,
+                model_identifier,
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
     fn emit_parameters<const N: usize>(
         &self,
         xmit: &mut Vec<u8, N>,
@@ -219,41 +312,172 @@ impl ModelPublicationStatusMessage {
         let addr_bytes = self.element_address.as_bytes();
         xmit.push(addr_bytes[1]).map_err(|_| InsufficientBuffer)?;
         xmit.push(addr_bytes[0]).map_err(|_| InsufficientBuffer)?;
-        match self.publish_address {
-            PublishAddress::Unicast(addr) => {
-                let addr_bytes = addr.as_bytes();
-                xmit.push(addr_bytes[1]).map_err(|_| InsufficientBuffer)?;
-                xmit.push(addr_bytes[0]).map_err(|_| InsufficientBuffer)?;
-            }
-            PublishAddress::Group(_addr) => {
-                todo!("group address")
-            }
-            PublishAddress::Virtual(addr) => {
-                let addr_bytes = addr.virtual_address().as_bytes();
-                xmit.push(addr_bytes[1]).map_err(|_| InsufficientBuffer)?;
-                xmit.push(addr_bytes[0]).map_err(|_| InsufficientBuffer)?;
+        let addr_bytes = self.publish_address.as_bytes();
+        xmit.push(addr_bytes[1]).map_err(|_| InsufficientBuffer)?;
+        xmit.push(addr_bytes[0]).map_err(|_| InsufficientBuffer)?;
+        emit_publish_parameters(
+            &self.app_key_index,
+            self.credential_flag,
+            self.publish_ttl,
+            self.publish_period,
+            self.publish_retransmit_count,
+            self.publish_retransmit_interval_steps,
+            xmit,
+        )?;
+        self.model_identifier.emit(xmit)?;
+        Ok(())
+    }
+}
+
+fn emit_publish_parameters<const N: usize>(
+    app_key_index: &AppKeyIndex,
+    credential_flag: bool,
+    publish_ttl: Option<u8>,
+    publish_period: u8,
+    publish_retransmit_count: u8,
+    publish_retransmit_interval_steps: u8,
+    xmit: &mut Vec<u8, N>,
+) -> Result<(), InsufficientBuffer> {
+    app_key_index.emit(xmit)?;
+    if credential_flag {
+        if let Some(last) = xmit.last_mut() {
+            *last = *last | CREDENTIAL_FLAG;
+        } else {
+            return Err(InsufficientBuffer);
+        }
+    }
+    if let Some(ttl) = publish_ttl {
+        xmit.push(ttl).map_err(|_| InsufficientBuffer)?;
+    } else {
+        xmit.push(0xFF).map_err(|_| InsufficientBuffer)?;
+    }
+    xmit.push(publish_period).map_err(|_| InsufficientBuffer)?;
+
+    let retransmit =
+        (publish_retransmit_count << 5) | (publish_retransmit_interval_steps & 0b00011111);
+    xmit.push(retransmit).map_err(|_| InsufficientBuffer)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn emit(message: ModelPublicationMessage) -> Vec<u8, 64> {
+        let mut xmit = Vec::new();
+        message.emit_parameters(&mut xmit).unwrap();
+        xmit
+    }
+
+    fn set(publish_address: PublishAddress) -> ModelPublicationSetMessage {
+        ModelPublicationSetMessage {
+            element_address: UnicastAddress(0x0102),
+            publish_address,
+            app_key_index: AppKeyIndex::new(0x321),
+            credential_flag: true,
+            publish_ttl: Some(7),
+            publish_period: 0x41,
+            publish_retransmit_count: 3,
+            publish_retransmit_interval_steps: 9,
+            model_identifier: ModelIdentifier::SIG(0x1000),
+        }
+    }
+
+    fn assert_same(expected: &ModelPublicationSetMessage, actual: &ModelPublicationSetMessage) {
+        assert_eq!(expected.element_address, actual.element_address);
+        assert_eq!(expected.publish_address, actual.publish_address);
+        assert_eq!(expected.app_key_index, actual.app_key_index);
+        assert_eq!(expected.credential_flag, actual.credential_flag);
+        assert_eq!(expected.publish_ttl, actual.publish_ttl);
+        assert_eq!(expected.publish_period, actual.publish_period);
+        assert_eq!(
+            expected.publish_retransmit_count,
+            actual.publish_retransmit_count
+        );
+        assert_eq!(
+            expected.publish_retransmit_interval_steps,
+            actual.publish_retransmit_interval_steps
+        );
+        assert_eq!(expected.model_identifier, actual.model_identifier);
+    }
+
+    #[test]
+    fn test_get_round_trip() {
+        let xmit = emit(ModelPublicationMessage::Get(ModelPublicationGetMessage {
+            element_address: UnicastAddress(0x0102),
+            model_identifier: ModelIdentifier::SIG(0x1000),
+        }));
+        match ModelPublicationMessage::parse_get(&xmit).unwrap() {
+            ModelPublicationMessage::Get(get) => {
+                assert_eq!(UnicastAddress(0x0102), get.element_address);
+                assert_eq!(ModelIdentifier::SIG(0x1000), get.model_identifier);
             }
+            _ => panic!("expected get"),
         }
-        self.app_key_index.emit(xmit)?;
-        if self.credential_flag {
-            if let Some(last) = xmit.last_mut() {
-                *last = *last | 0b00001000;
-            } else {
-                return Err(InsufficientBuffer);
+    }
+
+    #[test]
+    fn test_set_round_trip() {
+        for publish_address in [
+            PublishAddress::Unassigned,
+            PublishAddress::Unicast(UnicastAddress(0x0005)),
+            PublishAddress::Group(GroupAddress::RFU(0xC002)),
+        ] {
+            let expected = set(publish_address);
+            let xmit = emit(ModelPublicationMessage::Set(set(publish_address)));
+            assert_eq!(11, xmit.len());
+            match ModelPublicationMessage::parse_set(&xmit).unwrap() {
+                ModelPublicationMessage::Set(actual) => assert_same(&expected, &actual),
+                _ => panic!("expected set"),
             }
         }
-        if let Some(ttl) = self.publish_ttl {
-            xmit.push(ttl).map_err(|_| InsufficientBuffer)?;
-        } else {
-            xmit.push(0xFF).map_err(|_| InsufficientBuffer)?;
+    }
+
+    #[test]
+    fn test_credential_flag_does_not_clobber_index() {
+        let xmit = emit(ModelPublicationMessage::Set(set(
+            PublishAddress::Unassigned,
+        )));
+        assert_eq!(&[0x21, 0x13], &xmit[4..=5]);
+    }
+
+    #[test]
+    fn test_virtual_address_set_round_trip() {
+        let publish_address = PublishAddress::Virtual(LabelUuid::new([0x24; 16]).unwrap());
+        let xmit = emit(ModelPublicationMessage::VirtualAddressSet(set(
+            publish_address,
+        )));
+        assert_eq!(25, xmit.len());
+        match ModelPublicationMessage::parse_virtual_address_set(&xmit).unwrap() {
+            ModelPublicationMessage::VirtualAddressSet(actual) => {
+                assert_same(&set(publish_address), &actual)
+            }
+            _ => panic!("expected virtual address set"),
         }
-        xmit.push(self.publish_period)
-            .map_err(|_| InsufficientBuffer)?;
+    }
 
-        let retransmit = (self.publish_retransmit_count << 5)
-            | (self.publish_retransmit_interval_steps & 0b00011111);
-        xmit.push(retransmit).map_err(|_| InsufficientBuffer)?;
-        self.model_identifier.emit(xmit)?;
-        Ok(())
+    #[test]
+    fn test_status_round_trip() {
+        let label_uuid = LabelUuid::new([0x24; 16]).unwrap();
+        let set = set(PublishAddress::Virtual(label_uuid));
+        let xmit = emit(ModelPublicationMessage::Status(
+            set.create_status_response(Status::Success),
+        ));
+        match ModelPublicationMessage::parse_status(&xmit).unwrap() {
+            ModelPublicationMessage::Status(status) => {
+                assert_eq!(Status::Success, status.status);
+                assert_eq!(set.element_address, status.element_address);
+                assert_eq!(
+                    Address::Virtual(label_uuid.virtual_address()),
+                    status.publish_address
+                );
+                assert_eq!(set.app_key_index, status.app_key_index);
+                assert_eq!(set.credential_flag, status.credential_flag);
+                assert_eq!(set.publish_ttl, status.publish_ttl);
+                assert_eq!(set.publish_period, status.publish_period);
+                assert_eq!(set.model_identifier, status.model_identifier);
+            }
+            _ => panic!("expected status"),
+        }
     }
 }
@@ -67,13 +67,49 @@ impl ModelSubscriptionMessage {
     }
 
     pub fn parse_virtual_address_add(parameters: &[u8]) -> Result<Self, ParseError> {
-        Ok(Self::Add(
+        Ok(Self::VirtualAddressAdd(
             ModelSubscriptionAddMessage::parse_virtual_address(parameters)?,
         ))
     }
+
+    pub fn parse_delete(parameters: &[u8]) -> Result<Self, ParseError> {
+        Ok(Self::Delete(ModelSubscriptionDeleteMessage::parse(
+            parameters,
+        )?))
+    }
+
+    pub fn parse_virtual_address_delete(parameters: &[u8]) -> Result<Self, ParseError> {
+        Ok(Self::VirtualAddressDelete(
+            ModelSubscriptionDeleteMessage::parse_virtual_address(parameters)?,
+        ))
+    }
+
+    pub fn parse_overwrite(parameters: &[u8]) -> Result<Self, ParseError> {
+        Ok(Self::Overwrite(ModelSubscriptionOverwriteMessage::parse(
+            parameters,
+        )?))
+    }
+
+    pub fn parse_virtual_address_overwrite(parameters: &[u8]) -> Result<Self, ParseError> {
+        Ok(Self::VirtualAddressOverwrite(
+            ModelSubscriptionOverwriteMessage::parse_virtual_address(parameters)?,
+        ))
+    }
+
+    pub fn parse_delete_all(parameters: &[u8]) -> Result<Self, ParseError> {
+        Ok(Self::DeleteAll(ModelSubscriptionDeleteAllMessage::parse(
+            parameters,
+        )?))
+    }
+
+    pub fn parse_status(parameters: &[u8]) -> Result<Self, ParseError> {
+        Ok(Self::Status(ModelSubscriptionStatusMessage::parse(
+            parameters,
+        )?))
+    }
 }
 
-#[derive(Serialize, Deserialize, Copy, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum SubscriptionAddress {
     Unicast(UnicastAddress),
@@ -81,6 +117,25 @@ pub enum SubscriptionAddress {
     Virtual(LabelUuid),
 }
 
+impl SubscriptionAddress {
+    fn parse(data: [u8; 2]) -> Result<Self, ParseError> {
+        match Address::parse(data) {
+            Address::Unicast(inner) => Ok(Self::Unicast(inner)),
+            Address::Group(inner) => Ok(Self::Group(inner)),
+            _ => Err(ParseError::InvalidValue),
+        }
+    }
+
+    /// The 16-bit form of the address, as reported in a status message.
+    pub fn address(&self) -> Address {
+        match self {
+            Self::Unicast(inner) => Address::Unicast(*inner),
+            Self::Group(inner) => Address::Group(*inner),
+            Self::Virtual(inner) => Address::Virtual(inner.virtual_address()),
+        }
+    }
+}
+
 impl TryInto<SubscriptionAddress> for Address {
     type Error = ();
 
@@ -95,6 +150,8 @@ impl TryInto<SubscriptionAddress> for Address {
     }
 }
 
+/// Add, Delete and Overwrite, along with their virtual address
+/// variants, all share the same parameters.
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ModelSubscriptionAddMessage {
     pub element_address: UnicastAddress,
@@ -102,15 +159,15 @@ pub struct ModelSubscriptionAddMessage {
     pub model_identifier: ModelIdentifier,
 }
 
+pub type ModelSubscriptionDeleteMessage = ModelSubscriptionAddMessage;
+pub type ModelSubscriptionOverwriteMessage = ModelSubscriptionAddMessage;
+
 impl ModelSubscriptionAddMessage {
     pub fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
         if parameters.len() >= 6 {
             let element_address = UnicastAddress::parse([parameters[1], parameters[0]])?;
-            let subscription_address = SubscriptionAddress::Unicast(UnicastAddress::parse([
-                parameters[3],
-                parameters[2],
-            ])?);
-            let model_identifier = ModelIdentifier::parse(&parameters[9..])?;
+            let subscription_address = SubscriptionAddress::parse([parameters[3], parameters[2]])?;
+            let model_identifier = ModelIdentifier::parse(&parameters[4..])?;
             Ok(Self {
                 element_address,
                 subscription_address,
@@ -122,7 +179,7 @@ impl ModelSubscriptionAddMessage {
     }
 
     pub fn parse_virtual_address(parameters: &[u8]) -> Result<Self, ParseError> {
-        if parameters.len() >= 19 {
+        if parameters.len() >= 20 {
             let element_address = UnicastAddress::parse([parameters[1], parameters[0]])?;
             let subscription_address =
                 SubscriptionAddress::Virtual(LabelUuid::parse(&parameters[2..=17])?);
@@ -140,80 +197,101 @@ impl ModelSubscriptionAddMessage {
 
     pub fn emit_parameters<const N: usize>(
         &self,
-        _xmit: &mut Vec<u8, N>,
+        xmit: &mut Vec<u8, N>,
     ) -> Result<(), InsufficientBuffer> {
-        todo!()
+        let addr_bytes = self.element_address.as_bytes();
+        xmit.push(addr_bytes[1]).map_err(|_| InsufficientBuffer)?;
+        xmit.push(addr_bytes[0]).map_err(|_| InsufficientBuffer)?;
+        match self.subscription_address {
+            SubscriptionAddress::Virtual(label_uuid) => {
+                xmit.extend_from_slice(label_uuid.label_uuid())
+                    .map_err(|_| InsufficientBuffer)?;
+            }
+            _ => {
+                let addr_bytes = self.subscription_address.address().as_bytes();
+                xmit.push(addr_bytes[1]).map_err(|_| InsufficientBuffer)?;
+                xmit.push(addr_bytes[0]).map_err(|_| InsufficientBuffer)?;
+            }
+        }
+        self.model_identifier.emit(xmit)?;
+        Ok(())
     }
 
     pub fn create_status_response(&self, status: Status) -> ModelSubscriptionStatusMessage {
         ModelSubscriptionStatusMessage {
             status,
             element_address: self.element_address,
-            subscription_address: self.subscription_address,
+            subscription_address: self.subscription_address.address(),
             model_identifier: self.model_identifier,
         }
     }
 }
 
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
-pub struct ModelSubscriptionDeleteMessage {}
-
-impl ModelSubscriptionDeleteMessage {
-    pub fn parse(_parameters: &[u8]) -> Result<Self, ParseError> {
-        todo!()
-    }
-
-    pub fn emit_parameters<const N: usize>(
-        &self,
-        _xmit: &mut Vec<u8, N>,
-    ) -> Result<(), InsufficientBuffer> {
-        todo!()
-    }
+pub struct ModelSubscriptionDeleteAllMessage {
+    pub element_address: UnicastAddress,
+    pub model_identifier: ModelIdentifier,
 }
 
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
-pub struct ModelSubscriptionDeleteAllMessage {}
-
 impl ModelSubscriptionDeleteAllMessage {
-    pub fn parse(_parameters: &[u8]) -> Result<Self, ParseError> {
-        todo!()
+    pub fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() >= 4 {
+            let element_address = UnicastAddress::parse([parameters[1], parameters[0]])?;
+            let model_identifier = ModelIdentifier::parse(&parameters[2..])?;
+            Ok(Self {
+                element_address,
+                model_identifier,
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
     }
 
     pub fn emit_parameters<const N: usize>(
         &self,
-        _xmit: &mut Vec<u8, N>,
+        xmit: &mut Vec<u8, N>,
     ) -> Result<(), InsufficientBuffer> {
-        todo!()
-    }
-}
-
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
-pub struct ModelSubscriptionOverwriteMessage {}
-
-impl ModelSubscriptionOverwriteMessage {
-    pub fn parse(_parameters: &[u8]) -> Result<Self, ParseError> {
-        todo!()
+        let addr_bytes = self.element_address.as_bytes();
+        xmit.push(addr_bytes[1]).map_err(|_| InsufficientBuffer)?;
+        xmit.push(addr_bytes[0]).map_err(|_| InsufficientBuffer)?;
+        self.model_identifier.emit(xmit)?;
+        Ok(())
     }
 
-    pub fn emit_parameters<const N: usize>(
-        &self,
-        _xmit: &mut Vec<u8, N>,
-    ) -> Result<(), InsufficientBuffer> {
-        todo!()
+    pub fn create_status_response(&self, status: Status) -> ModelSubscriptionStatusMessage {
+        ModelSubscriptionStatusMessage {
+            status,
+            element_address: self.element_address,
+            subscription_address: Address::Unassigned,
+            model_identifier: self.model_identifier,
+        }
     }
 }
 
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ModelSubscriptionStatusMessage {
-    status: Status,
-    element_address: UnicastAddress,
-    subscription_address: SubscriptionAddress,
-    model_identifier: ModelIdentifier,
+    pub status: Status,
+    pub element_address: UnicastAddress,
+    pub subscription_address: Address,
+    pub model_identifier: ModelIdentifier,
 }
 
 impl ModelSubscriptionStatusMessage {
-    pub fn parse(_parameters: &[u8]) -> Result<Self, ParseError> {
-        todo!("parse subscription status")
+    pub fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() >= 7 {
+            let status = Status::parse(parameters[0])?;
+            let element_address = UnicastAddress::parse([parameters[2], parameters[1]])?;
+            let subscription_address = Address::parse([parameters[4], parameters[3]]);
+            let model_identifier = ModelIdentifier::parse(&parameters[5..])?;
+            Ok(Self {
+                status,
+                element_address,
+                subscription_address,
+                model_identifier,
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
     }
 
     pub fn emit_parameters<const N: usize>(
@@ -225,22 +303,143 @@ impl ModelSubscriptionStatusMessage {
         let addr_bytes = self.element_address.as_bytes();
         xmit.push(addr_bytes[1]).map_err(|_| InsufficientBuffer)?;
         xmit.push(addr_bytes[0]).map_err(|_| InsufficientBuffer)?;
-        match self.subscription_address {
-            SubscriptionAddress::Unicast(addr) => {
-                let addr_bytes = addr.as_bytes();
-                xmit.push(addr_bytes[1]).map_err(|_| InsufficientBuffer)?;
-                xmit.push(addr_bytes[0]).map_err(|_| InsufficientBuffer)?;
+        let addr_bytes = self.subscription_address.as_bytes();
+        xmit.push(addr_bytes[1]).map_err(|_| InsufficientBuffer)?;
+        xmit.push(addr_bytes[0]).map_err(|_| InsufficientBuffer)?;
+        self.model_identifier.emit(xmit)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drivers::ble::mesh::composition::CompanyIdentifier;
+
+    fn emit(message: ModelSubscriptionMessage) -> Vec<u8, 64> {
+        let mut xmit = Vec::new();
+        message.emit_parameters(&mut xmit).unwrap();
+        xmit
+    }
+
+    fn group_add() -> ModelSubscriptionAddMessage {
+        ModelSubscriptionAddMessage {
+            element_address: UnicastAddress(0x0102),
+            subscription_address: SubscriptionAddress::Group(GroupAddress::RFU(0xC001)),
+            model_identifier: ModelIdentifier::SIG(0x1000),
+        }
+    }
+
+    fn virtual_add() -> ModelSubscriptionAddMessage {
+        ModelSubscriptionAddMessage {
+            element_address: UnicastAddress(0x0102),
+            subscription_address: SubscriptionAddress::Virtual(LabelUuid::new([0x42; 16]).unwrap()),
+            model_identifier: ModelIdentifier::Vendor(CompanyIdentifier(0x0059), 0x0001),
+        }
+    }
+
+    fn assert_same(expected: &ModelSubscriptionAddMessage, actual: &ModelSubscriptionAddMessage) {
+        assert_eq!(expected.element_address, actual.element_address);
+        assert_eq!(expected.subscription_address, actual.subscription_address);
+        assert_eq!(expected.model_identifier, actual.model_identifier);
+    }
+
+    #[test]
+    fn test_add_round_trip() {
+        let xmit = emit(ModelSubscriptionMessage::Add(group_add()));
+        assert_eq!(&[0x02, 0x01, 0x01, 0xC0, 0x00, 0x10], &xmit[..]);
+        match ModelSubscriptionMessage::parse_add(&xmit).unwrap() {
+            ModelSubscriptionMessage::Add(add) => assert_same(&group_add(), &add),
+            _ => panic!("expected add"),
+        }
+    }
+
+    #[test]
+    fn test_delete_round_trip() {
+        let xmit = emit(ModelSubscriptionMessage::Delete(group_add()));
+        match ModelSubscriptionMessage::parse_delete(&xmit).unwrap() {
+            ModelSubscriptionMessage::Delete(delete) => assert_same(&group_add(), &delete),
+            _ => panic!("expected delete"),
+        }
+    }
+
+    #[test]
+    fn test_overwrite_round_trip() {
+        let xmit = emit(ModelSubscriptionMessage::Overwrite(group_add()));
+        match ModelSubscriptionMessage::parse_overwrite(&xmit).unwrap() {
+            ModelSubscriptionMessage::Overwrite(overwrite) => assert_same(&group_add(), &overwrite),
+            _ => panic!("expected overwrite"),
+        }
+    }
+
+    #[test]
+    fn test_virtual_address_add_round_trip() {
+        let xmit = emit(ModelSubscriptionMessage::VirtualAddressAdd(virtual_add()));
+        assert_eq!(22, xmit.len());
+        match ModelSubscriptionMessage::parse_virtual_address_add(&xmit).unwrap() {
+            ModelSubscriptionMessage::VirtualAddressAdd(add) => assert_same(&virtual_add(), &add),
+            _ => panic!("expected virtual address add"),
+        }
+    }
+
+    #[test]
+    fn test_virtual_address_delete_round_trip() {
+        let xmit = emit(ModelSubscriptionMessage::VirtualAddressDelete(virtual_add()));
+        match ModelSubscriptionMessage::parse_virtual_address_delete(&xmit).unwrap() {
+            ModelSubscriptionMessage::VirtualAddressDelete(delete) => {
+                assert_same(&virtual_add(), &delete)
             }
-            SubscriptionAddress::Group(_addr) => {
-                todo!("group address")
+            _ => panic!("expected virtual address delete"),
+        }
+    }
+
+    #[test]
+    fn test_virtual_address_overwrite_round_trip() {
+        let xmit = emit(ModelSubscriptionMessage::VirtualAddressOverwrite(
+            virtual_add(),
+        ));
+        match ModelSubscriptionMessage::parse_virtual_address_overwrite(&xmit).unwrap() {
+            ModelSubscriptionMessage::VirtualAddressOverwrite(overwrite) => {
+                assert_same(&virtual_add(), &overwrite)
             }
-            SubscriptionAddress::Virtual(addr) => {
-                let addr_bytes = addr.virtual_address().as_bytes();
-                xmit.push(addr_bytes[1]).map_err(|_| InsufficientBuffer)?;
-                xmit.push(addr_bytes[0]).map_err(|_| InsufficientBuffer)?;
+            _ => panic!("expected virtual address overwrite"),
+        }
+    }
+
+    #[test]
+    fn test_delete_all_round_trip() {
+        let xmit = emit(ModelSubscriptionMessage::DeleteAll(
+            ModelSubscriptionDeleteAllMessage {
+                element_address: UnicastAddress(0x0102),
+                model_identifier: ModelIdentifier::SIG(0x1000),
+            },
+        ));
+        match ModelSubscriptionMessage::parse_delete_all(&xmit).unwrap() {
+            ModelSubscriptionMessage::DeleteAll(delete_all) => {
+                assert_eq!(UnicastAddress(0x0102), delete_all.element_address);
+                assert_eq!(ModelIdentifier::SIG(0x1000), delete_all.model_identifier);
             }
+            _ => panic!("expected delete all"),
+        }
+    }
+
+    #[test]
+    fn test_status_round_trip() {
+        let add = virtual_add();
+        let xmit = emit(ModelSubscriptionMessage::Status(
+            add.create_status_response(Status::InsufficientResources),
+        ));
+        match ModelSubscriptionMessage::parse_status(&xmit).unwrap() {
+            ModelSubscriptionMessage::Status(status) => {
+                assert_eq!(Status::InsufficientResources, status.status);
+                assert_eq!(add.element_address, status.element_address);
+                assert_eq!(
+                    add.subscription_address.address(),
+                    status.subscription_address
+                );
+                assert_eq!(add.model_identifier, status.model_identifier);
+            }
+            _ => panic!("expected status"),
         }
-        self.model_identifier.emit(xmit)?;
-        Ok(())
     }
 }
@@ -0,0 +1,198 @@
+use crate::drivers::ble::mesh::model::foundation::configuration::{KeyIndex, NetKeyIndex};
+use crate::drivers::ble::mesh::model::{Message, Status};
+use crate::drivers::ble::mesh::pdu::access::Opcode;
+use crate::drivers::ble::mesh::pdu::ParseError;
+use crate::drivers::ble::mesh::InsufficientBuffer;
+use crate::opcode;
+use core::convert::TryInto;
+use heapless::Vec;
+
+opcode!( CONFIG_NETKEY_ADD 0x80, 0x40 );
+opcode!( CONFIG_NETKEY_DELETE 0x80, 0x41 );
+opcode!( CONFIG_NETKEY_GET 0x80, 0x42 );
+opcode!( CONFIG_NETKEY_LIST 0x80, 0x43 );
+opcode!( CONFIG_NETKEY_STATUS 0x80, 0x44 );
+opcode!( CONFIG_NETKEY_UPDATE 0x80, 0x45 );
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum NetKeyMessage {
+    Add(NetKeyAddMessage),
+    Delete(NetKeyIndex),
+    Get,
+    List(NetKeyListMessage),
+    Status(NetKeyStatusMessage),
+    Update(NetKeyUpdateMessage),
+}
+
+#[allow(unused)]
+impl NetKeyMessage {
+    pub fn parse_add(parameters: &[u8]) -> Result<Self, ParseError> {
+        let (net_key_index, net_key) = Self::parse_index_and_key(parameters)?;
+        Ok(Self::Add(NetKeyAddMessage {
+            net_key_index,
+            net_key,
+        }))
+    }
+
+    pub fn parse_update(parameters: &[u8]) -> Result<Self, ParseError> {
+        let (net_key_index, net_key) = Self::parse_index_and_key(parameters)?;
+        Ok(Self::Update(NetKeyUpdateMessage {
+            net_key_index,
+            net_key,
+        }))
+    }
+
+    pub fn parse_delete(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 2 {
+            Ok(Self::Delete(NetKeyIndex(KeyIndex::parse_one(parameters)?)))
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    pub fn parse_get(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.is_empty() {
+            Ok(Self::Get)
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn parse_index_and_key(parameters: &[u8]) -> Result<(NetKeyIndex, [u8; 16]), ParseError> {
+        if parameters.len() == 18 {
+            let net_key_index = NetKeyIndex(KeyIndex::parse_one(&parameters[0..=1])?);
+            let net_key = parameters[2..]
+                .try_into()
+                .map_err(|_| ParseError::InvalidLength)?;
+            Ok((net_key_index, net_key))
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+}
+
+impl Message for NetKeyMessage {
+    fn opcode(&self) -> Opcode {
+        match self {
+            Self::Add(_) => CONFIG_NETKEY_ADD,
+            Self::Delete(_) => CONFIG_NETKEY_DELETE,
+            Self::Get => CONFIG_NETKEY_GET,
+            Self::List(_) => CONFIG_NETKEY_LIST,
+            Self::Status(_) => CONFIG_NETKEY_STATUS,
+            Self::Update(_) => CONFIG_NETKEY_UPDATE,
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        match self {
+            Self::Add(inner) => inner.emit_parameters(xmit),
+            Self::Delete(net_key_index) => net_key_index.emit(xmit),
+            Self::Get => Ok(()),
+            Self::List(inner) => inner.emit_parameters(xmit),
+            Self::Status(inner) => inner.emit_parameters(xmit),
+            Self::Update(inner) => inner.emit_parameters(xmit),
+        }
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NetKeyAddMessage {
+    pub(crate) net_key_index: NetKeyIndex,
+    pub(crate) net_key: [u8; 16],
+}
+
+impl NetKeyAddMessage {
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        self.net_key_index.emit(xmit)?;
+        xmit.extend_from_slice(&self.net_key)
+            .map_err(|_| InsufficientBuffer)?;
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NetKeyUpdateMessage {
+    pub(crate) net_key_index: NetKeyIndex,
+    pub(crate) net_key: [u8; 16],
+}
+
+impl NetKeyUpdateMessage {
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        self.net_key_index.emit(xmit)?;
+        xmit.extend_from_slice(&self.net_key)
+            .map_err(|_| InsufficientBuffer)?;
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NetKeyListMessage {
+    pub(crate) net_key_indexes: Vec<NetKeyIndex, 10>,
+}
+
+impl NetKeyListMessage {
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        let indexes: Vec<KeyIndex, 10> = self.net_key_indexes.iter().map(|e| e.0).collect();
+        KeyIndex::emit_many(&indexes, xmit)
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NetKeyStatusMessage {
+    pub(crate) status: Status,
+    pub(crate) net_key_index: NetKeyIndex,
+}
+
+impl NetKeyStatusMessage {
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        xmit.push(self.status as u8)
+            .map_err(|_| InsufficientBuffer)?;
+        self.net_key_index.emit(xmit)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_round_trip() {
+        let net_key = [0x55; 16];
+        let mut xmit: Vec<u8, 32> = Vec::new();
+        NetKeyMessage::Update(NetKeyUpdateMessage {
+            net_key_index: NetKeyIndex::new(0x123),
+            net_key,
+        })
+        .emit_parameters(&mut xmit)
+        .unwrap();
+        assert_eq!(&[0x23, 0x01], &xmit[0..2]);
+
+        match NetKeyMessage::parse_update(&xmit).unwrap() {
+            NetKeyMessage::Update(update) => {
+                assert_eq!(NetKeyIndex::new(0x123), update.net_key_index);
+                assert_eq!(net_key, update.net_key);
+            }
+            _ => panic!("expected update"),
+        }
+        assert!(matches!(
+            NetKeyMessage::parse_update(&xmit[..17]),
+            Err(ParseError::InvalidLength)
+        ));
+    }
+}
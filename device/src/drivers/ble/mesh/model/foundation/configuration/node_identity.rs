@@ -0,0 +1,193 @@
+use crate::drivers::ble::mesh::model::foundation::configuration::{KeyIndex, NetKeyIndex};
+use crate::drivers::ble::mesh::model::{Message, Status};
+use crate::drivers::ble::mesh::pdu::access::Opcode;
+use crate::drivers::ble::mesh::pdu::ParseError;
+use crate::drivers::ble::mesh::InsufficientBuffer;
+use crate::opcode;
+use heapless::Vec;
+
+opcode!( CONFIG_NODE_IDENTITY_GET 0x80, 0x46 );
+opcode!( CONFIG_NODE_IDENTITY_SET 0x80, 0x47 );
+opcode!( CONFIG_NODE_IDENTITY_STATUS 0x80, 0x48 );
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum NodeIdentityState {
+    /// Node Identity advertising for the subnet is stopped.
+    Stopped,
+    /// Node Identity advertising for the subnet is running.
+    Running,
+    /// Node Identity advertising is not supported.
+    NotSupported,
+}
+
+impl NodeIdentityState {
+    pub fn parse(val: u8) -> Result<Self, ParseError> {
+        match val {
+            0x00 => Ok(Self::Stopped),
+            0x01 => Ok(Self::Running),
+            0x02 => Ok(Self::NotSupported),
+            _ => Err(ParseError::InvalidValue),
+        }
+    }
+
+    pub fn emit<const N: usize>(&self, xmit: &mut Vec<u8, N>) -> Result<(), InsufficientBuffer> {
+        let val = match self {
+            Self::Stopped => 0x00,
+            Self::Running => 0x01,
+            Self::NotSupported => 0x02,
+        };
+        xmit.push(val).map_err(|_| InsufficientBuffer)
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum NodeIdentityMessage {
+    Get(NetKeyIndex),
+    Set(NodeIdentitySetMessage),
+    Status(NodeIdentityStatusMessage),
+}
+
+#[allow(unused)]
+impl NodeIdentityMessage {
+    pub fn parse_get(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 2 {
+            Ok(Self::Get(NetKeyIndex(KeyIndex::parse_one(parameters)?)))
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    pub fn parse_set(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 3 {
+            let net_key_index = NetKeyIndex(KeyIndex::parse_one(&parameters[0..=1])?);
+            let identity = match NodeIdentityState::parse(parameters[2])? {
+                // prohibited in a set
+                NodeIdentityState::NotSupported => Err(ParseError::InvalidValue)?,
+                identity => identity,
+            };
+            Ok(Self::Set(NodeIdentitySetMessage {
+                net_key_index,
+                identity,
+            }))
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    pub fn parse_status(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 4 {
+            Ok(Self::Status(NodeIdentityStatusMessage {
+                status: Status::parse(parameters[0])?,
+                net_key_index: NetKeyIndex(KeyIndex::parse_one(&parameters[1..=2])?),
+                identity: NodeIdentityState::parse(parameters[3])?,
+            }))
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+}
+
+impl Message for NodeIdentityMessage {
+    fn opcode(&self) -> Opcode {
+        match self {
+            Self::Get(_) => CONFIG_NODE_IDENTITY_GET,
+            Self::Set(_) => CONFIG_NODE_IDENTITY_SET,
+            Self::Status(_) => CONFIG_NODE_IDENTITY_STATUS,
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        match self {
+            Self::Get(net_key_index) => net_key_index.emit(xmit),
+            Self::Set(inner) => inner.emit_parameters(xmit),
+            Self::Status(inner) => inner.emit_parameters(xmit),
+        }
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NodeIdentitySetMessage {
+    pub(crate) net_key_index: NetKeyIndex,
+    pub(crate) identity: NodeIdentityState,
+}
+
+impl NodeIdentitySetMessage {
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        self.net_key_index.emit(xmit)?;
+        self.identity.emit(xmit)?;
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NodeIdentityStatusMessage {
+    pub(crate) status: Status,
+    pub(crate) net_key_index: NetKeyIndex,
+    pub(crate) identity: NodeIdentityState,
+}
+
+impl NodeIdentityStatusMessage {
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        xmit.push(self.status as u8)
+            .map_err(|_| InsufficientBuffer)?;
+        self.net_key_index.emit(xmit)?;
+        self.identity.emit(xmit)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set() {
+        let message = NodeIdentityMessage::parse_set(&[0x23, 0x01, 0x01]).unwrap();
+        match &message {
+            NodeIdentityMessage::Set(set) => {
+                assert_eq!(set.net_key_index, NetKeyIndex::new(0x123));
+                assert_eq!(set.identity, NodeIdentityState::Running);
+            }
+            _ => panic!("expected set"),
+        }
+
+        let mut bytes = Vec::<u8, 8>::new();
+        message.emit_parameters(&mut bytes).unwrap();
+        assert_eq!(&*bytes, &[0x23, 0x01, 0x01]);
+
+        // not supported is prohibited in a set
+        assert!(NodeIdentityMessage::parse_set(&[0x23, 0x01, 0x02]).is_err());
+    }
+
+    #[test]
+    fn test_status() {
+        let message = NodeIdentityMessage::Status(NodeIdentityStatusMessage {
+            status: Status::InvalidNetKeyIndex,
+            net_key_index: NetKeyIndex::new(0x001),
+            identity: NodeIdentityState::Stopped,
+        });
+
+        let mut bytes = Vec::<u8, 8>::new();
+        message.emit_parameters(&mut bytes).unwrap();
+        assert_eq!(&*bytes, &[0x04, 0x01, 0x00, 0x00]);
+
+        match NodeIdentityMessage::parse_status(&bytes).unwrap() {
+            NodeIdentityMessage::Status(status) => {
+                assert_eq!(status.status, Status::InvalidNetKeyIndex);
+                assert_eq!(status.net_key_index, NetKeyIndex::new(0x001));
+                assert_eq!(status.identity, NodeIdentityState::Stopped);
+            }
+            _ => panic!("expected status"),
+        }
+    }
+}
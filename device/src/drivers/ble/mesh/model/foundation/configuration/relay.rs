@@ -126,6 +126,6 @@ impl RelayMessage {
     }
 
     pub fn parse_status(parameters: &[u8]) -> Result<Self, ParseError> {
-        Ok(Self::Set(RelayConfig::parse(parameters)?))
+        Ok(Self::Status(RelayConfig::parse(parameters)?))
     }
 }
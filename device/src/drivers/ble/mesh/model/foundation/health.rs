@@ -0,0 +1,325 @@
+use crate::drivers::ble::mesh::composition::CompanyIdentifier;
+use crate::drivers::ble::mesh::model::{Message, Model, ModelIdentifier};
+use crate::drivers::ble::mesh::pdu::access::{
+    Opcode, HEALTH_ATTENTION_GET, HEALTH_ATTENTION_SET, HEALTH_ATTENTION_SET_UNACKNOWLEDGED,
+    HEALTH_ATTENTION_STATUS, HEALTH_CURRENT_STATUS, HEALTH_FAULT_CLEAR,
+    HEALTH_FAULT_CLEAR_UNACKNOWLEDGED, HEALTH_FAULT_GET, HEALTH_FAULT_STATUS, HEALTH_FAULT_TEST,
+    HEALTH_FAULT_TEST_UNACKNOWLEDGED, HEALTH_PERIOD_GET, HEALTH_PERIOD_SET,
+    HEALTH_PERIOD_SET_UNACKNOWLEDGED, HEALTH_PERIOD_STATUS,
+};
+use crate::drivers::ble::mesh::pdu::ParseError;
+use crate::drivers::ble::mesh::InsufficientBuffer;
+use heapless::Vec;
+
+pub const HEALTH_SERVER: ModelIdentifier = ModelIdentifier::SIG(0x0002);
+pub const HEALTH_CLIENT: ModelIdentifier = ModelIdentifier::SIG(0x0003);
+
+/// Maximum number of faults reported in a fault array.
+pub const MAX_FAULTS: usize = 16;
+
+/// Highest Fast Period Divisor, dividing the publish period by 2^15.
+pub const MAX_FAST_PERIOD_DIVISOR: u8 = 15;
+
+#[derive(Clone, Debug, Default)]
+pub struct HealthServer;
+
+#[derive(Clone, Debug, Default)]
+pub struct HealthClient;
+
+#[derive(Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HealthMessage {
+    CurrentStatus(FaultStatus),
+    FaultStatus(FaultStatus),
+    FaultGet(CompanyIdentifier),
+    FaultClear(CompanyIdentifier),
+    FaultClearUnacknowledged(CompanyIdentifier),
+    FaultTest(FaultTest),
+    FaultTestUnacknowledged(FaultTest),
+    PeriodGet,
+    PeriodSet(u8),
+    PeriodSetUnacknowledged(u8),
+    PeriodStatus(u8),
+    AttentionGet,
+    AttentionSet(u8),
+    AttentionSetUnacknowledged(u8),
+    AttentionStatus(u8),
+}
+
+impl Message for HealthMessage {
+    fn opcode(&self) -> Opcode {
+        match self {
+            Self::CurrentStatus(_) => HEALTH_CURRENT_STATUS,
+            Self::FaultStatus(_) => HEALTH_FAULT_STATUS,
+            Self::FaultGet(_) => HEALTH_FAULT_GET,
+            Self::FaultClear(_) => HEALTH_FAULT_CLEAR,
+            Self::FaultClearUnacknowledged(_) => HEALTH_FAULT_CLEAR_UNACKNOWLEDGED,
+            Self::FaultTest(_) => HEALTH_FAULT_TEST,
+            Self::FaultTestUnacknowledged(_) => HEALTH_FAULT_TEST_UNACKNOWLEDGED,
+            Self::PeriodGet => HEALTH_PERIOD_GET,
+            Self::PeriodSet(_) => HEALTH_PERIOD_SET,
+            Self::PeriodSetUnacknowledged(_) => HEALTH_PERIOD_SET_UNACKNOWLEDGED,
+            Self::PeriodStatus(_) => HEALTH_PERIOD_STATUS,
+            Self::AttentionGet => HEALTH_ATTENTION_GET,
+            Self::AttentionSet(_) => HEALTH_ATTENTION_SET,
+            Self::AttentionSetUnacknowledged(_) => HEALTH_ATTENTION_SET_UNACKNOWLEDGED,
+            Self::AttentionStatus(_) => HEALTH_ATTENTION_STATUS,
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        match self {
+            Self::CurrentStatus(inner) | Self::FaultStatus(inner) => inner.emit_parameters(xmit),
+            Self::FaultGet(company_id)
+            | Self::FaultClear(company_id)
+            | Self::FaultClearUnacknowledged(company_id) => {
+                xmit.extend_from_slice(&company_id.0.to_le_bytes())
+                    .map_err(|_| InsufficientBuffer)?;
+                Ok(())
+            }
+            Self::FaultTest(inner) | Self::FaultTestUnacknowledged(inner) => {
+                inner.emit_parameters(xmit)
+            }
+            Self::PeriodGet | Self::AttentionGet => Ok(()),
+            Self::PeriodSet(val)
+            | Self::PeriodSetUnacknowledged(val)
+            | Self::PeriodStatus(val)
+            | Self::AttentionSet(val)
+            | Self::AttentionSetUnacknowledged(val)
+            | Self::AttentionStatus(val) => {
+                xmit.push(*val).map_err(|_| InsufficientBuffer)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Model for HealthServer {
+    const IDENTIFIER: ModelIdentifier = HEALTH_SERVER;
+    const SUPPORTS_SUBSCRIPTION: bool = false;
+    type Message<'m> = HealthMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            HEALTH_FAULT_GET => Ok(Some(HealthMessage::FaultGet(parse_company_id(parameters)?))),
+            HEALTH_FAULT_CLEAR => Ok(Some(HealthMessage::FaultClear(parse_company_id(
+                parameters,
+            )?))),
+            HEALTH_FAULT_CLEAR_UNACKNOWLEDGED => Ok(Some(HealthMessage::FaultClearUnacknowledged(
+                parse_company_id(parameters)?,
+            ))),
+            HEALTH_FAULT_TEST => Ok(Some(HealthMessage::FaultTest(FaultTest::parse(
+                parameters,
+            )?))),
+            HEALTH_FAULT_TEST_UNACKNOWLEDGED => Ok(Some(HealthMessage::FaultTestUnacknowledged(
+                FaultTest::parse(parameters)?,
+            ))),
+            HEALTH_PERIOD_GET => Ok(Some(HealthMessage::PeriodGet)),
+            HEALTH_PERIOD_SET => Ok(Some(HealthMessage::PeriodSet(parse_fast_period_divisor(
+                parameters,
+            )?))),
+            HEALTH_PERIOD_SET_UNACKNOWLEDGED => Ok(Some(HealthMessage::PeriodSetUnacknowledged(
+                parse_fast_period_divisor(parameters)?,
+            ))),
+            HEALTH_ATTENTION_GET => Ok(Some(HealthMessage::AttentionGet)),
+            HEALTH_ATTENTION_SET => Ok(Some(HealthMessage::AttentionSet(parse_u8(parameters)?))),
+            HEALTH_ATTENTION_SET_UNACKNOWLEDGED => Ok(Some(
+                HealthMessage::AttentionSetUnacknowledged(parse_u8(parameters)?),
+            )),
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl Model for HealthClient {
+    const IDENTIFIER: ModelIdentifier = HEALTH_CLIENT;
+    type Message<'m> = HealthMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            HEALTH_CURRENT_STATUS => Ok(Some(HealthMessage::CurrentStatus(FaultStatus::parse(
+                parameters,
+            )?))),
+            HEALTH_FAULT_STATUS => Ok(Some(HealthMessage::FaultStatus(FaultStatus::parse(
+                parameters,
+            )?))),
+            HEALTH_PERIOD_STATUS => Ok(Some(HealthMessage::PeriodStatus(
+                parse_fast_period_divisor(parameters)?,
+            ))),
+            HEALTH_ATTENTION_STATUS => {
+                Ok(Some(HealthMessage::AttentionStatus(parse_u8(parameters)?)))
+            }
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+fn parse_u8(parameters: &[u8]) -> Result<u8, ParseError> {
+    if parameters.len() == 1 {
+        Ok(parameters[0])
+    } else {
+        Err(ParseError::InvalidLength)
+    }
+}
+
+fn parse_fast_period_divisor(parameters: &[u8]) -> Result<u8, ParseError> {
+    let divisor = parse_u8(parameters)?;
+    if divisor > MAX_FAST_PERIOD_DIVISOR {
+        Err(ParseError::InvalidValue)
+    } else {
+        Ok(divisor)
+    }
+}
+
+fn parse_company_id(parameters: &[u8]) -> Result<CompanyIdentifier, ParseError> {
+    if parameters.len() == 2 {
+        CompanyIdentifier::parse(parameters)
+    } else {
+        Err(ParseError::InvalidLength)
+    }
+}
+
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FaultTest {
+    pub test_id: u8,
+    pub company_id: CompanyIdentifier,
+}
+
+impl FaultTest {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 3 {
+            Ok(Self {
+                test_id: parameters[0],
+                company_id: CompanyIdentifier::parse(&parameters[1..])?,
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        xmit.push(self.test_id).map_err(|_| InsufficientBuffer)?;
+        xmit.extend_from_slice(&self.company_id.0.to_le_bytes())
+            .map_err(|_| InsufficientBuffer)?;
+        Ok(())
+    }
+}
+
+/// The fault array of the most recent test, as carried by both
+/// Health Current Status and Health Fault Status.
+#[derive(Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FaultStatus {
+    pub test_id: u8,
+    pub company_id: CompanyIdentifier,
+    pub faults: Vec<u8, MAX_FAULTS>,
+}
+
+impl FaultStatus {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() >= 3 {
+            Ok(Self {
+                test_id: parameters[0],
+                company_id: CompanyIdentifier::parse(&parameters[1..3])?,
+                faults: Vec::from_slice(&parameters[3..])
+                    .map_err(|_| ParseError::InsufficientBuffer)?,
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        xmit.push(self.test_id).map_err(|_| InsufficientBuffer)?;
+        xmit.extend_from_slice(&self.company_id.0.to_le_bytes())
+            .map_err(|_| InsufficientBuffer)?;
+        xmit.extend_from_slice(&self.faults)
+            .map_err(|_| InsufficientBuffer)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fault_status_round_trip() {
+        let mut faults = Vec::new();
+        faults.extend_from_slice(&[0x01, 0x2A]).unwrap();
+        let mut xmit: Vec<u8, 16> = Vec::new();
+        HealthMessage::CurrentStatus(FaultStatus {
+            test_id: 0x00,
+            company_id: CompanyIdentifier(0x05F1),
+            faults,
+        })
+        .emit_parameters(&mut xmit)
+        .unwrap();
+        assert_eq!(&[0x00, 0xF1, 0x05, 0x01, 0x2A], &xmit[..]);
+
+        match HealthClient::parse(HEALTH_CURRENT_STATUS, &xmit).unwrap() {
+            Some(HealthMessage::CurrentStatus(status)) => {
+                assert_eq!(0x00, status.test_id);
+                assert_eq!(CompanyIdentifier(0x05F1), status.company_id);
+                assert_eq!(&[0x01, 0x2A], &status.faults[..]);
+            }
+            _ => panic!("expected current status"),
+        }
+    }
+
+    #[test]
+    fn test_parse_fault_messages() {
+        match HealthServer::parse(HEALTH_FAULT_GET, &[0xF1, 0x05]).unwrap() {
+            Some(HealthMessage::FaultGet(company_id)) => {
+                assert_eq!(CompanyIdentifier(0x05F1), company_id)
+            }
+            _ => panic!("expected fault get"),
+        }
+        match HealthServer::parse(HEALTH_FAULT_TEST_UNACKNOWLEDGED, &[0x03, 0xF1, 0x05]).unwrap() {
+            Some(HealthMessage::FaultTestUnacknowledged(test)) => {
+                assert_eq!(0x03, test.test_id);
+                assert_eq!(CompanyIdentifier(0x05F1), test.company_id);
+            }
+            _ => panic!("expected fault test"),
+        }
+        assert!(HealthServer::parse(HEALTH_FAULT_CLEAR, &[0xF1]).is_err());
+    }
+
+    #[test]
+    fn test_parse_period_and_attention() {
+        match HealthServer::parse(HEALTH_PERIOD_SET, &[0x0F]).unwrap() {
+            Some(HealthMessage::PeriodSet(divisor)) => assert_eq!(15, divisor),
+            _ => panic!("expected period set"),
+        }
+        assert!(HealthServer::parse(HEALTH_PERIOD_SET, &[0x10]).is_err());
+
+        match HealthServer::parse(HEALTH_ATTENTION_SET_UNACKNOWLEDGED, &[0x05]).unwrap() {
+            Some(HealthMessage::AttentionSetUnacknowledged(attention)) => {
+                assert_eq!(5, attention)
+            }
+            _ => panic!("expected attention set"),
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use crate::drivers::ble::mesh::model::{Message, Model, ModelIdentifier};
+use crate::drivers::ble::mesh::pdu::access::Opcode;
+use crate::drivers::ble::mesh::pdu::ParseError;
+use crate::drivers::ble::mesh::InsufficientBuffer;
+use crate::opcode;
+use heapless::Vec;
+
+#[derive(Clone, Debug)]
+pub struct GenericDefaultTransitionTimeServer;
+
+#[derive(Clone, Debug)]
+pub struct GenericDefaultTransitionTimeClient;
+
+pub const GENERIC_DEFAULT_TRANSITION_TIME_SERVER: ModelIdentifier = ModelIdentifier::SIG(0x1004);
+pub const GENERIC_DEFAULT_TRANSITION_TIME_CLIENT: ModelIdentifier = ModelIdentifier::SIG(0x1005);
+
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GenericDefaultTransitionTimeMessage {
+    Get,
+    Set(TransitionTime),
+    SetUnacknowledged(TransitionTime),
+    Status(TransitionTime),
+}
+
+impl Message for GenericDefaultTransitionTimeMessage {
+    fn opcode(&self) -> Opcode {
+        match self {
+            Self::Get => GENERIC_DEFAULT_TRANSITION_TIME_GET,
+            Self::Set(_) => GENERIC_DEFAULT_TRANSITION_TIME_SET,
+            Self::SetUnacknowledged(_) => GENERIC_DEFAULT_TRANSITION_TIME_SET_UNACKNOWLEDGE,
+            Self::Status(_) => GENERIC_DEFAULT_TRANSITION_TIME_STATUS,
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut heapless::Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        match self {
+            Self::Get => Ok(()),
+            Self::Set(inner) => inner.emit_parameters(xmit),
+            Self::SetUnacknowledged(inner) => inner.emit_parameters(xmit),
+            Self::Status(inner) => inner.emit_parameters(xmit),
+        }
+    }
+}
+
+impl Model for GenericDefaultTransitionTimeServer {
+    const IDENTIFIER: ModelIdentifier = GENERIC_DEFAULT_TRANSITION_TIME_SERVER;
+    type Message<'m> = GenericDefaultTransitionTimeMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            GENERIC_DEFAULT_TRANSITION_TIME_GET => {
+                Ok(Some(GenericDefaultTransitionTimeMessage::Get))
+            }
+            GENERIC_DEFAULT_TRANSITION_TIME_SET => Ok(Some(
+                GenericDefaultTransitionTimeMessage::Set(TransitionTime::parse(parameters)?),
+            )),
+            GENERIC_DEFAULT_TRANSITION_TIME_SET_UNACKNOWLEDGE => Ok(Some(
+                GenericDefaultTransitionTimeMessage::SetUnacknowledged(TransitionTime::parse(
+                    parameters,
+                )?),
+            )),
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl Model for GenericDefaultTransitionTimeClient {
+    const IDENTIFIER: ModelIdentifier = GENERIC_DEFAULT_TRANSITION_TIME_CLIENT;
+    type Message<'m> = GenericDefaultTransitionTimeMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            GENERIC_DEFAULT_TRANSITION_TIME_STATUS => Ok(Some(
+                GenericDefaultTransitionTimeMessage::Status(TransitionTime::parse(parameters)?),
+            )),
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+opcode!( GENERIC_DEFAULT_TRANSITION_TIME_GET 0x82, 0x0D );
+opcode!( GENERIC_DEFAULT_TRANSITION_TIME_SET 0x82, 0x0E );
+opcode!( GENERIC_DEFAULT_TRANSITION_TIME_SET_UNACKNOWLEDGE 0x82, 0x0F );
+opcode!( GENERIC_DEFAULT_TRANSITION_TIME_STATUS 0x82, 0x10 );
+
+/// Step resolution of a transition time.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StepResolution {
+    Milliseconds100 = 0b00,
+    Seconds1 = 0b01,
+    Seconds10 = 0b10,
+    Minutes10 = 0b11,
+}
+
+impl StepResolution {
+    fn millis(&self) -> u32 {
+        match self {
+            Self::Milliseconds100 => 100,
+            Self::Seconds1 => 1_000,
+            Self::Seconds10 => 10_000,
+            Self::Minutes10 => 600_000,
+        }
+    }
+}
+
+/// A transition time, as a number of steps of a given resolution.
+///
+/// A step count of `0x3F` denotes an unknown time, and is not allowed
+/// as the default transition time.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TransitionTime {
+    pub steps: u8,
+    pub resolution: StepResolution,
+}
+
+impl TransitionTime {
+    pub const UNKNOWN_STEPS: u8 = 0x3F;
+
+    pub fn new(steps: u8, resolution: StepResolution) -> Self {
+        Self { steps, resolution }
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        self.steps == Self::UNKNOWN_STEPS
+    }
+
+    /// Duration of the transition, unless it is unknown.
+    pub fn as_millis(&self) -> Option<u32> {
+        if self.is_unknown() {
+            None
+        } else {
+            Some(self.steps as u32 * self.resolution.millis())
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        let resolution = match value >> 6 {
+            0b00 => StepResolution::Milliseconds100,
+            0b01 => StepResolution::Seconds1,
+            0b10 => StepResolution::Seconds10,
+            _ => StepResolution::Minutes10,
+        };
+        Self {
+            steps: value & 0x3F,
+            resolution,
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        ((self.resolution as u8) << 6) | (self.steps & 0x3F)
+    }
+
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 1 {
+            let transition_time = Self::from_u8(parameters[0]);
+            if transition_time.is_unknown() {
+                Err(ParseError::InvalidValue)
+            } else {
+                Ok(transition_time)
+            }
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        xmit.push(self.to_u8()).map_err(|_| InsufficientBuffer)?;
+        Ok(())
+    }
+}
+
+impl Default for TransitionTime {
+    fn default() -> Self {
+        Self::new(0, StepResolution::Milliseconds100)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_set() {
+        // 10 steps of 1 second
+        match GenericDefaultTransitionTimeServer::parse(
+            GENERIC_DEFAULT_TRANSITION_TIME_SET,
+            &[0x4A],
+        )
+        .unwrap()
+        {
+            Some(GenericDefaultTransitionTimeMessage::Set(time)) => {
+                assert_eq!(TransitionTime::new(10, StepResolution::Seconds1), time);
+                assert_eq!(Some(10_000), time.as_millis());
+            }
+            _ => panic!("expected set"),
+        }
+    }
+
+    #[test]
+    fn test_parse_set_rejects_unknown() {
+        assert!(GenericDefaultTransitionTimeServer::parse(
+            GENERIC_DEFAULT_TRANSITION_TIME_SET,
+            &[0xFF]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_status_round_trip() {
+        let mut xmit: Vec<u8, 1> = Vec::new();
+        GenericDefaultTransitionTimeMessage::Status(TransitionTime::new(
+            3,
+            StepResolution::Minutes10,
+        ))
+        .emit_parameters(&mut xmit)
+        .unwrap();
+        assert_eq!(&[0xC3], &xmit[..]);
+
+        match GenericDefaultTransitionTimeClient::parse(
+            GENERIC_DEFAULT_TRANSITION_TIME_STATUS,
+            &xmit,
+        )
+        .unwrap()
+        {
+            Some(GenericDefaultTransitionTimeMessage::Status(time)) => {
+                assert_eq!(TransitionTime::new(3, StepResolution::Minutes10), time);
+                assert_eq!(Some(1_800_000), time.as_millis());
+            }
+            _ => panic!("expected status"),
+        }
+    }
+}
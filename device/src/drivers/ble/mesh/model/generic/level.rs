@@ -0,0 +1,398 @@
+use crate::drivers::ble::mesh::model::{Message, Model, ModelIdentifier};
+use crate::drivers::ble::mesh::pdu::access::Opcode;
+use crate::drivers::ble::mesh::pdu::ParseError;
+use crate::drivers::ble::mesh::InsufficientBuffer;
+use crate::opcode;
+use heapless::Vec;
+
+#[derive(Clone, Debug)]
+pub struct GenericLevelServer;
+
+#[derive(Clone, Debug)]
+pub struct GenericLevelClient;
+
+pub const GENERIC_LEVEL_SERVER: ModelIdentifier = ModelIdentifier::SIG(0x1002);
+pub const GENERIC_LEVEL_CLIENT: ModelIdentifier = ModelIdentifier::SIG(0x1003);
+
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GenericLevelMessage {
+    Get,
+    Set(Set),
+    SetUnacknowledged(Set),
+    DeltaSet(DeltaSet),
+    DeltaSetUnacknowledged(DeltaSet),
+    MoveSet(MoveSet),
+    MoveSetUnacknowledged(MoveSet),
+    Status(Status),
+}
+
+impl Message for GenericLevelMessage {
+    fn opcode(&self) -> Opcode {
+        match self {
+            Self::Get => GENERIC_LEVEL_GET,
+            Self::Set(_) => GENERIC_LEVEL_SET,
+            Self::SetUnacknowledged(_) => GENERIC_LEVEL_SET_UNACKNOWLEDGE,
+            Self::DeltaSet(_) => GENERIC_DELTA_SET,
+            Self::DeltaSetUnacknowledged(_) => GENERIC_DELTA_SET_UNACKNOWLEDGE,
+            Self::MoveSet(_) => GENERIC_MOVE_SET,
+            Self::MoveSetUnacknowledged(_) => GENERIC_MOVE_SET_UNACKNOWLEDGE,
+            Self::Status(_) => GENERIC_LEVEL_STATUS,
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut heapless::Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        match self {
+            Self::Get => Ok(()),
+            Self::Set(inner) => inner.emit_parameters(xmit),
+            Self::SetUnacknowledged(inner) => inner.emit_parameters(xmit),
+            Self::DeltaSet(inner) => inner.emit_parameters(xmit),
+            Self::DeltaSetUnacknowledged(inner) => inner.emit_parameters(xmit),
+            Self::MoveSet(inner) => inner.emit_parameters(xmit),
+            Self::MoveSetUnacknowledged(inner) => inner.emit_parameters(xmit),
+            Self::Status(inner) => inner.emit_parameters(xmit),
+        }
+    }
+}
+
+impl Model for GenericLevelServer {
+    const IDENTIFIER: ModelIdentifier = GENERIC_LEVEL_SERVER;
+    type Message<'m> = GenericLevelMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            GENERIC_LEVEL_GET => Ok(Some(GenericLevelMessage::Get)),
+            GENERIC_LEVEL_SET => Ok(Some(GenericLevelMessage::Set(Set::parse(parameters)?))),
+            GENERIC_LEVEL_SET_UNACKNOWLEDGE => Ok(Some(GenericLevelMessage::SetUnacknowledged(
+                Set::parse(parameters)?,
+            ))),
+            GENERIC_DELTA_SET => Ok(Some(GenericLevelMessage::DeltaSet(DeltaSet::parse(
+                parameters,
+            )?))),
+            GENERIC_DELTA_SET_UNACKNOWLEDGE => Ok(Some(
+                GenericLevelMessage::DeltaSetUnacknowledged(DeltaSet::parse(parameters)?),
+            )),
+            GENERIC_MOVE_SET => Ok(Some(GenericLevelMessage::MoveSet(MoveSet::parse(
+                parameters,
+            )?))),
+            GENERIC_MOVE_SET_UNACKNOWLEDGE => Ok(Some(GenericLevelMessage::MoveSetUnacknowledged(
+                MoveSet::parse(parameters)?,
+            ))),
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl Model for GenericLevelClient {
+    const IDENTIFIER: ModelIdentifier = GENERIC_LEVEL_CLIENT;
+    type Message<'m> = GenericLevelMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            GENERIC_LEVEL_STATUS => Ok(Some(GenericLevelMessage::Status(Status::parse(
+                parameters,
+            )?))),
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+opcode!( GENERIC_LEVEL_GET 0x82, 0x05 );
+opcode!( GENERIC_LEVEL_SET 0x82, 0x06 );
+opcode!( GENERIC_LEVEL_SET_UNACKNOWLEDGE 0x82, 0x07 );
+opcode!( GENERIC_LEVEL_STATUS 0x82, 0x08 );
+opcode!( GENERIC_DELTA_SET 0x82, 0x09 );
+opcode!( GENERIC_DELTA_SET_UNACKNOWLEDGE 0x82, 0x0A );
+opcode!( GENERIC_MOVE_SET 0x82, 0x0B );
+opcode!( GENERIC_MOVE_SET_UNACKNOWLEDGE 0x82, 0x0C );
+
+/// Optional transition time and delay trailing the Set, Delta Set and Move Set messages.
+///
+/// The delay is only present alongside a transition time.
+pub(crate) fn parse_transition(parameters: &[u8]) -> Result<(Option<u8>, Option<u8>), ParseError> {
+    match parameters.len() {
+        0 => Ok((None, None)),
+        2 => Ok((Some(parameters[0]), Some(parameters[1]))),
+        _ => Err(ParseError::InvalidLength),
+    }
+}
+
+pub(crate) fn emit_transition<const N: usize>(
+    transition_time: Option<u8>,
+    delay: Option<u8>,
+    xmit: &mut Vec<u8, N>,
+) -> Result<(), InsufficientBuffer> {
+    if let Some(transition_time) = transition_time {
+        xmit.push(transition_time).map_err(|_| InsufficientBuffer)?;
+        xmit.push(delay.unwrap_or(0))
+            .map_err(|_| InsufficientBuffer)?;
+    }
+    Ok(())
+}
+
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Set {
+    pub level: i16,
+    pub tid: u8,
+    pub transition_time: Option<u8>,
+    pub delay: Option<u8>,
+}
+
+impl Set {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() >= 3 {
+            let level = i16::from_le_bytes([parameters[0], parameters[1]]);
+            let tid = parameters[2];
+            let (transition_time, delay) = parse_transition(&parameters[3..])?;
+            Ok(Self {
+                level,
+                tid,
+                transition_time,
+                delay,
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        xmit.extend_from_slice(&self.level.to_le_bytes())
+            .map_err(|_| InsufficientBuffer)?;
+        xmit.push(self.tid).map_err(|_| InsufficientBuffer)?;
+        emit_transition(self.transition_time, self.delay, xmit)
+    }
+}
+
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DeltaSet {
+    pub delta_level: i32,
+    pub tid: u8,
+    pub transition_time: Option<u8>,
+    pub delay: Option<u8>,
+}
+
+impl DeltaSet {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() >= 5 {
+            let delta_level =
+                i32::from_le_bytes([parameters[0], parameters[1], parameters[2], parameters[3]]);
+            let tid = parameters[4];
+            let (transition_time, delay) = parse_transition(&parameters[5..])?;
+            Ok(Self {
+                delta_level,
+                tid,
+                transition_time,
+                delay,
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        xmit.extend_from_slice(&self.delta_level.to_le_bytes())
+            .map_err(|_| InsufficientBuffer)?;
+        xmit.push(self.tid).map_err(|_| InsufficientBuffer)?;
+        emit_transition(self.transition_time, self.delay, xmit)
+    }
+}
+
+/// Start moving the level by `delta_level` every `transition_time`.
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MoveSet {
+    pub delta_level: i16,
+    pub tid: u8,
+    pub transition_time: Option<u8>,
+    pub delay: Option<u8>,
+}
+
+impl MoveSet {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() >= 3 {
+            let delta_level = i16::from_le_bytes([parameters[0], parameters[1]]);
+            let tid = parameters[2];
+            let (transition_time, delay) = parse_transition(&parameters[3..])?;
+            Ok(Self {
+                delta_level,
+                tid,
+                transition_time,
+                delay,
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        xmit.extend_from_slice(&self.delta_level.to_le_bytes())
+            .map_err(|_| InsufficientBuffer)?;
+        xmit.push(self.tid).map_err(|_| InsufficientBuffer)?;
+        emit_transition(self.transition_time, self.delay, xmit)
+    }
+}
+
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Status {
+    pub present_level: i16,
+    /// Target level and remaining time, while a transition is in progress.
+    pub target: Option<(i16, u8)>,
+}
+
+impl Status {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        match parameters.len() {
+            2 => Ok(Self {
+                present_level: i16::from_le_bytes([parameters[0], parameters[1]]),
+                target: None,
+            }),
+            5 => Ok(Self {
+                present_level: i16::from_le_bytes([parameters[0], parameters[1]]),
+                target: Some((
+                    i16::from_le_bytes([parameters[2], parameters[3]]),
+                    parameters[4],
+                )),
+            }),
+            _ => Err(ParseError::InvalidLength),
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        xmit.extend_from_slice(&self.present_level.to_le_bytes())
+            .map_err(|_| InsufficientBuffer)?;
+        if let Some((target_level, remaining_time)) = self.target {
+            xmit.extend_from_slice(&target_level.to_le_bytes())
+                .map_err(|_| InsufficientBuffer)?;
+            xmit.push(remaining_time).map_err(|_| InsufficientBuffer)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_set() {
+        // level -2 (0xFFFE), tid 0x11, 100ms x 5 steps, 5ms x 4 delay
+        let parameters = [0xFE, 0xFF, 0x11, 0x05, 0x04];
+        match GenericLevelServer::parse(GENERIC_LEVEL_SET, &parameters).unwrap() {
+            Some(GenericLevelMessage::Set(set)) => {
+                assert_eq!(-2, set.level);
+                assert_eq!(0x11, set.tid);
+                assert_eq!(Some(0x05), set.transition_time);
+                assert_eq!(Some(0x04), set.delay);
+            }
+            _ => panic!("expected set"),
+        }
+
+        let mut xmit: Vec<u8, 8> = Vec::new();
+        GenericLevelMessage::SetUnacknowledged(Set {
+            level: -2,
+            tid: 0x11,
+            transition_time: Some(0x05),
+            delay: Some(0x04),
+        })
+        .emit_parameters(&mut xmit)
+        .unwrap();
+        assert_eq!(&parameters, &xmit[..]);
+    }
+
+    #[test]
+    fn test_parse_set_rejects_transition_without_delay() {
+        assert!(GenericLevelServer::parse(GENERIC_LEVEL_SET, &[0x00, 0x80, 0x01, 0x05]).is_err());
+    }
+
+    #[test]
+    fn test_parse_delta_set() {
+        // delta 0x00012345, tid 0x22, no transition
+        let parameters = [0x45, 0x23, 0x01, 0x00, 0x22];
+        match GenericLevelServer::parse(GENERIC_DELTA_SET_UNACKNOWLEDGE, &parameters).unwrap() {
+            Some(GenericLevelMessage::DeltaSetUnacknowledged(delta)) => {
+                assert_eq!(0x12345, delta.delta_level);
+                assert_eq!(0x22, delta.tid);
+                assert_eq!(None, delta.transition_time);
+                assert_eq!(None, delta.delay);
+
+                let mut xmit: Vec<u8, 8> = Vec::new();
+                delta.emit_parameters(&mut xmit).unwrap();
+                assert_eq!(&parameters, &xmit[..]);
+            }
+            _ => panic!("expected delta set"),
+        }
+    }
+
+    #[test]
+    fn test_parse_move_set() {
+        // delta -256 per 1s step, tid 0x33, no delay
+        let parameters = [0x00, 0xFF, 0x33, 0x41, 0x00];
+        match GenericLevelServer::parse(GENERIC_MOVE_SET, &parameters).unwrap() {
+            Some(GenericLevelMessage::MoveSet(move_set)) => {
+                assert_eq!(-256, move_set.delta_level);
+                assert_eq!(0x33, move_set.tid);
+                assert_eq!(Some(0x41), move_set.transition_time);
+                assert_eq!(Some(0x00), move_set.delay);
+            }
+            _ => panic!("expected move set"),
+        }
+    }
+
+    #[test]
+    fn test_status_round_trip() {
+        let mut xmit: Vec<u8, 8> = Vec::new();
+        GenericLevelMessage::Status(Status {
+            present_level: 0x1234,
+            target: Some((0x7FFF, 0x0A)),
+        })
+        .emit_parameters(&mut xmit)
+        .unwrap();
+        assert_eq!(&[0x34, 0x12, 0xFF, 0x7F, 0x0A], &xmit[..]);
+
+        match GenericLevelClient::parse(GENERIC_LEVEL_STATUS, &xmit).unwrap() {
+            Some(GenericLevelMessage::Status(status)) => {
+                assert_eq!(0x1234, status.present_level);
+                assert_eq!(Some((0x7FFF, 0x0A)), status.target);
+            }
+            _ => panic!("expected status"),
+        }
+
+        match GenericLevelClient::parse(GENERIC_LEVEL_STATUS, &[0x00, 0x80]).unwrap() {
+            Some(GenericLevelMessage::Status(status)) => {
+                assert_eq!(i16::MIN, status.present_level);
+                assert_eq!(None, status.target);
+            }
+            _ => panic!("expected status"),
+        }
+    }
+}
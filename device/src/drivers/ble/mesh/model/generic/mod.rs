@@ -1,2 +1,5 @@
 pub mod battery;
+pub mod default_transition_time;
+pub mod level;
 pub mod onoff;
+pub mod power_on_off;
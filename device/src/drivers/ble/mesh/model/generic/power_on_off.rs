@@ -0,0 +1,228 @@
+use crate::drivers::ble::mesh::model::{Message, Model, ModelIdentifier};
+use crate::drivers::ble::mesh::pdu::access::Opcode;
+use crate::drivers::ble::mesh::pdu::ParseError;
+use crate::drivers::ble::mesh::storage::{Payload, Storage};
+use crate::drivers::ble::mesh::InsufficientBuffer;
+use crate::opcode;
+use heapless::Vec;
+
+#[derive(Clone, Debug)]
+pub struct GenericPowerOnOffServer;
+
+#[derive(Clone, Debug)]
+pub struct GenericPowerOnOffSetupServer;
+
+#[derive(Clone, Debug)]
+pub struct GenericPowerOnOffClient;
+
+pub const GENERIC_POWER_ONOFF_SERVER: ModelIdentifier = ModelIdentifier::SIG(0x1006);
+pub const GENERIC_POWER_ONOFF_SETUP_SERVER: ModelIdentifier = ModelIdentifier::SIG(0x1007);
+pub const GENERIC_POWER_ONOFF_CLIENT: ModelIdentifier = ModelIdentifier::SIG(0x1008);
+
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GenericPowerOnOffMessage {
+    Get,
+    Set(OnPowerUp),
+    SetUnacknowledged(OnPowerUp),
+    Status(OnPowerUp),
+}
+
+impl Message for GenericPowerOnOffMessage {
+    fn opcode(&self) -> Opcode {
+        match self {
+            Self::Get => GENERIC_ON_POWER_UP_GET,
+            Self::Set(_) => GENERIC_ON_POWER_UP_SET,
+            Self::SetUnacknowledged(_) => GENERIC_ON_POWER_UP_SET_UNACKNOWLEDGE,
+            Self::Status(_) => GENERIC_ON_POWER_UP_STATUS,
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut heapless::Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        match self {
+            Self::Get => Ok(()),
+            Self::Set(inner) => inner.emit_parameters(xmit),
+            Self::SetUnacknowledged(inner) => inner.emit_parameters(xmit),
+            Self::Status(inner) => inner.emit_parameters(xmit),
+        }
+    }
+}
+
+impl Model for GenericPowerOnOffServer {
+    const IDENTIFIER: ModelIdentifier = GENERIC_POWER_ONOFF_SERVER;
+    type Message<'m> = GenericPowerOnOffMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        _parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            GENERIC_ON_POWER_UP_GET => Ok(Some(GenericPowerOnOffMessage::Get)),
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl Model for GenericPowerOnOffSetupServer {
+    const IDENTIFIER: ModelIdentifier = GENERIC_POWER_ONOFF_SETUP_SERVER;
+    const SUPPORTS_PUBLICATION: bool = false;
+    type Message<'m> = GenericPowerOnOffMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            GENERIC_ON_POWER_UP_SET => Ok(Some(GenericPowerOnOffMessage::Set(OnPowerUp::parse(
+                parameters,
+            )?))),
+            GENERIC_ON_POWER_UP_SET_UNACKNOWLEDGE => Ok(Some(
+                GenericPowerOnOffMessage::SetUnacknowledged(OnPowerUp::parse(parameters)?),
+            )),
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl Model for GenericPowerOnOffClient {
+    const IDENTIFIER: ModelIdentifier = GENERIC_POWER_ONOFF_CLIENT;
+    type Message<'m> = GenericPowerOnOffMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            GENERIC_ON_POWER_UP_STATUS => Ok(Some(GenericPowerOnOffMessage::Status(
+                OnPowerUp::parse(parameters)?,
+            ))),
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+opcode!( GENERIC_ON_POWER_UP_GET 0x82, 0x11 );
+opcode!( GENERIC_ON_POWER_UP_STATUS 0x82, 0x12 );
+opcode!( GENERIC_ON_POWER_UP_SET 0x82, 0x13 );
+opcode!( GENERIC_ON_POWER_UP_SET_UNACKNOWLEDGE 0x82, 0x14 );
+
+/// Behaviour of the Generic OnOff state when the element powers up.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum OnPowerUp {
+    Off = 0x00,
+    Default = 0x01,
+    Restore = 0x02,
+}
+
+impl OnPowerUp {
+    /// Generic OnOff state to power up with, given the last known state.
+    pub fn on_off(&self, last_on_off: u8) -> u8 {
+        match self {
+            Self::Off => 0,
+            Self::Default => 1,
+            Self::Restore => last_on_off,
+        }
+    }
+
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 1 {
+            match parameters[0] {
+                0x00 => Ok(Self::Off),
+                0x01 => Ok(Self::Default),
+                0x02 => Ok(Self::Restore),
+                _ => Err(ParseError::InvalidValue),
+            }
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        xmit.push(*self as u8).map_err(|_| InsufficientBuffer)?;
+        Ok(())
+    }
+}
+
+impl Default for OnPowerUp {
+    fn default() -> Self {
+        Self::Off
+    }
+}
+
+/// Persists the OnPowerUp state of a Generic Power OnOff Server.
+///
+/// The state needs its own `Storage`, separate from the node configuration,
+/// such as a dedicated flash page.
+pub struct OnPowerUpStorage<S: Storage> {
+    storage: S,
+}
+
+impl<S: Storage> OnPowerUpStorage<S> {
+    pub fn new(storage: S) -> Self {
+        Self { storage }
+    }
+
+    /// The stored state, or the default if nothing valid was stored.
+    pub async fn load(&mut self) -> Result<OnPowerUp, ()> {
+        match self.storage.retrieve().await? {
+            Some(payload) => Ok(OnPowerUp::parse(&payload.padded()[0..1]).unwrap_or_default()),
+            None => Ok(OnPowerUp::default()),
+        }
+    }
+
+    pub async fn store(&mut self, on_power_up: OnPowerUp) -> Result<(), ()> {
+        let payload = Payload::from_slice(0, &[on_power_up as u8]).map_err(|_| ())?;
+        self.storage.store(&payload).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_set() {
+        match GenericPowerOnOffSetupServer::parse(GENERIC_ON_POWER_UP_SET, &[0x02]).unwrap() {
+            Some(GenericPowerOnOffMessage::Set(on_power_up)) => {
+                assert_eq!(OnPowerUp::Restore, on_power_up);
+                assert_eq!(0x07, on_power_up.on_off(0x07));
+            }
+            _ => panic!("expected set"),
+        }
+        assert!(GenericPowerOnOffSetupServer::parse(GENERIC_ON_POWER_UP_SET, &[0x03]).is_err());
+        assert!(GenericPowerOnOffSetupServer::parse(GENERIC_ON_POWER_UP_SET, &[]).is_err());
+    }
+
+    #[test]
+    fn test_status_round_trip() {
+        let mut xmit: Vec<u8, 1> = Vec::new();
+        GenericPowerOnOffMessage::Status(OnPowerUp::Default)
+            .emit_parameters(&mut xmit)
+            .unwrap();
+        assert_eq!(&[0x01], &xmit[..]);
+
+        match GenericPowerOnOffClient::parse(GENERIC_ON_POWER_UP_STATUS, &xmit).unwrap() {
+            Some(GenericPowerOnOffMessage::Status(on_power_up)) => {
+                assert_eq!(OnPowerUp::Default, on_power_up);
+                assert_eq!(1, on_power_up.on_off(0));
+            }
+            _ => panic!("expected status"),
+        }
+    }
+}
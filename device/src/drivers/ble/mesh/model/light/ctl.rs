@@ -0,0 +1,659 @@
+use crate::drivers::ble::mesh::model::generic::level::{emit_transition, parse_transition};
+use crate::drivers::ble::mesh::model::light::{emit_u16, Range, RangeStatus, RangeStatusCode};
+use crate::drivers::ble::mesh::model::{Message, Model, ModelIdentifier};
+use crate::drivers::ble::mesh::pdu::access::Opcode;
+use crate::drivers::ble::mesh::pdu::ParseError;
+use crate::drivers::ble::mesh::InsufficientBuffer;
+use crate::opcode;
+use heapless::Vec;
+
+#[derive(Clone, Debug)]
+pub struct LightCtlServer;
+
+#[derive(Clone, Debug)]
+pub struct LightCtlSetupServer;
+
+#[derive(Clone, Debug)]
+pub struct LightCtlClient;
+
+#[derive(Clone, Debug)]
+pub struct LightCtlTemperatureServer;
+
+pub const LIGHT_CTL_SERVER: ModelIdentifier = ModelIdentifier::SIG(0x1303);
+pub const LIGHT_CTL_SETUP_SERVER: ModelIdentifier = ModelIdentifier::SIG(0x1304);
+pub const LIGHT_CTL_CLIENT: ModelIdentifier = ModelIdentifier::SIG(0x1305);
+pub const LIGHT_CTL_TEMPERATURE_SERVER: ModelIdentifier = ModelIdentifier::SIG(0x1306);
+
+/// Lowest color temperature, in Kelvin.
+pub const TEMPERATURE_MIN: u16 = 0x0320;
+/// Highest color temperature, in Kelvin.
+pub const TEMPERATURE_MAX: u16 = 0x4E20;
+
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LightCtlMessage {
+    Get,
+    Set(Set),
+    SetUnacknowledged(Set),
+    Status(Status),
+    TemperatureGet,
+    TemperatureSet(TemperatureSet),
+    TemperatureSetUnacknowledged(TemperatureSet),
+    TemperatureStatus(TemperatureStatus),
+    TemperatureRangeGet,
+    TemperatureRangeSet(Range),
+    TemperatureRangeSetUnacknowledged(Range),
+    TemperatureRangeStatus(RangeStatus),
+    DefaultGet,
+    DefaultSet(Defaults),
+    DefaultSetUnacknowledged(Defaults),
+    DefaultStatus(Defaults),
+}
+
+impl Message for LightCtlMessage {
+    fn opcode(&self) -> Opcode {
+        match self {
+            Self::Get => LIGHT_CTL_GET,
+            Self::Set(_) => LIGHT_CTL_SET,
+            Self::SetUnacknowledged(_) => LIGHT_CTL_SET_UNACKNOWLEDGE,
+            Self::Status(_) => LIGHT_CTL_STATUS,
+            Self::TemperatureGet => LIGHT_CTL_TEMPERATURE_GET,
+            Self::TemperatureSet(_) => LIGHT_CTL_TEMPERATURE_SET,
+            Self::TemperatureSetUnacknowledged(_) => LIGHT_CTL_TEMPERATURE_SET_UNACKNOWLEDGE,
+            Self::TemperatureStatus(_) => LIGHT_CTL_TEMPERATURE_STATUS,
+            Self::TemperatureRangeGet => LIGHT_CTL_TEMPERATURE_RANGE_GET,
+            Self::TemperatureRangeSet(_) => LIGHT_CTL_TEMPERATURE_RANGE_SET,
+            Self::TemperatureRangeSetUnacknowledged(_) => {
+                LIGHT_CTL_TEMPERATURE_RANGE_SET_UNACKNOWLEDGE
+            }
+            Self::TemperatureRangeStatus(_) => LIGHT_CTL_TEMPERATURE_RANGE_STATUS,
+            Self::DefaultGet => LIGHT_CTL_DEFAULT_GET,
+            Self::DefaultSet(_) => LIGHT_CTL_DEFAULT_SET,
+            Self::DefaultSetUnacknowledged(_) => LIGHT_CTL_DEFAULT_SET_UNACKNOWLEDGE,
+            Self::DefaultStatus(_) => LIGHT_CTL_DEFAULT_STATUS,
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut heapless::Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        match self {
+            Self::Get | Self::TemperatureGet | Self::TemperatureRangeGet | Self::DefaultGet => {
+                Ok(())
+            }
+            Self::Set(inner) | Self::SetUnacknowledged(inner) => inner.emit_parameters(xmit),
+            Self::Status(inner) => inner.emit_parameters(xmit),
+            Self::TemperatureSet(inner) | Self::TemperatureSetUnacknowledged(inner) => {
+                inner.emit_parameters(xmit)
+            }
+            Self::TemperatureStatus(inner) => inner.emit_parameters(xmit),
+            Self::TemperatureRangeSet(inner) | Self::TemperatureRangeSetUnacknowledged(inner) => {
+                inner.emit_parameters(xmit)
+            }
+            Self::TemperatureRangeStatus(inner) => inner.emit_parameters(xmit),
+            Self::DefaultSet(inner)
+            | Self::DefaultSetUnacknowledged(inner)
+            | Self::DefaultStatus(inner) => inner.emit_parameters(xmit),
+        }
+    }
+}
+
+impl Model for LightCtlServer {
+    const IDENTIFIER: ModelIdentifier = LIGHT_CTL_SERVER;
+    type Message<'m> = LightCtlMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            LIGHT_CTL_GET => Ok(Some(LightCtlMessage::Get)),
+            LIGHT_CTL_SET => Ok(Some(LightCtlMessage::Set(Set::parse(parameters)?))),
+            LIGHT_CTL_SET_UNACKNOWLEDGE => Ok(Some(LightCtlMessage::SetUnacknowledged(
+                Set::parse(parameters)?,
+            ))),
+            LIGHT_CTL_TEMPERATURE_RANGE_GET => Ok(Some(LightCtlMessage::TemperatureRangeGet)),
+            LIGHT_CTL_DEFAULT_GET => Ok(Some(LightCtlMessage::DefaultGet)),
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl Model for LightCtlTemperatureServer {
+    const IDENTIFIER: ModelIdentifier = LIGHT_CTL_TEMPERATURE_SERVER;
+    type Message<'m> = LightCtlMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            LIGHT_CTL_TEMPERATURE_GET => Ok(Some(LightCtlMessage::TemperatureGet)),
+            LIGHT_CTL_TEMPERATURE_SET => Ok(Some(LightCtlMessage::TemperatureSet(
+                TemperatureSet::parse(parameters)?,
+            ))),
+            LIGHT_CTL_TEMPERATURE_SET_UNACKNOWLEDGE => Ok(Some(
+                LightCtlMessage::TemperatureSetUnacknowledged(TemperatureSet::parse(parameters)?),
+            )),
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl Model for LightCtlSetupServer {
+    const IDENTIFIER: ModelIdentifier = LIGHT_CTL_SETUP_SERVER;
+    const SUPPORTS_PUBLICATION: bool = false;
+    type Message<'m> = LightCtlMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            LIGHT_CTL_DEFAULT_SET => Ok(Some(LightCtlMessage::DefaultSet(Defaults::parse(
+                parameters,
+            )?))),
+            LIGHT_CTL_DEFAULT_SET_UNACKNOWLEDGE => Ok(Some(
+                LightCtlMessage::DefaultSetUnacknowledged(Defaults::parse(parameters)?),
+            )),
+            LIGHT_CTL_TEMPERATURE_RANGE_SET => Ok(Some(LightCtlMessage::TemperatureRangeSet(
+                Range::parse(parameters)?,
+            ))),
+            LIGHT_CTL_TEMPERATURE_RANGE_SET_UNACKNOWLEDGE => Ok(Some(
+                LightCtlMessage::TemperatureRangeSetUnacknowledged(Range::parse(parameters)?),
+            )),
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl Model for LightCtlClient {
+    const IDENTIFIER: ModelIdentifier = LIGHT_CTL_CLIENT;
+    type Message<'m> = LightCtlMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            LIGHT_CTL_STATUS => Ok(Some(LightCtlMessage::Status(Status::parse(parameters)?))),
+            LIGHT_CTL_TEMPERATURE_STATUS => Ok(Some(LightCtlMessage::TemperatureStatus(
+                TemperatureStatus::parse(parameters)?,
+            ))),
+            LIGHT_CTL_TEMPERATURE_RANGE_STATUS => Ok(Some(
+                LightCtlMessage::TemperatureRangeStatus(RangeStatus::parse(parameters)?),
+            )),
+            LIGHT_CTL_DEFAULT_STATUS => Ok(Some(LightCtlMessage::DefaultStatus(Defaults::parse(
+                parameters,
+            )?))),
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+opcode!( LIGHT_CTL_GET 0x82, 0x5D );
+opcode!( LIGHT_CTL_SET 0x82, 0x5E );
+opcode!( LIGHT_CTL_SET_UNACKNOWLEDGE 0x82, 0x5F );
+opcode!( LIGHT_CTL_STATUS 0x82, 0x60 );
+opcode!( LIGHT_CTL_TEMPERATURE_GET 0x82, 0x61 );
+opcode!( LIGHT_CTL_TEMPERATURE_RANGE_GET 0x82, 0x62 );
+opcode!( LIGHT_CTL_TEMPERATURE_RANGE_STATUS 0x82, 0x63 );
+opcode!( LIGHT_CTL_TEMPERATURE_SET 0x82, 0x64 );
+opcode!( LIGHT_CTL_TEMPERATURE_SET_UNACKNOWLEDGE 0x82, 0x65 );
+opcode!( LIGHT_CTL_TEMPERATURE_STATUS 0x82, 0x66 );
+opcode!( LIGHT_CTL_DEFAULT_GET 0x82, 0x67 );
+opcode!( LIGHT_CTL_DEFAULT_STATUS 0x82, 0x68 );
+opcode!( LIGHT_CTL_DEFAULT_SET 0x82, 0x69 );
+opcode!( LIGHT_CTL_DEFAULT_SET_UNACKNOWLEDGE 0x82, 0x6A );
+opcode!( LIGHT_CTL_TEMPERATURE_RANGE_SET 0x82, 0x6B );
+opcode!( LIGHT_CTL_TEMPERATURE_RANGE_SET_UNACKNOWLEDGE 0x82, 0x6C );
+
+fn parse_temperature(parameters: &[u8]) -> Result<u16, ParseError> {
+    let temperature = u16::from_le_bytes([parameters[0], parameters[1]]);
+    if temperature < TEMPERATURE_MIN || temperature > TEMPERATURE_MAX {
+        Err(ParseError::InvalidValue)
+    } else {
+        Ok(temperature)
+    }
+}
+
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Set {
+    pub lightness: u16,
+    pub temperature: u16,
+    pub delta_uv: i16,
+    pub tid: u8,
+    pub transition_time: Option<u8>,
+    pub delay: Option<u8>,
+}
+
+impl Set {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() >= 7 {
+            let lightness = u16::from_le_bytes([parameters[0], parameters[1]]);
+            let temperature = parse_temperature(&parameters[2..4])?;
+            let delta_uv = i16::from_le_bytes([parameters[4], parameters[5]]);
+            let tid = parameters[6];
+            let (transition_time, delay) = parse_transition(&parameters[7..])?;
+            Ok(Self {
+                lightness,
+                temperature,
+                delta_uv,
+                tid,
+                transition_time,
+                delay,
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        emit_u16(self.lightness, xmit)?;
+        emit_u16(self.temperature, xmit)?;
+        xmit.extend_from_slice(&self.delta_uv.to_le_bytes())
+            .map_err(|_| InsufficientBuffer)?;
+        xmit.push(self.tid).map_err(|_| InsufficientBuffer)?;
+        emit_transition(self.transition_time, self.delay, xmit)
+    }
+}
+
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Status {
+    pub present_lightness: u16,
+    pub present_temperature: u16,
+    /// Target lightness, target temperature and remaining time,
+    /// while a transition is in progress.
+    pub target: Option<(u16, u16, u8)>,
+}
+
+impl Status {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() < 4 {
+            return Err(ParseError::InvalidLength);
+        }
+        let present_lightness = u16::from_le_bytes([parameters[0], parameters[1]]);
+        let present_temperature = u16::from_le_bytes([parameters[2], parameters[3]]);
+        match parameters.len() {
+            4 => Ok(Self {
+                present_lightness,
+                present_temperature,
+                target: None,
+            }),
+            9 => Ok(Self {
+                present_lightness,
+                present_temperature,
+                target: Some((
+                    u16::from_le_bytes([parameters[4], parameters[5]]),
+                    u16::from_le_bytes([parameters[6], parameters[7]]),
+                    parameters[8],
+                )),
+            }),
+            _ => Err(ParseError::InvalidLength),
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        emit_u16(self.present_lightness, xmit)?;
+        emit_u16(self.present_temperature, xmit)?;
+        if let Some((target_lightness, target_temperature, remaining_time)) = self.target {
+            emit_u16(target_lightness, xmit)?;
+            emit_u16(target_temperature, xmit)?;
+            xmit.push(remaining_time).map_err(|_| InsufficientBuffer)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TemperatureSet {
+    pub temperature: u16,
+    pub delta_uv: i16,
+    pub tid: u8,
+    pub transition_time: Option<u8>,
+    pub delay: Option<u8>,
+}
+
+impl TemperatureSet {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() >= 5 {
+            let temperature = parse_temperature(&parameters[0..2])?;
+            let delta_uv = i16::from_le_bytes([parameters[2], parameters[3]]);
+            let tid = parameters[4];
+            let (transition_time, delay) = parse_transition(&parameters[5..])?;
+            Ok(Self {
+                temperature,
+                delta_uv,
+                tid,
+                transition_time,
+                delay,
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        emit_u16(self.temperature, xmit)?;
+        xmit.extend_from_slice(&self.delta_uv.to_le_bytes())
+            .map_err(|_| InsufficientBuffer)?;
+        xmit.push(self.tid).map_err(|_| InsufficientBuffer)?;
+        emit_transition(self.transition_time, self.delay, xmit)
+    }
+}
+
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TemperatureStatus {
+    pub present_temperature: u16,
+    pub present_delta_uv: i16,
+    /// Target temperature, target delta UV and remaining time,
+    /// while a transition is in progress.
+    pub target: Option<(u16, i16, u8)>,
+}
+
+impl TemperatureStatus {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() < 4 {
+            return Err(ParseError::InvalidLength);
+        }
+        let present_temperature = u16::from_le_bytes([parameters[0], parameters[1]]);
+        let present_delta_uv = i16::from_le_bytes([parameters[2], parameters[3]]);
+        match parameters.len() {
+            4 => Ok(Self {
+                present_temperature,
+                present_delta_uv,
+                target: None,
+            }),
+            9 => Ok(Self {
+                present_temperature,
+                present_delta_uv,
+                target: Some((
+                    u16::from_le_bytes([parameters[4], parameters[5]]),
+                    i16::from_le_bytes([parameters[6], parameters[7]]),
+                    parameters[8],
+                )),
+            }),
+            _ => Err(ParseError::InvalidLength),
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        emit_u16(self.present_temperature, xmit)?;
+        xmit.extend_from_slice(&self.present_delta_uv.to_le_bytes())
+            .map_err(|_| InsufficientBuffer)?;
+        if let Some((target_temperature, target_delta_uv, remaining_time)) = self.target {
+            emit_u16(target_temperature, xmit)?;
+            xmit.extend_from_slice(&target_delta_uv.to_le_bytes())
+                .map_err(|_| InsufficientBuffer)?;
+            xmit.push(remaining_time).map_err(|_| InsufficientBuffer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Default lightness, temperature and delta UV to power up with.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Defaults {
+    pub lightness: u16,
+    pub temperature: u16,
+    pub delta_uv: i16,
+}
+
+impl Defaults {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 6 {
+            Ok(Self {
+                lightness: u16::from_le_bytes([parameters[0], parameters[1]]),
+                temperature: parse_temperature(&parameters[2..4])?,
+                delta_uv: i16::from_le_bytes([parameters[4], parameters[5]]),
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        emit_u16(self.lightness, xmit)?;
+        emit_u16(self.temperature, xmit)?;
+        xmit.extend_from_slice(&self.delta_uv.to_le_bytes())
+            .map_err(|_| InsufficientBuffer)?;
+        Ok(())
+    }
+}
+
+/// Temperature states of a Light CTL Server.
+///
+/// The CTL lightness is the Light Lightness Actual state of the same element,
+/// kept in a `LightLightnessState`. Generic Level of the temperature element
+/// is bound to the temperature, across the temperature range.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LightCtlState {
+    temperature: u16,
+    delta_uv: i16,
+    default_temperature: u16,
+    default_delta_uv: i16,
+    range: Range,
+}
+
+impl Default for LightCtlState {
+    fn default() -> Self {
+        Self {
+            temperature: TEMPERATURE_MIN,
+            delta_uv: 0,
+            default_temperature: TEMPERATURE_MIN,
+            default_delta_uv: 0,
+            range: Range::new(TEMPERATURE_MIN, TEMPERATURE_MAX),
+        }
+    }
+}
+
+impl LightCtlState {
+    pub fn temperature(&self) -> u16 {
+        self.temperature
+    }
+
+    /// Set the temperature, clamped to the temperature range.
+    pub fn set_temperature(&mut self, temperature: u16) {
+        self.temperature = self.range.clamp(temperature);
+    }
+
+    pub fn delta_uv(&self) -> i16 {
+        self.delta_uv
+    }
+
+    pub fn set_delta_uv(&mut self, delta_uv: i16) {
+        self.delta_uv = delta_uv;
+    }
+
+    pub fn default_temperature(&self) -> u16 {
+        self.default_temperature
+    }
+
+    pub fn default_delta_uv(&self) -> i16 {
+        self.default_delta_uv
+    }
+
+    pub fn set_defaults(&mut self, temperature: u16, delta_uv: i16) {
+        self.default_temperature = temperature;
+        self.default_delta_uv = delta_uv;
+    }
+
+    pub fn range(&self) -> Range {
+        self.range
+    }
+
+    /// Set the temperature range, rejecting bounds outside of the supported temperatures.
+    pub fn set_range(&mut self, range: Range) -> RangeStatusCode {
+        if range.min < TEMPERATURE_MIN {
+            RangeStatusCode::CannotSetRangeMin
+        } else if range.max > TEMPERATURE_MAX {
+            RangeStatusCode::CannotSetRangeMax
+        } else {
+            self.range = range;
+            self.temperature = self.range.clamp(self.temperature);
+            RangeStatusCode::Success
+        }
+    }
+
+    /// Generic Level bound to the temperature.
+    pub fn level(&self) -> i16 {
+        let span = (self.range.max - self.range.min) as i32;
+        if span == 0 {
+            return i16::MIN;
+        }
+        let offset = (self.temperature - self.range.min) as i32;
+        (offset * 65535 / span - 32768) as i16
+    }
+
+    pub fn set_level(&mut self, level: i16) {
+        let span = (self.range.max - self.range.min) as i32;
+        let offset = (level as i32 + 32768) * span / 65535;
+        self.temperature = self.range.min + offset as u16;
+    }
+
+    pub fn temperature_status(&self) -> TemperatureStatus {
+        TemperatureStatus {
+            present_temperature: self.temperature,
+            present_delta_uv: self.delta_uv,
+            target: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_set() {
+        // lightness 0x8000, 6500K, delta UV -1, tid 0x10, no transition
+        let parameters = [0x00, 0x80, 0x64, 0x19, 0xFF, 0xFF, 0x10];
+        match LightCtlServer::parse(LIGHT_CTL_SET, &parameters).unwrap() {
+            Some(LightCtlMessage::Set(set)) => {
+                assert_eq!(0x8000, set.lightness);
+                assert_eq!(6500, set.temperature);
+                assert_eq!(-1, set.delta_uv);
+                assert_eq!(0x10, set.tid);
+                assert_eq!(None, set.transition_time);
+
+                let mut xmit: Vec<u8, 16> = Vec::new();
+                LightCtlMessage::SetUnacknowledged(set)
+                    .emit_parameters(&mut xmit)
+                    .unwrap();
+                assert_eq!(&parameters, &xmit[..]);
+            }
+            _ => panic!("expected set"),
+        }
+    }
+
+    #[test]
+    fn test_parse_temperature_set_rejects_out_of_range() {
+        // 700K is below the lowest temperature
+        assert!(LightCtlTemperatureServer::parse(
+            LIGHT_CTL_TEMPERATURE_SET,
+            &[0xBC, 0x02, 0x00, 0x00, 0x01]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_status_round_trip() {
+        let mut xmit: Vec<u8, 16> = Vec::new();
+        LightCtlMessage::TemperatureStatus(TemperatureStatus {
+            present_temperature: 2700,
+            present_delta_uv: 0x0100,
+            target: Some((4000, -0x0100, 0x0A)),
+        })
+        .emit_parameters(&mut xmit)
+        .unwrap();
+        assert_eq!(
+            &[0x8C, 0x0A, 0x00, 0x01, 0xA0, 0x0F, 0x00, 0xFF, 0x0A],
+            &xmit[..]
+        );
+
+        match LightCtlClient::parse(LIGHT_CTL_TEMPERATURE_STATUS, &xmit).unwrap() {
+            Some(LightCtlMessage::TemperatureStatus(status)) => {
+                assert_eq!(2700, status.present_temperature);
+                assert_eq!(0x0100, status.present_delta_uv);
+                assert_eq!(Some((4000, -0x0100, 0x0A)), status.target);
+            }
+            _ => panic!("expected temperature status"),
+        }
+    }
+
+    #[test]
+    fn test_default_round_trip() {
+        let defaults = Defaults {
+            lightness: 0xFFFF,
+            temperature: 3000,
+            delta_uv: 0,
+        };
+        let mut xmit: Vec<u8, 8> = Vec::new();
+        LightCtlMessage::DefaultSet(defaults)
+            .emit_parameters(&mut xmit)
+            .unwrap();
+        match LightCtlSetupServer::parse(LIGHT_CTL_DEFAULT_SET, &xmit).unwrap() {
+            Some(LightCtlMessage::DefaultSet(parsed)) => assert_eq!(defaults, parsed),
+            _ => panic!("expected default set"),
+        }
+    }
+
+    #[test]
+    fn test_state_bindings() {
+        let mut state = LightCtlState::default();
+        assert_eq!(
+            RangeStatusCode::CannotSetRangeMin,
+            state.set_range(Range::new(0x0100, 0x1000))
+        );
+        assert_eq!(
+            RangeStatusCode::Success,
+            state.set_range(Range::new(1000, 11000))
+        );
+        assert_eq!(1000, state.temperature());
+
+        state.set_level(i16::MIN);
+        assert_eq!(1000, state.temperature());
+        assert_eq!(i16::MIN, state.level());
+
+        state.set_level(i16::MAX);
+        assert_eq!(11000, state.temperature());
+        assert_eq!(i16::MAX, state.level());
+
+        state.set_temperature(20000);
+        assert_eq!(11000, state.temperature());
+    }
+}
@@ -0,0 +1,116 @@
+use crate::drivers::ble::mesh::pdu::ParseError;
+use crate::drivers::ble::mesh::InsufficientBuffer;
+use heapless::Vec;
+
+pub mod ctl;
+pub mod lightness;
+#[cfg(feature = "neopixel")]
+pub mod neopixel;
+
+/// Status code of the range status messages.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RangeStatusCode {
+    Success = 0x00,
+    CannotSetRangeMin = 0x01,
+    CannotSetRangeMax = 0x02,
+}
+
+impl RangeStatusCode {
+    fn parse(value: u8) -> Result<Self, ParseError> {
+        match value {
+            0x00 => Ok(Self::Success),
+            0x01 => Ok(Self::CannotSetRangeMin),
+            0x02 => Ok(Self::CannotSetRangeMax),
+            _ => Err(ParseError::InvalidValue),
+        }
+    }
+}
+
+/// Inclusive range of a lightness or temperature state.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Range {
+    pub min: u16,
+    pub max: u16,
+}
+
+impl Range {
+    pub fn new(min: u16, max: u16) -> Self {
+        Self { min, max }
+    }
+
+    pub fn clamp(&self, value: u16) -> u16 {
+        value.max(self.min).min(self.max)
+    }
+
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 4 {
+            let min = u16::from_le_bytes([parameters[0], parameters[1]]);
+            let max = u16::from_le_bytes([parameters[2], parameters[3]]);
+            if min > max {
+                Err(ParseError::InvalidValue)
+            } else {
+                Ok(Self { min, max })
+            }
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        xmit.extend_from_slice(&self.min.to_le_bytes())
+            .map_err(|_| InsufficientBuffer)?;
+        xmit.extend_from_slice(&self.max.to_le_bytes())
+            .map_err(|_| InsufficientBuffer)?;
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RangeStatus {
+    pub status: RangeStatusCode,
+    pub range: Range,
+}
+
+impl RangeStatus {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 5 {
+            Ok(Self {
+                status: RangeStatusCode::parse(parameters[0])?,
+                range: Range {
+                    min: u16::from_le_bytes([parameters[1], parameters[2]]),
+                    max: u16::from_le_bytes([parameters[3], parameters[4]]),
+                },
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        xmit.push(self.status as u8)
+            .map_err(|_| InsufficientBuffer)?;
+        self.range.emit_parameters(xmit)
+    }
+}
+
+fn parse_u16(parameters: &[u8]) -> Result<u16, ParseError> {
+    if parameters.len() == 2 {
+        Ok(u16::from_le_bytes([parameters[0], parameters[1]]))
+    } else {
+        Err(ParseError::InvalidLength)
+    }
+}
+
+fn emit_u16<const N: usize>(value: u16, xmit: &mut Vec<u8, N>) -> Result<(), InsufficientBuffer> {
+    xmit.extend_from_slice(&value.to_le_bytes())
+        .map_err(|_| InsufficientBuffer)
+}
@@ -0,0 +1,148 @@
+use crate::drivers::ble::mesh::model::light::ctl::LightCtlState;
+use crate::drivers::ble::mesh::model::light::lightness::LightLightnessState;
+use crate::drivers::led::neopixel::filter::{ComposedFilter, Filter};
+use crate::drivers::led::neopixel::rgb::Rgb8;
+use crate::drivers::led::neopixel::{InvalidChannel, Pixel};
+
+/// Scales every channel by a Light Lightness Actual value.
+pub struct Lightness(pub u16);
+
+impl<P: Pixel<C>, const C: usize> Filter<P, C> for Lightness {
+    fn apply(&self, pixel: &P) -> Result<P, InvalidChannel> {
+        let mut filtered = pixel.clone();
+        for i in 0..C {
+            filtered.set(i, (pixel.get(i)? as u32 * self.0 as u32 / 65535) as u8)?;
+        }
+        Ok(filtered)
+    }
+}
+
+// White point of a black body every 1000K, from 1000K up to 20000K.
+const WHITE_POINTS: [(u8, u8, u8); 20] = [
+    (255, 56, 0),
+    (255, 137, 14),
+    (255, 180, 107),
+    (255, 209, 163),
+    (255, 228, 206),
+    (255, 243, 239),
+    (245, 243, 255),
+    (227, 233, 255),
+    (214, 225, 255),
+    (204, 219, 255),
+    (196, 215, 255),
+    (191, 211, 255),
+    (186, 208, 255),
+    (182, 205, 255),
+    (179, 203, 255),
+    (176, 201, 255),
+    (174, 199, 255),
+    (172, 197, 255),
+    (170, 196, 255),
+    (168, 195, 255),
+];
+
+/// Tints a pixel towards the white point of a color temperature, in Kelvin.
+pub struct ColorTemperature(pub u16);
+
+impl ColorTemperature {
+    fn white_point(&self) -> Rgb8 {
+        let kelvin = self.0.max(1000).min(20000) as u32;
+        let index = ((kelvin - 1000) / 1000) as usize;
+        let low = WHITE_POINTS[index];
+        let high = WHITE_POINTS[(index + 1).min(WHITE_POINTS.len() - 1)];
+        let fraction = (kelvin - 1000) % 1000;
+        let mix = |low: u8, high: u8| {
+            ((low as u32 * (1000 - fraction) + high as u32 * fraction) / 1000) as u8
+        };
+        Rgb8::new(mix(low.0, high.0), mix(low.1, high.1), mix(low.2, high.2))
+    }
+}
+
+impl Filter<Rgb8, 3> for ColorTemperature {
+    fn apply(&self, pixel: &Rgb8) -> Result<Rgb8, InvalidChannel> {
+        let white_point = self.white_point();
+        let mut filtered = pixel.clone();
+        for i in 0..3 {
+            filtered.set(
+                i,
+                (pixel.get(i)? as u16 * (white_point.get(i)? as u16 + 1) / 256) as u8,
+            )?;
+        }
+        Ok(filtered)
+    }
+}
+
+/// Filter rendering the Light Lightness Actual state.
+pub fn lightness_filter(lightness: &LightLightnessState) -> Lightness {
+    Lightness(lightness.actual())
+}
+
+/// Filter rendering the Light CTL states: the temperature tint, scaled by lightness.
+///
+/// Apply it to `WHITE`, and add `Gamma` to correct the output for the LEDs.
+pub fn ctl_filter(
+    lightness: &LightLightnessState,
+    ctl: &LightCtlState,
+) -> ComposedFilter<Rgb8, ColorTemperature, Lightness, 3> {
+    ColorTemperature(ctl.temperature()).and(Lightness(lightness.actual()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drivers::led::neopixel::rgb::{BLACK, WHITE};
+
+    #[test]
+    fn test_lightness() {
+        let pixel = Rgb8::new(255, 128, 2);
+        assert_eq!(BLACK, Lightness(0).apply(&pixel).ok().unwrap());
+        assert_eq!(pixel, Lightness(65535).apply(&pixel).ok().unwrap());
+        assert_eq!(
+            Rgb8::new(127, 64, 1),
+            Lightness(32768).apply(&pixel).ok().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_white_point() {
+        assert_eq!(Rgb8::new(255, 56, 0), ColorTemperature(1000).white_point());
+        assert_eq!(Rgb8::new(255, 96, 7), ColorTemperature(1500).white_point());
+        assert_eq!(
+            Rgb8::new(250, 243, 247),
+            ColorTemperature(6500).white_point()
+        );
+        assert_eq!(
+            Rgb8::new(169, 195, 255),
+            ColorTemperature(19500).white_point()
+        );
+        assert_eq!(
+            Rgb8::new(168, 195, 255),
+            ColorTemperature(20000).white_point()
+        );
+
+        // clamped to the table
+        assert_eq!(Rgb8::new(255, 56, 0), ColorTemperature(800).white_point());
+        assert_eq!(
+            Rgb8::new(168, 195, 255),
+            ColorTemperature(40000).white_point()
+        );
+    }
+
+    #[test]
+    fn test_ctl_filter() {
+        let mut lightness = LightLightnessState::default();
+        lightness.set_actual(65535);
+        let mut ctl = LightCtlState::default();
+        ctl.set_temperature(6500);
+        assert_eq!(
+            Rgb8::new(250, 243, 247),
+            ctl_filter(&lightness, &ctl).apply(&WHITE).ok().unwrap()
+        );
+
+        lightness.set_actual(32768);
+        assert_eq!(
+            Rgb8::new(125, 121, 123),
+            ctl_filter(&lightness, &ctl).apply(&WHITE).ok().unwrap()
+        );
+    }
+}
@@ -4,12 +4,32 @@ use crate::drivers::ble::mesh::model::foundation::configuration::{
     CONFIGURATION_CLIENT, CONFIGURATION_SERVER,
 };
 #[allow(unused_imports)]
+use crate::drivers::ble::mesh::model::foundation::health::{HEALTH_CLIENT, HEALTH_SERVER};
+#[allow(unused_imports)]
 use crate::drivers::ble::mesh::model::{
     generic::{
         battery::{GENERIC_BATTERY_CLIENT, GENERIC_BATTERY_SERVER},
+        default_transition_time::{
+            GENERIC_DEFAULT_TRANSITION_TIME_CLIENT, GENERIC_DEFAULT_TRANSITION_TIME_SERVER,
+        },
+        level::{GENERIC_LEVEL_CLIENT, GENERIC_LEVEL_SERVER},
         onoff::{GENERIC_ONOFF_CLIENT, GENERIC_ONOFF_SERVER},
+        power_on_off::{
+            GENERIC_POWER_ONOFF_CLIENT, GENERIC_POWER_ONOFF_SERVER,
+            GENERIC_POWER_ONOFF_SETUP_SERVER,
+        },
+    },
+    light::{
+        ctl::{
+            LIGHT_CTL_CLIENT, LIGHT_CTL_SERVER, LIGHT_CTL_SETUP_SERVER,
+            LIGHT_CTL_TEMPERATURE_SERVER,
+        },
+        lightness::{LIGHT_LIGHTNESS_CLIENT, LIGHT_LIGHTNESS_SERVER, LIGHT_LIGHTNESS_SETUP_SERVER},
     },
+    scene::{SCENE_CLIENT, SCENE_SERVER, SCENE_SETUP_SERVER},
+    scheduler::{SCHEDULER_CLIENT, SCHEDULER_SERVER, SCHEDULER_SETUP_SERVER},
     sensor::{SENSOR_CLIENT, SENSOR_SERVER, SENSOR_SETUP_SERVER},
+    time::{TIME_CLIENT, TIME_SERVER, TIME_SETUP_SERVER},
 };
 use crate::drivers::ble::mesh::pdu::access::Opcode;
 use crate::drivers::ble::mesh::pdu::ParseError;
@@ -20,9 +40,13 @@ use serde::{Deserialize, Serialize};
 pub mod firmware;
 pub mod foundation;
 pub mod generic;
+pub mod light;
+pub mod scene;
+pub mod scheduler;
 pub mod sensor;
+pub mod time;
 
-#[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Debug)]
 pub enum ModelIdentifier {
     SIG(u16),
     Vendor(CompanyIdentifier, u16),
@@ -38,12 +62,39 @@ impl defmt::Format for ModelIdentifier {
             CONFIGURATION_CLIENT => {
                 defmt::write!(fmt, "Configuration Client (0x0001)");
             }
+            HEALTH_SERVER => {
+                defmt::write!(fmt, "Health Server (0x0002)");
+            }
+            HEALTH_CLIENT => {
+                defmt::write!(fmt, "Health Client (0x0003)");
+            }
             GENERIC_ONOFF_SERVER => {
                 defmt::write!(fmt, "Generic OnOff Server (0x1000)");
             }
             GENERIC_ONOFF_CLIENT => {
                 defmt::write!(fmt, "Generic OnOff Client (0x1001)");
             }
+            GENERIC_LEVEL_SERVER => {
+                defmt::write!(fmt, "Generic Level Server (0x1002)");
+            }
+            GENERIC_LEVEL_CLIENT => {
+                defmt::write!(fmt, "Generic Level Client (0x1003)");
+            }
+            GENERIC_DEFAULT_TRANSITION_TIME_SERVER => {
+                defmt::write!(fmt, "Generic Default Transition Time Server (0x1004)");
+            }
+            GENERIC_DEFAULT_TRANSITION_TIME_CLIENT => {
+                defmt::write!(fmt, "Generic Default Transition Time Client (0x1005)");
+            }
+            GENERIC_POWER_ONOFF_SERVER => {
+                defmt::write!(fmt, "Generic Power OnOff Server (0x1006)");
+            }
+            GENERIC_POWER_ONOFF_SETUP_SERVER => {
+                defmt::write!(fmt, "Generic Power OnOff Setup Server (0x1007)");
+            }
+            GENERIC_POWER_ONOFF_CLIENT => {
+                defmt::write!(fmt, "Generic Power OnOff Client (0x1008)");
+            }
             GENERIC_BATTERY_SERVER => {
                 defmt::write!(fmt, "Generic Battery Server (0x100C)");
             }
@@ -59,6 +110,54 @@ impl defmt::Format for ModelIdentifier {
             GENERIC_BATTERY_CLIENT => {
                 defmt::write!(fmt, "Generic Battery Client (0x100D)");
             }
+            TIME_SERVER => {
+                defmt::write!(fmt, "Time Server (0x1200)");
+            }
+            TIME_SETUP_SERVER => {
+                defmt::write!(fmt, "Time Setup Server (0x1201)");
+            }
+            TIME_CLIENT => {
+                defmt::write!(fmt, "Time Client (0x1202)");
+            }
+            SCENE_SERVER => {
+                defmt::write!(fmt, "Scene Server (0x1203)");
+            }
+            SCENE_SETUP_SERVER => {
+                defmt::write!(fmt, "Scene Setup Server (0x1204)");
+            }
+            SCENE_CLIENT => {
+                defmt::write!(fmt, "Scene Client (0x1205)");
+            }
+            SCHEDULER_SERVER => {
+                defmt::write!(fmt, "Scheduler Server (0x1206)");
+            }
+            SCHEDULER_SETUP_SERVER => {
+                defmt::write!(fmt, "Scheduler Setup Server (0x1207)");
+            }
+            SCHEDULER_CLIENT => {
+                defmt::write!(fmt, "Scheduler Client (0x1208)");
+            }
+            LIGHT_LIGHTNESS_SERVER => {
+                defmt::write!(fmt, "Light Lightness Server (0x1300)");
+            }
+            LIGHT_LIGHTNESS_SETUP_SERVER => {
+                defmt::write!(fmt, "Light Lightness Setup Server (0x1301)");
+            }
+            LIGHT_LIGHTNESS_CLIENT => {
+                defmt::write!(fmt, "Light Lightness Client (0x1302)");
+            }
+            LIGHT_CTL_SERVER => {
+                defmt::write!(fmt, "Light CTL Server (0x1303)");
+            }
+            LIGHT_CTL_SETUP_SERVER => {
+                defmt::write!(fmt, "Light CTL Setup Server (0x1304)");
+            }
+            LIGHT_CTL_CLIENT => {
+                defmt::write!(fmt, "Light CTL Client (0x1305)");
+            }
+            LIGHT_CTL_TEMPERATURE_SERVER => {
+                defmt::write!(fmt, "Light CTL Temperature Server (0x1306)");
+            }
             ModelIdentifier::SIG(id) => match id {
                 _ => {
                     defmt::write!(fmt, "SIG(0x{=u16:04x})", id);
@@ -142,7 +241,7 @@ pub trait Model {
     ) -> Result<Option<Self::Message<'m>>, ParseError>;
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Status {
     Success = 0x00,
@@ -164,3 +263,29 @@ pub enum Status {
     UnspecifiedError = 0x10,
     InvalidBinding = 0x11,
 }
+
+impl Status {
+    pub fn parse(status: u8) -> Result<Self, ParseError> {
+        match status {
+            0x00 => Ok(Self::Success),
+            0x01 => Ok(Self::InvalidAddress),
+            0x02 => Ok(Self::InvalidModel),
+            0x03 => Ok(Self::InvalidAppKeyIndex),
+            0x04 => Ok(Self::InvalidNetKeyIndex),
+            0x05 => Ok(Self::InsufficientResources),
+            0x06 => Ok(Self::KeyIndexAlreadyStored),
+            0x07 => Ok(Self::InvalidPublishParameters),
+            0x08 => Ok(Self::NotASubscribeModel),
+            0x09 => Ok(Self::StorageFailure),
+            0x0A => Ok(Self::FeatureNotSupported),
+            0x0B => Ok(Self::CannotUpdate),
+            0x0C => Ok(Self::CannotRemove),
+            0x0D => Ok(Self::CannotBind),
+            0x0E => Ok(Self::TemporarilyUnableToChangeState),
+            0x0F => Ok(Self::CannotSet),
+            0x10 => Ok(Self::UnspecifiedError),
+            0x11 => Ok(Self::InvalidBinding),
+            _ => Err(ParseError::InvalidValue),
+        }
+    }
+}
@@ -0,0 +1,555 @@
+use crate::drivers::ble::mesh::model::generic::level::{emit_transition, parse_transition};
+use crate::drivers::ble::mesh::model::{Message, Model, ModelIdentifier};
+use crate::drivers::ble::mesh::pdu::access::Opcode;
+use crate::drivers::ble::mesh::pdu::ParseError;
+use crate::drivers::ble::mesh::storage::{Payload, Storage};
+use crate::drivers::ble::mesh::InsufficientBuffer;
+use crate::opcode;
+use heapless::Vec;
+
+#[derive(Clone, Debug)]
+pub struct SceneServer;
+
+#[derive(Clone, Debug)]
+pub struct SceneSetupServer;
+
+#[derive(Clone, Debug)]
+pub struct SceneClient;
+
+pub const SCENE_SERVER: ModelIdentifier = ModelIdentifier::SIG(0x1203);
+pub const SCENE_SETUP_SERVER: ModelIdentifier = ModelIdentifier::SIG(0x1204);
+pub const SCENE_CLIENT: ModelIdentifier = ModelIdentifier::SIG(0x1205);
+
+/// Maximum number of scenes in a scene register.
+pub const MAX_SCENES: usize = 16;
+
+/// Maximum size of the model states stored for a scene.
+pub const MAX_SCENE_STATE: usize = 16;
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SceneMessage {
+    Get,
+    Recall(Recall),
+    RecallUnacknowledged(Recall),
+    Status(Status),
+    RegisterGet,
+    RegisterStatus(RegisterStatus),
+    Store(u16),
+    StoreUnacknowledged(u16),
+    Delete(u16),
+    DeleteUnacknowledged(u16),
+}
+
+impl Message for SceneMessage {
+    fn opcode(&self) -> Opcode {
+        match self {
+            Self::Get => SCENE_GET,
+            Self::Recall(_) => SCENE_RECALL,
+            Self::RecallUnacknowledged(_) => SCENE_RECALL_UNACKNOWLEDGED,
+            Self::Status(_) => SCENE_STATUS,
+            Self::RegisterGet => SCENE_REGISTER_GET,
+            Self::RegisterStatus(_) => SCENE_REGISTER_STATUS,
+            Self::Store(_) => SCENE_STORE,
+            Self::StoreUnacknowledged(_) => SCENE_STORE_UNACKNOWLEDGED,
+            Self::Delete(_) => SCENE_DELETE,
+            Self::DeleteUnacknowledged(_) => SCENE_DELETE_UNACKNOWLEDGED,
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut heapless::Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        match self {
+            Self::Get | Self::RegisterGet => Ok(()),
+            Self::Recall(inner) => inner.emit_parameters(xmit),
+            Self::RecallUnacknowledged(inner) => inner.emit_parameters(xmit),
+            Self::Status(inner) => inner.emit_parameters(xmit),
+            Self::RegisterStatus(inner) => inner.emit_parameters(xmit),
+            Self::Store(scene_number)
+            | Self::StoreUnacknowledged(scene_number)
+            | Self::Delete(scene_number)
+            | Self::DeleteUnacknowledged(scene_number) => xmit
+                .extend_from_slice(&scene_number.to_le_bytes())
+                .map_err(|_| InsufficientBuffer),
+        }
+    }
+}
+
+impl Model for SceneServer {
+    const IDENTIFIER: ModelIdentifier = SCENE_SERVER;
+    type Message<'m> = SceneMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            SCENE_GET => Ok(Some(SceneMessage::Get)),
+            SCENE_RECALL => Ok(Some(SceneMessage::Recall(Recall::parse(parameters)?))),
+            SCENE_RECALL_UNACKNOWLEDGED => Ok(Some(SceneMessage::RecallUnacknowledged(
+                Recall::parse(parameters)?,
+            ))),
+            SCENE_REGISTER_GET => Ok(Some(SceneMessage::RegisterGet)),
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl Model for SceneSetupServer {
+    const IDENTIFIER: ModelIdentifier = SCENE_SETUP_SERVER;
+    const SUPPORTS_PUBLICATION: bool = false;
+    type Message<'m> = SceneMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            SCENE_STORE => Ok(Some(SceneMessage::Store(parse_scene_number(parameters)?))),
+            SCENE_STORE_UNACKNOWLEDGED => Ok(Some(SceneMessage::StoreUnacknowledged(
+                parse_scene_number(parameters)?,
+            ))),
+            SCENE_DELETE => Ok(Some(SceneMessage::Delete(parse_scene_number(parameters)?))),
+            SCENE_DELETE_UNACKNOWLEDGED => Ok(Some(SceneMessage::DeleteUnacknowledged(
+                parse_scene_number(parameters)?,
+            ))),
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl Model for SceneClient {
+    const IDENTIFIER: ModelIdentifier = SCENE_CLIENT;
+    type Message<'m> = SceneMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            SCENE_STATUS => Ok(Some(SceneMessage::Status(Status::parse(parameters)?))),
+            SCENE_REGISTER_STATUS => Ok(Some(SceneMessage::RegisterStatus(RegisterStatus::parse(
+                parameters,
+            )?))),
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+opcode!( SCENE_GET 0x82, 0x41 );
+opcode!( SCENE_RECALL 0x82, 0x42 );
+opcode!( SCENE_RECALL_UNACKNOWLEDGED 0x82, 0x43 );
+opcode!( SCENE_STATUS 0x5E );
+opcode!( SCENE_REGISTER_GET 0x82, 0x44 );
+opcode!( SCENE_REGISTER_STATUS 0x82, 0x45 );
+opcode!( SCENE_STORE 0x82, 0x46 );
+opcode!( SCENE_STORE_UNACKNOWLEDGED 0x82, 0x47 );
+opcode!( SCENE_DELETE 0x82, 0x9E );
+opcode!( SCENE_DELETE_UNACKNOWLEDGED 0x82, 0x9F );
+
+fn parse_scene_number(parameters: &[u8]) -> Result<u16, ParseError> {
+    if parameters.len() == 2 {
+        match u16::from_le_bytes([parameters[0], parameters[1]]) {
+            0 => Err(ParseError::InvalidValue),
+            scene_number => Ok(scene_number),
+        }
+    } else {
+        Err(ParseError::InvalidLength)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SceneStatusCode {
+    Success = 0x00,
+    SceneRegisterFull = 0x01,
+    SceneNotFound = 0x02,
+}
+
+impl SceneStatusCode {
+    fn parse(value: u8) -> Result<Self, ParseError> {
+        match value {
+            0x00 => Ok(Self::Success),
+            0x01 => Ok(Self::SceneRegisterFull),
+            0x02 => Ok(Self::SceneNotFound),
+            _ => Err(ParseError::InvalidValue),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Recall {
+    pub scene_number: u16,
+    pub tid: u8,
+    pub transition_time: Option<u8>,
+    pub delay: Option<u8>,
+}
+
+impl Recall {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() >= 3 {
+            let scene_number = parse_scene_number(&parameters[0..2])?;
+            let (transition_time, delay) = parse_transition(&parameters[3..])?;
+            Ok(Self {
+                scene_number,
+                tid: parameters[2],
+                transition_time,
+                delay,
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        xmit.extend_from_slice(&self.scene_number.to_le_bytes())
+            .map_err(|_| InsufficientBuffer)?;
+        xmit.push(self.tid).map_err(|_| InsufficientBuffer)?;
+        emit_transition(self.transition_time, self.delay, xmit)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Status {
+    pub status: SceneStatusCode,
+    pub current_scene: u16,
+    /// Target scene and remaining time, while a transition is in progress.
+    pub target: Option<(u16, u8)>,
+}
+
+impl Status {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        let target = match parameters.len() {
+            3 => None,
+            6 => Some((
+                u16::from_le_bytes([parameters[3], parameters[4]]),
+                parameters[5],
+            )),
+            _ => return Err(ParseError::InvalidLength),
+        };
+        Ok(Self {
+            status: SceneStatusCode::parse(parameters[0])?,
+            current_scene: u16::from_le_bytes([parameters[1], parameters[2]]),
+            target,
+        })
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        xmit.push(self.status as u8)
+            .map_err(|_| InsufficientBuffer)?;
+        xmit.extend_from_slice(&self.current_scene.to_le_bytes())
+            .map_err(|_| InsufficientBuffer)?;
+        if let Some((target_scene, remaining_time)) = self.target {
+            xmit.extend_from_slice(&target_scene.to_le_bytes())
+                .map_err(|_| InsufficientBuffer)?;
+            xmit.push(remaining_time).map_err(|_| InsufficientBuffer)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RegisterStatus {
+    pub status: SceneStatusCode,
+    pub current_scene: u16,
+    pub scenes: Vec<u16, MAX_SCENES>,
+}
+
+impl RegisterStatus {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() >= 3 && parameters.len() % 2 == 1 {
+            let mut scenes = Vec::new();
+            for scene in parameters[3..].chunks_exact(2) {
+                scenes
+                    .push(u16::from_le_bytes([scene[0], scene[1]]))
+                    .map_err(|_| ParseError::InsufficientBuffer)?;
+            }
+            Ok(Self {
+                status: SceneStatusCode::parse(parameters[0])?,
+                current_scene: u16::from_le_bytes([parameters[1], parameters[2]]),
+                scenes,
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        xmit.push(self.status as u8)
+            .map_err(|_| InsufficientBuffer)?;
+        xmit.extend_from_slice(&self.current_scene.to_le_bytes())
+            .map_err(|_| InsufficientBuffer)?;
+        for scene in &self.scenes {
+            xmit.extend_from_slice(&scene.to_le_bytes())
+                .map_err(|_| InsufficientBuffer)?;
+        }
+        Ok(())
+    }
+}
+
+/// A stored scene, with the model states to restore on recall.
+///
+/// The encoding of the states is up to the application.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scene {
+    pub number: u16,
+    pub state: Vec<u8, MAX_SCENE_STATE>,
+}
+
+/// Scene register of a Scene Server.
+pub struct SceneRegister {
+    current: u16,
+    scenes: Vec<Scene, MAX_SCENES>,
+}
+
+impl SceneRegister {
+    pub fn new() -> Self {
+        Self {
+            current: 0,
+            scenes: Vec::new(),
+        }
+    }
+
+    /// The current scene, zero when no scene is active.
+    pub fn current(&self) -> u16 {
+        self.current
+    }
+
+    /// Forget the current scene, once the states it stored have changed.
+    pub fn invalidate(&mut self) {
+        self.current = 0;
+    }
+
+    pub fn scenes(&self) -> impl Iterator<Item = &Scene> {
+        self.scenes.iter()
+    }
+
+    /// Store the current model states under a scene number, replacing any previous scene.
+    pub fn store(&mut self, number: u16, state: &[u8]) -> SceneStatusCode {
+        let state = match Vec::from_slice(state) {
+            Ok(state) => state,
+            Err(_) => return SceneStatusCode::SceneRegisterFull,
+        };
+        if let Some(scene) = self.scenes.iter_mut().find(|scene| scene.number == number) {
+            scene.state = state;
+        } else if self.scenes.push(Scene { number, state }).is_err() {
+            return SceneStatusCode::SceneRegisterFull;
+        }
+        self.current = number;
+        SceneStatusCode::Success
+    }
+
+    /// Recall a scene, making it the current scene, and return its stored states.
+    pub fn recall(&mut self, number: u16) -> Result<&[u8], SceneStatusCode> {
+        match self.scenes.iter().find(|scene| scene.number == number) {
+            Some(scene) => {
+                self.current = number;
+                Ok(&scene.state)
+            }
+            None => Err(SceneStatusCode::SceneNotFound),
+        }
+    }
+
+    pub fn delete(&mut self, number: u16) -> SceneStatusCode {
+        self.scenes.retain(|scene| scene.number != number);
+        if self.current == number {
+            self.current = 0;
+        }
+        SceneStatusCode::Success
+    }
+
+    pub fn status(&self, status: SceneStatusCode) -> Status {
+        Status {
+            status,
+            current_scene: self.current,
+            target: None,
+        }
+    }
+
+    pub fn register_status(&self, status: SceneStatusCode) -> RegisterStatus {
+        let mut scenes = Vec::new();
+        for scene in &self.scenes {
+            scenes.push(scene.number).ok();
+        }
+        RegisterStatus {
+            status,
+            current_scene: self.current,
+            scenes,
+        }
+    }
+
+    // Each scene takes a fixed slot: number, length of the states, and the states.
+    const SLOT_SIZE: usize = 3 + MAX_SCENE_STATE;
+
+    fn from_payload(payload: &Payload) -> Self {
+        let mut register = Self::new();
+        let data = payload.padded();
+        let count = data[0] as usize;
+        if count > MAX_SCENES {
+            // erased or corrupted storage
+            return register;
+        }
+        for slot in data[1..1 + count * Self::SLOT_SIZE].chunks_exact(Self::SLOT_SIZE) {
+            let number = u16::from_le_bytes([slot[0], slot[1]]);
+            let len = slot[2] as usize;
+            if number != 0 && len <= MAX_SCENE_STATE {
+                if let Ok(state) = Vec::from_slice(&slot[3..3 + len]) {
+                    register.scenes.push(Scene { number, state }).ok();
+                }
+            }
+        }
+        register
+    }
+
+    fn to_payload(&self) -> Payload {
+        let mut payload = Payload::new(0);
+        let data = payload.buffer_mut();
+        data[0] = self.scenes.len() as u8;
+        for (scene, slot) in self
+            .scenes
+            .iter()
+            .zip(data[1..].chunks_exact_mut(Self::SLOT_SIZE))
+        {
+            slot[0..2].copy_from_slice(&scene.number.to_le_bytes());
+            slot[2] = scene.state.len() as u8;
+            slot[3..3 + scene.state.len()].copy_from_slice(&scene.state);
+        }
+        payload.set_len(1 + self.scenes.len() * Self::SLOT_SIZE);
+        payload
+    }
+}
+
+impl Default for SceneRegister {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Persists the scene register of a Scene Server.
+///
+/// The register needs its own `Storage`, separate from the node configuration,
+/// such as a dedicated flash page.
+pub struct SceneStorage<S: Storage> {
+    storage: S,
+}
+
+impl<S: Storage> SceneStorage<S> {
+    pub fn new(storage: S) -> Self {
+        Self { storage }
+    }
+
+    /// The stored register, or an empty register if nothing valid was stored.
+    pub async fn load(&mut self) -> Result<SceneRegister, ()> {
+        match self.storage.retrieve().await? {
+            Some(payload) => Ok(SceneRegister::from_payload(&payload)),
+            None => Ok(SceneRegister::new()),
+        }
+    }
+
+    pub async fn store(&mut self, register: &SceneRegister) -> Result<(), ()> {
+        self.storage.store(&register.to_payload()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_recall() {
+        match SceneServer::parse(SCENE_RECALL, &[0x01, 0x00, 0x07, 0x41, 0x00]).unwrap() {
+            Some(SceneMessage::Recall(recall)) => {
+                assert_eq!(1, recall.scene_number);
+                assert_eq!(7, recall.tid);
+                assert_eq!(Some(0x41), recall.transition_time);
+                assert_eq!(Some(0), recall.delay);
+            }
+            _ => panic!("expected recall"),
+        }
+        // scene number zero is prohibited
+        assert!(SceneServer::parse(SCENE_RECALL, &[0x00, 0x00, 0x07]).is_err());
+        assert!(SceneServer::parse(SCENE_RECALL, &[0x01, 0x00, 0x07, 0x41]).is_err());
+    }
+
+    #[test]
+    fn test_register() {
+        let mut register = SceneRegister::new();
+        assert_eq!(SceneStatusCode::Success, register.store(1, &[0x01]));
+        assert_eq!(SceneStatusCode::Success, register.store(2, &[0x00, 0xFF]));
+        assert_eq!(2, register.current());
+
+        assert_eq!(Ok(&[0x01][..]), register.recall(1));
+        assert_eq!(1, register.current());
+        assert_eq!(Err(SceneStatusCode::SceneNotFound), register.recall(3));
+
+        assert_eq!(SceneStatusCode::Success, register.delete(1));
+        assert_eq!(0, register.current());
+
+        for number in 3..=(MAX_SCENES as u16 + 1) {
+            assert_eq!(SceneStatusCode::Success, register.store(number, &[]));
+        }
+        assert_eq!(
+            SceneStatusCode::SceneRegisterFull,
+            register.store(MAX_SCENES as u16 + 2, &[])
+        );
+        assert_eq!(
+            SceneStatusCode::SceneRegisterFull,
+            register.store(2, &[0; MAX_SCENE_STATE + 1])
+        );
+
+        let mut xmit: Vec<u8, 64> = Vec::new();
+        SceneMessage::RegisterStatus(register.register_status(SceneStatusCode::Success))
+            .emit_parameters(&mut xmit)
+            .unwrap();
+        assert_eq!(3 + 2 * MAX_SCENES, xmit.len());
+        match SceneClient::parse(SCENE_REGISTER_STATUS, &xmit).unwrap() {
+            Some(SceneMessage::RegisterStatus(status)) => {
+                assert_eq!(register.current(), status.current_scene);
+                assert_eq!(MAX_SCENES, status.scenes.len());
+                assert_eq!(2, status.scenes[0]);
+            }
+            _ => panic!("expected register status"),
+        }
+    }
+
+    #[test]
+    fn test_register_payload_round_trip() {
+        let mut register = SceneRegister::new();
+        register.store(1, &[0x01, 0x02]);
+        register.store(0x1234, &[]);
+
+        let restored = SceneRegister::from_payload(&register.to_payload());
+        assert_eq!(0, restored.current());
+        let scenes: Vec<&Scene, MAX_SCENES> = restored.scenes().collect();
+        assert_eq!(2, scenes.len());
+        assert_eq!(1, scenes[0].number);
+        assert_eq!(&[0x01, 0x02], &scenes[0].state[..]);
+        assert_eq!(0x1234, scenes[1].number);
+
+        let erased = SceneRegister::from_payload(&Payload::from_slice(0, &[0xFF; 512]).unwrap());
+        assert_eq!(0, erased.scenes().count());
+    }
+}
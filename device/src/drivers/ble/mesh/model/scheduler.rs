@@ -0,0 +1,603 @@
+use crate::drivers::ble::mesh::model::scene::{SceneRegister, MAX_SCENE_STATE};
+use crate::drivers::ble::mesh::model::time::{DateTime, TimeState};
+use crate::drivers::ble::mesh::model::{Message, Model, ModelIdentifier};
+use crate::drivers::ble::mesh::pdu::access::Opcode;
+use crate::drivers::ble::mesh::pdu::ParseError;
+use crate::drivers::ble::mesh::InsufficientBuffer;
+use crate::opcode;
+use heapless::Vec;
+
+#[derive(Clone, Debug)]
+pub struct SchedulerServer;
+
+#[derive(Clone, Debug)]
+pub struct SchedulerSetupServer;
+
+#[derive(Clone, Debug)]
+pub struct SchedulerClient;
+
+pub const SCHEDULER_SERVER: ModelIdentifier = ModelIdentifier::SIG(0x1206);
+pub const SCHEDULER_SETUP_SERVER: ModelIdentifier = ModelIdentifier::SIG(0x1207);
+pub const SCHEDULER_CLIENT: ModelIdentifier = ModelIdentifier::SIG(0x1208);
+
+/// Number of entries in the Schedule Register.
+pub const MAX_SCHEDULES: usize = 16;
+
+pub const ANY_YEAR: u8 = 0x64;
+pub const ANY_MONTH: u16 = 0x0FFF;
+pub const ANY_DAY: u8 = 0x00;
+pub const ANY_HOUR: u8 = 0x18;
+/// Once a day, at a random hour.
+pub const RANDOM_HOUR: u8 = 0x19;
+pub const ANY_MINUTE: u8 = 0x3C;
+pub const EVERY_15_MINUTES: u8 = 0x3D;
+pub const EVERY_20_MINUTES: u8 = 0x3E;
+/// Once an hour, at a random minute.
+pub const RANDOM_MINUTE: u8 = 0x3F;
+pub const ANY_SECOND: u8 = 0x3C;
+pub const EVERY_15_SECONDS: u8 = 0x3D;
+pub const EVERY_20_SECONDS: u8 = 0x3E;
+/// Once a minute, at a random second.
+pub const RANDOM_SECOND: u8 = 0x3F;
+pub const ANY_DAY_OF_WEEK: u8 = 0x7F;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SchedulerMessage {
+    Get,
+    /// Bitfield of the defined entries of the Schedule Register.
+    Status(u16),
+    ActionGet(u8),
+    ActionSet(ScheduleEntry),
+    ActionSetUnacknowledged(ScheduleEntry),
+    ActionStatus(ScheduleEntry),
+}
+
+impl Message for SchedulerMessage {
+    fn opcode(&self) -> Opcode {
+        match self {
+            Self::Get => SCHEDULER_GET,
+            Self::Status(_) => SCHEDULER_STATUS,
+            Self::ActionGet(_) => SCHEDULER_ACTION_GET,
+            Self::ActionSet(_) => SCHEDULER_ACTION_SET,
+            Self::ActionSetUnacknowledged(_) => SCHEDULER_ACTION_SET_UNACKNOWLEDGED,
+            Self::ActionStatus(_) => SCHEDULER_ACTION_STATUS,
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut heapless::Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        match self {
+            Self::Get => Ok(()),
+            Self::Status(schedules) => xmit
+                .extend_from_slice(&schedules.to_le_bytes())
+                .map_err(|_| InsufficientBuffer),
+            Self::ActionGet(index) => xmit.push(*index).map_err(|_| InsufficientBuffer),
+            Self::ActionSet(inner) => inner.emit_parameters(xmit),
+            Self::ActionSetUnacknowledged(inner) => inner.emit_parameters(xmit),
+            Self::ActionStatus(inner) => inner.emit_parameters(xmit),
+        }
+    }
+}
+
+impl Model for SchedulerServer {
+    const IDENTIFIER: ModelIdentifier = SCHEDULER_SERVER;
+    type Message<'m> = SchedulerMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            SCHEDULER_GET => Ok(Some(SchedulerMessage::Get)),
+            SCHEDULER_ACTION_GET => {
+                if parameters.len() == 1 {
+                    if (parameters[0] as usize) < MAX_SCHEDULES {
+                        Ok(Some(SchedulerMessage::ActionGet(parameters[0])))
+                    } else {
+                        Err(ParseError::InvalidValue)
+                    }
+                } else {
+                    Err(ParseError::InvalidLength)
+                }
+            }
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl Model for SchedulerSetupServer {
+    const IDENTIFIER: ModelIdentifier = SCHEDULER_SETUP_SERVER;
+    const SUPPORTS_PUBLICATION: bool = false;
+    type Message<'m> = SchedulerMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            SCHEDULER_ACTION_SET => Ok(Some(SchedulerMessage::ActionSet(ScheduleEntry::parse(
+                parameters,
+            )?))),
+            SCHEDULER_ACTION_SET_UNACKNOWLEDGED => Ok(Some(
+                SchedulerMessage::ActionSetUnacknowledged(ScheduleEntry::parse(parameters)?),
+            )),
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl Model for SchedulerClient {
+    const IDENTIFIER: ModelIdentifier = SCHEDULER_CLIENT;
+    type Message<'m> = SchedulerMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            SCHEDULER_STATUS => {
+                if parameters.len() == 2 {
+                    Ok(Some(SchedulerMessage::Status(u16::from_le_bytes([
+                        parameters[0],
+                        parameters[1],
+                    ]))))
+                } else {
+                    Err(ParseError::InvalidLength)
+                }
+            }
+            SCHEDULER_ACTION_STATUS => Ok(Some(SchedulerMessage::ActionStatus(
+                ScheduleEntry::parse(parameters)?,
+            ))),
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+opcode!( SCHEDULER_ACTION_GET 0x82, 0x48 );
+opcode!( SCHEDULER_ACTION_STATUS 0x5F );
+opcode!( SCHEDULER_GET 0x82, 0x49 );
+opcode!( SCHEDULER_STATUS 0x82, 0x4A );
+opcode!( SCHEDULER_ACTION_SET 0x60 );
+opcode!( SCHEDULER_ACTION_SET_UNACKNOWLEDGED 0x61 );
+
+/// Action of a schedule entry, carried out by the application on the
+/// Generic OnOff or Scene Server of the element.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ScheduleAction {
+    TurnOff = 0x00,
+    TurnOn = 0x01,
+    SceneRecall = 0x02,
+    NoAction = 0x0F,
+}
+
+impl ScheduleAction {
+    fn parse(value: u8) -> Result<Self, ParseError> {
+        match value {
+            0x00 => Ok(Self::TurnOff),
+            0x01 => Ok(Self::TurnOn),
+            0x02 => Ok(Self::SceneRecall),
+            0x0F => Ok(Self::NoAction),
+            _ => Err(ParseError::InvalidValue),
+        }
+    }
+}
+
+/// An entry of the Schedule Register.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ScheduleEntry {
+    pub index: u8,
+    /// Last two digits of the year, or `ANY_YEAR`.
+    pub year: u8,
+    /// Bitfield of months, from bit 0 for January.
+    pub month: u16,
+    /// Day of the month, or `ANY_DAY`.
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    /// Bitfield of days of the week, from bit 0 for Monday.
+    pub day_of_week: u8,
+    pub action: ScheduleAction,
+    pub transition_time: u8,
+    pub scene_number: u16,
+}
+
+impl ScheduleEntry {
+    /// An undefined entry of the register.
+    pub fn new(index: u8) -> Self {
+        Self {
+            index,
+            year: 0,
+            month: 0,
+            day: 0,
+            hour: 0,
+            minute: 0,
+            second: 0,
+            day_of_week: 0,
+            action: ScheduleAction::NoAction,
+            transition_time: 0,
+            scene_number: 0,
+        }
+    }
+
+    pub fn is_defined(&self) -> bool {
+        self.action != ScheduleAction::NoAction
+    }
+
+    /// Whether the entry is due at the given local time.
+    ///
+    /// Random hours, minutes and seconds are scheduled at the start of their
+    /// period rather than at a random time within it.
+    pub fn is_due(&self, now: &DateTime) -> bool {
+        self.is_defined()
+            && (self.year == ANY_YEAR || self.year as u16 == now.year % 100)
+            && self.month & (1 << (now.month - 1)) != 0
+            && (self.day == ANY_DAY
+                || self.day == now.day
+                // days past the end of the month fall on its last day
+                || (self.day > now.day && now.day == now.days_in_month()))
+            && self.day_of_week & (1 << now.weekday) != 0
+            && match self.hour {
+                ANY_HOUR => true,
+                RANDOM_HOUR => now.hour == 0,
+                hour => hour == now.hour,
+            }
+            && Self::matches_sexagesimal(self.minute, now.minute)
+            && Self::matches_sexagesimal(self.second, now.second)
+    }
+
+    // Minutes and seconds share the same special values.
+    fn matches_sexagesimal(scheduled: u8, now: u8) -> bool {
+        match scheduled {
+            ANY_MINUTE => true,
+            EVERY_15_MINUTES => now % 15 == 0,
+            EVERY_20_MINUTES => now % 20 == 0,
+            RANDOM_MINUTE => now == 0,
+            scheduled => scheduled == now,
+        }
+    }
+
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 10 {
+            let mut bytes = [0; 16];
+            bytes[0..10].copy_from_slice(parameters);
+            let bits = u128::from_le_bytes(bytes);
+            let field = |offset: u32, width: u32| ((bits >> offset) & ((1 << width) - 1)) as u16;
+
+            let entry = Self {
+                index: field(0, 4) as u8,
+                year: field(4, 7) as u8,
+                month: field(11, 12),
+                day: field(23, 5) as u8,
+                hour: field(28, 5) as u8,
+                minute: field(33, 6) as u8,
+                second: field(39, 6) as u8,
+                day_of_week: field(45, 7) as u8,
+                action: ScheduleAction::parse(field(52, 4) as u8)?,
+                transition_time: field(56, 8) as u8,
+                scene_number: field(64, 16),
+            };
+            if entry.year > ANY_YEAR || entry.hour > RANDOM_HOUR {
+                Err(ParseError::InvalidValue)
+            } else {
+                Ok(entry)
+            }
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        let fields: [(u128, u32); 11] = [
+            (self.index as u128, 4),
+            (self.year as u128, 7),
+            (self.month as u128, 12),
+            (self.day as u128, 5),
+            (self.hour as u128, 5),
+            (self.minute as u128, 6),
+            (self.second as u128, 6),
+            (self.day_of_week as u128, 7),
+            (self.action as u128, 4),
+            (self.transition_time as u128, 8),
+            (self.scene_number as u128, 16),
+        ];
+        let mut bits = 0u128;
+        let mut offset = 0;
+        for (value, width) in fields {
+            bits |= (value & ((1 << width) - 1)) << offset;
+            offset += width;
+        }
+        xmit.extend_from_slice(&bits.to_le_bytes()[0..10])
+            .map_err(|_| InsufficientBuffer)
+    }
+}
+
+/// Action of a due schedule entry, for the application to apply to the
+/// models of the element.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScheduledAction {
+    OnOff {
+        on: bool,
+        transition_time: u8,
+    },
+    /// A scene recalled from the scene register, with its stored states.
+    SceneRecall {
+        scene_number: u16,
+        transition_time: u8,
+        state: Vec<u8, MAX_SCENE_STATE>,
+    },
+}
+
+/// Schedule Register of a Scheduler Server.
+pub struct SchedulerState {
+    entries: [ScheduleEntry; MAX_SCHEDULES],
+    last_run: Option<DateTime>,
+}
+
+impl SchedulerState {
+    pub fn new() -> Self {
+        let mut entries = [ScheduleEntry::new(0); MAX_SCHEDULES];
+        for (index, entry) in entries.iter_mut().enumerate() {
+            entry.index = index as u8;
+        }
+        Self {
+            entries,
+            last_run: None,
+        }
+    }
+
+    /// Bitfield of the defined entries, as reported in a Scheduler Status.
+    pub fn schedules(&self) -> u16 {
+        self.entries
+            .iter()
+            .filter(|entry| entry.is_defined())
+            .fold(0, |schedules, entry| schedules | (1 << entry.index))
+    }
+
+    pub fn action(&self, index: u8) -> Option<ScheduleEntry> {
+        self.entries.get(index as usize).copied()
+    }
+
+    pub fn set_action(&mut self, entry: ScheduleEntry) {
+        if let Some(slot) = self.entries.get_mut(entry.index as usize) {
+            *slot = entry;
+        }
+    }
+
+    /// Entries due at the given local time.
+    pub fn due(&self, now: &DateTime) -> Vec<ScheduleEntry, MAX_SCHEDULES> {
+        let mut due = Vec::new();
+        for entry in self.entries.iter().filter(|entry| entry.is_due(now)) {
+            due.push(*entry).ok();
+        }
+        due
+    }
+
+    /// Carry out the entries due at the current local time of the Time Server.
+    ///
+    /// Meant to be polled at least once a second; entries fire once per
+    /// second however often it is polled, and never while the time is unknown.
+    pub fn run(
+        &mut self,
+        time: &TimeState,
+        scenes: &mut SceneRegister,
+    ) -> Vec<ScheduledAction, MAX_SCHEDULES> {
+        match time.local_time() {
+            Some(now) => self.run_at(&now, scenes),
+            None => Vec::new(),
+        }
+    }
+
+    fn run_at(
+        &mut self,
+        now: &DateTime,
+        scenes: &mut SceneRegister,
+    ) -> Vec<ScheduledAction, MAX_SCHEDULES> {
+        let mut actions = Vec::new();
+        if self.last_run.as_ref() == Some(now) {
+            return actions;
+        }
+        self.last_run.replace(*now);
+
+        for entry in self.due(now) {
+            let action = match entry.action {
+                ScheduleAction::TurnOff | ScheduleAction::TurnOn => ScheduledAction::OnOff {
+                    on: entry.action == ScheduleAction::TurnOn,
+                    transition_time: entry.transition_time,
+                },
+                ScheduleAction::SceneRecall => match scenes.recall(entry.scene_number) {
+                    Ok(state) => ScheduledAction::SceneRecall {
+                        scene_number: entry.scene_number,
+                        transition_time: entry.transition_time,
+                        // scenes never store more than this.
+                        state: Vec::from_slice(state).unwrap_or_default(),
+                    },
+                    // the scene was deleted since it was scheduled.
+                    Err(_) => continue,
+                },
+                ScheduleAction::NoAction => continue,
+            };
+            actions.push(action).ok();
+        }
+        actions
+    }
+}
+
+impl Default for SchedulerState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drivers::ble::mesh::model::time::TimeRole;
+
+    fn entry() -> ScheduleEntry {
+        ScheduleEntry {
+            index: 3,
+            year: ANY_YEAR,
+            month: ANY_MONTH,
+            day: ANY_DAY,
+            hour: 7,
+            minute: 30,
+            second: 0,
+            // weekdays
+            day_of_week: 0x1F,
+            action: ScheduleAction::SceneRecall,
+            transition_time: 0x41,
+            scene_number: 0x1234,
+        }
+    }
+
+    #[test]
+    fn test_action_round_trip() {
+        let entry = entry();
+        let mut xmit: Vec<u8, 10> = Vec::new();
+        SchedulerMessage::ActionSet(entry)
+            .emit_parameters(&mut xmit)
+            .unwrap();
+        assert_eq!(
+            &[0x43, 0xFE, 0x7F, 0x70, 0x3C, 0xE0, 0x23, 0x41, 0x34, 0x12],
+            &xmit[..]
+        );
+
+        match SchedulerSetupServer::parse(SCHEDULER_ACTION_SET, &xmit).unwrap() {
+            Some(SchedulerMessage::ActionSet(parsed)) => assert_eq!(entry, parsed),
+            _ => panic!("expected action set"),
+        }
+        assert!(SchedulerSetupServer::parse(SCHEDULER_ACTION_SET, &xmit[0..9]).is_err());
+    }
+
+    #[test]
+    fn test_due() {
+        let mut scheduler = SchedulerState::new();
+        assert_eq!(0, scheduler.schedules());
+        scheduler.set_action(entry());
+        assert_eq!(0x0008, scheduler.schedules());
+
+        // Thursday 2024-02-29
+        let mut now = DateTime {
+            year: 2024,
+            month: 2,
+            day: 29,
+            hour: 7,
+            minute: 30,
+            second: 0,
+            weekday: 3,
+        };
+        let due = scheduler.due(&now);
+        assert_eq!(1, due.len());
+        assert_eq!(0x1234, due[0].scene_number);
+
+        now.second = 1;
+        assert!(scheduler.due(&now).is_empty());
+
+        // Saturday
+        now.second = 0;
+        now.weekday = 5;
+        assert!(scheduler.due(&now).is_empty());
+
+        // the 31st of every month falls on the 29th of February 2024
+        let mut monthly = entry();
+        monthly.day = 31;
+        monthly.day_of_week = ANY_DAY_OF_WEEK;
+        assert!(monthly.is_due(&now));
+        now.day = 28;
+        assert!(!monthly.is_due(&now));
+    }
+
+    #[test]
+    fn test_run_scene_recall() {
+        let mut scenes = SceneRegister::new();
+        scenes.store(0x1234, &[0x01, 0x02]);
+        scenes.invalidate();
+
+        let mut scheduler = SchedulerState::new();
+        scheduler.set_action(entry());
+        let mut off = entry();
+        off.index = 4;
+        off.action = ScheduleAction::TurnOff;
+        scheduler.set_action(off);
+
+        // Thursday 2024-02-29
+        let mut now = DateTime {
+            year: 2024,
+            month: 2,
+            day: 29,
+            hour: 7,
+            minute: 29,
+            second: 59,
+            weekday: 3,
+        };
+        assert!(scheduler.run_at(&now, &mut scenes).is_empty());
+
+        now.minute = 30;
+        now.second = 0;
+        let actions = scheduler.run_at(&now, &mut scenes);
+        assert_eq!(2, actions.len());
+        assert_eq!(
+            ScheduledAction::SceneRecall {
+                scene_number: 0x1234,
+                transition_time: 0x41,
+                state: Vec::from_slice(&[0x01, 0x02]).unwrap(),
+            },
+            actions[0]
+        );
+        assert_eq!(
+            ScheduledAction::OnOff {
+                on: false,
+                transition_time: 0x41,
+            },
+            actions[1]
+        );
+        assert_eq!(0x1234, scenes.current());
+
+        // polled again within the same second.
+        assert!(scheduler.run_at(&now, &mut scenes).is_empty());
+
+        // a deleted scene is skipped.
+        scenes.delete(0x1234);
+        now.day = 28;
+        now.weekday = 2;
+        let actions = scheduler.run_at(&now, &mut scenes);
+        assert_eq!(1, actions.len());
+        assert!(matches!(
+            actions[0],
+            ScheduledAction::OnOff { on: false, .. }
+        ));
+    }
+
+    #[test]
+    fn test_run_unknown_time() {
+        let mut scenes = SceneRegister::new();
+        let mut scheduler = SchedulerState::new();
+        let mut entry = entry();
+        entry.hour = ANY_HOUR;
+        entry.minute = ANY_MINUTE;
+        entry.second = ANY_SECOND;
+        entry.action = ScheduleAction::TurnOn;
+        scheduler.set_action(entry);
+
+        let time = TimeState::new(TimeRole::None);
+        assert!(scheduler.run(&time, &mut scenes).is_empty());
+    }
+}
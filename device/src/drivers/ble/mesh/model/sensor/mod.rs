@@ -3,7 +3,7 @@ use crate::drivers::ble::mesh::pdu::access::Opcode;
 use crate::drivers::ble::mesh::pdu::ParseError;
 use crate::drivers::ble::mesh::InsufficientBuffer;
 use crate::opcode;
-use embassy::time::Duration;
+use embassy::time::{Duration, Instant};
 use heapless::Vec;
 use micromath::F32Ext;
 
@@ -33,6 +33,78 @@ where
             _c: core::marker::PhantomData,
         }
     }
+
+    /// Answer a Sensor Descriptor Get with the descriptors of the configuration.
+    pub fn descriptor_status(get: &DescriptorGet) -> DescriptorStatus<NUM_SENSORS> {
+        let mut descriptors = Vec::new();
+        for d in C::DESCRIPTORS
+            .iter()
+            .filter(|d| get.id.map(|id| id == d.id).unwrap_or(true))
+        {
+            if descriptors.push(d.clone()).is_err() {
+                break;
+            }
+        }
+        match get.id {
+            Some(id) if descriptors.is_empty() => DescriptorStatus::NotFound(id),
+            _ => DescriptorStatus::Descriptors(descriptors),
+        }
+    }
+
+    /// Answer a Sensor Get with the data of the sensors.
+    pub fn status<'m>(get: &SensorGet, data: C::Data<'m>) -> SensorStatus<'m, C> {
+        match get.id {
+            Some(id) => SensorStatus::for_property(id, data),
+            None => SensorStatus::new(data),
+        }
+    }
+
+    /// Answer a Sensor Column Get with the column of the series starting at the raw X value.
+    pub fn column_status<'m>(get: &ColumnGet<'m>, data: &'m C::Data<'_>) -> ColumnStatus<'m> {
+        let column = (0..)
+            .map_while(|index| data.column(get.id, index))
+            .find(|column| column.x == get.x.0);
+        ColumnStatus {
+            id: get.id,
+            x: RawValue(get.x.0),
+            values: column.map(|column| (RawValue(column.width), RawValue(column.y))),
+        }
+    }
+
+    /// Answer a Sensor Series Get with the columns of the series within the raw X values, if any.
+    pub fn series_status<'m>(
+        get: &SeriesGet<'m>,
+        data: &'m C::Data<'_>,
+    ) -> SeriesStatus<'m, NUM_COLUMNS> {
+        let mut values = Vec::new();
+        for column in (0..).map_while(|index| data.column(get.id, index)) {
+            let within = match &get.x {
+                Some((x1, x2)) => {
+                    compare_raw(column.x, x1.0).is_ge() && compare_raw(column.x, x2.0).is_le()
+                }
+                None => true,
+            };
+            if within
+                && values
+                    .push((
+                        RawValue(column.x),
+                        RawValue(column.width),
+                        RawValue(column.y),
+                    ))
+                    .is_err()
+            {
+                break;
+            }
+        }
+        SeriesStatus { id: get.id, values }
+    }
+}
+
+/// Compares raw little-endian values of the same property.
+fn compare_raw(a: &[u8], b: &[u8]) -> core::cmp::Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
 }
 
 impl<C, const NUM_SENSORS: usize, const NUM_COLUMNS: usize>
@@ -66,6 +138,7 @@ pub struct PropertyId(pub u16);
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct RawValue<'m>(pub &'m [u8]);
 
+#[derive(Clone)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Tolerance(pub u16);
 
@@ -89,6 +162,24 @@ pub trait SensorData: Default {
         property: PropertyId,
         xmit: &mut Vec<u8, N>,
     ) -> Result<(), InsufficientBuffer>;
+
+    /// The value of a property as a number, to evaluate the cadence triggers against.
+    fn value(&self, _property: PropertyId) -> Option<i64> {
+        None
+    }
+
+    /// The column at the given index of the series of a property, in increasing X order.
+    fn column(&self, _property: PropertyId, _index: usize) -> Option<Column<'_>> {
+        None
+    }
+}
+
+/// A column of a sensor series, as raw values of the property.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Column<'m> {
+    pub x: &'m [u8],
+    pub width: &'m [u8],
+    pub y: &'m [u8],
 }
 
 pub trait SensorSetupConfig: SensorConfig {
@@ -122,6 +213,7 @@ pub enum DescriptorStatus<const NUM_SENSORS: usize> {
     Descriptors(Vec<SensorDescriptor, NUM_SENSORS>),
 }
 
+#[derive(Clone)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct SensorDescriptor {
     pub id: PropertyId,
@@ -147,6 +239,7 @@ pub struct SettingDescriptor {
     size: usize,
 }
 
+#[derive(Clone)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum SamplingFunction {
     Unspecified,
@@ -170,6 +263,7 @@ where
     C: SensorConfig,
 {
     pub data: C::Data<'a>,
+    id: Option<PropertyId>,
 }
 
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -232,6 +326,7 @@ pub struct CadenceSet<'m> {
     fast_cadence_high: RawValue<'m>,
 }
 
+#[derive(Copy, Clone, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum StatusTriggerType {
     Property,
@@ -425,9 +520,15 @@ where
             SENSOR_COLUMN_GET => Ok(Some(SensorMessage::ColumnGet(ColumnGet::parse::<C>(
                 parameters,
             )?))),
+            SENSOR_COLUMN_STATUS => Ok(Some(SensorMessage::ColumnStatus(
+                ColumnStatus::parse::<C>(parameters)?,
+            ))),
             SENSOR_SERIES_GET => Ok(Some(SensorMessage::SeriesGet(SeriesGet::parse::<C>(
                 parameters,
             )?))),
+            SENSOR_SERIES_STATUS => Ok(Some(SensorMessage::SeriesStatus(
+                SeriesStatus::parse::<C>(parameters)?,
+            ))),
             _ => Ok(None),
         }
     }
@@ -582,6 +683,12 @@ impl SensorDescriptor {
     }
 }
 
+impl CadenceDescriptor {
+    pub const fn new(id: PropertyId, size: usize) -> Self {
+        Self { id, size }
+    }
+}
+
 impl SamplingFunction {
     fn emit_parameters<const N: usize>(
         &self,
@@ -626,46 +733,53 @@ impl<'a, C> SensorStatus<'a, C>
 where
     C: SensorConfig,
 {
+    /// Status of all the sensors.
     pub fn new(data: C::Data<'a>) -> Self {
-        Self { data }
+        Self { data, id: None }
+    }
+
+    /// Status of the sensor of a single property, as asked for by a Sensor Get.
+    pub fn for_property(id: PropertyId, data: C::Data<'a>) -> Self {
+        Self { data, id: Some(id) }
     }
 
     fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
         let mut data = C::Data::default();
-        for d in C::DESCRIPTORS {
-            let mut pos = 0;
-            let format = parameters[0] & 0b1000_0000;
-            let (length, id, offset): (usize, u16, usize);
-
-            if format == 0 {
-                length = ((parameters[0] & 0b0111_1000) >> 3) as usize;
-                id = ((parameters[0] & 0b0000_0111) | parameters[1]).into();
-                offset = 2;
-            } else {
-                length = (parameters[0] & 0b0111_1111) as usize;
-                id = ((parameters[1] as u16) << 8) | parameters[2] as u16;
-                offset = 3;
+        let mut parameters = parameters;
+        while !parameters.is_empty() {
+            let (id, len, offset) = parse_marshalled_header(parameters)?;
+            if parameters.len() < offset + len {
+                return Err(ParseError::InvalidLength);
             }
-
-            if id == d.id.0 && d.size == length {
-                pos += offset;
-            } else {
-                return Err(ParseError::InvalidValue);
+            if let Some(d) = lookup_descriptor::<C>(id) {
+                // a zero-length value is a sensor without data
+                if len > 0 {
+                    if d.size != len {
+                        return Err(ParseError::InvalidValue);
+                    }
+                    data.decode(id, &parameters[offset..offset + len])?;
+                }
             }
-
-            let parameters = &parameters[pos..(pos + d.size)];
-            data.decode(d.id, parameters)?;
+            parameters = &parameters[offset + len..];
         }
-        Ok(Self { data })
+        Ok(Self { data, id: None })
     }
 
     fn emit_parameters<const N: usize>(
         &self,
         xmit: &mut heapless::Vec<u8, N>,
     ) -> Result<(), InsufficientBuffer> {
-        let descriptors = C::DESCRIPTORS;
-        for d in descriptors {
-            self.emit_property(d.id, d.size, xmit)?;
+        match self.id {
+            Some(id) => match lookup_descriptor::<C>(id) {
+                Some(d) => self.emit_property(d.id, d.size, xmit)?,
+                // unknown property
+                None => emit_marshalled_header(id, 0, xmit)?,
+            },
+            None => {
+                for d in C::DESCRIPTORS {
+                    self.emit_property(d.id, d.size, xmit)?;
+                }
+            }
         }
         Ok(())
     }
@@ -676,28 +790,58 @@ where
         size: usize,
         xmit: &mut heapless::Vec<u8, N>,
     ) -> Result<(), InsufficientBuffer> {
-        let len = size;
-
-        if id.0 < 2048 && len <= 16 {
-            let value: u8 = (len as u8) << 3;
-            let value: u8 = value | ((id.0 & 0x0700) >> 8) as u8;
-            xmit.push(value).map_err(|_| InsufficientBuffer)?;
+        emit_marshalled_header(id, size, xmit)?;
+        self.data.encode(id, xmit).map_err(|_| InsufficientBuffer)?;
+        Ok(())
+    }
+}
 
-            let value = (id.0 & 0xFF) as u8;
-            xmit.push(value).map_err(|_| InsufficientBuffer)?;
+/// Emits the header of a property in Marshalled Sensor Data.
+///
+/// Format A packs a 1-based length of up to 16 octets and an 11-bit property ID
+/// in two octets, format B otherwise uses one octet for the length, where 0x7F
+/// means no value, followed by the property ID.
+fn emit_marshalled_header<const N: usize>(
+    id: PropertyId,
+    len: usize,
+    xmit: &mut heapless::Vec<u8, N>,
+) -> Result<(), InsufficientBuffer> {
+    if len > 0 && len <= 16 && id.0 < 2048 {
+        let header = ((len as u16 - 1) << 1) | (id.0 << 5);
+        xmit.extend_from_slice(&header.to_le_bytes())
+            .map_err(|_| InsufficientBuffer)?;
+    } else {
+        let len = if len == 0 || len > 127 {
+            0x7F
         } else {
-            let value: u8 = 0x80;
-            let value: u8 = value | len as u8 & 0x7F;
-            xmit.push(value).map_err(|_| InsufficientBuffer)?;
-
-            xmit.push((id.0 & 0xFF00 >> 8) as u8)
-                .map_err(|_| InsufficientBuffer)?;
-            xmit.push((id.0 & 0xFF) as u8)
-                .map_err(|_| InsufficientBuffer)?;
-        }
+            len as u8 - 1
+        };
+        xmit.push((len << 1) | 0x01)
+            .map_err(|_| InsufficientBuffer)?;
+        id.emit_parameters(xmit)?;
+    }
+    Ok(())
+}
 
-        self.data.encode(id, xmit).map_err(|_| InsufficientBuffer)?;
-        Ok(())
+/// Parses the header of a property in Marshalled Sensor Data, as the property,
+/// the length of its value and the length of the header.
+fn parse_marshalled_header(data: &[u8]) -> Result<(PropertyId, usize, usize), ParseError> {
+    if data.is_empty() {
+        Err(ParseError::InvalidLength)
+    } else if data[0] & 0x01 == 0 {
+        if data.len() < 2 {
+            return Err(ParseError::InvalidLength);
+        }
+        let header = u16::from_le_bytes([data[0], data[1]]);
+        let len = ((header >> 1) & 0x0F) as usize + 1;
+        Ok((PropertyId(header >> 5), len, 2))
+    } else {
+        let id = PropertyId::parse(&data[1..])?;
+        let len = match data[0] >> 1 {
+            0x7F => 0,
+            len => len as usize + 1,
+        };
+        Ok((id, len, 3))
     }
 }
 
@@ -747,6 +891,9 @@ impl<'a> ColumnGet<'a> {
         if let Some(d) = lookup_descriptor::<C>(id) {
             let x_len = d.x_size;
             let parameters = &parameters[2..];
+            if parameters.len() < x_len {
+                return Err(ParseError::InvalidLength);
+            }
             Ok(Self {
                 id,
                 x: RawValue(&parameters[..x_len]),
@@ -767,6 +914,32 @@ impl<'a> ColumnGet<'a> {
 }
 
 impl<'a> ColumnStatus<'a> {
+    fn parse<C>(parameters: &'a [u8]) -> Result<Self, ParseError>
+    where
+        C: SensorConfig,
+    {
+        let id = PropertyId::parse(parameters)?;
+        let d = lookup_descriptor::<C>(id).ok_or(ParseError::InvalidValue)?;
+        let parameters = &parameters[2..];
+        if parameters.len() < d.x_size {
+            return Err(ParseError::InvalidLength);
+        }
+        let (x, parameters) = parameters.split_at(d.x_size);
+        let values = if parameters.is_empty() {
+            None
+        } else if parameters.len() == d.x_size + d.size {
+            let (w, y) = parameters.split_at(d.x_size);
+            Some((RawValue(w), RawValue(y)))
+        } else {
+            return Err(ParseError::InvalidLength);
+        };
+        Ok(Self {
+            id,
+            x: RawValue(x),
+            values,
+        })
+    }
+
     fn emit_parameters<const N: usize>(
         &self,
         xmit: &mut heapless::Vec<u8, N>,
@@ -820,6 +993,28 @@ impl<'a> SeriesGet<'a> {
 }
 
 impl<'a, const NUM_COLUMNS: usize> SeriesStatus<'a, NUM_COLUMNS> {
+    fn parse<C>(parameters: &'a [u8]) -> Result<Self, ParseError>
+    where
+        C: SensorConfig,
+    {
+        let id = PropertyId::parse(parameters)?;
+        let d = lookup_descriptor::<C>(id).ok_or(ParseError::InvalidValue)?;
+        let column_size = 2 * d.x_size + d.size;
+        let parameters = &parameters[2..];
+        if column_size == 0 || parameters.len() % column_size != 0 {
+            return Err(ParseError::InvalidLength);
+        }
+        let mut values = Vec::new();
+        for column in parameters.chunks_exact(column_size) {
+            let (x, column) = column.split_at(d.x_size);
+            let (w, y) = column.split_at(d.x_size);
+            values
+                .push((RawValue(x), RawValue(w), RawValue(y)))
+                .map_err(|_| ParseError::InsufficientBuffer)?;
+        }
+        Ok(Self { id, values })
+    }
+
     fn emit_parameters<const N: usize>(
         &self,
         xmit: &mut heapless::Vec<u8, N>,
@@ -866,8 +1061,11 @@ impl<'a> CadenceSet<'a> {
         C: SensorSetupConfig,
     {
         let id = PropertyId::parse(parameters)?;
-        let fast_cadence_divisor = parameters[2] & 0xF7 >> 1;
-        let status_trigger_type = if parameters[2] & 0x01 == 1 {
+        if parameters.len() < 3 {
+            return Err(ParseError::InvalidLength);
+        }
+        let fast_cadence_divisor = parameters[2] & 0x7F;
+        let status_trigger_type = if parameters[2] & 0x80 != 0 {
             StatusTriggerType::Unitless
         } else {
             StatusTriggerType::Property
@@ -875,12 +1073,20 @@ impl<'a> CadenceSet<'a> {
 
         if let Some(d) = lookup_cadence_descriptor::<C>(id) {
             let c_len = d.size;
+            // percentages are in units of 0.01%
+            let delta_len = match status_trigger_type {
+                StatusTriggerType::Property => c_len,
+                StatusTriggerType::Unitless => 2,
+            };
+            if parameters.len() != 3 + 2 * delta_len + 1 + 2 * c_len {
+                return Err(ParseError::InvalidLength);
+            }
 
             let parameters = &parameters[3..];
-            let status_trigger_delta_down = RawValue(&parameters[..c_len]);
-            let parameters = &parameters[c_len..];
-            let status_trigger_delta_up = RawValue(&parameters[..c_len]);
-            let parameters = &parameters[c_len..];
+            let status_trigger_delta_down = RawValue(&parameters[..delta_len]);
+            let parameters = &parameters[delta_len..];
+            let status_trigger_delta_up = RawValue(&parameters[..delta_len]);
+            let parameters = &parameters[delta_len..];
 
             let status_min_interval = parameters[0];
             let parameters = &parameters[1..];
@@ -909,10 +1115,10 @@ impl<'a> CadenceSet<'a> {
         xmit: &mut heapless::Vec<u8, N>,
     ) -> Result<(), InsufficientBuffer> {
         self.id.emit_parameters(xmit)?;
-        let val = self.fast_cadence_divisor << 1;
+        let val = self.fast_cadence_divisor & 0x7F;
         let val = val
             | match self.status_trigger_type {
-                StatusTriggerType::Unitless => 1,
+                StatusTriggerType::Unitless => 0x80,
                 _ => 0,
             };
         xmit.push(val).map_err(|_| InsufficientBuffer)?;
@@ -1019,7 +1225,454 @@ impl<'a> SettingStatus<'a> {
     }
 }
 
+/// Largest raw value of a cadence.
+pub const MAX_CADENCE_SIZE: usize = 8;
+
+static NO_CADENCE: [u8; MAX_CADENCE_SIZE] = [0; MAX_CADENCE_SIZE];
+
+struct Cadence {
+    id: PropertyId,
+    fast_cadence_divisor: u8,
+    status_trigger_type: StatusTriggerType,
+    status_trigger_delta_down: Vec<u8, MAX_CADENCE_SIZE>,
+    status_trigger_delta_up: Vec<u8, MAX_CADENCE_SIZE>,
+    status_min_interval: u8,
+    fast_cadence_low: Vec<u8, MAX_CADENCE_SIZE>,
+    fast_cadence_high: Vec<u8, MAX_CADENCE_SIZE>,
+    // the raw values above, as numbers
+    delta_down: i64,
+    delta_up: i64,
+    fast: Option<(i64, i64)>,
+    published: Option<(i64, Instant)>,
+}
+
+/// Cadence of the publications of a Sensor Server, as set by Sensor Cadence Set.
+///
+/// A sensor without a cadence is published at the publish period only. With a
+/// cadence, it is published faster while its value is within the fast cadence
+/// range, and in between whenever its value moves by more than the trigger deltas.
+///
+/// The cadence is set by the messages of the Sensor Setup Server, see `dispatch`.
+pub struct SensorServerState<C, const NUM_SENSORS: usize>
+where
+    C: SensorConfig,
+{
+    cadences: Vec<Cadence, NUM_SENSORS>,
+    _c: core::marker::PhantomData<C>,
+}
+
+impl<C, const NUM_SENSORS: usize> SensorServerState<C, NUM_SENSORS>
+where
+    C: SensorConfig,
+{
+    pub fn new() -> Self {
+        Self {
+            cadences: Vec::new(),
+            _c: core::marker::PhantomData,
+        }
+    }
+
+    fn cadence(&self, id: PropertyId) -> Option<&Cadence> {
+        self.cadences.iter().find(|c| c.id == id)
+    }
+
+    /// Period to publish the status of a sensor at, given the publish period of the model.
+    pub fn publish_period(&self, id: PropertyId, period: Duration, data: &C::Data<'_>) -> Duration {
+        match (self.cadence(id), data.value(id)) {
+            (Some(cadence), Some(value)) => {
+                let fast = match cadence.fast {
+                    Some((low, high)) if low <= high => value >= low && value <= high,
+                    Some((low, high)) => value >= low || value <= high,
+                    None => false,
+                };
+                if fast {
+                    period / (1 << cadence.fast_cadence_divisor.min(15))
+                } else {
+                    period
+                }
+            }
+            _ => period,
+        }
+    }
+
+    /// Whether the status of a sensor should be published ahead of its period,
+    /// because its value moved past a trigger delta since it was last published.
+    pub fn should_publish(&self, id: PropertyId, data: &C::Data<'_>, now: Instant) -> bool {
+        let (cadence, value) = match (self.cadence(id), data.value(id)) {
+            (Some(cadence), Some(value)) => (cadence, value),
+            _ => return false,
+        };
+        let (last, at) = match cadence.published {
+            Some(published) => published,
+            None => return true,
+        };
+        let min_interval = Duration::from_millis(1 << cadence.status_min_interval.min(26));
+        if now < at + min_interval {
+            return false;
+        }
+        let (up, down) = match cadence.status_trigger_type {
+            StatusTriggerType::Property => (cadence.delta_up, cadence.delta_down),
+            // in units of 0.01% of the last published value
+            StatusTriggerType::Unitless => (
+                last.abs() * cadence.delta_up / 10000,
+                last.abs() * cadence.delta_down / 10000,
+            ),
+        };
+        (value > last && value - last >= up) || (value < last && last - value >= down)
+    }
+
+    /// Record the publication of the status of a sensor, for its triggers.
+    pub fn published(&mut self, id: PropertyId, data: &C::Data<'_>, now: Instant) {
+        let value = data.value(id);
+        if let Some(cadence) = self.cadences.iter_mut().find(|c| c.id == id) {
+            cadence.published = value.map(|value| (value, now));
+        }
+    }
+}
+
+impl<C, const NUM_SENSORS: usize> SensorServerState<C, NUM_SENSORS>
+where
+    C: SensorSetupConfig,
+{
+    /// Apply a Sensor Cadence Set.
+    pub fn set_cadence(&mut self, set: &CadenceSet) -> Result<(), InsufficientBuffer> {
+        let raw = |value: &RawValue| -> Result<Vec<u8, MAX_CADENCE_SIZE>, InsufficientBuffer> {
+            Vec::from_slice(value.0).map_err(|_| InsufficientBuffer)
+        };
+        let delta = |value: &RawValue| match set.status_trigger_type {
+            StatusTriggerType::Property => raw_value::<C>(set.id, value.0),
+            StatusTriggerType::Unitless if value.0.len() == 2 => {
+                Some(u16::from_le_bytes([value.0[0], value.0[1]]) as i64)
+            }
+            StatusTriggerType::Unitless => None,
+        };
+        let fast = match (
+            raw_value::<C>(set.id, set.fast_cadence_low.0),
+            raw_value::<C>(set.id, set.fast_cadence_high.0),
+        ) {
+            (Some(low), Some(high)) => Some((low, high)),
+            _ => None,
+        };
+
+        let cadence = Cadence {
+            id: set.id,
+            fast_cadence_divisor: set.fast_cadence_divisor,
+            status_trigger_type: set.status_trigger_type,
+            status_trigger_delta_down: raw(&set.status_trigger_delta_down)?,
+            status_trigger_delta_up: raw(&set.status_trigger_delta_up)?,
+            status_min_interval: set.status_min_interval,
+            fast_cadence_low: raw(&set.fast_cadence_low)?,
+            fast_cadence_high: raw(&set.fast_cadence_high)?,
+            delta_down: delta(&set.status_trigger_delta_down).unwrap_or(0).abs(),
+            delta_up: delta(&set.status_trigger_delta_up).unwrap_or(0).abs(),
+            fast,
+            published: None,
+        };
+
+        if let Some(current) = self.cadences.iter_mut().find(|c| c.id == set.id) {
+            *current = cadence;
+            Ok(())
+        } else {
+            self.cadences.push(cadence).map_err(|_| InsufficientBuffer)
+        }
+    }
+
+    /// Apply the cadence messages of the Sensor Setup Server, returning the
+    /// Sensor Cadence Status to respond with, if any.
+    pub fn dispatch<'m, const NUM_COLUMNS: usize>(
+        &'m mut self,
+        message: &SensorSetupMessage<'_, C, NUM_SENSORS, NUM_COLUMNS>,
+    ) -> Result<Option<SensorSetupMessage<'m, C, NUM_SENSORS, NUM_COLUMNS>>, InsufficientBuffer>
+    {
+        match message {
+            SensorSetupMessage::CadenceGet(get) => Ok(self
+                .cadence_status(get.id)
+                .map(SensorSetupMessage::CadenceStatus)),
+            SensorSetupMessage::CadenceSet(set) => {
+                self.set_cadence(set)?;
+                Ok(self
+                    .cadence_status(set.id)
+                    .map(SensorSetupMessage::CadenceStatus))
+            }
+            SensorSetupMessage::CadenceSetUnacknowledged(set) => {
+                self.set_cadence(set)?;
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Answer a Sensor Cadence Get, or `None` if the sensor does not support a cadence.
+    pub fn cadence_status(&self, id: PropertyId) -> Option<CadenceStatus<'_>> {
+        let d = lookup_cadence_descriptor::<C>(id)?;
+        Some(match self.cadence(id) {
+            Some(cadence) => CadenceStatus {
+                id,
+                fast_cadence_divisor: cadence.fast_cadence_divisor,
+                status_trigger_type: cadence.status_trigger_type,
+                status_trigger_delta_down: RawValue(&cadence.status_trigger_delta_down),
+                status_trigger_delta_up: RawValue(&cadence.status_trigger_delta_up),
+                status_min_interval: cadence.status_min_interval,
+                fast_cadence_low: RawValue(&cadence.fast_cadence_low),
+                fast_cadence_high: RawValue(&cadence.fast_cadence_high),
+            },
+            None => {
+                let none = &NO_CADENCE[..d.size.min(MAX_CADENCE_SIZE)];
+                CadenceStatus {
+                    id,
+                    fast_cadence_divisor: 0,
+                    status_trigger_type: StatusTriggerType::Property,
+                    status_trigger_delta_down: RawValue(none),
+                    status_trigger_delta_up: RawValue(none),
+                    status_min_interval: 0,
+                    fast_cadence_low: RawValue(none),
+                    fast_cadence_high: RawValue(none),
+                }
+            }
+        })
+    }
+}
+
+impl<C, const NUM_SENSORS: usize> Default for SensorServerState<C, NUM_SENSORS>
+where
+    C: SensorConfig,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A raw value of a property as a number, as decoded by the sensor data.
+fn raw_value<C>(id: PropertyId, raw: &[u8]) -> Option<i64>
+where
+    C: SensorConfig,
+{
+    let mut data = C::Data::default();
+    data.decode(id, raw).ok()?;
+    data.value(id)
+}
+
 /// Approxmiates the log with base 1.1
 fn log_1_1(seconds: f32) -> u8 {
     (seconds.log(1.1) as u8) + 64
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPERATURE: PropertyId = PropertyId(0x4F);
+    const SERIES: PropertyId = PropertyId(0x0852);
+
+    #[derive(Clone)]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    struct TestConfig;
+
+    #[derive(Default)]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    struct TestData {
+        temperature: i8,
+        series: [[u8; 3]; 3],
+    }
+
+    impl SensorConfig for TestConfig {
+        type Data<'m> = TestData;
+
+        const DESCRIPTORS: &'static [SensorDescriptor] = &[
+            SensorDescriptor::new(TEMPERATURE, 1),
+            SensorDescriptor {
+                x_size: 1,
+                ..SensorDescriptor::new(SERIES, 1)
+            },
+        ];
+    }
+
+    impl SensorSetupConfig for TestConfig {
+        const CADENCE_DESCRIPTORS: &'static [CadenceDescriptor] =
+            &[CadenceDescriptor::new(TEMPERATURE, 1)];
+        const SETTING_DESCRIPTORS: &'static [SettingDescriptor] = &[];
+    }
+
+    impl SensorData for TestData {
+        fn decode(&mut self, property: PropertyId, data: &[u8]) -> Result<(), ParseError> {
+            if property == TEMPERATURE {
+                self.temperature = data[0] as i8;
+            }
+            Ok(())
+        }
+
+        fn encode<const N: usize>(
+            &self,
+            property: PropertyId,
+            xmit: &mut Vec<u8, N>,
+        ) -> Result<(), InsufficientBuffer> {
+            let value = if property == TEMPERATURE {
+                self.temperature as u8
+            } else {
+                self.series.iter().map(|c| c[2]).sum()
+            };
+            xmit.push(value).map_err(|_| InsufficientBuffer)
+        }
+
+        fn value(&self, property: PropertyId) -> Option<i64> {
+            if property == TEMPERATURE {
+                Some(self.temperature as i64)
+            } else {
+                None
+            }
+        }
+
+        fn column(&self, property: PropertyId, index: usize) -> Option<Column<'_>> {
+            if property == SERIES {
+                self.series.get(index).map(|c| Column {
+                    x: &c[0..1],
+                    width: &c[1..2],
+                    y: &c[2..3],
+                })
+            } else {
+                None
+            }
+        }
+    }
+
+    type Server = SensorServer<TestConfig, 2, 4>;
+
+    fn data(temperature: i8) -> TestData {
+        TestData {
+            temperature,
+            series: [[0, 10, 1], [10, 10, 2], [20, 10, 3]],
+        }
+    }
+
+    #[test]
+    fn test_marshalled_sensor_data() {
+        let mut xmit: Vec<u8, 16> = Vec::new();
+        SensorStatus::<TestConfig>::new(data(-2))
+            .emit_parameters(&mut xmit)
+            .unwrap();
+        // format A for the temperature, format B for the series above 2047
+        assert_eq!(&[0xE0, 0x09, 0xFE, 0x01, 0x52, 0x08, 0x06], &xmit[..]);
+
+        let status = SensorStatus::<TestConfig>::parse(&xmit).unwrap();
+        assert_eq!(-2, status.data.temperature);
+
+        let mut xmit: Vec<u8, 16> = Vec::new();
+        Server::status(
+            &SensorGet {
+                id: Some(PropertyId(0x1234)),
+            },
+            data(0),
+        )
+        .emit_parameters(&mut xmit)
+        .unwrap();
+        assert_eq!(&[0xFF, 0x34, 0x12], &xmit[..]);
+    }
+
+    #[test]
+    fn test_column_and_series() {
+        let data = data(0);
+
+        let parameters = [0x52, 0x08, 10];
+        let get = ColumnGet::parse::<TestConfig>(&parameters).unwrap();
+        let mut xmit: Vec<u8, 16> = Vec::new();
+        Server::column_status(&get, &data)
+            .emit_parameters(&mut xmit)
+            .unwrap();
+        assert_eq!(&[0x52, 0x08, 10, 10, 2], &xmit[..]);
+
+        let parameters = [0x52, 0x08, 5];
+        let get = ColumnGet::parse::<TestConfig>(&parameters).unwrap();
+        assert!(Server::column_status(&get, &data).values.is_none());
+
+        let parameters = [0x52, 0x08, 5, 20];
+        let get = SeriesGet::parse::<TestConfig>(&parameters).unwrap();
+        let mut xmit: Vec<u8, 16> = Vec::new();
+        Server::series_status(&get, &data)
+            .emit_parameters(&mut xmit)
+            .unwrap();
+        assert_eq!(&[0x52, 0x08, 10, 10, 2, 20, 10, 3], &xmit[..]);
+
+        let status = SeriesStatus::<4>::parse::<TestConfig>(&xmit).unwrap();
+        assert_eq!(2, status.values.len());
+    }
+
+    #[test]
+    fn test_cadence() {
+        // divisor 2, delta down 2, delta up 3, 2ms, fast between 20 and 30
+        let parameters = [0x4F, 0x00, 0x02, 2, 3, 1, 20, 30];
+        let set = CadenceSet::parse::<TestConfig>(&parameters).unwrap();
+        let mut state: SensorServerState<TestConfig, 2> = SensorServerState::new();
+        state.set_cadence(&set).unwrap();
+
+        let mut xmit: Vec<u8, 16> = Vec::new();
+        state
+            .cadence_status(TEMPERATURE)
+            .unwrap()
+            .emit_parameters(&mut xmit)
+            .unwrap();
+        assert_eq!(&parameters, &xmit[..]);
+
+        let period = Duration::from_secs(8);
+        assert_eq!(period, state.publish_period(TEMPERATURE, period, &data(10)));
+        assert_eq!(
+            Duration::from_secs(2),
+            state.publish_period(TEMPERATURE, period, &data(25))
+        );
+
+        let now = Instant::from_secs(100);
+        assert!(state.should_publish(TEMPERATURE, &data(10), now));
+        state.published(TEMPERATURE, &data(10), now);
+
+        let later = now + Duration::from_millis(10);
+        assert!(!state.should_publish(TEMPERATURE, &data(12), later));
+        assert!(state.should_publish(TEMPERATURE, &data(13), later));
+        assert!(state.should_publish(TEMPERATURE, &data(8), later));
+        assert!(!state.should_publish(TEMPERATURE, &data(13), now));
+    }
+
+    #[test]
+    fn test_cadence_dispatch() {
+        type SetupServer = SensorSetupServer<TestConfig, 2, 4>;
+        let mut state: SensorServerState<TestConfig, 2> = SensorServerState::new();
+
+        let get = SetupServer::parse(SENSOR_CADENCE_GET, &[0x4F, 0x00])
+            .unwrap()
+            .unwrap();
+        let mut xmit: Vec<u8, 16> = Vec::new();
+        state
+            .dispatch(&get)
+            .unwrap()
+            .unwrap()
+            .emit_parameters(&mut xmit)
+            .unwrap();
+        assert_eq!(&[0x4F, 0x00, 0, 0, 0, 0, 0, 0], &xmit[..]);
+
+        let parameters = [0x4F, 0x00, 0x02, 2, 3, 1, 20, 30];
+        let set = SetupServer::parse(SENSOR_CADENCE_SET_UNACKNOWLEDGED, &parameters)
+            .unwrap()
+            .unwrap();
+        assert!(state.dispatch(&set).unwrap().is_none());
+
+        let mut xmit: Vec<u8, 16> = Vec::new();
+        state
+            .dispatch(&get)
+            .unwrap()
+            .unwrap()
+            .emit_parameters(&mut xmit)
+            .unwrap();
+        assert_eq!(&parameters, &xmit[..]);
+
+        let parameters = [0x4F, 0x00, 0x01, 1, 1, 0, 0, 10];
+        let set = SetupServer::parse(SENSOR_CADENCE_SET, &parameters)
+            .unwrap()
+            .unwrap();
+        let mut xmit: Vec<u8, 16> = Vec::new();
+        state
+            .dispatch(&set)
+            .unwrap()
+            .unwrap()
+            .emit_parameters(&mut xmit)
+            .unwrap();
+        assert_eq!(&parameters, &xmit[..]);
+    }
+}
@@ -0,0 +1,720 @@
+use crate::drivers::ble::mesh::model::{Message, Model, ModelIdentifier};
+use crate::drivers::ble::mesh::pdu::access::Opcode;
+use crate::drivers::ble::mesh::pdu::ParseError;
+use crate::drivers::ble::mesh::InsufficientBuffer;
+use crate::opcode;
+use embassy::time::Instant;
+use heapless::Vec;
+
+#[derive(Clone, Debug)]
+pub struct TimeServer;
+
+#[derive(Clone, Debug)]
+pub struct TimeSetupServer;
+
+#[derive(Clone, Debug)]
+pub struct TimeClient;
+
+pub const TIME_SERVER: ModelIdentifier = ModelIdentifier::SIG(0x1200);
+pub const TIME_SETUP_SERVER: ModelIdentifier = ModelIdentifier::SIG(0x1201);
+pub const TIME_CLIENT: ModelIdentifier = ModelIdentifier::SIG(0x1202);
+
+// Encoded value of a zero TAI-UTC delta.
+const TAI_UTC_DELTA_ZERO: i16 = 0xFF;
+// Encoded value of a zero time zone offset.
+const TIME_ZONE_OFFSET_ZERO: i16 = 0x40;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TimeMessage {
+    Get,
+    Set(Time),
+    Status(Time),
+    RoleGet,
+    RoleSet(TimeRole),
+    RoleStatus(TimeRole),
+    ZoneGet,
+    ZoneSet(TimeZoneSet),
+    ZoneStatus(TimeZoneStatus),
+    TaiUtcDeltaGet,
+    TaiUtcDeltaSet(TaiUtcDeltaSet),
+    TaiUtcDeltaStatus(TaiUtcDeltaStatus),
+}
+
+impl Message for TimeMessage {
+    fn opcode(&self) -> Opcode {
+        match self {
+            Self::Get => TIME_GET,
+            Self::Set(_) => TIME_SET,
+            Self::Status(_) => TIME_STATUS,
+            Self::RoleGet => TIME_ROLE_GET,
+            Self::RoleSet(_) => TIME_ROLE_SET,
+            Self::RoleStatus(_) => TIME_ROLE_STATUS,
+            Self::ZoneGet => TIME_ZONE_GET,
+            Self::ZoneSet(_) => TIME_ZONE_SET,
+            Self::ZoneStatus(_) => TIME_ZONE_STATUS,
+            Self::TaiUtcDeltaGet => TAI_UTC_DELTA_GET,
+            Self::TaiUtcDeltaSet(_) => TAI_UTC_DELTA_SET,
+            Self::TaiUtcDeltaStatus(_) => TAI_UTC_DELTA_STATUS,
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut heapless::Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        match self {
+            Self::Get | Self::RoleGet | Self::ZoneGet | Self::TaiUtcDeltaGet => Ok(()),
+            Self::Set(inner) => inner.emit_parameters(xmit),
+            Self::Status(inner) => {
+                if inner.is_unknown() {
+                    // only the TAI seconds are present when the time is unknown
+                    emit_tai_seconds(0, xmit)
+                } else {
+                    inner.emit_parameters(xmit)
+                }
+            }
+            Self::RoleSet(inner) => inner.emit_parameters(xmit),
+            Self::RoleStatus(inner) => inner.emit_parameters(xmit),
+            Self::ZoneSet(inner) => inner.emit_parameters(xmit),
+            Self::ZoneStatus(inner) => inner.emit_parameters(xmit),
+            Self::TaiUtcDeltaSet(inner) => inner.emit_parameters(xmit),
+            Self::TaiUtcDeltaStatus(inner) => inner.emit_parameters(xmit),
+        }
+    }
+}
+
+impl Model for TimeServer {
+    const IDENTIFIER: ModelIdentifier = TIME_SERVER;
+    type Message<'m> = TimeMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            TIME_GET => Ok(Some(TimeMessage::Get)),
+            // published by Time Servers acting as authority or relay
+            TIME_STATUS => Ok(Some(TimeMessage::Status(Time::parse_status(parameters)?))),
+            TIME_ZONE_GET => Ok(Some(TimeMessage::ZoneGet)),
+            TAI_UTC_DELTA_GET => Ok(Some(TimeMessage::TaiUtcDeltaGet)),
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl Model for TimeSetupServer {
+    const IDENTIFIER: ModelIdentifier = TIME_SETUP_SERVER;
+    const SUPPORTS_SUBSCRIPTION: bool = false;
+    const SUPPORTS_PUBLICATION: bool = false;
+    type Message<'m> = TimeMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            TIME_SET => Ok(Some(TimeMessage::Set(Time::parse(parameters)?))),
+            TIME_ROLE_GET => Ok(Some(TimeMessage::RoleGet)),
+            TIME_ROLE_SET => Ok(Some(TimeMessage::RoleSet(TimeRole::parse(parameters)?))),
+            TIME_ZONE_SET => Ok(Some(TimeMessage::ZoneSet(TimeZoneSet::parse(parameters)?))),
+            TAI_UTC_DELTA_SET => Ok(Some(TimeMessage::TaiUtcDeltaSet(TaiUtcDeltaSet::parse(
+                parameters,
+            )?))),
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl Model for TimeClient {
+    const IDENTIFIER: ModelIdentifier = TIME_CLIENT;
+    type Message<'m> = TimeMessage;
+
+    fn parse<'m>(
+        opcode: Opcode,
+        parameters: &'m [u8],
+    ) -> Result<Option<Self::Message<'m>>, ParseError> {
+        match opcode {
+            TIME_STATUS => Ok(Some(TimeMessage::Status(Time::parse_status(parameters)?))),
+            TIME_ROLE_STATUS => Ok(Some(TimeMessage::RoleStatus(TimeRole::parse(parameters)?))),
+            TIME_ZONE_STATUS => Ok(Some(TimeMessage::ZoneStatus(TimeZoneStatus::parse(
+                parameters,
+            )?))),
+            TAI_UTC_DELTA_STATUS => Ok(Some(TimeMessage::TaiUtcDeltaStatus(
+                TaiUtcDeltaStatus::parse(parameters)?,
+            ))),
+            _ => {
+                // not applicable to this role
+                Ok(None)
+            }
+        }
+    }
+}
+
+opcode!( TIME_GET 0x82, 0x37 );
+opcode!( TIME_SET 0x5C );
+opcode!( TIME_STATUS 0x5D );
+opcode!( TIME_ROLE_GET 0x82, 0x38 );
+opcode!( TIME_ROLE_SET 0x82, 0x39 );
+opcode!( TIME_ROLE_STATUS 0x82, 0x3A );
+opcode!( TIME_ZONE_GET 0x82, 0x3B );
+opcode!( TIME_ZONE_SET 0x82, 0x3C );
+opcode!( TIME_ZONE_STATUS 0x82, 0x3D );
+opcode!( TAI_UTC_DELTA_GET 0x82, 0x3E );
+opcode!( TAI_UTC_DELTA_SET 0x82, 0x3F );
+opcode!( TAI_UTC_DELTA_STATUS 0x82, 0x40 );
+
+fn parse_tai_seconds(parameters: &[u8]) -> u64 {
+    let mut bytes = [0; 8];
+    bytes[0..5].copy_from_slice(&parameters[0..5]);
+    u64::from_le_bytes(bytes)
+}
+
+fn emit_tai_seconds<const N: usize>(
+    tai_seconds: u64,
+    xmit: &mut Vec<u8, N>,
+) -> Result<(), InsufficientBuffer> {
+    xmit.extend_from_slice(&tai_seconds.to_le_bytes()[0..5])
+        .map_err(|_| InsufficientBuffer)
+}
+
+fn parse_tai_utc_delta(parameters: &[u8]) -> i16 {
+    (u16::from_le_bytes([parameters[0], parameters[1]]) & 0x7FFF) as i16 - TAI_UTC_DELTA_ZERO
+}
+
+fn emit_tai_utc_delta<const N: usize>(
+    tai_utc_delta: i16,
+    xmit: &mut Vec<u8, N>,
+) -> Result<(), InsufficientBuffer> {
+    let encoded = (tai_utc_delta + TAI_UTC_DELTA_ZERO) as u16 & 0x7FFF;
+    xmit.extend_from_slice(&encoded.to_le_bytes())
+        .map_err(|_| InsufficientBuffer)
+}
+
+fn parse_time_zone_offset(value: u8) -> i16 {
+    value as i16 - TIME_ZONE_OFFSET_ZERO
+}
+
+fn emit_time_zone_offset<const N: usize>(
+    time_zone_offset: i16,
+    xmit: &mut Vec<u8, N>,
+) -> Result<(), InsufficientBuffer> {
+    xmit.push((time_zone_offset + TIME_ZONE_OFFSET_ZERO) as u8)
+        .map_err(|_| InsufficientBuffer)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Time {
+    /// Seconds since 2000-01-01T00:00:00 TAI, zero when the time is unknown.
+    pub tai_seconds: u64,
+    /// Fraction of the current second, in 1/256th of a second.
+    pub subsecond: u8,
+    /// Accumulated uncertainty of the time, in 10 millisecond steps.
+    pub uncertainty: u8,
+    pub time_authority: bool,
+    /// TAI minus UTC, in seconds.
+    pub tai_utc_delta: i16,
+    /// Offset of the local time zone from UTC, in 15 minute steps.
+    pub time_zone_offset: i16,
+}
+
+impl Time {
+    pub fn is_unknown(&self) -> bool {
+        self.tai_seconds == 0
+    }
+
+    /// Seconds since 2000-01-01T00:00:00 local time, if the time is known.
+    pub fn local_seconds(&self) -> Option<u64> {
+        if self.is_unknown() {
+            return None;
+        }
+        let local = self.tai_seconds as i64 - self.tai_utc_delta as i64
+            + self.time_zone_offset as i64 * 15 * 60;
+        if local < 0 {
+            None
+        } else {
+            Some(local as u64)
+        }
+    }
+
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 10 {
+            let flags = u16::from_le_bytes([parameters[7], parameters[8]]);
+            Ok(Self {
+                tai_seconds: parse_tai_seconds(parameters),
+                subsecond: parameters[5],
+                uncertainty: parameters[6],
+                time_authority: flags & 0x01 != 0,
+                tai_utc_delta: (flags >> 1) as i16 - TAI_UTC_DELTA_ZERO,
+                time_zone_offset: parse_time_zone_offset(parameters[9]),
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn parse_status(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 5 {
+            if parse_tai_seconds(parameters) == 0 {
+                Ok(Self::default())
+            } else {
+                Err(ParseError::InvalidLength)
+            }
+        } else {
+            Self::parse(parameters)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        emit_tai_seconds(self.tai_seconds, xmit)?;
+        xmit.push(self.subsecond).map_err(|_| InsufficientBuffer)?;
+        xmit.push(self.uncertainty)
+            .map_err(|_| InsufficientBuffer)?;
+        let flags = (((self.tai_utc_delta + TAI_UTC_DELTA_ZERO) as u16 & 0x7FFF) << 1)
+            | self.time_authority as u16;
+        xmit.extend_from_slice(&flags.to_le_bytes())
+            .map_err(|_| InsufficientBuffer)?;
+        emit_time_zone_offset(self.time_zone_offset, xmit)
+    }
+}
+
+/// Role of a Time Server in the propagation of time through the network.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TimeRole {
+    None = 0x00,
+    Authority = 0x01,
+    Relay = 0x02,
+    Client = 0x03,
+}
+
+impl TimeRole {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 1 {
+            match parameters[0] {
+                0x00 => Ok(Self::None),
+                0x01 => Ok(Self::Authority),
+                0x02 => Ok(Self::Relay),
+                0x03 => Ok(Self::Client),
+                _ => Err(ParseError::InvalidValue),
+            }
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        xmit.push(*self as u8).map_err(|_| InsufficientBuffer)?;
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TimeZoneSet {
+    pub time_zone_offset_new: i16,
+    /// TAI seconds at which the new offset takes effect.
+    pub tai_of_zone_change: u64,
+}
+
+impl TimeZoneSet {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 6 {
+            Ok(Self {
+                time_zone_offset_new: parse_time_zone_offset(parameters[0]),
+                tai_of_zone_change: parse_tai_seconds(&parameters[1..]),
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        emit_time_zone_offset(self.time_zone_offset_new, xmit)?;
+        emit_tai_seconds(self.tai_of_zone_change, xmit)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TimeZoneStatus {
+    pub time_zone_offset_current: i16,
+    pub time_zone_offset_new: i16,
+    pub tai_of_zone_change: u64,
+}
+
+impl TimeZoneStatus {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 7 {
+            Ok(Self {
+                time_zone_offset_current: parse_time_zone_offset(parameters[0]),
+                time_zone_offset_new: parse_time_zone_offset(parameters[1]),
+                tai_of_zone_change: parse_tai_seconds(&parameters[2..]),
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        emit_time_zone_offset(self.time_zone_offset_current, xmit)?;
+        emit_time_zone_offset(self.time_zone_offset_new, xmit)?;
+        emit_tai_seconds(self.tai_of_zone_change, xmit)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TaiUtcDeltaSet {
+    pub tai_utc_delta_new: i16,
+    /// TAI seconds at which the new delta takes effect.
+    pub tai_of_delta_change: u64,
+}
+
+impl TaiUtcDeltaSet {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 7 {
+            Ok(Self {
+                tai_utc_delta_new: parse_tai_utc_delta(parameters),
+                tai_of_delta_change: parse_tai_seconds(&parameters[2..]),
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        emit_tai_utc_delta(self.tai_utc_delta_new, xmit)?;
+        emit_tai_seconds(self.tai_of_delta_change, xmit)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TaiUtcDeltaStatus {
+    pub tai_utc_delta_current: i16,
+    pub tai_utc_delta_new: i16,
+    pub tai_of_delta_change: u64,
+}
+
+impl TaiUtcDeltaStatus {
+    fn parse(parameters: &[u8]) -> Result<Self, ParseError> {
+        if parameters.len() == 9 {
+            Ok(Self {
+                tai_utc_delta_current: parse_tai_utc_delta(parameters),
+                tai_utc_delta_new: parse_tai_utc_delta(&parameters[2..]),
+                tai_of_delta_change: parse_tai_seconds(&parameters[4..]),
+            })
+        } else {
+            Err(ParseError::InvalidLength)
+        }
+    }
+
+    fn emit_parameters<const N: usize>(
+        &self,
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        emit_tai_utc_delta(self.tai_utc_delta_current, xmit)?;
+        emit_tai_utc_delta(self.tai_utc_delta_new, xmit)?;
+        emit_tai_seconds(self.tai_of_delta_change, xmit)
+    }
+}
+
+/// Time state of a Time Server.
+///
+/// The time keeps running from the last Time Set, or Time Status from an
+/// authority, and pending time zone and TAI-UTC delta changes are applied
+/// once their TAI time has passed.
+pub struct TimeState {
+    time: Time,
+    set_at: Option<Instant>,
+    role: TimeRole,
+    time_zone_offset_new: i16,
+    tai_of_zone_change: u64,
+    tai_utc_delta_new: i16,
+    tai_of_delta_change: u64,
+}
+
+impl TimeState {
+    pub fn new(role: TimeRole) -> Self {
+        Self {
+            time: Time::default(),
+            set_at: None,
+            role,
+            time_zone_offset_new: 0,
+            tai_of_zone_change: 0,
+            tai_utc_delta_new: 0,
+            tai_of_delta_change: 0,
+        }
+    }
+
+    pub fn role(&self) -> TimeRole {
+        self.role
+    }
+
+    pub fn set_role(&mut self, role: TimeRole) {
+        self.role = role;
+    }
+
+    /// The current time.
+    pub fn now(&self) -> Time {
+        let mut time = self.time;
+        if let Some(set_at) = self.set_at {
+            let elapsed = (Instant::now() - set_at).as_millis() * 256 / 1000;
+            let subseconds = time.subsecond as u64 + elapsed;
+            time.tai_seconds += subseconds / 256;
+            time.subsecond = (subseconds % 256) as u8;
+        }
+        if self.tai_of_zone_change != 0 && time.tai_seconds >= self.tai_of_zone_change {
+            time.time_zone_offset = self.time_zone_offset_new;
+        }
+        if self.tai_of_delta_change != 0 && time.tai_seconds >= self.tai_of_delta_change {
+            time.tai_utc_delta = self.tai_utc_delta_new;
+        }
+        time
+    }
+
+    /// Set the time, from a Time Set.
+    pub fn set(&mut self, time: Time) {
+        self.time = time;
+        self.set_at = if time.is_unknown() {
+            None
+        } else {
+            Some(Instant::now())
+        };
+    }
+
+    /// Synchronize with a Time Status, if this server takes its time from others.
+    ///
+    /// Returns whether the time was updated.
+    pub fn synchronize(&mut self, time: Time) -> bool {
+        match self.role {
+            TimeRole::Relay | TimeRole::Client if !time.is_unknown() => {
+                self.set(time);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn set_zone(&mut self, set: TimeZoneSet) {
+        self.time = self.now();
+        self.set_at = self.set_at.map(|_| Instant::now());
+        self.time_zone_offset_new = set.time_zone_offset_new;
+        self.tai_of_zone_change = set.tai_of_zone_change;
+    }
+
+    pub fn zone_status(&self) -> TimeZoneStatus {
+        TimeZoneStatus {
+            time_zone_offset_current: self.now().time_zone_offset,
+            time_zone_offset_new: self.time_zone_offset_new,
+            tai_of_zone_change: self.tai_of_zone_change,
+        }
+    }
+
+    pub fn set_tai_utc_delta(&mut self, set: TaiUtcDeltaSet) {
+        self.time = self.now();
+        self.set_at = self.set_at.map(|_| Instant::now());
+        self.tai_utc_delta_new = set.tai_utc_delta_new;
+        self.tai_of_delta_change = set.tai_of_delta_change;
+    }
+
+    pub fn tai_utc_delta_status(&self) -> TaiUtcDeltaStatus {
+        TaiUtcDeltaStatus {
+            tai_utc_delta_current: self.now().tai_utc_delta,
+            tai_utc_delta_new: self.tai_utc_delta_new,
+            tai_of_delta_change: self.tai_of_delta_change,
+        }
+    }
+
+    /// The current local date and time, if the time is known.
+    pub fn local_time(&self) -> Option<DateTime> {
+        self.now().local_seconds().map(DateTime::from_seconds)
+    }
+}
+
+/// Calendar date and time.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DateTime {
+    pub year: u16,
+    /// Month of the year, 1 to 12.
+    pub month: u8,
+    /// Day of the month, 1 to 31.
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    /// Day of the week, from 0 for Monday to 6 for Sunday.
+    pub weekday: u8,
+}
+
+impl DateTime {
+    /// Date and time of a number of seconds since 2000-01-01T00:00:00.
+    pub fn from_seconds(seconds: u64) -> Self {
+        let days = seconds / 86400;
+        let time = seconds % 86400;
+
+        // 2000-01-01 was a Saturday.
+        let weekday = ((days + 5) % 7) as u8;
+
+        // Civil date from days, counting years from March so the leap day
+        // is the last day of the year.
+        let days = days + 10957 + 719468;
+        let era = days / 146097;
+        let day_of_era = days - era * 146097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month + 2) / 5 + 1;
+        let month = if month < 10 { month + 3 } else { month - 9 };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        Self {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+            hour: (time / 3600) as u8,
+            minute: (time % 3600 / 60) as u8,
+            second: (time % 60) as u8,
+            weekday,
+        }
+    }
+
+    pub fn is_leap_year(&self) -> bool {
+        (self.year % 4 == 0 && self.year % 100 != 0) || self.year % 400 == 0
+    }
+
+    pub fn days_in_month(&self) -> u8 {
+        match self.month {
+            2 if self.is_leap_year() => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_round_trip() {
+        let time = Time {
+            tai_seconds: 0x01_2345_6789,
+            subsecond: 0x80,
+            uncertainty: 0x02,
+            time_authority: true,
+            tai_utc_delta: 37,
+            time_zone_offset: -4,
+        };
+        let mut xmit: Vec<u8, 10> = Vec::new();
+        TimeMessage::Set(time).emit_parameters(&mut xmit).unwrap();
+        assert_eq!(
+            &[0x89, 0x67, 0x45, 0x23, 0x01, 0x80, 0x02, 0x49, 0x02, 0x3C],
+            &xmit[..]
+        );
+
+        match TimeSetupServer::parse(TIME_SET, &xmit).unwrap() {
+            Some(TimeMessage::Set(parsed)) => assert_eq!(time, parsed),
+            _ => panic!("expected set"),
+        }
+        assert!(TimeSetupServer::parse(TIME_SET, &xmit[0..9]).is_err());
+    }
+
+    #[test]
+    fn test_unknown_time_status() {
+        let mut xmit: Vec<u8, 10> = Vec::new();
+        TimeMessage::Status(Time::default())
+            .emit_parameters(&mut xmit)
+            .unwrap();
+        assert_eq!(&[0x00; 5], &xmit[..]);
+
+        match TimeClient::parse(TIME_STATUS, &xmit).unwrap() {
+            Some(TimeMessage::Status(time)) => {
+                assert!(time.is_unknown());
+                assert_eq!(None, time.local_seconds());
+            }
+            _ => panic!("expected status"),
+        }
+        assert!(TimeClient::parse(TIME_STATUS, &[0x01, 0x00, 0x00, 0x00, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_tai_utc_delta_status_round_trip() {
+        let status = TaiUtcDeltaStatus {
+            tai_utc_delta_current: 37,
+            tai_utc_delta_new: -1,
+            tai_of_delta_change: 0x0A_0000_0000,
+        };
+        let mut xmit: Vec<u8, 9> = Vec::new();
+        TimeMessage::TaiUtcDeltaStatus(status)
+            .emit_parameters(&mut xmit)
+            .unwrap();
+        assert_eq!(
+            &[0x24, 0x01, 0xFE, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0A],
+            &xmit[..]
+        );
+
+        match TimeClient::parse(TAI_UTC_DELTA_STATUS, &xmit).unwrap() {
+            Some(TimeMessage::TaiUtcDeltaStatus(parsed)) => assert_eq!(status, parsed),
+            _ => panic!("expected tai-utc delta status"),
+        }
+    }
+
+    #[test]
+    fn test_local_time() {
+        let time = Time {
+            // 2024-02-29T12:34:56 UTC
+            tai_seconds: 762525296 + 37,
+            tai_utc_delta: 37,
+            // UTC+1
+            time_zone_offset: 4,
+            ..Default::default()
+        };
+        let local = DateTime::from_seconds(time.local_seconds().unwrap());
+        assert_eq!(
+            DateTime {
+                year: 2024,
+                month: 2,
+                day: 29,
+                hour: 13,
+                minute: 34,
+                second: 56,
+                weekday: 3,
+            },
+            local
+        );
+        assert_eq!(29, local.days_in_month());
+
+        let epoch = DateTime::from_seconds(0);
+        assert_eq!(
+            (2000, 1, 1, 5),
+            (epoch.year, epoch.month, epoch.day, epoch.weekday)
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use crate::drivers::ble::mesh::provisioning::{InputOOBAction, OutputOOBAction};
+use core::future::Future;
+use heapless::Vec;
+
+/// A value exchanged out-of-band during provisioning.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum OobValue {
+    /// Number of blinks, beeps, vibrations, pushes or twists.
+    Count(u32),
+    /// Decimal number of at most the requested number of digits.
+    Numeric(u32),
+    /// Uppercase ASCII letters and digits.
+    Alphanumeric(Vec<u8, 8>),
+}
+
+/// Application callbacks used to authenticate provisioning out-of-band.
+///
+/// Which callbacks are used depends on the OOB capabilities advertised
+/// and the authentication method the provisioner selects.
+pub trait OobHandler {
+    type DisplayFuture<'m>: Future<Output = ()> + 'm
+    where
+        Self: 'm;
+
+    /// Present an output OOB value to the user, for them to enter on the provisioner.
+    ///
+    /// The future should complete once the value is being presented; the
+    /// application may continue presenting it until provisioning completes.
+    fn display<'m>(
+        &'m self,
+        action: OutputOOBAction,
+        value: &'m OobValue,
+    ) -> Self::DisplayFuture<'m>;
+
+    type InputFuture<'m>: Future<Output = OobValue> + 'm
+    where
+        Self: 'm;
+
+    /// Wait for the user to input the value presented by the provisioner,
+    /// of at most `size` events, digits or characters.
+    fn input<'m>(&'m self, action: InputOOBAction, size: u8) -> Self::InputFuture<'m>;
+
+    /// The 16-byte static OOB value, if this device has one.
+    fn static_oob(&self) -> Option<[u8; 16]>;
+}
+
+/// For devices without any OOB capabilities.
+pub struct NoOob;
+
+impl OobHandler for NoOob {
+    type DisplayFuture<'m> = impl Future<Output = ()> + 'm
+    where
+        Self: 'm;
+
+    fn display<'m>(
+        &'m self,
+        _action: OutputOOBAction,
+        _value: &'m OobValue,
+    ) -> Self::DisplayFuture<'m> {
+        async move {}
+    }
+
+    type InputFuture<'m> = impl Future<Output = OobValue> + 'm
+    where
+        Self: 'm;
+
+    fn input<'m>(&'m self, _action: InputOOBAction, _size: u8) -> Self::InputFuture<'m> {
+        // never advertised as an input capability, so never requested.
+        futures::future::pending()
+    }
+
+    fn static_oob(&self) -> Option<[u8; 16]> {
+        None
+    }
+}
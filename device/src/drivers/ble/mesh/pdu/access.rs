@@ -17,6 +17,9 @@ pub struct AccessMessage {
     pub(crate) nid: u8,
     pub(crate) akf: bool,
     pub(crate) aid: ApplicationKeyIdentifier,
+    /// Device key of a remote node, used instead of this node's own
+    /// device key when `akf` is not set.
+    pub(crate) remote_device_key: Option<[u8; 16]>,
     pub(crate) src: UnicastAddress,
     pub(crate) dst: Address,
     pub(crate) payload: AccessPayload,
@@ -45,6 +48,7 @@ impl AccessMessage {
             nid: access.nid,
             akf: access.akf,
             aid: access.aid,
+            remote_device_key: access.remote_device_key,
             src: access.src,
             dst: access.dst,
             payload: AccessPayload::parse(&access.payload)?,
@@ -66,6 +70,7 @@ impl AccessMessage {
             .map_err(|_| InsufficientBuffer)?;
         Ok(Self {
             ttl: None,
+            remote_device_key: None,
             src,
             dst: self.src.into(),
             payload: AccessPayload {
@@ -24,6 +24,12 @@ pub struct ObfuscatedAndEncryptedNetworkPDU {
     pub(crate) nid: u8, /* 7 bits */
     pub(crate) obfuscated: [u8; 6],
     pub(crate) encrypted_and_mic: Vec<u8, 28>,
+    /// Cleartext destination, known only for locally-encrypted PDUs and never emitted.
+    /// Used by proxy filters to decide whether to forward the PDU to a proxy client.
+    pub(crate) dst: Option<Address>,
+    /// Whether the PDU was received from a proxy client, never emitted.
+    /// The proxy filter then accepts replies to the source of the PDU.
+    pub(crate) from_proxy_client: bool,
 }
 
 impl ObfuscatedAndEncryptedNetworkPDU {
@@ -41,6 +47,8 @@ impl ObfuscatedAndEncryptedNetworkPDU {
             nid,
             obfuscated,
             encrypted_and_mic,
+            dst: None,
+            from_proxy_client: false,
         })
     }
 
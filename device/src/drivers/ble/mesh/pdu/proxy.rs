@@ -1,3 +1,5 @@
+use crate::drivers::ble::mesh::address::{Address, UnicastAddress};
+use crate::drivers::ble::mesh::config::network::NetworkKeyHandle;
 use crate::drivers::ble::mesh::pdu::ParseError;
 use crate::drivers::ble::mesh::InsufficientBuffer;
 use heapless::Vec;
@@ -101,3 +103,199 @@ impl ProxyPDU {
         })
     }
 }
+
+/// Maximum number of addresses carried by a single unsegmented proxy configuration message.
+pub const MAX_FILTER_ADDRESSES: usize = 8;
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FilterType {
+    Whitelist,
+    Blacklist,
+}
+
+impl FilterType {
+    pub fn parse(data: u8) -> Result<Self, ParseError> {
+        match data {
+            0x00 => Ok(Self::Whitelist),
+            0x01 => Ok(Self::Blacklist),
+            _ => Err(ParseError::InvalidValue),
+        }
+    }
+}
+
+impl Into<u8> for FilterType {
+    fn into(self) -> u8 {
+        match self {
+            FilterType::Whitelist => 0x00,
+            FilterType::Blacklist => 0x01,
+        }
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, PartialEq, Debug)]
+pub enum ProxyConfigurationMessage {
+    SetFilterType(FilterType),
+    AddAddresses(Vec<Address, MAX_FILTER_ADDRESSES>),
+    RemoveAddresses(Vec<Address, MAX_FILTER_ADDRESSES>),
+    FilterStatus {
+        filter_type: FilterType,
+        list_size: u16,
+    },
+}
+
+impl ProxyConfigurationMessage {
+    const SET_FILTER_TYPE: u8 = 0x00;
+    const ADD_ADDRESSES: u8 = 0x01;
+    const REMOVE_ADDRESSES: u8 = 0x02;
+    const FILTER_STATUS: u8 = 0x03;
+
+    pub fn parse(data: &[u8]) -> Result<Self, ParseError> {
+        if data.len() < 1 {
+            Err(ParseError::InvalidLength)?;
+        }
+
+        let parameters = &data[1..];
+        match data[0] {
+            Self::SET_FILTER_TYPE => {
+                if parameters.len() != 1 {
+                    Err(ParseError::InvalidLength)?;
+                }
+                Ok(Self::SetFilterType(FilterType::parse(parameters[0])?))
+            }
+            Self::ADD_ADDRESSES => Ok(Self::AddAddresses(Self::parse_addresses(parameters)?)),
+            Self::REMOVE_ADDRESSES => Ok(Self::RemoveAddresses(Self::parse_addresses(parameters)?)),
+            Self::FILTER_STATUS => {
+                if parameters.len() != 3 {
+                    Err(ParseError::InvalidLength)?;
+                }
+                Ok(Self::FilterStatus {
+                    filter_type: FilterType::parse(parameters[0])?,
+                    list_size: u16::from_be_bytes([parameters[1], parameters[2]]),
+                })
+            }
+            _ => Err(ParseError::InvalidValue),
+        }
+    }
+
+    fn parse_addresses(data: &[u8]) -> Result<Vec<Address, MAX_FILTER_ADDRESSES>, ParseError> {
+        if data.len() % 2 != 0 {
+            Err(ParseError::InvalidLength)?;
+        }
+        let mut addresses = Vec::new();
+        for address in data.chunks(2) {
+            addresses
+                .push(Address::parse([address[0], address[1]]))
+                .map_err(|_| ParseError::InsufficientBuffer)?;
+        }
+        Ok(addresses)
+    }
+
+    pub fn emit<const N: usize>(&self, xmit: &mut Vec<u8, N>) -> Result<(), InsufficientBuffer> {
+        match self {
+            ProxyConfigurationMessage::SetFilterType(filter_type) => {
+                xmit.push(Self::SET_FILTER_TYPE)
+                    .map_err(|_| InsufficientBuffer)?;
+                xmit.push((*filter_type).into())
+                    .map_err(|_| InsufficientBuffer)?;
+            }
+            ProxyConfigurationMessage::AddAddresses(addresses) => {
+                xmit.push(Self::ADD_ADDRESSES)
+                    .map_err(|_| InsufficientBuffer)?;
+                Self::emit_addresses(addresses, xmit)?;
+            }
+            ProxyConfigurationMessage::RemoveAddresses(addresses) => {
+                xmit.push(Self::REMOVE_ADDRESSES)
+                    .map_err(|_| InsufficientBuffer)?;
+                Self::emit_addresses(addresses, xmit)?;
+            }
+            ProxyConfigurationMessage::FilterStatus {
+                filter_type,
+                list_size,
+            } => {
+                xmit.push(Self::FILTER_STATUS)
+                    .map_err(|_| InsufficientBuffer)?;
+                xmit.push((*filter_type).into())
+                    .map_err(|_| InsufficientBuffer)?;
+                xmit.extend_from_slice(&list_size.to_be_bytes())
+                    .map_err(|_| InsufficientBuffer)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn emit_addresses<const N: usize>(
+        addresses: &[Address],
+        xmit: &mut Vec<u8, N>,
+    ) -> Result<(), InsufficientBuffer> {
+        for address in addresses {
+            xmit.extend_from_slice(&address.as_bytes())
+                .map_err(|_| InsufficientBuffer)?;
+        }
+        Ok(())
+    }
+}
+
+/// A decrypted proxy configuration PDU.
+///
+/// Proxy configuration PDUs are network PDUs with CTL set, a TTL of zero and
+/// an unassigned destination, carrying a proxy configuration message
+/// directly instead of a lower transport PDU.
+#[derive(Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CleartextProxyConfigurationPDU {
+    pub(crate) network_key: NetworkKeyHandle,
+    pub(crate) ivi: u8,  /* 1 bit */
+    pub(crate) nid: u8,  /* 7 bits */
+    pub(crate) seq: u32, /* 24 bits */
+    pub(crate) src: UnicastAddress,
+    pub(crate) message: ProxyConfigurationMessage,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(message: ProxyConfigurationMessage) -> Vec<u8, 32> {
+        let mut bytes = Vec::<u8, 32>::new();
+        message.emit(&mut bytes).unwrap();
+        assert_eq!(ProxyConfigurationMessage::parse(&bytes).unwrap(), message);
+        bytes
+    }
+
+    #[test]
+    fn test_set_filter_type() {
+        let bytes = round_trip(ProxyConfigurationMessage::SetFilterType(
+            FilterType::Blacklist,
+        ));
+        assert_eq!(&*bytes, &[0x00, 0x01]);
+
+        assert!(ProxyConfigurationMessage::parse(&[0x00, 0x02]).is_err());
+        assert!(ProxyConfigurationMessage::parse(&[0x00]).is_err());
+    }
+
+    #[test]
+    fn test_add_remove_addresses() {
+        let mut addresses = Vec::new();
+        addresses.push(Address::parse([0x00, 0x01])).unwrap();
+        addresses.push(Address::parse([0xC0, 0x05])).unwrap();
+
+        let bytes = round_trip(ProxyConfigurationMessage::AddAddresses(addresses.clone()));
+        assert_eq!(&*bytes, &[0x01, 0x00, 0x01, 0xC0, 0x05]);
+
+        let bytes = round_trip(ProxyConfigurationMessage::RemoveAddresses(addresses));
+        assert_eq!(&*bytes, &[0x02, 0x00, 0x01, 0xC0, 0x05]);
+
+        assert!(ProxyConfigurationMessage::parse(&[0x01, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_filter_status() {
+        let bytes = round_trip(ProxyConfigurationMessage::FilterStatus {
+            filter_type: FilterType::Whitelist,
+            list_size: 0x0102,
+        });
+        assert_eq!(&*bytes, &[0x03, 0x00, 0x01, 0x02]);
+    }
+}
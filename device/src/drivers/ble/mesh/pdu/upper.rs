@@ -33,6 +33,7 @@ pub struct UpperAccess {
     pub(crate) nid: u8,
     pub(crate) akf: bool,
     pub(crate) aid: ApplicationKeyIdentifier,
+    pub(crate) remote_device_key: Option<[u8; 16]>,
     pub(crate) src: UnicastAddress,
     pub(crate) dst: Address,
     pub(crate) payload: Vec<u8, 380>,
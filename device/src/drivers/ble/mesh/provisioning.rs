@@ -284,6 +284,16 @@ impl Data {
             })
         }
     }
+
+    pub fn emit<const N: usize>(&self, xmit: &mut Vec<u8, N>) -> Result<(), InsufficientBuffer> {
+        xmit.push(ProvisioningPDU::DATA)
+            .map_err(|_| InsufficientBuffer)?;
+        xmit.extend_from_slice(&self.encrypted)
+            .map_err(|_| InsufficientBuffer)?;
+        xmit.extend_from_slice(&self.mic)
+            .map_err(|_| InsufficientBuffer)?;
+        Ok(())
+    }
 }
 
 /// The decrypted provisioning data wrapped in `Data` above.
@@ -319,6 +329,26 @@ impl ProvisioningData {
             })
         }
     }
+
+    pub fn emit<const N: usize>(&self, xmit: &mut Vec<u8, N>) -> Result<(), InsufficientBuffer> {
+        xmit.extend_from_slice(&self.network_key)
+            .map_err(|_| InsufficientBuffer)?;
+        xmit.extend_from_slice(&u16::from(self.key_index).to_be_bytes())
+            .map_err(|_| InsufficientBuffer)?;
+        let mut flags = 0;
+        if let KeyRefreshFlag::Phase2 = self.key_refresh_flag {
+            flags |= 0b00000001;
+        }
+        if let IVUpdateFlag::UpdateActive = self.iv_update_flag {
+            flags |= 0b00000010;
+        }
+        xmit.push(flags).map_err(|_| InsufficientBuffer)?;
+        xmit.extend_from_slice(&self.iv_index.to_be_bytes())
+            .map_err(|_| InsufficientBuffer)?;
+        xmit.extend_from_slice(&self.unicast_address.as_bytes())
+            .map_err(|_| InsufficientBuffer)?;
+        Ok(())
+    }
 }
 
 // TODO: probably move this elsewhere
@@ -399,6 +429,12 @@ impl Failed {
             })
         }
     }
+
+    pub fn emit<const N: usize>(&self, xmit: &mut Vec<u8, N>) -> Result<(), InsufficientBuffer> {
+        xmit.push(ProvisioningPDU::FAILED)
+            .map_err(|_| InsufficientBuffer)?;
+        self.error_code.emit(xmit)
+    }
 }
 
 impl ProvisioningPDU {
@@ -435,26 +471,18 @@ impl ProvisioningPDU {
 
     pub fn emit<const N: usize>(&self, xmit: &mut Vec<u8, N>) -> Result<(), InsufficientBuffer> {
         match self {
-            ProvisioningPDU::Invite(_) => {
-                unimplemented!()
-            }
+            ProvisioningPDU::Invite(invite) => invite.emit(xmit),
             ProvisioningPDU::Capabilities(capabilities) => capabilities.emit(xmit),
-            ProvisioningPDU::Start(_) => {
-                unimplemented!()
-            }
+            ProvisioningPDU::Start(start) => start.emit(xmit),
             ProvisioningPDU::PublicKey(public_key) => public_key.emit(xmit),
             ProvisioningPDU::InputComplete => xmit
                 .push(Self::INPUT_COMPLETE)
                 .map_err(|_| InsufficientBuffer),
             ProvisioningPDU::Confirmation(confirmation) => confirmation.emit(xmit),
             ProvisioningPDU::Random(random) => random.emit(xmit),
-            ProvisioningPDU::Data(_) => {
-                unimplemented!()
-            }
+            ProvisioningPDU::Data(data) => data.emit(xmit),
             ProvisioningPDU::Complete => xmit.push(Self::COMPLETE).map_err(|_| InsufficientBuffer),
-            ProvisioningPDU::Failed(_) => {
-                unimplemented!()
-            }
+            ProvisioningPDU::Failed(failed) => failed.emit(xmit),
         }
     }
 
@@ -656,7 +684,7 @@ impl OOBSize {
     pub fn parse(octet: u8) -> Result<Self, ParseError> {
         if octet == 0 {
             Ok(Self::NotSupported)
-        } else if octet < 8 {
+        } else if octet <= 8 {
             Ok(Self::MaximumSize(octet))
         } else {
             Err(ParseError::InvalidValue)
@@ -694,7 +722,8 @@ impl OutputOOBAction {
     }
 
     pub fn emit<const N: usize>(&self, xmit: &mut Vec<u8, N>) -> Result<(), InsufficientBuffer> {
-        xmit.extend_from_slice(&(*self as u16).to_be_bytes())
+        // a single action is identified by its bit position.
+        xmit.push((*self as u16).trailing_zeros() as u8)
             .map_err(|_| InsufficientBuffer)
     }
 }
@@ -792,7 +821,8 @@ impl InputOOBAction {
     }
 
     pub fn emit<const N: usize>(&self, xmit: &mut Vec<u8, N>) -> Result<(), InsufficientBuffer> {
-        xmit.extend_from_slice(&(*self as u16).to_be_bytes())
+        // a single action is identified by its bit position.
+        xmit.push((*self as u16).trailing_zeros() as u8)
             .map_err(|_| InsufficientBuffer)
     }
 }
@@ -974,4 +1004,81 @@ impl ErrorCode {
             _ => Err(ParseError::InvalidValue),
         }
     }
+
+    pub fn emit<const N: usize>(&self, xmit: &mut Vec<u8, N>) -> Result<(), InsufficientBuffer> {
+        xmit.push(self.clone() as u8)
+            .map_err(|_| InsufficientBuffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_round_trip() {
+        let start = Start {
+            algorithm: Algorithm::P256,
+            public_key: PublicKeySelected::NoPublicKey,
+            authentication_method: AuthenticationMethod::OutputOOBAuthentication,
+            authentication_action: OOBAction::Output(OutputOOBAction::OutputNumeric),
+            authentication_size: OOBSize::MaximumSize(4),
+        };
+        let mut bytes = Vec::<u8, 8>::new();
+        ProvisioningPDU::Start(start).emit(&mut bytes).unwrap();
+        assert_eq!(&*bytes, &[0x02, 0x00, 0x00, 0x02, 0x03, 0x04]);
+
+        match ProvisioningPDU::parse(&bytes).unwrap() {
+            ProvisioningPDU::Start(start) => {
+                assert!(matches!(
+                    start.authentication_action,
+                    OOBAction::Output(OutputOOBAction::OutputNumeric)
+                ));
+            }
+            _ => panic!("expected start"),
+        }
+    }
+
+    #[test]
+    fn test_provisioning_data_round_trip() {
+        let data = ProvisioningData {
+            network_key: [0x7d; 16],
+            key_index: NetKeyIndex::new(0x567),
+            key_refresh_flag: KeyRefreshFlag::Phase0,
+            iv_update_flag: IVUpdateFlag::UpdateActive,
+            iv_index: 0x01020304,
+            unicast_address: UnicastAddress::parse([0x0b, 0x0c]).unwrap(),
+        };
+        let mut bytes = Vec::<u8, 25>::new();
+        data.emit(&mut bytes).unwrap();
+        assert_eq!(
+            &bytes[16..],
+            &[0x05, 0x67, 0x02, 0x01, 0x02, 0x03, 0x04, 0x0b, 0x0c]
+        );
+
+        let parsed = ProvisioningData::parse(&bytes).unwrap();
+        assert_eq!(parsed.network_key, [0x7d; 16]);
+        assert_eq!(parsed.key_index, NetKeyIndex::new(0x567));
+        assert!(matches!(parsed.iv_update_flag, IVUpdateFlag::UpdateActive));
+        assert_eq!(parsed.iv_index, 0x01020304);
+        assert_eq!(parsed.unicast_address, data.unicast_address);
+    }
+
+    #[test]
+    fn test_failed_round_trip() {
+        let mut bytes = Vec::<u8, 2>::new();
+        ProvisioningPDU::Failed(Failed {
+            error_code: ErrorCode::ConfirmationFailed,
+        })
+        .emit(&mut bytes)
+        .unwrap();
+        assert_eq!(&*bytes, &[0x09, 0x04]);
+
+        assert!(matches!(
+            ProvisioningPDU::parse(&bytes).unwrap(),
+            ProvisioningPDU::Failed(Failed {
+                error_code: ErrorCode::ConfirmationFailed
+            })
+        ));
+    }
 }
@@ -1,3 +1,4 @@
+use crate::drivers::ble::mesh::InsufficientBuffer;
 use core::future::Future;
 use embedded_storage_async::nor_flash::{AsyncNorFlash, AsyncReadNorFlash};
 /*
@@ -33,9 +34,62 @@ impl Keys {
 }
  */
 
-#[repr(align(4))]
+/// Largest payload kept by a `Storage`.
+pub const MAX_PAYLOAD_SIZE: usize = 1024;
+
+/// A record kept by a `Storage`.
+///
+/// The version describes the format of the data, and is up to the owner of
+/// the payload. Bytes past the data are always zero.
+#[repr(C, align(4))]
 pub struct Payload {
-    pub payload: [u8; 512],
+    data: [u8; MAX_PAYLOAD_SIZE],
+    len: usize,
+    version: u8,
+}
+
+impl Payload {
+    pub fn new(version: u8) -> Self {
+        Self {
+            data: [0; MAX_PAYLOAD_SIZE],
+            len: 0,
+            version,
+        }
+    }
+
+    pub fn from_slice(version: u8, data: &[u8]) -> Result<Self, InsufficientBuffer> {
+        if data.len() > MAX_PAYLOAD_SIZE {
+            return Err(InsufficientBuffer);
+        }
+        let mut payload = Self::new(version);
+        payload.data[0..data.len()].copy_from_slice(data);
+        payload.len = data.len();
+        Ok(payload)
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.data[0..self.len]
+    }
+
+    /// The whole buffer, to serialize into before calling `set_len`.
+    pub fn buffer_mut(&mut self) -> &mut [u8] {
+        &mut self.data
+    }
+
+    /// The data followed by the zeroed remainder of the buffer.
+    pub fn padded(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn set_len(&mut self, len: usize) {
+        let len = len.min(MAX_PAYLOAD_SIZE);
+        self.data[len..].fill(0);
+        self.len = len;
+    }
 }
 
 pub trait Storage {
@@ -52,21 +106,242 @@ pub trait Storage {
     fn retrieve<'m>(&'m mut self) -> Self::RetrieveFuture<'m>;
 }
 
+// "MESH", at the start of every page of the journal.
+const MAGIC: [u8; 4] = *b"MESH";
+// Layout of the journal.
+const FORMAT_VERSION: u32 = 1;
+const PAGE_HEADER_SIZE: usize = 8;
+// Length, version, reserved, sequence and CRC of a record.
+const RECORD_HEADER_SIZE: usize = 12;
+const ERASED_LENGTH: u16 = 0xFFFF;
+// Size of the payload written by the previous, single page, layout.
+const LEGACY_PAYLOAD_SIZE: usize = 512;
+
+#[repr(align(4))]
+struct Aligned<const N: usize>([u8; N]);
+
+fn aligned(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+/// CRC-32 (IEEE 802.3), computed incrementally from `!0` and inverted once done.
+fn crc32(mut crc: u32, data: &[u8]) -> u32 {
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+#[derive(Copy, Clone)]
+struct Head {
+    page: usize,
+    // where the next record goes
+    offset: usize,
+    seq: u32,
+}
+
+#[derive(Copy, Clone)]
+struct Record {
+    page: usize,
+    offset: usize,
+    len: usize,
+    version: u8,
+    seq: u32,
+}
+
+struct Scan {
+    latest: Option<Record>,
+    head: Option<Head>,
+    legacy: bool,
+}
+
 /// Flash storage implementation
+///
+/// Payloads are appended to a journal spread over a number of erase pages,
+/// each record with its own sequence number and CRC, so updates only erase a
+/// page once it is full, and wear is spread across all the pages. The latest
+/// valid record is retrieved, which also survives an interrupted write as long
+/// as more than one page is used.
+///
+/// Records are aligned to 4 bytes, which must be a multiple of the write size of the flash.
 pub struct FlashStorage<F>
 where
     F: AsyncNorFlash + AsyncReadNorFlash,
 {
     address: usize,
+    pages: usize,
     flash: F,
+    head: Option<Head>,
 }
 
 impl<F> FlashStorage<F>
 where
     F: AsyncNorFlash + AsyncReadNorFlash,
 {
+    /// Storage on a single page, at the given address.
     pub fn new(address: usize, flash: F) -> Self {
-        Self { address, flash }
+        Self::with_pages(address, 1, flash)
+    }
+
+    /// Storage spread over a number of consecutive pages, from the given address.
+    pub fn with_pages(address: usize, pages: usize, flash: F) -> Self {
+        Self {
+            address,
+            pages: pages.max(1),
+            flash,
+            head: None,
+        }
+    }
+
+    fn page_address(&self, page: usize) -> u32 {
+        (self.address + page * F::ERASE_SIZE) as u32
+    }
+
+    async fn read(&mut self, address: u32, data: &mut [u8]) -> Result<(), ()> {
+        self.flash.read(address, data).await.map_err(|_| ())
+    }
+
+    async fn is_valid(&mut self, record: &Record, header: &[u8]) -> Result<bool, ()> {
+        let mut crc = crc32(!0, &header[0..8]);
+        let mut address =
+            self.page_address(record.page) + (record.offset + RECORD_HEADER_SIZE) as u32;
+        let mut remaining = record.len;
+        let mut chunk = [0; 32];
+        while remaining > 0 {
+            let len = remaining.min(chunk.len());
+            self.read(address, &mut chunk[0..len]).await?;
+            crc = crc32(crc, &chunk[0..len]);
+            address += len as u32;
+            remaining -= len;
+        }
+        Ok(!crc == u32::from_le_bytes([header[8], header[9], header[10], header[11]]))
+    }
+
+    async fn scan(&mut self) -> Result<Scan, ()> {
+        let mut latest: Option<Record> = None;
+        let mut head = None;
+        // never reuse the sequence of a torn record
+        let mut seq = 0;
+        let mut journaled = false;
+        let mut legacy = false;
+
+        for page in 0..self.pages {
+            let mut header = [0; PAGE_HEADER_SIZE];
+            self.read(self.page_address(page), &mut header).await?;
+            if header[0..4] != MAGIC || header[4..8] != FORMAT_VERSION.to_le_bytes() {
+                // anything but erased flash on the first page was stored by the previous layout.
+                legacy |= page == 0 && header.iter().any(|b| *b != 0xFF);
+                continue;
+            }
+            journaled = true;
+
+            let mut offset = PAGE_HEADER_SIZE;
+            while offset + RECORD_HEADER_SIZE <= F::ERASE_SIZE {
+                let mut header = [0; RECORD_HEADER_SIZE];
+                self.read(self.page_address(page) + offset as u32, &mut header)
+                    .await?;
+                let len = u16::from_le_bytes([header[0], header[1]]);
+                if len == ERASED_LENGTH {
+                    break;
+                }
+                let len = len as usize;
+                let end = offset + RECORD_HEADER_SIZE + aligned(len);
+                if len > MAX_PAYLOAD_SIZE || end > F::ERASE_SIZE {
+                    // corrupted, don't write anything more to this page.
+                    offset = F::ERASE_SIZE;
+                    break;
+                }
+                let record = Record {
+                    page,
+                    offset,
+                    len,
+                    version: header[2],
+                    seq: u32::from_le_bytes([header[4], header[5], header[6], header[7]]),
+                };
+                seq = seq.max(record.seq);
+                let newer = latest.map(|l| record.seq > l.seq).unwrap_or(true);
+                if newer && self.is_valid(&record, &header).await? {
+                    latest.replace(record);
+                }
+                offset = end;
+            }
+
+            if let Some(latest) = latest {
+                if latest.page == page {
+                    head.replace(Head { page, offset, seq });
+                }
+            }
+        }
+
+        if let Some(head) = head.as_mut() {
+            head.seq = seq;
+        }
+
+        Ok(Scan {
+            latest,
+            head,
+            legacy: legacy && !journaled,
+        })
+    }
+
+    async fn write(&mut self, payload: &Payload) -> Result<Head, ()> {
+        let head = match self.head {
+            Some(head) => head,
+            // start over on the first page.
+            None => self.scan().await?.head.unwrap_or(Head {
+                page: self.pages - 1,
+                offset: F::ERASE_SIZE,
+                seq: 0,
+            }),
+        };
+
+        let size = RECORD_HEADER_SIZE + aligned(payload.len);
+        let (page, offset) = if head.offset + size <= F::ERASE_SIZE {
+            (head.page, head.offset)
+        } else {
+            let page = (head.page + 1) % self.pages;
+            let address = self.page_address(page);
+            self.flash
+                .erase(address, address + F::ERASE_SIZE as u32)
+                .await
+                .map_err(|_| ())?;
+            let mut header = Aligned([0; PAGE_HEADER_SIZE]);
+            header.0[0..4].copy_from_slice(&MAGIC);
+            header.0[4..8].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+            self.flash.write(address, &header.0).await.map_err(|_| ())?;
+            (page, PAGE_HEADER_SIZE)
+        };
+
+        let seq = head.seq + 1;
+        let mut header = Aligned([0; RECORD_HEADER_SIZE]);
+        header.0[0..2].copy_from_slice(&(payload.len as u16).to_le_bytes());
+        header.0[2] = payload.version;
+        header.0[4..8].copy_from_slice(&seq.to_le_bytes());
+        let crc = !crc32(crc32(!0, &header.0[0..8]), payload.as_slice());
+        header.0[8..12].copy_from_slice(&crc.to_le_bytes());
+
+        let address = self.page_address(page) + offset as u32;
+        self.flash.write(address, &header.0).await.map_err(|_| ())?;
+        self.flash
+            .write(
+                address + RECORD_HEADER_SIZE as u32,
+                &payload.data[0..aligned(payload.len)],
+            )
+            .await
+            .map_err(|_| ())?;
+
+        Ok(Head {
+            page,
+            offset: offset + size,
+            seq,
+        })
     }
 }
 
@@ -78,16 +353,19 @@ where
     where
         Self: 'm;
 
-    fn store<'m>(&'m mut self, keys: &'m Payload) -> Self::StoreFuture<'m> {
+    fn store<'m>(&'m mut self, payload: &'m Payload) -> Self::StoreFuture<'m> {
         async move {
-            self.flash
-                .erase(self.address as u32, self.address as u32 + 4096)
-                .await
-                .map_err(|_| ())?;
-            self.flash
-                .write(self.address as u32, &keys.payload)
-                .await
-                .map_err(|_| ())
+            match self.write(payload).await {
+                Ok(head) => {
+                    self.head.replace(head);
+                    Ok(())
+                }
+                Err(e) => {
+                    // find out where we stand on the next attempt.
+                    self.head.take();
+                    Err(e)
+                }
+            }
         }
     }
 
@@ -97,12 +375,176 @@ where
 
     fn retrieve<'m>(&'m mut self) -> Self::RetrieveFuture<'m> {
         async move {
-            let mut payload = [0; 512];
-            self.flash
-                .read(self.address as u32, &mut payload)
-                .await
-                .map_err(|_| ())?;
-            Ok(Some(Payload { payload }))
+            let scan = self.scan().await?;
+            self.head = scan.head;
+            if let Some(record) = scan.latest {
+                let mut payload = Payload::new(record.version);
+                let address =
+                    self.page_address(record.page) + (record.offset + RECORD_HEADER_SIZE) as u32;
+                self.read(address, &mut payload.data[0..record.len]).await?;
+                payload.len = record.len;
+                Ok(Some(payload))
+            } else if scan.legacy {
+                let mut payload = Payload::new(0);
+                let address = self.page_address(0);
+                self.read(address, &mut payload.data[0..LEGACY_PAYLOAD_SIZE])
+                    .await?;
+                payload.len = LEGACY_PAYLOAD_SIZE;
+                Ok(Some(payload))
+            } else {
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_storage::nor_flash::{ErrorType, NorFlashErrorKind};
+    use futures::executor::block_on;
+
+    const PAGE_SIZE: usize = 4096;
+    const PAGES: usize = 3;
+
+    struct MemoryFlash {
+        data: [u8; PAGE_SIZE * PAGES],
+        erases: [usize; PAGES],
+    }
+
+    impl MemoryFlash {
+        fn new() -> Self {
+            Self {
+                data: [0xFF; PAGE_SIZE * PAGES],
+                erases: [0; PAGES],
+            }
+        }
+    }
+
+    impl ErrorType for &mut MemoryFlash {
+        type Error = NorFlashErrorKind;
+    }
+
+    impl AsyncReadNorFlash for &mut MemoryFlash {
+        const READ_SIZE: usize = 1;
+
+        type ReadFuture<'m> = impl Future<Output = Result<(), Self::Error>> + 'm where Self: 'm;
+        fn read<'m>(&'m mut self, offset: u32, bytes: &'m mut [u8]) -> Self::ReadFuture<'m> {
+            async move {
+                let offset = offset as usize;
+                bytes.copy_from_slice(&self.data[offset..offset + bytes.len()]);
+                Ok(())
+            }
+        }
+
+        fn capacity(&self) -> usize {
+            self.data.len()
+        }
+    }
+
+    impl AsyncNorFlash for &mut MemoryFlash {
+        const WRITE_SIZE: usize = 4;
+        const ERASE_SIZE: usize = PAGE_SIZE;
+
+        type EraseFuture<'m> = impl Future<Output = Result<(), Self::Error>> + 'm where Self: 'm;
+        fn erase<'m>(&'m mut self, from: u32, to: u32) -> Self::EraseFuture<'m> {
+            async move {
+                self.data[from as usize..to as usize].fill(0xFF);
+                self.erases[from as usize / PAGE_SIZE] += 1;
+                Ok(())
+            }
+        }
+
+        type WriteFuture<'m> = impl Future<Output = Result<(), Self::Error>> + 'm where Self: 'm;
+        fn write<'m>(&'m mut self, offset: u32, bytes: &'m [u8]) -> Self::WriteFuture<'m> {
+            async move {
+                if offset as usize % 4 != 0 || bytes.len() % 4 != 0 {
+                    return Err(NorFlashErrorKind::NotAligned);
+                }
+                for (i, byte) in bytes.iter().enumerate() {
+                    // NOR flash only clears bits
+                    self.data[offset as usize + i] &= *byte;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        let mut flash = MemoryFlash::new();
+        let mut storage = FlashStorage::with_pages(0, PAGES, &mut flash);
+        assert!(block_on(storage.retrieve()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_latest_record_survives_reboot() {
+        let mut flash = MemoryFlash::new();
+        let mut storage = FlashStorage::with_pages(0, PAGES, &mut flash);
+        for i in 0..100u32 {
+            let payload = Payload::from_slice(1, &i.to_le_bytes()[0..3]).unwrap();
+            block_on(storage.store(&payload)).unwrap();
         }
+
+        let mut storage = FlashStorage::with_pages(0, PAGES, &mut flash);
+        let payload = block_on(storage.retrieve()).unwrap().unwrap();
+        assert_eq!(1, payload.version());
+        assert_eq!(&99u32.to_le_bytes()[0..3], payload.as_slice());
+        assert_eq!(&[0; 4], &payload.padded()[3..7]);
+
+        block_on(storage.store(&Payload::from_slice(2, &[0xAA; 700]).unwrap())).unwrap();
+        let payload = block_on(storage.retrieve()).unwrap().unwrap();
+        assert_eq!(2, payload.version());
+        assert_eq!(&[0xAA; 700][..], payload.as_slice());
+    }
+
+    #[test]
+    fn test_wear_levelling() {
+        let mut flash = MemoryFlash::new();
+        let mut storage = FlashStorage::with_pages(0, PAGES, &mut flash);
+        let payload = Payload::from_slice(1, &[0x55; 496]).unwrap();
+        for _ in 0..(8 * 3 * PAGES) {
+            block_on(storage.store(&payload)).unwrap();
+        }
+        // 8 records of 508 bytes fit a page.
+        assert_eq!([3, 3, 3], flash.erases);
+    }
+
+    #[test]
+    fn test_torn_write_keeps_previous_record() {
+        let mut flash = MemoryFlash::new();
+        let mut storage = FlashStorage::with_pages(0, PAGES, &mut flash);
+        block_on(storage.store(&Payload::from_slice(1, &[0x01; 16]).unwrap())).unwrap();
+        block_on(storage.store(&Payload::from_slice(1, &[0x02; 16]).unwrap())).unwrap();
+
+        // lose the end of the last record.
+        let end = PAGE_HEADER_SIZE + 2 * (RECORD_HEADER_SIZE + 16);
+        flash.data[end - 4..end].fill(0xFF);
+
+        let mut storage = FlashStorage::with_pages(0, PAGES, &mut flash);
+        let payload = block_on(storage.retrieve()).unwrap().unwrap();
+        assert_eq!(&[0x01; 16], payload.as_slice());
+
+        block_on(storage.store(&Payload::from_slice(1, &[0x03; 16]).unwrap())).unwrap();
+        let payload = block_on(storage.retrieve()).unwrap().unwrap();
+        assert_eq!(&[0x03; 16], payload.as_slice());
+    }
+
+    #[test]
+    fn test_legacy_layout() {
+        let mut flash = MemoryFlash::new();
+        flash.data[0..LEGACY_PAYLOAD_SIZE].fill(0);
+        flash.data[0] = 0x2A;
+
+        let mut storage = FlashStorage::with_pages(0, PAGES, &mut flash);
+        let payload = block_on(storage.retrieve()).unwrap().unwrap();
+        assert_eq!(0, payload.version());
+        assert_eq!(LEGACY_PAYLOAD_SIZE, payload.as_slice().len());
+        assert_eq!(0x2A, payload.as_slice()[0]);
+
+        block_on(storage.store(&Payload::from_slice(1, &[0x01]).unwrap())).unwrap();
+        let payload = block_on(storage.retrieve()).unwrap().unwrap();
+        assert_eq!(1, payload.version());
+        assert_eq!(&[0x01], payload.as_slice());
     }
 }
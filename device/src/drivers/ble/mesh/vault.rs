@@ -12,13 +12,16 @@ use p256::PublicKey;
 use crate::drivers::ble::mesh::address::UnicastAddress;
 use crate::drivers::ble::mesh::app::ApplicationKeyIdentifier;
 use crate::drivers::ble::mesh::config::configuration_manager::ConfigurationManager;
-use crate::drivers::ble::mesh::config::network::{Network, NetworkDetails};
+use crate::drivers::ble::mesh::config::network::{Network, NetworkDetails, NetworkKeyHandle};
 use crate::drivers::ble::mesh::config::Configuration;
 use crate::drivers::ble::mesh::crypto::nonce::{ApplicationNonce, DeviceNonce};
 use crate::drivers::ble::mesh::device::Uuid;
+use crate::drivers::ble::mesh::driver::node::NodeIdentity;
 use crate::drivers::ble::mesh::driver::DeviceError;
+use crate::drivers::ble::mesh::model::foundation::configuration::NetKeyIndex;
 use crate::drivers::ble::mesh::provisioning::ProvisioningData;
 use crate::drivers::ble::mesh::storage::Storage;
+use heapless::Vec;
 
 pub trait Vault {
     fn uuid(&self) -> Uuid;
@@ -121,6 +124,12 @@ pub trait Vault {
     ) -> Result<(), DeviceError>;
 
     fn primary_unicast_address(&self) -> Option<UnicastAddress>;
+
+    fn node_identity(
+        &self,
+        net_key_index: &NetKeyIndex,
+        random: [u8; 8],
+    ) -> Result<NodeIdentity, DeviceError>;
 }
 
 pub struct StorageVault<'c, S: Storage> {
@@ -198,16 +207,10 @@ impl<'c, S: Storage + 'c> Vault for StorageVault<'c, S> {
         data: &'m ProvisioningData,
     ) -> Self::SetProvisioningDataFuture<'m> {
         async move {
-            let (nid, encryption_key, privacy_key) = crypto::k2(&data.network_key, &[0x00])
-                .map_err(|_| DeviceError::KeyInitialization)?;
-
-            let primary_network_details = NetworkDetails::new(
-                data.network_key.into(),
-                data.key_index,
-                nid,
-                encryption_key,
-                privacy_key,
-            );
+            let primary_network_details: NetworkDetails =
+                NetworkKeyHandle::new(data.network_key.into(), data.key_index)
+                    .map_err(|_| DeviceError::KeyInitialization)?
+                    .into();
 
             self.configuration_manager
                 .update_configuration(|config| {
@@ -281,9 +284,12 @@ impl<'c, S: Storage + 'c> Vault for StorageVault<'c, S> {
         additional_data: Option<&[u8]>,
     ) -> Result<(), DeviceError> {
         if let Some(network) = self.config().network() {
-            if let Some(app_key) = network.find_app_key_by_aid(aid) {
+            if let Some(app_key) = network
+                .find_app_key_by_aid(aid)
+                .and_then(|e| e.keys_by_aid(aid).next())
+            {
                 return crypto::aes_ccm_encrypt_detached(
-                    app_key.key.as_ref(),
+                    app_key.as_ref(),
                     &*nonce,
                     bytes,
                     mic,
@@ -306,14 +312,21 @@ impl<'c, S: Storage + 'c> Vault for StorageVault<'c, S> {
     ) -> Result<(), DeviceError> {
         if let Some(network) = self.config().network() {
             if let Some(app_key) = network.find_app_key_by_aid(aid) {
-                if let Ok(_) = crypto::aes_ccm_decrypt_detached(
-                    app_key.key.as_ref(),
-                    &*nonce,
-                    bytes,
-                    mic,
-                    additional_data,
-                ) {
-                    return Ok(());
+                // during key refresh both the old and new key may match the aid,
+                // so decrypt a copy in case this is the wrong key.
+                for key in app_key.keys_by_aid(aid) {
+                    let mut attempt: Vec<u8, 384> =
+                        Vec::from_slice(bytes).map_err(|_| DeviceError::InsufficientBuffer)?;
+                    if let Ok(_) = crypto::aes_ccm_decrypt_detached(
+                        key.as_ref(),
+                        &*nonce,
+                        &mut attempt,
+                        mic,
+                        additional_data,
+                    ) {
+                        bytes.copy_from_slice(&attempt);
+                        return Ok(());
+                    }
                 }
             }
         }
@@ -328,4 +341,24 @@ impl<'c, S: Storage + 'c> Vault for StorageVault<'c, S> {
             None
         }
     }
+
+    fn node_identity(
+        &self,
+        net_key_index: &NetKeyIndex,
+        random: [u8; 8],
+    ) -> Result<NodeIdentity, DeviceError> {
+        if let Some(network) = self.config().network() {
+            let identity_key = network
+                .find_by_net_key_index(net_key_index)?
+                .identity_key()?;
+            let hash = crypto::node_identity_hash(
+                &identity_key,
+                &random,
+                network.unicast_address().as_bytes(),
+            )?;
+            Ok(NodeIdentity { hash, random })
+        } else {
+            Err(DeviceError::NotProvisioned)
+        }
+    }
 }
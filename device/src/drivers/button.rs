@@ -4,6 +4,9 @@ use core::marker::PhantomData;
 use embedded_hal::digital::v2::InputPin;
 use embedded_hal_async::digital::Wait;
 
+#[cfg(feature = "time")]
+use embassy::time::{Duration, Timer};
+
 pub use crate::drivers::ActiveHigh;
 pub use crate::drivers::ActiveLow;
 
@@ -122,3 +125,96 @@ where
         Button::wait_any(self)
     }
 }
+
+/// A button ignoring changes of level that last less than the debounce time.
+#[cfg(feature = "time")]
+pub struct DebouncedButton<P, ACTIVE = ActiveLow>
+where
+    P: Wait + InputPin + 'static,
+    ACTIVE: Active,
+{
+    pin: P,
+    debounce: Duration,
+    pressed: bool,
+    _marker: PhantomData<ACTIVE>,
+}
+
+#[cfg(feature = "time")]
+impl<P, ACTIVE> DebouncedButton<P, ACTIVE>
+where
+    P: Wait + InputPin + 'static,
+    ACTIVE: Active,
+{
+    pub fn new(pin: P, debounce: Duration) -> Self {
+        let pressed = ACTIVE::is_pressed(&pin).unwrap_or(false);
+        Self {
+            pin,
+            debounce,
+            pressed,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    /// Wait for the level to settle on the other state than the last one reported.
+    pub async fn wait_any(&mut self) -> Event {
+        loop {
+            self.pin.wait_for_any_edge().await.unwrap();
+            Timer::after(self.debounce).await;
+            let pressed = ACTIVE::is_pressed(&self.pin).unwrap_or(self.pressed);
+            if pressed != self.pressed {
+                self.pressed = pressed;
+                return if pressed {
+                    Event::Pressed
+                } else {
+                    Event::Released
+                };
+            }
+        }
+    }
+
+    pub async fn wait_pressed(&mut self) {
+        while self.wait_any().await != Event::Pressed {}
+    }
+
+    pub async fn wait_released(&mut self) {
+        while self.wait_any().await != Event::Released {}
+    }
+}
+
+#[cfg(feature = "time")]
+impl<P, ACTIVE> crate::traits::button::Button for DebouncedButton<P, ACTIVE>
+where
+    P: Wait + InputPin + 'static,
+    ACTIVE: Active,
+{
+    type WaitPressed<'m> = impl Future<Output = ()> + 'm where Self: 'm;
+
+    fn wait_pressed<'m>(&'m mut self) -> Self::WaitPressed<'m>
+    where
+        Self: 'm,
+    {
+        DebouncedButton::wait_pressed(self)
+    }
+
+    type WaitReleased<'m> = impl Future<Output = ()> + 'm where Self: 'm;
+
+    fn wait_released<'m>(&'m mut self) -> Self::WaitReleased<'m>
+    where
+        Self: 'm,
+    {
+        DebouncedButton::wait_released(self)
+    }
+
+    type WaitAny<'m> = impl Future<Output = Event> + 'm where Self: 'm;
+
+    fn wait_any<'m>(&'m mut self) -> Self::WaitAny<'m>
+    where
+        Self: 'm,
+    {
+        DebouncedButton::wait_any(self)
+    }
+}
@@ -1,119 +1,266 @@
-use crate::traits::led::ToFrame;
+use crate::traits::led::{ToFrame, ToGrayFrame};
 use crate::{domain::led::matrix::*, traits::led::TextDisplay};
 use core::convert::Infallible;
 use core::future::Future;
 use embassy::time::{block_for, Duration, Instant, Timer};
 use embedded_hal::digital::v2::OutputPin;
 
-const REFRESH_INTERVAL: Duration = Duration::from_micros(500);
+// How long each row is lit, split in one slot per brightness level.
+const ROW_TIME: Duration = Duration::from_micros(1000);
 
-// Led matrix driver supporting up to 32x32 led matrices.
-pub struct LedMatrix<P, const ROWS: usize, const COLS: usize>
+// Longest time a row stays dark before being lit, at the lowest brightness.
+const MAX_DARK_TIME_MICROS: u64 = 6000;
+
+/// Drives the rows and columns of a led matrix, one row at a time.
+pub trait MatrixBackend<const ROWS: usize, const COLS: usize> {
+    /// Turn every row off.
+    fn rows_off(&mut self);
+
+    /// Turn a row on, lighting its leds whose column is set.
+    fn row_on(&mut self, row: usize);
+
+    /// Set whether the led of a column is lit when its row is on.
+    fn set_column(&mut self, col: usize, lit: bool);
+
+    /// Dim the leds in hardware, for instance with PWM on the columns.
+    ///
+    /// Returns false if unsupported, in which case the brightness is made by
+    /// keeping each row dark for a while before lighting it.
+    fn set_brightness(&mut self, _brightness: Brightness) -> bool {
+        false
+    }
+}
+
+/// Rows and columns driven by GPIO, rows active high and columns active low.
+pub struct OutputPins<P, const ROWS: usize, const COLS: usize>
 where
-    P: OutputPin + 'static,
+    P: OutputPin,
 {
     pin_rows: [P; ROWS],
     pin_cols: [P; COLS],
-    frame_buffer: Frame<COLS, ROWS>,
-    row_p: usize,
-    brightness: Brightness,
 }
 
-impl<P, const ROWS: usize, const COLS: usize> LedMatrix<P, ROWS, COLS>
+impl<P, const ROWS: usize, const COLS: usize> OutputPins<P, ROWS, COLS>
 where
     P: OutputPin,
 {
     pub fn new(pin_rows: [P; ROWS], pin_cols: [P; COLS]) -> Self {
-        LedMatrix {
-            pin_rows,
-            pin_cols,
-            frame_buffer: Frame::empty(),
-            row_p: 0,
-            brightness: Default::default(),
-        }
+        Self { pin_rows, pin_cols }
     }
+}
 
-    pub fn clear(&mut self) {
-        self.frame_buffer.clear();
+impl<P, const ROWS: usize, const COLS: usize> MatrixBackend<ROWS, COLS>
+    for OutputPins<P, ROWS, COLS>
+where
+    P: OutputPin,
+{
+    fn rows_off(&mut self) {
         for row in self.pin_rows.iter_mut() {
-            row.set_high().ok();
-        }
-
-        for col in self.pin_cols.iter_mut() {
-            col.set_high().ok();
+            row.set_low().ok();
         }
     }
 
-    pub fn on(&mut self, x: usize, y: usize) {
-        self.frame_buffer.set(x, y);
+    fn row_on(&mut self, row: usize) {
+        self.pin_rows[row].set_high().ok();
     }
 
-    pub fn off(&mut self, x: usize, y: usize) {
-        self.frame_buffer.unset(x, y);
+    fn set_column(&mut self, col: usize, lit: bool) {
+        if lit {
+            self.pin_cols[col].set_low().ok();
+        } else {
+            self.pin_cols[col].set_high().ok();
+        }
     }
+}
 
-    pub fn apply(&mut self, frame: Frame<COLS, ROWS>) {
-        self.frame_buffer = frame;
+// Led matrix driver supporting up to 32x32 led matrices.
+pub struct LedMatrix<B, const ROWS: usize, const COLS: usize>
+where
+    B: MatrixBackend<ROWS, COLS>,
+{
+    backend: B,
+    frame_buffer: GrayFrame<COLS, ROWS>,
+    row_p: usize,
+    brightness: Brightness,
+    hardware_brightness: bool,
+}
+
+impl<P, const ROWS: usize, const COLS: usize> LedMatrix<OutputPins<P, ROWS, COLS>, ROWS, COLS>
+where
+    P: OutputPin,
+{
+    pub fn new(pin_rows: [P; ROWS], pin_cols: [P; COLS]) -> Self {
+        Self::with_backend(OutputPins::new(pin_rows, pin_cols))
     }
 
     pub fn set_row_high(&mut self, row: usize) {
-        self.pin_rows[row].set_high().ok().unwrap();
+        self.backend.pin_rows[row].set_high().ok().unwrap();
     }
 
     pub fn set_row_low(&mut self, row: usize) {
-        self.pin_rows[row].set_low().ok().unwrap();
+        self.backend.pin_rows[row].set_low().ok().unwrap();
     }
 
     pub fn set_col_high(&mut self, col: usize) {
-        self.pin_cols[col].set_high().ok().unwrap();
+        self.backend.pin_cols[col].set_high().ok().unwrap();
     }
 
     pub fn set_col_low(&mut self, col: usize) {
-        self.pin_cols[col].set_low().ok().unwrap();
+        self.backend.pin_cols[col].set_low().ok().unwrap();
+    }
+}
+
+impl<B, const ROWS: usize, const COLS: usize> LedMatrix<B, ROWS, COLS>
+where
+    B: MatrixBackend<ROWS, COLS>,
+{
+    pub fn with_backend(mut backend: B) -> Self {
+        let brightness = Default::default();
+        let hardware_brightness = backend.set_brightness(brightness);
+        LedMatrix {
+            backend,
+            frame_buffer: GrayFrame::empty(),
+            row_p: 0,
+            brightness,
+            hardware_brightness,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.frame_buffer.clear();
+        for col in 0..COLS {
+            self.backend.set_column(col, false);
+        }
+    }
+
+    pub fn on(&mut self, x: usize, y: usize) {
+        self.frame_buffer.set(x, y, Brightness::MAX);
+    }
+
+    pub fn off(&mut self, x: usize, y: usize) {
+        self.frame_buffer.set(x, y, Brightness::MIN);
+    }
+
+    /// Set the level of a single led, on top of the brightness of the whole matrix.
+    pub fn set_level(&mut self, x: usize, y: usize, level: Brightness) {
+        self.frame_buffer.set(x, y, level);
+    }
+
+    pub fn apply(&mut self, frame: Frame<COLS, ROWS>) {
+        self.frame_buffer = frame.into();
+    }
+
+    pub fn apply_gray(&mut self, frame: GrayFrame<COLS, ROWS>) {
+        self.frame_buffer = frame;
     }
 
     pub fn set_brightness(&mut self, brightness: Brightness) {
         self.brightness = brightness;
+        self.hardware_brightness = self.backend.set_brightness(brightness);
     }
 
     pub fn increase_brightness(&mut self) {
-        self.brightness += 1;
+        let mut brightness = self.brightness;
+        brightness += 1;
+        self.set_brightness(brightness);
     }
 
     pub fn decrease_brightness(&mut self) {
-        self.brightness -= 1;
+        let mut brightness = self.brightness;
+        brightness -= 1;
+        self.set_brightness(brightness);
     }
 
-    pub fn render(&mut self) {
-        for row in self.pin_rows.iter_mut() {
-            row.set_low().ok();
+    // Adjust interval will impact brightness of the LEDs
+    fn dark_time(&self) -> Duration {
+        if self.hardware_brightness {
+            Duration::from_ticks(0)
+        } else {
+            Duration::from_micros(
+                ((Brightness::MAX.level() - self.brightness.level()) as u64) * MAX_DARK_TIME_MICROS
+                    / Brightness::MAX.level() as u64,
+            )
         }
+    }
 
-        for (cid, col) in self.pin_cols.iter_mut().enumerate() {
-            if self.frame_buffer.is_set(cid, self.row_p) {
-                col.set_low().ok();
-            } else {
-                col.set_high().ok();
+    // Levels are shown by PWM within the time a row is lit: every led of
+    // the row is lit at first, a led at level n is turned off after n out of
+    // `Brightness::MAX` slots, and those at the highest level stay lit until
+    // the next row is.
+    fn next_row(&mut self) -> usize {
+        self.backend.rows_off();
+        for col in 0..COLS {
+            let level = self.frame_buffer.get(col, self.row_p).level();
+            self.backend.set_column(col, level > 0);
+        }
+        let row = self.row_p;
+        self.row_p = (self.row_p + 1) % ROWS;
+        row
+    }
+
+    /// The next level at which leds of the row are turned off, past `level`.
+    fn next_level(&self, row: usize, level: u8) -> Option<u8> {
+        (0..COLS)
+            .map(|col| self.frame_buffer.get(col, row).level())
+            .filter(|l| *l > level && *l < Brightness::MAX.level())
+            .min()
+    }
+
+    fn dim(&mut self, row: usize, level: u8) {
+        for col in 0..COLS {
+            if self.frame_buffer.get(col, row).level() == level {
+                self.backend.set_column(col, false);
             }
         }
+    }
 
-        // Adjust interval will impact brightness of the LEDs
-        block_for(Duration::from_micros(
-            ((Brightness::MAX.level() - self.brightness.level()) as u64) * 6000
-                / Brightness::MAX.level() as u64,
-        ));
+    fn slots(levels: u8) -> Duration {
+        ROW_TIME * levels as u32 / Brightness::MAX.level() as u32
+    }
 
-        self.pin_rows[self.row_p].set_high().ok();
+    /// Light the next row for its time, busy-waiting.
+    ///
+    /// This blocks the executor for 1ms, and up to 6ms more at low brightness,
+    /// prefer `refresh`.
+    pub fn render(&mut self) {
+        let row = self.next_row();
+        block_for(self.dark_time());
+        self.backend.row_on(row);
+        let mut level = 0;
+        while let Some(next) = self.next_level(row, level) {
+            block_for(Self::slots(next - level));
+            self.dim(row, next);
+            level = next;
+        }
+        block_for(Self::slots(Brightness::MAX.level() - level));
+    }
 
-        self.row_p = (self.row_p + 1) % self.pin_rows.len();
+    /// Light the next row for its time, letting other tasks run meanwhile.
+    pub async fn refresh(&mut self) {
+        let row = self.next_row();
+        let dark_time = self.dark_time();
+        if dark_time.as_ticks() > 0 {
+            Timer::after(dark_time).await;
+        }
+        self.backend.row_on(row);
+        let mut level = 0;
+        while let Some(next) = self.next_level(row, level) {
+            Timer::after(Self::slots(next - level)).await;
+            self.dim(row, next);
+            level = next;
+        }
+        Timer::after(Self::slots(Brightness::MAX.level() - level)).await;
     }
 
     pub async fn display(&mut self, frame: Frame<COLS, ROWS>, length: Duration) {
-        self.apply(frame);
+        self.display_gray(frame.into(), length).await;
+    }
+
+    pub async fn display_gray(&mut self, frame: GrayFrame<COLS, ROWS>, length: Duration) {
+        self.apply_gray(frame);
         let end = Instant::now() + length;
         while Instant::now() < end {
-            self.render();
-            Timer::after(REFRESH_INTERVAL).await;
+            self.refresh().await;
         }
         self.clear();
     }
@@ -129,22 +276,8 @@ where
     }
 
     pub async fn animate(&mut self, data: &[u8], effect: AnimationEffect, duration: Duration) {
-        let mut animation: Animation<'_, COLS, ROWS> =
-            Animation::new(AnimationData::Bytes(data), effect, duration).unwrap();
-        loop {
-            match animation.next(Instant::now()) {
-                AnimationState::Apply(f) => {
-                    self.apply(f);
-                }
-                AnimationState::Wait => {}
-                AnimationState::Done => {
-                    break;
-                }
-            }
-            self.render();
-            Timer::after(REFRESH_INTERVAL).await;
-        }
-        self.clear();
+        let animation = Animation::new(AnimationData::Bytes(data), effect, duration).unwrap();
+        self.run(animation).await;
     }
 
     pub async fn animate_frames(
@@ -153,28 +286,127 @@ where
         effect: AnimationEffect,
         duration: Duration,
     ) {
-        let mut animation: Animation<'_, COLS, ROWS> =
-            Animation::new(AnimationData::Frames(data), effect, duration).unwrap();
+        let animation = Animation::new(AnimationData::Frames(data), effect, duration).unwrap();
+        self.run(animation).await;
+    }
+
+    pub async fn animate_gray_frames(
+        &mut self,
+        data: &[&dyn ToGrayFrame<COLS, ROWS>],
+        effect: AnimationEffect,
+        duration: Duration,
+    ) {
+        let animation = Animation::new(AnimationData::GrayFrames(data), effect, duration).unwrap();
+        self.run(animation).await;
+    }
+
+    async fn run(&mut self, mut animation: Animation<'_, COLS, ROWS>) {
         loop {
             match animation.next(Instant::now()) {
                 AnimationState::Apply(f) => {
-                    self.apply(f);
+                    self.apply_gray(f);
                 }
                 AnimationState::Wait => {}
                 AnimationState::Done => {
                     break;
                 }
             }
-            self.render();
-            Timer::after(REFRESH_INTERVAL).await;
+            self.refresh().await;
         }
         self.clear();
     }
 }
 
-impl<P, const ROWS: usize, const COLS: usize> TextDisplay for LedMatrix<P, ROWS, COLS>
+#[cfg(feature = "embedded-graphics")]
+impl<B, const ROWS: usize, const COLS: usize> LedMatrix<B, ROWS, COLS>
 where
-    P: OutputPin + 'static,
+    B: MatrixBackend<ROWS, COLS>,
+{
+    /// Scroll text in the given font across the matrix, taking `speed` overall.
+    pub async fn scroll_with_font(
+        &mut self,
+        text: &str,
+        font: &embedded_graphics::mono_font::MonoFont<'_>,
+        speed: Duration,
+    ) {
+        use embedded_graphics::{
+            mono_font::MonoTextStyle,
+            pixelcolor::BinaryColor,
+            prelude::*,
+            text::{Baseline, Text},
+        };
+
+        let style = MonoTextStyle::new(font, BinaryColor::On);
+        let advance = font.character_size.width + font.character_spacing;
+        let width = (text.chars().count() as u32 * advance) as usize;
+        // from entering on the right until gone on the left
+        let steps = width + COLS;
+        let wait = speed.checked_div(steps as u32).unwrap_or(ROW_TIME);
+
+        let mut step = 0;
+        let mut next = Instant::now();
+        while step <= steps {
+            if next <= Instant::now() {
+                let mut frame: Frame<COLS, ROWS> = Frame::empty();
+                let position = Point::new(COLS as i32 - step as i32, 0);
+                Text::with_baseline(text, position, style, Baseline::Top)
+                    .draw(&mut frame)
+                    .ok();
+                self.apply(frame);
+                step += 1;
+                next += wait;
+            }
+            self.refresh().await;
+        }
+        self.clear();
+    }
+}
+
+#[cfg(feature = "embedded-graphics")]
+impl<B, const ROWS: usize, const COLS: usize> embedded_graphics::geometry::OriginDimensions
+    for LedMatrix<B, ROWS, COLS>
+where
+    B: MatrixBackend<ROWS, COLS>,
+{
+    fn size(&self) -> embedded_graphics::geometry::Size {
+        embedded_graphics::geometry::Size::new(COLS as u32, ROWS as u32)
+    }
+}
+
+/// Draw into the frame buffer, which is shown at the next render.
+#[cfg(feature = "embedded-graphics")]
+impl<B, const ROWS: usize, const COLS: usize> embedded_graphics::draw_target::DrawTarget
+    for LedMatrix<B, ROWS, COLS>
+where
+    B: MatrixBackend<ROWS, COLS>,
+{
+    type Color = embedded_graphics::pixelcolor::BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = embedded_graphics::Pixel<Self::Color>>,
+    {
+        for embedded_graphics::Pixel(point, color) in pixels {
+            if point.x >= 0
+                && point.y >= 0
+                && (point.x as usize) < COLS
+                && (point.y as usize) < ROWS
+            {
+                if color.is_on() {
+                    self.on(point.x as usize, point.y as usize);
+                } else {
+                    self.off(point.x as usize, point.y as usize);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<B, const ROWS: usize, const COLS: usize> TextDisplay for LedMatrix<B, ROWS, COLS>
+where
+    B: MatrixBackend<ROWS, COLS>,
 {
     type Error = Infallible;
     type ScrollFuture<'m> = impl Future<Output = Result<(), Self::Error>> + 'm where Self: 'm;
@@ -214,14 +446,35 @@ impl<const XSIZE: usize, const YSIZE: usize> ToFrame<XSIZE, YSIZE> for Frame<XSI
     }
 }
 
+impl<const XSIZE: usize, const YSIZE: usize> ToGrayFrame<XSIZE, YSIZE> for GrayFrame<XSIZE, YSIZE> {
+    fn to_gray_frame(&self) -> GrayFrame<XSIZE, YSIZE> {
+        *self
+    }
+}
+
+// Steps of a fade from one frame to the next.
+const FADE_STEPS: usize = Brightness::MAX.level() as usize;
+
 #[derive(Clone, Copy)]
 pub enum AnimationEffect {
     None,
     Slide,
+    Fade,
+}
+
+impl AnimationEffect {
+    fn steps<const XSIZE: usize>(&self) -> usize {
+        match self {
+            AnimationEffect::None => 1,
+            AnimationEffect::Slide => XSIZE,
+            AnimationEffect::Fade => FADE_STEPS,
+        }
+    }
 }
 
 pub enum AnimationData<'a, const XSIZE: usize, const YSIZE: usize> {
     Frames(&'a [&'a dyn ToFrame<XSIZE, YSIZE>]),
+    GrayFrames(&'a [&'a dyn ToGrayFrame<XSIZE, YSIZE>]),
     Bytes(&'a [u8]),
 }
 
@@ -229,14 +482,16 @@ impl<'a, const XSIZE: usize, const YSIZE: usize> AnimationData<'a, XSIZE, YSIZE>
     fn len(&self) -> usize {
         match self {
             AnimationData::Frames(f) => f.len(),
+            AnimationData::GrayFrames(f) => f.len(),
             AnimationData::Bytes(f) => f.len(),
         }
     }
 
-    fn frame(&self, idx: usize) -> Frame<XSIZE, YSIZE> {
+    fn frame(&self, idx: usize) -> GrayFrame<XSIZE, YSIZE> {
         match self {
-            AnimationData::Frames(f) => f[idx].to_frame(),
-            AnimationData::Bytes(f) => f[idx].to_frame(),
+            AnimationData::Frames(f) => f[idx].to_gray_frame(),
+            AnimationData::GrayFrames(f) => f[idx].to_gray_frame(),
+            AnimationData::Bytes(f) => f[idx].to_gray_frame(),
         }
     }
 }
@@ -255,7 +510,7 @@ pub struct Animation<'a, const XSIZE: usize, const YSIZE: usize> {
 #[derive(PartialEq, Debug)]
 pub enum AnimationState<const XSIZE: usize, const YSIZE: usize> {
     Wait,
-    Apply(Frame<XSIZE, YSIZE>),
+    Apply(GrayFrame<XSIZE, YSIZE>),
     Done,
 }
 
@@ -266,10 +521,7 @@ impl<'a, const XSIZE: usize, const YSIZE: usize> Animation<'a, XSIZE, YSIZE> {
         duration: Duration,
     ) -> Result<Self, AnimationError> {
         assert!(frames.len() > 0);
-        let length = match effect {
-            AnimationEffect::Slide => frames.len() * XSIZE,
-            AnimationEffect::None => frames.len(),
-        };
+        let length = frames.len() * effect.steps::<XSIZE>();
 
         if let Some(wait) = duration.checked_div(length as u32) {
             Ok(Self {
@@ -286,31 +538,35 @@ impl<'a, const XSIZE: usize, const YSIZE: usize> Animation<'a, XSIZE, YSIZE> {
             Err(AnimationError::TooFast)
         }
     }
-    fn current(&self) -> Frame<XSIZE, YSIZE> {
+
+    fn current(&self) -> GrayFrame<XSIZE, YSIZE> {
         let mut current = self.frames.frame(self.frame_index);
 
         let mut next = if self.frame_index < self.frames.len() - 1 {
             self.frames.frame(self.frame_index + 1)
         } else {
-            Frame::empty()
+            GrayFrame::empty()
         };
 
-        current.shift_left(self.sequence);
-        next.shift_right(XSIZE - self.sequence);
+        match self.effect {
+            AnimationEffect::None => current,
+            AnimationEffect::Slide => {
+                current.shift_left(self.sequence);
+                next.shift_right(XSIZE - self.sequence);
 
-        current.or(&next);
-        current
+                current.or(&next);
+                current
+            }
+            AnimationEffect::Fade => current.blend(&next, self.sequence, FADE_STEPS),
+        }
     }
 
     fn next(&mut self, now: Instant) -> AnimationState<XSIZE, YSIZE> {
         if self.next <= now {
             if self.index < self.length {
                 let current = self.current();
-                if self.sequence >= XSIZE - 1 {
-                    self.sequence = match self.effect {
-                        AnimationEffect::None => XSIZE,
-                        AnimationEffect::Slide => 0,
-                    };
+                if self.sequence >= self.effect.steps::<XSIZE>() - 1 {
+                    self.sequence = 0;
                     self.frame_index += 1;
                 } else {
                     self.sequence += 1;
@@ -588,6 +844,157 @@ pub mod fonts {
 mod tests {
     use super::*;
 
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        RowsOff,
+        RowOn(usize, [bool; 3]),
+        ColumnOff(usize),
+    }
+
+    struct MockBackend {
+        columns: [bool; 3],
+        lit: bool,
+        events: std::vec::Vec<(Instant, Event)>,
+        hardware_brightness: bool,
+    }
+
+    impl MockBackend {
+        fn new(hardware_brightness: bool) -> Self {
+            Self {
+                columns: [false; 3],
+                lit: false,
+                events: std::vec::Vec::new(),
+                hardware_brightness,
+            }
+        }
+    }
+
+    impl MatrixBackend<2, 3> for MockBackend {
+        fn rows_off(&mut self) {
+            self.lit = false;
+            self.events.push((Instant::now(), Event::RowsOff));
+        }
+
+        fn row_on(&mut self, row: usize) {
+            self.lit = true;
+            self.events
+                .push((Instant::now(), Event::RowOn(row, self.columns)));
+        }
+
+        fn set_column(&mut self, col: usize, lit: bool) {
+            if self.lit && self.columns[col] && !lit {
+                self.events.push((Instant::now(), Event::ColumnOff(col)));
+            }
+            self.columns[col] = lit;
+        }
+
+        fn set_brightness(&mut self, _brightness: Brightness) -> bool {
+            self.hardware_brightness
+        }
+    }
+
+    #[test]
+    fn test_row_scan() {
+        let mut matrix: LedMatrix<MockBackend, 2, 3> =
+            LedMatrix::with_backend(MockBackend::new(false));
+        matrix.set_brightness(Brightness::new(8));
+        matrix.on(0, 0);
+        matrix.on(2, 1);
+
+        matrix.render();
+        matrix.render();
+        matrix.render();
+
+        let events = &matrix.backend.events;
+        assert_eq!(6, events.len());
+        assert_eq!(Event::RowOn(0, [true, false, false]), events[1].1);
+        assert_eq!(Event::RowOn(1, [false, false, true]), events[3].1);
+        assert_eq!(Event::RowOn(0, [true, false, false]), events[5].1);
+
+        // each row stays dark for 2/10 of 6ms before being lit, then lit for its time.
+        for pair in events.chunks(2) {
+            assert_eq!(Event::RowsOff, pair[0].1);
+            assert!(pair[1].0 - pair[0].0 >= Duration::from_micros(1200));
+        }
+        for pair in events[1..].chunks(2).filter(|pair| pair.len() == 2) {
+            assert!(pair[1].0 - pair[0].0 >= ROW_TIME);
+        }
+    }
+
+    #[test]
+    fn test_hardware_brightness() {
+        let mut matrix: LedMatrix<MockBackend, 2, 3> =
+            LedMatrix::with_backend(MockBackend::new(true));
+        matrix.set_brightness(Brightness::MIN);
+        assert_eq!(Duration::from_ticks(0), matrix.dark_time());
+
+        matrix.backend.hardware_brightness = false;
+        matrix.set_brightness(Brightness::MIN);
+        assert_eq!(Duration::from_micros(6000), matrix.dark_time());
+    }
+
+    #[test]
+    fn test_gray_levels() {
+        let mut matrix: LedMatrix<MockBackend, 2, 3> =
+            LedMatrix::with_backend(MockBackend::new(true));
+        matrix.set_level(0, 0, Brightness::new(3));
+        matrix.on(1, 0);
+        matrix.set_level(2, 0, Brightness::new(7));
+        matrix.set_level(1, 1, Brightness::new(7));
+
+        matrix.render();
+        matrix.render();
+        matrix.render();
+
+        // every lit led is on once its row is, and dimmer ones are turned off first.
+        let events: std::vec::Vec<&Event> = matrix.backend.events.iter().map(|(_, e)| e).collect();
+        assert_eq!(
+            std::vec![
+                &Event::RowsOff,
+                &Event::RowOn(0, [true, true, true]),
+                &Event::ColumnOff(0),
+                &Event::ColumnOff(2),
+                &Event::RowsOff,
+                &Event::RowOn(1, [false, true, false]),
+                &Event::ColumnOff(1),
+                &Event::RowsOff,
+                &Event::RowOn(0, [true, true, true]),
+                &Event::ColumnOff(0),
+                &Event::ColumnOff(2),
+            ],
+            events
+        );
+
+        // each for its share of the row time.
+        let events = &matrix.backend.events;
+        let lit_at = events[1].0;
+        assert!(events[2].0 - lit_at >= ROW_TIME * 3 / 10);
+        assert!(events[3].0 - lit_at >= ROW_TIME * 7 / 10);
+        assert!(events[4].0 - lit_at >= ROW_TIME);
+        assert!(events[6].0 - events[5].0 >= ROW_TIME * 7 / 10);
+    }
+
+    #[test]
+    fn test_fade() {
+        let first: Frame<5, 5> = '1'.to_frame();
+        let frames: [&dyn ToFrame<5, 5>; 1] = [&first];
+        let mut animation: Animation<5, 5> = Animation::new(
+            AnimationData::Frames(&frames),
+            AnimationEffect::Fade,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+        assert_eq!(FADE_STEPS, animation.length);
+
+        let later = Instant::now() + Duration::from_secs(1);
+        let mut levels = std::vec::Vec::new();
+        while let AnimationState::Apply(frame) = animation.next(later) {
+            levels.push(frame.get(2, 0).level());
+        }
+        // '1' is lit at (2, 0), fading out to an empty frame.
+        assert_eq!(std::vec![10, 9, 8, 7, 6, 5, 4, 3, 2, 1], levels);
+    }
+
     #[test]
     fn test_animation() {
         let mut animation: Animation<5, 5> = Animation::new(
@@ -7,7 +7,7 @@ pub use crate::drivers::ActiveLow;
 #[cfg(feature = "time")]
 pub mod matrix;
 
-#[cfg(all(feature = "neopixel", feature = "nrf", feature = "time"))]
+#[cfg(feature = "neopixel")]
 pub mod neopixel;
 
 pub trait Active<P>
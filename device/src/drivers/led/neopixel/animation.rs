@@ -0,0 +1,417 @@
+//! Effects computed frame by frame for a strip of `N` smart LEDs.
+//!
+//! An [`Animation`] knows nothing about time: every call to [`Animation::next_frame`]
+//! advances it by one step, and all durations are counted in steps. The frame
+//! rate is up to whoever drives it, such as the animator actor.
+
+use crate::drivers::led::neopixel::rgb::Rgb8;
+use crate::drivers::led::neopixel::rgbw::Rgbw8;
+use crate::drivers::led::neopixel::Pixel;
+
+/// Pixels which can show an arbitrary RGB color.
+pub trait FromRgb {
+    fn from_rgb(r: u8, g: u8, b: u8) -> Self;
+}
+
+impl FromRgb for Rgb8 {
+    fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        Rgb8::new(r, g, b)
+    }
+}
+
+impl FromRgb for Rgbw8 {
+    fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        Rgbw8::new(r, g, b, 0)
+    }
+}
+
+/// Shape of a transition, over a progress going from 0 to 255.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    pub fn apply(&self, t: u8) -> u8 {
+        let t = t as u16;
+        let eased = match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t / 255,
+            Easing::EaseOut => 255 - (255 - t) * (255 - t) / 255,
+            Easing::EaseInOut => {
+                if t < 128 {
+                    2 * t * t / 255
+                } else {
+                    255 - 2 * (255 - t) * (255 - t) / 255
+                }
+            }
+        };
+        eased as u8
+    }
+}
+
+/// Blend every channel from `from` to `to`, 0 being `from` and 255 being `to`.
+pub fn lerp<P: Pixel<C>, const C: usize>(from: &P, to: &P, t: u8) -> P {
+    let mut blended = *from;
+    for ch in 0..C {
+        if let (Ok(a), Ok(b)) = (from.get(ch), to.get(ch)) {
+            let value = a as i32 + (b as i32 - a as i32) * t as i32 / 255;
+            blended.set(ch, value as u8).ok();
+        }
+    }
+    blended
+}
+
+/// Scale every channel of the pixel by `level / 255`.
+pub fn scale<P: Pixel<C>, const C: usize>(pixel: &P, level: u8) -> P {
+    let mut scaled = *pixel;
+    for ch in 0..C {
+        if let Ok(value) = pixel.get(ch) {
+            scaled
+                .set(ch, (value as u16 * level as u16 / 255) as u8)
+                .ok();
+        }
+    }
+    scaled
+}
+
+/// Colors of the wheel, going from red to green to blue and back to red.
+pub fn wheel<P: FromRgb>(position: u8) -> P {
+    let position = position as u16;
+    if position < 85 {
+        P::from_rgb((255 - position * 3) as u8, (position * 3) as u8, 0)
+    } else if position < 170 {
+        let position = position - 85;
+        P::from_rgb(0, (255 - position * 3) as u8, (position * 3) as u8)
+    } else {
+        let position = position - 170;
+        P::from_rgb((position * 3) as u8, 0, (255 - position * 3) as u8)
+    }
+}
+
+/// Color of a flame, from black through red and yellow to white.
+pub fn heat_color<P: FromRgb>(heat: u8) -> P {
+    let t192 = (heat as u16 * 191 / 255) as u8;
+    let ramp = (t192 & 0x3F) << 2;
+    if t192 & 0x80 != 0 {
+        P::from_rgb(255, 255, ramp)
+    } else if t192 & 0x40 != 0 {
+        P::from_rgb(255, ramp, 0)
+    } else {
+        P::from_rgb(ramp, 0, 0)
+    }
+}
+
+/// A frame to reach, from the previous keyframe, in some steps.
+#[derive(Copy, Clone)]
+pub struct Keyframe<P, const N: usize> {
+    pub frame: [P; N],
+    pub steps: u16,
+}
+
+impl<P, const N: usize> Keyframe<P, N> {
+    pub const fn new(frame: [P; N], steps: u16) -> Self {
+        Self { frame, steps }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub enum Effect<P: 'static, const N: usize> {
+    /// All pixels off.
+    Off,
+    /// All pixels showing the same color.
+    Solid(P),
+    /// Go through the keyframes, easing from one to the next.
+    Keyframes {
+        keyframes: &'static [Keyframe<P, N>],
+        easing: Easing,
+        repeat: bool,
+    },
+    /// The color wheel spread over the strip, turning once per cycle.
+    Rainbow { steps_per_cycle: u16 },
+    /// `length` lit pixels moving along the strip, by one pixel every `steps_per_pixel`.
+    Chase {
+        color: P,
+        background: P,
+        length: usize,
+        steps_per_pixel: u16,
+    },
+    /// All pixels fading in and out of a color, once per period.
+    Breathe { color: P, period: u16 },
+    /// Flames rising from the start of the strip.
+    ///
+    /// Hotter flames cool down by up to `cooling` every step, and a spark
+    /// ignites with a chance of `sparking` out of 255.
+    Fire { cooling: u8, sparking: u8 },
+}
+
+/// Computes the frames of an effect, one step at a time.
+pub struct Animation<P: 'static, const C: usize, const N: usize>
+where
+    P: Pixel<C> + FromRgb,
+{
+    effect: Effect<P, N>,
+    step: u32,
+    frame: [P; N],
+    heat: [u8; N],
+    seed: u32,
+}
+
+impl<P, const C: usize, const N: usize> Animation<P, C, N>
+where
+    P: Pixel<C> + FromRgb,
+{
+    pub fn new(effect: Effect<P, N>) -> Self {
+        Self {
+            effect,
+            step: 0,
+            frame: [P::from_rgb(0, 0, 0); N],
+            heat: [0; N],
+            seed: 0x2545_F491,
+        }
+    }
+
+    pub fn effect(&self) -> &Effect<P, N> {
+        &self.effect
+    }
+
+    /// Start a new effect from its first step.
+    pub fn set_effect(&mut self, effect: Effect<P, N>) {
+        self.effect = effect;
+        self.step = 0;
+        self.heat = [0; N];
+    }
+
+    /// The frame of the current step, then advance to the next step.
+    pub fn next_frame(&mut self) -> &[P; N] {
+        match self.effect {
+            Effect::Off => self.fill(P::from_rgb(0, 0, 0)),
+            Effect::Solid(color) => self.fill(color),
+            Effect::Keyframes {
+                keyframes,
+                easing,
+                repeat,
+            } => self.keyframes(keyframes, easing, repeat),
+            Effect::Rainbow { steps_per_cycle } => {
+                let offset = (self.step % steps_per_cycle.max(1) as u32) * 256
+                    / steps_per_cycle.max(1) as u32;
+                for (i, pixel) in self.frame.iter_mut().enumerate() {
+                    *pixel = wheel(((i * 256 / N) as u32 + offset) as u8);
+                }
+            }
+            Effect::Chase {
+                color,
+                background,
+                length,
+                steps_per_pixel,
+            } => {
+                let head = (self.step / steps_per_pixel.max(1) as u32) as usize % N.max(1);
+                for (i, pixel) in self.frame.iter_mut().enumerate() {
+                    *pixel = if (head + N - i) % N < length {
+                        color
+                    } else {
+                        background
+                    };
+                }
+            }
+            Effect::Breathe { color, period } => {
+                let period = period.max(2) as u32;
+                let half = period / 2;
+                let phase = self.step % period;
+                let t = if phase < half {
+                    phase * 255 / half
+                } else {
+                    (period - phase) * 255 / (period - half)
+                };
+                let level = Easing::EaseInOut.apply(t as u8);
+                self.fill(scale(&color, level));
+            }
+            Effect::Fire { cooling, sparking } => self.fire(cooling, sparking),
+        }
+        self.step = self.step.wrapping_add(1);
+        &self.frame
+    }
+
+    fn fill(&mut self, color: P) {
+        self.frame = [color; N];
+    }
+
+    fn keyframes(&mut self, keyframes: &[Keyframe<P, N>], easing: Easing, repeat: bool) {
+        let total: u32 = keyframes.iter().map(|k| k.steps as u32).sum();
+        if keyframes.is_empty() {
+            return;
+        }
+        if total == 0 {
+            self.frame = keyframes[keyframes.len() - 1].frame;
+            return;
+        }
+
+        let mut step = if repeat {
+            self.step % total
+        } else {
+            self.step.min(total)
+        };
+
+        // the last keyframe leads back to the first one when repeating.
+        let mut from = if repeat {
+            &keyframes[keyframes.len() - 1].frame
+        } else {
+            &keyframes[0].frame
+        };
+        for keyframe in keyframes {
+            let steps = keyframe.steps as u32;
+            if step < steps {
+                let t = easing.apply((step * 255 / steps) as u8);
+                for (i, pixel) in self.frame.iter_mut().enumerate() {
+                    *pixel = lerp(&from[i], &keyframe.frame[i], t);
+                }
+                return;
+            }
+            step -= steps;
+            from = &keyframe.frame;
+        }
+        self.frame = *from;
+    }
+
+    fn fire(&mut self, cooling: u8, sparking: u8) {
+        for i in 0..N {
+            let cool = self.random() % (cooling as u32 + 1);
+            self.heat[i] = self.heat[i].saturating_sub(cool as u8);
+        }
+
+        for i in (2..N).rev() {
+            self.heat[i] = ((self.heat[i - 1] as u16 + 2 * self.heat[i - 2] as u16) / 3) as u8;
+        }
+
+        if N > 0 && (self.random() % 255) < sparking as u32 {
+            let i = self.random() as usize % N.min(3);
+            let spark = 160 + (self.random() % 96) as u8;
+            self.heat[i] = self.heat[i].saturating_add(spark);
+        }
+
+        for (pixel, heat) in self.frame.iter_mut().zip(self.heat.iter()) {
+            *pixel = heat_color(*heat);
+        }
+    }
+
+    fn random(&mut self) -> u32 {
+        // xorshift32
+        let mut x = self.seed;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.seed = x;
+        x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drivers::led::neopixel::rgb::{BLACK, BLUE, GREEN, RED, WHITE};
+
+    #[test]
+    fn test_easing() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert_eq!(0, easing.apply(0));
+            assert_eq!(255, easing.apply(255));
+        }
+        assert_eq!(64, Easing::EaseIn.apply(128));
+        assert_eq!(192, Easing::EaseOut.apply(128));
+        assert!(Easing::EaseInOut.apply(64) < 64);
+    }
+
+    #[test]
+    fn test_keyframes() {
+        static KEYFRAMES: [Keyframe<Rgb8, 2>; 2] = [
+            Keyframe::new([BLACK, WHITE], 0),
+            Keyframe::new([RED, BLACK], 4),
+        ];
+        let mut animation = Animation::new(Effect::Keyframes {
+            keyframes: &KEYFRAMES,
+            easing: Easing::Linear,
+            repeat: false,
+        });
+
+        assert_eq!(&[BLACK, WHITE], animation.next_frame());
+        animation.next_frame();
+        assert_eq!(
+            &[Rgb8::new(0x7F, 0, 0), Rgb8::new(0x80, 0x80, 0x80)],
+            animation.next_frame()
+        );
+        animation.next_frame();
+        assert_eq!(&[RED, BLACK], animation.next_frame());
+        assert_eq!(&[RED, BLACK], animation.next_frame());
+    }
+
+    #[test]
+    fn test_chase() {
+        let mut animation: Animation<Rgb8, 3, 4> = Animation::new(Effect::Chase {
+            color: RED,
+            background: BLUE,
+            length: 2,
+            steps_per_pixel: 2,
+        });
+
+        assert_eq!(&[RED, BLUE, BLUE, RED], animation.next_frame());
+        assert_eq!(&[RED, BLUE, BLUE, RED], animation.next_frame());
+        assert_eq!(&[RED, RED, BLUE, BLUE], animation.next_frame());
+
+        animation.set_effect(Effect::Solid(GREEN));
+        assert_eq!(&[GREEN; 4], animation.next_frame());
+    }
+
+    #[test]
+    fn test_rainbow_and_breathe() {
+        let mut animation: Animation<Rgb8, 3, 3> =
+            Animation::new(Effect::Rainbow { steps_per_cycle: 3 });
+        assert_eq!(
+            &[
+                Rgb8::new(255, 0, 0),
+                Rgb8::new(0, 255, 0),
+                Rgb8::new(0, 0, 255)
+            ],
+            animation.next_frame()
+        );
+        assert_eq!(Rgb8::new(0, 255, 0), animation.next_frame()[0]);
+
+        animation.set_effect(Effect::Breathe {
+            color: WHITE,
+            period: 4,
+        });
+        assert_eq!(&[BLACK; 3], animation.next_frame());
+        animation.next_frame();
+        assert_eq!(&[WHITE; 3], animation.next_frame());
+    }
+
+    #[test]
+    fn test_fire_cools_down() {
+        let mut animation: Animation<Rgb8, 3, 8> = Animation::new(Effect::Fire {
+            cooling: 55,
+            sparking: 255,
+        });
+        for _ in 0..16 {
+            animation.next_frame();
+        }
+        assert!(animation.heat[0..3].iter().any(|h| *h > 0));
+
+        animation.set_effect(Effect::Fire {
+            cooling: 255,
+            sparking: 0,
+        });
+        animation.heat = [255; 8];
+        for _ in 0..64 {
+            animation.next_frame();
+        }
+        assert_eq!(&[BLACK; 8], animation.next_frame());
+    }
+}
@@ -1,42 +1,45 @@
+use core::future::Future;
+
+pub mod animation;
 pub mod filter;
+#[cfg(all(feature = "nrf", feature = "time"))]
+pub mod pwm;
 pub mod rgb;
 pub mod rgbw;
+pub mod spi;
+
+/// Duration of one bit on the wire, in nanoseconds (800 kHz).
+pub const BIT_PERIOD_NANOS: u32 = 1250;
 
-const ONE: u16 = 0x8000 | 13;
-// Duty = 13/20 ticks (0.8us/1.25us) for a 1
-const ZERO: u16 = 0x8000 | 7;
-// Duty 7/20 ticks (0.4us/1.25us) for a 0
-const RES: u16 = 0x8000;
+/// How long the data line is held low to latch the pixels, in microseconds.
+///
+/// WS2812B parts need at least 280us, older WS2812 parts 50us.
+pub const RESET_MICROS: u32 = 300;
 
 pub struct InvalidChannel;
 
+/// A pixel of a strip of smart LEDs.
+///
+/// Channels are sent on the wire in index order, most significant bit first.
 pub trait Pixel<const N: usize>: Copy + Clone {
     const CHANNELS: usize = N;
 
-    fn fill_pwm_words(&self, dst: &mut [u16]) -> Result<(), InvalidChannel> {
-        let mut cur = 0;
-        for i in 0..Self::CHANNELS {
-            let v = self.get(i)?;
-            Self::byte_to_word(v, &mut dst[cur..cur + 8]);
-            cur += 8;
-        }
-        Ok(())
-    }
-
-    fn byte_to_word(byte: u8, dst: &mut [u16]) {
-        let mut pos = 0;
-        let mut mask = 0x80;
-        for _ in 0..8 {
-            if (byte & mask) != 0 {
-                dst[pos] = ONE;
-            } else {
-                dst[pos] = ZERO;
-            }
-            pos += 1;
-            mask >>= 1;
-        }
-    }
-
     fn get(&self, ch: usize) -> Result<u8, InvalidChannel>;
     fn set(&mut self, ch: usize, val: u8) -> Result<(), InvalidChannel>;
 }
+
+/// A transport able to clock pixels out to a strip of smart LEDs.
+pub trait SmartLedWriter<P, const C: usize>
+where
+    P: Pixel<C>,
+{
+    type Error;
+
+    type WriteFuture<'m>: Future<Output = Result<(), Self::Error>> + 'm
+    where
+        Self: 'm,
+        P: 'm;
+
+    /// Write the pixels starting from the first LED of the strip, then latch them.
+    fn write<'m>(&'m mut self, pixels: &'m [P]) -> Self::WriteFuture<'m>;
+}
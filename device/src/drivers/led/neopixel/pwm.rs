@@ -0,0 +1,244 @@
+//! Smart LEDs driven by the nRF PWM peripheral, one PWM sequence word per bit.
+
+use crate::drivers::led::neopixel::filter::Filter;
+use crate::drivers::led::neopixel::rgb::{self, Rgb8};
+use crate::drivers::led::neopixel::rgbw::{self, Rgbw8};
+use crate::drivers::led::neopixel::{InvalidChannel, Pixel, SmartLedWriter, RESET_MICROS};
+use core::future::Future;
+use core::mem::transmute;
+use core::ops::Deref;
+use core::slice;
+use embassy::time::{Duration, Timer};
+use embassy_hal_common::{unborrow, Unborrow};
+use embassy_nrf::gpio::Pin;
+use embassy_nrf::pwm::{
+    Config, Error, Instance, Prescaler, SequenceConfig, SequenceLoad, SequencePwm,
+    SingleSequenceMode, SingleSequencer,
+};
+
+const ONE: u16 = 0x8000 | 13;
+// Duty = 13/20 ticks (0.8us/1.25us) for a 1
+const ZERO: u16 = 0x8000 | 7;
+// Duty 7/20 ticks (0.4us/1.25us) for a 0
+const RES: u16 = 0x8000;
+// Words of RES appended to each sequence, 50us.
+const RES_WORDS: usize = 40;
+
+/// Fill `dst` with the PWM words of each bit of the pixel.
+pub fn fill_pwm_words<P: Pixel<C>, const C: usize>(
+    pixel: &P,
+    dst: &mut [u16],
+) -> Result<(), InvalidChannel> {
+    let mut cur = 0;
+    for i in 0..C {
+        let v = pixel.get(i)?;
+        byte_to_words(v, &mut dst[cur..cur + 8]);
+        cur += 8;
+    }
+    Ok(())
+}
+
+fn byte_to_words(byte: u8, dst: &mut [u16]) {
+    let mut mask = 0x80;
+    for word in dst.iter_mut().take(8) {
+        if (byte & mask) != 0 {
+            *word = ONE;
+        } else {
+            *word = ZERO;
+        }
+        mask >>= 1;
+    }
+}
+
+/// `W` words for each of the `N` pixels, followed by the reset words.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+struct RawPwm<const W: usize, const N: usize> {
+    words: [[u16; W]; N],
+    end: [u16; RES_WORDS],
+}
+
+impl<const W: usize, const N: usize> RawPwm<W, N> {
+    fn from_iter<'i, P, I, const C: usize>(iter: I) -> Result<Self, Error>
+    where
+        P: Pixel<C> + 'i,
+        I: Iterator<Item = &'i P>,
+    {
+        let mut raw = Self::default();
+        for (cur, pixel) in iter.enumerate() {
+            if cur >= N {
+                return Err(Error::SequenceTooLong);
+            }
+            fill_pwm_words(pixel, &mut raw.words[cur]).map_err(|_| Error::SequenceTooLong)?;
+        }
+        Ok(raw)
+    }
+}
+
+impl<const W: usize, const N: usize> Default for RawPwm<W, N> {
+    fn default() -> Self {
+        Self {
+            words: [[0; W]; N],
+            end: [RES; RES_WORDS],
+        }
+    }
+}
+
+impl<const W: usize, const N: usize> Deref for RawPwm<W, N> {
+    type Target = [u16];
+
+    fn deref(&self) -> &Self::Target {
+        unsafe {
+            let ptr: *const u16 = transmute(self as *const _ as *const u16);
+            slice::from_raw_parts(ptr, (N * W) + RES_WORDS)
+        }
+    }
+}
+
+struct Sequencer<'d, T: Instance> {
+    pwm: SequencePwm<'d, T>,
+}
+
+impl<'d, T: Instance> Sequencer<'d, T> {
+    fn new(
+        pwm: impl Unborrow<Target = T>,
+        pin: impl Unborrow<Target = impl Pin> + 'd,
+    ) -> Result<Self, Error> {
+        unborrow!(pwm);
+        unborrow!(pin);
+        let mut config = Config::default();
+        config.sequence_load = SequenceLoad::Common;
+        config.prescaler = Prescaler::Div1;
+        config.max_duty = 20; // 1.25us (1s / 16Mhz * 20)
+
+        Ok(Self {
+            pwm: SequencePwm::new_1ch(pwm, pin, config)?,
+        })
+    }
+
+    async fn send<const W: usize, const N: usize>(
+        &mut self,
+        raw: &RawPwm<W, N>,
+    ) -> Result<(), Error> {
+        let mut seq_config = SequenceConfig::default();
+        seq_config.end_delay = 799;
+
+        let sequences = SingleSequencer::new(&mut self.pwm, &**raw, seq_config);
+        sequences.start(SingleSequenceMode::Times(1))?;
+
+        // 30us per pixel and per reset word, then latch.
+        Timer::after(Duration::from_micros(
+            (30 * (N + RES_WORDS) as u64) + RESET_MICROS as u64,
+        ))
+        .await;
+        Ok(())
+    }
+}
+
+pub struct NeoPixelRgb<'d, T: Instance, const N: usize = 1> {
+    sequencer: Sequencer<'d, T>,
+}
+
+impl<'d, T: Instance, const N: usize> NeoPixelRgb<'d, T, N> {
+    pub fn new(
+        pwm: impl Unborrow<Target = T>,
+        pin: impl Unborrow<Target = impl Pin> + 'd,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            sequencer: Sequencer::new(pwm, pin)?,
+        })
+    }
+
+    pub async fn set(&mut self, pixels: &[Rgb8; N]) -> Result<(), Error> {
+        self.set_from_iter(pixels.iter()).await
+    }
+
+    pub async fn set_from_iter<'i, I: Iterator<Item = &'i Rgb8>>(
+        &mut self,
+        pixels: I,
+    ) -> Result<(), Error> {
+        let raw = RawPwm::<24, N>::from_iter::<_, _, 3>(pixels)?;
+        self.sequencer.send(&raw).await
+    }
+
+    pub async fn set_with_filter<F: Filter<Rgb8, 3>>(
+        &mut self,
+        pixels: &[Rgb8; N],
+        filter: &mut F,
+    ) -> Result<(), Error> {
+        let mut filtered = [rgb::BLACK; N];
+        for (i, pixel) in pixels.iter().enumerate() {
+            filtered[i] = filter
+                .apply(pixel)
+                .map_err(|_| Error::SequenceTimesAtLeastOne)?;
+        }
+        filter.complete();
+        self.set(&filtered).await
+    }
+}
+
+impl<'d, T: Instance, const N: usize> SmartLedWriter<Rgb8, 3> for NeoPixelRgb<'d, T, N> {
+    type Error = Error;
+
+    type WriteFuture<'m> = impl Future<Output = Result<(), Self::Error>> + 'm
+    where
+        Self: 'm;
+
+    fn write<'m>(&'m mut self, pixels: &'m [Rgb8]) -> Self::WriteFuture<'m> {
+        self.set_from_iter(pixels.iter())
+    }
+}
+
+pub struct NeoPixelRgbw<'d, T: Instance, const N: usize = 1> {
+    sequencer: Sequencer<'d, T>,
+}
+
+impl<'d, T: Instance, const N: usize> NeoPixelRgbw<'d, T, N> {
+    pub fn new(
+        pwm: impl Unborrow<Target = T>,
+        pin: impl Unborrow<Target = impl Pin> + 'd,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            sequencer: Sequencer::new(pwm, pin)?,
+        })
+    }
+
+    pub async fn set(&mut self, pixels: &[Rgbw8; N]) -> Result<(), Error> {
+        self.set_from_iter(pixels.iter()).await
+    }
+
+    pub async fn set_from_iter<'i, I: Iterator<Item = &'i Rgbw8>>(
+        &mut self,
+        pixels: I,
+    ) -> Result<(), Error> {
+        let raw = RawPwm::<32, N>::from_iter::<_, _, 4>(pixels)?;
+        self.sequencer.send(&raw).await
+    }
+
+    pub async fn set_with_filter<F: Filter<Rgbw8, 4>>(
+        &mut self,
+        pixels: &[Rgbw8; N],
+        filter: &mut F,
+    ) -> Result<(), Error> {
+        let mut filtered = [rgbw::BLACK; N];
+        for (i, pixel) in pixels.iter().enumerate() {
+            filtered[i] = filter
+                .apply(pixel)
+                .map_err(|_| Error::SequenceTimesAtLeastOne)?;
+        }
+        filter.complete();
+        self.set(&filtered).await
+    }
+}
+
+impl<'d, T: Instance, const N: usize> SmartLedWriter<Rgbw8, 4> for NeoPixelRgbw<'d, T, N> {
+    type Error = Error;
+
+    type WriteFuture<'m> = impl Future<Output = Result<(), Self::Error>> + 'm
+    where
+        Self: 'm;
+
+    fn write<'m>(&'m mut self, pixels: &'m [Rgbw8]) -> Self::WriteFuture<'m> {
+        self.set_from_iter(pixels.iter())
+    }
+}
@@ -0,0 +1,210 @@
+//! Smart LEDs driven by the MOSI line of an SPI bus.
+//!
+//! Each bit of a pixel is sent as a symbol of 3 or 4 SPI bits, starting high
+//! and ending low, so the SPI clock is chosen to make one symbol last one bit
+//! period. The SPI bus must be dedicated to the strip and idle low.
+
+use crate::drivers::led::neopixel::{Pixel, SmartLedWriter, BIT_PERIOD_NANOS, RESET_MICROS};
+use core::future::Future;
+use embedded_hal_async::spi::*;
+
+/// How bits of a pixel are encoded as SPI bits.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SymbolEncoding {
+    /// 3 SPI bits per bit at 2.4 MHz: `110` for a 1, `100` for a 0.
+    ThreeBit,
+    /// 4 SPI bits per bit at 3.2 MHz: `1110` for a 1, `1000` for a 0.
+    FourBit,
+}
+
+impl SymbolEncoding {
+    const fn bits(&self) -> usize {
+        match self {
+            SymbolEncoding::ThreeBit => 3,
+            SymbolEncoding::FourBit => 4,
+        }
+    }
+
+    const fn symbol(&self, bit: bool) -> u32 {
+        match (self, bit) {
+            (SymbolEncoding::ThreeBit, true) => 0b110,
+            (SymbolEncoding::ThreeBit, false) => 0b100,
+            (SymbolEncoding::FourBit, true) => 0b1110,
+            (SymbolEncoding::FourBit, false) => 0b1000,
+        }
+    }
+
+    /// The SPI clock frequency to configure the bus with, in Hz.
+    pub const fn frequency(&self) -> u32 {
+        (self.bits() as u32) * (1_000_000_000 / BIT_PERIOD_NANOS)
+    }
+
+    /// SPI bytes needed for each byte of a pixel.
+    pub const fn bytes_per_byte(&self) -> usize {
+        self.bits()
+    }
+
+    /// Zero SPI bytes holding the line low long enough to latch the pixels.
+    pub const fn reset_bytes(&self) -> usize {
+        let bits = RESET_MICROS as u64 * self.frequency() as u64 / 1_000_000;
+        ((bits + 7) / 8) as usize
+    }
+
+    /// Encode a byte, most significant bit first, into the start of `dst`.
+    pub fn encode_byte(&self, byte: u8, dst: &mut [u8]) {
+        let mut symbols: u32 = 0;
+        let mut mask = 0x80;
+        while mask != 0 {
+            symbols = (symbols << self.bits()) | self.symbol((byte & mask) != 0);
+            mask >>= 1;
+        }
+        let len = self.bytes_per_byte();
+        dst[..len].copy_from_slice(&symbols.to_be_bytes()[4 - len..]);
+    }
+}
+
+/// Size of the buffer needed to send `pixels` pixels of `channels` channels each.
+pub const fn buffer_size(encoding: SymbolEncoding, pixels: usize, channels: usize) -> usize {
+    pixels * channels * encoding.bytes_per_byte() + encoding.reset_bytes()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SpiWriterError<E> {
+    BufferTooSmall,
+    InvalidChannel,
+    Spi(E),
+}
+
+/// Encode the pixels followed by the reset into `dst`, returning the length used.
+pub fn encode_pixels<P: Pixel<C>, E, const C: usize>(
+    encoding: SymbolEncoding,
+    pixels: &[P],
+    dst: &mut [u8],
+) -> Result<usize, SpiWriterError<E>> {
+    let len = buffer_size(encoding, pixels.len(), C);
+    if dst.len() < len {
+        return Err(SpiWriterError::BufferTooSmall);
+    }
+
+    let mut cur = 0;
+    for pixel in pixels {
+        for ch in 0..C {
+            let byte = pixel.get(ch).map_err(|_| SpiWriterError::InvalidChannel)?;
+            encoding.encode_byte(byte, &mut dst[cur..]);
+            cur += encoding.bytes_per_byte();
+        }
+    }
+    dst[cur..len].fill(0);
+    Ok(len)
+}
+
+/// Writes pixels through an SPI bus, using a buffer of `BUF` bytes.
+///
+/// Use [`buffer_size`] to size the buffer for the strip.
+pub struct SpiWriter<SPI, const BUF: usize>
+where
+    SPI: SpiBusWrite<u8>,
+{
+    spi: SPI,
+    encoding: SymbolEncoding,
+    buffer: [u8; BUF],
+}
+
+impl<SPI, const BUF: usize> SpiWriter<SPI, BUF>
+where
+    SPI: SpiBusWrite<u8>,
+{
+    /// The bus must be clocked at `encoding.frequency()`.
+    pub fn new(spi: SPI, encoding: SymbolEncoding) -> Self {
+        Self {
+            spi,
+            encoding,
+            buffer: [0; BUF],
+        }
+    }
+
+    pub fn encoding(&self) -> SymbolEncoding {
+        self.encoding
+    }
+}
+
+impl<SPI, P, const BUF: usize, const C: usize> SmartLedWriter<P, C> for SpiWriter<SPI, BUF>
+where
+    SPI: SpiBusWrite<u8>,
+    P: Pixel<C>,
+{
+    type Error = SpiWriterError<SPI::Error>;
+
+    type WriteFuture<'m> = impl Future<Output = Result<(), Self::Error>> + 'm
+    where
+        Self: 'm,
+        P: 'm;
+
+    fn write<'m>(&'m mut self, pixels: &'m [P]) -> Self::WriteFuture<'m> {
+        async move {
+            let len = encode_pixels(self.encoding, pixels, &mut self.buffer)?;
+            self.spi
+                .write(&self.buffer[..len])
+                .await
+                .map_err(SpiWriterError::Spi)?;
+            self.spi.flush().await.map_err(SpiWriterError::Spi)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drivers::led::neopixel::rgb::Rgb8;
+
+    fn encode(encoding: SymbolEncoding, byte: u8) -> [u8; 4] {
+        let mut dst = [0; 4];
+        encoding.encode_byte(byte, &mut dst);
+        dst
+    }
+
+    #[test]
+    fn test_three_bit_symbols() {
+        let encoding = SymbolEncoding::ThreeBit;
+        assert_eq!(2_400_000, encoding.frequency());
+        assert_eq!([0xDB, 0x6D, 0xB6, 0x00], encode(encoding, 0xFF));
+        assert_eq!([0x92, 0x49, 0x24, 0x00], encode(encoding, 0x00));
+        // 1010 0101
+        assert_eq!([0xD3, 0x49, 0xA6, 0x00], encode(encoding, 0xA5));
+    }
+
+    #[test]
+    fn test_four_bit_symbols() {
+        let encoding = SymbolEncoding::FourBit;
+        assert_eq!(3_200_000, encoding.frequency());
+        assert_eq!([0xEE; 4], encode(encoding, 0xFF));
+        assert_eq!([0x88; 4], encode(encoding, 0x00));
+        assert_eq!([0xE8, 0xE8, 0x8E, 0x8E], encode(encoding, 0xA5));
+    }
+
+    #[test]
+    fn test_encode_pixels() {
+        let encoding = SymbolEncoding::ThreeBit;
+        assert_eq!(90, encoding.reset_bytes());
+
+        let pixels = [Rgb8::new(0x00, 0xFF, 0x00), Rgb8::new(0xFF, 0x00, 0x00)];
+        let mut dst = [0xAA; buffer_size(SymbolEncoding::ThreeBit, 2, 3)];
+        let len = encode_pixels::<_, (), 3>(encoding, &pixels, &mut dst).unwrap();
+        assert_eq!(2 * 9 + 90, len);
+
+        // green is sent first.
+        assert_eq!([0xDB, 0x6D, 0xB6], dst[0..3]);
+        assert_eq!([0x92, 0x49, 0x24], dst[3..6]);
+        assert_eq!([0x92, 0x49, 0x24], dst[9..12]);
+        assert_eq!([0xDB, 0x6D, 0xB6], dst[12..15]);
+        assert!(dst[18..].iter().all(|b| *b == 0));
+
+        let mut small = [0; 20];
+        assert_eq!(
+            Err(SpiWriterError::BufferTooSmall),
+            encode_pixels::<_, (), 3>(encoding, &pixels, &mut small)
+        );
+    }
+}
@@ -6,6 +6,7 @@ pub(crate) mod common;
 pub mod dns;
 pub mod led;
 pub mod lora;
+pub mod mqtt;
 pub mod sensors;
 pub mod tcp;
 pub mod wifi;
@@ -0,0 +1,245 @@
+//! A minimal MQTT 3.1.1 client, publishing with QoS 0.
+
+use embedded_io::asynch::{Read, Write};
+use embedded_nal_async::*;
+
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const DISCONNECT: u8 = 0xE0;
+
+const PROTOCOL_NAME: &[u8] = b"MQTT";
+const PROTOCOL_LEVEL: u8 = 4;
+const CLEAN_SESSION: u8 = 0x02;
+const PASSWORD_FLAG: u8 = 0x40;
+const USERNAME_FLAG: u8 = 0x80;
+const KEEP_ALIVE_SECS: u16 = 60;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MqttError {
+    Dns,
+    Connect,
+    Io,
+    BufferTooSmall,
+    /// The return code of a refused connection.
+    ConnectionRefused(u8),
+}
+
+struct Encoder<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> Encoder<'a> {
+    fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn bytes(&mut self, data: &[u8]) -> Result<(), MqttError> {
+        let end = self.pos + data.len();
+        if end > self.buf.len() {
+            return Err(MqttError::BufferTooSmall);
+        }
+        self.buf[self.pos..end].copy_from_slice(data);
+        self.pos = end;
+        Ok(())
+    }
+
+    fn u8(&mut self, value: u8) -> Result<(), MqttError> {
+        self.bytes(&[value])
+    }
+
+    fn u16(&mut self, value: u16) -> Result<(), MqttError> {
+        self.bytes(&value.to_be_bytes())
+    }
+
+    fn string(&mut self, value: &[u8]) -> Result<(), MqttError> {
+        self.u16(value.len() as u16)?;
+        self.bytes(value)
+    }
+
+    fn remaining_length(&mut self, mut len: usize) -> Result<(), MqttError> {
+        loop {
+            let mut byte = (len % 128) as u8;
+            len /= 128;
+            if len > 0 {
+                byte |= 0x80;
+            }
+            self.u8(byte)?;
+            if len == 0 {
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn encode_connect(
+    buf: &mut [u8],
+    client_id: &str,
+    credentials: Option<(&str, &str)>,
+) -> Result<usize, MqttError> {
+    let mut flags = CLEAN_SESSION;
+    let mut len = 2 + PROTOCOL_NAME.len() + 4 + 2 + client_id.len();
+    if let Some((username, password)) = credentials {
+        flags |= USERNAME_FLAG | PASSWORD_FLAG;
+        len += 2 + username.len() + 2 + password.len();
+    }
+
+    let mut encoder = Encoder::new(buf);
+    encoder.u8(CONNECT)?;
+    encoder.remaining_length(len)?;
+    encoder.string(PROTOCOL_NAME)?;
+    encoder.u8(PROTOCOL_LEVEL)?;
+    encoder.u8(flags)?;
+    encoder.u16(KEEP_ALIVE_SECS)?;
+    encoder.string(client_id.as_bytes())?;
+    if let Some((username, password)) = credentials {
+        encoder.string(username.as_bytes())?;
+        encoder.string(password.as_bytes())?;
+    }
+    Ok(encoder.pos)
+}
+
+fn encode_publish(buf: &mut [u8], topic: &str, payload: &[u8]) -> Result<usize, MqttError> {
+    let mut encoder = Encoder::new(buf);
+    encoder.u8(PUBLISH)?;
+    encoder.remaining_length(2 + topic.len() + payload.len())?;
+    encoder.string(topic.as_bytes())?;
+    encoder.bytes(payload)?;
+    Ok(encoder.pos)
+}
+
+async fn write_all<W: Write>(connection: &mut W, mut data: &[u8]) -> Result<(), MqttError> {
+    while !data.is_empty() {
+        let n = connection.write(data).await.map_err(|_| MqttError::Io)?;
+        if n == 0 {
+            return Err(MqttError::Io);
+        }
+        data = &data[n..];
+    }
+    connection.flush().await.map_err(|_| MqttError::Io)
+}
+
+async fn read_exact<R: Read>(connection: &mut R, mut buf: &mut [u8]) -> Result<(), MqttError> {
+    while !buf.is_empty() {
+        let n = connection.read(buf).await.map_err(|_| MqttError::Io)?;
+        if n == 0 {
+            return Err(MqttError::Io);
+        }
+        let rest = buf;
+        buf = &mut rest[n..];
+    }
+    Ok(())
+}
+
+/// Publishes with QoS 0, connecting for each message.
+///
+/// `TX` bounds the size of the packets, so it must hold the payload and the
+/// topic, plus a few bytes of header.
+pub struct MqttClient<N, D, const TX: usize>
+where
+    N: TcpConnect,
+    D: Dns,
+{
+    network: N,
+    dns: D,
+    host: &'static str,
+    port: u16,
+    client_id: &'static str,
+    credentials: Option<(&'static str, &'static str)>,
+    tx: [u8; TX],
+}
+
+impl<N, D, const TX: usize> MqttClient<N, D, TX>
+where
+    N: TcpConnect,
+    D: Dns,
+{
+    pub fn new(network: N, dns: D, host: &'static str, port: u16, client_id: &'static str) -> Self {
+        Self {
+            network,
+            dns,
+            host,
+            port,
+            client_id,
+            credentials: None,
+            tx: [0; TX],
+        }
+    }
+
+    pub fn with_credentials(mut self, username: &'static str, password: &'static str) -> Self {
+        self.credentials.replace((username, password));
+        self
+    }
+
+    pub async fn publish(&mut self, topic: &str, payload: &[u8]) -> Result<(), MqttError> {
+        let ip = self
+            .dns
+            .get_host_by_name(self.host, AddrType::IPv4)
+            .await
+            .map_err(|_| MqttError::Dns)?;
+
+        let mut connection = self
+            .network
+            .connect(SocketAddr::new(ip, self.port))
+            .await
+            .map_err(|_| MqttError::Connect)?;
+
+        let len = encode_connect(&mut self.tx, self.client_id, self.credentials)?;
+        write_all(&mut connection, &self.tx[..len]).await?;
+
+        let mut connack = [0; 4];
+        read_exact(&mut connection, &mut connack).await?;
+        if connack[0] != CONNACK || connack[3] != 0 {
+            return Err(MqttError::ConnectionRefused(connack[3]));
+        }
+
+        let len = encode_publish(&mut self.tx, topic, payload)?;
+        write_all(&mut connection, &self.tx[..len]).await?;
+        write_all(&mut connection, &[DISCONNECT, 0]).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_connect() {
+        let mut buf = [0; 64];
+        let len = encode_connect(&mut buf, "dev", None).unwrap();
+        assert_eq!(
+            [0x10, 15, 0, 4, b'M', b'Q', b'T', b'T', 4, 0x02, 0, 60, 0, 3, b'd', b'e', b'v'],
+            buf[..len]
+        );
+
+        let len = encode_connect(&mut buf, "d", Some(("u", "pw"))).unwrap();
+        assert_eq!(
+            [
+                0x10, 20, 0, 4, b'M', b'Q', b'T', b'T', 4, 0xC2, 0, 60, 0, 1, b'd', 0, 1, b'u', 0,
+                2, b'p', b'w'
+            ],
+            buf[..len]
+        );
+
+        let mut small = [0; 8];
+        assert_eq!(
+            Err(MqttError::BufferTooSmall),
+            encode_connect(&mut small, "dev", None)
+        );
+    }
+
+    #[test]
+    fn test_encode_publish() {
+        let mut buf = [0; 256];
+        let len = encode_publish(&mut buf, "t", b"42").unwrap();
+        assert_eq!([0x30, 5, 0, 1, b't', b'4', b'2'], buf[..len]);
+
+        // the remaining length takes two bytes from 128 on
+        let payload = [b'x'; 196];
+        let len = encode_publish(&mut buf, "ab", &payload).unwrap();
+        assert_eq!([0x30, 0xC8, 0x01, 0, 2, b'a', b'b'], buf[..7]);
+        assert_eq!(3 + 200, len);
+    }
+}
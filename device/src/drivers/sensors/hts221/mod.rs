@@ -1,21 +1,66 @@
 mod register;
-use crate::domain::{temperature::Celsius, SensorAcquisition};
-use crate::traits::{i2c::I2cAddress, sensors::temperature::TemperatureSensor};
+use crate::domain::{humidity::RelativeHumidity, temperature::Celsius, SensorAcquisition};
+use crate::traits::{
+    i2c::I2cAddress,
+    sensors::{humidity::HumiditySensor, temperature::TemperatureSensor, Sensor},
+};
 use core::future::Future;
+use embassy::time::{Duration, Timer};
 use embedded_hal_async::i2c::*;
+use register::av_conf::AvConf;
 use register::calibration::*;
-use register::ctrl1::{BlockDataUpdate, Ctrl1, OutputDataRate};
+use register::ctrl1::Ctrl1;
 use register::ctrl2::Ctrl2;
 use register::ctrl3::Ctrl3;
 use register::h_out::Hout;
 use register::status::Status;
 use register::t_out::Tout;
 
+pub use register::av_conf::{HumidityAverage, TemperatureAverage};
+pub use register::ctrl1::{BlockDataUpdate, OutputDataRate};
+pub use register::ctrl3::{ActiveState, ReadyMode};
+
 pub const ADDR: u8 = 0x5F;
 
+// A one-shot conversion takes at most a few tens of milliseconds.
+const ONE_SHOT_POLL_INTERVAL: Duration = Duration::from_millis(5);
+const ONE_SHOT_MAX_POLLS: usize = 100;
+
 pub enum Hts221Error<E> {
     I2c(E),
     NotCalibrated,
+    /// A one-shot conversion didn't complete in time.
+    Timeout,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Config {
+    /// With `OutputDataRate::OneShot` the sensor stays powered down between
+    /// reads, and each read triggers a single conversion.
+    pub output_data_rate: OutputDataRate,
+    pub temperature_average: TemperatureAverage,
+    pub humidity_average: HumidityAverage,
+    pub block_data_update: BlockDataUpdate,
+    pub heater: bool,
+    /// Signal new data on the DRDY pin.
+    pub data_ready: bool,
+    pub data_ready_active: ActiveState,
+    pub data_ready_mode: ReadyMode,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            output_data_rate: OutputDataRate::Hz1,
+            temperature_average: TemperatureAverage::Avg16,
+            humidity_average: HumidityAverage::Avg32,
+            block_data_update: BlockDataUpdate::MsbLsbReading,
+            heater: false,
+            data_ready: true,
+            data_ready_active: ActiveState::High,
+            data_ready_mode: ReadyMode::PushPull,
+        }
+    }
 }
 
 pub struct Hts221<I>
@@ -25,6 +70,7 @@ where
 {
     i2c: I,
     address: I2cAddress,
+    config: Config,
     calibration: Option<Calibration>,
 }
 
@@ -34,28 +80,52 @@ where
     <I as ErrorType>::Error: Send,
 {
     pub fn new(i2c: I) -> Self {
+        Self::with_config(i2c, Config::default())
+    }
+
+    pub fn with_config(i2c: I, config: Config) -> Self {
         Self {
             i2c,
             address: I2cAddress::new(ADDR),
+            config,
             calibration: None,
         }
     }
 
+    pub fn config(&self) -> Config {
+        self.config
+    }
+
+    /// Apply the configuration and read the calibration of the sensor.
     pub async fn initialize(&mut self) -> Result<(), Hts221Error<I::Error>> {
+        let config = self.config;
         Ctrl2::modify(self.address, &mut self.i2c, |reg| {
             reg.boot();
         })
         .await?;
 
+        AvConf::modify(self.address, &mut self.i2c, |reg| {
+            reg.temperature_average(config.temperature_average)
+                .humidity_average(config.humidity_average);
+        })
+        .await?;
+
         Ctrl1::modify(self.address, &mut self.i2c, |reg| {
             reg.power_active()
-                .output_data_rate(OutputDataRate::Hz1)
-                .block_data_update(BlockDataUpdate::MsbLsbReading);
+                .output_data_rate(config.output_data_rate)
+                .block_data_update(config.block_data_update);
+        })
+        .await?;
+
+        Ctrl2::modify(self.address, &mut self.i2c, |reg| {
+            reg.heater(config.heater);
         })
         .await?;
 
         Ctrl3::modify(self.address, &mut self.i2c, |reg| {
-            reg.enable(true);
+            reg.active_state(config.data_ready_active)
+                .ready_mode(config.data_ready_mode)
+                .enable(config.data_ready);
         })
         .await?;
 
@@ -75,8 +145,43 @@ where
         Ok(())
     }
 
+    /// Turn the internal heater on or off, e.g. to evaporate condensation.
+    ///
+    /// Readings taken while heating are not representative of the ambient air.
+    pub async fn set_heater(&mut self, on: bool) -> Result<(), Hts221Error<I::Error>> {
+        Ctrl2::modify(self.address, &mut self.i2c, |reg| {
+            reg.heater(on);
+        })
+        .await?;
+        self.config.heater = on;
+        Ok(())
+    }
+
+    /// Start a single conversion and wait for it to complete.
+    pub async fn trigger_one_shot(&mut self) -> Result<(), Hts221Error<I::Error>> {
+        Self::one_shot(self.address, &mut self.i2c).await
+    }
+
+    async fn one_shot(address: I2cAddress, i2c: &mut I) -> Result<(), Hts221Error<I::Error>> {
+        Ctrl2::modify(address, i2c, |reg| {
+            reg.enable_one_shot();
+        })
+        .await?;
+        for _ in 0..ONE_SHOT_MAX_POLLS {
+            if !Ctrl2::read(address, i2c).await?.one_shot_pending() {
+                return Ok(());
+            }
+            Timer::after(ONE_SHOT_POLL_INTERVAL).await;
+        }
+        Err(Hts221Error::Timeout)
+    }
+
     pub async fn read(&mut self) -> Result<SensorAcquisition<Celsius>, Hts221Error<I::Error>> {
         if let Some(calibration) = &self.calibration {
+            if self.config.output_data_rate == OutputDataRate::OneShot {
+                Self::one_shot(self.address, &mut self.i2c).await?;
+            }
+
             let t_out = Tout::read(self.address, &mut self.i2c).await? as i16;
             let temperature = calibration.calibrated_temperature(t_out);
 
@@ -113,6 +218,44 @@ where
     }
 }
 
+impl<I> HumiditySensor for Hts221<I>
+where
+    I: I2c<SevenBitAddress> + 'static,
+    <I as ErrorType>::Error: Send,
+{
+    type Error = Hts221Error<<I as ErrorType>::Error>;
+
+    type HumidityFuture<'m> = impl Future<Output = Result<RelativeHumidity, Hts221Error<<I as ErrorType>::Error>>> + 'm where I: 'm;
+
+    fn humidity<'m>(&'m mut self) -> Self::HumidityFuture<'m> {
+        async move {
+            let acquisition = self.read().await?;
+            Ok(RelativeHumidity::new(acquisition.relative_humidity))
+        }
+    }
+}
+
+impl<I> Sensor for Hts221<I>
+where
+    I: I2c<SevenBitAddress> + 'static,
+    <I as ErrorType>::Error: Send,
+{
+    type Error = Hts221Error<<I as ErrorType>::Error>;
+    type Reading = SensorAcquisition<Celsius>;
+
+    type CalibrateFuture<'m> = impl Future<Output = Result<(), Hts221Error<<I as ErrorType>::Error>>> + 'm where I: 'm;
+
+    fn calibrate<'m>(&'m mut self) -> Self::CalibrateFuture<'m> {
+        self.initialize()
+    }
+
+    type ReadFuture<'m> = impl Future<Output = Result<SensorAcquisition<Celsius>, Hts221Error<<I as ErrorType>::Error>>> + 'm where I: 'm;
+
+    fn read<'m>(&'m mut self) -> Self::ReadFuture<'m> {
+        Hts221::read(self)
+    }
+}
+
 impl<E> From<E> for Hts221Error<E>
 where
     E: Send,
@@ -121,3 +264,193 @@ where
         Hts221Error::I2c(e)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+    use ector::testutil::TestContext;
+    use futures::executor::block_on;
+
+    const CTRL_REG1: usize = 0x20;
+    const CTRL_REG2: usize = 0x21;
+    const CTRL_REG3: usize = 0x22;
+    const AV_CONF: usize = 0x10;
+    const H_OUT: usize = 0x28;
+    const T_OUT: usize = 0x2A;
+    const CALIBRATION: usize = 0x30;
+    const AUTO_INCREMENT: u8 = 0x80;
+
+    struct MockI2c {
+        registers: [u8; 0x80],
+        /// Reads of CTRL_REG2 before a one-shot conversion completes, if ever.
+        conversion_polls: Option<usize>,
+        pending_polls: usize,
+        ctrl2_reads: usize,
+    }
+
+    impl MockI2c {
+        fn new() -> Self {
+            let mut registers = [0; 0x80];
+            // 20%RH at 0, 60%RH at 1000, 10°C at 0, 20°C at 1000
+            registers[CALIBRATION..CALIBRATION + 16].copy_from_slice(&[
+                40, 120, 80, 160, 0, 0, 0, 0, 0, 0, 0xE8, 0x03, 0, 0, 0xE8, 0x03,
+            ]);
+            registers[H_OUT..H_OUT + 2].copy_from_slice(&500i16.to_le_bytes());
+            registers[T_OUT..T_OUT + 2].copy_from_slice(&500i16.to_le_bytes());
+            Self {
+                registers,
+                conversion_polls: Some(0),
+                pending_polls: 0,
+                ctrl2_reads: 0,
+            }
+        }
+    }
+
+    impl ErrorType for MockI2c {
+        type Error = Infallible;
+    }
+
+    impl I2c for MockI2c {
+        type ReadFuture<'a> = impl Future<Output = Result<(), Self::Error>> + 'a where Self: 'a;
+
+        fn read<'a>(&'a mut self, _: u8, _: &'a mut [u8]) -> Self::ReadFuture<'a> {
+            async move { Ok(()) }
+        }
+
+        type WriteFuture<'a> = impl Future<Output = Result<(), Self::Error>> + 'a where Self: 'a;
+
+        fn write<'a>(&'a mut self, address: u8, write: &'a [u8]) -> Self::WriteFuture<'a> {
+            async move {
+                assert_eq!(ADDR, address);
+                let start = (write[0] & !AUTO_INCREMENT) as usize;
+                self.registers[start..start + write.len() - 1].copy_from_slice(&write[1..]);
+                if start == CTRL_REG2 {
+                    // the reboot is done at once
+                    self.registers[CTRL_REG2] &= !0b1000_0000;
+                    if let Some(polls) = self.conversion_polls {
+                        self.pending_polls = polls;
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        type WriteReadFuture<'a> = impl Future<Output = Result<(), Self::Error>> + 'a where Self: 'a;
+
+        fn write_read<'a>(
+            &'a mut self,
+            address: u8,
+            write: &'a [u8],
+            read: &'a mut [u8],
+        ) -> Self::WriteReadFuture<'a> {
+            async move {
+                assert_eq!(ADDR, address);
+                if read.len() > 1 {
+                    assert!(write[0] & AUTO_INCREMENT != 0);
+                }
+                let start = (write[0] & !AUTO_INCREMENT) as usize;
+                if start == CTRL_REG2 {
+                    self.ctrl2_reads += 1;
+                    if self.conversion_polls.is_some() {
+                        if self.pending_polls == 0 {
+                            self.registers[CTRL_REG2] &= !0b0000_0001;
+                        } else {
+                            self.pending_polls -= 1;
+                        }
+                    }
+                }
+                read.copy_from_slice(&self.registers[start..start + read.len()]);
+                Ok(())
+            }
+        }
+
+        type TransactionFuture<'a, 'b> = impl Future<Output = Result<(), Self::Error>> + 'a where Self: 'a, 'b: 'a;
+
+        fn transaction<'a, 'b>(
+            &'a mut self,
+            _: u8,
+            _: &'a mut [Operation<'b>],
+        ) -> Self::TransactionFuture<'a, 'b> {
+            async move { Ok(()) }
+        }
+    }
+
+    #[test]
+    fn test_initialize() {
+        let mut sensor = Hts221::new(MockI2c::new());
+        assert!(block_on(sensor.initialize()).is_ok());
+        let registers = &sensor.i2c.registers;
+        assert_eq!(0x85, registers[CTRL_REG1]);
+        assert_eq!(0x00, registers[CTRL_REG2]);
+        assert_eq!(0x04, registers[CTRL_REG3]);
+        assert_eq!(0x1B, registers[AV_CONF]);
+
+        let mut sensor = Hts221::with_config(
+            MockI2c::new(),
+            Config {
+                output_data_rate: OutputDataRate::Hz12p5,
+                temperature_average: TemperatureAverage::Avg2,
+                humidity_average: HumidityAverage::Avg512,
+                block_data_update: BlockDataUpdate::Continuous,
+                heater: true,
+                data_ready: false,
+                data_ready_active: ActiveState::Low,
+                data_ready_mode: ReadyMode::OpenDrain,
+            },
+        );
+        assert!(block_on(sensor.initialize()).is_ok());
+        let registers = &sensor.i2c.registers;
+        assert_eq!(0x83, registers[CTRL_REG1]);
+        assert_eq!(0x02, registers[CTRL_REG2]);
+        assert_eq!(0xC0, registers[CTRL_REG3]);
+        assert_eq!(0x07, registers[AV_CONF]);
+    }
+
+    #[test]
+    fn test_read() {
+        let mut sensor = Hts221::new(MockI2c::new());
+        assert!(matches!(
+            block_on(sensor.read()),
+            Err(Hts221Error::NotCalibrated)
+        ));
+
+        assert!(block_on(sensor.initialize()).is_ok());
+        let acquisition = block_on(sensor.read()).ok().unwrap();
+        assert!((15.0 - acquisition.temperature.raw_value()).abs() < 0.01);
+        assert!((40.0 - acquisition.relative_humidity).abs() < 0.01);
+        // continuous conversions aren't triggered
+        assert_eq!(2, sensor.i2c.ctrl2_reads);
+    }
+
+    #[test]
+    fn test_one_shot() {
+        run_task!(one_shot);
+    }
+
+    #[embassy::task]
+    async fn one_shot(_context: TestContext<()>) {
+        let config = Config {
+            output_data_rate: OutputDataRate::OneShot,
+            ..Default::default()
+        };
+        let mut sensor = Hts221::with_config(MockI2c::new(), config);
+        assert!(sensor.initialize().await.is_ok());
+        assert_eq!(0x84, sensor.i2c.registers[CTRL_REG1]);
+
+        sensor.i2c.conversion_polls = Some(3);
+        sensor.i2c.ctrl2_reads = 0;
+        let acquisition = sensor.read().await.ok().unwrap();
+        assert!((15.0 - acquisition.temperature.raw_value()).abs() < 0.01);
+        // one read to set the bit, then polls until it clears
+        assert_eq!(5, sensor.i2c.ctrl2_reads);
+
+        sensor.i2c.conversion_polls = None;
+        sensor.i2c.ctrl2_reads = 0;
+        assert!(matches!(
+            sensor.trigger_one_shot().await,
+            Err(Hts221Error::Timeout)
+        ));
+        assert_eq!(1 + ONE_SHOT_MAX_POLLS, sensor.i2c.ctrl2_reads);
+    }
+}
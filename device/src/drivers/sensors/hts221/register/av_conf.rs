@@ -0,0 +1,165 @@
+use crate::traits::i2c::I2cAddress;
+use embedded_hal_async::i2c::*;
+
+const AV_CONF: u8 = 0x10;
+
+/// Number of internal samples averaged for each temperature output.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TemperatureAverage {
+    Avg2,
+    Avg4,
+    Avg8,
+    Avg16,
+    Avg32,
+    Avg64,
+    Avg128,
+    Avg256,
+}
+
+/// Number of internal samples averaged for each humidity output.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HumidityAverage {
+    Avg4,
+    Avg8,
+    Avg16,
+    Avg32,
+    Avg64,
+    Avg128,
+    Avg256,
+    Avg512,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct AvConf {
+    temperature: TemperatureAverage,
+    humidity: HumidityAverage,
+}
+
+impl AvConf {
+    pub async fn read<I: I2c>(address: I2cAddress, i2c: &mut I) -> Result<AvConf, I::Error> {
+        let mut buf = [0; 1];
+        let _ = i2c.write_read(address.into(), &[AV_CONF], &mut buf).await?;
+        Ok(buf[0].into())
+    }
+
+    pub async fn write<I: I2c>(
+        address: I2cAddress,
+        i2c: &mut I,
+        reg: AvConf,
+    ) -> Result<(), I::Error> {
+        Ok(i2c.write(address.into(), &[AV_CONF, reg.into()]).await?)
+    }
+
+    pub async fn modify<I: I2c, F: FnOnce(&mut AvConf)>(
+        address: I2cAddress,
+        i2c: &mut I,
+        modify: F,
+    ) -> Result<(), I::Error> {
+        let mut reg = Self::read(address, i2c).await?;
+        modify(&mut reg);
+        Self::write(address, i2c, reg).await
+    }
+
+    pub fn temperature_average(&mut self, avg: TemperatureAverage) -> &mut Self {
+        self.temperature = avg;
+        self
+    }
+
+    pub fn humidity_average(&mut self, avg: HumidityAverage) -> &mut Self {
+        self.humidity = avg;
+        self
+    }
+}
+
+impl Into<TemperatureAverage> for u8 {
+    fn into(self) -> TemperatureAverage {
+        match (self >> 3) & 0b111 {
+            0b000 => TemperatureAverage::Avg2,
+            0b001 => TemperatureAverage::Avg4,
+            0b010 => TemperatureAverage::Avg8,
+            0b011 => TemperatureAverage::Avg16,
+            0b100 => TemperatureAverage::Avg32,
+            0b101 => TemperatureAverage::Avg64,
+            0b110 => TemperatureAverage::Avg128,
+            _ => TemperatureAverage::Avg256,
+        }
+    }
+}
+
+impl From<TemperatureAverage> for u8 {
+    fn from(avg: TemperatureAverage) -> Self {
+        let bits = match avg {
+            TemperatureAverage::Avg2 => 0b000,
+            TemperatureAverage::Avg4 => 0b001,
+            TemperatureAverage::Avg8 => 0b010,
+            TemperatureAverage::Avg16 => 0b011,
+            TemperatureAverage::Avg32 => 0b100,
+            TemperatureAverage::Avg64 => 0b101,
+            TemperatureAverage::Avg128 => 0b110,
+            TemperatureAverage::Avg256 => 0b111,
+        };
+        bits << 3
+    }
+}
+
+impl Into<HumidityAverage> for u8 {
+    fn into(self) -> HumidityAverage {
+        match self & 0b111 {
+            0b000 => HumidityAverage::Avg4,
+            0b001 => HumidityAverage::Avg8,
+            0b010 => HumidityAverage::Avg16,
+            0b011 => HumidityAverage::Avg32,
+            0b100 => HumidityAverage::Avg64,
+            0b101 => HumidityAverage::Avg128,
+            0b110 => HumidityAverage::Avg256,
+            _ => HumidityAverage::Avg512,
+        }
+    }
+}
+
+impl From<HumidityAverage> for u8 {
+    fn from(avg: HumidityAverage) -> Self {
+        match avg {
+            HumidityAverage::Avg4 => 0b000,
+            HumidityAverage::Avg8 => 0b001,
+            HumidityAverage::Avg16 => 0b010,
+            HumidityAverage::Avg32 => 0b011,
+            HumidityAverage::Avg64 => 0b100,
+            HumidityAverage::Avg128 => 0b101,
+            HumidityAverage::Avg256 => 0b110,
+            HumidityAverage::Avg512 => 0b111,
+        }
+    }
+}
+
+impl Into<AvConf> for u8 {
+    fn into(self) -> AvConf {
+        AvConf {
+            temperature: self.into(),
+            humidity: self.into(),
+        }
+    }
+}
+
+impl Into<u8> for AvConf {
+    fn into(self) -> u8 {
+        u8::from(self.temperature) | u8::from(self.humidity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_av_conf_bits() {
+        // reset value
+        let mut reg: AvConf = 0x1Bu8.into();
+        assert_eq!(TemperatureAverage::Avg16, reg.temperature);
+        assert_eq!(HumidityAverage::Avg32, reg.humidity);
+
+        reg.temperature_average(TemperatureAverage::Avg256)
+            .humidity_average(HumidityAverage::Avg4);
+        assert_eq!(0b0011_1000, Into::<u8>::into(reg));
+    }
+}
@@ -9,13 +9,13 @@ pub enum Power {
     Active,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum BlockDataUpdate {
     Continuous,
     MsbLsbReading,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum OutputDataRate {
     OneShot,
     Hz1,
@@ -100,7 +100,7 @@ impl From<Power> for u8 {
 
 impl Into<BlockDataUpdate> for u8 {
     fn into(self) -> BlockDataUpdate {
-        if (self & 0x40) != 0 {
+        if (self & 0b100) != 0 {
             BlockDataUpdate::MsbLsbReading
         } else {
             BlockDataUpdate::Continuous
@@ -51,6 +51,11 @@ impl Ctrl2 {
         self.enable_one_shot = true;
         self
     }
+
+    /// The one-shot bit clears itself once the conversion is done.
+    pub fn one_shot_pending(&self) -> bool {
+        self.enable_one_shot
+    }
 }
 
 impl Into<Ctrl2> for u8 {
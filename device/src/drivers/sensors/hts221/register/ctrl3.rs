@@ -3,13 +3,13 @@ use embedded_hal_async::i2c::*;
 
 const CTRL_REG3: u8 = 0x22;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ReadyMode {
     PushPull,
     OpenDrain,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ActiveState {
     High,
     Low,
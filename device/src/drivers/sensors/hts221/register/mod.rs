@@ -1,3 +1,4 @@
+pub mod av_conf;
 pub mod calibration;
 pub mod ctrl1;
 pub mod ctrl2;
@@ -0,0 +1,568 @@
+mod register;
+use crate::domain::motion::{Acceleration, MagneticField};
+use crate::traits::{
+    i2c::I2cAddress,
+    sensors::{
+        motion::{Accelerometer, Magnetometer},
+        Sensor,
+    },
+};
+use core::future::Future;
+use embedded_hal_async::i2c::*;
+use micromath::F32Ext;
+use register::*;
+
+pub use register::{AccelDataRate, MagDataRate};
+
+pub const ACCEL_ADDR: u8 = 0x19;
+pub const MAG_ADDR: u8 = 0x1E;
+
+// Magnetometer sensitivity, 1.5 mgauss per digit.
+const NANOTESLA_PER_DIGIT: i32 = 150;
+
+pub enum Lsm303agrError<E> {
+    I2c(E),
+    WrongDevice,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Config {
+    pub accel_data_rate: AccelDataRate,
+    pub mag_data_rate: MagDataRate,
+    /// Signal new accelerometer data on INT1 and new magnetometer data on INT_MAG.
+    pub data_ready_interrupts: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            accel_data_rate: AccelDataRate::Hz50,
+            mag_data_rate: MagDataRate::Hz50,
+            data_ready_interrupts: false,
+        }
+    }
+}
+
+/// Hard and soft iron correction of the magnetometer.
+///
+/// The offset of each axis is removed first, then the axis is scaled.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MagCalibration {
+    pub offset: MagneticField,
+    pub scale: [f32; 3],
+}
+
+impl Default for MagCalibration {
+    fn default() -> Self {
+        Self {
+            offset: MagneticField::default(),
+            scale: [1.0; 3],
+        }
+    }
+}
+
+impl MagCalibration {
+    pub fn apply(&self, field: MagneticField) -> MagneticField {
+        MagneticField::new(
+            ((field.x - self.offset.x) as f32 * self.scale[0]) as i32,
+            ((field.y - self.offset.y) as f32 * self.scale[1]) as i32,
+            ((field.z - self.offset.z) as f32 * self.scale[2]) as i32,
+        )
+    }
+}
+
+/// Collects the extremes of raw magnetometer samples while the device is turned
+/// around in every direction, to compute a `MagCalibration`.
+pub struct MagCalibrator {
+    min: MagneticField,
+    max: MagneticField,
+    samples: usize,
+}
+
+impl MagCalibrator {
+    pub fn new() -> Self {
+        Self {
+            min: MagneticField::new(i32::MAX, i32::MAX, i32::MAX),
+            max: MagneticField::new(i32::MIN, i32::MIN, i32::MIN),
+            samples: 0,
+        }
+    }
+
+    pub fn add(&mut self, field: MagneticField) {
+        self.min = MagneticField::new(
+            self.min.x.min(field.x),
+            self.min.y.min(field.y),
+            self.min.z.min(field.z),
+        );
+        self.max = MagneticField::new(
+            self.max.x.max(field.x),
+            self.max.y.max(field.y),
+            self.max.z.max(field.z),
+        );
+        self.samples += 1;
+    }
+
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// The hard iron offset is the center of the extremes, and the soft iron
+    /// scale brings the range of every axis to their average range.
+    pub fn calibration(&self) -> MagCalibration {
+        if self.samples == 0 {
+            return MagCalibration::default();
+        }
+        let offset = MagneticField::new(
+            (self.min.x + self.max.x) / 2,
+            (self.min.y + self.max.y) / 2,
+            (self.min.z + self.max.z) / 2,
+        );
+        let radius = [
+            (self.max.x - self.min.x) as f32 / 2.0,
+            (self.max.y - self.min.y) as f32 / 2.0,
+            (self.max.z - self.min.z) as f32 / 2.0,
+        ];
+        let average = (radius[0] + radius[1] + radius[2]) / 3.0;
+        let mut scale = [1.0; 3];
+        for (scale, radius) in scale.iter_mut().zip(radius.iter()) {
+            if *radius > 0.0 {
+                *scale = average / radius;
+            }
+        }
+        MagCalibration { offset, scale }
+    }
+}
+
+impl Default for MagCalibrator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Tilt-compensated heading in degrees, from 0 up to 360, of the x axis
+/// measured clockwise from magnetic north when the device lies flat.
+pub fn heading(acceleration: &Acceleration, field: &MagneticField) -> f32 {
+    let (gx, gy, gz) = (
+        acceleration.x as f32,
+        acceleration.y as f32,
+        acceleration.z as f32,
+    );
+    let (bx, by, bz) = (field.x as f32, field.y as f32, field.z as f32);
+
+    let roll = gy.atan2(gz);
+    let (sin_roll, cos_roll) = (roll.sin(), roll.cos());
+    let pitch = (-gx).atan2(gy * sin_roll + gz * cos_roll);
+    let (sin_pitch, cos_pitch) = (pitch.sin(), pitch.cos());
+
+    // field rotated back to the horizontal plane
+    let y = bz * sin_roll - by * cos_roll;
+    let x = bx * cos_pitch + by * sin_pitch * sin_roll + bz * sin_pitch * cos_roll;
+
+    let heading = (-y).atan2(x).to_degrees();
+    if heading < 0.0 {
+        heading + 360.0
+    } else {
+        heading
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Measurement {
+    pub acceleration: Acceleration,
+    pub magnetic_field: MagneticField,
+    pub heading: f32,
+}
+
+pub struct Lsm303agr<I>
+where
+    I: I2c<SevenBitAddress> + 'static,
+    <I as ErrorType>::Error: Send,
+{
+    i2c: I,
+    accel_address: I2cAddress,
+    mag_address: I2cAddress,
+    config: Config,
+    calibration: MagCalibration,
+}
+
+impl<I> Lsm303agr<I>
+where
+    I: I2c<SevenBitAddress> + 'static,
+    <I as ErrorType>::Error: Send,
+{
+    pub fn new(i2c: I) -> Self {
+        Self::with_config(i2c, Config::default())
+    }
+
+    pub fn with_config(i2c: I, config: Config) -> Self {
+        Self {
+            i2c,
+            accel_address: I2cAddress::new(ACCEL_ADDR),
+            mag_address: I2cAddress::new(MAG_ADDR),
+            config,
+            calibration: MagCalibration::default(),
+        }
+    }
+
+    pub async fn initialize(&mut self) -> Result<(), Lsm303agrError<I::Error>> {
+        if self.read_register(self.accel_address, WHO_AM_I_A).await? != WHO_AM_I_A_VALUE
+            || self.read_register(self.mag_address, WHO_AM_I_M).await? != WHO_AM_I_M_VALUE
+        {
+            return Err(Lsm303agrError::WrongDevice);
+        }
+
+        let config = self.config;
+        let (int1, int_mag) = if config.data_ready_interrupts {
+            (I1_ZYXDA, INT_MAG)
+        } else {
+            (0, 0)
+        };
+
+        self.write_register(
+            self.accel_address,
+            CTRL_REG1_A,
+            u8::from(config.accel_data_rate) | XYZ_ENABLE,
+        )
+        .await?;
+        self.write_register(self.accel_address, CTRL_REG3_A, int1)
+            .await?;
+        // +/-2g, 12 bits
+        self.write_register(self.accel_address, CTRL_REG4_A, BDU_A | HIGH_RESOLUTION)
+            .await?;
+
+        // continuous mode
+        self.write_register(
+            self.mag_address,
+            CFG_REG_A_M,
+            COMP_TEMP_EN | u8::from(config.mag_data_rate),
+        )
+        .await?;
+        self.write_register(self.mag_address, CFG_REG_B_M, OFF_CANC)
+            .await?;
+        self.write_register(self.mag_address, CFG_REG_C_M, BDU_M | int_mag)
+            .await?;
+        Ok(())
+    }
+
+    pub fn calibration(&self) -> MagCalibration {
+        self.calibration
+    }
+
+    pub fn set_calibration(&mut self, calibration: MagCalibration) {
+        self.calibration = calibration;
+    }
+
+    pub async fn accel_data_ready(&mut self) -> Result<bool, Lsm303agrError<I::Error>> {
+        let status = self.read_register(self.accel_address, STATUS_REG_A).await?;
+        Ok(status & ZYXDA != 0)
+    }
+
+    pub async fn mag_data_ready(&mut self) -> Result<bool, Lsm303agrError<I::Error>> {
+        let status = self.read_register(self.mag_address, STATUS_REG_M).await?;
+        Ok(status & ZYXDA != 0)
+    }
+
+    /// Acceleration in milli-g.
+    pub async fn read_acceleration(&mut self) -> Result<Acceleration, Lsm303agrError<I::Error>> {
+        let mut buf = [0; 6];
+        self.i2c
+            .write_read(
+                self.accel_address.into(),
+                &[OUT_X_L_A | AUTO_INCREMENT],
+                &mut buf,
+            )
+            .await?;
+        // 12 bits left-justified, 1 mg per digit
+        let axis = |i: usize| (i16::from_le_bytes([buf[i], buf[i + 1]]) >> 4) as i32;
+        Ok(Acceleration::new(axis(0), axis(2), axis(4)))
+    }
+
+    /// Magnetic field in nanotesla, without calibration.
+    pub async fn read_raw_magnetic_field(
+        &mut self,
+    ) -> Result<MagneticField, Lsm303agrError<I::Error>> {
+        let mut buf = [0; 6];
+        self.i2c
+            .write_read(self.mag_address.into(), &[OUTX_L_REG_M], &mut buf)
+            .await?;
+        let axis = |i: usize| i16::from_le_bytes([buf[i], buf[i + 1]]) as i32 * NANOTESLA_PER_DIGIT;
+        Ok(MagneticField::new(axis(0), axis(2), axis(4)))
+    }
+
+    /// Magnetic field in nanotesla, corrected by the calibration.
+    pub async fn read_magnetic_field(&mut self) -> Result<MagneticField, Lsm303agrError<I::Error>> {
+        let field = self.read_raw_magnetic_field().await?;
+        Ok(self.calibration.apply(field))
+    }
+
+    pub async fn measure(&mut self) -> Result<Measurement, Lsm303agrError<I::Error>> {
+        let acceleration = self.read_acceleration().await?;
+        let magnetic_field = self.read_magnetic_field().await?;
+        Ok(Measurement {
+            acceleration,
+            magnetic_field,
+            heading: heading(&acceleration, &magnetic_field),
+        })
+    }
+
+    async fn read_register(
+        &mut self,
+        address: I2cAddress,
+        register: u8,
+    ) -> Result<u8, Lsm303agrError<I::Error>> {
+        let mut buf = [0; 1];
+        self.i2c
+            .write_read(address.into(), &[register], &mut buf)
+            .await?;
+        Ok(buf[0])
+    }
+
+    async fn write_register(
+        &mut self,
+        address: I2cAddress,
+        register: u8,
+        value: u8,
+    ) -> Result<(), Lsm303agrError<I::Error>> {
+        Ok(self.i2c.write(address.into(), &[register, value]).await?)
+    }
+}
+
+impl<I> Accelerometer for Lsm303agr<I>
+where
+    I: I2c<SevenBitAddress> + 'static,
+    <I as ErrorType>::Error: Send,
+{
+    type Error = Lsm303agrError<<I as ErrorType>::Error>;
+
+    type AccelerationFuture<'m> = impl Future<Output = Result<Acceleration, Lsm303agrError<<I as ErrorType>::Error>>> + 'm where I: 'm;
+
+    fn acceleration<'m>(&'m mut self) -> Self::AccelerationFuture<'m> {
+        self.read_acceleration()
+    }
+}
+
+impl<I> Magnetometer for Lsm303agr<I>
+where
+    I: I2c<SevenBitAddress> + 'static,
+    <I as ErrorType>::Error: Send,
+{
+    type Error = Lsm303agrError<<I as ErrorType>::Error>;
+
+    type MagneticFieldFuture<'m> = impl Future<Output = Result<MagneticField, Lsm303agrError<<I as ErrorType>::Error>>> + 'm where I: 'm;
+
+    fn magnetic_field<'m>(&'m mut self) -> Self::MagneticFieldFuture<'m> {
+        self.read_magnetic_field()
+    }
+}
+
+impl<I> Sensor for Lsm303agr<I>
+where
+    I: I2c<SevenBitAddress> + 'static,
+    <I as ErrorType>::Error: Send,
+{
+    type Error = Lsm303agrError<<I as ErrorType>::Error>;
+    type Reading = Measurement;
+
+    type CalibrateFuture<'m> = impl Future<Output = Result<(), Lsm303agrError<<I as ErrorType>::Error>>> + 'm where I: 'm;
+
+    fn calibrate<'m>(&'m mut self) -> Self::CalibrateFuture<'m> {
+        self.initialize()
+    }
+
+    type ReadFuture<'m> = impl Future<Output = Result<Measurement, Lsm303agrError<<I as ErrorType>::Error>>> + 'm where I: 'm;
+
+    fn read<'m>(&'m mut self) -> Self::ReadFuture<'m> {
+        self.measure()
+    }
+}
+
+impl<E> From<E> for Lsm303agrError<E>
+where
+    E: Send,
+{
+    fn from(e: E) -> Lsm303agrError<E> {
+        Lsm303agrError::I2c(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+    use futures::executor::block_on;
+
+    struct MockI2c {
+        accel: [u8; 0x80],
+        mag: [u8; 0x80],
+    }
+
+    impl MockI2c {
+        fn new() -> Self {
+            let mut i2c = Self {
+                accel: [0; 0x80],
+                mag: [0; 0x80],
+            };
+            i2c.accel[WHO_AM_I_A as usize] = WHO_AM_I_A_VALUE;
+            i2c.mag[WHO_AM_I_M as usize] = WHO_AM_I_M_VALUE;
+            i2c
+        }
+
+        fn registers(&mut self, address: u8) -> &mut [u8; 0x80] {
+            match address {
+                ACCEL_ADDR => &mut self.accel,
+                MAG_ADDR => &mut self.mag,
+                _ => panic!("no device at {:x}", address),
+            }
+        }
+    }
+
+    impl ErrorType for MockI2c {
+        type Error = Infallible;
+    }
+
+    impl I2c for MockI2c {
+        type ReadFuture<'a> = impl Future<Output = Result<(), Self::Error>> + 'a where Self: 'a;
+
+        fn read<'a>(&'a mut self, _: u8, _: &'a mut [u8]) -> Self::ReadFuture<'a> {
+            async move { Ok(()) }
+        }
+
+        type WriteFuture<'a> = impl Future<Output = Result<(), Self::Error>> + 'a where Self: 'a;
+
+        fn write<'a>(&'a mut self, address: u8, write: &'a [u8]) -> Self::WriteFuture<'a> {
+            async move {
+                let start = (write[0] & !AUTO_INCREMENT) as usize;
+                self.registers(address)[start..start + write.len() - 1]
+                    .copy_from_slice(&write[1..]);
+                Ok(())
+            }
+        }
+
+        type WriteReadFuture<'a> = impl Future<Output = Result<(), Self::Error>> + 'a where Self: 'a;
+
+        fn write_read<'a>(
+            &'a mut self,
+            address: u8,
+            write: &'a [u8],
+            read: &'a mut [u8],
+        ) -> Self::WriteReadFuture<'a> {
+            async move {
+                if address == ACCEL_ADDR && read.len() > 1 {
+                    assert!(write[0] & AUTO_INCREMENT != 0);
+                }
+                let start = (write[0] & !AUTO_INCREMENT) as usize;
+                read.copy_from_slice(&self.registers(address)[start..start + read.len()]);
+                Ok(())
+            }
+        }
+
+        type TransactionFuture<'a, 'b> = impl Future<Output = Result<(), Self::Error>> + 'a where Self: 'a, 'b: 'a;
+
+        fn transaction<'a, 'b>(
+            &'a mut self,
+            _: u8,
+            _: &'a mut [Operation<'b>],
+        ) -> Self::TransactionFuture<'a, 'b> {
+            async move { Ok(()) }
+        }
+    }
+
+    fn assert_heading(expected: f32, heading: f32) {
+        assert!(
+            (expected - heading).abs() < 1.0,
+            "expected {} got {}",
+            expected,
+            heading
+        );
+    }
+
+    #[test]
+    fn test_initialize() {
+        let mut sensor = Lsm303agr::with_config(
+            MockI2c::new(),
+            Config {
+                data_ready_interrupts: true,
+                ..Default::default()
+            },
+        );
+        assert!(block_on(sensor.initialize()).is_ok());
+
+        let i2c = &sensor.i2c;
+        assert_eq!(0x47, i2c.accel[CTRL_REG1_A as usize]);
+        assert_eq!(0x10, i2c.accel[CTRL_REG3_A as usize]);
+        assert_eq!(0x88, i2c.accel[CTRL_REG4_A as usize]);
+        assert_eq!(0x88, i2c.mag[CFG_REG_A_M as usize]);
+        assert_eq!(0x02, i2c.mag[CFG_REG_B_M as usize]);
+        assert_eq!(0x11, i2c.mag[CFG_REG_C_M as usize]);
+
+        let mut sensor = Lsm303agr::new(MockI2c::new());
+        sensor.i2c.mag[WHO_AM_I_M as usize] = 0;
+        assert!(matches!(
+            block_on(sensor.initialize()),
+            Err(Lsm303agrError::WrongDevice)
+        ));
+    }
+
+    #[test]
+    fn test_measure() {
+        let mut i2c = MockI2c::new();
+        let accel = OUT_X_L_A as usize;
+        i2c.accel[accel..accel + 2].copy_from_slice(&(-1000i16 << 4).to_le_bytes());
+        i2c.accel[accel + 4..accel + 6].copy_from_slice(&(10i16 << 4).to_le_bytes());
+        let mag = OUTX_L_REG_M as usize;
+        i2c.mag[mag..mag + 2].copy_from_slice(&20i16.to_le_bytes());
+        i2c.mag[mag + 2..mag + 4].copy_from_slice(&(-4i16).to_le_bytes());
+        i2c.mag[STATUS_REG_M as usize] = ZYXDA;
+
+        let mut sensor = Lsm303agr::new(i2c);
+        assert!(matches!(block_on(sensor.mag_data_ready()), Ok(true)));
+        assert!(matches!(block_on(sensor.accel_data_ready()), Ok(false)));
+
+        let measurement = block_on(sensor.measure()).ok().unwrap();
+        assert_eq!(Acceleration::new(-1000, 0, 10), measurement.acceleration);
+        assert_eq!(
+            MagneticField::new(3000, -600, 0),
+            measurement.magnetic_field
+        );
+
+        sensor.set_calibration(MagCalibration {
+            offset: MagneticField::new(1000, 0, 0),
+            scale: [0.5, 2.0, 1.0],
+        });
+        let field = block_on(sensor.read_magnetic_field()).ok().unwrap();
+        assert_eq!(MagneticField::new(1000, -1200, 0), field);
+    }
+
+    #[test]
+    fn test_calibrator() {
+        let mut calibrator = MagCalibrator::new();
+        assert_eq!(MagCalibration::default(), calibrator.calibration());
+
+        calibrator.add(MagneticField::new(-1000, 2000, 0));
+        calibrator.add(MagneticField::new(3000, -2000, 1000));
+        calibrator.add(MagneticField::new(0, 0, 500));
+        assert_eq!(3, calibrator.samples());
+
+        let calibration = calibrator.calibration();
+        assert_eq!(MagneticField::new(1000, 0, 500), calibration.offset);
+        assert_eq!([0.75, 0.75, 3.0], calibration.scale);
+        assert_eq!(
+            MagneticField::new(1500, -1500, 1500),
+            calibration.apply(MagneticField::new(3000, -2000, 1000))
+        );
+    }
+
+    #[test]
+    fn test_heading() {
+        let flat = Acceleration::new(0, 0, 1000);
+        assert_heading(0.0, heading(&flat, &MagneticField::new(2000, 0, -4000)));
+        assert_heading(90.0, heading(&flat, &MagneticField::new(0, 2000, -4000)));
+        assert_heading(180.0, heading(&flat, &MagneticField::new(-2000, 0, -4000)));
+        assert_heading(270.0, heading(&flat, &MagneticField::new(0, -2000, -4000)));
+
+        // pitched by 30 degrees, the vertical component of the field shows on x.
+        let pitched = Acceleration::new(-500, 0, 866);
+        let field = MagneticField::new(1000, 2000, -1732);
+        assert_heading(90.0, heading(&pitched, &field));
+    }
+}
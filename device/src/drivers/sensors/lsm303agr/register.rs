@@ -0,0 +1,87 @@
+// Accelerometer registers.
+pub const WHO_AM_I_A: u8 = 0x0F;
+pub const CTRL_REG1_A: u8 = 0x20;
+pub const CTRL_REG3_A: u8 = 0x22;
+pub const CTRL_REG4_A: u8 = 0x23;
+pub const STATUS_REG_A: u8 = 0x27;
+pub const OUT_X_L_A: u8 = 0x28;
+
+// Magnetometer registers.
+pub const WHO_AM_I_M: u8 = 0x4F;
+pub const CFG_REG_A_M: u8 = 0x60;
+pub const CFG_REG_B_M: u8 = 0x61;
+pub const CFG_REG_C_M: u8 = 0x62;
+pub const STATUS_REG_M: u8 = 0x67;
+pub const OUTX_L_REG_M: u8 = 0x68;
+
+pub const WHO_AM_I_A_VALUE: u8 = 0x33;
+pub const WHO_AM_I_M_VALUE: u8 = 0x40;
+
+// The accelerometer only increments the register address of multi-byte
+// accesses when the most significant bit is set.
+pub const AUTO_INCREMENT: u8 = 0x80;
+
+// CTRL_REG1_A
+pub const XYZ_ENABLE: u8 = 0b0000_0111;
+// CTRL_REG3_A
+pub const I1_ZYXDA: u8 = 0b0001_0000;
+// CTRL_REG4_A
+pub const BDU_A: u8 = 0b1000_0000;
+pub const HIGH_RESOLUTION: u8 = 0b0000_1000;
+// CFG_REG_A_M
+pub const COMP_TEMP_EN: u8 = 0b1000_0000;
+// CFG_REG_B_M
+pub const OFF_CANC: u8 = 0b0000_0010;
+// CFG_REG_C_M
+pub const BDU_M: u8 = 0b0001_0000;
+pub const INT_MAG: u8 = 0b0000_0001;
+// STATUS_REG_A and STATUS_REG_M
+pub const ZYXDA: u8 = 0b0000_1000;
+
+/// Output data rate of the accelerometer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AccelDataRate {
+    Hz1,
+    Hz10,
+    Hz25,
+    Hz50,
+    Hz100,
+    Hz200,
+    Hz400,
+}
+
+impl From<AccelDataRate> for u8 {
+    fn from(odr: AccelDataRate) -> Self {
+        let bits = match odr {
+            AccelDataRate::Hz1 => 0b0001,
+            AccelDataRate::Hz10 => 0b0010,
+            AccelDataRate::Hz25 => 0b0011,
+            AccelDataRate::Hz50 => 0b0100,
+            AccelDataRate::Hz100 => 0b0101,
+            AccelDataRate::Hz200 => 0b0110,
+            AccelDataRate::Hz400 => 0b0111,
+        };
+        bits << 4
+    }
+}
+
+/// Output data rate of the magnetometer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MagDataRate {
+    Hz10,
+    Hz20,
+    Hz50,
+    Hz100,
+}
+
+impl From<MagDataRate> for u8 {
+    fn from(odr: MagDataRate) -> Self {
+        let bits = match odr {
+            MagDataRate::Hz10 => 0b00,
+            MagDataRate::Hz20 => 0b01,
+            MagDataRate::Hz50 => 0b10,
+            MagDataRate::Hz100 => 0b11,
+        };
+        bits << 2
+    }
+}
@@ -1 +1,3 @@
 pub mod hts221;
+#[cfg(feature = "lsm303agr")]
+pub mod lsm303agr;
@@ -45,6 +45,9 @@
 //! ~~~
 pub(crate) mod fmt;
 
+#[cfg(all(test, feature = "std"))]
+mod testutil;
+
 pub mod actors;
 
 pub mod traits;
@@ -0,0 +1,34 @@
+#![macro_use]
+
+/// Run a task to completion in a unit test, the way `drogue_test` does for
+/// integration tests, so that embassy timers work.
+///
+/// The task takes a `TestContext<()>`, and the test is over once it's dropped.
+/// The executor runs on a thread named `main`, as the thread mode mutex may
+/// only be locked from that one under std.
+macro_rules! run_task {
+    ($task:ident) => {
+        ::std::thread::Builder::new()
+            .name("main".into())
+            .spawn(|| {
+                static DEVICE: ::embassy::util::Forever<()> = ::embassy::util::Forever::new();
+                static RUNNER: ::embassy::util::Forever<::ector::testutil::TestRunner> =
+                    ::embassy::util::Forever::new();
+
+                let runner = RUNNER.put(::ector::testutil::TestRunner::default());
+                runner.initialize(|spawner| {
+                    let runner = unsafe { RUNNER.steal() };
+                    spawner
+                        .spawn($task(::ector::testutil::TestContext::new(runner, &DEVICE)))
+                        .unwrap();
+                });
+
+                while !runner.is_done() {
+                    runner.run_until_idle();
+                }
+            })
+            .unwrap()
+            .join()
+            .unwrap()
+    };
+}
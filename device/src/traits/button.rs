@@ -2,6 +2,8 @@ use core::future::Future;
 use embedded_hal::digital::v2::InputPin;
 use embedded_hal_async::digital::Wait;
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Event {
     Pressed,
     Released,
@@ -33,6 +35,8 @@ pub trait Button {
         Self: 'm;
 }
 
+/// A plain pin is considered pressed while it is low, as most buttons pull
+/// the line to ground. Use `drivers::button::Button` for active high buttons.
 impl<P: InputPin + Wait> Button for P {
     type WaitPressed<'m> = impl Future<Output = ()> + 'm where Self: 'm;
 
@@ -59,7 +63,7 @@ impl<P: InputPin + Wait> Button for P {
         async move {
             loop {
                 self.wait_for_any_edge().await.unwrap();
-                if self.is_low().ok().unwrap() {
+                if self.is_high().ok().unwrap() {
                     break;
                 }
             }
@@ -73,13 +77,11 @@ impl<P: InputPin + Wait> Button for P {
         Self: 'm,
     {
         async move {
-            loop {
-                self.wait_for_any_edge().await.unwrap();
-                if self.is_low().ok().unwrap() {
-                    return Event::Released;
-                } else {
-                    return Event::Pressed;
-                }
+            self.wait_for_any_edge().await.unwrap();
+            if self.is_low().ok().unwrap() {
+                Event::Pressed
+            } else {
+                Event::Released
             }
         }
     }
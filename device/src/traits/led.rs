@@ -88,3 +88,17 @@ pub trait LedMatrix<const ROWS: usize, const COLS: usize> {
 pub trait ToFrame<const XSIZE: usize, const YSIZE: usize>: Sync {
     fn to_frame(&self) -> Frame<XSIZE, YSIZE>;
 }
+
+pub trait ToGrayFrame<const XSIZE: usize, const YSIZE: usize>: Sync {
+    fn to_gray_frame(&self) -> GrayFrame<XSIZE, YSIZE>;
+}
+
+/// Anything shown as a frame is shown at full brightness.
+impl<T, const XSIZE: usize, const YSIZE: usize> ToGrayFrame<XSIZE, YSIZE> for T
+where
+    T: ToFrame<XSIZE, YSIZE>,
+{
+    fn to_gray_frame(&self) -> GrayFrame<XSIZE, YSIZE> {
+        self.to_frame().into()
+    }
+}
@@ -0,0 +1,11 @@
+use crate::domain::gas::GasConcentration;
+use core::future::Future;
+
+pub trait GasSensor {
+    type Error;
+
+    type GasFuture<'m>: Future<Output = Result<GasConcentration, Self::Error>>
+    where
+        Self: 'm;
+    fn gas<'m>(&'m mut self) -> Self::GasFuture<'m>;
+}
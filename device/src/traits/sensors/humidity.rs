@@ -0,0 +1,11 @@
+use crate::domain::humidity::RelativeHumidity;
+use core::future::Future;
+
+pub trait HumiditySensor {
+    type Error;
+
+    type HumidityFuture<'m>: Future<Output = Result<RelativeHumidity, Self::Error>>
+    where
+        Self: 'm;
+    fn humidity<'m>(&'m mut self) -> Self::HumidityFuture<'m>;
+}
@@ -0,0 +1,11 @@
+use crate::domain::light::Illuminance;
+use core::future::Future;
+
+pub trait LightSensor {
+    type Error;
+
+    type IlluminanceFuture<'m>: Future<Output = Result<Illuminance, Self::Error>>
+    where
+        Self: 'm;
+    fn illuminance<'m>(&'m mut self) -> Self::IlluminanceFuture<'m>;
+}
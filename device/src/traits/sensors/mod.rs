@@ -1 +1,27 @@
+use core::future::Future;
+
+pub mod gas;
+pub mod humidity;
+pub mod light;
+pub mod motion;
+pub mod pressure;
 pub mod temperature;
+
+/// Any sensor, producing readings of a single type.
+///
+/// This is what the generic sensor actor drives, while the typed traits such
+/// as `temperature::TemperatureSensor` tell what a driver can measure.
+pub trait Sensor {
+    type Error;
+    type Reading: Copy + 'static;
+
+    type CalibrateFuture<'m>: Future<Output = Result<(), Self::Error>>
+    where
+        Self: 'm;
+    fn calibrate<'m>(&'m mut self) -> Self::CalibrateFuture<'m>;
+
+    type ReadFuture<'m>: Future<Output = Result<Self::Reading, Self::Error>>
+    where
+        Self: 'm;
+    fn read<'m>(&'m mut self) -> Self::ReadFuture<'m>;
+}
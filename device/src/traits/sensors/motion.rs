@@ -0,0 +1,20 @@
+use crate::domain::motion::{Acceleration, MagneticField};
+use core::future::Future;
+
+pub trait Accelerometer {
+    type Error;
+
+    type AccelerationFuture<'m>: Future<Output = Result<Acceleration, Self::Error>>
+    where
+        Self: 'm;
+    fn acceleration<'m>(&'m mut self) -> Self::AccelerationFuture<'m>;
+}
+
+pub trait Magnetometer {
+    type Error;
+
+    type MagneticFieldFuture<'m>: Future<Output = Result<MagneticField, Self::Error>>
+    where
+        Self: 'm;
+    fn magnetic_field<'m>(&'m mut self) -> Self::MagneticFieldFuture<'m>;
+}
@@ -0,0 +1,11 @@
+use crate::domain::pressure::Pressure;
+use core::future::Future;
+
+pub trait PressureSensor {
+    type Error;
+
+    type PressureFuture<'m>: Future<Output = Result<Pressure, Self::Error>>
+    where
+        Self: 'm;
+    fn pressure<'m>(&'m mut self) -> Self::PressureFuture<'m>;
+}
@@ -5,10 +5,14 @@
 #[cfg(feature = "std")]
 mod tests {
     use drogue_device::actors::button::*;
+    use drogue_device::drivers::button::{ActiveLow, DebouncedButton};
+    use drogue_device::traits::button::Event;
     #[allow(unused_imports)]
     use drogue_device_macros::test as drogue_test;
     use ector::testutil::*;
     use embassy::executor::Spawner;
+    use embassy::time::{Duration, Timer};
+    use embassy::util::{select, Either};
 
     #[allow(dead_code)]
     struct TestDevicePressed {
@@ -30,7 +34,7 @@ mod tests {
         device.button.mount(spawner, Button::new(pin, handler_addr));
 
         assert!(notified.message().is_none());
-        pin.set_high();
+        pin.set_low();
         notified.wait_signaled().await;
         assert_eq!(0, notified.message().unwrap().0);
     }
@@ -56,8 +60,74 @@ mod tests {
         device.button.mount(spawner, Button::new(pin, handler_addr));
 
         assert!(notified.message().is_none());
-        pin.set_low();
+        pin.set_high();
         notified.wait_signaled().await;
         assert_eq!(1, notified.message().unwrap().0);
     }
+
+    #[allow(dead_code)]
+    struct TestDeviceDebounce;
+
+    #[drogue_test]
+    #[allow(dead_code)]
+    async fn test_debounce(_spawner: Spawner, mut context: TestContext<TestDeviceDebounce>) {
+        let pin = context.pin(true);
+        let mut button = DebouncedButton::<_, ActiveLow>::new(pin, Duration::from_millis(50));
+
+        // a bounce shorter than the debounce time is ignored
+        let bounce = async {
+            pin.set_low();
+            Timer::after(Duration::from_millis(10)).await;
+            pin.set_high();
+            Timer::after(Duration::from_millis(200)).await;
+        };
+        assert!(matches!(
+            select(button.wait_any(), bounce).await,
+            Either::Second(_)
+        ));
+        assert!(!button.is_pressed());
+
+        pin.set_low();
+        assert_eq!(Event::Pressed, button.wait_any().await);
+        assert!(button.is_pressed());
+    }
+
+    #[allow(dead_code)]
+    struct TestDeviceLongPress {
+        handler: ector::ActorContext<TestHandler>,
+        button: ector::ActorContext<Button<TestPin, TestMessage>>,
+    }
+
+    #[drogue_test]
+    #[allow(dead_code)]
+    async fn test_long_press(spawner: Spawner, mut context: TestContext<TestDeviceLongPress>) {
+        let pin = context.pin(true);
+        let notified = context.signal();
+
+        let device = context.configure(TestDeviceLongPress {
+            handler: ector::ActorContext::new(),
+            button: ector::ActorContext::new(),
+        });
+
+        let handler_addr = device.handler.mount(spawner, TestHandler::new(notified));
+        device.button.mount(
+            spawner,
+            Button::with_gestures(
+                pin,
+                handler_addr,
+                Gestures {
+                    long_press: Duration::from_millis(100),
+                    hold_interval: Duration::from_secs(10),
+                    double_click: Duration::from_millis(50),
+                },
+            ),
+        );
+
+        pin.set_low();
+        notified.wait_signaled().await;
+        assert_eq!(0, notified.message().unwrap().0);
+
+        Timer::after(Duration::from_millis(200)).await;
+        assert_eq!(2, notified.message().unwrap().0);
+    }
 }
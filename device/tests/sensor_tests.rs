@@ -0,0 +1,175 @@
+#![macro_use]
+#![feature(generic_associated_types)]
+#![feature(type_alias_impl_trait)]
+
+#[cfg(feature = "std")]
+mod tests {
+    use core::future::Future;
+    use core::sync::atomic::{AtomicU32, Ordering};
+    use drogue_device::actors::sensors::{DataReady, Every, Sensor};
+    use drogue_device::traits;
+    #[allow(unused_imports)]
+    use drogue_device_macros::test as drogue_test;
+    use ector::testutil::*;
+    use embassy::executor::Spawner;
+    use embassy::time::{Duration, Timer};
+
+    /// Counts up with every reading, failing the first `failures` ones. A
+    /// successful reading clears the data-ready pin, like real hardware does.
+    struct FakeSensor {
+        pin: Option<TestPin>,
+        failures: u32,
+        reads: &'static AtomicU32,
+        next: u32,
+    }
+
+    impl traits::sensors::Sensor for FakeSensor {
+        type Error = ();
+        type Reading = TestMessage;
+
+        type CalibrateFuture<'m> = impl Future<Output = Result<(), Self::Error>> + 'm where Self: 'm;
+        fn calibrate<'m>(&'m mut self) -> Self::CalibrateFuture<'m> {
+            async move { Ok(()) }
+        }
+
+        type ReadFuture<'m> = impl Future<Output = Result<Self::Reading, Self::Error>> + 'm where Self: 'm;
+        fn read<'m>(&'m mut self) -> Self::ReadFuture<'m> {
+            async move {
+                self.reads.fetch_add(1, Ordering::SeqCst);
+                if self.failures > 0 {
+                    self.failures -= 1;
+                    return Err(());
+                }
+                if let Some(pin) = &self.pin {
+                    pin.set_low();
+                }
+                self.next += 1;
+                Ok(TestMessage(self.next))
+            }
+        }
+    }
+
+    #[allow(dead_code)]
+    struct TestDeviceDataReady {
+        handler: ector::ActorContext<TestHandler>,
+        sensor: ector::ActorContext<Sensor<FakeSensor, DataReady<TestPin>>>,
+    }
+
+    static DATA_READY_READS: AtomicU32 = AtomicU32::new(0);
+
+    #[drogue_test]
+    #[allow(dead_code)]
+    async fn test_data_ready(spawner: Spawner, mut context: TestContext<TestDeviceDataReady>) {
+        let pin = context.pin(false);
+        let notified = context.signal();
+
+        let device = context.configure(TestDeviceDataReady {
+            handler: ector::ActorContext::new(),
+            sensor: ector::ActorContext::new(),
+        });
+        let handler_addr = device.handler.mount(spawner, TestHandler::new(notified));
+        let sensor = FakeSensor {
+            pin: Some(pin),
+            failures: 0,
+            reads: &DATA_READY_READS,
+            next: 0,
+        };
+        device.sensor.mount(
+            spawner,
+            Sensor::new(sensor, DataReady::new(pin), handler_addr),
+        );
+
+        Timer::after(Duration::from_millis(10)).await;
+        assert_eq!(0, DATA_READY_READS.load(Ordering::SeqCst));
+        assert!(notified.message().is_none());
+
+        pin.set_high();
+        notified.wait_signaled().await;
+        assert_eq!(1, notified.message().unwrap().0);
+        assert_eq!(1, DATA_READY_READS.load(Ordering::SeqCst));
+    }
+
+    #[allow(dead_code)]
+    struct TestDeviceReadFailed {
+        handler: ector::ActorContext<TestHandler>,
+        sensor: ector::ActorContext<Sensor<FakeSensor, DataReady<TestPin>>>,
+    }
+
+    static READ_FAILED_READS: AtomicU32 = AtomicU32::new(0);
+
+    #[drogue_test]
+    #[allow(dead_code)]
+    async fn test_read_failed(spawner: Spawner, mut context: TestContext<TestDeviceReadFailed>) {
+        let pin = context.pin(true);
+        let notified = context.signal();
+
+        let device = context.configure(TestDeviceReadFailed {
+            handler: ector::ActorContext::new(),
+            sensor: ector::ActorContext::new(),
+        });
+        let handler_addr = device.handler.mount(spawner, TestHandler::new(notified));
+        let sensor = FakeSensor {
+            pin: Some(pin),
+            failures: 1,
+            reads: &READ_FAILED_READS,
+            next: 0,
+        };
+        device.sensor.mount(
+            spawner,
+            Sensor::new(sensor, DataReady::new(pin), handler_addr),
+        );
+
+        // the pin stays high after the failed read, which must not be retried
+        // until the pin changes
+        Timer::after(Duration::from_millis(10)).await;
+        assert_eq!(1, READ_FAILED_READS.load(Ordering::SeqCst));
+        assert!(notified.message().is_none());
+
+        pin.set_low();
+        pin.set_high();
+        notified.wait_signaled().await;
+        assert_eq!(1, notified.message().unwrap().0);
+        assert_eq!(2, READ_FAILED_READS.load(Ordering::SeqCst));
+    }
+
+    #[allow(dead_code)]
+    struct TestDeviceEvery {
+        handler: ector::ActorContext<TestHandler>,
+        sensor: ector::ActorContext<Sensor<FakeSensor, Every>>,
+    }
+
+    static EVERY_READS: AtomicU32 = AtomicU32::new(0);
+
+    #[drogue_test]
+    #[allow(dead_code)]
+    async fn test_every(spawner: Spawner, mut context: TestContext<TestDeviceEvery>) {
+        let notified = context.signal();
+
+        let device = context.configure(TestDeviceEvery {
+            handler: ector::ActorContext::new(),
+            sensor: ector::ActorContext::new(),
+        });
+        let handler_addr = device.handler.mount(spawner, TestHandler::new(notified));
+        let sensor = FakeSensor {
+            pin: None,
+            failures: 1,
+            reads: &EVERY_READS,
+            next: 0,
+        };
+        device.sensor.mount(
+            spawner,
+            Sensor::new(sensor, Every::new(Duration::from_millis(20)), handler_addr),
+        );
+
+        // a failed read is retried on the next tick
+        Timer::after(Duration::from_millis(10)).await;
+        assert_eq!(0, EVERY_READS.load(Ordering::SeqCst));
+
+        notified.wait_signaled().await;
+        assert_eq!(1, notified.message().unwrap().0);
+        assert_eq!(2, EVERY_READS.load(Ordering::SeqCst));
+
+        Timer::after(Duration::from_millis(50)).await;
+        assert!(notified.message().unwrap().0 >= 2);
+    }
+}
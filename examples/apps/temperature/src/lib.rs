@@ -7,7 +7,10 @@ pub(crate) mod fmt;
 
 use core::convert::TryFrom;
 use core::future::Future;
+#[cfg(feature = "tls")]
+use drogue_device::actors::publisher::http::HttpsBackend;
 use drogue_device::{
+    actors::publisher::{http::HttpBackend, Publisher, PublisherConfig, PublisherMessage},
     actors::sensors::Temperature,
     actors::transformer::Transformer,
     domain::{
@@ -23,15 +26,9 @@ use ector::{Actor, ActorContext, Address, Inbox};
 use embassy::executor::Spawner;
 use embedded_hal::digital::v2::InputPin;
 use embedded_hal_async::digital::Wait;
-use embedded_io::{Error, ErrorKind};
 use embedded_nal_async::*;
-use heapless::String;
-use reqwless::{client::*, request::*};
 use serde::{Deserialize, Serialize};
 
-#[cfg(feature = "tls")]
-use embedded_tls::{Aes128GcmSha256, NoClock, TlsConfig, TlsConnection, TlsContext};
-
 #[derive(Clone, Serialize, Deserialize, Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct GeoLocation {
@@ -55,108 +52,29 @@ pub enum Command {
     Send,
 }
 
-pub struct App<B>
-where
-    B: TemperatureBoard + 'static,
-{
-    host: &'static str,
-    port: u16,
-    username: &'static str,
-    password: &'static str,
-    network: B::Network,
-    #[allow(dead_code)]
-    rng: B::Rng,
-}
-
-impl<B> App<B>
-where
-    B: TemperatureBoard + 'static,
-{
-    pub fn new(
-        host: &'static str,
-        port: u16,
-        username: &'static str,
-        password: &'static str,
-        network: B::Network,
-        rng: B::Rng,
-    ) -> Self {
-        Self {
-            host,
-            port,
-            username,
-            password,
-            network,
-            rng,
-        }
-    }
-
-    async fn send(&mut self, sensor_data: &TemperatureData) -> Result<(), ErrorKind> {
-        debug!("Resolving {}:{}", self.host, self.port);
-        let ip = DNS
-            .get_host_by_name(self.host, AddrType::IPv4)
-            .await
-            .map_err(|_| ErrorKind::Other)?;
+/// Readings kept while offline.
+const BUFFERED: usize = 8;
+const PAYLOAD_SIZE: usize = 128;
 
-        #[cfg(feature = "tls")]
-        let mut tls = [0; 16384];
+#[cfg(not(feature = "tls"))]
+type Backend<B> = HttpBackend<<B as TemperatureBoard>::Network, StaticDnsResolver<'static, 3>>;
+#[cfg(feature = "tls")]
+type Backend<B> = HttpsBackend<
+    <B as TemperatureBoard>::Network,
+    StaticDnsResolver<'static, 3>,
+    <B as TemperatureBoard>::Rng,
+>;
 
-        #[allow(unused_mut)]
-        let mut connection = self
-            .network
-            .connect(SocketAddr::new(ip, self.port))
-            .await
-            .map_err(|e| e.kind())?;
+type AppPublisher<B> = Publisher<Backend<B>, TemperatureData, BUFFERED, PAYLOAD_SIZE>;
 
-        #[cfg(feature = "tls")]
-        let mut connection = {
-            let mut connection: TlsConnection<'_, _, Aes128GcmSha256> =
-                TlsConnection::new(connection, &mut tls);
-            connection
-                .open::<_, NoClock, 1>(TlsContext::new(
-                    &TlsConfig::new().with_server_name(self.host),
-                    &mut self.rng,
-                ))
-                .await
-                .map_err(|_| ErrorKind::Other)?;
-            connection
-        };
+/// Keeps the latest measurement, and publishes it when triggered.
+pub struct App {
+    publisher: Address<PublisherMessage<TemperatureData>>,
+}
 
-        debug!("Connected to {}:{}", self.host, self.port);
-
-        let mut client = HttpClient::new(&mut connection, self.host);
-
-        let tx: String<128> =
-            serde_json_core::ser::to_string(&sensor_data).map_err(|_| ErrorKind::Other)?;
-        let mut rx_buf = [0; 1024];
-        let response = client
-            .request(
-                Request::post()
-                    // Pass on schema
-                    .path("/v1/foo?data_schema=urn:drogue:iot:temperature")
-                    .basic_auth(self.username, self.password)
-                    .payload(tx.as_bytes())
-                    .content_type(ContentType::ApplicationJson)
-                    .build(),
-                &mut rx_buf[..],
-            )
-            .await;
-
-        match response {
-            Ok(response) => {
-                info!("Response status: {:?}", response.status);
-                if let Some(payload) = response.payload {
-                    let s = core::str::from_utf8(payload).map_err(|_| ErrorKind::Other)?;
-                    trace!("Payload: {}", s);
-                } else {
-                    trace!("No response body");
-                }
-                Ok(())
-            }
-            Err(e) => {
-                warn!("Error doing HTTP request: {:?}", e);
-                Err(ErrorKind::Other)
-            }
-        }
+impl App {
+    pub fn new(publisher: Address<PublisherMessage<TemperatureData>>) -> Self {
+        Self { publisher }
     }
 }
 
@@ -174,17 +92,11 @@ where
     }
 }
 
-impl<B> Actor for App<B>
-where
-    B: TemperatureBoard + 'static,
-{
-    type Message<'m> = Command
-    where
-        B: 'm;
+impl Actor for App {
+    type Message<'m> = Command;
 
     type OnMountFuture<'m, M> = impl Future<Output = ()> + 'm
     where
-        B: 'm,
         M: 'm + Inbox<Command>;
     fn on_mount<'m, M>(
         &'m mut self,
@@ -207,14 +119,9 @@ where
                             info!("Sending temperature measurement number {}", counter);
                             counter += 1;
 
-                            match self.send(sensor_data).await {
-                                Ok(_) => {
-                                    info!("Temperature measurement sent");
-                                }
-                                Err(e) => {
-                                    warn!("Error sending temperature measurement: {:?}", e);
-                                }
-                            }
+                            self.publisher
+                                .notify(PublisherMessage::Reading(sensor_data.clone()))
+                                .await;
                         } else {
                             info!("Not temperature measurement received yet");
                         }
@@ -225,7 +132,7 @@ where
     }
 }
 
-static DNS: StaticDnsResolver<'static, 3> = StaticDnsResolver::new(&[
+static DNS: [DnsEntry<'static>; 3] = [
     DnsEntry::new("localhost", IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))),
     DnsEntry::new(
         "http.sandbox.drogue.cloud",
@@ -235,7 +142,7 @@ static DNS: StaticDnsResolver<'static, 3> = StaticDnsResolver::new(&[
         "http-endpoint-drogue-dev.apps.wonderful.iot-playground.org",
         IpAddr::V4(Ipv4Addr::new(65, 108, 135, 161)),
     ),
-]);
+];
 
 pub trait TemperatureBoard {
     type Network: TcpConnect;
@@ -257,7 +164,8 @@ pub struct TemperatureDevice<B>
 where
     B: TemperatureBoard + 'static,
 {
-    app: ActorContext<App<B>, 3>,
+    app: ActorContext<App, 3>,
+    publisher: ActorContext<AppPublisher<B>>,
     trigger: ActorContext<AppTrigger<B>>,
     sensor: ActorContext<Temperature<B::SensorReadyIndicator, B::Sensor, B::TemperatureScale>>,
     bridge: ActorContext<Transformer<SensorAcquisition<B::TemperatureScale>, Command>>,
@@ -282,6 +190,7 @@ where
             sensor: ActorContext::new(),
             trigger: ActorContext::new(),
             app: ActorContext::new(),
+            publisher: ActorContext::new(),
             bridge: ActorContext::new(),
         }
     }
@@ -292,19 +201,28 @@ where
         rng: B::Rng,
         config: TemperatureBoardConfig<B>,
     ) {
-        let network = config.network;
-
-        let app = self.app.mount(
-            spawner,
-            App::new(
-                HOST,
-                PORT.parse::<u16>().unwrap(),
-                USERNAME.trim_end(),
-                PASSWORD.trim_end(),
-                network,
-                rng,
-            ),
+        let http = HttpBackend::new(
+            config.network,
+            StaticDnsResolver::new(&DNS),
+            HOST,
+            PORT.parse::<u16>().unwrap(),
+            // Pass on schema
+            "/v1/foo?data_schema=urn:drogue:iot:temperature",
+            USERNAME.trim_end(),
+            PASSWORD.trim_end(),
         );
+        #[cfg(not(feature = "tls"))]
+        let backend = {
+            let _ = rng;
+            http
+        };
+        #[cfg(feature = "tls")]
+        let backend = HttpsBackend::new(http, rng);
+
+        let publisher = self
+            .publisher
+            .mount(spawner, Publisher::new(backend, PublisherConfig::default()));
+        let app = self.app.mount(spawner, App::new(publisher));
         let bridge = self.bridge.mount(spawner, Transformer::new(app.clone()));
         self.sensor.mount(
             spawner,
@@ -18,6 +18,7 @@ use drogue_device::drivers::ble::mesh::driver::elements::AppElementsContext;
 use drogue_device::drivers::ble::mesh::driver::DeviceError;
 use drogue_device::drivers::ble::mesh::interface::AdvertisingOnlyNetworkInterfaces;
 use drogue_device::drivers::ble::mesh::model::ModelIdentifier;
+use drogue_device::drivers::ble::mesh::oob::NoOob;
 use drogue_device::drivers::ble::mesh::pdu::access::AccessMessage;
 use drogue_device::drivers::ble::mesh::pdu::ParseError;
 use drogue_device::drivers::ble::mesh::provisioning::{
@@ -71,6 +72,7 @@ type ConcreteMeshNode = MeshNode<
     'static,
     CustomElementsHandler,
     AdvertisingOnlyNetworkInterfaces<SoftdeviceAdvertisingBearer>,
+    NoOob,
     FlashStorage<SharedFlash<'static, Flash>>,
     SoftdeviceRng,
 >;
@@ -167,7 +169,7 @@ async fn main(spawner: Spawner, p: Peripherals) {
         publisher: device.publisher.sender().into(),
     };
     let network = AdvertisingOnlyNetworkInterfaces::new(advertising_bearer);
-    let mesh_node = MeshNode::new(elements, capabilities, network, storage, rng);
+    let mesh_node = MeshNode::new(elements, capabilities, network, NoOob, storage, rng);
     let mesh_node = device.mesh.put(mesh_node);
 
     spawner
@@ -18,7 +18,8 @@ use drogue_device::drivers::ble::mesh::config::ConfigurationModel;
 use drogue_device::drivers::ble::mesh::driver::elements::AppElementsContext;
 use drogue_device::drivers::ble::mesh::driver::DeviceError;
 use drogue_device::drivers::ble::mesh::interface::AdvertisingOnlyNetworkInterfaces;
-use drogue_device::drivers::ble::mesh::model::ModelIdentifier;
+use drogue_device::drivers::ble::mesh::model::{Model, ModelIdentifier};
+use drogue_device::drivers::ble::mesh::oob::NoOob;
 use drogue_device::drivers::ble::mesh::pdu::access::AccessMessage;
 use drogue_device::drivers::ble::mesh::pdu::ParseError;
 use drogue_device::drivers::ble::mesh::provisioning::{
@@ -30,8 +31,9 @@ use drogue_device::drivers::ble::mesh::InsufficientBuffer;
 use drogue_device::{
     actors::ble::mesh::{MeshNode, MeshNodeMessage},
     drivers::ble::mesh::model::sensor::{
-        PropertyId, SensorConfig, SensorData, SensorDescriptor, SensorMessage, SensorServer,
-        SensorStatus, SENSOR_SERVER,
+        CadenceDescriptor, PropertyId, SensorConfig, SensorData, SensorDescriptor, SensorMessage,
+        SensorServer, SensorServerState, SensorSetupConfig, SensorSetupServer, SensorStatus,
+        SettingDescriptor, SENSOR_SERVER, SENSOR_SETUP_SERVER,
     },
     flash::{FlashState, SharedFlash},
     Board,
@@ -39,7 +41,7 @@ use drogue_device::{
 
 cfg_if::cfg_if! {
     if #[cfg(feature = "dfu")] {
-        use drogue_device::{firmware::FirmwareManager, drivers::ble::mesh::model::{Model, firmware::{
+        use drogue_device::{firmware::FirmwareManager, drivers::ble::mesh::model::{firmware::{
             Control as FirmwareControl, FirmwareUpdateMessage, FirmwareUpdateServer,
             Status as FirmwareStatus, FIRMWARE_UPDATE_SERVER,
             drivers::ble::mesh::model::Model,
@@ -51,7 +53,7 @@ cfg_if::cfg_if! {
 
 use embassy::channel::mpmc::{Channel, DynamicReceiver, DynamicSender};
 use embassy::time::Ticker;
-use embassy::time::{Duration, Timer};
+use embassy::time::{Duration, Instant, Timer};
 use embassy::util::Forever;
 use embassy::util::{select, Either};
 use embassy::{blocking_mutex::raw::NoopRawMutex, executor::Spawner};
@@ -77,6 +79,7 @@ type ConcreteMeshNode = MeshNode<
     'static,
     CustomElementsHandler,
     AdvertisingOnlyNetworkInterfaces<SoftdeviceAdvertisingBearer>,
+    NoOob,
     FlashStorage<SharedFlash<'static, Flash>>,
     SoftdeviceRng,
 >;
@@ -155,7 +158,11 @@ async fn main(spawner: Spawner, p: Peripherals) {
         FEATURES,
     );
     composition
-        .add_element(ElementDescriptor::new(Location(0x0001)).add_model(SENSOR_SERVER))
+        .add_element(
+            ElementDescriptor::new(Location(0x0001))
+                .add_model(SENSOR_SERVER)
+                .add_model(SENSOR_SETUP_SERVER),
+        )
         .ok();
     #[cfg(feature = "dfu")]
     composition
@@ -185,7 +192,14 @@ async fn main(spawner: Spawner, p: Peripherals) {
         "Mesh node size: {}",
         core::mem::size_of::<ConcreteMeshNode>()
     );
-    let mesh_node = MESH.put(MeshNode::new(elements, capabilities, network, storage, rng));
+    let mesh_node = MESH.put(MeshNode::new(
+        elements,
+        capabilities,
+        network,
+        NoOob,
+        storage,
+        rng,
+    ));
     spawner
         .spawn(mesh_task(mesh_node, device.control.receiver().into()))
         .unwrap();
@@ -284,14 +298,22 @@ async fn state_task(mut display: LedMatrix, inbox: DynamicReceiver<'static, AppS
     }
 }
 
+/// How often the temperature is sampled, to check the cadence triggers.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+const TEMPERATURE: PropertyId = PropertyId(0x4F);
+
 #[embassy::task]
 async fn publisher_task(
-    interval: Duration,
+    period: Duration,
     sd: &'static Softdevice,
     inbox: DynamicReceiver<'static, PublisherMessage>,
 ) {
     let mut context = None;
-    let mut ticker = Ticker::every(interval);
+    let mut period = period;
+    let mut state: SensorServerState<SensorModel, 1> = SensorServerState::new();
+    let mut last_published: Option<Instant> = None;
+    let mut ticker = Ticker::every(SAMPLE_INTERVAL);
     loop {
         let next = inbox.recv();
         let tick = ticker.next();
@@ -301,18 +323,53 @@ async fn publisher_task(
                 PublisherMessage::Connect(ctx) => {
                     context.replace(ctx);
                 }
-                PublisherMessage::SetPeriod(interval) => {
-                    ticker = Ticker::every(interval);
+                PublisherMessage::SetPeriod(p) => {
+                    period = p;
+                }
+                PublisherMessage::Setup(access) => {
+                    match SensorSetupServer::<SensorModel, 1, 1>::parse(
+                        access.opcode(),
+                        access.parameters(),
+                    ) {
+                        Ok(Some(message)) => match state.dispatch(&message) {
+                            Ok(Some(status)) => {
+                                if let Some(ctx) = &context {
+                                    if let Err(e) = ctx.respond(&access, status).await {
+                                        defmt::warn!("Error reporting cadence: {:?}", e);
+                                    }
+                                }
+                            }
+                            Ok(None) => {}
+                            Err(_) => {
+                                defmt::warn!("Cadence does not fit");
+                            }
+                        },
+                        Ok(None) => {}
+                        Err(e) => {
+                            defmt::warn!("Error parsing sensor setup message: {:?}", e);
+                        }
+                    }
                 }
             },
             Either::Second(_) => {
                 let value: i8 = temperature_celsius(sd).unwrap().to_num();
-                defmt::info!("Measured temperature: {}℃", value);
-                let value = value * 2;
+                defmt::debug!("Measured temperature: {}℃", value);
+                let data = Temperature(value * 2);
+                let now = Instant::now();
+                let due = match last_published {
+                    Some(at) => now >= at + state.publish_period(TEMPERATURE, period, &data),
+                    None => true,
+                };
+                if !due && !state.should_publish(TEMPERATURE, &data, now) {
+                    continue;
+                }
+
                 if let Some(ctx) = &context {
                     // Report sensor data
                     let c = ctx.for_element_model::<SensorServer<SensorModel, 1, 1>>(0);
-                    let message = SensorMessage::Status(SensorStatus::new(Temperature(value)));
+                    state.published(TEMPERATURE, &data, now);
+                    last_published.replace(now);
+                    let message = SensorMessage::Status(SensorStatus::new(data));
                     match c.publish(message).await {
                         Ok(_) => {
                             defmt::debug!("Published sensor data");
@@ -354,6 +411,8 @@ pub struct CustomElementsHandler {
 pub enum PublisherMessage {
     Connect(AppElementsContext<'static>),
     SetPeriod(Duration),
+    /// A message to the Sensor Setup Server, which owns the cadence.
+    Setup(AccessMessage),
 }
 
 #[cfg(feature = "dfu")]
@@ -396,6 +455,11 @@ impl ElementsHandler<'static> for CustomElementsHandler {
                 access.opcode(),
                 access.parameters().len()
             );
+            if element == 0 && *model_identifier == SENSOR_SETUP_SERVER {
+                self.publisher
+                    .send(PublisherMessage::Setup(access.clone()))
+                    .await;
+            }
             #[cfg(feature = "dfu")]
             if element == 1 && *model_identifier == FIRMWARE_UPDATE_SERVER {
                 match FirmwareUpdateServer::parse(access.opcode(), access.parameters()) {
@@ -497,18 +561,35 @@ impl ElementsHandler<'static> for CustomElementsHandler {
 #[derive(Clone, defmt::Format)]
 pub struct SensorModel;
 
-#[derive(Clone, defmt::Format)]
+/// Temperature in units of 0.5℃.
+#[derive(Clone, Default, defmt::Format)]
 pub struct Temperature(i8);
 
 impl SensorConfig for SensorModel {
     type Data<'m> = Temperature;
 
-    const DESCRIPTORS: &'static [SensorDescriptor] = &[SensorDescriptor::new(PropertyId(0x4F), 1)];
+    const DESCRIPTORS: &'static [SensorDescriptor] = &[SensorDescriptor::new(TEMPERATURE, 1)];
+}
+
+impl SensorSetupConfig for SensorModel {
+    const CADENCE_DESCRIPTORS: &'static [CadenceDescriptor] =
+        &[CadenceDescriptor::new(TEMPERATURE, 1)];
+    const SETTING_DESCRIPTORS: &'static [SettingDescriptor] = &[];
 }
 
 impl SensorData for Temperature {
-    fn decode(&mut self, _: PropertyId, _: &[u8]) -> Result<(), ParseError> {
-        todo!()
+    fn decode(&mut self, _: PropertyId, data: &[u8]) -> Result<(), ParseError> {
+        match data {
+            [value] => {
+                self.0 = *value as i8;
+                Ok(())
+            }
+            _ => Err(ParseError::InvalidLength),
+        }
+    }
+
+    fn value(&self, _: PropertyId) -> Option<i64> {
+        Some(self.0 as i64)
     }
 
     fn encode<const N: usize>(
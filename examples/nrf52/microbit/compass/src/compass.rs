@@ -1,44 +1,29 @@
-use core::fmt::Debug;
 use drogue_device::bsp::boards::nrf52::microbit::LedMatrix;
 use drogue_device::domain::led::matrix::Frame;
 use drogue_device::drivers::led::matrix::fonts;
+use drogue_device::drivers::sensors::lsm303agr::{Lsm303agr, MagCalibrator};
 use drogue_device::traits::led::ToFrame;
 use embassy::time::{Duration, Timer};
-use lsm303agr::{
-    interface::{ReadData, WriteData},
-    mode::MagContinuous,
-    Error, Lsm303agr,
-};
-use micromath::F32Ext;
+use embedded_hal_async::i2c::{ErrorType, I2c, SevenBitAddress};
 
-pub struct MicrobitCompass<I, E1, E2>
+pub struct MicrobitCompass<I>
 where
-    I: WriteData<Error = Error<E1, E2>> + ReadData<Error = Error<E1, E2>>,
-    E1: Debug,
-    E2: Debug,
+    I: I2c<SevenBitAddress> + 'static,
+    <I as ErrorType>::Error: Send,
 {
     heading_offset: i32,
-    x_offset: i32,
-    y_offset: i32,
-    x_scale: f32,
-    y_scale: f32,
-    sensor: Lsm303agr<I, MagContinuous>,
+    sensor: Lsm303agr<I>,
 }
 
-impl<I, E1, E2> MicrobitCompass<I, E1, E2>
+impl<I> MicrobitCompass<I>
 where
-    I: WriteData<Error = Error<E1, E2>> + ReadData<Error = Error<E1, E2>>,
-    E1: Debug,
-    E2: Debug,
+    I: I2c<SevenBitAddress> + 'static,
+    <I as ErrorType>::Error: Send,
 {
-    pub fn new(sensor: Lsm303agr<I, MagContinuous>, heading_offset: i32) -> Self {
+    pub fn new(sensor: Lsm303agr<I>, heading_offset: i32) -> Self {
         Self {
             sensor,
             heading_offset,
-            x_offset: 0,
-            y_offset: 0,
-            x_scale: 1.0,
-            y_scale: 1.0,
         }
     }
 
@@ -84,51 +69,44 @@ where
         let mut cursor = Point(2, 2);
 
         matrix.clear();
-        let (mut min_x, mut min_y, mut min_z) = (i32::MAX, i32::MAX, i32::MAX);
-        let (mut max_x, mut max_y, mut max_z) = (i32::MIN, i32::MIN, i32::MIN);
+        let mut calibrator = MagCalibrator::new();
         let mut samples = 0;
         while samples < PERIMETER_POINTS {
-            if self.sensor.accel_status().unwrap().xyz_new_data {
-                let data = self.sensor.accel_data().unwrap();
+            if let Ok(true) = self.sensor.accel_data_ready().await {
+                if let Ok(data) = self.sensor.read_acceleration().await {
+                    if data.x < -PIXEL2_THRESHOLD {
+                        cursor.0 = 0;
+                    } else if data.x < -PIXEL1_THRESHOLD {
+                        cursor.0 = 1;
+                    } else if data.x > PIXEL2_THRESHOLD {
+                        cursor.0 = 4;
+                    } else if data.x > PIXEL1_THRESHOLD {
+                        cursor.0 = 3;
+                    } else {
+                        cursor.0 = 2;
+                    }
 
-                if data.x < -PIXEL2_THRESHOLD {
-                    cursor.0 = 0;
-                } else if data.x < -PIXEL1_THRESHOLD {
-                    cursor.0 = 1;
-                } else if data.x > PIXEL2_THRESHOLD {
-                    cursor.0 = 4;
-                } else if data.x > PIXEL1_THRESHOLD {
-                    cursor.0 = 3;
-                } else {
-                    cursor.0 = 2;
-                }
-
-                if data.y < -PIXEL2_THRESHOLD {
-                    cursor.1 = 0;
-                } else if data.y < -PIXEL1_THRESHOLD {
-                    cursor.1 = 1;
-                } else if data.y > PIXEL2_THRESHOLD {
-                    cursor.1 = 4;
-                } else if data.y > PIXEL1_THRESHOLD {
-                    cursor.1 = 3;
-                } else {
-                    cursor.1 = 2;
+                    if data.y < -PIXEL2_THRESHOLD {
+                        cursor.1 = 0;
+                    } else if data.y < -PIXEL1_THRESHOLD {
+                        cursor.1 = 1;
+                    } else if data.y > PIXEL2_THRESHOLD {
+                        cursor.1 = 4;
+                    } else if data.y > PIXEL1_THRESHOLD {
+                        cursor.1 = 3;
+                    } else {
+                        cursor.1 = 2;
+                    }
                 }
             }
 
             defmt::trace!("Cursor: ({}, {})", cursor.0, cursor.1);
 
             // Sample some data
-            let status = self.sensor.mag_status().unwrap();
-            if status.xyz_new_data {
-                let data = self.sensor.mag_data().unwrap();
-                max_x = core::cmp::max(max_x, data.x);
-                max_y = core::cmp::max(max_y, data.y);
-                max_z = core::cmp::max(max_z, data.z);
-
-                min_x = core::cmp::min(min_x, data.x);
-                min_y = core::cmp::min(min_y, data.y);
-                min_z = core::cmp::min(min_z, data.z);
+            if let Ok(true) = self.sensor.mag_data_ready().await {
+                if let Ok(field) = self.sensor.read_raw_magnetic_field().await {
+                    calibrator.add(field);
+                }
             }
 
             // Update visited state
@@ -139,60 +117,34 @@ where
                     samples += 1;
                 }
             }
-            matrix.render();
-
-            Timer::after(Duration::from_micros(500)).await;
+            matrix.refresh().await;
         }
 
         defmt::info!("Calibration complete!");
         matrix.clear();
 
-        self.x_offset = (min_x + max_x) / 2;
-        self.y_offset = (min_y + max_y) / 2;
-
-        self.x_scale = 1.0 / (max_x - min_x) as f32;
-        self.y_scale = 1.0 / (max_y - min_y) as f32;
-
+        let calibration = calibrator.calibration();
         defmt::trace!(
-        "Calibrated values (x, y) offset({}, {}), scale({}, {}): x({}, {}), y({}, {}), z({}, {})",
-        self.x_offset,
-        self.y_offset,
-        self.x_scale,
-        self.y_scale,
-        min_x,
-        max_x,
-        min_y,
-        max_y,
-        min_z,
-        max_z
-    );
+            "Calibrated values (x, y, z) offset({}, {}, {}), scale({}, {}, {})",
+            calibration.offset.x,
+            calibration.offset.y,
+            calibration.offset.z,
+            calibration.scale[0],
+            calibration.scale[1],
+            calibration.scale[2]
+        );
+        self.sensor.set_calibration(calibration);
     }
 
     pub async fn heading(&mut self) -> i32 {
         loop {
-            let status = self.sensor.mag_status().unwrap();
-            if status.xyz_new_data {
-                let data = self.sensor.mag_data().unwrap();
-                // Normalize for within calibrated range
-                let x_sample = ((data.x - self.x_offset) as f32) * self.x_scale;
-                let y_sample = ((data.y - self.y_offset) as f32) * self.y_scale;
-
-                let x: f32 = -y_sample;
-                let y: f32 = x_sample;
-
-                defmt::trace!("Coord ({}, {})", x, y);
-
-                let mut heading =
-                    (x.atan2(y) * 180.0 / core::f32::consts::PI) + self.heading_offset as f32;
-                if heading < 0.0 {
-                    heading += 360.0;
+            if let Ok(true) = self.sensor.mag_data_ready().await {
+                if let Ok(measurement) = self.sensor.measure().await {
+                    defmt::trace!("Heading: {}", measurement.heading);
+                    return (measurement.heading as i32 + self.heading_offset).rem_euclid(360);
                 }
-                defmt::trace!("Heading: {}", heading);
-
-                return heading as i32;
-            } else {
-                Timer::after(Duration::from_millis(10)).await;
             }
+            Timer::after(Duration::from_millis(10)).await;
         }
     }
 }
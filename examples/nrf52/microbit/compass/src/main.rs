@@ -8,9 +8,9 @@ use defmt_rtt as _;
 use drogue_device::traits::led::ToFrame;
 use drogue_device::{bsp::boards::nrf52::microbit::*, Board};
 
+use drogue_device::drivers::sensors::lsm303agr::Lsm303agr;
 use embassy::time::Duration;
 use embassy_nrf::{interrupt, twim, Peripherals};
-use lsm303agr::{AccelOutputDataRate, Lsm303agr, MagOutputDataRate};
 
 mod compass;
 use compass::*;
@@ -25,11 +25,9 @@ async fn main(_s: embassy::executor::Spawner, p: Peripherals) {
     let irq = interrupt::take!(SPIM0_SPIS0_TWIM0_TWIS0_SPI0_TWI0);
     let twi = twim::Twim::new(board.twispi0, irq, board.p23, board.p22, config);
 
-    let mut sensor = Lsm303agr::new_with_i2c(twi);
-    sensor.init().unwrap();
-    sensor.set_accel_odr(AccelOutputDataRate::Hz50).unwrap();
-    sensor.set_mag_odr(MagOutputDataRate::Hz50).unwrap();
-    let sensor = sensor.into_mag_continuous().ok().unwrap();
+    // 50Hz accelerometer and continuous 50Hz magnetometer
+    let mut sensor = Lsm303agr::new(twi);
+    sensor.initialize().await.ok().unwrap();
 
     // Use heading offset of 90 which seems accurate during testing
     let mut display = board.display;
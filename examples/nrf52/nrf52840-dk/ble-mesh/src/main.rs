@@ -28,6 +28,7 @@ use drogue_device::drivers::ble::mesh::model::generic::onoff::{
     GENERIC_ONOFF_SERVER,
 };
 use drogue_device::drivers::ble::mesh::model::{Model, ModelIdentifier};
+use drogue_device::drivers::ble::mesh::oob::NoOob;
 use drogue_device::drivers::ble::mesh::pdu::access::AccessMessage;
 use drogue_device::drivers::ble::mesh::provisioning::{
     Algorithms, Capabilities, InputOOBActions, OOBSize, OutputOOBActions, PublicKeyType,
@@ -35,7 +36,6 @@ use drogue_device::drivers::ble::mesh::provisioning::{
 };
 use drogue_device::drivers::ble::mesh::storage::FlashStorage;
 use drogue_device::drivers::ActiveLow;
-use drogue_device::traits::button::Event;
 use drogue_device::{actors, drivers};
 use ector::{Actor, ActorContext, Address, Inbox};
 use embassy::channel::mpmc::{Channel, DynamicReceiver, Sender};
@@ -62,6 +62,7 @@ type ConcreteMeshNode = MeshNode<
     'static,
     CustomElementsHandler,
     AdvertisingAndGattNetworkInterfaces<SoftdeviceAdvertisingBearer, SoftdeviceGattBearer, 66>,
+    NoOob,
     FlashStorage<Flash>,
     SoftdeviceRng,
 >;
@@ -184,7 +185,7 @@ async fn main(spawner: Spawner, p: Peripherals) {
     //let network = AdvertisingOnlyNetworkInterfaces::new(advertising_bearer);
     let network = AdvertisingAndGattNetworkInterfaces::new(advertising_bearer, gatt_bearer);
 
-    let mesh_node = MeshNode::new(elements, capabilities, network, storage, rng);
+    let mesh_node = MeshNode::new(elements, capabilities, network, NoOob, storage, rng);
     let mesh_node = device.mesh.put(mesh_node);
 
     static CONTROL: Channel<NodeMutex, MeshNodeMessage, 2> = Channel::new();
@@ -263,7 +264,7 @@ impl ElementsHandler<'static> for CustomElementsHandler {
 
 pub enum MeshButtonMessage {
     Connect(AppElementContext<'static, GenericOnOffClient>),
-    Event(Event),
+    Event(ButtonEvent),
 }
 
 pub struct MeshButtonPublisher {
@@ -305,7 +306,7 @@ impl Actor for MeshButtonPublisher {
                         self.ctx.replace(ctx.clone());
                     }
                     MeshButtonMessage::Event(event) => match event {
-                        Event::Pressed => {
+                        ButtonEvent::Pressed => {
                             if let Some(ctx) = &self.ctx {
                                 ctx.publish(GenericOnOffMessage::SetUnacknowledged(Set {
                                     on_off: 1,
@@ -317,7 +318,7 @@ impl Actor for MeshButtonPublisher {
                                 .ok();
                             }
                         }
-                        Event::Released => {
+                        ButtonEvent::Released => {
                             if let Some(ctx) = &self.ctx {
                                 ctx.publish(GenericOnOffMessage::SetUnacknowledged(Set {
                                     on_off: 0,
@@ -329,6 +330,7 @@ impl Actor for MeshButtonPublisher {
                                 .ok();
                             }
                         }
+                        _ => {}
                     },
                 }
             }
@@ -386,7 +388,7 @@ impl Actor for MeshNodeReset {
                             }
                         }
                     }
-                    ButtonEvent::Released => {
+                    _ => {
                         // nothing
                     }
                 }
@@ -193,7 +193,7 @@ impl core::convert::TryFrom<ButtonEvent> for Command {
     fn try_from(event: ButtonEvent) -> Result<Self, Self::Error> {
         match event {
             ButtonEvent::Pressed => Ok(Command::Draw),
-            ButtonEvent::Released => Err(()),
+            _ => Err(()),
         }
     }
 }
@@ -214,7 +214,7 @@ fn test_device() -> Result<(), anyhow::Error> {
     device.push("device");
     let _p = xshell::pushd(&device)?;
     cmd!("cargo fmt --check").run()?;
-    cmd!("cargo test --all --features 'std wifi+esp8266 wifi+eswifi tcp+smoltcp tls'").run()?;
+    cmd!("cargo test --all --features 'std wifi+esp8266 wifi+eswifi tcp+smoltcp tls embedded-graphics neopixel lsm303agr publisher ble'").run()?;
     // Sanity check that we can build on cortex-m
     cmd!("cargo build --no-default-features --features 'wifi+esp8266 wifi+eswifi tcp+smoltcp tls ble+nrf52840 embassy-nrf/nrf52840 embassy-nrf/time-driver-rtc1 embassy/time' --target thumbv7em-none-eabihf").run()?;
     Ok(())